@@ -0,0 +1,105 @@
+use bytesize::ByteSize;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use structopt::StructOpt;
+
+use ya_transfer::cache::{self, Cache};
+
+use crate::execution::exe_unit_cache_dir;
+use crate::startup_config::ProviderConfig;
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum CacheConfig {
+    /// List images cached by ExeUnits, least recently used first
+    Ls,
+    /// Remove cached images, which aren't used by running ExeUnits
+    Purge {
+        /// Remove least recently used images until the cache fits within given size, e.g. `20GiB`.
+        /// Removes all images when not set
+        #[structopt(long)]
+        max_size: Option<ByteSize>,
+        /// Perform a dry run
+        #[structopt(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    name: String,
+    size: u64,
+    last_used: DateTime<Utc>,
+    /// Image is used by a running ExeUnit and won't be removed
+    in_use: bool,
+}
+
+impl From<cache::CacheEntry> for CacheEntry {
+    fn from(entry: cache::CacheEntry) -> Self {
+        CacheEntry {
+            name: entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            size: entry.size,
+            last_used: entry.last_used.into(),
+            in_use: Cache::in_use(&entry.path),
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn run(self, config: ProviderConfig) -> anyhow::Result<()> {
+        let data_dir = config.data_dir.get_or_create()?;
+        let cache = Cache::new(exe_unit_cache_dir(&data_dir));
+
+        match self {
+            CacheConfig::Ls => list(&config, &cache),
+            CacheConfig::Purge { max_size, dry_run } => {
+                purge(&cache, max_size.map(|s| s.as_u64()), dry_run)
+            }
+        }
+    }
+}
+
+fn list(config: &ProviderConfig, cache: &Cache) -> anyhow::Result<()> {
+    let entries: Vec<CacheEntry> = cache.entries()?.into_iter().map(Into::into).collect();
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    println!("Using cache dir: {}", cache.dir().display());
+    for entry in entries {
+        println!(
+            "{:>10}  {}  {:6}  {}",
+            ByteSize(entry.size).to_string(),
+            entry.last_used.format("%Y-%m-%d %H:%M:%S"),
+            if entry.in_use { "in use" } else { "" },
+            entry.name
+        );
+    }
+    println!("Total: {}", ByteSize(total));
+    Ok(())
+}
+
+/// Images in use by running ExeUnits are never removed.
+fn purge(cache: &Cache, max_size: Option<u64>, dry_run: bool) -> anyhow::Result<()> {
+    let size = |entries: Vec<cache::CacheEntry>| entries.iter().map(|e| e.size).sum::<u64>();
+    let freed = match (max_size, dry_run) {
+        (Some(max_size), false) => cache.evict(max_size, None)?,
+        (None, false) => cache.purge()?,
+        (Some(max_size), true) => size(cache.to_evict(max_size, None)?),
+        (None, true) => size(cache.to_evict(0, None)?) + size(cache.partial_downloads()?),
+    };
+
+    if dry_run {
+        println!("Dry run: {} to be freed", ByteSize(freed))
+    } else {
+        println!("Freed {} of disk space", ByteSize(freed))
+    }
+    Ok(())
+}
@@ -109,6 +109,10 @@ impl PresetUpdater {
             .items(&self.exeunits[..])
             .default(prev_exeunit)
             .interact()?;
+        if self.preset.exeunit_name != self.exeunits[exeunit_idx] {
+            // Version pinned for previous ExeUnit doesn't apply to the new one.
+            self.preset.exeunit_version = None;
+        }
         self.preset.exeunit_name = self.exeunits[exeunit_idx].clone();
         Ok(())
     }
@@ -132,7 +136,10 @@ impl PresetUpdater {
     pub fn update_metrics(&mut self, config: &ProviderConfig) -> Result<()> {
         let registry = config.registry()?;
         let mut usage_coeffs: BTreeMap<String, f64> = Default::default();
-        let exe_unit_desc = registry.find_exeunit(&self.preset.exeunit_name)?;
+        let exe_unit_desc = registry.find_exeunit_version(
+            &self.preset.exeunit_name,
+            self.preset.exeunit_version.as_ref(),
+        )?;
 
         fn get_usage(m: &BTreeMap<String, f64>, k1: &str, k2: &str) -> f64 {
             m.get(k1)
@@ -183,7 +190,7 @@ pub fn create_interactive(config: ProviderConfig) -> anyhow::Result<()> {
     let mut presets = PresetManager::load_or_create(&config.presets_file)?;
     let registry = config.registry()?;
 
-    let exeunits = registry.list().into_iter().map(|desc| desc.name).collect();
+    let exeunits = registry.names();
     let pricing_models = vec!["linear".to_string()];
 
     let preset =
@@ -216,13 +223,15 @@ pub fn create(config: ProviderConfig, params: PresetNoInteractive) -> anyhow::Re
         exeunit_name: params
             .exe_unit
             .ok_or_else(|| anyhow!("ExeUnit is required."))?,
+        exeunit_version: params.exe_unit_version,
         pricing_model: params.pricing.unwrap_or_else(|| "linear".to_string()),
         ..Default::default()
     };
 
     let registry = config.registry()?;
 
-    let exe_unit_desc = registry.find_exeunit(&preset.exeunit_name)?;
+    let exe_unit_desc =
+        registry.find_exeunit_version(&preset.exeunit_name, preset.exeunit_version.as_ref())?;
 
     for (name, price) in params.price.iter() {
         if is_initial_coefficient_name(name) {
@@ -312,10 +321,14 @@ fn update_presets(
             if let Some(new_exeunit_name) = params.exe_unit {
                 preset.exeunit_name = new_exeunit_name;
             }
+            if let Some(new_exeunit_version) = params.exe_unit_version {
+                preset.exeunit_version = Some(new_exeunit_version);
+            }
             if let Some(new_pricing_model) = params.pricing {
                 preset.pricing_model = new_pricing_model;
             }
-            let exe_unit_desc = registry.find_exeunit(&preset.exeunit_name)?;
+            let exe_unit_desc = registry
+                .find_exeunit_version(&preset.exeunit_name, preset.exeunit_version.as_ref())?;
 
             for (name, price) in params.price.iter() {
                 if is_initial_coefficient_name(name) {
@@ -349,7 +362,7 @@ fn remove_preset(config: ProviderConfig, name: String) -> anyhow::Result<()> {
 fn validate_preset(config: &ProviderConfig, preset: &Preset) -> anyhow::Result<()> {
     // Validate ExeUnit existence and pricing model.
     let registry = config.registry()?;
-    registry.find_exeunit(&preset.exeunit_name)?;
+    registry.find_exeunit_version(&preset.exeunit_name, preset.exeunit_version.as_ref())?;
 
     if preset.pricing_model != "linear" {
         bail!("Not supported pricing model.")
@@ -366,7 +379,7 @@ fn update_preset_interactive(config: ProviderConfig, name: String) -> anyhow::Re
     let mut presets = PresetManager::load_or_create(&config.presets_file)?;
     let registry = config.registry()?;
 
-    let exeunits = registry.list().into_iter().map(|desc| desc.name).collect();
+    let exeunits = registry.names();
     let pricing_models = vec!["linear".to_string()];
 
     let preset =
@@ -27,6 +27,12 @@ pub enum ProfileConfig {
     Remove { name: String },
     /// Activate a profile
     Activate { name: String },
+    /// Show profiles dedicated to runtimes
+    Assignments,
+    /// Dedicate a profile to a runtime
+    Assign { runtime: String, name: String },
+    /// Make a runtime use the active profile again
+    Unassign { runtime: String },
 }
 
 impl ProfileConfig {
@@ -59,6 +65,22 @@ impl ProfileConfig {
                     profiles.set_active(name)?;
                     profiles.save(path)?;
                 }
+                ProfileConfig::Assignments => {
+                    let profiles = Profiles::load_or_create(&config)?;
+                    println!("{}", serde_json::to_string_pretty(profiles.assignments())?);
+                }
+                ProfileConfig::Assign { runtime, name } => {
+                    let mut profiles = Profiles::load_or_create(&config)?;
+                    profiles.assign(runtime, name)?;
+                    let available = Resources::try_with_config(path, &config)?;
+                    profiles.partitions(&available)?;
+                    profiles.save(path)?;
+                }
+                ProfileConfig::Unassign { runtime } => {
+                    let mut profiles = Profiles::load_or_create(&config)?;
+                    profiles.unassign(runtime)?;
+                    profiles.save(path)?;
+                }
                 ProfileConfig::Active => {
                     let profiles = Profiles::load_or_create(&config)?;
                     println!("{}", serde_json::to_string_pretty(profiles.active())?);
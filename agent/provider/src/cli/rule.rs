@@ -108,16 +108,31 @@ pub enum AuditedPayloadRuleWithCert {
 #[derive(StructOpt, Clone, Debug)]
 pub enum PartnerRuleWithCert {
     /// Set rule for Golem certificate with given id.
-    CertId(CertId),
+    CertId {
+        #[structopt(flatten)]
+        cert: CertId,
+        #[structopt(flatten)]
+        patterns: UrlPatterns,
+    },
     /// Import and set rule for Golem certificate or Golem certificates chain.
     ImportCert {
         /// Path to Golem certificate.
         imported_cert: PathBuf,
         #[structopt(short, long, possible_values = Mode::VARIANTS)]
         mode: Mode,
+        #[structopt(flatten)]
+        patterns: UrlPatterns,
     },
 }
 
+#[derive(StructOpt, Clone, Debug)]
+pub struct UrlPatterns {
+    /// Match URLs permitted by the certificate as patterns (scheme, host, port
+    /// and path prefix ending with `*`) instead of exactly.
+    #[structopt(long)]
+    url_patterns: bool,
+}
+
 #[derive(StructOpt, Clone, Debug)]
 pub enum RestrictRuleWithCert {
     /// Set rule for Golem certificate with given id.
@@ -271,16 +286,18 @@ fn set(set_rule: SetRule, config: ProviderConfig) -> Result<()> {
 
                 Ok(())
             }
-            SetOutboundRule::Partner(PartnerRuleWithCert::CertId(CertId { cert_id, mode })) => {
-                rules.set_partner_mode(cert_id, mode)
-            }
+            SetOutboundRule::Partner(PartnerRuleWithCert::CertId {
+                cert: CertId { cert_id, mode },
+                patterns,
+            }) => rules.set_partner_mode(cert_id, mode, patterns.url_patterns),
             SetOutboundRule::Partner(PartnerRuleWithCert::ImportCert {
                 imported_cert: import_cert,
                 mode,
+                patterns,
             }) => {
                 let leaf_cert_ids = rules.import_certs(&import_cert)?;
                 for cert_id in leaf_cert_ids {
-                    rules.set_partner_mode(cert_id, mode.clone())?;
+                    rules.set_partner_mode(cert_id, mode.clone(), patterns.url_patterns)?;
                 }
 
                 Ok(())
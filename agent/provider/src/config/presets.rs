@@ -136,6 +136,7 @@ impl From<PresetV0> for Preset {
         Preset {
             name: old_preset.name,
             exeunit_name: old_preset.exeunit_name,
+            exeunit_version: None,
             pricing_model: old_preset.pricing_model,
             initial_price: old_preset
                 .usage_coeffs
@@ -1,4 +1,5 @@
 use crate::config::presets::Presets;
+use crate::execution::ExeUnitsRegistry;
 
 #[derive(Clone, Debug)]
 pub enum Event {
@@ -9,4 +10,5 @@ pub enum Event {
         updated: Vec<String>,
         removed: Vec<String>,
     },
+    ExeUnitsChanged(ExeUnitsRegistry),
 }
@@ -1,6 +1,6 @@
 pub use task_runner::{
     ActivityDestroyed, CreateActivity, DestroyActivity, GetExeUnit, GetOfferTemplates, Shutdown,
-    TaskRunner, TaskRunnerConfig, TerminateActivity, UpdateActivity,
+    TaskRunner, TaskRunnerConfig, TerminateActivity, UpdateActivity, UpdateRegistry,
 };
 
 pub use self::registry::Configuration;
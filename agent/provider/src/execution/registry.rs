@@ -1,15 +1,15 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fmt,
     fs::File,
-    io::BufReader,
+    io::{BufReader, Read},
     path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, Context, Result};
 use futures::Future;
 use path_clean::PathClean;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use thiserror::Error;
@@ -47,6 +47,24 @@ pub fn default_counter_config() -> HashMap<String, CounterDefinition> {
         },
     );
 
+    counters.insert(
+        "golem.usage.network.in-mib".into(),
+        CounterDefinition {
+            name: "network_in_mib".into(),
+            description: "Network inbound traffic".into(),
+            price: false,
+        },
+    );
+
+    counters.insert(
+        "golem.usage.network.out-mib".into(),
+        CounterDefinition {
+            name: "network_out_mib".into(),
+            description: "Network outbound traffic".into(),
+            price: false,
+        },
+    );
+
     counters
 }
 
@@ -65,6 +83,12 @@ pub struct ExeUnitDesc {
     /// Optional runtime.
     #[serde(default)]
     pub runtime_path: Option<PathBuf>,
+    /// Expected hex-encoded SHA-256 of the supervisor binary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supervisor_sha256: Option<String>,
+    /// Expected hex-encoded SHA-256 of the runtime binary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_sha256: Option<String>,
     /// ExeUnit defined properties, that will be appended to offer.
     #[serde(default)]
     pub properties: Map<String, Value>,
@@ -141,9 +165,10 @@ impl ExeUnitDesc {
 
 /// Responsible for creating ExeUnits.
 /// Stores registry of ExeUnits that can be created.
-#[derive(Default)]
+/// Multiple versions of the same ExeUnit can be registered.
+#[derive(Clone, Debug, Default)]
 pub struct ExeUnitsRegistry {
-    descriptors: HashMap<String, ExeUnitDesc>,
+    descriptors: BTreeMap<String, BTreeMap<Version, ExeUnitDesc>>,
 }
 
 impl ExeUnitsRegistry {
@@ -157,10 +182,11 @@ impl ExeUnitsRegistry {
     pub fn spawn_exeunit(
         &self,
         name: &str,
+        version: Option<&VersionReq>,
         args: Vec<String>,
         working_dir: &Path,
     ) -> Result<ExeUnitInstance> {
-        let exeunit_desc = self.find_exeunit(name)?;
+        let exeunit_desc = self.find_exeunit_version(name, version)?;
         let extended_args = Self::exeunit_args(&exeunit_desc, args)?;
         ExeUnitInstance::new(
             name,
@@ -173,11 +199,12 @@ impl ExeUnitsRegistry {
     pub fn run_exeunit_with_output(
         &self,
         name: &str,
+        version: Option<&VersionReq>,
         args: Vec<String>,
         working_dir: &Path,
     ) -> impl Future<Output = Result<String>> {
         let working_dir = working_dir.to_owned();
-        let exeunit_desc = self.find_exeunit(name);
+        let exeunit_desc = self.find_exeunit_version(name, version);
         async move {
             let exeunit_desc = exeunit_desc?;
             ExeUnitInstance::run_with_output(
@@ -219,12 +246,16 @@ impl ExeUnitsRegistry {
         };
 
         log::info!(
-            "Added [{}] ExeUnit to registry. Supervisor path: [{}], Runtime path: [{:?}].",
+            "Added [{}] ExeUnit version [{}] to registry. Supervisor path: [{}], Runtime path: [{:?}].",
             desc.name,
+            desc.version,
             desc.supervisor_path.display(),
             desc.runtime_path
         );
-        self.descriptors.insert(desc.name.clone(), desc);
+        self.descriptors
+            .entry(desc.name.clone())
+            .or_default()
+            .insert(desc.version.clone(), desc);
         Ok(())
     }
 
@@ -268,22 +299,56 @@ impl ExeUnitsRegistry {
         Ok(())
     }
 
+    /// Finds the most recent version of ExeUnit
     pub fn find_exeunit(&self, name: &str) -> Result<ExeUnitDesc> {
-        Ok(self
+        self.find_exeunit_version(name, None)
+    }
+
+    /// Finds the most recent version of ExeUnit matching `version` requirement
+    pub fn find_exeunit_version(
+        &self,
+        name: &str,
+        version: Option<&VersionReq>,
+    ) -> Result<ExeUnitDesc> {
+        let versions = self
             .descriptors
             .get(name)
-            .ok_or_else(|| anyhow!("ExeUnit [{}] doesn't exist in registry.", name))?
-            .clone())
+            .ok_or_else(|| anyhow!("ExeUnit [{}] doesn't exist in registry.", name))?;
+        let desc = match version {
+            Some(req) => versions
+                .values()
+                .rev()
+                .find(|desc| req.matches(&desc.version))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "ExeUnit [{}] in version matching [{}] doesn't exist in registry.",
+                        name,
+                        req
+                    )
+                })?,
+            None => versions
+                .values()
+                .next_back()
+                .ok_or_else(|| anyhow!("ExeUnit [{}] doesn't exist in registry.", name))?,
+        };
+        Ok(desc.clone())
     }
 
     pub fn list(&self) -> Vec<ExeUnitDesc> {
-        self.descriptors.values().cloned().collect()
+        self.descriptors
+            .values()
+            .flat_map(|versions| versions.values().cloned())
+            .collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.descriptors.keys().cloned().collect()
     }
 
     pub fn validate(&self) -> Result<(), RegistryError> {
         let errors = self
-            .descriptors
-            .values()
+            .list()
+            .iter()
             .map(|desc| desc.validate())
             .filter_map(|result| match result {
                 Err(error) => Some(error),
@@ -297,17 +362,38 @@ impl ExeUnitsRegistry {
         Err(RegistryError(errors))
     }
 
+    /// Removes ExeUnits which failed validation, so no Offers are created for them
+    pub fn retain_valid(&mut self) -> Option<RegistryError> {
+        let mut errors = Vec::new();
+        for versions in self.descriptors.values_mut() {
+            versions.retain(|_, desc| match desc.validate() {
+                Ok(_) => true,
+                Err(error) => {
+                    log::error!("{}. Skipping ExeUnit.", error);
+                    errors.push(error);
+                    false
+                }
+            });
+        }
+        self.descriptors.retain(|_, versions| !versions.is_empty());
+
+        match errors.is_empty() {
+            true => None,
+            false => Some(RegistryError(errors)),
+        }
+    }
+
     pub async fn test_runtimes(&self, data_dir: &Path) -> anyhow::Result<()> {
         if self.descriptors.is_empty() {
             anyhow::bail!("No runtimes available");
         }
         let working_dir = exe_unit_work_dir(data_dir);
         std::fs::create_dir_all(&working_dir)?;
-        for (name, desc) in self.descriptors.iter() {
-            log::info!("Testing runtime [{}]", name);
-            test_runtime(desc, &working_dir)
+        for desc in self.list() {
+            log::info!("Testing runtime [{}] version [{}]", desc.name, desc.version);
+            test_runtime(&desc, &working_dir)
                 .await
-                .map_err(|e| e.context(format!("Runtime '{name}' test failed")))?;
+                .map_err(|e| e.context(format!("Runtime '{}' test failed", desc.name)))?;
         }
 
         Ok(())
@@ -332,6 +418,19 @@ pub enum ExeUnitValidation {
     SupervisorNotFound { desc: ExeUnitDesc },
     #[error("ExeUnit [{}] Runtime binary [{:?}] doesn't exist.", .desc.name, .desc.runtime_path)]
     RuntimeNotFound { desc: ExeUnitDesc },
+    #[error("ExeUnit [{}] binary [{}] hash mismatch: expected [{expected}], got [{actual}].", .desc.name, .path.display())]
+    HashMismatch {
+        desc: ExeUnitDesc,
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+    #[error("ExeUnit [{}] binary [{}] can't be hashed: {error}.", .desc.name, .path.display())]
+    HashError {
+        desc: ExeUnitDesc,
+        path: PathBuf,
+        error: String,
+    },
 }
 
 impl ExeUnitDesc {
@@ -344,10 +443,50 @@ impl ExeUnitDesc {
                 return Err(ExeUnitValidation::RuntimeNotFound { desc: self.clone() });
             }
         }
+
+        let binaries = [
+            (Some(&self.supervisor_path), &self.supervisor_sha256),
+            (self.runtime_path.as_ref(), &self.runtime_sha256),
+        ];
+        for (path, expected) in binaries {
+            if let (Some(path), Some(expected)) = (path, expected) {
+                self.verify_hash(path, expected)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_hash(&self, path: &Path, expected: &str) -> Result<(), ExeUnitValidation> {
+        let actual = sha256_file(path).map_err(|e| ExeUnitValidation::HashError {
+            desc: self.clone(),
+            path: path.to_path_buf(),
+            error: e.to_string(),
+        })?;
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(ExeUnitValidation::HashMismatch {
+                desc: self.clone(),
+                path: path.to_path_buf(),
+                expected: expected.to_string(),
+                actual,
+            });
+        }
         Ok(())
     }
 }
 
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = openssl::sha::Sha256::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf)? {
+            0 => break,
+            n => hasher.update(&buf[..n]),
+        }
+    }
+    Ok(hex::encode(hasher.finish()))
+}
+
 impl OfferBuilder for ExeUnitDesc {
     fn build(&self) -> Value {
         let mut common = serde_json::json!({
@@ -406,6 +545,9 @@ impl fmt::Display for ExeUnitDesc {
         if let Some(rt) = &self.runtime_path {
             writeln!(f, "{:width$}{}", "Runtime:", rt.display(), width = align)?;
         }
+        if let Some(hash) = &self.runtime_sha256 {
+            writeln!(f, "{:width$}{}", "Runtime hash:", hash, width = align)?;
+        }
         writeln!(
             f,
             "{:width$}{}",
@@ -534,4 +676,45 @@ mod tests {
             .unwrap()
             .contains("/usr/lib/yagna/plugins/exe-unit"));
     }
+
+    #[test]
+    fn test_find_exeunit_version() {
+        let mut registry = ExeUnitsRegistry::default();
+        registry
+            .register_exeunits_from_file(&resources_directory().join("versioned-exeunits.json"))
+            .unwrap();
+
+        assert_eq!(registry.names(), vec!["wasm".to_string()]);
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(
+            registry.find_exeunit("wasm").unwrap().version,
+            Version::new(0, 2, 0)
+        );
+
+        let req = VersionReq::parse("=0.1.0").unwrap();
+        let desc = registry.find_exeunit_version("wasm", Some(&req)).unwrap();
+        assert_eq!(desc.version, Version::new(0, 1, 0));
+
+        let req = VersionReq::parse("^0.3").unwrap();
+        assert!(registry.find_exeunit_version("wasm", Some(&req)).is_err());
+    }
+
+    #[test]
+    fn test_retain_valid_checks_hash() {
+        let mut registry = ExeUnitsRegistry::default();
+        registry
+            .register_exeunits_from_file(&resources_directory().join("versioned-exeunits.json"))
+            .unwrap();
+
+        let errors = registry.retain_valid().unwrap();
+        assert!(matches!(
+            errors.0.as_slice(),
+            [ExeUnitValidation::HashMismatch { .. }]
+        ));
+        assert_eq!(registry.list().len(), 1);
+        assert_eq!(
+            registry.find_exeunit("wasm").unwrap().version,
+            Version::new(0, 1, 0)
+        );
+    }
 }
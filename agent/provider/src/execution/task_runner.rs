@@ -1,11 +1,13 @@
 use actix::prelude::*;
 use anyhow::{anyhow, bail, Error, Result};
+use bytesize::ByteSize;
 use chrono::{DateTime, Utc};
 use derive_more::Display;
 use futures::future::{join_all, select, Either};
 use futures::{Future, FutureExt, TryFutureExt};
 use humantime;
 use log_derive::{logfn, logfn_inputs};
+use semver::{Version, VersionReq};
 use std::collections::HashMap;
 use std::fs::{create_dir_all, File};
 use std::path::{Path, PathBuf};
@@ -27,6 +29,7 @@ use ya_utils_process::ExeUnitExitStatus;
 
 use super::registry::{ExeUnitDesc, ExeUnitsRegistry};
 use super::task::Task;
+use crate::hardware;
 use crate::market::provider_market::NewAgreement;
 use crate::market::Preset;
 use crate::tasks::{AgreementBroken, AgreementClosed};
@@ -50,8 +53,14 @@ pub struct UpdateActivity;
 #[rtype(result = "Result<ExeUnitDesc>")]
 pub struct GetExeUnit {
     pub name: String,
+    pub version: Option<VersionReq>,
 }
 
+/// Replaces registry of ExeUnits used for new activities and Offers.
+#[derive(Message)]
+#[rtype(result = "Result<()>")]
+pub struct UpdateRegistry(pub ExeUnitsRegistry);
+
 #[derive(Message)]
 #[rtype(result = "Result<HashMap<String, OfferTemplate>>")]
 pub struct GetOfferTemplates(pub Vec<Preset>);
@@ -135,6 +144,14 @@ pub struct TaskRunnerConfig {
     /// Use this option to save disk space. Shouldn't be used when debugging.
     #[structopt(long, env)]
     pub auto_cleanup_agreement: bool,
+    /// Maximum size of the image cache shared by ExeUnits, e.g. `20GiB`.
+    /// Least recently used images, which aren't in use, are removed when exceeded.
+    #[structopt(long, env)]
+    pub exe_unit_cache_max_size: Option<ByteSize>,
+    /// Terminates activities which stay idle for given time, e.g. `30min`:
+    /// no commands were executed and no processes are running in the runtime.
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration))]
+    pub exe_unit_idle_timeout: Option<Duration>,
     #[structopt(skip = "you-forgot-to-set-session-id")]
     pub session_id: String,
 }
@@ -149,6 +166,8 @@ pub struct TaskRunnerConfig {
 pub struct TaskRunner {
     api: Arc<ActivityProviderApi>,
     registry: ExeUnitsRegistry,
+    /// Charges resources of spawned activities against runtime partitions.
+    hardware: hardware::Allocator,
     /// Spawned tasks.
     tasks: Vec<Task>,
     active_agreements: HashMap<String, AgreementView>,
@@ -169,6 +188,7 @@ impl TaskRunner {
         client: ActivityProviderApi,
         config: TaskRunnerConfig,
         registry: ExeUnitsRegistry,
+        hardware: hardware::Allocator,
         data_dir: P,
     ) -> Result<TaskRunner> {
         let data_dir = data_dir.as_ref();
@@ -205,6 +225,7 @@ impl TaskRunner {
         Ok(TaskRunner {
             api: Arc::new(client),
             registry,
+            hardware,
             tasks: vec![],
             active_agreements: HashMap::new(),
             activity_created: SignalSlot::<CreateActivity>::default(),
@@ -221,7 +242,14 @@ impl TaskRunner {
         msg: GetExeUnit,
         _ctx: &mut Context<Self>,
     ) -> Result<ExeUnitDesc> {
-        self.registry.find_exeunit(&msg.name)
+        self.registry
+            .find_exeunit_version(&msg.name, msg.version.as_ref())
+    }
+
+    pub fn update_registry(&mut self, msg: UpdateRegistry, _ctx: &mut Context<Self>) -> Result<()> {
+        log::info!("Updating ExeUnits registry: {:?}", msg.0.names());
+        self.registry = msg.0;
+        Ok(())
     }
 
     // =========================================== //
@@ -278,16 +306,28 @@ impl TaskRunner {
             Some(agreement) => agreement,
         };
 
-        let exeunit_name = exe_unit_name_from(agreement)?;
+        let (exeunit_name, exeunit_version) = exe_unit_name_from(agreement)?;
+        let resources = resources_from(agreement);
+
+        if let Err(error) = self
+            .hardware
+            .allocate(&msg.activity_id, &exeunit_name, resources)
+        {
+            bail!("Can't create activity [{}]: {}", msg.activity_id, error);
+        }
 
         let task = match self.create_task(
             &exeunit_name,
+            exeunit_version.as_ref(),
             &msg.activity_id,
             &msg.agreement_id,
             msg.requestor_pub_key.as_deref(),
         ) {
             Ok(task) => task,
-            Err(error) => bail!("Error creating activity: {:?}: {}", msg, error),
+            Err(error) => {
+                let _ = self.hardware.release(&msg.activity_id).log_warn();
+                bail!("Error creating activity: {:?}: {}", msg, error)
+            }
         };
 
         let process = task.exeunit.get_process_handle();
@@ -321,13 +361,35 @@ impl TaskRunner {
         let agreement_id = msg.agreement_id.clone();
         let state_retry_interval = self.config.exeunit_state_retry_interval;
         let api = self.api.clone();
+        let recover = self.recover_activity(
+            &exeunit_name,
+            exeunit_version.as_ref(),
+            &activity_id,
+            &agreement_id,
+        );
 
         tokio::task::spawn_local(async move {
             let status = process.wait_until_finished().await;
 
+            // ExeUnit journals its state, so after a crash it's able to report
+            // the terminal state together with the final usage.
+            let recovered = match &status {
+                ExeUnitExitStatus::Aborted(_) | ExeUnitExitStatus::Error(_) => {
+                    match recover.await {
+                        Ok(_) => true,
+                        Err(error) => {
+                            log::warn!("Unable to recover activity [{}]: {}", activity_id, error);
+                            false
+                        }
+                    }
+                }
+                _ => false,
+            };
+
             // If it was brutal termination than ExeUnit probably didn't set state.
             // We must do it instead of him. Repeat until it will succeed.
             match &status {
+                _ if recovered => (),
                 ExeUnitExitStatus::Aborted(exit_status) => {
                     log::warn!(
                         "ExeUnit [{}] execution aborted. Setting activity [{}] state to Terminated",
@@ -377,6 +439,8 @@ impl TaskRunner {
             msg.activity_id
         );
 
+        let _ = self.hardware.release(&msg.activity_id).log_warn();
+
         if self.config.auto_cleanup_activity {
             let workdir = self
                 .agreement_dir(&msg.agreement_id)
@@ -411,22 +475,56 @@ impl TaskRunner {
         Ok(())
     }
 
-    fn offer_template(&self, exeunit_name: &str) -> impl Future<Output = Result<String>> {
+    fn recover_activity(
+        &self,
+        exeunit_name: &str,
+        exeunit_version: Option<&VersionReq>,
+        activity_id: &str,
+        agreement_id: &str,
+    ) -> impl Future<Output = Result<String>> {
+        let working_dir = self.agreement_dir(agreement_id).secure_join(activity_id);
+        let args = vec![
+            "recover".to_string(),
+            activity_id.to_string(),
+            ya_core_model::activity::local::BUS_ID.to_string(),
+            "-w".to_string(),
+            working_dir.display().to_string(),
+        ];
+        self.registry
+            .run_exeunit_with_output(exeunit_name, exeunit_version, args, &working_dir)
+            .map_err(|error| error.context("ExeUnit recover command failed".to_string()))
+    }
+
+    fn offer_template(
+        &self,
+        exeunit_name: &str,
+        exeunit_version: Option<&VersionReq>,
+    ) -> impl Future<Output = Result<String>> {
         let working_dir = self.tasks_dir.clone();
         let args = vec![String::from("offer-template")];
         self.registry
-            .run_exeunit_with_output(exeunit_name, args, &working_dir)
+            .run_exeunit_with_output(exeunit_name, exeunit_version, args, &working_dir)
             .map_err(|error| error.context("ExeUnit offer-template command failed".to_string()))
     }
 
-    fn exeunit_coeffs(&self, exeunit_name: &str) -> Result<Vec<String>> {
-        Ok(match self.registry.find_exeunit(exeunit_name)?.config {
-            Some(ref config) => (config.counters.iter())
-                .filter_map(|(prop, cnt)| cnt.price.then_some(prop))
-                .cloned()
-                .collect(),
-            _ => Default::default(),
-        })
+    fn exeunit_coeffs(
+        &self,
+        exeunit_name: &str,
+        exeunit_version: Option<&VersionReq>,
+    ) -> Result<Vec<String>> {
+        Ok(
+            match self
+                .registry
+                .find_exeunit_version(exeunit_name, exeunit_version)?
+                .config
+            {
+                Some(ref config) => (config.counters.iter())
+                    .filter_map(|(prop, cnt)| cnt.price.then_some(prop))
+                    .cloned()
+                    .collect(),
+                _ => Default::default(),
+            },
+        )
     }
 
     fn agreement_dir(&self, agreement_id: &str) -> PathBuf {
@@ -437,6 +535,7 @@ impl TaskRunner {
     fn create_task(
         &self,
         exeunit_name: &str,
+        exeunit_version: Option<&VersionReq>,
         activity_id: &str,
         agreement_id: &str,
         requestor_pub_key: Option<&str>,
@@ -480,6 +579,22 @@ impl TaskRunner {
             .iter(),
         );
 
+        let cache_max_size = self
+            .config
+            .exe_unit_cache_max_size
+            .map(|size| size.as_u64().to_string());
+        if let Some(size) = &cache_max_size {
+            args.extend(["--cache-max-size", size.as_str()].iter());
+        }
+
+        let idle_timeout = self
+            .config
+            .exe_unit_idle_timeout
+            .map(|timeout| humantime::format_duration(timeout).to_string());
+        if let Some(timeout) = &idle_timeout {
+            args.extend(["--idle-timeout", timeout.as_str()].iter());
+        }
+
         if let Some(req_pub_key) = requestor_pub_key {
             args.extend(["--requestor-pub-key", req_pub_key].iter());
         }
@@ -495,7 +610,7 @@ impl TaskRunner {
 
         let exeunit_instance = self
             .registry
-            .spawn_exeunit(exeunit_name, args, &working_dir)
+            .spawn_exeunit(exeunit_name, exeunit_version, args, &working_dir)
             .map_err(|error| {
                 anyhow!(
                     "Spawning ExeUnit failed for agreement [{}] with error: {}",
@@ -550,9 +665,31 @@ pub fn exe_unit_cache_dir<P: AsRef<Path>>(data_dir: P) -> PathBuf {
     data_dir.join(EXE_UNIT_DIR).join(CACHE_DIR)
 }
 
-fn exe_unit_name_from(agreement: &AgreementView) -> Result<String> {
+/// Returns ExeUnit name and version, which was offered in Agreement.
+fn exe_unit_name_from(agreement: &AgreementView) -> Result<(String, Option<VersionReq>)> {
     let runtime_key_str = "/offer/properties/golem/runtime/name";
-    Ok(agreement.pointer_typed::<String>(runtime_key_str)?)
+    let version_key_str = "/offer/properties/golem/runtime/version";
+    let name = agreement.pointer_typed::<String>(runtime_key_str)?;
+    let version = agreement
+        .pointer_typed::<String>(version_key_str)
+        .ok()
+        .and_then(|version| Version::parse(&version).ok())
+        .map(|version| VersionReq::exact(&version));
+    Ok((name, version))
+}
+
+/// Resources the Provider committed to in the Offer. Missing ones aren't charged.
+fn resources_from(agreement: &AgreementView) -> hardware::Resources {
+    let value = |key: &str| {
+        agreement
+            .pointer_typed::<f64>(&format!("/offer/properties/golem/inf/{key}"))
+            .unwrap_or(0.)
+    };
+    hardware::Resources {
+        cpu_threads: value("cpu/threads") as i32,
+        mem_gib: value("mem/gib"),
+        storage_gib: value("storage/gib"),
+    }
 }
 
 async fn set_activity_terminated(
@@ -620,6 +757,7 @@ impl Actor for TaskRunner {
 forward_actix_handler!(TaskRunner, NewAgreement, on_agreement_approved);
 forward_actix_handler!(TaskRunner, ExeUnitProcessFinished, on_exeunit_exited);
 forward_actix_handler!(TaskRunner, GetExeUnit, get_exeunit);
+forward_actix_handler!(TaskRunner, UpdateRegistry, update_registry);
 actix_signal_handler!(TaskRunner, CreateActivity, activity_created);
 actix_signal_handler!(TaskRunner, ActivityDestroyed, activity_destroyed);
 
@@ -631,10 +769,24 @@ impl Handler<GetOfferTemplates> for TaskRunner {
     fn handle(&mut self, msg: GetOfferTemplates, _: &mut Context<Self>) -> Self::Result {
         let mut result: HashMap<String, OfferTemplate> = HashMap::with_capacity(msg.0.len());
         let entries = (msg.0.into_iter())
+            .filter(|preset| {
+                let version = preset.exeunit_version.as_ref();
+                match self
+                    .registry
+                    .find_exeunit_version(&preset.exeunit_name, version)
+                {
+                    Ok(_) => true,
+                    Err(error) => {
+                        log::warn!("Skipping offer template for {}: {}", preset.name, error);
+                        false
+                    }
+                }
+            })
             .map(|preset| {
-                let fut = self.offer_template(&preset.exeunit_name);
+                let version = preset.exeunit_version.as_ref();
+                let fut = self.offer_template(&preset.exeunit_name, version);
                 let coeffs = self
-                    .exeunit_coeffs(&preset.exeunit_name)
+                    .exeunit_coeffs(&preset.exeunit_name, version)
                     .map(|mut coll| {
                         coll.retain(|prop| preset.usage_coeffs.contains_key(prop));
                         coll
@@ -35,6 +35,10 @@ pub enum ProfileError {
     AlreadyExists(String),
     #[error("profile is active: '{0}'")]
     Active(String),
+    #[error("profile is assigned to runtime '{1}': '{0}'")]
+    Assigned(String, String),
+    #[error("runtime partitions exceed available hardware resources")]
+    Overcommitted,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -139,6 +143,15 @@ impl Resources {
         self.cpu_threads <= 0 || self.mem_gib <= 0. || self.storage_gib <= 0.
     }
 
+    /// Lower of both values of each resource
+    fn limit(self, res: &Resources) -> Self {
+        Resources {
+            cpu_threads: self.cpu_threads.min(res.cpu_threads),
+            mem_gib: self.mem_gib.min(res.mem_gib),
+            storage_gib: self.storage_gib.min(res.storage_gib),
+        }
+    }
+
     pub fn cap(mut self, res: &Resources) -> Self {
         self.cpu_threads = MIN_CAPS
             .cpu_threads
@@ -219,6 +232,10 @@ impl From<Resources> for InfNodeInfo {
 pub struct Profiles {
     active: String,
     profiles: HashMap<String, Resources>,
+    /// Profiles dedicated to runtimes (ExeUnit name -> profile name).
+    /// Runtimes without an assigned profile share the active one.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    runtimes: HashMap<String, String>,
 }
 
 impl Profiles {
@@ -263,7 +280,11 @@ impl Profiles {
         let resources = Resources::try_with_config(path.as_ref(), config)?;
         let active = DEFAULT_PROFILE_NAME.to_string();
         let profiles = vec![(active.clone(), resources)].into_iter().collect();
-        Ok(Profiles { active, profiles })
+        Ok(Profiles {
+            active,
+            profiles,
+            runtimes: Default::default(),
+        })
     }
 }
 
@@ -298,6 +319,9 @@ impl Profiles {
         if name == self.active {
             return Err(ProfileError::Active(name).into());
         }
+        if let Some((runtime, _)) = self.runtimes.iter().find(|(_, p)| **p == name) {
+            return Err(ProfileError::Assigned(name, runtime.clone()).into());
+        }
         if self.profiles.remove(&name).is_none() {
             return Err(ProfileError::Unknown(name).into());
         }
@@ -317,6 +341,49 @@ impl Profiles {
         self.active = name;
         Ok(())
     }
+
+    #[inline]
+    pub fn assignments(&self) -> &HashMap<String, String> {
+        &self.runtimes
+    }
+
+    pub fn assign(&mut self, runtime: impl ToString, name: impl ToString) -> Result<(), Error> {
+        let name = name.to_string();
+        if self.profiles.contains_key(&name).not() {
+            return Err(ProfileError::Unknown(name).into());
+        }
+        self.runtimes.insert(runtime.to_string(), name);
+        Ok(())
+    }
+
+    pub fn unassign(&mut self, runtime: impl ToString) -> Result<(), Error> {
+        let runtime = runtime.to_string();
+        match self.runtimes.remove(&runtime) {
+            Some(_) => Ok(()),
+            None => Err(ProfileError::Unknown(runtime).into()),
+        }
+    }
+
+    /// Resources dedicated to runtimes, capped by the available ones.
+    /// Partitions can't exceed available resources in total.
+    pub fn partitions(&self, available: &Resources) -> Result<HashMap<String, Resources>, Error> {
+        let partitions = self
+            .runtimes
+            .iter()
+            .map(|(runtime, name)| match self.profiles.get(name) {
+                Some(res) => Ok((runtime.clone(), res.cap(available))),
+                None => Err(ProfileError::Unknown(name.clone()).into()),
+            })
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+
+        let total = partitions
+            .values()
+            .fold(Resources::new_empty(), |total, res| total + *res);
+        if *available < total {
+            return Err(ProfileError::Overcommitted.into());
+        }
+        Ok(partitions)
+    }
 }
 
 #[derive(Debug)]
@@ -332,15 +399,34 @@ struct ManagerState {
     profiles: Profiles,
     res_available: Resources,
     res_cap: Resources,
-    res_remaining: Resources,
-    res_alloc: HashMap<String, Resources>,
+    res_alloc: HashMap<String, Allocation>,
+    res_partitions: HashMap<String, Resources>,
+}
+
+#[derive(Clone, Debug)]
+struct Allocation {
+    runtime: String,
+    res: Resources,
 }
 
 impl ManagerState {
     #[inline]
     fn update(&mut self, profiles: Profiles) -> Result<bool, Error> {
+        let partitions = profiles.partitions(&self.res_available)?;
         self.profiles = profiles;
-        self.change_profile(self.profiles.active.clone())
+        let changed = self.change_profile(self.profiles.active.clone())?;
+        Ok(self.change_partitions(partitions) || changed)
+    }
+
+    fn change_partitions(&mut self, partitions: HashMap<String, Resources>) -> bool {
+        if partitions == self.res_partitions {
+            return false;
+        }
+        for (runtime, res) in partitions.iter() {
+            log::info!("Hardware resources dedicated to runtime '{runtime}': {res:?}");
+        }
+        self.res_partitions = partitions;
+        true
     }
 
     fn change_profile(&mut self, name: impl ToString) -> Result<bool, Error> {
@@ -357,14 +443,61 @@ impl ManagerState {
         if res == self.res_cap {
             Ok(false)
         } else {
-            let delta = self.res_cap - res;
             self.res_cap = res;
-            self.res_remaining = self.res_remaining - delta;
             log::info!("Hardware resources cap: {:?}", self.res_cap);
-            log::info!("Hardware resources remaining: {:?}", self.res_remaining);
             Ok(true)
         }
     }
+
+    /// Resources not dedicated to any runtime, capped by the active profile
+    fn shared(&self) -> Resources {
+        let dedicated = self
+            .res_partitions
+            .values()
+            .fold(Resources::new_empty(), |total, res| total + *res);
+        self.res_cap.limit(&(self.res_available - dedicated))
+    }
+
+    /// Dedicated partition of the runtime, or resources shared by runtimes without one
+    fn pool(&self, runtime: &str) -> Resources {
+        match self.res_partitions.get(runtime) {
+            Some(res) => *res,
+            None => self.shared(),
+        }
+    }
+
+    /// Resources allocated from the same pool as the runtime
+    fn allocated(&self, runtime: &str) -> Resources {
+        let dedicated = self.res_partitions.contains_key(runtime);
+        self.res_alloc
+            .values()
+            .filter(|alloc| match dedicated {
+                true => alloc.runtime == runtime,
+                false => !self.res_partitions.contains_key(&alloc.runtime),
+            })
+            .fold(Resources::new_empty(), |total, alloc| total + alloc.res)
+    }
+
+    fn allocate(&mut self, id: String, runtime: &str, res: Resources) -> Result<(), Error> {
+        if self.res_alloc.contains_key(&id) {
+            return Err(Error::AlreadyAllocated(id));
+        }
+        // Without partitions all runtimes are offered the whole cap, the same as before
+        // profiles could be dedicated to runtimes.
+        if !self.res_partitions.is_empty() && self.pool(runtime) < self.allocated(runtime) + res {
+            return Err(Error::InsufficientResources);
+        }
+        let runtime = runtime.to_string();
+        self.res_alloc.insert(id, Allocation { runtime, res });
+        Ok(())
+    }
+
+    fn release(&mut self, id: String) -> Result<(), Error> {
+        match self.res_alloc.remove(&id) {
+            Some(_) => Ok(()),
+            None => Err(Error::NotAllocated(id)),
+        }
+    }
 }
 
 impl Manager {
@@ -375,10 +508,12 @@ impl Manager {
             profiles,
             res_available: Resources::try_with_config(conf.hardware_file.as_path(), conf)?,
             res_cap: Resources::new_empty(),
-            res_remaining: Resources::new_empty(),
             res_alloc: HashMap::new(),
+            res_partitions: HashMap::new(),
         };
         state.change_profile(state.profiles.active.clone())?;
+        let partitions = state.profiles.partitions(&state.res_available)?;
+        state.change_partitions(partitions);
 
         let (tx, rx) = watch::channel(Event::Initialized);
         Ok(Manager {
@@ -424,28 +559,38 @@ impl Manager {
         state.res_cap
     }
 
-    #[allow(dead_code)]
-    pub fn allocate(&mut self, id: String, res: Resources) -> Result<(), Error> {
-        let mut state = self.state.lock().unwrap();
-        if state.res_alloc.contains_key(&id) {
-            return Err(Error::AlreadyAllocated(id));
-        }
-        if state.res_remaining < res {
-            return Err(Error::InsufficientResources);
+    /// Resources offered for a runtime: its dedicated partition, or the part
+    /// of the cap which isn't dedicated to other runtimes.
+    pub fn capped_for(&self, runtime: &str) -> Resources {
+        let state = self.state.lock().unwrap();
+        state.pool(runtime)
+    }
+
+    #[inline]
+    pub fn allocator(&self) -> Allocator {
+        Allocator {
+            state: self.state.clone(),
         }
-        state.res_remaining = state.res_remaining - res;
-        state.res_alloc.insert(id, res);
-        Ok(())
     }
+}
+
+/// Accounts resources of running activities against partitions of their runtimes.
+#[derive(Clone, Debug)]
+pub struct Allocator {
+    state: Arc<Mutex<ManagerState>>,
+}
 
-    #[allow(dead_code)]
-    pub fn release(&mut self, id: String) -> Result<(), Error> {
+impl Allocator {
+    /// Fails when the runtime's partition (or resources shared by runtimes without
+    /// a partition) can't fit `res` next to resources of its running activities.
+    pub fn allocate(&self, id: impl ToString, runtime: &str, res: Resources) -> Result<(), Error> {
         let mut state = self.state.lock().unwrap();
-        match state.res_alloc.remove(&id) {
-            Some(res) => state.res_remaining = state.res_remaining + res,
-            _ => return Err(Error::NotAllocated(id)),
-        }
-        Ok(())
+        state.allocate(id.to_string(), runtime, res)
+    }
+
+    pub fn release(&self, id: impl ToString) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        state.release(id.to_string())
     }
 }
 
@@ -516,7 +661,28 @@ mod tests {
             storage_gib: 100.,
         };
         let profiles = vec![(active.clone(), resources)].into_iter().collect();
-        Profiles { active, profiles }
+        Profiles {
+            active,
+            profiles,
+            runtimes: Default::default(),
+        }
+    }
+
+    fn manager(available: Resources, partitions: HashMap<String, Resources>) -> Manager {
+        let state = ManagerState {
+            res_available: available,
+            res_cap: available,
+            res_alloc: HashMap::new(),
+            res_partitions: partitions,
+            profiles: profiles(),
+        };
+        let (tx, rx) = watch::channel(Event::Initialized);
+        Manager {
+            state: Arc::new(Mutex::new(state)),
+            monitor: None,
+            sender: Some(tx),
+            receiver: rx,
+        }
     }
 
     #[test]
@@ -580,37 +746,24 @@ mod tests {
             mem_gib: 24.,
             storage_gib: 200.,
         };
-        let state = ManagerState {
-            res_available: res,
-            res_cap: res,
-            res_remaining: res,
-            res_alloc: HashMap::new(),
-            profiles: profiles(),
-        };
-        let (tx, rx) = watch::channel(Event::Initialized);
-        let mut man = Manager {
-            state: Arc::new(Mutex::new(state)),
-            monitor: None,
-            sender: Some(tx),
-            receiver: rx,
-        };
+        let man = manager(res, HashMap::from([("vm".to_string(), res)]));
         let alloc = Resources {
             cpu_threads: 1,
             mem_gib: 1.51,
             storage_gib: 12.37,
         };
 
-        man.allocate("1".into(), alloc).unwrap();
-        man.allocate("2".into(), alloc).unwrap();
-        man.allocate("3".into(), alloc).unwrap();
-        man.release("1".into()).unwrap();
-        man.release("2".into()).unwrap();
-        man.release("3".into()).unwrap();
+        let allocator = man.allocator();
 
-        let remaining = man.state.lock().unwrap().res_remaining;
-        assert_eq!(remaining.cpu_threads, res.cpu_threads);
-        assert_eq!(remaining.mem_gib, res.mem_gib);
-        assert_eq!(remaining.storage_gib, res.storage_gib);
+        allocator.allocate("1", "vm", alloc).unwrap();
+        allocator.allocate("2", "vm", alloc).unwrap();
+        allocator.allocate("3", "vm", alloc).unwrap();
+        allocator.release("1").unwrap();
+        allocator.release("2").unwrap();
+        allocator.release("3").unwrap();
+
+        let allocated = man.state.lock().unwrap().allocated("vm");
+        assert_eq!(allocated, Resources::new_empty());
     }
 
     #[test]
@@ -620,32 +773,22 @@ mod tests {
             mem_gib: 24.,
             storage_gib: 200.,
         };
-        let state = ManagerState {
-            res_available: res,
-            res_cap: res,
-            res_remaining: res,
-            res_alloc: HashMap::new(),
-            profiles: profiles(),
-        };
-        let (tx, rx) = watch::channel(Event::Initialized);
-        let mut man = Manager {
-            state: Arc::new(Mutex::new(state)),
-            monitor: None,
-            sender: Some(tx),
-            receiver: rx,
-        };
+        let man = manager(res, HashMap::from([("vm".to_string(), res)]));
         let alloc = Resources {
             cpu_threads: 1,
             mem_gib: 1.51,
             storage_gib: 12.37,
         };
 
-        man.allocate("1".into(), alloc).unwrap();
-        assert!(man.allocate("1".into(), alloc).is_err());
-        assert!(man.release("2".into()).is_err());
-        assert!(man
+        let allocator = man.allocator();
+
+        allocator.allocate("1", "vm", alloc).unwrap();
+        assert!(allocator.allocate("1", "vm", alloc).is_err());
+        assert!(allocator.release("2").is_err());
+        assert!(allocator
             .allocate(
-                "3".into(),
+                "3",
+                "vm",
                 Resources {
                     cpu_threads: 1000,
                     mem_gib: 10000.,
@@ -654,4 +797,73 @@ mod tests {
             )
             .is_err());
     }
+
+    #[test]
+    fn runtime_partitions() {
+        let available = Resources {
+            cpu_threads: 12,
+            mem_gib: 24.,
+            storage_gib: 200.,
+        };
+        let mut profiles = profiles();
+        profiles
+            .add(
+                "vm",
+                Resources {
+                    cpu_threads: 8,
+                    mem_gib: 16.,
+                    storage_gib: 100.,
+                },
+            )
+            .unwrap();
+        assert!(profiles.assign("vm", "unknown").is_err());
+        profiles.assign("vm", "vm").unwrap();
+        profiles.assign("wasmtime", DEFAULT_PROFILE_NAME).unwrap();
+        assert!(profiles.remove("vm").is_err());
+
+        let partitions = profiles.partitions(&available).unwrap();
+        assert_eq!(partitions["vm"].cpu_threads, 8);
+        assert_eq!(partitions["wasmtime"].cpu_threads, 4);
+
+        profiles.assign("wasmtime", "vm").unwrap();
+        assert!(profiles.partitions(&available).is_err());
+
+        profiles.unassign("wasmtime").unwrap();
+        assert!(profiles.unassign("wasmtime").is_err());
+        assert_eq!(profiles.partitions(&available).unwrap().len(), 1);
+    }
+    #[test]
+    fn partition_allocation() {
+        let available = Resources {
+            cpu_threads: 12,
+            mem_gib: 24.,
+            storage_gib: 200.,
+        };
+        let vm = Resources {
+            cpu_threads: 10,
+            mem_gib: 16.,
+            storage_gib: 100.,
+        };
+        let man = manager(available, HashMap::from([("vm".to_string(), vm)]));
+        let allocator = man.allocator();
+
+        // Runtimes without a partition share the rest
+        let shared = Resources {
+            cpu_threads: 2,
+            mem_gib: 8.,
+            storage_gib: 100.,
+        };
+        assert_eq!(man.capped_for("vm"), vm);
+        assert_eq!(man.capped_for("wasmtime"), shared);
+
+        allocator.allocate("vm-1", "vm", vm).unwrap();
+        assert!(allocator.allocate("vm-2", "vm", MIN_CAPS).is_err());
+        allocator.allocate("wasm-1", "wasmtime", shared).unwrap();
+        assert!(allocator.allocate("other-1", "other", MIN_CAPS).is_err());
+
+        allocator.release("vm-1").unwrap();
+        allocator.allocate("vm-2", "vm", vm).unwrap();
+        allocator.release("wasm-1").unwrap();
+        allocator.allocate("other-1", "other", MIN_CAPS).unwrap();
+    }
 }
@@ -57,6 +57,7 @@ async fn main() -> anyhow::Result<()> {
         Commands::Keystore(keystore_cmd) => keystore_cmd.run(config),
         Commands::Whitelist(whitelist_cmd) => whitelist_cmd.run(config),
         Commands::Clean(clean_cmd) => clean_cmd.run(config),
+        Commands::Cache(cache_cmd) => cache_cmd.run(config),
         Commands::Rule(outbound_cmd) => outbound_cmd.run(config),
     }
 }
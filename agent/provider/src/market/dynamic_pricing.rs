@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use structopt::StructOpt;
+
+use crate::market::Preset;
+
+/// Configuration for utilization based price adjustment.
+#[derive(StructOpt, Clone, Debug)]
+pub struct DynamicPricingConfig {
+    /// Periodically adjust offer prices based on recent utilization
+    #[structopt(long, env)]
+    pub dynamic_pricing: bool,
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "30min")]
+    pub pricing_adjustment_interval: Duration,
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "1min")]
+    pub pricing_sample_interval: Duration,
+    /// Lowest multiplier applied to preset prices
+    #[structopt(long, env, default_value = "0.5")]
+    pub pricing_min_multiplier: f64,
+    /// Highest multiplier applied to preset prices
+    #[structopt(long, env, default_value = "2.0")]
+    pub pricing_max_multiplier: f64,
+    /// Multiplier change in a single adjustment
+    #[structopt(long, env, default_value = "0.1")]
+    pub pricing_step: f64,
+    /// Prices are raised when utilization is above this threshold
+    #[structopt(long, env, default_value = "0.8")]
+    pub pricing_high_utilization: f64,
+    /// Prices are lowered when utilization is below this threshold
+    #[structopt(long, env, default_value = "0.2")]
+    pub pricing_low_utilization: f64,
+}
+
+impl DynamicPricingConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.pricing_min_multiplier <= 0.0
+            || self.pricing_min_multiplier > 1.0
+            || self.pricing_max_multiplier < 1.0
+        {
+            return Err(anyhow!(
+                "Invalid price multiplier bounds [{}, {}]. Bounds must include 1.0",
+                self.pricing_min_multiplier,
+                self.pricing_max_multiplier
+            ));
+        }
+        if self.pricing_low_utilization >= self.pricing_high_utilization {
+            return Err(anyhow!(
+                "Low utilization threshold ({}) must be lower than the high one ({})",
+                self.pricing_low_utilization,
+                self.pricing_high_utilization
+            ));
+        }
+        if self.pricing_step <= 0.0 {
+            return Err(anyhow!("Pricing step must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Adjusts the multiplier applied to preset prices, based on utilization
+/// samples collected since the last adjustment.
+pub struct PriceAdjuster {
+    config: DynamicPricingConfig,
+    multiplier: f64,
+    samples: Vec<f64>,
+}
+
+impl PriceAdjuster {
+    pub fn new(config: DynamicPricingConfig) -> Self {
+        PriceAdjuster {
+            config,
+            multiplier: 1.0,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &DynamicPricingConfig {
+        &self.config
+    }
+
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// Records utilization as a fraction of capacity in use.
+    pub fn sample(&mut self, active: usize, capacity: usize) {
+        let utilization = match capacity {
+            0 => 1.0,
+            capacity => (active as f64 / capacity as f64).min(1.0),
+        };
+        self.samples.push(utilization);
+    }
+
+    /// Returns the new multiplier if it has changed.
+    pub fn adjust(&mut self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let utilization = self.samples.iter().sum::<f64>() / self.samples.len() as f64;
+        self.samples.clear();
+
+        let multiplier = if utilization > self.config.pricing_high_utilization {
+            self.multiplier + self.config.pricing_step
+        } else if utilization < self.config.pricing_low_utilization {
+            self.multiplier - self.config.pricing_step
+        } else {
+            return None;
+        }
+        .clamp(
+            self.config.pricing_min_multiplier,
+            self.config.pricing_max_multiplier,
+        );
+
+        if (multiplier - self.multiplier).abs() < f64::EPSILON {
+            return None;
+        }
+
+        log::info!(
+            "Average utilization {:.2}. Adjusting price multiplier from {:.2} to {:.2}",
+            utilization,
+            self.multiplier,
+            multiplier
+        );
+        self.multiplier = multiplier;
+        Some(multiplier)
+    }
+
+    /// Applies the current multiplier to preset prices.
+    pub fn apply(&self, mut preset: Preset) -> Preset {
+        preset.initial_price *= self.multiplier;
+        preset
+            .usage_coeffs
+            .values_mut()
+            .for_each(|price| *price *= self.multiplier);
+        preset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adjuster() -> PriceAdjuster {
+        PriceAdjuster::new(DynamicPricingConfig {
+            dynamic_pricing: true,
+            pricing_adjustment_interval: Duration::from_secs(60),
+            pricing_sample_interval: Duration::from_secs(1),
+            pricing_min_multiplier: 0.8,
+            pricing_max_multiplier: 1.2,
+            pricing_step: 0.1,
+            pricing_high_utilization: 0.8,
+            pricing_low_utilization: 0.2,
+        })
+    }
+
+    #[test]
+    fn raise_price_near_capacity() {
+        let mut adjuster = adjuster();
+        adjuster.sample(4, 4);
+        adjuster.sample(3, 4);
+        assert!((adjuster.adjust().unwrap() - 1.1).abs() < 1e-9);
+        adjuster.sample(4, 4);
+        assert!((adjuster.adjust().unwrap() - 1.2).abs() < 1e-9);
+        adjuster.sample(4, 4);
+        assert_eq!(adjuster.adjust(), None);
+    }
+
+    #[test]
+    fn lower_price_when_idle() {
+        let mut adjuster = adjuster();
+        adjuster.sample(0, 4);
+        assert!((adjuster.adjust().unwrap() - 0.9).abs() < 1e-9);
+        adjuster.sample(0, 4);
+        adjuster.adjust();
+        adjuster.sample(0, 4);
+        assert_eq!(adjuster.adjust(), None);
+        assert!((adjuster.multiplier() - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn keep_price_on_moderate_utilization() {
+        let mut adjuster = adjuster();
+        assert_eq!(adjuster.adjust(), None);
+        adjuster.sample(2, 4);
+        assert_eq!(adjuster.adjust(), None);
+    }
+
+    #[test]
+    fn apply_multiplier() {
+        let mut adjuster = adjuster();
+        adjuster.sample(4, 4);
+        adjuster.adjust();
+
+        let preset = Preset {
+            initial_price: 1.0,
+            usage_coeffs: [("golem.usage.cpu_sec".to_string(), 0.5)].into(),
+            ..Default::default()
+        };
+        let preset = adjuster.apply(preset);
+        assert!((preset.initial_price - 1.1).abs() < 1e-9);
+        assert!((preset.usage_coeffs["golem.usage.cpu_sec"] - 0.55).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod dynamic_pricing;
 pub mod negotiator;
 pub mod presets;
 pub mod provider_market;
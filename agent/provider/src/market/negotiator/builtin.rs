@@ -4,6 +4,7 @@ pub mod demand_validation;
 pub mod expiration;
 pub mod manifest;
 pub mod max_agreements;
+pub mod max_agreements_per_requestor;
 pub mod note_interval;
 pub mod payment_timeout;
 pub mod price;
@@ -11,6 +12,7 @@ pub mod price;
 pub use expiration::LimitExpiration;
 pub use manifest::ManifestSignature;
 pub use max_agreements::MaxAgreements;
+pub use max_agreements_per_requestor::MaxAgreementsPerRequestor;
 pub use note_interval::DebitNoteInterval;
 pub use payment_timeout::PaymentTimeout;
 pub use price::PriceNego;
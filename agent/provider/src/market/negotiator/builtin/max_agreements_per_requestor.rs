@@ -0,0 +1,178 @@
+use anyhow::bail;
+use std::collections::HashMap;
+
+use ya_client_model::market::proposal::State;
+use ya_client_model::NodeId;
+
+use crate::market::negotiator::factory::LimitRequestorAgreementsNegotiatorConfig;
+use crate::market::negotiator::{
+    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+};
+
+/// Negotiator that can limit number of running agreements with a single Requestor.
+pub struct MaxAgreementsPerRequestor {
+    active_agreements: HashMap<String, NodeId>,
+    max_agreements: Option<u32>,
+    /// Requestor of the last Agreement, which passed negotiations. `on_agreement_approved`
+    /// doesn't get Agreement content, so we must remember who we are approving.
+    pending_requestor: Option<NodeId>,
+}
+
+impl MaxAgreementsPerRequestor {
+    pub fn new(config: &LimitRequestorAgreementsNegotiatorConfig) -> MaxAgreementsPerRequestor {
+        MaxAgreementsPerRequestor {
+            active_agreements: HashMap::new(),
+            max_agreements: config.max_agreements_per_requestor,
+            pending_requestor: None,
+        }
+    }
+
+    fn count(&self, requestor: &NodeId) -> usize {
+        self.active_agreements
+            .values()
+            .filter(|id| *id == requestor)
+            .count()
+    }
+
+    pub fn has_free_slot(&self, requestor: &NodeId) -> bool {
+        match self.max_agreements {
+            Some(max) => self.count(requestor) < max as usize,
+            None => true,
+        }
+    }
+}
+
+impl NegotiatorComponent for MaxAgreementsPerRequestor {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        if self.has_free_slot(&demand.issuer) {
+            // Proposals extracted from Agreement are in `Accepted` state.
+            if demand.state == State::Accepted {
+                self.pending_requestor = Some(demand.issuer);
+            }
+            Ok(NegotiationResult::Ready { offer })
+        } else {
+            log::info!(
+                "'MaxAgreementsPerRequestor' negotiator: Reject proposal [{}] from Requestor [{}] due to limit.",
+                demand.id,
+                demand.issuer,
+            );
+            Ok(NegotiationResult::Reject {
+                message: format!(
+                    "No capacity available. Reached Agreements limit per Requestor: {}",
+                    self.max_agreements.unwrap_or_default()
+                ),
+                is_final: false,
+            })
+        }
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        agreement_id: &str,
+        _result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        self.active_agreements.remove(agreement_id);
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()> {
+        let requestor = match self.pending_requestor.take() {
+            Some(requestor) => requestor,
+            None => bail!(
+                "Agreement [{}] approved without negotiations.",
+                agreement_id
+            ),
+        };
+
+        let free_slot = self.has_free_slot(&requestor);
+        self.active_agreements
+            .insert(agreement_id.to_string(), requestor);
+
+        if !free_slot {
+            bail!(
+                "Agreement [{}] approved despite not available capacity for Requestor [{}].",
+                agreement_id,
+                requestor
+            )
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ya_agreement_utils::OfferTemplate;
+
+    fn proposal(issuer: NodeId, state: State) -> ProposalView {
+        ProposalView {
+            content: OfferTemplate::default(),
+            id: "proposalId".to_string(),
+            issuer,
+            state,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn approve(
+        negotiator: &mut MaxAgreementsPerRequestor,
+        requestor: NodeId,
+        agreement_id: &str,
+    ) -> bool {
+        let demand = proposal(requestor, State::Accepted);
+        let offer = proposal(NodeId::default(), State::Accepted);
+        match negotiator.negotiate_step(&demand, offer).unwrap() {
+            NegotiationResult::Ready { .. } => {
+                negotiator.on_agreement_approved(agreement_id).unwrap();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Negotiator rejects Requestor, who reached the limit, but accepts others.
+    #[test]
+    fn test_limit_per_requestor() {
+        let config = LimitRequestorAgreementsNegotiatorConfig {
+            max_agreements_per_requestor: Some(1),
+        };
+        let mut negotiator = MaxAgreementsPerRequestor::new(&config);
+        let first: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let second: NodeId = "0x0000000000000000000000000000000000000002"
+            .parse()
+            .unwrap();
+
+        assert!(approve(&mut negotiator, first, "agreement-1"));
+        assert!(!approve(&mut negotiator, first, "agreement-2"));
+        assert!(approve(&mut negotiator, second, "agreement-3"));
+
+        negotiator
+            .on_agreement_terminated("agreement-1", &AgreementResult::ClosedByUs)
+            .unwrap();
+        assert!(approve(&mut negotiator, first, "agreement-4"));
+    }
+
+    /// Without configured limit all Agreements are accepted.
+    #[test]
+    fn test_no_limit() {
+        let config = LimitRequestorAgreementsNegotiatorConfig {
+            max_agreements_per_requestor: None,
+        };
+        let mut negotiator = MaxAgreementsPerRequestor::new(&config);
+
+        for i in 0..5 {
+            assert!(approve(
+                &mut negotiator,
+                NodeId::default(),
+                &format!("agreement-{}", i)
+            ));
+        }
+    }
+}
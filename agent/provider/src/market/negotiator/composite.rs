@@ -9,7 +9,8 @@ use ya_client::model::market::NewOffer;
 use ya_client_model::market::proposal::State;
 
 use super::builtin::{
-    DebitNoteInterval, LimitExpiration, ManifestSignature, MaxAgreements, PaymentTimeout,
+    DebitNoteInterval, LimitExpiration, ManifestSignature, MaxAgreements,
+    MaxAgreementsPerRequestor, PaymentTimeout,
 };
 use super::common::{offer_definition_to_offer, AgreementResponse, Negotiator, ProposalResponse};
 use super::{NegotiationResult, NegotiatorsPack};
@@ -53,6 +54,12 @@ impl CompositeNegotiator {
                 "LimitAgreements",
                 Box::new(MaxAgreements::new(&config.limit_agreements_config)),
             )
+            .add_component(
+                "LimitRequestorAgreements",
+                Box::new(MaxAgreementsPerRequestor::new(
+                    &config.limit_requestor_agreements_config,
+                )),
+            )
             .add_component(
                 "LimitExpiration",
                 Box::new(LimitExpiration::new(&config.expire_agreements_config)?),
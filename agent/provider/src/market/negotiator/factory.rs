@@ -25,6 +25,15 @@ pub struct LimitAgreementsNegotiatorConfig {
     pub max_simultaneous_agreements: u32,
 }
 
+/// Configuration for LimitRequestorAgreements Negotiator.
+#[derive(StructOpt, Clone, Debug)]
+pub struct LimitRequestorAgreementsNegotiatorConfig {
+    /// Maximal number of simultaneous Agreements with a single Requestor.
+    /// Unlimited if not set.
+    #[structopt(long, env)]
+    pub max_agreements_per_requestor: Option<u32>,
+}
+
 /// Configuration for LimitAgreements Negotiator.
 #[derive(StructOpt, Clone, Debug)]
 pub struct AgreementExpirationNegotiatorConfig {
@@ -70,6 +79,8 @@ pub struct CompositeNegotiatorConfig {
     #[structopt(flatten)]
     pub limit_agreements_config: LimitAgreementsNegotiatorConfig,
     #[structopt(flatten)]
+    pub limit_requestor_agreements_config: LimitRequestorAgreementsNegotiatorConfig,
+    #[structopt(flatten)]
     pub expire_agreements_config: AgreementExpirationNegotiatorConfig,
     #[structopt(flatten)]
     pub debit_note_interval_config: DebitNoteIntervalConfig,
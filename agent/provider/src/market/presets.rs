@@ -5,6 +5,7 @@ use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Result};
+use semver::VersionReq;
 use serde::{Deserialize, Serialize};
 use tokio::sync::watch;
 
@@ -19,6 +20,9 @@ use crate::startup_config::FileMonitor;
 pub struct Preset {
     pub name: String,
     pub exeunit_name: String,
+    /// Pins ExeUnit version. The most recent version is used when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exeunit_version: Option<VersionReq>,
     pub pricing_model: String,
     pub initial_price: f64,
     // It's important that all values are sorted, so that other tools can easily detect changes.
@@ -224,6 +228,7 @@ impl Default for Preset {
             name: "default".to_string(),
             initial_price: 0.0,
             exeunit_name: "wasmtime".to_string(),
+            exeunit_version: None,
             pricing_model: "linear".to_string(),
             usage_coeffs,
         }
@@ -234,6 +239,7 @@ impl PartialEq for Preset {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
             && self.exeunit_name == other.exeunit_name
+            && self.exeunit_version == other.exeunit_version
             && self.pricing_model == other.pricing_model
             && self.usage_coeffs == other.usage_coeffs
     }
@@ -266,6 +272,9 @@ fn display_preset(
         preset.exeunit_name,
         width = align
     )?;
+    if let Some(version) = &preset.exeunit_version {
+        writeln!(f, "{:width$}{}", "ExeUnit version:", version, width = align)?;
+    }
     writeln!(
         f,
         "{:width$}{}",
@@ -275,7 +284,9 @@ fn display_preset(
     )?;
     writeln!(f, "Coefficients:")?;
 
-    let exe_unit = registry.find_exeunit(&preset.exeunit_name).ok();
+    let exe_unit = registry
+        .find_exeunit_version(&preset.exeunit_name, preset.exeunit_version.as_ref())
+        .ok();
 
     for (name, coeff) in preset.usage_coeffs.iter() {
         let price_desc = exe_unit
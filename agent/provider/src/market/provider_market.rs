@@ -12,7 +12,7 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tokio::time::timeout;
 
-use ya_agreement_utils::{AgreementView, OfferDefinition};
+use ya_agreement_utils::{AgreementView, OfferDefinition, ProposalView};
 use ya_client::market::MarketProviderApi;
 use ya_client::model::market::agreement_event::AgreementEventType;
 use ya_client::model::market::proposal::State;
@@ -283,6 +283,39 @@ async fn dispatch_event(
     }
 }
 
+/// Logs what the Requestor altered since its previous Demand in the negotiation.
+/// Our counter-proposal, which the Demand responds to, points to that previous Demand.
+async fn log_demand_changes(
+    ctx: &AsyncCtx,
+    subscription: &Subscription,
+    our_proposal: &Proposal,
+    demand: &Proposal,
+) {
+    let Some(prev_demand_id) = &our_proposal.prev_proposal_id else {
+        return;
+    };
+    let prev_demand = match ctx.api.get_proposal(&subscription.id, prev_demand_id).await {
+        Ok(prev_demand) => prev_demand,
+        Err(e) => {
+            log::debug!("Failed to get previous Demand [{prev_demand_id}]. {e}");
+            return;
+        }
+    };
+    match (
+        ProposalView::try_from(&prev_demand),
+        ProposalView::try_from(demand),
+    ) {
+        (Ok(prev), Ok(current)) => log::debug!(
+            "Requestor [{}] changes in Demand [{}] relative to [{}]: {}",
+            demand.issuer_id,
+            demand.proposal_id,
+            prev_demand_id,
+            prev.diff(&current)
+        ),
+        (Err(e), _) | (_, Err(e)) => log::debug!("Can't compare Requestor Demands. {e}"),
+    }
+}
+
 async fn process_proposal(
     ctx: AsyncCtx,
     subscription: Subscription,
@@ -322,6 +355,10 @@ async fn process_proposal(
         },
     };
 
+    if log::log_enabled!(log::Level::Debug) {
+        log_demand_changes(&ctx, &subscription, &prev_proposal, demand).await;
+    }
+
     let action = ctx
         .negotiator
         .react_to_proposal(prev_proposal, demand.clone())
@@ -4,10 +4,12 @@ use futures::{FutureExt, StreamExt, TryFutureExt};
 use ya_client::net::NetApi;
 use ya_core_model::NodeId;
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
 use tokio_stream::wrappers::WatchStream;
 
 use ya_agreement_utils::agreement::TypedArrayPointer;
@@ -20,15 +22,19 @@ use ya_manifest_utils::{manifest, Feature};
 use crate::config::globals::GlobalsState;
 use crate::dir::clean_provider_dir;
 use crate::events::Event;
-use crate::execution::{ExeUnitDesc, GetExeUnit, GetOfferTemplates, TaskRunner, UpdateActivity};
+use crate::execution::{
+    ExeUnitDesc, ExeUnitsRegistry, GetExeUnit, GetOfferTemplates, TaskRunner, UpdateActivity,
+    UpdateRegistry,
+};
 use crate::hardware;
+use crate::market::dynamic_pricing::PriceAdjuster;
 use crate::market::provider_market::{OfferKind, Shutdown as MarketShutdown, Unsubscribe};
 use crate::market::{CreateOffer, Preset, PresetManager, ProviderMarket};
 use crate::payments::{AccountView, LinearPricingOffer, Payments, PricingOffer};
 use crate::rules::RulesManager;
 use crate::startup_config::{FileMonitor, NodeConfig, PaymentPlatform, ProviderConfig, RunConfig};
 use crate::tasks::task_manager::{
-    InitializeTaskManager, Shutdown as TaskManagerShutdown, TaskManager,
+    GetActiveAgreements, InitializeTaskManager, Shutdown as TaskManagerShutdown, TaskManager,
 };
 
 struct GlobalsManager {
@@ -65,6 +71,66 @@ impl GlobalsManager {
     }
 }
 
+/// Reloads ExeUnit descriptors when files in plugins directory change.
+struct ExeUnitsManager {
+    monitor: Option<FileMonitor>,
+    sender: Option<watch::Sender<Event>>,
+    receiver: watch::Receiver<Event>,
+}
+
+impl ExeUnitsManager {
+    fn new() -> Self {
+        let (sender, receiver) = watch::channel(Event::Initialized);
+        Self {
+            monitor: None,
+            sender: Some(sender),
+            receiver,
+        }
+    }
+
+    fn spawn_monitor(
+        &mut self,
+        exe_unit_path: &Path,
+        registry: &ExeUnitsRegistry,
+    ) -> anyhow::Result<()> {
+        let plugins_dir = exe_unit_path
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid ExeUnits path: {}", exe_unit_path.display()))?;
+        let tx = self.sender.take().unwrap();
+        let pattern = exe_unit_path.to_path_buf();
+        let state = Mutex::new(serde_json::to_value(registry.list())?);
+
+        let handler = move |_| {
+            let mut registry = ExeUnitsRegistry::default();
+            if let Err(e) = registry.register_from_file_pattern(&pattern) {
+                log::warn!("Error reloading ExeUnits from {:?}: {:?}", pattern, e);
+                return;
+            }
+            registry.retain_valid();
+
+            let snapshot = serde_json::to_value(registry.list()).unwrap_or_default();
+            let mut state = state.lock().unwrap();
+            match *state == snapshot {
+                true => log::info!("ExeUnits configuration unchanged"),
+                false => {
+                    *state = snapshot;
+                    tx.send(Event::ExeUnitsChanged(registry))
+                        .unwrap_or_default();
+                }
+            }
+        };
+
+        let monitor = FileMonitor::spawn(plugins_dir, FileMonitor::on_modified(handler))?;
+        self.monitor = Some(monitor);
+        Ok(())
+    }
+
+    #[inline]
+    fn event_receiver(&self) -> watch::Receiver<Event> {
+        self.receiver.clone()
+    }
+}
+
 #[derive(Clone)]
 pub struct AgentNegotiatorsConfig {
     pub rules_manager: RulesManager,
@@ -77,6 +143,8 @@ pub struct ProviderAgent {
     task_manager: Addr<TaskManager>,
     presets: PresetManager,
     hardware: hardware::Manager,
+    exe_units: ExeUnitsManager,
+    data_dir: PathBuf,
     account: NodeId,
     log_handler: LoggerHandle,
     networks: Vec<PaymentPlatform>,
@@ -84,6 +152,8 @@ pub struct ProviderAgent {
     keystore_monitor: FileMonitor,
     whitelist_monitor: FileMonitor,
     net_api: NetApi,
+    pricing: Option<PriceAdjuster>,
+    max_agreements: usize,
 }
 
 impl ProviderAgent {
@@ -118,8 +188,10 @@ impl ProviderAgent {
         log::info!("Loading payment accounts...");
         let account = api.identity.me().await?.identity;
         log::info!("Payment account: {:#?}", account);
-        let registry = config.registry()?;
-        registry.validate()?;
+        let mut registry = config.registry()?;
+        if let Some(errors) = registry.retain_valid() {
+            log::warn!("Offers won't be created for invalid ExeUnits:\n{}", errors);
+        }
         registry
             .test_runtimes(&data_dir)
             .await
@@ -164,14 +236,37 @@ impl ProviderAgent {
         presets.spawn_monitor(&config.presets_file)?;
         let mut hardware = hardware::Manager::try_new(&config)?;
         hardware.spawn_monitor(&config.hardware_file)?;
+        let mut exe_units = ExeUnitsManager::new();
+        exe_units.spawn_monitor(&config.exe_unit_path, &registry)?;
         let (rulestore_monitor, keystore_monitor, whitelist_monitor) =
             rules_manager.spawn_file_monitors()?;
 
         let agent_negotiators_cfg = AgentNegotiatorsConfig { rules_manager };
 
+        let pricing = match args.pricing.dynamic_pricing {
+            true => {
+                args.pricing.validate()?;
+                Some(PriceAdjuster::new(args.pricing))
+            }
+            false => None,
+        };
+        let max_agreements = args
+            .market
+            .negotiator_config
+            .composite_config
+            .limit_agreements_config
+            .max_simultaneous_agreements as usize;
+
         let market = ProviderMarket::new(api.market, args.market, agent_negotiators_cfg).start();
         let payments = Payments::new(api.activity.clone(), api.payment, args.payment).start();
-        let runner = TaskRunner::new(api.activity, args.runner, registry, data_dir)?.start();
+        let runner = TaskRunner::new(
+            api.activity,
+            args.runner,
+            registry,
+            hardware.allocator(),
+            &data_dir,
+        )?
+        .start();
         let task_manager =
             TaskManager::new(market.clone(), runner.clone(), payments, args.tasks)?.start();
         let net_api = api.net;
@@ -183,6 +278,8 @@ impl ProviderAgent {
             task_manager,
             presets,
             hardware,
+            exe_units,
+            data_dir,
             account,
             log_handler,
             networks,
@@ -190,13 +287,15 @@ impl ProviderAgent {
             keystore_monitor,
             whitelist_monitor,
             net_api,
+            pricing,
+            max_agreements,
         })
     }
 
     async fn create_offers(
         presets: Vec<Preset>,
         node_info: NodeInfo,
-        inf_node_info: InfNodeInfo,
+        inf_node_infos: HashMap<String, InfNodeInfo>,
         runner: Addr<TaskRunner>,
         market: Addr<ProviderMarket>,
         accounts: Vec<AccountView>,
@@ -210,13 +309,24 @@ impl ProviderAgent {
         let offer_templates = runner.send(GetOfferTemplates(presets.clone())).await??;
 
         for preset in presets {
-            let offer: OfferTemplate = offer_templates
-                .get(&preset.name)
-                .ok_or_else(|| anyhow!("Offer template not found for preset [{}]", preset.name))?
-                .clone();
+            // Presets with ExeUnits missing from registry are skipped by `GetOfferTemplates`.
+            let offer: OfferTemplate = match offer_templates.get(&preset.name) {
+                Some(offer) => offer.clone(),
+                None => {
+                    log::warn!("Offer template not found for preset [{}]", preset.name);
+                    continue;
+                }
+            };
             let exeunit_name = preset.exeunit_name.clone();
+            let inf_node_info = inf_node_infos
+                .get(&exeunit_name)
+                .cloned()
+                .unwrap_or_default();
             let exeunit_desc = runner
-                .send(GetExeUnit { name: exeunit_name })
+                .send(GetExeUnit {
+                    name: exeunit_name,
+                    version: preset.exeunit_version.clone(),
+                })
                 .await?
                 .map_err(|error| {
                     anyhow!(
@@ -228,7 +338,7 @@ impl ProviderAgent {
 
             let offer = Self::build_offer(
                 node_info.clone(),
-                inf_node_info.clone(),
+                inf_node_info,
                 &accounts,
                 preset,
                 offer,
@@ -314,6 +424,43 @@ impl ProviderAgent {
             .support_multi_activity(true))
     }
 
+    fn sample_utilization(&mut self, ctx: &mut Context<Self>) {
+        let capacity = self.max_agreements;
+        self.task_manager
+            .send(GetActiveAgreements)
+            .into_actor(self)
+            .map(move |result, myself, _| match result {
+                Ok(active) => {
+                    if let Some(pricing) = myself.pricing.as_mut() {
+                        pricing.sample(active, capacity);
+                    }
+                }
+                Err(e) => log::warn!("Unable to sample utilization: {}", e),
+            })
+            .spawn(ctx);
+    }
+
+    fn adjust_prices(&mut self, ctx: &mut Context<Self>) {
+        if self.pricing.as_mut().and_then(|p| p.adjust()).is_none() {
+            return;
+        }
+
+        let market = self.market.clone();
+        let agent = ctx.address();
+        async move {
+            let _ = market
+                .send(Unsubscribe(OfferKind::Any))
+                .map_err(|e| log::error!("Cannot unsubscribe offers: {}", e))
+                .await;
+            let _ = agent
+                .send(CreateOffers(OfferKind::Any))
+                .map_err(|e| log::error!("Cannot create offers: {}", e))
+                .await;
+        }
+        .into_actor(self)
+        .spawn(ctx);
+    }
+
     fn accounts(&self, networks: &[PaymentPlatform]) -> anyhow::Result<Vec<AccountView>> {
         let globals = self.globals.get_state();
 
@@ -395,6 +542,12 @@ impl Actor for ProviderAgent {
     fn started(&mut self, ctx: &mut Context<Self>) {
         let runner = self.runner.clone();
         ctx.spawn(process_activity_events(runner).into_actor(self));
+
+        if let Some(pricing) = self.pricing.as_ref() {
+            let config = pricing.config();
+            ctx.run_interval(config.pricing_sample_interval, Self::sample_utilization);
+            ctx.run_interval(config.pricing_adjustment_interval, Self::adjust_prices);
+        }
     }
 }
 
@@ -403,12 +556,15 @@ impl Handler<Initialize> for ProviderAgent {
 
     fn handle(&mut self, _: Initialize, ctx: &mut Context<Self>) -> Self::Result {
         let market = self.market.clone();
+        let runner = self.runner.clone();
         let agent = ctx.address();
         let preset_state = self.presets.state.clone();
+        let data_dir = self.data_dir.clone();
 
         let rx = futures::stream::select_all(vec![
             WatchStream::new(self.hardware.event_receiver()),
             WatchStream::new(self.presets.event_receiver()),
+            WatchStream::new(self.exe_units.event_receiver()),
         ]);
 
         tokio::task::spawn_local(async move {
@@ -457,6 +613,24 @@ impl Handler<Initialize> for ProviderAgent {
                                 .await;
                         }
                     }
+                    Event::ExeUnitsChanged(registry) => {
+                        if let Err(e) = registry.test_runtimes(&data_dir).await {
+                            log::error!("Runtimes test failed, keeping previous ExeUnits: {e}");
+                            return;
+                        }
+                        if let Err(e) = runner.send(UpdateRegistry(registry)).await {
+                            log::error!("Cannot update ExeUnits registry: {}", e);
+                            return;
+                        }
+                        let _ = market
+                            .send(Unsubscribe(OfferKind::Any))
+                            .map_err(|e| log::error!("Cannot unsubscribe offers: {}", e))
+                            .await;
+                        let _ = agent
+                            .send(CreateOffers(OfferKind::Any))
+                            .map_err(|e| log::error!("Cannot create offers: {}", e))
+                            .await;
+                    }
                     _ => (),
                 }
             })
@@ -506,7 +680,6 @@ impl Handler<CreateOffers> for ProviderAgent {
             Ok(acc) => acc,
             Err(e) => return Box::pin(async { Err(e) }),
         };
-        let inf_node_info = InfNodeInfo::from(self.hardware.capped());
         let preset_names = match msg.0 {
             OfferKind::Any => self.presets.active(),
             OfferKind::WithPresets(names) => names,
@@ -515,13 +688,38 @@ impl Handler<CreateOffers> for ProviderAgent {
                 vec![]
             }
         };
-        let presets = self.presets.list_matching(&preset_names);
+        let presets = self.presets.list_matching(&preset_names).map(|presets| {
+            presets
+                .into_iter()
+                .map(|preset| match &self.pricing {
+                    Some(pricing) => pricing.apply(preset),
+                    None => preset,
+                })
+                .collect::<Vec<_>>()
+        });
+        // runtimes with a dedicated hardware profile advertise their own partition
+        let inf_node_infos = presets
+            .iter()
+            .flatten()
+            .map(|preset| {
+                let resources = self.hardware.capped_for(&preset.exeunit_name);
+                (preset.exeunit_name.clone(), InfNodeInfo::from(resources))
+            })
+            .collect::<HashMap<_, _>>();
         let globals = self.globals.get_state();
         let net_api = self.net_api.clone();
 
         async move {
             let node_info = Self::build_node_info(globals, net_api).await?;
-            Self::create_offers(presets?, node_info, inf_node_info, runner, market, accounts).await
+            Self::create_offers(
+                presets?,
+                node_info,
+                inf_node_infos,
+                runner,
+                market,
+                accounts,
+            )
+            .await
         }
         .boxed_local()
     }
@@ -628,6 +826,8 @@ mod tests {
             supervisor_path: Default::default(),
             extra_args: Default::default(),
             runtime_path: None,
+            supervisor_sha256: None,
+            runtime_sha256: None,
             properties: Default::default(),
             config: None,
         };
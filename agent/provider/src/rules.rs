@@ -154,6 +154,7 @@ impl RulesManager {
                 CertRule {
                     mode: mode.clone(),
                     description: "".into(),
+                    url_patterns: false,
                 },
             );
         log::trace!("Added Audited-Payload rule for cert_id: {cert_id} with mode: {mode}");
@@ -188,7 +189,7 @@ impl RulesManager {
             .collect()
     }
 
-    pub fn set_partner_mode(&self, cert_id: String, mode: Mode) -> Result<()> {
+    pub fn set_partner_mode(&self, cert_id: String, mode: Mode, url_patterns: bool) -> Result<()> {
         let cert_id = {
             let certs: Vec<Cert> = self
                 .keystore
@@ -225,6 +226,7 @@ impl RulesManager {
                 CertRule {
                     mode: mode.clone(),
                     description: "".into(),
+                    url_patterns,
                 },
             );
         log::trace!("Added Partner rule for cert_id: {cert_id} with mode: {mode}");
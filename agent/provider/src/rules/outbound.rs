@@ -12,6 +12,7 @@ use url::Url;
 use ya_client_model::NodeId;
 use ya_manifest_utils::keystore::Keystore;
 use ya_manifest_utils::matching::domain::DomainWhitelistState;
+use ya_manifest_utils::matching::url_pattern::UrlPattern;
 use ya_manifest_utils::matching::Matcher;
 use ya_manifest_utils::{CompositeKeystore, OutboundAccess};
 
@@ -30,6 +31,10 @@ pub struct OutboundConfig {
 pub struct CertRule {
     pub mode: Mode,
     pub description: String,
+    /// Partner rule only. Matches URLs permitted by the certificate as patterns
+    /// (scheme, host, port and path prefix ending with `*`), instead of exactly.
+    #[serde(default, rename = "url-patterns", skip_serializing_if = "Not::not")]
+    pub url_patterns: bool,
 }
 
 #[derive(
@@ -180,11 +185,8 @@ impl OutboundRules {
             ));
         }
 
-        self::verify_golem_permissions(&node_descriptor.permissions, access)
-            .map_err(|e| anyhow!("Partner {e}"))?;
-
         for cert_id in node_descriptor.certificate_chain_fingerprints.iter() {
-            if let Some(rule) = self
+            let rule = self
                 .rulestore
                 .config
                 .read()
@@ -192,7 +194,15 @@ impl OutboundRules {
                 .outbound
                 .partner
                 .get(cert_id)
-            {
+                .cloned();
+            if let Some(rule) = rule {
+                self::verify_golem_permissions(
+                    &node_descriptor.permissions,
+                    access,
+                    rule.url_patterns,
+                )
+                .map_err(|e| anyhow!("Partner {e}"))?;
+
                 return self
                     .check_mode(&rule.mode, access)
                     .map_err(|e| anyhow!("Partner {e}"));
@@ -256,6 +266,7 @@ fn extract_rejected_message(rules_checks: Vec<anyhow::Error>) -> String {
 fn verify_golem_permissions(
     cert_permissions: &Permissions,
     outbound_access: &OutboundAccess,
+    url_patterns: bool,
 ) -> anyhow::Result<()> {
     match cert_permissions {
         Permissions::All => Ok(()),
@@ -270,7 +281,13 @@ fn verify_golem_permissions(
                     match outbound_access {
                         OutboundAccess::Urls(requested_urls) => {
                             for requested_url in requested_urls {
-                                if permitted_urls.contains(requested_url).not() {
+                                let permitted = match url_patterns {
+                                    true => permitted_urls
+                                        .iter()
+                                        .any(|url| UrlPattern::from(url).matches(requested_url)),
+                                    false => permitted_urls.contains(requested_url),
+                                };
+                                if permitted.not() {
                                     anyhow::bail!("Partner rule forbidden url requested: {requested_url}");
                                 }
                             }
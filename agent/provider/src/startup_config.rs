@@ -16,6 +16,7 @@ use ya_client::{cli::ApiOpts, model::node_id::NodeId};
 use ya_core_model::payment::local::{DriverName, NetworkName, DEFAULT_PAYMENT_DRIVER};
 use ya_utils_path::data_dir::DataDir;
 
+use crate::cli::cache::CacheConfig;
 use crate::cli::clean::CleanConfig;
 use crate::cli::config::ConfigConfig;
 use crate::cli::exe_unit::ExeUnitsConfig;
@@ -28,6 +29,7 @@ use crate::cli::whitelist::WhitelistConfig;
 pub(crate) use crate::config::globals::GLOBALS_JSON;
 use crate::execution::{ExeUnitsRegistry, TaskRunnerConfig};
 use crate::market::config::MarketConfig;
+use crate::market::dynamic_pricing::DynamicPricingConfig;
 use crate::payments::PaymentsConfig;
 use crate::tasks::config::TaskConfig;
 
@@ -245,6 +247,8 @@ pub struct RunConfig {
     pub payment: PaymentsConfig,
     #[structopt(flatten)]
     pub tasks: TaskConfig,
+    #[structopt(flatten)]
+    pub pricing: DynamicPricingConfig,
     ///changes log level from info to debug
     #[structopt(long)]
     pub debug: bool,
@@ -256,6 +260,9 @@ pub struct PresetNoInteractive {
     pub preset_name: Option<String>,
     #[structopt(long)]
     pub exe_unit: Option<String>,
+    /// ExeUnit version requirement, e.g. `=0.3.0` or `^0.3`
+    #[structopt(long)]
+    pub exe_unit_version: Option<semver::VersionReq>,
     #[structopt(long)]
     pub pricing: Option<String>,
     #[structopt(long, parse(try_from_str = parse_key_val))]
@@ -308,6 +315,8 @@ pub enum Commands {
     Whitelist(WhitelistConfig),
     /// Free up disk space by removing old exe-unit files
     Clean(CleanConfig),
+    /// Manage image cache shared by ExeUnits
+    Cache(CacheConfig),
     /// Manage Rule config
     Rule(RuleCommand),
 }
@@ -4,6 +4,6 @@ pub mod task_manager;
 mod task_state;
 
 pub use task_manager::{
-    AgreementBroken, AgreementClosed, BreakAgreement, CloseAgreement, InitializeTaskManager,
-    TaskManager,
+    AgreementBroken, AgreementClosed, BreakAgreement, CloseAgreement, GetActiveAgreements,
+    InitializeTaskManager, TaskManager,
 };
@@ -91,6 +91,11 @@ pub struct AgreementClosed {
 // TaskManager messages not related to agreements
 // =========================================== //
 
+/// Returns number of Agreements, which are not finalized.
+#[derive(Message)]
+#[rtype(result = "usize")]
+pub struct GetActiveAgreements;
+
 /// Initialize TaskManager.
 #[derive(Message)]
 #[rtype(result = "Result<()>")]
@@ -575,6 +580,14 @@ impl Handler<CloseAgreement> for TaskManager {
     }
 }
 
+impl Handler<GetActiveAgreements> for TaskManager {
+    type Result = usize;
+
+    fn handle(&mut self, _msg: GetActiveAgreements, _ctx: &mut Context<Self>) -> Self::Result {
+        self.tasks.list_active().len()
+    }
+}
+
 impl Handler<Shutdown> for TaskManager {
     type Result = ResponseFuture<std::result::Result<(), Error>>;
 
@@ -274,6 +274,20 @@ fn manifest_negotiator_test_with_valid_payload_signature(
     Some("Partner rule is disabled"); // error msg
     "Rejected because valid descriptor is not trusted"
 )]
+#[test_case(
+    r#""cb16a2ed213c1cf7e14faa7cf05743bc145b8555ec2eedb6b12ba0d31d17846d2ed4341b048f2e43b1ca5195a347bfeb0cd663c9e6002a4adb7cc7385112d3cc": { "mode": "all", "description": ""}"#,
+    &["https://domain.com/api/v1"], // compManifest.net.inet.out.urls
+    "node-descriptor-happy-path.signed.json",
+    Some("Partner Partner rule forbidden url requested: https://domain.com/api/v1"); // error msg
+    "Rejected because descriptor urls are matched exactly by default"
+)]
+#[test_case(
+    r#""cb16a2ed213c1cf7e14faa7cf05743bc145b8555ec2eedb6b12ba0d31d17846d2ed4341b048f2e43b1ca5195a347bfeb0cd663c9e6002a4adb7cc7385112d3cc": { "mode": "all", "description": "", "url-patterns": true}"#,
+    &["https://domain.com/api/v1"], // compManifest.net.inet.out.urls
+    "node-descriptor-happy-path.signed.json",
+    None; // error msg
+    "Accepted because descriptor urls are matched as patterns"
+)]
 #[serial]
 fn manifest_negotiator_test_with_node_identity(
     partner_rule: &str,
@@ -4,12 +4,22 @@ use actix_web::Scope;
 use crate::TrackerRef;
 
 use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::openapi::{ApiScope, ScopeSpec};
 use ya_service_api_web::scope::ExtendableScope;
 
 pub fn web_scope(db: &DbExecutor, tracker: TrackerRef) -> Scope {
-    actix_web::web::scope(crate::ACTIVITY_API_PATH)
+    api_scope()
+        .into_scope()
         .app_data(Data::new(db.clone()))
         .app_data(Data::new(tracker))
+}
+
+pub fn api_spec() -> ScopeSpec {
+    api_scope().into_spec()
+}
+
+fn api_scope() -> ApiScope {
+    ApiScope::new("activity", crate::ACTIVITY_API_PATH)
         .extend(common::extend_web_scope)
         .extend(crate::provider::extend_web_scope)
         .extend(crate::requestor::control::extend_web_scope)
@@ -20,31 +30,60 @@ pub fn web_scope(db: &DbExecutor, tracker: TrackerRef) -> Scope {
 /// Common operations for both sides: Provider and Requestor
 mod common {
     use actix_web::{web, HttpResponse, Responder};
+    use futures::future::Either;
     use futures::prelude::*;
+    use std::time::Duration;
+    use tokio_stream::wrappers::IntervalStream;
 
+    use ya_client_model::activity::{ActivityState, ActivityUsage};
     use ya_client_model::market::Role;
     use ya_core_model::{activity, NodeId};
     use ya_persistence::executor::DbExecutor;
     use ya_service_api_web::middleware::Identity;
-    use ya_service_bus::{timeout::IntoTimeoutFuture, RpcEndpoint};
+    use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
+    use ya_service_bus::{timeout::IntoTimeoutFuture, RpcEndpoint, RpcMessage};
 
     use crate::common::*;
     use crate::error::Error;
-    use crate::tracker::TrackingEvent;
+    use crate::tracker::{ActivityEvent, StateEvent, TrackingEvent};
     use crate::TrackerRef;
     use actix_web::http::header;
     use actix_web::web::Json;
 
-    pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
+    /// Server-Sent Event name of ACL violations
+    const ACL_VIOLATION_EVENT: &str = "ActivityAclViolation";
+
+    pub fn extend_web_scope(scope: ApiScope) -> ApiScope {
         scope
-            .service(get_events)
-            .service(get_activities_for_agreement_web)
-            .service(get_activity_agreement_web)
-            .service(get_activity_state_web)
-            .service(get_activity_usage_web)
+            .route(
+                Operation::get("/_monitor", "getMonitorEvents").response(Schema::EventStream),
+                get_events,
+            )
+            .route(
+                Operation::get("/activity", "getActivities").response(Schema::of::<Vec<String>>()),
+                get_activities_for_agreement_web,
+            )
+            .route(
+                Operation::get("/activity/{activity_id}/agreement", "getActivityAgreement")
+                    .response(Schema::of::<String>()),
+                get_activity_agreement_web,
+            )
+            .route(
+                Operation::get("/activity/{activity_id}/state", "getActivityState")
+                    .response(Schema::model::<ActivityState>()),
+                get_activity_state_web,
+            )
+            .route(
+                Operation::get("/activity/{activity_id}/usage", "getActivityUsage")
+                    .response(Schema::model::<ActivityUsage>()),
+                get_activity_usage_web,
+            )
+            .route(
+                Operation::get("/stateEvents", "getStateEvents").response(Schema::EventStream),
+                get_state_events,
+            )
     }
 
-    #[actix_web::get("/activity")]
     async fn get_activities_for_agreement_web(
         db: web::Data<DbExecutor>,
         query: web::Query<QueryAgreement>,
@@ -62,7 +101,6 @@ mod common {
         Ok::<Json<Vec<std::string::String>>, Error>(web::Json(activities))
     }
 
-    #[actix_web::get("/activity/{activity_id}/agreement")]
     async fn get_activity_agreement_web(
         db: web::Data<DbExecutor>,
         path: web::Path<PathActivity>,
@@ -94,7 +132,6 @@ mod common {
             .map(web::Json)
     }
 
-    #[actix_web::get("/activity/{activity_id}/state")]
     async fn get_activity_state_web(
         db: web::Data<DbExecutor>,
         path: web::Path<PathActivity>,
@@ -144,7 +181,6 @@ mod common {
             .map(web::Json)
     }
 
-    #[actix_web::get("/activity/{activity_id}/usage")]
     async fn get_activity_usage_web(
         db: web::Data<DbExecutor>,
         path: web::Path<PathActivity>,
@@ -210,7 +246,95 @@ mod common {
         })
     }
 
-    #[actix_web::get("/_monitor")]
+    #[derive(serde::Deserialize)]
+    struct QueryStateEvents {
+        #[serde(rename = "agreementId")]
+        agreement_id: Option<String>,
+    }
+
+    /// Streams state transitions and ACL violations of the owner's Activities.
+    /// When filtered by Agreement, the stream ends after the first `Terminated` transition.
+    pub(super) fn state_event_stream(
+        stream: tokio::sync::broadcast::Receiver<StateEvent>,
+        owner: NodeId,
+        agreement_id: Option<String>,
+    ) -> impl futures::stream::Stream<Item = Result<web::Bytes, actix_web::Error>> {
+        use tokio::sync::broadcast::error::RecvError;
+
+        futures::stream::unfold(Some((stream, 0u64)), move |state| {
+            let agreement_id = agreement_id.clone();
+            async move {
+                let (mut stream, mut seq) = state?;
+                loop {
+                    let event = match stream.recv().await {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(n)) => {
+                            log::warn!("Activity state event stream lagged by {} events", n);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return None,
+                    };
+                    if event.owner != owner
+                        || matches!(&agreement_id, Some(id) if id != event.event.agreement_id())
+                    {
+                        continue;
+                    }
+
+                    let (name, json) = match &event.event {
+                        ActivityEvent::StateChanged(e) => {
+                            (activity::StateChanged::ID, serde_json::to_string(e))
+                        }
+                        ActivityEvent::AclViolation(e) => {
+                            (ACL_VIOLATION_EVENT, serde_json::to_string(e))
+                        }
+                    };
+                    let line = match json {
+                        Ok(json) => format!("event: {}\ndata: {}\nid: {}\n\n", name, json, seq),
+                        Err(e) => {
+                            let err = actix_web::error::ErrorInternalServerError(e);
+                            return Some((Err(err), None));
+                        }
+                    };
+                    seq += 1;
+                    let next = if agreement_id.is_some() && event.event.is_terminated() {
+                        None
+                    } else {
+                        Some((stream, seq))
+                    };
+                    return Some((Ok(web::Bytes::from(line)), next));
+                }
+            }
+        })
+    }
+
+    /// Streams Activity state transitions as Server-Sent Events.
+    async fn get_state_events(
+        tracker: web::Data<TrackerRef>,
+        query: web::Query<QueryStateEvents>,
+        id: Identity,
+    ) -> impl Responder {
+        let stream = tracker.subscribe_states();
+        let events = state_event_stream(stream, id.identity, query.into_inner().agreement_id)
+            .map(|e| Some(Either::Left(e)))
+            .chain(stream::once(future::ready(None)));
+        let pings = IntervalStream::new(tokio::time::interval(Duration::from_secs(15)))
+            .map(|i| Some(Either::Right(i)));
+
+        HttpResponse::Ok()
+            .append_header((header::CONTENT_TYPE, mime::TEXT_EVENT_STREAM.essence_str()))
+            .append_header((header::CACHE_CONTROL, "no-cache"))
+            .streaming(
+                // Pings stop as soon as the event stream ends
+                stream::select(events, pings)
+                    .take_while(|e| future::ready(e.is_some()))
+                    .filter_map(future::ready)
+                    .map(|e| match e {
+                        Either::Left(r) => r,
+                        Either::Right(_) => Ok(web::Bytes::from_static(b":ping\n")),
+                    }),
+            )
+    }
+
     async fn get_events(tracker: web::Data<TrackerRef>, id: Identity) -> impl Responder {
         let mut tracker = tracker.as_ref().clone();
         let (event, stream) = tracker.subscribe().await.unwrap();
@@ -231,3 +355,42 @@ mod common {
             ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn state_stream_ends_on_terminated() {
+        use futures::StreamExt;
+        use ya_client_model::activity::{ActivityState, State};
+        use ya_core_model::{activity::StateChanged, NodeId};
+
+        use crate::tracker::{ActivityEvent, StateEvent};
+
+        let owner = NodeId::default();
+        let (tx, rx) = tokio::sync::broadcast::channel(16);
+        let event = |state: State| StateEvent {
+            owner,
+            event: ActivityEvent::StateChanged(StateChanged {
+                activity_id: "activity".to_string(),
+                agreement_id: "agreement".to_string(),
+                state: ActivityState {
+                    state: state.into(),
+                    reason: None,
+                    error_message: None,
+                },
+                timestamp: chrono::Utc::now(),
+            }),
+        };
+        for state in [State::Ready, State::Terminated, State::Ready] {
+            tx.send(event(state)).unwrap();
+        }
+
+        let lines: Vec<_> = common::state_event_stream(rx, owner, Some("agreement".into()))
+            .collect()
+            .await;
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.is_ok()));
+    }
+}
@@ -33,6 +33,14 @@ pub struct QueryAgreement {
     pub agreement_id: String,
 }
 
+#[derive(Deserialize)]
+pub struct QueryAgreementTimeout {
+    #[serde(rename = "agreementId")]
+    pub agreement_id: String,
+    #[serde(rename = "timeout", default = "default_query_timeout")]
+    pub timeout: Option<f32>,
+}
+
 #[derive(Deserialize)]
 pub struct PathActivityUrl {
     pub activity_id: String,
@@ -3,6 +3,7 @@ use actix_web::{error::ResponseError, HttpResponse};
 use ya_client_model::ErrorMessage;
 use ya_core_model::activity::RpcMessageError;
 use ya_core_model::market::RpcMessageError as MarketRpcMessageError;
+use ya_service_api_web::error::{self as api, ApiError, ErrorCategory};
 
 use crate::dao::DaoError;
 
@@ -100,26 +101,26 @@ impl From<Error> for RpcMessageError {
     }
 }
 
+impl Error {
+    pub fn api_error(&self) -> ApiError {
+        match self {
+            Error::BadRequest(_) => ApiError::bad_request(api::BAD_REQUEST, self),
+            Error::NotFound(_) => ApiError::not_found(api::NOT_FOUND, self),
+            Error::Forbidden(_) => ApiError::new(ErrorCategory::Forbidden, api::FORBIDDEN, self),
+            Error::Timeout => ApiError::new(ErrorCategory::Timeout, api::TIMEOUT, self),
+            Error::Dao(_) => ApiError::new(ErrorCategory::Internal, "ACTIVITY_DB_ERROR", self),
+            Error::Gsb(_) => ApiError::new(ErrorCategory::Internal, "ACTIVITY_GSB_ERROR", self),
+            Error::Service(_) => ApiError::internal(self),
+        }
+    }
+}
+
 impl ResponseError for Error {
     fn error_response(&self) -> HttpResponse {
-        match self {
-            Error::BadRequest(_) => {
-                HttpResponse::BadRequest().json(ErrorMessage::new(self.to_string()))
-            }
-            Error::NotFound(_) => {
-                HttpResponse::NotFound().json(ErrorMessage::new(self.to_string()))
-            }
-            Error::Forbidden(_) => {
-                HttpResponse::Forbidden().json(ErrorMessage::new(self.to_string()))
-            }
-            Error::Timeout => {
-                HttpResponse::RequestTimeout().json(ErrorMessage::new(self.to_string()))
-            }
-            _ => {
-                let e = self.to_string();
-                log::error!("Activity API server error: {}", e);
-                HttpResponse::InternalServerError().json(ErrorMessage::new(e))
-            }
+        let e = self.api_error();
+        if e.category == ErrorCategory::Internal {
+            log::error!("Activity API server error: {}", self);
         }
+        e.response()
     }
 }
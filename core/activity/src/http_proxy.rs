@@ -1,12 +1,17 @@
+use actix::prelude::*;
 use actix_http::header::HeaderValue;
 use actix_http::{header, StatusCode};
 use actix_web::web::Bytes;
 use actix_web::{web, Either, HttpRequest, HttpResponse, Responder};
-use futures::Stream;
+use actix_web_actors::ws;
+use futures::channel::mpsc;
+use futures::stream::LocalBoxStream;
+use futures::{Stream, StreamExt};
 
 use ya_client_model::market::Role;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Method, Operation, Schema};
 use ya_service_bus::Error;
 
 use crate::common::*;
@@ -14,25 +19,70 @@ use crate::error;
 use ya_core_model::activity;
 use ya_gsb_http_proxy::http_to_gsb::BindingMode::Net;
 use ya_gsb_http_proxy::http_to_gsb::{
-    HttpToGsbProxy, HttpToGsbProxyResponse, HttpToGsbProxyStreamingResponse, NetBindingNodes,
+    HttpToGsbProxy, HttpToGsbProxyResponse, HttpToGsbProxyStreamingResponse, HttpToGsbWebSocket,
+    NetBindingNodes,
 };
+use ya_gsb_http_proxy::message::GsbWsFrame;
 
-pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
-    scope.service(proxy_http_request)
+const PROXY_HTTP_PATH: &str = "/activity/{activity_id}/proxy-http/{url:.*}";
+
+pub fn extend_web_scope(scope: ApiScope) -> ApiScope {
+    scope
+        .route(
+            Operation::get(PROXY_HTTP_PATH, "proxyHttpRequest").response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::post(PROXY_HTTP_PATH, "proxyHttpPostRequest")
+                .body(Schema::Binary)
+                .response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::put(PROXY_HTTP_PATH, "proxyHttpPutRequest")
+                .body(Schema::Binary)
+                .response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::patch(PROXY_HTTP_PATH, "proxyHttpPatchRequest")
+                .body(Schema::Binary)
+                .response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::delete(PROXY_HTTP_PATH, "proxyHttpDeleteRequest").response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::new(Method::Head, PROXY_HTTP_PATH, "proxyHttpHeadRequest"),
+            proxy_http_request,
+        )
+        .route(
+            Operation::new(Method::Options, PROXY_HTTP_PATH, "proxyHttpOptionsRequest")
+                .response(Schema::Binary),
+            proxy_http_request,
+        )
+        .route(
+            Operation::new(Method::Trace, PROXY_HTTP_PATH, "proxyHttpTraceRequest")
+                .response(Schema::Binary),
+            proxy_http_request,
+        )
+        .undescribed_route(
+            PROXY_HTTP_PATH,
+            actix_web::http::Method::CONNECT,
+            proxy_http_request,
+        )
+        .route(
+            Operation::get(
+                "/activity/{activity_id}/proxy-ws/{url:.*}",
+                "proxyWebsocket",
+            )
+            .response(Schema::Binary),
+            proxy_websocket,
+        )
 }
 
-#[actix_web::route(
-    "/activity/{activity_id}/proxy-http/{url:.*}",
-    method = "GET",
-    method = "POST",
-    method = "PUT",
-    method = "DELETE",
-    method = "HEAD",
-    method = "OPTIONS",
-    method = "CONNECT",
-    method = "PATCH",
-    method = "TRACE"
-)]
 async fn proxy_http_request(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivityUrl>,
@@ -65,18 +115,13 @@ async fn proxy_http_request(
         let accept_header = accept_header
             .to_str()
             .map_err(|e| error::Error::BadRequest(format!("Invalid accept header: {e}")))?;
-        if accept_header.eq(mime::TEXT_EVENT_STREAM.essence_str())
-            || accept_header.eq(mime::APPLICATION_OCTET_STREAM.essence_str())
-        {
-            let accept_header_value = HeaderValue::from_str(accept_header).map_err(|e| {
-                error::Error::BadRequest(format!("Invalid accept header value: {e}"))
-            })?;
+        if let Some(content_type) = streamed_content_type(accept_header) {
             let response = http_to_gsb
                 .pass_streaming(method, path, headers, body)
                 .await;
 
             return Ok(Either::Left(
-                stream_results(response, accept_header_value).await?,
+                stream_results(response, HeaderValue::from_static(content_type)).await?,
             ));
         }
     }
@@ -84,6 +129,22 @@ async fn proxy_http_request(
     Ok(Either::Right(build_response(response).await?))
 }
 
+/// Returns content type of the streamed response if `accept` header requests streaming.
+fn streamed_content_type(accept: &str) -> Option<&'static str> {
+    const STREAMED: [&str; 2] = ["text/event-stream", "application/octet-stream"];
+
+    accept
+        .split(',')
+        .filter_map(|media_range| media_range.split(';').next())
+        .map(str::trim)
+        .find_map(|essence| {
+            STREAMED
+                .iter()
+                .copied()
+                .find(|streamed| streamed.eq_ignore_ascii_case(essence))
+        })
+}
+
 async fn stream_results(
     response: HttpToGsbProxyStreamingResponse<
         impl Stream<Item = Result<Bytes, Error>> + Unpin + 'static,
@@ -94,16 +155,20 @@ async fn stream_results(
         StatusCode::from_u16(response.status_code)
             .map_err(|e| error::Error::Service(format!("Invalid status code {e}")))?,
     );
+    let has_content_type = response
+        .response_headers
+        .keys()
+        .any(|h| h.eq_ignore_ascii_case(header::CONTENT_TYPE.as_str()));
     for (h, vals) in response.response_headers {
         for v in vals {
             response_builder.append_header((h.as_str(), v));
         }
     }
+    if !has_content_type {
+        response_builder.content_type(content_type);
+    }
     return match response.body {
-        Ok(body) => Ok(response_builder
-            .keep_alive()
-            .content_type(content_type)
-            .streaming(body)),
+        Ok(body) => Ok(response_builder.keep_alive().streaming(body)),
         Err(err) => {
             let reason = format!("{err}");
             Ok(response_builder.body(reason))
@@ -115,9 +180,6 @@ async fn build_response(
     response: HttpToGsbProxyResponse<Result<Bytes, Error>>,
 ) -> crate::Result<impl Responder> {
     if let Ok(bytes) = response.body {
-        let response_body = String::from_utf8(bytes.to_vec())
-            .map_err(|e| error::Error::Service(format!("Conversion from utf8 failed {e}")))?;
-
         let mut response_builder = HttpResponse::build(
             StatusCode::from_u16(response.status_code)
                 .map_err(|e| error::Error::Service(format!("Invalid status code {e}")))?,
@@ -127,8 +189,189 @@ async fn build_response(
                 response_builder.append_header((h.as_str(), v));
             }
         }
-        return Ok(response_builder.body(response_body));
+        return Ok(response_builder.body(bytes));
     }
 
     Ok(HttpResponse::InternalServerError().body("No response"))
 }
+
+/// Tunnels WebSocket connection to the service running inside the ExeUnit.
+async fn proxy_websocket(
+    db: web::Data<DbExecutor>,
+    path: web::Path<PathActivityUrl>,
+    id: Identity,
+    request: HttpRequest,
+    stream: web::Payload,
+) -> crate::Result<HttpResponse> {
+    let path_activity_url = path.into_inner();
+    let activity_id = path_activity_url.activity_id;
+
+    authorize_activity_executor(&db, id.identity, &activity_id, Role::Requestor).await?;
+    let agreement = get_activity_agreement(&db, &activity_id, Role::Requestor).await?;
+
+    let mut http_to_gsb = HttpToGsbProxy::new(Net(NetBindingNodes {
+        from: id.identity,
+        to: *agreement.provider_id(),
+    }))
+    .bus_addr(&activity::exeunit::bus_id(&activity_id));
+
+    let HttpToGsbWebSocket {
+        connection_id,
+        frames,
+    } = http_to_gsb.pass_websocket(path_activity_url.url, request.headers().clone());
+
+    let websocket = ProxyWebSocket {
+        proxy: http_to_gsb,
+        connection_id,
+        frames: Some(frames.boxed_local()),
+        outgoing: None,
+    };
+    ws::start(websocket, &request, stream)
+        .map_err(|e| error::Error::BadRequest(format!("WebSocket handshake failed: {e}")))
+}
+
+/// Frames from the requestor waiting to be sent to the service
+const OUTGOING_QUEUE_SIZE: usize = 64;
+
+struct ProxyWebSocket {
+    proxy: HttpToGsbProxy,
+    connection_id: String,
+    frames: Option<LocalBoxStream<'static, Result<GsbWsFrame, Error>>>,
+    /// Frames for the service, sent in order by `forward_frames`
+    outgoing: Option<mpsc::Sender<GsbWsFrame>>,
+}
+
+impl ProxyWebSocket {
+    /// Returns `false` when the service doesn't keep up with the requestor.
+    fn forward(&mut self, frame: GsbWsFrame) -> bool {
+        match &mut self.outgoing {
+            Some(outgoing) => !matches!(outgoing.try_send(frame), Err(e) if e.is_full()),
+            None => true,
+        }
+    }
+}
+
+impl Actor for ProxyWebSocket {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        if let Some(frames) = self.frames.take() {
+            ctx.add_stream(frames);
+        }
+
+        // Not bound to the actor, so that frames queued before the actor stops
+        // (including the final Close) are still delivered.
+        let (tx, rx) = mpsc::channel(OUTGOING_QUEUE_SIZE);
+        self.outgoing = Some(tx);
+        tokio::task::spawn_local(forward_frames(
+            self.proxy.clone(),
+            self.connection_id.clone(),
+            rx,
+            ctx.address(),
+        ));
+    }
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        self.outgoing.take();
+        log::debug!("Proxy WebSocket {} closed", self.connection_id);
+    }
+}
+
+/// Sends frames to the service one at a time, preserving their order.
+/// Closes the service connection, when the requestor disconnected without closing it.
+async fn forward_frames(
+    proxy: HttpToGsbProxy,
+    connection_id: String,
+    mut frames: mpsc::Receiver<GsbWsFrame>,
+    websocket: Addr<ProxyWebSocket>,
+) {
+    while let Some(frame) = frames.next().await {
+        let closed = matches!(frame, GsbWsFrame::Close(_));
+        if let Err(e) = proxy.send_websocket(connection_id.clone(), frame).await {
+            websocket.do_send(ForwardFailed(e.to_string()));
+            return;
+        }
+        if closed {
+            return;
+        }
+    }
+    let _ = proxy
+        .send_websocket(connection_id, GsbWsFrame::Close(None))
+        .await;
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+struct ForwardFailed(String);
+
+impl Handler<ForwardFailed> for ProxyWebSocket {
+    type Result = ();
+
+    fn handle(&mut self, msg: ForwardFailed, ctx: &mut Self::Context) {
+        log::warn!("Proxy WebSocket {} error: {}", self.connection_id, msg.0);
+        ctx.close(Some(ws::CloseReason {
+            code: ws::CloseCode::Error,
+            description: Some(msg.0),
+        }));
+        ctx.stop();
+    }
+}
+
+/// Frames received from the service.
+impl StreamHandler<Result<GsbWsFrame, Error>> for ProxyWebSocket {
+    fn handle(&mut self, frame: Result<GsbWsFrame, Error>, ctx: &mut Self::Context) {
+        match frame {
+            Ok(frame) => {
+                let closed = matches!(frame, GsbWsFrame::Close(_));
+                ctx.write_raw(frame.into_message());
+                if closed {
+                    ctx.stop();
+                }
+            }
+            Err(e) => {
+                log::warn!("Proxy WebSocket {} error: {e}", self.connection_id);
+                ctx.close(Some(ws::CloseReason {
+                    code: ws::CloseCode::Error,
+                    description: Some(e.to_string()),
+                }));
+                ctx.stop();
+            }
+        }
+    }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        ctx.stop();
+    }
+}
+
+/// Frames received from the requestor.
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ProxyWebSocket {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        let frame = match msg {
+            Ok(msg) => match GsbWsFrame::from_message(msg) {
+                Some(frame) => frame,
+                None => return,
+            },
+            Err(e) => {
+                log::warn!("Proxy WebSocket {} error: {e}", self.connection_id);
+                ctx.stop();
+                return;
+            }
+        };
+
+        let closed = matches!(frame, GsbWsFrame::Close(_));
+        if !self.forward(frame) {
+            log::warn!(
+                "Proxy WebSocket {}: too many frames waiting for the service",
+                self.connection_id
+            );
+            ctx.close(Some(ws::CloseReason {
+                code: ws::CloseCode::Again,
+                description: Some("Too many pending frames".to_string()),
+            }));
+            ctx.stop();
+        } else if closed {
+            ctx.stop();
+        }
+    }
+}
@@ -7,6 +7,7 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 
 use crate::common::{authorize_activity_executor, set_persisted_state, PathActivity, QueryEvents};
 use crate::dao::EventDao;
@@ -14,11 +15,20 @@ use crate::error::Error;
 
 pub mod service;
 
-pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
-    scope.service(get_events).service(set_activity_state)
+pub fn extend_web_scope(scope: ApiScope) -> ApiScope {
+    scope
+        .route(
+            Operation::get("/events", "collectActivityEvents")
+                .response(Schema::array_of_model::<ProviderEvent>()),
+            get_events,
+        )
+        .route(
+            Operation::put("/activity/{activity_id}/state", "setActivityState")
+                .body(Schema::model::<ActivityState>()),
+            set_activity_state,
+        )
 }
 
-#[actix_web::put("/activity/{activity_id}/state")]
 async fn set_activity_state(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivity>,
@@ -34,7 +44,6 @@ async fn set_activity_state(
 }
 
 /// Fetch Requestor command events.
-#[actix_web::get("/events")]
 async fn get_events(
     db: web::Data<DbExecutor>,
     query: web::Query<QueryEvents>,
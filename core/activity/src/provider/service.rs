@@ -1,9 +1,12 @@
 #![allow(clippy::let_unit_value)]
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::channel::mpsc;
 use futures::future::LocalBoxFuture;
 use futures::prelude::*;
 use metrics::{counter, gauge};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::From;
 use std::time::Duration;
 
@@ -14,15 +17,17 @@ use ya_core_model::activity::local::Credentials;
 use ya_core_model::activity::RpcMessageError;
 use ya_core_model::market::Agreement;
 use ya_core_model::{activity, market};
+use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 
 use ya_service_bus::{timeout::*, typed::ServiceBinder};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 use crate::common::{
-    authorize_activity_initiator, authorize_agreement_initiator, generate_id,
-    get_activities_for_agreement, get_activity_agreement, get_agreement, get_agreements_by_state,
-    get_persisted_state, get_persisted_usage, is_responsive, set_persisted_state, RpcMessageResult,
+    authorize_activity_initiator, authorize_agreement_initiator, authorize_caller, generate_id,
+    get_activities_for_agreement, get_activity_agreement, get_agreement, get_agreement_id,
+    get_agreements_by_state, get_persisted_state, get_persisted_usage, is_responsive,
+    set_persisted_state, RpcMessageResult,
 };
 use crate::dao::*;
 use crate::db::models::ActivityEventType;
@@ -67,7 +72,8 @@ pub fn bind_gsb(db: &DbExecutor, tracker: TrackerRef) {
         .bind_with_processor(create_activity_gsb)
         .bind(destroy_activity_gsb)
         .bind(get_activity_state_gsb)
-        .bind(get_activity_usage_gsb);
+        .bind(get_activity_usage_gsb)
+        .bind_with_processor(activity_state_changed_gsb);
 
     // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
     // until first change to value will be made.
@@ -78,6 +84,7 @@ pub fn bind_gsb(db: &DbExecutor, tracker: TrackerRef) {
     counter!("activity.provider.responsive-again", 0);
     counter!("activity.provider.destroyed.by_requestor", 0);
     counter!("activity.provider.destroyed.unresponsive", 0);
+    counter!("activity.provider.acl.violations", 0);
     counter!("activity.provider.events.query", 0);
 
     local::bind_gsb(&db.clone(), tracker.clone());
@@ -346,6 +353,134 @@ async fn get_activity_usage_gsb(
     Ok(get_persisted_usage(&db, &msg.activity_id).await?)
 }
 
+/// Receives Activity state transitions pushed by the Provider.
+async fn activity_state_changed_gsb(
+    db: DbExecutor,
+    tracker: TrackerRef,
+    caller: String,
+    msg: activity::StateChanged,
+) -> RpcMessageResult<activity::StateChanged> {
+    let agreement = get_agreement(&msg.agreement_id, Role::Requestor).await?;
+    authorize_caller(
+        &caller.parse().map_err(Error::from)?,
+        agreement.provider_id(),
+    )?;
+    if get_agreement_id(&db, &msg.activity_id).await? != msg.agreement_id {
+        let err = format!("Activity {} not found in Agreement", msg.activity_id);
+        return Err(Error::NotFound(err).into());
+    }
+
+    // Notifications may arrive out of order, so ignore the ones older than already applied
+    let stale = APPLIED_STATE_CHANGES.with(|applied| {
+        let mut applied = applied.borrow_mut();
+        match applied.get(&msg.activity_id) {
+            Some(timestamp) if *timestamp >= msg.timestamp => true,
+            _ => {
+                applied.insert(msg.activity_id.clone(), msg.timestamp);
+                false
+            }
+        }
+    });
+    let current = get_persisted_state(&db, &msg.activity_id).await?;
+    if stale || current.state.0 == State::Terminated {
+        log::debug!(
+            "Ignoring stale Activity [{}] state change to {:?}",
+            msg.activity_id,
+            msg.state.state
+        );
+        return Ok(());
+    }
+    if msg.state.state.0 == State::Terminated {
+        APPLIED_STATE_CHANGES.with(|applied| applied.borrow_mut().remove(&msg.activity_id));
+    }
+
+    set_persisted_state(&db, &msg.activity_id, msg.state.clone()).await?;
+    tracker.publish_state(*agreement.requestor_id(), msg);
+    Ok(())
+}
+
+type StateNotification = (ActivityState, DateTime<Utc>);
+
+thread_local! {
+    /// Pending state notifications, queued per Activity.
+    static STATE_NOTIFICATIONS: RefCell<HashMap<String, mpsc::UnboundedSender<StateNotification>>> =
+        Default::default();
+    /// Timestamp of the latest state notification applied, per Activity.
+    static APPLIED_STATE_CHANGES: RefCell<HashMap<String, DateTime<Utc>>> = Default::default();
+}
+
+/// Queues Activity state transition for notification. Transitions of a single Activity
+/// are delivered one at a time, in the order they were reported by the ExeUnit.
+fn enqueue_state_notification(
+    db: DbExecutor,
+    tracker: TrackerRef,
+    activity_id: String,
+    state: ActivityState,
+) {
+    let terminated = state.state.0 == State::Terminated;
+    let notification = (state, Utc::now());
+
+    STATE_NOTIFICATIONS.with(|queues| {
+        let mut queues = queues.borrow_mut();
+        let tx = queues.entry(activity_id.clone()).or_insert_with(|| {
+            let (tx, rx) = mpsc::unbounded();
+            tokio::task::spawn_local(state_notification_loop(
+                db,
+                tracker,
+                activity_id.clone(),
+                rx,
+            ));
+            tx
+        });
+        let _ = tx.unbounded_send(notification);
+        // Dropping the sender lets the loop finish after the remaining notifications
+        if terminated {
+            queues.remove(&activity_id);
+        }
+    });
+}
+
+async fn state_notification_loop(
+    db: DbExecutor,
+    tracker: TrackerRef,
+    activity_id: String,
+    mut rx: mpsc::UnboundedReceiver<StateNotification>,
+) {
+    while let Some((state, timestamp)) = rx.next().await {
+        let result =
+            notify_state_changed(&db, &tracker, activity_id.clone(), state, timestamp).await;
+        if let Err(e) = result {
+            // Requestors running older versions don't support state notifications
+            log::debug!("Unable to notify Activity [{activity_id}] state change: {e}");
+        }
+    }
+}
+
+/// Publishes Activity state transition locally and pushes it to the Requestor.
+async fn notify_state_changed(
+    db: &DbExecutor,
+    tracker: &TrackerRef,
+    activity_id: String,
+    state: ActivityState,
+    timestamp: DateTime<Utc>,
+) -> Result<(), Error> {
+    let agreement = get_activity_agreement(db, &activity_id, Role::Provider).await?;
+    let msg = activity::StateChanged {
+        activity_id,
+        agreement_id: agreement.agreement_id.clone(),
+        state,
+        timestamp,
+    };
+    tracker.publish_state(*agreement.provider_id(), msg.clone());
+
+    ya_net::from(*agreement.provider_id())
+        .to(*agreement.requestor_id())
+        .service(activity::BUS_ID)
+        .send(msg)
+        .await??;
+    Ok(())
+}
+
 async fn get_activity_progress(
     db: &DbExecutor,
     activity_id: &str,
@@ -495,6 +630,7 @@ mod local {
             .bind_with_processor(set_activity_state_gsb)
             .bind_with_processor(set_activity_usage_gsb)
             .bind(get_agreement_id_gsb)
+            .bind_with_processor(report_violation_gsb)
             .bind(activity_status);
     }
 
@@ -542,7 +678,9 @@ mod local {
         let _ = tracker
             .update_state(msg.activity_id.clone(), msg.state.state.0)
             .await;
-        set_persisted_state(&db, &msg.activity_id, msg.state).await?;
+        set_persisted_state(&db, &msg.activity_id, msg.state.clone()).await?;
+
+        enqueue_state_notification(db, tracker, msg.activity_id, msg.state);
         Ok(())
     }
 
@@ -572,6 +710,29 @@ mod local {
         Ok(())
     }
 
+    /// Record a call rejected by the ExeUnit's access control list
+    /// and publish it as an Activity event. Called by ExeUnits.
+    async fn report_violation_gsb(
+        db: DbExecutor,
+        tracker: TrackerRef,
+        _caller: String,
+        msg: activity::local::ReportViolation,
+    ) -> RpcMessageResult<activity::local::ReportViolation> {
+        log::warn!(
+            "Activity [{}]: call to {} from {} rejected at {}: {}",
+            msg.activity_id,
+            msg.endpoint,
+            msg.caller,
+            msg.timestamp,
+            msg.reason
+        );
+        counter!("activity.provider.acl.violations", 1);
+
+        let agreement = get_activity_agreement(&db, &msg.activity_id, Role::Provider).await?;
+        tracker.publish_violation(*agreement.provider_id(), agreement.agreement_id, msg);
+        Ok(())
+    }
+
     /// Get agreement ID for a given activity ID
     /// Called e.g. by payment module
     async fn get_agreement_id_gsb(
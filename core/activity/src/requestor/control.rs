@@ -4,32 +4,79 @@ use actix_web::{web, Either, HttpRequest, HttpResponse, Responder};
 use futures::StreamExt;
 use metrics::counter;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tokio_stream::wrappers::IntervalStream;
 
 use ya_client_model::activity::{
     ActivityState, CreateActivityRequest, CreateActivityResult, Credentials, ExeScriptCommand,
-    ExeScriptRequest, SgxCredentials, State,
+    ExeScriptCommandResult, ExeScriptRequest, SgxCredentials, State,
 };
 use ya_client_model::market::{Agreement, Role};
 use ya_core_model::activity;
+use ya_core_model::activity::ExecBatchState;
+use ya_core_model::net::vpn as net_vpn;
 use ya_net::{self as net, RemoteEndpoint};
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
-use ya_service_bus::{timeout::IntoTimeoutFuture, RpcEndpoint};
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
+use ya_service_bus::{timeout::IntoTimeoutFuture, typed as bus, RpcEndpoint};
 
 use crate::common::*;
 use crate::dao::ActivityDao;
 use crate::{error::Error, Result};
 
-pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
+pub fn extend_web_scope(scope: ApiScope) -> ApiScope {
     scope
-        .service(create_activity)
-        .service(destroy_activity)
-        .service(exec)
-        .service(get_batch_results)
-        .service(encrypted)
+        .route(
+            Operation::post("/activity", "createActivity")
+                .body(Schema::model::<CreateActivityRequest>())
+                .response(Schema::model::<CreateActivityResult>()),
+            create_activity,
+        )
+        .route(
+            Operation::delete("/activity/{activity_id}", "destroyActivity"),
+            destroy_activity,
+        )
+        .route(
+            Operation::post("/activity/{activity_id}/exec", "exec")
+                .body(Schema::model::<ExeScriptRequest>())
+                .response(Schema::of::<String>()),
+            exec,
+        )
+        .route(
+            Operation::get(
+                "/activity/{activity_id}/exec/{batch_id}",
+                "getExecBatchResults",
+            )
+            .response(Schema::array_of_model::<ExeScriptCommandResult>()),
+            get_batch_results,
+        )
+        .route(
+            Operation::get("/activity/{activity_id}/exec", "getExecBatches")
+                .response(Schema::of::<Vec<ExecBatchState>>()),
+            get_batches,
+        )
+        .route(
+            Operation::get("/exec", "getAgreementExecBatches")
+                .response(Schema::of::<BTreeMap<String, Vec<ExecBatchState>>>()),
+            get_agreement_batches,
+        )
+        .route(
+            Operation::post(
+                "/activity/{activity_id}/exec/{batch_id}/stdin/{idx}",
+                "writeStdin",
+            )
+            .body(Schema::Binary),
+            write_stdin,
+        )
+        .route(
+            Operation::post("/activity/{activity_id}/encrypted", "callEncrypted")
+                .body(Schema::Binary)
+                .response(Schema::Binary),
+            encrypted,
+        )
 }
 
 #[derive(Deserialize)]
@@ -71,7 +118,6 @@ impl CreateActivityJson {
 }
 
 /// Creates new Activity based on given Agreement.
-#[actix_web::post("/activity")]
 async fn create_activity(
     db: web::Data<DbExecutor>,
     query: web::Query<QueryTimeout>,
@@ -122,7 +168,6 @@ async fn create_activity(
 }
 
 /// Destroys given Activity.
-#[actix_web::delete("/activity/{activity_id}")]
 async fn destroy_activity(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivity>,
@@ -151,20 +196,39 @@ async fn destroy_activity(
             error_message: None,
         },
     )
-    .await
-    .map(|_| {
-        counter!("activity.requestor.destroyed", 1);
-        log::info!(
-            "Requestor destroyed Activity [{}] for Agreement [{}]",
+    .await?;
+
+    // Provider's Node is no longer reachable within the Requestor's networks.
+    let msg = net_vpn::ActivityDestroyed {
+        owner: id.identity,
+        activity_id: path.activity_id.clone(),
+        provider_id: *agreement.provider_id(),
+    };
+    match bus::service(net_vpn::BUS_ID).send(msg).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::warn!(
+            "Unable to remove Activity [{}] from VPNs: {}",
             path.activity_id,
-            agreement.agreement_id
-        );
-        web::Json(())
-    })
+            e
+        ),
+        // VPN service is not running
+        Err(e) => log::debug!(
+            "Unable to remove Activity [{}] from VPNs: {}",
+            path.activity_id,
+            e
+        ),
+    }
+
+    counter!("activity.requestor.destroyed", 1);
+    log::info!(
+        "Requestor destroyed Activity [{}] for Agreement [{}]",
+        path.activity_id,
+        agreement.agreement_id
+    );
+    Ok::<_, Error>(web::Json(()))
 }
 
 /// Executes an ExeScript batch within a given Activity.
-#[actix_web::post("/activity/{activity_id}/exec")]
 async fn exec(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivity>,
@@ -197,7 +261,6 @@ async fn exec(
 }
 
 /// Queries for ExeScript batch results.
-#[actix_web::get("/activity/{activity_id}/exec/{batch_id}")]
 async fn get_batch_results(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivityBatch>,
@@ -291,8 +354,119 @@ fn map_event_result<T: Serialize>(
     Ok(bytes.freeze())
 }
 
+/// Queries for the state of all ExeScript batches within a given Activity.
+async fn get_batches(
+    db: web::Data<DbExecutor>,
+    path: web::Path<PathActivity>,
+    query: web::Query<QueryTimeout>,
+    id: Identity,
+) -> impl Responder {
+    authorize_activity_initiator(&db, id.identity, &path.activity_id, Role::Requestor).await?;
+
+    let agreement = get_activity_agreement(&db, &path.activity_id, Role::Requestor).await?;
+    let batches = query_batches(&id, &agreement, &path.activity_id, query.timeout).await?;
+
+    Ok::<_, Error>(web::Json(batches))
+}
+
+/// Queries for the state of ExeScript batches within all live Activities of a given Agreement.
+///
+/// Activities which failed to respond are omitted from the result.
+async fn get_agreement_batches(
+    db: web::Data<DbExecutor>,
+    query: web::Query<QueryAgreementTimeout>,
+    id: Identity,
+) -> impl Responder {
+    authorize_agreement_initiator(id.identity, &query.agreement_id, Role::Requestor).await?;
+
+    let agreement = get_agreement(&query.agreement_id, Role::Requestor).await?;
+    let timeout = query.timeout;
+    let mut activity_ids = Vec::new();
+    for activity_id in get_activities_for_agreement(&db, &query.agreement_id).await? {
+        if get_persisted_state(&db, &activity_id).await?.alive() {
+            activity_ids.push(activity_id);
+        }
+    }
+
+    let results = futures::future::join_all(activity_ids.into_iter().map(|activity_id| {
+        let agreement = &agreement;
+        let id = &id;
+        async move {
+            let result = query_batches(id, agreement, &activity_id, timeout).await;
+            (activity_id, result)
+        }
+    }))
+    .await;
+
+    let batches = results
+        .into_iter()
+        .filter_map(|(activity_id, result)| match result {
+            Ok(batches) => Some((activity_id, batches)),
+            Err(e) => {
+                log::warn!(
+                    "Unable to query batches of Activity [{}]: {}",
+                    activity_id,
+                    e
+                );
+                None
+            }
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    Ok::<_, Error>(web::Json(batches))
+}
+
+async fn query_batches(
+    id: &Identity,
+    agreement: &Agreement,
+    activity_id: &str,
+    timeout: Option<f32>,
+) -> Result<Vec<ExecBatchState>> {
+    let msg = activity::GetExecBatches {
+        activity_id: activity_id.to_string(),
+        timeout,
+    };
+
+    Ok(ya_net::from(id.identity)
+        .to(*agreement.provider_id())
+        .service(&activity::exeunit::bus_id(activity_id))
+        .send(msg)
+        .timeout(timeout_margin(timeout))
+        .await???)
+}
+
+/// Writes the request body to stdin of a running command.
+/// Stdin is available when the batch was deployed with `GOLEM_STDIN=true`.
+async fn write_stdin(
+    db: web::Data<DbExecutor>,
+    path: web::Path<PathActivityBatchCommand>,
+    query: web::Query<QueryStdin>,
+    body: Bytes,
+    id: Identity,
+) -> impl Responder {
+    authorize_activity_initiator(&db, id.identity, &path.activity_id, Role::Requestor).await?;
+
+    let agreement = get_activity_agreement(&db, &path.activity_id, Role::Requestor).await?;
+    let msg = activity::WriteStdin {
+        activity_id: path.activity_id.clone(),
+        batch_id: path.batch_id.clone(),
+        idx: path.idx,
+        data: body.to_vec(),
+        eof: query.eof,
+        timeout: query.timeout,
+    };
+
+    ya_net::from(id.identity)
+        .to(*agreement.provider_id())
+        .service(&activity::exeunit::bus_id(&path.activity_id))
+        .send(msg)
+        .timeout(timeout_margin(query.timeout))
+        .await???;
+
+    Ok::<_, Error>(web::Json(()))
+}
+
 /// Forwards an encrypted ExeUnit call.
-#[actix_web::post("/activity/{activity_id}/encrypted")]
 async fn encrypted(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivity>,
@@ -332,6 +506,21 @@ struct PathActivityBatch {
     batch_id: String,
 }
 
+#[derive(Deserialize)]
+struct PathActivityBatchCommand {
+    activity_id: String,
+    batch_id: String,
+    idx: usize,
+}
+
+#[derive(Deserialize)]
+struct QueryStdin {
+    #[serde(default)]
+    eof: bool,
+    #[serde(default = "default_query_timeout")]
+    timeout: Option<f32>,
+}
+
 fn convert_credentials(
     credentials: &ya_core_model::activity::local::Credentials,
 ) -> Result<Credentials> {
@@ -1,20 +1,25 @@
 use actix_web::{web, Responder};
 
+use ya_client_model::activity::ExeScriptCommandState;
 use ya_client_model::market::Role;
 use ya_core_model::activity;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::{timeout::IntoTimeoutFuture, RpcEndpoint};
 
 use crate::common::*;
 use crate::error::Error;
 
-pub fn extend_web_scope(scope: actix_web::Scope) -> actix_web::Scope {
-    scope.service(get_running_command)
+pub fn extend_web_scope(scope: ApiScope) -> ApiScope {
+    scope.route(
+        Operation::get("/activity/{activity_id}/command", "getRunningCommand")
+            .response(Schema::array_of_model::<ExeScriptCommandState>()),
+        get_running_command,
+    )
 }
 
 /// Get running command for a specified Activity.
-#[actix_web::get("/activity/{activity_id}/command")]
 async fn get_running_command(
     db: web::Data<DbExecutor>,
     path: web::Path<PathActivity>,
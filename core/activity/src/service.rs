@@ -25,4 +25,8 @@ impl Activity {
     ) -> actix_web::Scope {
         api::web_scope(&ctx.component(), ctx.component())
     }
+
+    pub fn api_spec() -> ya_service_api_web::openapi::ScopeSpec {
+        api::api_spec()
+    }
 }
@@ -12,9 +12,13 @@ mod state_manager;
 use anyhow::Context;
 use name_pool::NamePool;
 use ya_client_model::activity::State;
+use ya_core_model::activity::local::ReportViolation;
+use ya_core_model::activity::StateChanged;
 use ya_core_model::market::Agreement;
 use ya_core_model::NodeId;
 
+const STATE_EVENTS_CAPACITY: usize = 256;
+
 #[derive(Serialize, Clone)]
 pub struct TrackingEvent {
     ts: DateTime<Utc>,
@@ -22,6 +26,14 @@ pub struct TrackingEvent {
 }
 
 impl TrackingEvent {
+    /// Number of Activities which are not terminated yet.
+    fn running(&self) -> usize {
+        self.activities
+            .iter()
+            .filter(|activity| activity.state != State::Terminated)
+            .count()
+    }
+
     pub fn for_provider(self, provider_id: NodeId) -> Self {
         Self {
             ts: self.ts,
@@ -78,9 +90,48 @@ pub enum Command {
     },
 }
 
+/// Activity event, addressed to the identity owning the Activity.
+#[derive(Clone, Debug)]
+pub struct StateEvent {
+    pub owner: NodeId,
+    pub event: ActivityEvent,
+}
+
+#[derive(Clone, Debug)]
+pub enum ActivityEvent {
+    StateChanged(StateChanged),
+    /// Call rejected by the ExeUnit's access control list
+    AclViolation(AclViolation),
+}
+
+impl ActivityEvent {
+    pub fn agreement_id(&self) -> &str {
+        match self {
+            ActivityEvent::StateChanged(event) => &event.agreement_id,
+            ActivityEvent::AclViolation(event) => &event.agreement_id,
+        }
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        match self {
+            ActivityEvent::StateChanged(event) => event.state.state.0 == State::Terminated,
+            ActivityEvent::AclViolation(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AclViolation {
+    pub agreement_id: String,
+    #[serde(flatten)]
+    pub violation: ReportViolation,
+}
+
 #[derive(Clone)]
 pub struct TrackerRef {
     tx: mpsc::UnboundedSender<Command>,
+    states: broadcast::Sender<StateEvent>,
 }
 
 impl TrackerRef {
@@ -98,6 +149,51 @@ impl TrackerRef {
         anyhow::bail!("Fatal error activity state tracker is unavailable");
     }
 
+    /// Waits until all tracked Activities are terminated or destroyed.
+    pub async fn drain(&mut self) -> anyhow::Result<()> {
+        let (mut event, mut events) = self.subscribe().await?;
+        loop {
+            let running = event.running();
+            if running == 0 {
+                return Ok(());
+            }
+            log::info!("Waiting for {} running activities to finish...", running);
+
+            event = loop {
+                match events.recv().await {
+                    Ok(event) => break event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => {
+                        anyhow::bail!("Activity state tracker is unavailable")
+                    }
+                }
+            };
+        }
+    }
+
+    pub fn subscribe_states(&self) -> broadcast::Receiver<StateEvent> {
+        self.states.subscribe()
+    }
+
+    pub fn publish_state(&self, owner: NodeId, event: StateChanged) {
+        // No subscribers is not an error
+        let event = ActivityEvent::StateChanged(event);
+        let _ = self.states.send(StateEvent { owner, event });
+    }
+
+    pub fn publish_violation(
+        &self,
+        owner: NodeId,
+        agreement_id: String,
+        violation: ReportViolation,
+    ) {
+        let event = ActivityEvent::AclViolation(AclViolation {
+            agreement_id,
+            violation,
+        });
+        let _ = self.states.send(StateEvent { owner, event });
+    }
+
     pub async fn start_activity(
         &mut self,
         activity_id: &str,
@@ -174,6 +270,7 @@ impl TrackerRef {
 
 pub fn start_tracker() -> (TrackerRef, broadcast::Receiver<TrackingEvent>) {
     let (tx_event, rx_event) = broadcast::channel(1);
+    let (states, _) = broadcast::channel(STATE_EVENTS_CAPACITY);
     let (tx, mut rx) = mpsc::unbounded();
 
     let mut exe_units_names = NamePool::default();
@@ -230,5 +327,50 @@ pub fn start_tracker() -> (TrackerRef, broadcast::Receiver<TrackingEvent>) {
         }
     });
 
-    (TrackerRef { tx }, rx_event)
+    (TrackerRef { tx, states }, rx_event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[actix_rt::test]
+    async fn test_drain() {
+        let mut tracker = TrackerRef::create();
+        tracker.drain().await.unwrap();
+
+        for activity_id in ["a1", "a2"] {
+            tracker
+                .tx
+                .send(Command::Start {
+                    activity_id: activity_id.to_string(),
+                    identity_id: NodeId::default(),
+                    agreement_id: "agreement".to_string(),
+                    exe_unit: None,
+                    counters: vec![],
+                })
+                .await
+                .unwrap();
+        }
+
+        let drain = tokio::spawn({
+            let mut tracker = tracker.clone();
+            async move { tracker.drain().await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!drain.is_finished());
+
+        tracker
+            .update_state("a1".to_string(), State::Terminated)
+            .await
+            .unwrap();
+        tracker.stop_activity("a2".to_string()).await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(1), drain)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
 }
@@ -145,7 +145,11 @@ async fn main() -> Result<()> {
 
     log::info!("sending publish request");
     let files = vec![args.share.clone()];
-    let req = RpcRequest::Publish { files };
+    let req = RpcRequest::Publish {
+        files,
+        expires_in: None,
+        single_use: false,
+    };
     let urls = match send(&mut stdin, &mut reader, req).await? {
         RpcResult::Files(files) => files.into_iter().map(|r| r.url).collect::<Vec<_>>(),
         result => return Err(anyhow!("Invalid result: {:?}", result)),
@@ -175,7 +179,11 @@ async fn main() -> Result<()> {
 
     log::info!("sending publish request (for download)");
     let files = vec![args.share.clone()];
-    let req = RpcRequest::Publish { files };
+    let req = RpcRequest::Publish {
+        files,
+        expires_in: None,
+        single_use: false,
+    };
     let url = match send(&mut stdin, &mut reader, req).await? {
         RpcResult::Files(files) => files
             .into_iter()
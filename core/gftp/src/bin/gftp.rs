@@ -49,16 +49,17 @@ async fn execute(id: Option<RpcId>, request: RpcRequest, verbose: bool) -> ExecM
 }
 
 async fn execute_inner(id: Option<&RpcId>, request: RpcRequest, verbose: bool) -> Result<ExecMode> {
+    let options = request.share_options();
     let exec_mode = match request {
         RpcRequest::Version {} => {
             let version = ya_compile_time_utils::version_describe!().to_string();
             RpcMessage::response(id, RpcResult::String(version)).print(verbose);
             ExecMode::OneShot
         }
-        RpcRequest::Publish { files } => {
+        RpcRequest::Publish { files, .. } => {
             let mut result = Vec::new();
             for file in files {
-                let url = gftp::publish(&file).await?;
+                let url = gftp::publish_with(&file, &options).await?;
                 result.push((file, url));
             }
             match result.len() {
@@ -68,6 +69,11 @@ async fn execute_inner(id: Option<&RpcId>, request: RpcRequest, verbose: bool) -
             .print(verbose);
             ExecMode::Service
         }
+        RpcRequest::List {} => {
+            RpcMessage::response(id, RpcResult::Shares(gftp::share::list_all().await))
+                .print(verbose);
+            ExecMode::OneShot
+        }
         RpcRequest::Close { urls } => {
             let mut statuses = Vec::with_capacity(urls.len());
             for url in urls {
@@ -86,6 +92,16 @@ async fn execute_inner(id: Option<&RpcId>, request: RpcRequest, verbose: bool) -
             RpcMessage::file_response(id, output_file, url).print(verbose);
             ExecMode::OneShot
         }
+        RpcRequest::DownloadDir {
+            url,
+            output_dir,
+            patterns,
+        } => {
+            let files = gftp::download_dir(&url, &output_dir, &patterns).await?;
+            let result = files.into_iter().map(|file| (file, url.clone())).collect();
+            RpcMessage::files_response(id, result).print(verbose);
+            ExecMode::OneShot
+        }
         RpcRequest::Receive { output_file } => {
             let url = gftp::open_for_upload(&output_file).await?;
             RpcMessage::file_response(id, output_file, url).print(verbose);
@@ -137,6 +153,7 @@ async fn server_loop() {
                         tokio::task::spawn_local(async move {
                             if let ExecMode::Shutdown = execute(id, request, verbose).await {
                                 tokio::time::sleep(Duration::from_secs(1)).await;
+                                gftp::share::unregister();
                                 std::process::exit(0);
                             }
                         });
@@ -166,5 +183,6 @@ async fn main() -> Result<()> {
         Command::Server => server_loop().await,
     }
 
+    gftp::share::unregister();
     Ok(())
 }
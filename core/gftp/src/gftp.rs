@@ -1,12 +1,15 @@
 use anyhow::{anyhow, Context, Error, Result};
 use futures::lock::Mutex;
 use futures::prelude::*;
+use globset::{GlobBuilder, GlobSetBuilder};
+use percent_encoding::percent_decode_str;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::{fs, io};
@@ -18,6 +21,8 @@ use ya_core_model::net::{RemoteEndpoint, TryRemoteEndpoint};
 use ya_core_model::NodeId;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
+use crate::share::{self, ShareOptions};
+
 pub const DEFAULT_CHUNK_SIZE: u64 = 40 * 1024;
 
 // =========================================== //
@@ -49,17 +54,24 @@ impl FileDesc {
         Ok(FileDesc::new(file, hash, meta))
     }
 
-    pub fn bind_handlers(self: &Arc<Self>) {
-        let gsb_address = model::file_bus_id(&self.hash);
+    pub fn bind_handlers(self: &Arc<Self>, gsb_address: &str) {
         let desc = self.clone();
-        let _ = bus::bind(&gsb_address, move |_msg: model::GetMetadata| {
-            future::ok(desc.meta.clone())
+        let address = gsb_address.to_string();
+        let _ = bus::bind_with_caller(gsb_address, move |caller, _msg: model::GetMetadata| {
+            future::ready(share::authorize(&address, &caller).map(|_| desc.meta.clone()))
         });
 
         let desc = self.clone();
-        let _ = bus::bind(&gsb_address, move |msg: model::GetChunk| {
+        let address = gsb_address.to_string();
+        let _ = bus::bind_with_caller(gsb_address, move |caller, msg: model::GetChunk| {
             let desc = desc.clone();
-            async move { desc.get_chunk(msg.offset, msg.size).await }
+            let address = address.clone();
+            async move {
+                share::authorize(&address, &caller)?;
+                let chunk = desc.get_chunk(msg.offset, msg.size).await?;
+                share::served(&address, "", msg.offset, chunk.content.len() as u64);
+                Ok(chunk)
+            }
         });
     }
 
@@ -98,13 +110,131 @@ impl FileDesc {
     }
 }
 
+struct DirDesc {
+    hash: String,
+    files: HashMap<String, Arc<FileDesc>>,
+    listing: model::GftpDirectory,
+}
+
+impl DirDesc {
+    pub fn open(root: &Path) -> Result<Arc<DirDesc>> {
+        let mut files = HashMap::new();
+        let mut entries = Vec::new();
+
+        for path in list_files(root)? {
+            let relative = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let desc = FileDesc::open(&path)?;
+
+            entries.push(model::GftpFileEntry {
+                path: relative.clone(),
+                file_size: desc.meta.file_size,
+                hash: desc.hash.clone(),
+            });
+            files.insert(relative, desc);
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut hasher = Sha3_256::new();
+        for entry in entries.iter() {
+            hasher.input(entry.path.as_bytes());
+            hasher.input(b"\0");
+            hasher.input(entry.hash.as_bytes());
+            hasher.input(b"\n");
+        }
+        let hash = format!("{:x}", hasher.result());
+
+        Ok(Arc::new(DirDesc {
+            hash,
+            files,
+            listing: model::GftpDirectory { files: entries },
+        }))
+    }
+
+    fn size(&self) -> u64 {
+        self.listing.files.iter().map(|f| f.file_size).sum()
+    }
+
+    pub fn bind_handlers(self: &Arc<Self>, gsb_address: &str) {
+        let desc = self.clone();
+        let address = gsb_address.to_string();
+        let _ = bus::bind_with_caller(gsb_address, move |caller, _msg: model::GetDirectory| {
+            future::ready(share::authorize(&address, &caller).map(|_| desc.listing.clone()))
+        });
+
+        let desc = self.clone();
+        let address = gsb_address.to_string();
+        let _ = bus::bind_with_caller(gsb_address, move |caller, msg: model::GetFileChunk| {
+            let file = desc.files.get(&msg.path).cloned();
+            let address = address.clone();
+            async move {
+                share::authorize(&address, &caller)?;
+                let chunk = match file {
+                    Some(file) => file.get_chunk(msg.offset, msg.size).await?,
+                    None => {
+                        return Err(model::Error::ReadError(format!(
+                            "No such file in directory: {}",
+                            msg.path
+                        )))
+                    }
+                };
+                share::served(&address, &msg.path, msg.offset, chunk.content.len() as u64);
+                Ok(chunk)
+            }
+        });
+    }
+}
+
+/// Publishes a file or a directory tree. Files of a published directory
+/// are served under a single url and can be listed with `GetDirectory`.
 pub async fn publish(path: &Path) -> Result<Url> {
+    publish_with(path, &ShareOptions::default()).await
+}
+
+pub async fn publish_dir(path: &Path) -> Result<Url> {
+    publish_dir_with(path, &ShareOptions::default()).await
+}
+
+/// Publishes a file or a directory tree, restricting access according to `options`
+pub async fn publish_with(path: &Path, options: &ShareOptions) -> Result<Url> {
+    if path.is_dir() {
+        return publish_dir_with(path, options).await;
+    }
+
     let filedesc = FileDesc::open(path)?;
-    filedesc.bind_handlers();
+    let token = options.restricted().then(share::new_token);
+    let gsb_address = model::share_bus_id(&filedesc.hash, token.as_deref());
+    filedesc.bind_handlers(&gsb_address);
+
+    let url = share_url(&filedesc.hash, token.as_deref()).await?;
+    let size = filedesc.meta.file_size;
+    share::register(gsb_address, url.clone(), path.into(), size, options);
+    Ok(url)
+}
 
-    gftp_url(&filedesc.hash).await
+pub async fn publish_dir_with(path: &Path, options: &ShareOptions) -> Result<Url> {
+    let dirdesc = DirDesc::open(path)?;
+    let token = options.restricted().then(share::new_token);
+    let gsb_address = model::share_bus_id(&dirdesc.hash, token.as_deref());
+    dirdesc.bind_handlers(&gsb_address);
+
+    let url = share_url(&dirdesc.hash, token.as_deref()).await?;
+    share::register(
+        gsb_address,
+        url.clone(),
+        path.into(),
+        dirdesc.size(),
+        options,
+    );
+    Ok(url)
 }
 
+/// Stops publishing a file or revokes a share, also one published by another gftp process.
+/// Returns `false` if it was not published.
 pub async fn close(url: &Url) -> Result<bool> {
     let hash_name = match url.path_segments() {
         Some(segments) => match segments.last() {
@@ -114,9 +244,8 @@ pub async fn close(url: &Url) -> Result<bool> {
         _ => return Err(anyhow!("Invalid URL: {:?}", url)),
     };
 
-    bus::unbind(model::file_bus_id(hash_name).as_str())
-        .await
-        .map_err(|e| anyhow!(e))
+    let token = share::extract_token(url);
+    Ok(share::revoke_any(&model::share_bus_id(hash_name, token.as_deref())).await)
 }
 
 // =========================================== //
@@ -125,11 +254,17 @@ pub async fn close(url: &Url) -> Result<bool> {
 
 pub async fn download_from_url(url: &Url, dst_path: &Path) -> Result<()> {
     let (node_id, hash) = extract_url(url)?;
-    download_file(node_id, &hash, dst_path).await
+    let token = share::extract_token(url);
+    download_file(node_id, &hash, token.as_deref(), dst_path).await
 }
 
-pub async fn download_file(node_id: NodeId, hash: &str, dst_path: &Path) -> Result<()> {
-    let remote = node_id.service_transfer(&model::file_bus_id(hash));
+pub async fn download_file(
+    node_id: NodeId,
+    hash: &str,
+    token: Option<&str>,
+    dst_path: &Path,
+) -> Result<()> {
+    let remote = node_id.service_transfer(&model::share_bus_id(hash, token));
     log::debug!("Creating target file {}", dst_path.display());
 
     let mut file = create_dest_file(dst_path)?;
@@ -165,6 +300,62 @@ pub async fn download_file(node_id: NodeId, hash: &str, dst_path: &Path) -> Resu
     Ok(())
 }
 
+/// Downloads files of a published directory matching any of glob `patterns`
+/// (all files, if no patterns are given). Returns paths of downloaded files.
+pub async fn download_dir(url: &Url, dst_dir: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let (node_id, hash) = extract_url(url)?;
+    let token = share::extract_token(url);
+    let remote = node_id.service_transfer(&model::share_bus_id(&hash, token.as_deref()));
+
+    log::debug!("Loading directory {} listing.", url);
+    let listing = remote.send(model::GetDirectory {}).await??;
+    let entries = select_files(&listing, patterns)?;
+
+    let mut downloaded = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let dst_path = dst_dir.join(relative_path(&entry.path)?);
+        log::debug!("Downloading {} to {}", entry.path, dst_path.display());
+
+        let mut file = create_dest_file(&dst_path)?;
+        file.set_len(entry.file_size)?;
+
+        let chunk_size = DEFAULT_CHUNK_SIZE;
+        let num_chunks = (entry.file_size + (chunk_size - 1)) / chunk_size;
+
+        futures::stream::iter(0..num_chunks)
+            .map(|chunk_number| {
+                remote.call(model::GetFileChunk {
+                    path: entry.path.clone(),
+                    offset: chunk_number * chunk_size,
+                    size: chunk_size,
+                })
+            })
+            .buffered(12)
+            .map_err(anyhow::Error::from)
+            .try_for_each(|result| {
+                future::ready((|| {
+                    let chunk = result?;
+                    file.write_all(&chunk.content[..])?;
+                    Ok(())
+                })())
+            })
+            .await?;
+
+        let hash = hash_file_sha256(&mut file)?;
+        if hash != entry.hash {
+            return Err(anyhow!(
+                "File {} hash {} is different than expected hash {}.",
+                entry.path,
+                hash,
+                entry.hash
+            ));
+        }
+        downloaded.push(dst_path);
+    }
+
+    Ok(downloaded)
+}
+
 // =========================================== //
 // File upload - publisher side ("requestor")
 // =========================================== //
@@ -326,6 +517,84 @@ fn hash_file_sha256(mut file: &mut fs::File) -> Result<String> {
     Ok(format!("{:x}", hasher.result()))
 }
 
+fn list_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)
+            .with_context(|| format!("Can't read directory {}.", dir.display()))?
+        {
+            let path = entry?.path();
+            // Symlinks could point outside of the published directory or form loops
+            let metadata = fs::symlink_metadata(&path)?;
+            if metadata.is_dir() {
+                dirs.push(path);
+            } else if metadata.is_file() {
+                files.push(path);
+            } else if metadata.file_type().is_symlink() {
+                log::debug!("Skipping symlink {}", path.display());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Selects directory entries matching any of glob `patterns`.
+/// `*` does not cross `/` boundaries, `**` does.
+pub fn select_files<'a>(
+    dir: &'a model::GftpDirectory,
+    patterns: &[String],
+) -> Result<Vec<&'a model::GftpFileEntry>> {
+    if patterns.is_empty() {
+        return Ok(dir.files.iter().collect());
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            GlobBuilder::new(pattern.trim_start_matches('/'))
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid glob pattern: {}", pattern))?,
+        );
+    }
+    let globs = builder.build()?;
+
+    Ok(dir
+        .files
+        .iter()
+        .filter(|entry| globs.is_match(&entry.path))
+        .collect())
+}
+
+/// Splits path of gftp url into published hash and optional
+/// path (or glob pattern) of a file within published directory.
+/// The file path is percent-decoded.
+pub fn split_dir_path(path: &str) -> (&str, Option<String>) {
+    match path.split_once('/') {
+        Some((hash, file)) if !file.is_empty() => (
+            hash,
+            Some(percent_decode_str(file).decode_utf8_lossy().into_owned()),
+        ),
+        Some((hash, _)) => (hash, None),
+        None => (path, None),
+    }
+}
+
+/// Converts `/` separated directory entry path to a relative file system path,
+/// rejecting paths which would escape the destination directory.
+fn relative_path(path: &str) -> Result<PathBuf> {
+    let relative: PathBuf = path.split('/').filter(|s| !s.is_empty()).collect();
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(anyhow!("Invalid directory entry path: {}", path));
+    }
+    Ok(relative)
+}
+
 /// Returns NodeId and file hash from gftp url.
 /// Note: In case of upload, hash is not real hash of file
 /// but only cryptographically strong random string.
@@ -354,6 +623,14 @@ async fn gftp_url(hash: &str) -> Result<Url> {
     Ok(Url::parse(&format!("gftp://{:?}/{}", id.node_id, hash))?)
 }
 
+async fn share_url(hash: &str, token: Option<&str>) -> Result<Url> {
+    let url = gftp_url(hash).await?;
+    Ok(match token {
+        Some(token) => share::with_token(url, token),
+        None => url,
+    })
+}
+
 fn ensure_dir_exists(file_path: &Path) -> Result<()> {
     if let Some(file_dir) = file_path.parent() {
         fs::create_dir_all(file_dir)?
@@ -376,3 +653,171 @@ fn create_dest_file(file_path: &Path) -> Result<File> {
         .open(file_path)
         .with_context(|| format!("Can't create destination file: [{}].", file_path.display()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempdir::TempDir;
+
+    /// Publishes a file without asking the identity service for the url.
+    /// Returns hash of the file and the address it's served at.
+    fn share_file(dir: &TempDir, content: &[u8], options: &ShareOptions) -> (String, String) {
+        let path = dir.path().join("file");
+        fs::write(&path, content).unwrap();
+        let desc = FileDesc::open(&path).unwrap();
+        let token = options.restricted().then(share::new_token);
+        let address = model::share_bus_id(&desc.hash, token.as_deref());
+        desc.bind_handlers(&address);
+
+        let url = Url::parse(&format!("gftp://{}/{}", NodeId::default(), desc.hash)).unwrap();
+        share::register(address.clone(), url, path, desc.meta.file_size, options);
+        (desc.hash.clone(), address)
+    }
+
+    async fn get_chunk(address: &str, caller: &str) -> std::result::Result<Vec<u8>, String> {
+        let msg = model::GetChunk {
+            offset: 0,
+            size: DEFAULT_CHUNK_SIZE,
+        };
+        match bus::service(address).send_as(caller.to_string(), msg).await {
+            Ok(Ok(chunk)) => Ok(chunk.content),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn listing(paths: &[&str]) -> model::GftpDirectory {
+        model::GftpDirectory {
+            files: paths
+                .iter()
+                .map(|path| model::GftpFileEntry {
+                    path: path.to_string(),
+                    file_size: 0,
+                    hash: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn split_dir_path_decodes_pattern() {
+        assert_eq!(split_dir_path("abc"), ("abc", None));
+        assert_eq!(split_dir_path("abc/"), ("abc", None));
+        assert_eq!(
+            split_dir_path("abc/data/my%20file%3F.txt"),
+            ("abc", Some("data/my file?.txt".to_string()))
+        );
+        assert_eq!(
+            split_dir_path("abc/%2A%2A/%5Bab%5D.txt"),
+            ("abc", Some("**/[ab].txt".to_string()))
+        );
+    }
+
+    #[test]
+    fn select_files_by_glob() {
+        let dir = listing(&["a.txt", "b.bin", "sub/c.txt", "sub/deep/d.txt"]);
+        let select = |patterns: &[&str]| {
+            let patterns: Vec<_> = patterns.iter().map(|p| p.to_string()).collect();
+            select_files(&dir, &patterns)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(select(&[]).len(), 4);
+        assert_eq!(select(&["*.txt"]), ["a.txt"]);
+        assert_eq!(
+            select(&["**/*.txt"]),
+            ["a.txt", "sub/c.txt", "sub/deep/d.txt"]
+        );
+        assert_eq!(select(&["/sub/*", "*.bin"]), ["b.bin", "sub/c.txt"]);
+    }
+
+    #[actix_rt::test]
+    async fn restricted_share_requires_token() {
+        let dir = TempDir::new("gftp-token").unwrap();
+        let options = ShareOptions {
+            expires_in: Some(Duration::from_secs(60)),
+            single_use: false,
+        };
+        let (hash, address) = share_file(&dir, b"token", &options);
+
+        assert_eq!(get_chunk(&address, "0xa").await.unwrap(), b"token");
+        assert_eq!(get_chunk(&address, "0xb").await.unwrap(), b"token");
+        assert!(get_chunk(&model::share_bus_id(&hash, None), "0xa")
+            .await
+            .is_err());
+        assert!(share::revoke(&address).await);
+    }
+
+    #[actix_rt::test]
+    async fn single_use_share_is_revoked_once_downloaded() {
+        let dir = TempDir::new("gftp-single-use").unwrap();
+        let options = ShareOptions {
+            expires_in: None,
+            single_use: true,
+        };
+        let (_, address) = share_file(&dir, b"single use", &options);
+
+        let metadata = bus::service(&address)
+            .send_as("0xa".to_string(), model::GetMetadata {})
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.file_size, 10);
+        // Claimed by the first caller
+        let denied = get_chunk(&address, "0xb").await.unwrap_err();
+        assert!(denied.contains("share already used"), "{}", denied);
+        assert!(share::list()[0].claimed);
+
+        assert_eq!(get_chunk(&address, "0xa").await.unwrap(), b"single use");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(get_chunk(&address, "0xa").await.is_err());
+        assert!(!share::revoke(&address).await);
+    }
+
+    #[actix_rt::test]
+    async fn expired_share_is_revoked() {
+        let dir = TempDir::new("gftp-expiry").unwrap();
+        let options = ShareOptions {
+            expires_in: Some(Duration::from_millis(200)),
+            single_use: false,
+        };
+        let (_, address) = share_file(&dir, b"expiry", &options);
+
+        assert_eq!(get_chunk(&address, "0xa").await.unwrap(), b"expiry");
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(get_chunk(&address, "0xa").await.is_err());
+        assert!(share::list().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn list_files_skips_symlinks() {
+        let outside = TempDir::new("gftp-outside").unwrap();
+        fs::write(outside.path().join("secret"), b"secret").unwrap();
+
+        let root = TempDir::new("gftp-root").unwrap();
+        let root = root.path();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("a.txt"), b"a").unwrap();
+        fs::write(root.join("sub").join("b.txt"), b"b").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret"), root.join("secret")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.join("outside")).unwrap();
+        // Loop back to the published directory
+        std::os::unix::fs::symlink(root, root.join("sub").join("loop")).unwrap();
+
+        let mut files: Vec<_> = list_files(root)
+            .unwrap()
+            .into_iter()
+            .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [PathBuf::from("a.txt"), PathBuf::from("sub").join("b.txt")]
+        );
+    }
+}
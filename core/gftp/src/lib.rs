@@ -1,7 +1,10 @@
 mod gftp;
 pub mod rpc;
+pub mod share;
 
 pub use self::gftp::{
-    close, download_file, download_from_url, extract_url, open_for_upload, publish, upload_file,
+    close, download_dir, download_file, download_from_url, extract_url, open_for_upload, publish,
+    publish_dir, publish_dir_with, publish_with, select_files, split_dir_path, upload_file,
     DEFAULT_CHUNK_SIZE,
 };
+pub use self::share::{extract_token, ShareInfo, ShareOptions};
@@ -2,9 +2,12 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use std::io::Write;
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 use url::Url;
 
+use crate::share::{ShareInfo, ShareOptions};
+
 const JSON_RPC_VERSION: &str = "2.0";
 
 #[allow(unused)]
@@ -156,9 +159,21 @@ pub enum RpcBody {
 pub enum RpcRequest {
     /// Prints out version
     Version {},
-    /// Publishes files (blocking)
-    Publish { files: Vec<PathBuf> },
-    /// Stops publishing a file
+    /// Publishes files or directories (blocking)
+    Publish {
+        files: Vec<PathBuf>,
+        /// Revokes the share after the given number of seconds
+        #[structopt(long)]
+        #[serde(default)]
+        expires_in: Option<u64>,
+        /// Allows a single download, by a single node
+        #[structopt(long)]
+        #[serde(default)]
+        single_use: bool,
+    },
+    /// Lists active shares
+    List {},
+    /// Stops publishing a file or revokes a share
     Close { urls: Vec<Url> },
     /// Downloads a file
     Download {
@@ -167,6 +182,17 @@ pub enum RpcRequest {
         /// Destination path
         output_file: PathBuf,
     },
+    /// Downloads files of a published directory
+    DownloadDir {
+        /// Source URL
+        url: Url,
+        /// Destination directory
+        output_dir: PathBuf,
+        /// Glob patterns of files to download, e.g. `**/*.txt`. Downloads all files if empty
+        #[structopt(long = "pattern")]
+        #[serde(default)]
+        patterns: Vec<String>,
+    },
     /// Waits for file upload (blocking)
     Receive {
         /// Destination path
@@ -183,6 +209,23 @@ pub enum RpcRequest {
     Shutdown {},
 }
 
+impl RpcRequest {
+    /// Share options of a `Publish` request
+    pub fn share_options(&self) -> ShareOptions {
+        match self {
+            RpcRequest::Publish {
+                expires_in,
+                single_use,
+                ..
+            } => ShareOptions {
+                expires_in: expires_in.map(Duration::from_secs),
+                single_use: *single_use,
+            },
+            _ => ShareOptions::default(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
 pub enum RpcResult {
@@ -191,6 +234,7 @@ pub enum RpcResult {
     Files(Vec<RpcFileResult>),
     Status(RpcStatusResult),
     Statuses(Vec<RpcStatusResult>),
+    Shares(Vec<ShareInfo>),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
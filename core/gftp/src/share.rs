@@ -0,0 +1,411 @@
+//! Registry of files published by this process.
+//!
+//! Shares published with an expiry time or as single-use are restricted: they're bound
+//! under an address containing a random access token, which is passed in the `token`
+//! query parameter of the url. A single-use share is claimed by the first node which
+//! accesses it and revoked once all of its bytes have been served.
+//!
+//! Every publishing process binds a local control service and leaves a marker file
+//! with its pid in the temp directory, so that shares can be listed and revoked
+//! from other gftp processes. The marker is removed by [`unregister`] on exit, or by
+//! another process once the publisher is known to have exited.
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use ya_core_model::gftp as model;
+use ya_service_bus::{typed as bus, RpcEndpoint, RpcMessage};
+
+const TOKEN_LEN: usize = 32;
+const TOKEN_PARAM: &str = "token";
+const CONTROL_TIMEOUT: Duration = Duration::from_secs(5);
+
+thread_local! {
+    static SHARES: RefCell<HashMap<String, Share>> = RefCell::new(HashMap::new());
+    static CONTROL_BOUND: Cell<bool> = Cell::new(false);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ShareOptions {
+    /// Share is revoked after this time
+    pub expires_in: Option<Duration>,
+    /// Share can be downloaded by a single node, once
+    pub single_use: bool,
+}
+
+impl ShareOptions {
+    pub fn restricted(&self) -> bool {
+        self.expires_in.is_some() || self.single_use
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ShareInfo {
+    pub url: Url,
+    pub path: PathBuf,
+    /// Unix timestamp (seconds)
+    pub expires_at: Option<u64>,
+    pub single_use: bool,
+    pub claimed: bool,
+}
+
+struct Share {
+    info: ShareInfo,
+    expires: Option<Instant>,
+    claimed_by: Option<String>,
+    remaining: u64,
+    /// Chunks of a single-use share served so far, by file path and offset
+    served: HashSet<(String, u64)>,
+}
+
+/// Peers running older versions don't know any other error variants,
+/// so access errors are reported as read errors.
+fn access_denied(reason: &str) -> model::Error {
+    model::Error::ReadError(format!("Access denied: {}", reason))
+}
+
+impl Share {
+    fn authorize(&mut self, caller: &str, now: Instant) -> Result<(), model::Error> {
+        if self.expires.map(|t| t <= now).unwrap_or(false) {
+            return Err(access_denied("share expired"));
+        }
+        if self.info.single_use {
+            match &self.claimed_by {
+                Some(node) if node != caller => return Err(access_denied("share already used")),
+                Some(_) => (),
+                None => {
+                    self.claimed_by = Some(caller.to_string());
+                    self.info.claimed = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns whether a single-use share has been served completely.
+    /// Chunks requested again, e.g. on retry, are counted once.
+    fn served(&mut self, path: &str, offset: u64, bytes: u64) -> bool {
+        if !self.info.single_use {
+            return false;
+        }
+        if self.served.insert((path.to_string(), offset)) {
+            self.remaining = self.remaining.saturating_sub(bytes);
+        }
+        self.remaining == 0
+    }
+}
+
+pub(crate) fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .map(char::from)
+        .take(TOKEN_LEN)
+        .collect()
+}
+
+/// Access token of a restricted share url
+pub fn extract_token(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == TOKEN_PARAM)
+        .map(|(_, v)| v.into_owned())
+}
+
+pub(crate) fn with_token(mut url: Url, token: &str) -> Url {
+    url.query_pairs_mut().append_pair(TOKEN_PARAM, token);
+    url
+}
+
+/// Registers a published file under `bus_id`. `size` is the number of bytes
+/// to be served until a single-use share is exhausted.
+pub(crate) fn register(bus_id: String, url: Url, path: PathBuf, size: u64, options: &ShareOptions) {
+    let expires_at = options.expires_in.map(|d| {
+        (SystemTime::now() + d)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    });
+    let share = Share {
+        info: ShareInfo {
+            url,
+            path,
+            expires_at,
+            single_use: options.single_use,
+            claimed: false,
+        },
+        expires: options.expires_in.map(|d| Instant::now() + d),
+        claimed_by: None,
+        remaining: size,
+        served: HashSet::new(),
+    };
+    SHARES.with(|s| s.borrow_mut().insert(bus_id.clone(), share));
+    bind_control();
+
+    if let Some(expires_in) = options.expires_in {
+        tokio::task::spawn_local(async move {
+            tokio::time::sleep(expires_in).await;
+            if revoke(&bus_id).await {
+                log::debug!("Share {} expired", bus_id);
+            }
+        });
+    }
+}
+
+/// Checks whether `caller` may access the share. Unregistered addresses are not restricted.
+pub(crate) fn authorize(bus_id: &str, caller: &str) -> Result<(), model::Error> {
+    SHARES.with(|s| match s.borrow_mut().get_mut(bus_id) {
+        Some(share) => share.authorize(caller, Instant::now()),
+        None => Ok(()),
+    })
+}
+
+/// Records a chunk of `path` sent from the share, revoking exhausted single-use shares.
+/// `path` is empty for published files.
+pub(crate) fn served(bus_id: &str, path: &str, offset: u64, bytes: u64) {
+    let exhausted = SHARES.with(|s| {
+        s.borrow_mut()
+            .get_mut(bus_id)
+            .map(|share| share.served(path, offset, bytes))
+            .unwrap_or(false)
+    });
+    if exhausted {
+        let bus_id = bus_id.to_string();
+        tokio::task::spawn_local(async move {
+            revoke(&bus_id).await;
+        });
+    }
+}
+
+/// Stops serving the share. Returns `false` if it was not published.
+pub(crate) async fn revoke(bus_id: &str) -> bool {
+    let removed = SHARES.with(|s| s.borrow_mut().remove(bus_id)).is_some();
+    let unbound = bus::unbind(bus_id).await.unwrap_or(false);
+    removed || unbound
+}
+
+/// Revokes the share, also when published by another gftp process.
+/// Returns `false` if it was not published.
+pub(crate) async fn revoke_any(bus_id: &str) -> bool {
+    if revoke(bus_id).await {
+        return true;
+    }
+    let mut revoked = false;
+    for pid in other_publishers() {
+        let msg = RevokeShare {
+            bus_id: bus_id.to_string(),
+        };
+        revoked |= call(pid, msg).await.unwrap_or(false);
+    }
+    revoked
+}
+
+/// Lists shares published by this process
+pub fn list() -> Vec<ShareInfo> {
+    let mut shares = SHARES.with(|s| {
+        s.borrow()
+            .values()
+            .map(|share| share.info.clone())
+            .collect::<Vec<_>>()
+    });
+    shares.sort_by(|a, b| a.url.as_str().cmp(b.url.as_str()));
+    shares
+}
+
+/// Lists shares published by all running gftp processes
+pub async fn list_all() -> Vec<ShareInfo> {
+    let mut shares = list();
+    for pid in other_publishers() {
+        shares.extend(call(pid, ListShares {}).await.unwrap_or_default());
+    }
+    shares.sort_by(|a, b| a.url.as_str().cmp(b.url.as_str()));
+    shares
+}
+
+// =========================================== //
+// Control of shares from other processes
+// =========================================== //
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ListShares {}
+
+impl RpcMessage for ListShares {
+    const ID: &'static str = "ListShares";
+    type Item = Vec<ShareInfo>;
+    type Error = model::Error;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RevokeShare {
+    bus_id: String,
+}
+
+impl RpcMessage for RevokeShare {
+    const ID: &'static str = "RevokeShare";
+    type Item = bool;
+    type Error = model::Error;
+}
+
+fn control_bus_id(pid: u32) -> String {
+    format!("/local/gftp/shares/{}", pid)
+}
+
+fn publishers_dir() -> PathBuf {
+    std::env::temp_dir().join("gftp-shares")
+}
+
+fn bind_control() {
+    if CONTROL_BOUND.with(|bound| bound.replace(true)) {
+        return;
+    }
+    let pid = std::process::id();
+    let bus_id = control_bus_id(pid);
+    let _ = bus::bind(&bus_id, |_: ListShares| async { Ok(list()) });
+    let _ = bus::bind(&bus_id, |msg: RevokeShare| async move {
+        Ok(revoke(&msg.bus_id).await)
+    });
+
+    let marker = publishers_dir().join(pid.to_string());
+    if let Err(e) =
+        std::fs::create_dir_all(publishers_dir()).and_then(|_| std::fs::write(&marker, ""))
+    {
+        log::warn!("Shares won't be visible to other gftp processes. {}", e);
+    }
+}
+
+/// Removes the marker of this process, so that it's no longer asked for its shares.
+/// Should be called before a publishing process exits.
+pub fn unregister() {
+    if CONTROL_BOUND.with(|bound| bound.get()) {
+        let marker = publishers_dir().join(std::process::id().to_string());
+        let _ = std::fs::remove_file(marker);
+    }
+}
+
+/// Pids of other processes, which have published shares. Markers of processes,
+/// which have exited without removing them, are cleaned up.
+fn other_publishers() -> Vec<u32> {
+    let own = std::process::id();
+    let entries = match std::fs::read_dir(publishers_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .filter(|pid| *pid != own)
+        .filter(|pid| {
+            if exited(*pid) {
+                log::debug!("gftp process {} has exited", pid);
+                let _ = std::fs::remove_file(publishers_dir().join(pid.to_string()));
+                return false;
+            }
+            true
+        })
+        .collect()
+}
+
+/// Whether process `pid` is known not to be running.
+#[cfg(unix)]
+fn exited(pid: u32) -> bool {
+    use nix::errno::Errno;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    matches!(kill(Pid::from_raw(pid as i32), None), Err(Errno::ESRCH))
+}
+
+/// Liveness of other processes isn't checked, their markers are left in place.
+#[cfg(not(unix))]
+fn exited(_pid: u32) -> bool {
+    false
+}
+
+/// Sends `msg` to the control service of process `pid`. The process may be
+/// unreachable while running, e.g. when connected to another GSB router.
+async fn call<M: RpcMessage + Unpin>(pid: u32, msg: M) -> Option<M::Item> {
+    let request = bus::service(&control_bus_id(pid)).send(msg);
+    match tokio::time::timeout(CONTROL_TIMEOUT, request).await {
+        Ok(Ok(Ok(item))) => Some(item),
+        Ok(Ok(Err(e))) => {
+            log::warn!("gftp process {} failed: {}", pid, e);
+            None
+        }
+        Ok(Err(e)) => {
+            log::debug!("gftp process {} is unreachable: {}", pid, e);
+            None
+        }
+        Err(_) => {
+            log::warn!("gftp process {} didn't respond", pid);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(single_use: bool, expires: Option<Instant>) -> Share {
+        Share {
+            info: ShareInfo {
+                url: Url::parse("gftp://0x0000000000000000000000000000000000000000/a").unwrap(),
+                path: PathBuf::from("a"),
+                expires_at: None,
+                single_use,
+                claimed: false,
+            },
+            expires,
+            claimed_by: None,
+            remaining: 10,
+            served: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn single_use() {
+        let now = Instant::now();
+        let mut share = share(true, None);
+        share.authorize("0xa", now).unwrap();
+        share.authorize("0xa", now).unwrap();
+        assert!(share.info.claimed);
+        assert!(share.authorize("0xb", now).is_err());
+        assert!(!share.served("", 0, 6));
+        // Retried chunk
+        assert!(!share.served("", 0, 6));
+        assert!(share.served("", 6, 4));
+    }
+
+    #[test]
+    fn single_use_dir() {
+        let mut share = share(true, None);
+        assert!(!share.served("a", 0, 5));
+        assert!(!share.served("b", 0, 4));
+        assert!(!share.served("a", 0, 5));
+        assert!(share.served("b", 4, 1));
+    }
+
+    #[test]
+    fn expiry() {
+        let now = Instant::now();
+        let mut share = share(false, Some(now + Duration::from_secs(60)));
+        share.authorize("0xa", now).unwrap();
+        share.authorize("0xb", now).unwrap();
+        assert!(!share.served("", 0, 10));
+        assert!(share
+            .authorize("0xa", now + Duration::from_secs(60))
+            .is_err());
+    }
+
+    #[test]
+    fn token_url() {
+        let url = Url::parse("gftp://0x0000000000000000000000000000000000000000/abc").unwrap();
+        assert_eq!(extract_token(&url), None);
+        let token = new_token();
+        let url = with_token(url, &token);
+        assert_eq!(extract_token(&url), Some(token));
+    }
+}
@@ -1,8 +1,11 @@
+use crate::config::{Config, KeepaliveConfig, MessagesConfig};
+use crate::encoding::PayloadEncoding;
 use crate::model::{
     GsbApiError, ServiceListenResponse, ServicePath, ServiceRequest, ServiceResponse,
+    ServiceStatusResponse, WsQuery,
 };
 use crate::service::StartBuffering;
-use crate::services::{Bind, Find, Services, Unbind};
+use crate::services::{Bind, Find, List, Services, Unbind};
 use crate::{WsDisconnect, WsMessagesHandler};
 use actix::Addr;
 use actix_http::ws::{CloseCode, CloseReason};
@@ -13,19 +16,69 @@ use actix_web::{web, HttpRequest, Responder, Result};
 use actix_web_actors::ws::{self};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine as _};
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema, ScopeSpec};
 
-pub(crate) fn web_scope(services: Addr<Services>) -> Scope {
-    actix_web::web::scope(&format!("/{}", crate::GSB_API_PATH))
+pub(crate) fn web_scope(services: Addr<Services>, config: Config) -> Scope {
+    api_scope()
+        .into_scope()
         .app_data(Data::new(services))
-        .service(post_services)
-        .service(delete_services)
-        .service(get_service_messages)
+        .app_data(Data::new(config.messages))
+        .app_data(Data::new(config.keepalive))
+}
+
+pub(crate) fn api_spec() -> ScopeSpec {
+    api_scope().into_spec()
+}
+
+fn api_scope() -> ApiScope {
+    ApiScope::new("gsb-api", &format!("/{}", crate::GSB_API_PATH))
+        .route(
+            Operation::get("/services", "getServices")
+                .response(Schema::of::<Vec<ServiceStatusResponse>>()),
+            get_services,
+        )
+        .route(
+            Operation::post("/services", "bindServices")
+                .body(Schema::of::<ServiceRequest>())
+                .response(Schema::of::<ServiceResponse>()),
+            post_services,
+        )
+        .route(
+            Operation::delete("/services/{address}", "unbindServices"),
+            delete_services,
+        )
+        // WebSocket upgrade
+        .route(
+            Operation::get("/services/{address}", "getServiceMessages").response(Schema::Binary),
+            get_service_messages,
+        )
+}
+
+async fn get_services(
+    id: Identity,
+    services: Data<Addr<Services>>,
+) -> Result<impl Responder, GsbApiError> {
+    let statuses = services.send(List { owner: id.name }).await?;
+    let response = statuses
+        .into_iter()
+        .map(|(on, status)| ServiceStatusResponse {
+            service: ServiceResponse {
+                services_id: BASE64.encode(&on),
+                listen: ServiceListenResponse {
+                    on,
+                    components: status.components,
+                },
+            },
+            connected: status.connected,
+            idle_sec: status.idle.map(|idle| idle.as_secs()),
+        })
+        .collect::<Vec<_>>();
+    Ok(web::Json(response))
 }
 
-#[actix_web::post("/services")]
 async fn post_services(
     body: web::Json<ServiceRequest>,
-    _id: Identity,
+    id: Identity,
     services: Data<Addr<Services>>,
 ) -> Result<impl Responder, GsbApiError> {
     log::debug!("POST /services Body: {:?}", body);
@@ -35,6 +88,7 @@ async fn post_services(
     let bind = Bind {
         components: components.clone(),
         addr_prefix: on.clone(),
+        owner: id.name,
     };
     let response = services.send(bind).await;
     log::debug!("Service bind result: {:?}", response);
@@ -51,32 +105,37 @@ async fn post_services(
         .with_status(StatusCode::CREATED))
 }
 
-#[actix_web::delete("/services/{address}")]
 async fn delete_services(
     path: web::Path<ServicePath>,
-    _id: Identity,
+    id: Identity,
     services: Data<Addr<Services>>,
 ) -> Result<impl Responder, GsbApiError> {
     let addr = decode_addr(&path.address)?;
     log::debug!("DELETE service: {}", addr);
-    let unbind = Unbind { addr };
+    let unbind = Unbind {
+        addr,
+        owner: id.name,
+    };
     let response = services.send(unbind).await;
     log::debug!("Service delete result: {:?}", response);
     response??;
     Ok(web::Json(()))
 }
 
-#[actix_web::get("/services/{address}")]
 async fn get_service_messages(
     path: web::Path<ServicePath>,
+    query: web::Query<WsQuery>,
     req: HttpRequest,
     stream: web::Payload,
-    _id: Identity,
+    id: Identity,
     services: Data<Addr<Services>>,
+    config: Data<MessagesConfig>,
+    keepalive: Data<KeepaliveConfig>,
 ) -> Result<impl Responder, GsbApiError> {
     let addr = decode_addr(&path.address)?;
     log::debug!("GET WS service: {}", addr);
-    let service = services.send(Find { addr }).await??;
+    let owner = id.name;
+    let service = services.send(Find { addr, owner }).await??;
     if let Some(ws_handler) = service.send(StartBuffering).await? {
         let description =
             Some("Closing old WS connection in favour of new WS connection".to_string());
@@ -86,9 +145,17 @@ async fn get_service_messages(
     } else {
         log::debug!("No old WS connection");
     }
-    let handler = WsMessagesHandler { service };
+    let encoding = PayloadEncoding::negotiate(&req, query.into_inner().encoding);
+    log::debug!("WS payload encoding: {encoding:?}");
+    let handler = WsMessagesHandler::new(
+        service,
+        config.get_ref().clone(),
+        keepalive.get_ref().clone(),
+        encoding,
+    );
     let (_addr, resp) = ws::WsResponseBuilder::new(handler, &req, stream)
-        .protocols(&["gsb+flexbuffers"])
+        .protocols(&[encoding.protocol()])
+        .frame_size(config.max_frame_size)
         .start_with_addr()?;
     Ok(resp)
 }
@@ -112,10 +179,11 @@ fn decode_addr(addr_encoded: &str) -> Result<String, GsbApiError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::BufferConfig;
     use crate::model::ServiceListenRequest;
     use crate::{GsbApiService, GsbError, GSB_API_PATH};
     use actix::Actor;
-    use actix_http::ws::{self, CloseCode, CloseReason, Frame};
+    use actix_http::ws::{self, CloseCode, CloseReason, Frame, Item};
     use actix_test::{self, TestServer};
     use actix_web::App;
     use awc::error::WsClientError;
@@ -165,20 +233,44 @@ mod tests {
     const PAYLOAD_LEN: usize = 10;
 
     fn dummy_api() -> TestServer {
-        actix_test::start(|| {
+        dummy_api_w_config(Config::default())
+    }
+
+    fn dummy_api_w_config(config: Config) -> TestServer {
+        actix_test::start(move || {
             App::new()
                 .service(GsbApiService::rest_internal(
                     &TestContext {},
-                    Services::default().start(),
+                    Services::new(config.clone()).start(),
+                    config.clone(),
                 ))
                 .wrap(dummy_auth())
         })
     }
 
+    /// API authenticated as the given app-key, sharing bound services with other APIs.
+    fn dummy_api_w_app_key(services: Addr<Services>, app_key: &str) -> TestServer {
+        let config = Config::default();
+        let app_key = app_key.to_string();
+        actix_test::start(move || {
+            App::new()
+                .service(GsbApiService::rest_internal(
+                    &TestContext {},
+                    services.clone(),
+                    config.clone(),
+                ))
+                .wrap(dummy_auth_w_app_key(&app_key))
+        })
+    }
+
     fn dummy_auth() -> DummyAuth {
+        dummy_auth_w_app_key("dummy_node")
+    }
+
+    fn dummy_auth_w_app_key(app_key: &str) -> DummyAuth {
         let id = Identity {
             identity: NodeId::default(),
-            name: "dummy_node".to_string(),
+            name: app_key.to_string(),
             role: "dummy".to_string(),
         };
         DummyAuth::new(id)
@@ -312,6 +404,58 @@ mod tests {
         verify_delete_service(&mut api, &service_addr).await;
     }
 
+    #[actix_web::test]
+    #[serial]
+    async fn json_payload_test() {
+        let mut api = dummy_api();
+
+        let (bind_req, service_addr) = bind_get_chunk_service_req(&mut api);
+        let body =
+            verify_bind_service_response(bind_req, vec!["GetChunk".to_string()], &service_addr)
+                .await;
+
+        let services_path = format!("gsb-api/v1/services/{}?encoding=json", body.services_id);
+        let mut ws_frames = api.ws_at(&services_path).await.unwrap();
+
+        let gsb_endpoint = ya_service_bus::typed::service(service_addr.clone());
+
+        let (gsb_res, ws_res) = tokio::join!(
+            async {
+                gsb_endpoint
+                    .call(GetChunk {
+                        offset: u64::MIN,
+                        size: PAYLOAD_LEN as u64,
+                    })
+                    .await
+            },
+            async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let ws_req = match ws_frames.next().await {
+                    Some(Ok(Frame::Text(ws_req))) => {
+                        serde_json::from_slice::<TestWsRequest<GetChunk>>(&ws_req).unwrap()
+                    }
+                    msg => panic!("Unexpected msg: {:?}", msg),
+                };
+                let ws_res = json!({
+                    "id": ws_req.id,
+                    "payload": {
+                        "content": vec![7; ws_req.payload.size as usize],
+                        "offset": 0,
+                    },
+                });
+                ws_frames
+                    .send(ws::Message::Text(ws_res.to_string().into()))
+                    .await
+            }
+        );
+
+        ws_res.unwrap();
+        let gsb_res = gsb_res.unwrap().unwrap();
+        assert_eq!(gsb_res.content, vec![7; PAYLOAD_LEN]);
+
+        verify_delete_service(&mut api, &service_addr).await;
+    }
+
     #[actix_web::test]
     #[serial]
     async fn error_payload_test() {
@@ -485,6 +629,58 @@ mod tests {
         ));
     }
 
+    #[actix_web::test]
+    #[serial]
+    async fn services_are_scoped_to_app_key() {
+        let services = Services::new(Config::default()).start();
+        let mut owner_api = dummy_api_w_app_key(services.clone(), "owner");
+        let mut other_api = dummy_api_w_app_key(services, "other");
+
+        let (bind_req, service_addr) = bind_get_chunk_service_req(&mut owner_api);
+        let body =
+            verify_bind_service_response(bind_req, vec!["GetChunk".to_string()], &service_addr)
+                .await;
+        let services_path = format!("gsb-api/v1/services/{}", body.services_id);
+
+        let list = |api: &TestServer| {
+            let req = api.get(format!("/{}/{}", GSB_API_PATH, "services")).send();
+            async move {
+                let body = req.await.unwrap().body().await.unwrap();
+                serde_json::from_slice::<Vec<ServiceStatusResponse>>(&body).unwrap()
+            }
+        };
+        assert_eq!(list(&owner_api).await.len(), 1);
+        assert!(list(&other_api).await.is_empty());
+
+        let ws_frames = other_api.ws_at(&services_path).await;
+        assert!(matches!(
+            ws_frames.err(),
+            Some(WsClientError::InvalidResponseStatus(StatusCode::NOT_FOUND))
+        ));
+
+        let delete_resp = other_api
+            .delete(format!(
+                "/{}/{}/{}",
+                GSB_API_PATH,
+                "services",
+                BASE64.encode(&service_addr)
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(delete_resp.status(), StatusCode::NOT_FOUND);
+
+        // Address stays taken by the owner
+        let bind_resp = bind_get_chunk_service_req_w_address(&mut other_api, service_addr.clone())
+            .0
+            .await
+            .unwrap();
+        assert_eq!(bind_resp.status(), StatusCode::BAD_REQUEST);
+
+        owner_api.ws_at(&services_path).await.unwrap();
+        verify_delete_service(&mut owner_api, &service_addr).await;
+    }
+
     #[actix_web::test]
     #[serial]
     async fn api_400_error_on_ws_connect_to_incorrectly_encoded_service_address() {
@@ -863,4 +1059,169 @@ mod tests {
 
         assert!(ws_res_1.is_ok());
     }
+
+    #[actix_web::test]
+    #[serial]
+    async fn gsb_buffered_msgs_limits_test() {
+        let mut api = dummy_api_w_config(Config {
+            buffer: BufferConfig {
+                ttl: Duration::from_millis(300),
+                max_size: 1,
+            },
+            ..Default::default()
+        });
+
+        let (bind_req, service_addr) = bind_get_chunk_service_req(&mut api);
+        verify_bind_service_response(bind_req, vec!["GetChunk".to_string()], &service_addr).await;
+        let gsb_endpoint = ya_service_bus::typed::service(&service_addr);
+        let get_chunk = || GetChunk {
+            offset: u64::MIN,
+            size: PAYLOAD_LEN as u64,
+        };
+
+        let (buffered_res, rejected_res) = tokio::join!(gsb_endpoint.call(get_chunk()), async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            gsb_endpoint.call(get_chunk()).await
+        });
+        assert!(
+            matches!(rejected_res, Err(GsbError::GsbFailure(msg)) if msg.contains("Buffer of requests is full"))
+        );
+        assert!(
+            matches!(buffered_res, Err(GsbError::GsbFailure(msg)) if msg.contains("Request not delivered"))
+        );
+
+        verify_delete_service(&mut api, &service_addr).await;
+    }
+
+    #[actix_web::test]
+    #[serial]
+    async fn fragmented_ws_msgs_test() {
+        const FRAME_SIZE: usize = 32;
+        let mut config = Config::default();
+        config.messages.max_frame_size = FRAME_SIZE;
+        let mut api = dummy_api_w_config(config);
+
+        let (bind_req, service_addr) = bind_get_chunk_service_req(&mut api);
+        let body =
+            verify_bind_service_response(bind_req, vec!["GetChunk".to_string()], &service_addr)
+                .await;
+        let services_path = format!("gsb-api/v1/services/{}", body.services_id);
+        let mut ws_frames = api.ws_at(&services_path).await.unwrap();
+        let gsb_endpoint = ya_service_bus::typed::service(&service_addr);
+
+        let (gsb_res, ws_res) = tokio::join!(
+            gsb_endpoint.call(GetChunk {
+                offset: u64::MIN,
+                size: PAYLOAD_LEN as u64,
+            }),
+            async {
+                let mut ws_req = Vec::new();
+                loop {
+                    match ws_frames.next().await {
+                        Some(Ok(Frame::Continuation(Item::FirstBinary(fragment))))
+                        | Some(Ok(Frame::Continuation(Item::Continue(fragment)))) => {
+                            assert!(fragment.len() <= FRAME_SIZE);
+                            ws_req.extend_from_slice(&fragment);
+                        }
+                        Some(Ok(Frame::Continuation(Item::Last(fragment)))) => {
+                            ws_req.extend_from_slice(&fragment);
+                            break;
+                        }
+                        msg => panic!("Not expected msg: {:?}", msg),
+                    }
+                }
+                let ws_req = flexbuffers::from_slice::<TestWsRequest<GetChunk>>(&ws_req).unwrap();
+                let ws_res = TestWsResponse {
+                    id: ws_req.id,
+                    payload: GftpChunk {
+                        content: vec![7; ws_req.payload.size as usize],
+                        offset: 0,
+                    },
+                };
+                let ws_res = Bytes::from(flexbuffers::to_vec(ws_res).unwrap());
+                let chunks = ws_res.chunks(FRAME_SIZE).collect::<Vec<_>>();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let chunk = Bytes::copy_from_slice(chunk);
+                    let item = match i {
+                        0 => Item::FirstBinary(chunk),
+                        i if i == chunks.len() - 1 => Item::Last(chunk),
+                        _ => Item::Continue(chunk),
+                    };
+                    ws_frames.send(ws::Message::Continuation(item)).await?;
+                }
+                Ok::<_, awc::error::WsProtocolError>(())
+            }
+        );
+
+        ws_res.unwrap();
+        let gsb_res = gsb_res.unwrap().unwrap();
+        assert_eq!(gsb_res.content, vec![7; PAYLOAD_LEN]);
+
+        verify_delete_service(&mut api, &service_addr).await;
+    }
+
+    #[actix_web::test]
+    #[serial]
+    async fn ws_idle_timeout_test() {
+        let mut config = Config::default();
+        config.keepalive.ping_interval = Duration::from_millis(100);
+        config.keepalive.idle_timeout = Duration::from_millis(500);
+        let mut api = dummy_api_w_config(config);
+
+        let (bind_req, service_addr) = bind_get_chunk_service_req(&mut api);
+        let body =
+            verify_bind_service_response(bind_req, vec!["GetChunk".to_string()], &service_addr)
+                .await;
+        let services_path = format!("gsb-api/v1/services/{}", body.services_id);
+        let mut ws_frames = api.ws_at(&services_path).await.unwrap();
+
+        let list_services = |api: &TestServer| {
+            let req = api.get(format!("/{GSB_API_PATH}/services")).send();
+            async move {
+                let body = req.await.unwrap().body().await.unwrap();
+                serde_json::from_slice::<Vec<ServiceStatusResponse>>(&body).unwrap()
+            }
+        };
+
+        // Client answering pings stays connected
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(800);
+        while let Ok(frame) = tokio::time::timeout_at(deadline, ws_frames.next()).await {
+            match frame {
+                Some(Ok(Frame::Ping(msg))) => ws_frames.send(ws::Message::Pong(msg)).await.unwrap(),
+                msg => panic!("Unexpected msg: {:?}", msg),
+            }
+        }
+        let listed = list_services(&api).await;
+        let listed = listed
+            .iter()
+            .find(|s| s.service.listen.on == service_addr)
+            .unwrap();
+        assert!(listed.connected);
+        assert_eq!(listed.service.services_id, body.services_id);
+
+        // Client ignoring pings gets disconnected
+        let ws_msg = loop {
+            match ws_frames.next().await {
+                Some(Ok(Frame::Ping(_))) => continue,
+                msg => break msg,
+            }
+        };
+        assert!(matches!(
+            ws_msg,
+            Some(Ok(Frame::Close(Some(CloseReason {
+                code: CloseCode::Away,
+                description: Some(description)
+            })))) if description.starts_with("Idle timeout")));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let listed = list_services(&api).await;
+        let listed = listed
+            .iter()
+            .find(|s| s.service.listen.on == service_addr)
+            .unwrap();
+        assert!(!listed.connected);
+        assert_eq!(listed.idle_sec, None);
+
+        verify_delete_service(&mut api, &service_addr).await;
+    }
 }
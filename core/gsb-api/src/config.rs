@@ -0,0 +1,122 @@
+use lazy_static::lazy_static;
+use std::time::Duration;
+use structopt::StructOpt;
+
+lazy_static! {
+    pub(crate) static ref CONFIG: Config = Config::from_env().unwrap_or_else(|e| {
+        log::warn!("Invalid GSB API config, using defaults: {e}");
+        Config::default()
+    });
+}
+
+#[derive(StructOpt, Clone, Debug)]
+pub(crate) struct Config {
+    #[structopt(flatten)]
+    pub buffer: BufferConfig,
+    #[structopt(flatten)]
+    pub messages: MessagesConfig,
+    #[structopt(flatten)]
+    pub keepalive: KeepaliveConfig,
+}
+
+/// Limits of GSB requests buffered while bound service has no WebSocket connection.
+#[derive(StructOpt, Clone, Debug)]
+pub(crate) struct BufferConfig {
+    /// Buffered requests older than this are answered with an error.
+    #[structopt(
+        env = "YAGNA_GSB_API_BUFFER_TTL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "5min"
+    )]
+    pub ttl: Duration,
+    /// Maximum number of buffered requests per bound service.
+    #[structopt(env = "YAGNA_GSB_API_BUFFER_SIZE", default_value = "1000")]
+    pub max_size: usize,
+}
+
+/// Size limits of messages exchanged over WebSocket.
+#[derive(StructOpt, Clone, Debug)]
+pub(crate) struct MessagesConfig {
+    /// Maximum size of a single WebSocket frame. Larger GSB requests are sent
+    /// in fragments and clients should fragment larger responses too.
+    #[structopt(env = "YAGNA_GSB_API_MAX_FRAME_SIZE", default_value = "65536")]
+    pub max_frame_size: usize,
+    /// Maximum size of a whole (reassembled) message.
+    #[structopt(env = "YAGNA_GSB_API_MAX_MESSAGE_SIZE", default_value = "33554432")]
+    pub max_message_size: usize,
+}
+
+/// Liveness checks of WebSocket connections.
+#[derive(StructOpt, Clone, Debug)]
+pub(crate) struct KeepaliveConfig {
+    /// Interval of pings sent to WebSocket clients.
+    #[structopt(
+        env = "YAGNA_GSB_API_PING_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "30s"
+    )]
+    pub ping_interval: Duration,
+    /// Connection is closed and requests are buffered when client sends
+    /// nothing (including pongs) for this long.
+    #[structopt(
+        env = "YAGNA_GSB_API_IDLE_TIMEOUT",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "90s"
+    )]
+    pub idle_timeout: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Config, structopt::clap::Error> {
+        // Empty command line arguments, because we want to use ENV fallback
+        // or default values if ENV variables are not set.
+        Config::from_iter_safe(&[""])
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            buffer: BufferConfig {
+                ttl: Duration::from_secs(5 * 60),
+                max_size: 1000,
+            },
+            messages: MessagesConfig {
+                max_frame_size: 64 * 1024,
+                max_message_size: 32 * 1024 * 1024,
+            },
+            keepalive: KeepaliveConfig {
+                ping_interval: Duration::from_secs(30),
+                idle_timeout: Duration::from_secs(90),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_structopt_buffer() {
+        let c = Config::from_env().unwrap();
+        assert_eq!(300, c.buffer.ttl.as_secs());
+        assert_eq!(1000, c.buffer.max_size);
+    }
+
+    #[test]
+    fn test_default_structopt_messages() {
+        let c = Config::from_env().unwrap();
+        let d = Config::default();
+        assert_eq!(d.messages.max_frame_size, c.messages.max_frame_size);
+        assert_eq!(d.messages.max_message_size, c.messages.max_message_size);
+    }
+
+    #[test]
+    fn test_default_structopt_keepalive() {
+        let c = Config::from_env().unwrap();
+        let d = Config::default();
+        assert_eq!(d.keepalive.ping_interval, c.keepalive.ping_interval);
+        assert_eq!(d.keepalive.idle_timeout, c.keepalive.idle_timeout);
+    }
+}
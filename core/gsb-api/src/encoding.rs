@@ -0,0 +1,172 @@
+//! Encoding of messages exchanged over WebSocket.
+//!
+//! GSB payloads are flexbuffers encoded. Clients lacking flexbuffers support
+//! can negotiate JSON encoding, which is converted to and from flexbuffers here.
+use actix_web::http::header;
+use actix_web::HttpRequest;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{WsRequest, WsResponse, WsResponseMsg};
+
+pub(crate) const FLEXBUFFERS_PROTOCOL: &str = "gsb+flexbuffers";
+pub(crate) const JSON_PROTOCOL: &str = "gsb+json";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum PayloadEncoding {
+    #[default]
+    Flexbuffers,
+    Json,
+}
+
+impl PayloadEncoding {
+    /// JSON encoding is selected either with `gsb+json` WebSocket subprotocol,
+    /// or with `encoding=json` query parameter (`query`).
+    pub fn negotiate(req: &HttpRequest, query: Option<PayloadEncoding>) -> Self {
+        if let Some(encoding) = query {
+            return encoding;
+        }
+        let json_requested = req
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|protocol| protocol.trim() == JSON_PROTOCOL);
+        match json_requested {
+            true => PayloadEncoding::Json,
+            false => PayloadEncoding::Flexbuffers,
+        }
+    }
+
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            PayloadEncoding::Flexbuffers => FLEXBUFFERS_PROTOCOL,
+            PayloadEncoding::Json => JSON_PROTOCOL,
+        }
+    }
+}
+
+/// Encodes GSB request as JSON text message.
+pub(crate) fn json_request(request: &WsRequest) -> Result<String, String> {
+    let payload: Value = flexbuffers::from_slice(&request.payload)
+        .map_err(|err| format!("Failed to convert payload to JSON. Err: {err}"))?;
+    let msg = json!({
+        "id": request.id,
+        "component": request.component,
+        "payload": payload,
+    });
+    Ok(msg.to_string())
+}
+
+/// Reads JSON response (with either `payload` or `error` field) and converts it to flexbuffers.
+pub(crate) fn read_json_response(buffer: &[u8]) -> Result<WsResponse, String> {
+    let mut response: Value = serde_json::from_slice(buffer)
+        .map_err(|err| format!("Invalid JSON response. Err: {err}"))?;
+    let id = match response.get("id").and_then(Value::as_str) {
+        Some(id) => id.to_string(),
+        None => return Err("Missing response id.".to_string()),
+    };
+    let result = if let Some(error) = response.get_mut("error").map(Value::take) {
+        json!({ "Err": error })
+    } else if let Some(payload) = response.get_mut("payload").map(Value::take) {
+        json!({ "Ok": payload })
+    } else {
+        return Err(format!("Missing 'payload' and 'error' fields. Id: {id}."));
+    };
+    let msg = flexbuffers::to_vec(&result)
+        .map_err(|err| format!("Failed to convert payload. Id: {id}. Err: {err}"))?;
+    Ok(WsResponse {
+        id,
+        response: WsResponseMsg::Message(msg),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    struct TestPayload {
+        file_size: u64,
+        name: String,
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let req = TestRequest::default()
+            .insert_header((header::SEC_WEBSOCKET_PROTOCOL, "gsb+flexbuffers, gsb+json"))
+            .to_http_request();
+        assert_eq!(
+            PayloadEncoding::negotiate(&req, None),
+            PayloadEncoding::Json
+        );
+
+        let req = TestRequest::default()
+            .insert_header((header::SEC_WEBSOCKET_PROTOCOL, FLEXBUFFERS_PROTOCOL))
+            .to_http_request();
+        assert_eq!(
+            PayloadEncoding::negotiate(&req, None),
+            PayloadEncoding::Flexbuffers
+        );
+        assert_eq!(
+            PayloadEncoding::negotiate(&req, Some(PayloadEncoding::Json)),
+            PayloadEncoding::Json
+        );
+    }
+
+    #[test]
+    fn test_json_request() {
+        let payload = TestPayload {
+            file_size: 11,
+            name: "file".to_string(),
+        };
+        let request = WsRequest {
+            id: "1".to_string(),
+            component: "GetMetadata".to_string(),
+            payload: flexbuffers::to_vec(&payload).unwrap(),
+        };
+        let msg: Value = serde_json::from_str(&json_request(&request).unwrap()).unwrap();
+        assert_eq!(
+            msg,
+            json!({
+                "id": "1",
+                "component": "GetMetadata",
+                "payload": { "fileSize": 11, "name": "file" },
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_json_response() {
+        let response = json!({ "id": "1", "payload": { "fileSize": 11, "name": "file" } });
+        let response = read_json_response(response.to_string().as_bytes()).unwrap();
+        assert_eq!(response.id, "1");
+        let msg = match response.response {
+            WsResponseMsg::Message(msg) => msg,
+            WsResponseMsg::Error(err) => panic!("Unexpected error: {err}"),
+        };
+        let payload: Result<TestPayload, String> = flexbuffers::from_slice(&msg).unwrap();
+        assert_eq!(
+            payload,
+            Ok(TestPayload {
+                file_size: 11,
+                name: "file".to_string(),
+            })
+        );
+
+        let response = json!({ "id": "2", "error": "failed" });
+        let response = read_json_response(response.to_string().as_bytes()).unwrap();
+        let msg = match response.response {
+            WsResponseMsg::Message(msg) => msg,
+            WsResponseMsg::Error(err) => panic!("Unexpected error: {err}"),
+        };
+        let payload: Result<TestPayload, String> = flexbuffers::from_slice(&msg).unwrap();
+        assert_eq!(payload, Err("failed".to_string()));
+
+        assert!(read_json_response(br#"{"id": "3"}"#).is_err());
+    }
+}
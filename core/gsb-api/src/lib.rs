@@ -1,21 +1,28 @@
 mod api;
+mod config;
+mod encoding;
 mod model;
 mod service;
 mod services;
 
+use crate::config::{Config, KeepaliveConfig, MessagesConfig};
+use crate::encoding::PayloadEncoding;
 use crate::service::{DropMessages, StartBuffering, StartRelaying};
 use actix::prelude::*;
 use actix::ActorFutureExt;
 use actix::{Actor, Addr, Handler, StreamHandler};
 use actix_http::ws::CloseCode;
-use actix_http::ws::{CloseReason, ProtocolError};
+use actix_http::ws::{CloseReason, Item, ProtocolError};
 use actix_web_actors::ws::{self, WebsocketContext};
 
+use bytes::{Bytes, BytesMut};
 use flexbuffers::{BuilderOptions, Reader};
 use futures::FutureExt;
 use serde::{Deserialize, Serialize};
 use service::Service;
 use services::Services;
+use std::convert::TryInto;
+use std::time::{Duration, Instant};
 
 pub const GSB_API_PATH: &str = "gsb-api/v1";
 
@@ -27,14 +34,23 @@ impl GsbApiService {
     }
 
     pub fn rest<Context>(ctx: &Context) -> actix_web::Scope {
-        Self::rest_internal(ctx, crate::services::SERVICES.clone())
+        Self::rest_internal(
+            ctx,
+            crate::services::SERVICES.clone(),
+            crate::config::CONFIG.clone(),
+        )
     }
 
     pub(crate) fn rest_internal<Context>(
         _: &Context,
         services: Addr<Services>,
+        config: Config,
     ) -> actix_web::Scope {
-        api::web_scope(services)
+        api::web_scope(services, config)
+    }
+
+    pub fn api_spec() -> ya_service_api_web::openapi::ScopeSpec {
+        api::api_spec()
     }
 }
 
@@ -120,13 +136,45 @@ impl From<&DropMessages> for WsResponseMsg {
 #[rtype(result = "()")]
 struct WsDisconnect(CloseReason);
 
+/// Time since the WebSocket client sent anything.
+#[derive(Message, Debug)]
+#[rtype(result = "Duration")]
+pub(crate) struct GetIdleTime;
+
 pub(crate) struct WsMessagesHandler {
     service: Addr<Service>,
+    config: MessagesConfig,
+    encoding: PayloadEncoding,
+    keepalive: KeepaliveConfig,
+    /// Time of the last frame (including pong) received from the client.
+    last_seen: Instant,
+    /// Fragments of a message split into continuation frames.
+    fragments: Option<BytesMut>,
 }
 
 impl WsMessagesHandler {
+    pub fn new(
+        service: Addr<Service>,
+        config: MessagesConfig,
+        keepalive: KeepaliveConfig,
+        encoding: PayloadEncoding,
+    ) -> Self {
+        WsMessagesHandler {
+            service,
+            config,
+            encoding,
+            keepalive,
+            last_seen: Instant::now(),
+            fragments: None,
+        }
+    }
+
     pub fn handle(&mut self, buffer: &bytes::Bytes, ctx: &mut WebsocketContext<WsMessagesHandler>) {
-        match read_ws_response(buffer) {
+        let response = match self.encoding {
+            PayloadEncoding::Flexbuffers => read_ws_response(buffer),
+            PayloadEncoding::Json => encoding::read_json_response(buffer),
+        };
+        match response {
             Ok(ws_response) => {
                 self.service
                     .send(ws_response)
@@ -147,6 +195,49 @@ impl WsMessagesHandler {
         }
     }
 
+    fn handle_continuation(&mut self, item: Item, ctx: &mut WebsocketContext<WsMessagesHandler>) {
+        let (fragment, last) = match item {
+            Item::FirstBinary(fragment) => {
+                if self.fragments.is_some() {
+                    return self.close(ctx, CloseCode::Protocol, "Unfinished fragmented msg.");
+                }
+                self.fragments = Some(BytesMut::new());
+                (fragment, false)
+            }
+            Item::FirstText(fragment) if self.encoding == PayloadEncoding::Json => {
+                if self.fragments.is_some() {
+                    return self.close(ctx, CloseCode::Protocol, "Unfinished fragmented msg.");
+                }
+                self.fragments = Some(BytesMut::new());
+                (fragment, false)
+            }
+            Item::FirstText(_) => {
+                return self.close(ctx, CloseCode::Unsupported, "Text msg unsupported.")
+            }
+            Item::Continue(fragment) => (fragment, false),
+            Item::Last(fragment) => (fragment, true),
+        };
+        let buffer = match self.fragments.as_mut() {
+            Some(buffer) => buffer,
+            None => return self.close(ctx, CloseCode::Protocol, "Missing first msg fragment."),
+        };
+        if buffer.len() + fragment.len() > self.config.max_message_size {
+            self.fragments = None;
+            let desc = format!(
+                "Msg exceeds size limit of {} bytes.",
+                self.config.max_message_size
+            );
+            return self.close(ctx, CloseCode::Size, &desc);
+        }
+        buffer.extend_from_slice(&fragment);
+        if last {
+            if let Some(msg) = self.fragments.take() {
+                log::debug!("WS fragmented msg (len {})", msg.len());
+                self.handle(&msg.freeze(), ctx);
+            }
+        }
+    }
+
     fn start_buffering(
         &self,
         reason: Option<CloseReason>,
@@ -173,6 +264,20 @@ impl WsMessagesHandler {
         }));
     }
 
+    /// Pings the client or, when it stopped responding, closes the connection
+    /// so that requests are buffered until the client reconnects.
+    fn check_liveness(&mut self, ctx: &mut WebsocketContext<WsMessagesHandler>) {
+        let idle = self.last_seen.elapsed();
+        if idle < self.keepalive.idle_timeout {
+            ctx.ping(b"");
+            return;
+        }
+        let desc = format!("Idle timeout. No response for {}s.", idle.as_secs());
+        self.close(ctx, CloseCode::Away, &desc);
+        // Half-open connection would never finish the stream
+        ctx.run_later(Duration::ZERO, |_, ctx| ctx.stop());
+    }
+
     fn close(&self, ctx: &mut WebsocketContext<WsMessagesHandler>, code: CloseCode, desc: &str) {
         let description = Some(desc.to_string());
         let reason = Some(CloseReason { code, description });
@@ -182,6 +287,22 @@ impl WsMessagesHandler {
     }
 }
 
+/// Splits message into continuation frames of at most `frame_size` bytes.
+fn fragments(mut msg: Bytes, frame_size: usize, text: bool) -> Vec<Item> {
+    let frame_size = frame_size.max(1);
+    let first = msg.split_to(frame_size.min(msg.len()));
+    let first = match text {
+        true => Item::FirstText(first),
+        false => Item::FirstBinary(first),
+    };
+    let mut fragments = vec![first];
+    while msg.len() > frame_size {
+        fragments.push(Item::Continue(msg.split_to(frame_size)));
+    }
+    fragments.push(Item::Last(msg));
+    fragments
+}
+
 fn read_ws_response(buffer: &bytes::Bytes) -> Result<WsResponse, String> {
     let response =
         Reader::get_root(&**buffer).map_err(|err| format!("Missing root. Err: {err}"))?;
@@ -221,42 +342,76 @@ impl Handler<WsRequest> for WsMessagesHandler {
             request.id,
             request.component
         );
-        let mut request_builder = flexbuffers::Builder::new(BuilderOptions::empty());
-        let mut request_map_builder = request_builder.start_map();
-        request_map_builder.push("id", &*request.id);
-        request_map_builder.push("component", &*request.component);
-        let payload_map_builder = request_map_builder.start_map("payload");
-
-        let payload = Reader::get_root(&*request.payload).unwrap(); //TODO handle error
-        let payload_map = payload.as_map(); //TODO check type before as_map
-        flexbuffer_util::clone_map(payload_map_builder, &payload_map).unwrap(); //TODO handle error
-        request_map_builder.end_map();
-        ctx.binary(request_builder.view().to_vec());
+        let (msg, text) = match self.encoding {
+            PayloadEncoding::Flexbuffers => (flexbuffers_request(&request), false),
+            PayloadEncoding::Json => {
+                let msg = encoding::json_request(&request).map_err(|err| anyhow::anyhow!(err))?;
+                (Bytes::from(msg), true)
+            }
+        };
+        if msg.len() > self.config.max_message_size {
+            anyhow::bail!(
+                "Request size {} exceeds limit of {} bytes",
+                msg.len(),
+                self.config.max_message_size
+            );
+        }
+        if msg.len() <= self.config.max_frame_size {
+            match text {
+                true => ctx.write_raw(ws::Message::Text(msg.try_into()?)),
+                false => ctx.binary(msg),
+            }
+        } else {
+            log::debug!("WS request (id: {}) sent in fragments", request.id);
+            for fragment in fragments(msg, self.config.max_frame_size, text) {
+                ctx.write_raw(ws::Message::Continuation(fragment));
+            }
+        }
         Ok(())
     }
 }
 
+fn flexbuffers_request(request: &WsRequest) -> Bytes {
+    let mut request_builder = flexbuffers::Builder::new(BuilderOptions::empty());
+    let mut request_map_builder = request_builder.start_map();
+    request_map_builder.push("id", &*request.id);
+    request_map_builder.push("component", &*request.component);
+    let payload_map_builder = request_map_builder.start_map("payload");
+
+    let payload = Reader::get_root(&*request.payload).unwrap(); //TODO handle error
+    let payload_map = payload.as_map(); //TODO check type before as_map
+    flexbuffer_util::clone_map(payload_map_builder, &payload_map).unwrap(); //TODO handle error
+    request_map_builder.end_map();
+
+    Bytes::copy_from_slice(request_builder.view())
+}
+
 impl StreamHandler<Result<actix_http::ws::Message, ProtocolError>> for WsMessagesHandler {
     fn handle(
         &mut self,
         item: Result<actix_http::ws::Message, ProtocolError>,
         ctx: &mut Self::Context,
     ) {
+        if item.is_ok() {
+            self.last_seen = Instant::now();
+        }
         match item {
             Ok(msg) => match msg {
                 ws::Message::Binary(msg) => {
                     log::debug!("WS Binary (len {})", msg.len());
                     self.handle(&msg, ctx);
                 }
+                ws::Message::Text(msg) if self.encoding == PayloadEncoding::Json => {
+                    log::debug!("WS Text (len {})", msg.len());
+                    self.handle(msg.as_bytes(), ctx);
+                }
                 ws::Message::Text(_) => {
                     self.close(ctx, CloseCode::Unsupported, "Text msg unsupported.")
                 }
-                ws::Message::Continuation(_) => {
-                    self.close(ctx, CloseCode::Unsupported, "Continuation msg unsupported.")
-                }
+                ws::Message::Continuation(item) => self.handle_continuation(item, ctx),
                 ws::Message::Close(close_reason) => self.start_buffering(close_reason, ctx),
                 ws::Message::Ping(message) => ctx.pong(&message),
-                ws::Message::Pong(_) => log::warn!("Pong handling is not implemented."),
+                ws::Message::Pong(_) => log::trace!("WS Pong"),
                 ws::Message::Nop => log::warn!("Nop handling is not implemented."),
             },
             Err(cause) => ctx.close(Some(CloseReason {
@@ -268,6 +423,9 @@ impl StreamHandler<Result<actix_http::ws::Message, ProtocolError>> for WsMessage
 
     fn started(&mut self, ctx: &mut Self::Context) {
         log::debug!("WS handler started.");
+        ctx.run_interval(self.keepalive.ping_interval, |handler, ctx| {
+            handler.check_liveness(ctx)
+        });
         self.service
             .send(StartRelaying {
                 ws_handler: ctx.address(),
@@ -306,6 +464,14 @@ impl Handler<WsDisconnect> for WsMessagesHandler {
     }
 }
 
+impl Handler<GetIdleTime> for WsMessagesHandler {
+    type Result = <GetIdleTime as actix::Message>::Result;
+
+    fn handle(&mut self, _: GetIdleTime, _: &mut Self::Context) -> Self::Result {
+        self.last_seen.elapsed()
+    }
+}
+
 mod flexbuffer_util {
     use flexbuffers::{FlexBufferType, MapBuilder, MapReader, Pushable, Reader, VectorBuilder};
 
@@ -1,10 +1,12 @@
 use crate::{
+    encoding::PayloadEncoding,
     services::{BindError, FindError, UnbindError},
     GsbError,
 };
 use actix::MailboxError;
 use actix_http::StatusCode;
 use actix_web::{HttpResponse, ResponseError};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ya_client_model::ErrorMessage;
 
@@ -14,13 +16,20 @@ pub(crate) struct ServicePath {
     pub address: String,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WsQuery {
+    /// Payload encoding, alternatively negotiated with WebSocket subprotocol.
+    pub encoding: Option<PayloadEncoding>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ServiceRequest {
     pub(crate) listen: ServiceListenRequest,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ServiceResponse {
     pub(crate) listen: ServiceListenResponse,
@@ -30,7 +39,18 @@ pub(crate) struct ServiceResponse {
     pub(crate) services_id: String,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServiceStatusResponse {
+    #[serde(flatten)]
+    pub(crate) service: ServiceResponse,
+    /// Whether a WebSocket client is connected. Requests are buffered otherwise.
+    pub(crate) connected: bool,
+    /// Seconds since the connected WebSocket client sent anything (including pongs).
+    pub(crate) idle_sec: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ServiceListenRequest {
     /// GSB services address prefix.
@@ -41,7 +61,7 @@ pub(crate) struct ServiceListenRequest {
     pub(crate) components: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ServiceListenResponse {
     /// GSB services address prefix.
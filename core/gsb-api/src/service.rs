@@ -1,5 +1,8 @@
+use crate::config::BufferConfig;
 use crate::services::Bind;
-use crate::{GsbError, WsDisconnect, WsMessagesHandler, WsRequest, WsResponse, WsResponseMsg};
+use crate::{
+    GetIdleTime, GsbError, WsDisconnect, WsMessagesHandler, WsRequest, WsResponse, WsResponseMsg,
+};
 use actix::prelude::*;
 use actix::{Actor, Addr, Context, Handler, Message};
 use actix_http::ws::CloseReason;
@@ -14,6 +17,7 @@ use std::{
     future::Future,
     mem,
     result::Result::{Err, Ok},
+    time::{Duration, Instant},
 };
 use ya_service_bus::RpcRawCall;
 
@@ -23,9 +27,27 @@ pub(crate) struct Service {
     /// Service addresses with same prefix but different RpcMessage types.
     addresses: HashSet<String>,
     msg_handler: Box<dyn MessagesHandler>,
+    buffer_config: BufferConfig,
 }
 
 impl Service {
+    pub fn new(bind: Bind, buffer_config: BufferConfig) -> Self {
+        let msg_handler: Box<dyn MessagesHandler> =
+            Box::new(BufferingHandler::new(buffer_config.clone()));
+        // convert to error and return it when e.g. components empty
+        let addr_prefix = bind.addr_prefix;
+        let mut addresses = HashSet::new();
+        for component in bind.components {
+            addresses.insert(format!("{addr_prefix}/{component}"));
+        }
+        Service {
+            addr_prefix,
+            addresses,
+            msg_handler,
+            buffer_config,
+        }
+    }
+
     fn addr_prefix_to_component(addr: &str) -> String {
         addr.chars()
             .rev()
@@ -46,28 +68,14 @@ fn test_addr_prefix_to_component() {
     );
 }
 
-impl From<Bind> for Service {
-    fn from(bind: Bind) -> Self {
-        let msg_handler: Box<dyn MessagesHandler> = Box::<BufferingHandler>::default();
-        // convert to error and return it when e.g. components empty
-        let addr_prefix = bind.addr_prefix;
-        let mut addresses = HashSet::new();
-        for component in bind.components {
-            addresses.insert(format!("{addr_prefix}/{component}"));
-        }
-        Service {
-            addr_prefix,
-            addresses,
-            msg_handler,
-        }
-    }
-}
-
 impl Actor for Service {
     type Context = Context<Self>;
 
     fn started(&mut self, ctx: &mut Self::Context) {
         _ = ya_service_bus::actix_rpc::bind_raw(&self.addr_prefix, ctx.address().recipient());
+        ctx.run_interval(self.buffer_config.ttl, |service, _| {
+            service.msg_handler.drop_expired()
+        });
     }
 }
 
@@ -155,6 +163,44 @@ impl Handler<WsResponse> for Service {
     }
 }
 
+/// Components and WebSocket connection liveness of a bound service.
+#[derive(Clone, Debug)]
+pub(crate) struct ServiceStatus {
+    pub components: Vec<String>,
+    pub connected: bool,
+    /// Time since the WebSocket client sent anything, if connected.
+    pub idle: Option<Duration>,
+}
+
+#[derive(Message, Debug)]
+#[rtype(result = "ServiceStatus")]
+pub(crate) struct GetStatus;
+
+impl Handler<GetStatus> for Service {
+    type Result = ResponseFuture<<GetStatus as Message>::Result>;
+
+    fn handle(&mut self, _: GetStatus, _ctx: &mut Self::Context) -> Self::Result {
+        let mut components = self
+            .addresses
+            .iter()
+            .map(|addr| Service::addr_prefix_to_component(addr))
+            .collect::<Vec<_>>();
+        components.sort();
+        let ws_handler = self.msg_handler.ws_handler();
+        Box::pin(async move {
+            let idle = match ws_handler {
+                Some(ws_handler) => ws_handler.send(GetIdleTime).await.ok(),
+                None => None,
+            };
+            ServiceStatus {
+                components,
+                connected: idle.is_some(),
+                idle,
+            }
+        })
+    }
+}
+
 /// Message making message handler to relay messages.
 #[derive(Message, Debug)]
 #[rtype(result = "()")]
@@ -213,6 +259,9 @@ trait MessagesHandler {
 
     fn drop_messages(&mut self, msg: DropMessages);
 
+    /// Responds with an error to requests buffered longer than configured TTL.
+    fn drop_expired(&mut self) {}
+
     fn ws_handler(&self) -> Option<Addr<WsMessagesHandler>>;
 }
 
@@ -231,11 +280,11 @@ fn drop_messages(
 }
 
 async fn send_pending_requests(
-    mut pending_msgs: Vec<WsRequest>,
+    mut pending_msgs: Vec<BufferedRequest>,
     ws_handler: Addr<WsMessagesHandler>,
     service: Addr<Service>,
 ) {
-    while let Some(msg) = pending_msgs.pop() {
+    while let Some(BufferedRequest { msg, .. }) = pending_msgs.pop() {
         log::debug!("Sending buffered message: {}", msg.id);
         let id = msg.id.clone();
         if let Some(error) = match ws_handler.send(msg).await {
@@ -258,11 +307,28 @@ async fn send_pending_requests(
     }
 }
 
+#[derive(Debug)]
+struct BufferedRequest {
+    msg: WsRequest,
+    buffered_at: Instant,
+}
+
 /// Messages handler buffering GSB requests until WS (re)connects.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct BufferingHandler {
     pending_senders: HashMap<String, Sender<WsResponse>>,
-    pending_msgs: Vec<WsRequest>,
+    pending_msgs: Vec<BufferedRequest>,
+    config: BufferConfig,
+}
+
+impl BufferingHandler {
+    fn new(config: BufferConfig) -> Self {
+        BufferingHandler {
+            pending_senders: Default::default(),
+            pending_msgs: Default::default(),
+            config,
+        }
+    }
 }
 
 impl MessagesHandler for BufferingHandler {
@@ -275,6 +341,7 @@ impl MessagesHandler for BufferingHandler {
         ws_handler: Addr<WsMessagesHandler>,
         ctx: &mut <Service as Actor>::Context,
     ) -> Option<(Box<dyn MessagesHandler>, LocalBoxFuture<'static, ()>)> {
+        self.drop_expired();
         let pending_senders = mem::take(&mut self.pending_senders);
         let pending_msgs = mem::take(&mut self.pending_msgs);
 
@@ -289,6 +356,7 @@ impl MessagesHandler for BufferingHandler {
             Box::new(RelayingHandler {
                 pending_senders,
                 ws_handler,
+                buffer_config: self.config.clone(),
             }),
             sync_future,
         ))
@@ -299,10 +367,21 @@ impl MessagesHandler for BufferingHandler {
         msg: WsRequest,
     ) -> Pin<Box<dyn Future<Output = Result<Receiver<WsResponse>, anyhow::Error>>>> {
         log::debug!("Buffering handler request (id: {})", msg.id);
+        self.drop_expired();
+        if self.pending_msgs.len() >= self.config.max_size {
+            let error = anyhow!(
+                "Buffer of requests is full ({} requests). No WebSocket connection.",
+                self.config.max_size
+            );
+            return Box::pin(actix::fut::ready(Err(error)));
+        }
         let id = msg.id.clone();
         let (sender, receiver) = oneshot::channel();
         self.pending_senders.insert(id, sender);
-        self.pending_msgs.push(msg);
+        self.pending_msgs.push(BufferedRequest {
+            msg,
+            buffered_at: Instant::now(),
+        });
         Box::pin(actix::fut::ready(Ok(receiver)))
     }
 
@@ -320,6 +399,30 @@ impl MessagesHandler for BufferingHandler {
         drop_messages(&mut self.pending_senders, &drop_messages_msg);
     }
 
+    fn drop_expired(&mut self) {
+        let ttl = self.config.ttl;
+        let (expired, pending): (Vec<_>, Vec<_>) = mem::take(&mut self.pending_msgs)
+            .into_iter()
+            .partition(|request| request.buffered_at.elapsed() >= ttl);
+        self.pending_msgs = pending;
+
+        if !expired.is_empty() {
+            log::debug!("Dropping {} expired buffered messages", expired.len());
+        }
+        for BufferedRequest { msg, .. } in expired {
+            if let Some(sender) = self.pending_senders.remove(&msg.id) {
+                let error = GsbError::GsbFailure(format!(
+                    "Request not delivered within {}s. No WebSocket connection.",
+                    ttl.as_secs()
+                ));
+                let _ = sender.send(WsResponse {
+                    id: msg.id,
+                    response: WsResponseMsg::Error(error),
+                });
+            }
+        }
+    }
+
     fn ws_handler(&self) -> Option<Addr<WsMessagesHandler>> {
         None
     }
@@ -330,6 +433,7 @@ impl MessagesHandler for BufferingHandler {
 struct RelayingHandler {
     pending_senders: HashMap<String, Sender<WsResponse>>,
     ws_handler: Addr<WsMessagesHandler>,
+    buffer_config: BufferConfig,
 }
 
 impl MessagesHandler for RelayingHandler {
@@ -339,6 +443,7 @@ impl MessagesHandler for RelayingHandler {
         Some(Box::new(BufferingHandler {
             pending_senders,
             pending_msgs,
+            config: self.buffer_config.clone(),
         }))
     }
 
@@ -1,8 +1,10 @@
-use crate::service::{DropMessages, Service};
+use crate::config::Config;
+use crate::service::{DropMessages, GetStatus, Service, ServiceStatus};
 use actix::prelude::*;
 use actix::{Actor, Addr, Context, Handler, Message};
 use actix_http::ws::CloseReason;
 use actix_web_actors::ws;
+use futures::future::join_all;
 use lazy_static::lazy_static;
 use std::{
     collections::HashMap,
@@ -11,12 +13,38 @@ use std::{
 use thiserror::Error;
 
 lazy_static! {
-    pub(crate) static ref SERVICES: Addr<Services> = Services::default().start();
+    pub(crate) static ref SERVICES: Addr<Services> =
+        Services::new(crate::config::CONFIG.clone()).start();
 }
 
+/// Bound services stay registered until deleted by their owner, so after WebSocket reconnect
+/// client gets requests buffered in the meantime without binding services again.
 #[derive(Default)]
 pub(crate) struct Services {
-    services: HashMap<String, Addr<Service>>,
+    services: HashMap<String, BoundService>,
+    config: Config,
+}
+
+struct BoundService {
+    /// Name of the app-key which bound the service.
+    owner: String,
+    service: Addr<Service>,
+}
+
+impl Services {
+    pub fn new(config: Config) -> Self {
+        Services {
+            services: Default::default(),
+            config,
+        }
+    }
+
+    fn owned(&self, addr: &str, owner: &str) -> Option<&Addr<Service>> {
+        self.services
+            .get(addr)
+            .filter(|bound| bound.owner == owner)
+            .map(|bound| &bound.service)
+    }
 }
 
 impl Actor for Services {
@@ -36,6 +64,7 @@ pub(crate) enum BindError {
 pub(crate) struct Bind {
     pub components: Vec<String>,
     pub addr_prefix: String,
+    pub owner: String,
 }
 
 impl Handler<Bind> for Services {
@@ -51,9 +80,10 @@ impl Handler<Bind> for Services {
         if self.services.contains_key(&addr) {
             return Err(BindError::DuplicatedService(addr));
         }
-        let service = Service::from(msg).start();
-        log::debug!("Created new service (addr: {})", addr);
-        self.services.insert(addr, service);
+        let owner = msg.owner.clone();
+        let service = Service::new(msg, self.config.buffer.clone()).start();
+        log::debug!("Created new service (addr: {}, owner: {})", addr, owner);
+        self.services.insert(addr, BoundService { owner, service });
         Ok(())
     }
 }
@@ -78,6 +108,7 @@ impl From<MailboxError> for UnbindError {
 #[rtype(result = "Result<(), UnbindError>")]
 pub(crate) struct Unbind {
     pub addr: String,
+    pub owner: String,
 }
 
 impl Handler<Unbind> for Services {
@@ -91,7 +122,10 @@ impl Handler<Unbind> for Services {
                 ))
             });
         }
-        let some_service = self.services.remove(&msg.addr);
+        let some_service = match self.owned(&msg.addr, &msg.owner) {
+            Some(_) => self.services.remove(&msg.addr).map(|bound| bound.service),
+            None => None,
+        };
         Box::pin(async move {
             match some_service {
                 Some(service) => {
@@ -123,6 +157,7 @@ pub(crate) enum FindError {
 #[rtype(result = "Result<Addr<Service>, FindError>")]
 pub(crate) struct Find {
     pub addr: String,
+    pub owner: String,
 }
 
 impl Handler<Find> for Services {
@@ -132,9 +167,39 @@ impl Handler<Find> for Services {
         if msg.addr.is_empty() {
             return Err(FindError::EmptyAddress);
         }
-        if let Some(service) = self.services.get(&msg.addr) {
+        if let Some(service) = self.owned(&msg.addr, &msg.owner) {
             return Ok(service.clone());
         }
         Err(FindError::ServiceNotFound(msg.addr))
     }
 }
+
+/// Lists services bound by the owner, with their connection status.
+#[derive(Message, Debug)]
+#[rtype(result = "Vec<(String, ServiceStatus)>")]
+pub(crate) struct List {
+    pub owner: String,
+}
+
+impl Handler<List> for Services {
+    type Result = ResponseFuture<<List as Message>::Result>;
+
+    fn handle(&mut self, msg: List, _ctx: &mut Self::Context) -> Self::Result {
+        let owned = self
+            .services
+            .iter()
+            .filter(|(_, bound)| bound.owner == msg.owner)
+            .map(|(addr, bound)| (addr.clone(), bound.service.clone()))
+            .collect::<Vec<_>>();
+        Box::pin(async move {
+            let statuses = join_all(owned.into_iter().map(|(addr, service)| async move {
+                let status = service.send(GetStatus).await.ok()?;
+                Some((addr, status))
+            }))
+            .await;
+            let mut statuses = statuses.into_iter().flatten().collect::<Vec<_>>();
+            statuses.sort_by(|(a, _), (b, _)| a.cmp(b));
+            statuses
+        })
+    }
+}
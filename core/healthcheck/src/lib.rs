@@ -1,3 +1,4 @@
+pub mod model;
 mod service;
 
 pub use service::HealthcheckService;
@@ -0,0 +1,48 @@
+//! Aggregated health of daemon services.
+
+use serde::{Deserialize, Serialize};
+use ya_service_api_interfaces::HealthStatus;
+use ya_service_bus::RpcMessage;
+
+pub const BUS_ID: &str = "/local/healthcheck";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceHealth {
+    pub service: String,
+    pub state: HealthState,
+    pub details: Option<String>,
+}
+
+impl ServiceHealth {
+    pub fn new(service: &str, status: HealthStatus) -> Self {
+        let state = match &status {
+            HealthStatus::Healthy => HealthState::Healthy,
+            HealthStatus::Degraded(_) => HealthState::Degraded,
+            HealthStatus::Unhealthy(_) => HealthState::Unhealthy,
+        };
+        ServiceHealth {
+            service: service.to_string(),
+            state,
+            details: status.details().map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// Health of services implementing the `health` component. Bound by the daemon.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GetHealth {}
+
+impl RpcMessage for GetHealth {
+    const ID: &'static str = "GetHealth";
+    type Item = Vec<ServiceHealth>;
+    type Error = String;
+}
@@ -14,6 +14,8 @@ use ya_core_model::payment::local::{PaymentDriverStatus, PaymentDriverStatusErro
 
 use ya_service_bus::{timeout::IntoTimeoutFuture, typed::service, RpcEndpoint};
 
+use crate::model::{GetHealth, HealthState, BUS_ID as HEALTH_BUS_ID};
+
 pub const HEALTHCHECK_API_PATH: &str = "/healthcheck";
 
 pub fn web_scope() -> actix_web::Scope {
@@ -126,6 +128,54 @@ async fn market_healthcheck() -> Result<(), HttpResponse> {
     Ok(())
 }
 
+async fn services_healthcheck() -> Result<(), HttpResponse> {
+    let result = service(HEALTH_BUS_ID)
+        .call(GetHealth {})
+        .timeout(Some(Duration::from_secs(5)))
+        .await;
+
+    let result = match result {
+        Ok(ok) => ok,
+        Err(_elapsed) => return Err(errors::internal("internal-timeout", "services-check")),
+    };
+
+    let result = match result {
+        Ok(ok) => ok,
+        Err(gsb_err) => {
+            log::warn!("Healtcheck failed due to {gsb_err}");
+            return Err(errors::internal("gsb-error", "services-check"));
+        }
+    };
+
+    let statuses = match result {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("Healtcheck failed due to {err}");
+            return Err(errors::internal("services-error", "services-check"));
+        }
+    };
+
+    for status in statuses.iter() {
+        if status.state == HealthState::Degraded {
+            log::warn!(
+                "Service {} is degraded: {}",
+                status.service,
+                status.details.as_deref().unwrap_or_default()
+            );
+        }
+    }
+
+    let unhealthy: Vec<_> = statuses
+        .into_iter()
+        .filter(|status| status.state == HealthState::Unhealthy)
+        .collect();
+    if !unhealthy.is_empty() {
+        return Err(errors::services(unhealthy));
+    }
+
+    Ok(())
+}
+
 #[actix_web::get("")]
 async fn healthcheck() -> impl Responder {
     if let Err(response) = payment_healthcheck().await {
@@ -137,6 +187,9 @@ async fn healthcheck() -> impl Responder {
     if let Err(response) = market_healthcheck().await {
         return response;
     }
+    if let Err(response) = services_healthcheck().await {
+        return response;
+    }
 
     HttpResponse::Ok().json(json!({"status": "ok"}))
 }
@@ -151,6 +204,8 @@ mod errors {
     use std::str::FromStr;
     use ya_client::model::payment::DriverStatusProperty;
 
+    use crate::model::ServiceHealth;
+
     const CONTENT_TYPE_PROBLEM_JSON: (&str, &str) = ("Content-Type", "application/problem+json");
 
     pub fn internal(instance: &str, step: &str) -> HttpResponse {
@@ -209,6 +264,40 @@ mod errors {
             .json(problem)
     }
 
+    pub fn services(unhealthy: Vec<ServiceHealth>) -> HttpResponse {
+        let extensions = HashMap::<String, Value>::from_iter([
+            (
+                "step".to_string(),
+                Value::String("services-check".to_string()),
+            ),
+            (
+                "problems".to_string(),
+                Value::Array(
+                    unhealthy
+                        .into_iter()
+                        .map(|status| match status.details {
+                            Some(details) => format!("{}: {}", status.service, details),
+                            None => status.service,
+                        })
+                        .map(Value::String)
+                        .collect(),
+                ),
+            ),
+        ]);
+
+        let problem = ProblemDetails::new()
+            .with_detail(
+                "One or more services are unhealthy. Run `yagna status --health` to diagnose",
+            )
+            .with_type(Uri::from_static("/healthcheck/services-unhealthy"))
+            .with_instance(Uri::from_static("/healthcheck/services-unhealthy"))
+            .with_extensions(extensions);
+
+        HttpResponse::InternalServerError()
+            .insert_header(CONTENT_TYPE_PROBLEM_JSON)
+            .json(problem)
+    }
+
     pub fn market_bcast_timeout(last_bcast_age: chrono::Duration) -> HttpResponse {
         let extensions = HashMap::<String, Value>::from_iter([
             (
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+
+ALTER TABLE app_key ADD COLUMN "expires_date" DATETIME NULL;
+ALTER TABLE app_key ADD COLUMN "previous_key" VARCHAR(255) NULL;
+ALTER TABLE app_key ADD COLUMN "previous_key_expires" DATETIME NULL;
@@ -11,6 +11,9 @@ use anyhow::Context;
 // autoconfiguration
 const ENV_AUTOCONF_PK: &str = "YAGNA_AUTOCONF_ID_SECRET";
 const ENV_AUTOCONF_APP_KEY: &str = "YAGNA_AUTOCONF_APPKEY";
+// identities signed by an external signer daemon
+const ENV_REMOTE_SIGNER_ADDR: &str = "YAGNA_REMOTE_SIGNER_ADDR";
+const ENV_REMOTE_SIGNER_IDS: &str = "YAGNA_REMOTE_SIGNER_IDS";
 
 pub fn preconfigured_identity(password: Protected) -> anyhow::Result<Option<IdentityKey>> {
     let secret_hex: Vec<u8> = match env::var(ENV_AUTOCONF_PK) {
@@ -35,3 +38,28 @@ pub fn preconfigured_node_id() -> anyhow::Result<Option<NodeId>> {
 pub fn preconfigured_appkey() -> Option<String> {
     env::var(ENV_AUTOCONF_APP_KEY).ok()
 }
+
+/// GSB address of an external signer (e.g. bound via the GSB API) together with
+/// the identities it holds private keys for.
+pub fn remote_signer() -> anyhow::Result<Option<(String, Vec<NodeId>)>> {
+    let addr = match env::var(ENV_REMOTE_SIGNER_ADDR) {
+        Ok(v) if !v.trim().is_empty() => v.trim().to_string(),
+        _ => return Ok(None),
+    };
+    let ids = env::var(ENV_REMOTE_SIGNER_IDS)
+        .with_context(|| {
+            format!(
+                "{} requires {}",
+                ENV_REMOTE_SIGNER_ADDR, ENV_REMOTE_SIGNER_IDS
+            )
+        })?
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse()
+                .with_context(|| format!("Invalid node id in {}: {}", ENV_REMOTE_SIGNER_IDS, id))
+        })
+        .collect::<anyhow::Result<Vec<NodeId>>>()?;
+    Ok(Some((addr, ids)))
+}
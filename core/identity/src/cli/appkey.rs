@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
 use structopt::*;
 
 use ya_core_model::appkey as model;
@@ -22,6 +23,21 @@ pub enum AppKeyCommand {
         /// Set cors policy for request made using this app-key.
         #[structopt(long)]
         allow_origins: Vec<String>,
+        /// App-key expires after given time (e.g. "30days").
+        #[structopt(long)]
+        expires_in: Option<humantime::Duration>,
+    },
+    /// Issue a new key for the app-key, keeping the current one valid for a grace period.
+    Rotate {
+        name: String,
+        #[structopt(long)]
+        id: Option<String>,
+        /// Time the current key remains valid.
+        #[structopt(long, default_value = "1h")]
+        grace_period: humantime::Duration,
+        /// New key expires after given time. New key doesn't expire if not set.
+        #[structopt(long)]
+        expires_in: Option<humantime::Duration>,
     },
     Drop {
         name: String,
@@ -61,6 +77,7 @@ impl AppKeyCommand {
                 role,
                 id,
                 allow_origins: allow_origin,
+                expires_in,
             } => {
                 let identity = match id {
                     Some(id) => {
@@ -83,6 +100,7 @@ impl AppKeyCommand {
                     role: role.clone(),
                     identity,
                     allow_origins: allow_origin.clone(),
+                    expires: expires_in.map(expiration_date).transpose()?,
                 };
                 let key = gsb.local().send(create).await??;
                 Ok(CommandOutput::Object(serde_json::to_value(key)?))
@@ -99,6 +117,22 @@ impl AppKeyCommand {
                     .unwrap();
                 Ok(CommandOutput::NoOutput)
             }
+            AppKeyCommand::Rotate {
+                name,
+                id,
+                grace_period,
+                expires_in,
+            } => {
+                let rotate = model::Rotate {
+                    name: name.clone(),
+                    identity: id.clone(),
+                    grace_period_secs: grace_period.as_secs(),
+                    expires: expires_in.map(expiration_date).transpose()?,
+                    current_key: None,
+                };
+                let appkey = gsb.local().send(rotate).await??;
+                Ok(CommandOutput::Object(serde_json::to_value(appkey)?))
+            }
             AppKeyCommand::Show { name } => {
                 let appkey = gsb
                     .local()
@@ -128,6 +162,7 @@ impl AppKeyCommand {
                         "id".into(),
                         "role".into(),
                         "created".into(),
+                        "expires".into(),
                     ],
                     values: result
                         .0
@@ -135,7 +170,7 @@ impl AppKeyCommand {
                         .map(|app_key| {
                             serde_json::json! {[
                                 app_key.name, app_key.key, app_key.identity,
-                                app_key.role, app_key.created_date,
+                                app_key.role, app_key.created_date, app_key.expires_date,
                             ]}
                         })
                         .collect(),
@@ -145,3 +180,7 @@ impl AppKeyCommand {
         }
     }
 }
+
+fn expiration_date(expires_in: humantime::Duration) -> Result<NaiveDateTime> {
+    Ok(Utc::now().naive_utc() + chrono::Duration::from_std(expires_in.into())?)
+}
@@ -1,6 +1,6 @@
 pub use crate::dao::Error as DaoError;
 pub use crate::db::models::{AppKey, Role};
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 
 use diesel::{ExpressionMethods, RunQueryDsl};
@@ -49,6 +49,7 @@ impl<'c> AppKeyDao<'c> {
         role: String,
         identity: NodeId,
         cors_allow_origin: Vec<String>,
+        expires: Option<NaiveDateTime>,
     ) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
         use crate::db::schema::role as role_dsl;
@@ -69,6 +70,7 @@ impl<'c> AppKeyDao<'c> {
                     app_key_dsl::identity_id.eq(identity),
                     app_key_dsl::created_date.eq(Utc::now().naive_utc()),
                     app_key_dsl::allow_origins.eq(cors_allow_origin),
+                    app_key_dsl::expires_date.eq(expires),
                 ))
                 .execute(conn)?;
 
@@ -77,6 +79,7 @@ impl<'c> AppKeyDao<'c> {
         .await
     }
 
+    /// Finds app-key by its current key or by the key replaced by rotation.
     pub async fn get(&self, key: String) -> Result<(AppKey, Role)> {
         use crate::db::schema::app_key as app_key_dsl;
         use crate::db::schema::role as role_dsl;
@@ -84,7 +87,11 @@ impl<'c> AppKeyDao<'c> {
         readonly_transaction(self.pool, "app_key_dao_get", move |conn| {
             let result = app_key_dsl::table
                 .inner_join(role_dsl::table)
-                .filter(app_key_dsl::key.eq(key))
+                .filter(
+                    app_key_dsl::key
+                        .eq(&key)
+                        .or(app_key_dsl::previous_key.eq(&key)),
+                )
                 .first(conn)?;
 
             Ok(result)
@@ -155,6 +162,65 @@ impl<'c> AppKeyDao<'c> {
         .await
     }
 
+    /// Replaces the key with `new_key`. Current key becomes the previous key
+    /// valid until `previous_key_expires`.
+    ///
+    /// When `current_key` is set (app-key rotating itself), it must be the current key,
+    /// and `expires` is clamped to the expiration date of the current key.
+    pub async fn rotate(
+        &self,
+        name: String,
+        identity: Option<String>,
+        new_key: String,
+        previous_key_expires: NaiveDateTime,
+        mut expires: Option<NaiveDateTime>,
+        current_key: Option<String>,
+    ) -> Result<(AppKey, Role)> {
+        use crate::db::schema::app_key as app_key_dsl;
+        use crate::db::schema::role as role_dsl;
+
+        self.with_transaction("app_key_dao_rotate", move |conn| {
+            let mut query = app_key_dsl::table
+                .inner_join(role_dsl::table)
+                .filter(app_key_dsl::name.eq(&name))
+                .into_boxed();
+            if let Some(id) = identity {
+                query = query.filter(app_key_dsl::identity_id.eq(id));
+            }
+            let (app_key, _): (AppKey, Role) =
+                query.first(conn).optional()?.ok_or(DaoError::NotFound)?;
+
+            // replaced key can't outlive its own expiration date
+            let previous_key_expires = app_key.expires_date.map_or(previous_key_expires, |limit| {
+                previous_key_expires.min(limit)
+            });
+            if let Some(current_key) = current_key {
+                // previous key is accepted only to fetch the new one
+                if current_key != app_key.key {
+                    return Err(DaoError::Forbidden);
+                }
+                if let Some(limit) = app_key.expires_date {
+                    expires = Some(expires.map_or(limit, |expires| expires.min(limit)));
+                }
+            }
+
+            diesel::update(app_key_dsl::table.find(app_key.id))
+                .set((
+                    app_key_dsl::previous_key.eq(app_key.key),
+                    app_key_dsl::previous_key_expires.eq(previous_key_expires),
+                    app_key_dsl::key.eq(new_key),
+                    app_key_dsl::expires_date.eq(expires),
+                ))
+                .execute(conn)?;
+
+            Ok(app_key_dsl::table
+                .inner_join(role_dsl::table)
+                .filter(app_key_dsl::id.eq(app_key.id))
+                .first(conn)?)
+        })
+        .await
+    }
+
     pub async fn remove(&self, name: String, identity: Option<String>) -> Result<()> {
         use crate::db::schema::app_key as app_key_dsl;
 
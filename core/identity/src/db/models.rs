@@ -30,6 +30,9 @@ pub struct AppKey {
     pub identity_id: NodeId,
     pub created_date: NaiveDateTime,
     pub allow_origins: Option<String>,
+    pub expires_date: Option<NaiveDateTime>,
+    pub previous_key: Option<String>,
+    pub previous_key_expires: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -51,6 +54,10 @@ impl AppKey {
                 .allow_origins
                 .map(|allowed| serde_json::from_str(&allowed).unwrap_or(vec![]))
                 .unwrap_or(vec![]),
+            expires_date: self.expires_date,
+            previous_key: self.previous_key.zip(self.previous_key_expires).map(
+                |(key, expires_date)| ya_core_model::appkey::PreviousKey { key, expires_date },
+            ),
         }
     }
 }
@@ -9,6 +9,9 @@ diesel::table! {
         identity_id -> Text,
         created_date -> Timestamp,
         allow_origins -> Nullable<Text>,
+        expires_date -> Nullable<Timestamp>,
+        previous_key -> Nullable<Text>,
+        previous_key_expires -> Nullable<Timestamp>,
     }
 }
 
@@ -16,6 +16,9 @@ use ya_persistence::executor::DbExecutor;
 
 use crate::dao::AppKeyDao;
 
+/// Longest time the previous key stays valid after rotation.
+const MAX_GRACE_PERIOD_SECS: u64 = 30 * 24 * 3600;
+
 #[derive(Default)]
 struct Subscription {
     subscriptions: HashMap<u64, String>,
@@ -71,6 +74,8 @@ pub async fn preconfigured_to_appkey_model(
         identity: node_id,
         created_date,
         allow_origins: vec![],
+        expires_date: None,
+        previous_key: None,
     })
 }
 
@@ -135,6 +140,7 @@ pub async fn activate(db: &DbExecutor, gsb: Arc<GsbBindPoints>) -> anyhow::Resul
                             create.role,
                             create.identity,
                             create.allow_origins,
+                            create.expires,
                         )
                         .await
                         .map_err(model::Error::internal)
@@ -248,6 +254,51 @@ pub async fn activate(db: &DbExecutor, gsb: Arc<GsbBindPoints>) -> anyhow::Resul
         });
     }
 
+    {
+        let db = db.clone();
+        let preconfigured_appkey = preconfigured_appkey.clone();
+        let create_tx = tx.clone();
+        let _ = bus::bind(gsb.local_addr(), move |rotate: model::Rotate| {
+            let key = Uuid::new_v4().to_simple().to_string();
+            let db = db.clone();
+            let preconfigured_appkey = preconfigured_appkey.clone();
+            let mut create_tx = create_tx.clone();
+            async move {
+                if preconfigured_appkey.is_some() && model::AUTOCONFIGURED_KEY_NAME == rotate.name {
+                    return Err(model::Error::bad_request(
+                        "Cannot rotate autoconfigured key",
+                    ));
+                }
+
+                if rotate.grace_period_secs > MAX_GRACE_PERIOD_SECS {
+                    return Err(model::Error::bad_request(format!(
+                        "Grace period can't exceed {MAX_GRACE_PERIOD_SECS} seconds"
+                    )));
+                }
+                let previous_key_expires = Utc::now()
+                    .naive_utc()
+                    .checked_add_signed(chrono::Duration::seconds(rotate.grace_period_secs as i64))
+                    .ok_or_else(|| model::Error::bad_request("Invalid grace period"))?;
+                let (appkey, role) = db
+                    .as_dao::<AppKeyDao>()
+                    .rotate(
+                        rotate.name,
+                        rotate.identity,
+                        key,
+                        previous_key_expires,
+                        rotate.expires,
+                        rotate.current_key,
+                    )
+                    .await
+                    .map_err(Into::<model::Error>::into)?;
+
+                let appkey = appkey.to_core_model(role);
+                let _ = create_tx.send(AppKeyEvent::NewKey(appkey.clone())).await;
+                Ok(appkey)
+            }
+        });
+    }
+
     {
         let create_tx = tx;
         let db = db.clone();
@@ -3,10 +3,11 @@ use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::bail;
 use chrono::Utc;
-use ethsign::{KeyFile, Protected, PublicKey};
+use ethsign::{KeyFile, Protected, PublicKey, Signature};
 use futures::lock::Mutex;
 use futures::prelude::*;
 
@@ -44,8 +45,12 @@ pub struct IdentityService {
     sender: futures::channel::mpsc::UnboundedSender<IdentityEvent>,
     subscription: Rc<RefCell<Subscription>>,
     db: DbExecutor,
+    /// Identities whose private keys are held by an external signer, mapped to its GSB address.
+    remote: HashMap<NodeId, String>,
 }
 
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
 fn to_info(default_key: &NodeId, key: &IdentityKey) -> model::IdentityInfo {
     let node_id = key.id();
     let is_default = *default_key == node_id;
@@ -58,6 +63,79 @@ fn to_info(default_key: &NodeId, key: &IdentityKey) -> model::IdentityInfo {
     }
 }
 
+fn to_remote_info(default_key: &NodeId, node_id: NodeId) -> model::IdentityInfo {
+    model::IdentityInfo {
+        alias: None,
+        node_id,
+        is_locked: false,
+        is_default: *default_key == node_id,
+        deleted: false,
+    }
+}
+
+/// Forwards `Sign` to the external signer. Only the public key is known locally,
+/// so the signer is expected to respond with a recoverable (v, r, s) signature.
+async fn remote_sign(addr: String, sign: model::Sign) -> Result<Vec<u8>, model::Error> {
+    log::debug!("Forwarding sign request for {} to {}", sign.node_id, addr);
+    let (node_id, payload) = (sign.node_id, sign.payload.clone());
+    let signature = tokio::time::timeout(REMOTE_SIGNER_TIMEOUT, bus::service(&addr).send(sign))
+        .await
+        .map_err(|_| model::Error::new_err_msg(format!("remote signer {} timed out", addr)))?
+        .map_err(|e| model::Error::new_err_msg(format!("remote signer {}: {}", addr, e)))??;
+
+    verify_signer(&node_id, &payload, &signature).map_err(|e| {
+        model::Error::new_err_msg(format!(
+            "remote signer {} returned invalid signature: {}",
+            addr, e
+        ))
+    })?;
+    Ok(signature)
+}
+
+/// Checks that `signature` in the (v, r, s) layout produced by `IdentityKey::sign`
+/// was made with the key of `node_id`.
+fn verify_signer(node_id: &NodeId, payload: &[u8], signature: &[u8]) -> anyhow::Result<()> {
+    if signature.len() != 65 {
+        bail!("expected 65 bytes, got {}", signature.len());
+    }
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+
+    let pub_key = Signature {
+        v: signature[0],
+        r,
+        s,
+    }
+    .recover(payload)
+    .map_err(|e| anyhow::anyhow!("can't recover signer: {}", e))?;
+    let signer = NodeId::from(pub_key.address().as_ref());
+    if signer != *node_id {
+        bail!("signed by {} instead of {}", signer, node_id);
+    }
+    Ok(())
+}
+
+async fn remote_pub_key(addr: String, node_id: NodeId) -> Result<Vec<u8>, model::Error> {
+    let key = tokio::time::timeout(
+        REMOTE_SIGNER_TIMEOUT,
+        bus::service(&addr).send(model::GetPubKey(node_id)),
+    )
+    .await
+    .map_err(|_| model::Error::new_err_msg(format!("remote signer {} timed out", addr)))?
+    .map_err(|e| model::Error::new_err_msg(format!("remote signer {}: {}", addr, e)))??;
+
+    let pub_key = PublicKey::from_slice(&key).map_err(model::Error::new_err_msg)?;
+    if NodeId::from(pub_key.address().as_ref()) != node_id {
+        return Err(model::Error::new_err_msg(format!(
+            "remote signer {} returned public key not matching {}",
+            addr, node_id
+        )));
+    }
+    Ok(key)
+}
+
 fn send_event(s: Ref<Subscription>, event: IdentityEvent) -> impl Future<Output = ()> {
     let subscriptions: Vec<String> = s.subscriptions.clone();
     log::debug!("sending event: {:?} to {:?}", event, subscriptions);
@@ -139,6 +217,22 @@ impl IdentityService {
             let _ = ids.insert(key.id(), key);
         }
 
+        let mut remote: HashMap<NodeId, String> = Default::default();
+        if let Some((addr, remote_ids)) = crate::autoconf::remote_signer()? {
+            for node_id in remote_ids {
+                if ids.contains_key(&node_id) {
+                    log::warn!(
+                        "identity {} has a local key, ignoring remote signer {}",
+                        node_id,
+                        addr
+                    );
+                    continue;
+                }
+                log::info!("using remote signer {} for identity {}", addr, node_id);
+                remote.insert(node_id, addr.clone());
+            }
+        }
+
         Ok(IdentityService {
             default_key,
             db,
@@ -146,6 +240,7 @@ impl IdentityService {
             sender,
             subscription,
             alias_to_id,
+            remote,
         })
     }
 
@@ -167,6 +262,9 @@ impl IdentityService {
 
     pub fn get_by_id(&self, node_id: &NodeId) -> Result<Option<model::IdentityInfo>, model::Error> {
         let id = match self.ids.get(node_id) {
+            None if self.remote.contains_key(node_id) => {
+                return Ok(Some(to_remote_info(&self.default_key, *node_id)))
+            }
             None => return Ok(None),
             Some(id) => id,
         };
@@ -186,9 +284,19 @@ impl IdentityService {
             .ids
             .values()
             .map(|id_key| to_info(&self.default_key, id_key))
+            .chain(
+                self.remote
+                    .keys()
+                    .map(|node_id| to_remote_info(&self.default_key, *node_id)),
+            )
             .collect())
     }
 
+    /// GSB address of the external signer holding the key of `node_id`, if any.
+    pub fn remote_signer(&self, node_id: &NodeId) -> Option<String> {
+        self.remote.get(node_id).cloned()
+    }
+
     pub async fn create_identity(
         &mut self,
         alias: Option<String>,
@@ -462,6 +570,12 @@ impl IdentityService {
         &mut self,
         key_id: model::GetKeyFile,
     ) -> Result<String, model::Error> {
+        if let Some(addr) = self.remote.get(&key_id.0) {
+            return Err(model::Error::new_err_msg(format!(
+                "key of {} is held by remote signer {}",
+                key_id.0, addr
+            )));
+        }
         let key = self.get_key_by_id(&key_id.0)?;
         key.to_key_file().map_err(model::Error::new_err_msg)
     }
@@ -563,7 +677,13 @@ impl IdentityService {
         let this = me.clone();
         let _ = bus::bind(gsb.local_addr(), move |sign: model::Sign| {
             let this = this.clone();
-            async move { this.lock().await.sign(sign.node_id, sign.payload).await }
+            async move {
+                let remote = this.lock().await.remote_signer(&sign.node_id);
+                match remote {
+                    Some(addr) => remote_sign(addr, sign).await,
+                    None => this.lock().await.sign(sign.node_id, sign.payload).await,
+                }
+            }
         });
         let this = me.clone();
         let _ = bus::bind(gsb.local_addr(), move |subscribe: model::Subscribe| {
@@ -579,6 +699,10 @@ impl IdentityService {
         let _ = bus::bind(gsb.local_addr(), move |node_id: model::GetPubKey| {
             let this = this.clone();
             async move {
+                let remote = this.lock().await.remote_signer(&node_id.0);
+                if let Some(addr) = remote {
+                    return remote_pub_key(addr, node_id.0).await;
+                }
                 this.lock()
                     .await
                     .get_pub_key(node_id)
@@ -687,3 +811,30 @@ async fn get_default_identity_key(gsb: Arc<GsbBindPoints>) -> anyhow::Result<mod
         .await??
         .ok_or_else(|| anyhow::anyhow!("No default Identity found"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethsign::SecretKey;
+
+    fn sign(secret: &SecretKey, payload: &[u8]) -> Vec<u8> {
+        let signature = secret.sign(payload).unwrap();
+        let mut bytes = vec![signature.v];
+        bytes.extend_from_slice(&signature.r);
+        bytes.extend_from_slice(&signature.s);
+        bytes
+    }
+
+    #[test]
+    fn test_verify_signer() {
+        let secret = SecretKey::from_raw(&[1u8; 32]).unwrap();
+        let other = SecretKey::from_raw(&[2u8; 32]).unwrap();
+        let node_id = NodeId::from(secret.public().address().as_ref());
+        let payload = [7u8; 32];
+
+        verify_signer(&node_id, &payload, &sign(&secret, &payload)).unwrap();
+        assert!(verify_signer(&node_id, &payload, &sign(&other, &payload)).is_err());
+        assert!(verify_signer(&node_id, &[8u8; 32], &sign(&secret, &payload)).is_err());
+        assert!(verify_signer(&node_id, &payload, &sign(&secret, &payload)[..64]).is_err());
+    }
+}
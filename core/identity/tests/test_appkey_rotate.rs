@@ -0,0 +1,93 @@
+use chrono::{Duration, NaiveDateTime, Timelike, Utc};
+use test_context::test_context;
+
+use ya_core_model::appkey::{self as model, AppKey};
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::temp_dir;
+use ya_framework_mocks::identity::RealIdentity;
+use ya_framework_mocks::net::MockNet;
+use ya_framework_mocks::node::MockNode;
+use ya_service_bus::RpcEndpoint;
+
+async fn rotate(
+    identity: &RealIdentity,
+    grace_period_secs: u64,
+    expires: Option<NaiveDateTime>,
+    current_key: Option<&str>,
+) -> anyhow::Result<Result<AppKey, model::Error>> {
+    Ok(identity
+        .gsb_appkey()
+        .local()
+        .send(model::Rotate {
+            name: "rotated".to_string(),
+            identity: None,
+            grace_period_secs,
+            expires,
+            current_key: current_key.map(ToString::to_string),
+        })
+        .await?)
+}
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_appkey_rotate(_ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("test_appkey_rotate")?;
+    let dir = dir.path();
+
+    let net = MockNet::new().bind();
+    let node = MockNode::new(net, "node-1", dir)
+        .with_prefixed_gsb()
+        .with_identity();
+    node.bind_gsb().await?;
+
+    let identity = node.get_identity()?;
+    let appkey = identity.create_identity_key("rotated").await?;
+    let expires = (Utc::now().naive_utc() + Duration::days(1))
+        .with_nanosecond(0)
+        .unwrap();
+
+    // Rotation by the owner of the node can set any expiration date.
+    let rotated = rotate(&identity, 3600, Some(expires), None).await??;
+    assert_ne!(rotated.key, appkey.key);
+    assert_eq!(rotated.expires_date, Some(expires));
+    assert_eq!(rotated.previous_key.unwrap().key, appkey.key);
+
+    // Previous key is valid during its grace period, but can't rotate.
+    let error = rotate(&identity, 3600, None, Some(&appkey.key))
+        .await?
+        .unwrap_err();
+    assert_eq!(error.code, 403);
+
+    // App-key rotating itself can't extend its expiration date.
+    let extended = rotate(
+        &identity,
+        3600,
+        Some(expires + Duration::days(30)),
+        Some(&rotated.key),
+    )
+    .await??;
+    assert_eq!(extended.expires_date, Some(expires));
+
+    let unlimited = rotate(&identity, 3600, None, Some(&extended.key)).await??;
+    assert_eq!(unlimited.expires_date, Some(expires));
+
+    // Replaced key doesn't outlive its expiration date.
+    let shortened = rotate(
+        &identity,
+        7 * 24 * 3600,
+        Some(expires - Duration::hours(1)),
+        Some(&unlimited.key),
+    )
+    .await??;
+    assert_eq!(shortened.expires_date, Some(expires - Duration::hours(1)));
+    assert_eq!(shortened.previous_key.unwrap().expires_date, expires);
+
+    // Grace period is capped.
+    let error = rotate(&identity, u64::MAX, None, None).await?.unwrap_err();
+    assert_eq!(error.code, 400);
+    Ok(())
+}
@@ -9,6 +9,7 @@ use resolver::error::MatchError as InternalMatchErorr;
 use crate::resolver::properties::PropertyRef;
 use flatten::{flatten_properties, FlattenError};
 use resolver::error::PrepareError;
+pub use resolver::explain::{Explanation, MatchExplanation, Outcome};
 pub use resolver::matching::{match_weak, MatchResult};
 pub use resolver::prepare::{PreparedDemand, PreparedOffer};
 
@@ -46,7 +47,36 @@ pub fn match_demand_offer(
     let offer = Offer::from(offer_properties, offer_constraints)?;
     let prep_offer_result = PreparedOffer::from(&offer)?;
 
-    match match_weak(&prep_demand_result, &prep_offer_result)? {
+    to_match(match_weak(&prep_demand_result, &prep_offer_result)?)
+}
+
+/// Same as `match_demand_offer`, but additionally explains why Demand and Offer
+/// don't match, with every constraint resolved against properties of the other side.
+/// Explanation is built only if they don't match.
+pub fn match_demand_offer_explained(
+    demand_properties: &str,
+    demand_constraints: &str,
+    offer_properties: &str,
+    offer_constraints: &str,
+) -> Result<(Match, Option<MatchExplanation>), MatchError> {
+    let demand = Demand::from(demand_properties, demand_constraints)?;
+    let prep_demand_result = PreparedDemand::from(&demand)?;
+    let offer = Offer::from(offer_properties, offer_constraints)?;
+    let prep_offer_result = PreparedOffer::from(&offer)?;
+
+    let result = to_match(match_weak(&prep_demand_result, &prep_offer_result)?)?;
+    let explanation = match result {
+        Match::Yes => None,
+        _ => Some(MatchExplanation::new(
+            &prep_demand_result,
+            &prep_offer_result,
+        )),
+    };
+    Ok((result, explanation))
+}
+
+fn to_match(result: MatchResult) -> Result<Match, MatchError> {
+    match result {
         MatchResult::True => Ok(Match::Yes),
         MatchResult::False(from_offer, from_demand) => Ok(Match::No {
             offer_mismatch: extract_names(&from_offer),
@@ -1,4 +1,5 @@
 pub mod error;
+pub mod explain;
 pub mod expression;
 pub mod ldap_parser;
 pub mod matching;
@@ -6,6 +7,7 @@ pub mod prepare;
 pub mod prop_parser;
 pub mod properties;
 
+pub use self::explain::{Explanation, MatchExplanation, Outcome};
 pub use self::expression::Expression;
 pub use self::matching::match_weak;
 pub use self::prepare::{PreparedDemand, PreparedOffer};
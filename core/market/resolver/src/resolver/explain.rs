@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::expression::{Expression, ResolveResult};
+use super::prepare::{PreparedDemand, PreparedOffer};
+use super::properties::{Property, PropertyRef, PropertySet, PropertyValue};
+
+// Result of resolving a single node of the constraint expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Outcome {
+    True,
+    False,
+    Undefined,
+    Error,
+}
+
+impl<'a> From<&ResolveResult<'a>> for Outcome {
+    fn from(result: &ResolveResult<'a>) -> Self {
+        match result {
+            ResolveResult::True => Outcome::True,
+            ResolveResult::False(..) => Outcome::False,
+            ResolveResult::Undefined(..) => Outcome::Undefined,
+            ResolveResult::Err(_) => Outcome::Error,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Outcome::True => "true",
+            Outcome::False => "false",
+            Outcome::Undefined => "undefined",
+            Outcome::Error => "error",
+        })
+    }
+}
+
+// Explanation tree - mirrors the constraint expression, with every node
+// resolved against properties of the other side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explanation {
+    // Constraint in LDAP filter syntax, eg. `(golem.inf.mem.gib>=8)`
+    pub constraint: String,
+    pub outcome: Outcome,
+    // Value of the referenced property (or aspect), if it's present on the other side
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    // Operator and value required by the constraint, eg. `>=8`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operands: Vec<Explanation>,
+}
+
+impl Explanation {
+    pub fn new(expression: &Expression, property_set: &PropertySet) -> Self {
+        let outcome = Outcome::from(&expression.resolve(property_set));
+        let (actual, expected, operands) = match expression {
+            Expression::Equals(prop, val) => leaf(prop, "=", val, property_set),
+            Expression::Greater(prop, val) => leaf(prop, ">", val, property_set),
+            Expression::GreaterEqual(prop, val) => leaf(prop, ">=", val, property_set),
+            Expression::Less(prop, val) => leaf(prop, "<", val, property_set),
+            Expression::LessEqual(prop, val) => leaf(prop, "<=", val, property_set),
+            Expression::Present(prop) => leaf(prop, "=", "*", property_set),
+            Expression::And(exprs) | Expression::Or(exprs) => (
+                None,
+                None,
+                exprs
+                    .iter()
+                    .map(|expr| Explanation::new(expr, property_set))
+                    .collect(),
+            ),
+            Expression::Not(expr) => (None, None, vec![Explanation::new(expr, property_set)]),
+            Expression::Empty(_) => (None, None, vec![]),
+        };
+
+        Explanation {
+            constraint: expression.to_string(),
+            outcome,
+            actual,
+            expected,
+            operands,
+        }
+    }
+
+    // Innermost nodes responsible for the constraint not being satisfied.
+    // Empty if the constraint resolved to true.
+    pub fn failures(&self) -> Vec<&Explanation> {
+        if self.outcome == Outcome::True {
+            return vec![];
+        }
+        let inner: Vec<_> = self.operands.iter().flat_map(|e| e.failures()).collect();
+        if inner.is_empty() {
+            // eg. negation of a satisfied constraint
+            vec![self]
+        } else {
+            inner
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is {}", self.constraint, self.outcome)?;
+        match (&self.actual, self.operands.is_empty()) {
+            (Some(actual), _) => write!(f, " (actual: {})", actual),
+            (None, true) if self.expected.is_some() => f.write_str(" (property missing)"),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Explanation of both sides of the matching relation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchExplanation {
+    // Demand constraints resolved against Offer properties
+    pub demand: Explanation,
+    // Offer constraints resolved against Demand properties
+    pub offer: Explanation,
+}
+
+impl MatchExplanation {
+    pub fn new(demand: &PreparedDemand, offer: &PreparedOffer) -> Self {
+        MatchExplanation {
+            demand: Explanation::new(&demand.constraints, &offer.properties),
+            offer: Explanation::new(&offer.constraints, &demand.properties),
+        }
+    }
+}
+
+impl fmt::Display for MatchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = |e: &Explanation| {
+            e.failures()
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let sides = [
+            ("Demand", format(&self.demand)),
+            ("Offer", format(&self.offer)),
+        ];
+        let sides: Vec<_> = sides
+            .iter()
+            .filter(|(_, failures)| !failures.is_empty())
+            .map(|(side, failures)| format!("{} constraints: {}", side, failures))
+            .collect();
+
+        if sides.is_empty() {
+            f.write_str("all constraints satisfied")
+        } else {
+            f.write_str(&sides.join("; "))
+        }
+    }
+}
+
+fn leaf(
+    prop_ref: &PropertyRef,
+    operator: &str,
+    value: &str,
+    property_set: &PropertySet,
+) -> (Option<String>, Option<String>, Vec<Explanation>) {
+    let actual = match prop_ref {
+        PropertyRef::Value(name, _) => match property_set.properties.get(&name[..]) {
+            Some(Property::Explicit(_, value, _)) => Some(format_value(value)),
+            Some(Property::Implicit(_)) => Some("*".to_string()),
+            None => None,
+        },
+        PropertyRef::Aspect(name, aspect, _) => match property_set.properties.get(&name[..]) {
+            Some(Property::Explicit(_, _, aspects)) => {
+                aspects.get(&aspect[..]).map(|value| value.to_string())
+            }
+            _ => None,
+        },
+    };
+    (actual, Some(format!("{}{}", operator, value)), vec![])
+}
+
+fn format_value(value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::Str(value) => value.to_string(),
+        PropertyValue::Boolean(value) => value.to_string(),
+        PropertyValue::Number(value) => value.to_string(),
+        PropertyValue::Decimal(value) => value.to_string(),
+        PropertyValue::DateTime(value) => value.to_rfc3339(),
+        PropertyValue::Version(value) => value.to_string(),
+        PropertyValue::List(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(|value| format_value(value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
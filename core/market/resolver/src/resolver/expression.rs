@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str;
 
 use asnom::structures::{ExplicitTag, OctetString, Tag};
@@ -375,6 +376,33 @@ impl Expression {
     }
 }
 
+// Formats the expression in LDAP filter syntax
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Equals(prop, val) => write!(f, "({}={})", prop, val),
+            Expression::Greater(prop, val) => write!(f, "({}>{})", prop, val),
+            Expression::GreaterEqual(prop, val) => write!(f, "({}>={})", prop, val),
+            Expression::Less(prop, val) => write!(f, "({}<{})", prop, val),
+            Expression::LessEqual(prop, val) => write!(f, "({}<={})", prop, val),
+            Expression::Present(prop) => write!(f, "({}=*)", prop),
+            Expression::Or(exprs) => {
+                f.write_str("(|")?;
+                exprs.iter().try_for_each(|expr| write!(f, "{}", expr))?;
+                f.write_str(")")
+            }
+            Expression::And(exprs) => {
+                f.write_str("(&")?;
+                exprs.iter().try_for_each(|expr| write!(f, "{}", expr))?;
+                f.write_str(")")
+            }
+            Expression::Not(expr) => write!(f, "(!{})", expr),
+            Expression::Empty(true) => f.write_str("()"),
+            Expression::Empty(false) => f.write_str("(!())"),
+        }
+    }
+}
+
 // #region Expression building
 
 pub fn build_expression(root: &Tag) -> Result<Expression, ExpressionError> {
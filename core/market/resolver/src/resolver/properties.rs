@@ -5,6 +5,7 @@ use chrono::{DateTime, TimeZone, Utc};
 use regex::Regex;
 use semver::Version;
 use std::collections::HashMap;
+use std::fmt;
 
 use super::error::ParseError;
 use super::prop_parser;
@@ -436,6 +437,28 @@ pub enum PropertyRefType {
     DateTime,
 }
 
+// Formats the reference in filter expression syntax, eg. `name[aspect]$v`
+impl fmt::Display for PropertyRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let impl_type = match self {
+            PropertyRef::Value(name, impl_type) => {
+                f.write_str(name)?;
+                impl_type
+            }
+            PropertyRef::Aspect(name, aspect, impl_type) => {
+                write!(f, "{}[{}]", name, aspect)?;
+                impl_type
+            }
+        };
+        match impl_type {
+            PropertyRefType::Any => Ok(()),
+            PropertyRefType::Decimal => f.write_str("$d"),
+            PropertyRefType::Version => f.write_str("$v"),
+            PropertyRefType::DateTime => f.write_str("$t"),
+        }
+    }
+}
+
 pub fn parse_prop_ref(flat_prop: &str) -> Result<PropertyRef, ParseError> {
     // TODO parse the flat_prop using prop_parser and repack to PropertyRef
     match prop_parser::parse_prop_ref_with_aspect(flat_prop) {
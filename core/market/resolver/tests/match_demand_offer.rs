@@ -1,4 +1,6 @@
-use ya_market_resolver::{match_demand_offer, Match, MatchError};
+use ya_market_resolver::{
+    match_demand_offer, match_demand_offer_explained, Match, MatchError, Outcome,
+};
 
 mod sample;
 
@@ -119,3 +121,62 @@ fn match_poc_offer_demand_samples_deep() {
         Match::Yes
     );
 }
+
+#[test]
+fn match_explained_should_point_failed_constraint() {
+    let _ = env_logger::builder().try_init();
+    let (result, explanation) = match_demand_offer_explained(
+        "{\"foo\": \"bar\"}",
+        "(&(mem>=8)(qux=baz))",
+        "{\"mem\": 4, \"qux\": \"baz\"}",
+        "(foo=bar)",
+    )
+    .unwrap();
+    let explanation = explanation.unwrap();
+
+    assert_eq!(
+        result,
+        Match::No {
+            demand_mismatch: vec![],
+            offer_mismatch: vec![],
+        }
+    );
+    assert_eq!(explanation.demand.outcome, Outcome::False);
+    assert_eq!(explanation.demand.operands.len(), 2);
+    assert_eq!(explanation.offer.outcome, Outcome::True);
+
+    let failures = explanation.demand.failures();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].constraint, "(mem>=8)");
+    assert_eq!(failures[0].actual.as_deref(), Some("4"));
+    assert_eq!(failures[0].expected.as_deref(), Some(">=8"));
+    assert!(explanation.offer.failures().is_empty());
+    assert_eq!(
+        explanation.to_string(),
+        "Demand constraints: (mem>=8) is false (actual: 4)"
+    );
+}
+
+#[test]
+fn match_explained_should_report_missing_property() {
+    let _ = env_logger::builder().try_init();
+    let (_, explanation) =
+        match_demand_offer_explained("{\"foo1\": \"bar\"}", "()", "{}", "(foo=bar)").unwrap();
+    let explanation = explanation.unwrap();
+
+    assert_eq!(explanation.offer.outcome, Outcome::Undefined);
+    assert_eq!(explanation.offer.actual, None);
+    assert_eq!(
+        explanation.to_string(),
+        "Offer constraints: (foo=bar) is undefined (property missing)"
+    );
+}
+
+#[test]
+fn match_explained_should_skip_matching_pair() {
+    let (result, explanation) =
+        match_demand_offer_explained("{\"foo\": \"bar\"}", "()", "{}", "(foo=bar)").unwrap();
+
+    assert_eq!(result, Match::Yes);
+    assert_eq!(explanation, None);
+}
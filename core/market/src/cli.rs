@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use structopt::StructOpt;
 use ya_client::model::market::{agreement::State, Role};
-use ya_core_model::market::{GetAgreement, ListAgreements};
+use ya_core_model::market::{local, CollectGarbage, GetAgreement, ListAgreements};
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
@@ -9,12 +9,18 @@ use ya_service_bus::{typed as bus, RpcEndpoint};
 #[derive(StructOpt, Debug)]
 pub enum Command {
     Agreements(AgreementsCommand),
+    /// Remove expired market data from the database now
+    Gc,
 }
 
 impl Command {
     pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
         match self {
             Command::Agreements(agreements_cmd) => agreements_cmd.run_command(ctx).await,
+            Command::Gc => {
+                let stats = bus::service(local::BUS_ID).send(CollectGarbage).await??;
+                CommandOutput::object(stats)
+            }
         }
     }
 }
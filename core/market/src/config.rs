@@ -11,6 +11,8 @@ pub struct Config {
     pub events: EventsConfig,
     #[structopt(flatten)]
     pub db: DbConfig,
+    #[structopt(flatten)]
+    pub scoring: ScoringConfig,
 }
 
 #[derive(StructOpt, Clone)]
@@ -64,6 +66,17 @@ pub struct DbConfig {
     pub event_store_days: i32,
 }
 
+#[derive(StructOpt, Clone)]
+pub struct ScoringConfig {
+    /// GSB address of external component ranking Proposals collected by Requestor.
+    /// Proposals aren't scored if not set.
+    #[structopt(env = "MARKET_PROPOSAL_SCORER")]
+    pub proposal_scorer: Option<String>,
+    /// Time to wait for scores before returning Proposals unscored.
+    #[structopt(env = "MARKET_PROPOSAL_SCORER_TIMEOUT", parse(try_from_str = humantime::parse_duration), default_value = "3s")]
+    pub proposal_scorer_timeout: Duration,
+}
+
 impl Config {
     pub fn from_env() -> Result<Config, structopt::clap::Error> {
         // Empty command line arguments, because we want to use ENV fallback
@@ -100,4 +113,11 @@ mod test {
         assert_eq!(90, c.db.agreement_store_days);
         assert_eq!(1, c.db.event_store_days);
     }
+
+    #[test]
+    fn test_default_structopt_scoring_config() {
+        let c = Config::from_env().unwrap();
+        assert!(c.scoring.proposal_scorer.is_none());
+        assert_eq!(3, c.scoring.proposal_scorer_timeout.as_secs());
+    }
 }
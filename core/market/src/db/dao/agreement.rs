@@ -386,7 +386,8 @@ impl<'c> AgreementDao<'c> {
         .await
     }
 
-    pub async fn clean(&self, db_config: &DbConfig) -> DbResult<()> {
+    /// Returns numbers of removed Agreements and Agreement events.
+    pub async fn clean(&self, db_config: &DbConfig) -> DbResult<(usize, usize)> {
         log::trace!("Clean market agreements: start");
         let interval_days = db_config.agreement_store_days;
         let (num_agreements, num_events) =
@@ -410,7 +411,7 @@ impl<'c> AgreementDao<'c> {
             log::info!("Cleaned {} market agreement events", num_events);
         }
         log::trace!("Clean market agreements: done");
-        Ok(())
+        Ok((num_agreements, num_events))
     }
 }
 
@@ -1,30 +1,62 @@
 use futures::join;
+use metrics::counter;
 use tokio::time;
 
+use ya_core_model::market::GcStats;
+
 use crate::config::DbConfig;
 use crate::db::dao::{AgreementDao, DemandDao, NegotiationEventsDao, OfferDao, ProposalDao};
 use crate::db::DbMixedExecutor;
+use crate::db::DbResult;
 
-pub async fn clean(db: DbMixedExecutor, cfg: &DbConfig) {
+/// Returns numbers of removed rows. Failures of single DAO cleaners are logged
+/// and don't stop the others.
+pub async fn clean(db: DbMixedExecutor, cfg: &DbConfig) -> GcStats {
     let demand_db = db.clone();
     let events_db = db.clone();
     let offer_db = db.clone();
     let agreement_db = db.clone();
     let proposal_db = db.clone();
 
-    let results = join!(
+    let (demands, offers, agreements, proposals, events) = join!(
         async move { demand_db.as_dao::<DemandDao>().clean().await },
         async move { offer_db.as_dao::<OfferDao>().clean().await },
         async move { agreement_db.as_dao::<AgreementDao>().clean(cfg).await },
         async move { proposal_db.as_dao::<ProposalDao>().clean().await },
         async move { events_db.as_dao::<NegotiationEventsDao>().clean(cfg).await },
     );
-    let v_results = vec![results.0, results.1, results.2, results.3, results.4];
-    for db_result in v_results.into_iter() {
-        if let Err(e) = db_result {
-            log::error!("Market database cleaner error: {}", e)
-        }
-    }
+
+    let demands = log_error(demands).unwrap_or_default();
+    let (offers, offer_unsubscribes) = log_error(offers).unwrap_or_default();
+    let (agreements, agreement_events) = log_error(agreements).unwrap_or_default();
+    let (proposals, negotiations) = log_error(proposals).unwrap_or_default();
+    let negotiation_events = log_error(events).unwrap_or_default();
+
+    let stats = GcStats {
+        offers: offers as u64,
+        offer_unsubscribes: offer_unsubscribes as u64,
+        demands: demands as u64,
+        proposals: proposals as u64,
+        negotiations: negotiations as u64,
+        negotiation_events: negotiation_events as u64,
+        agreements: agreements as u64,
+        agreement_events: agreement_events as u64,
+    };
+    counter!("market.db.gc.offers", stats.offers);
+    counter!("market.db.gc.offer_unsubscribes", stats.offer_unsubscribes);
+    counter!("market.db.gc.demands", stats.demands);
+    counter!("market.db.gc.proposals", stats.proposals);
+    counter!("market.db.gc.negotiations", stats.negotiations);
+    counter!("market.db.gc.negotiation_events", stats.negotiation_events);
+    counter!("market.db.gc.agreements", stats.agreements);
+    counter!("market.db.gc.agreement_events", stats.agreement_events);
+    stats
+}
+
+fn log_error<T>(result: DbResult<T>) -> Option<T> {
+    result
+        .map_err(|e| log::error!("Market database cleaner error: {}", e))
+        .ok()
 }
 
 pub async fn clean_forever(db: DbMixedExecutor, cfg: DbConfig) {
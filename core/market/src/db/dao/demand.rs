@@ -98,7 +98,7 @@ impl<'c> DemandDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    pub async fn clean(&self) -> DbResult<usize> {
         log::debug!("Clean market demands: start");
         let num_deleted = do_with_transaction(self.pool, "demand_dao_clean", move |conn| {
             let nd = diesel::delete(dsl::market_demand.filter(dsl::expiration_ts.lt(sql_now)))
@@ -110,7 +110,7 @@ impl<'c> DemandDao<'c> {
             log::info!("Clean market demands: {} cleaned", num_deleted);
         }
         log::debug!("Clean market demands: done");
-        Ok(())
+        Ok(num_deleted)
     }
 
     pub async fn demand_state(self, id: &SubscriptionId) -> DbResult<DemandState> {
@@ -155,7 +155,7 @@ impl<'c> NegotiationEventsDao<'c> {
         .await
     }
 
-    pub async fn clean(&self, db_config: &DbConfig) -> DbResult<()> {
+    pub async fn clean(&self, db_config: &DbConfig) -> DbResult<usize> {
         log::debug!("Clean market events: start");
         let interval_days = db_config.event_store_days;
         let num_deleted =
@@ -171,7 +171,7 @@ impl<'c> NegotiationEventsDao<'c> {
             log::info!("Clean market events: {} cleaned", num_deleted);
         }
         log::debug!("Clean market events: done");
-        Ok(())
+        Ok(num_deleted)
     }
 }
 
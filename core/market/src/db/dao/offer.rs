@@ -1,7 +1,7 @@
 use chrono::NaiveDateTime;
 use diesel::expression::dsl::now as sql_now;
 use diesel::sqlite::Sqlite;
-use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, TextExpressionMethods};
 use ya_client::model::NodeId;
 
 use ya_persistence::executor::{do_with_transaction, readonly_transaction, ConnType, PoolType};
@@ -292,6 +292,62 @@ impl<'c> OfferDao<'c> {
         .await?
     }
 
+    /// Replaces Offer with its next revision: inserts the revision
+    /// and unsubscribes the previous one.
+    /// Returns inserted revision as `Active` on success,
+    /// or the state of the previous revision otherwise.
+    pub async fn revise(
+        &self,
+        previous_id: &SubscriptionId,
+        mut revision: Offer,
+        expiry_validation_ts: NaiveDateTime,
+    ) -> DbResult<OfferState> {
+        let previous_id = previous_id.clone();
+        do_with_transaction(self.pool, "offer_dao_revise", move |conn| {
+            let previous = match query_state(conn, &previous_id, &expiry_validation_ts)? {
+                OfferState::Active(offer) => offer,
+                state => return Ok(state),
+            };
+
+            diesel::insert_into(market_offer_unsubscribed)
+                .values(previous.into_unsubscribe())
+                .execute(conn)?;
+
+            let id = revision.id.clone();
+            revision.insertion_ts = Some(chrono::Utc::now().naive_utc());
+            diesel::insert_into(market_offer)
+                .values(revision)
+                .execute(conn)?;
+
+            let revision = query_offer(conn, &id)?.unwrap();
+            Ok(OfferState::Active(revision))
+        })
+        .await
+    }
+
+    /// Returns the latest active revision of Offer subscription owned by `node_id`.
+    /// Revisions are ordered by insertion into the local database, since `creation_ts`
+    /// is chosen by the sender.
+    pub async fn get_latest_revision(
+        &self,
+        id: &SubscriptionId,
+        node_id: NodeId,
+        expiry_validation_ts: NaiveDateTime,
+    ) -> DbResult<Option<Offer>> {
+        let pattern = format!("{}-%", id.random_id());
+        readonly_transaction(self.pool, "offer_dao_get_latest_revision", move |conn| {
+            Ok(active_market_offers(expiry_validation_ts)
+                .filter(offer::id.like(pattern))
+                .filter(offer::node_id.eq(node_id))
+                .order_by(
+                    diesel::dsl::sql::<diesel::sql_types::BigInt>("market_offer.rowid").desc(),
+                )
+                .first(conn)
+                .optional()?)
+        })
+        .await
+    }
+
     /// Deletes single Offer.
     /// Returns `true` on success.
     pub async fn delete(&self, id: &SubscriptionId) -> DbResult<bool> {
@@ -305,7 +361,8 @@ impl<'c> OfferDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    /// Returns numbers of removed Offers and unsubscribes.
+    pub async fn clean(&self) -> DbResult<(usize, usize)> {
         log::debug!("Clean market offers: start");
         let num_deleted = do_with_transaction(self.pool, "offer_dao_clean", move |conn| {
             let nd = diesel::delete(market_offer.filter(offer::expiration_ts.lt(sql_now)))
@@ -316,12 +373,12 @@ impl<'c> OfferDao<'c> {
         if num_deleted > 0 {
             log::info!("Clean market offers: {} cleaned", num_deleted);
         }
-        self.clean_unsubscribes().await?;
+        let num_unsubscribes = self.clean_unsubscribes().await?;
         log::debug!("Clean market offers: done");
-        Ok(())
+        Ok((num_deleted, num_unsubscribes))
     }
 
-    pub async fn clean_unsubscribes(&self) -> DbResult<()> {
+    pub async fn clean_unsubscribes(&self) -> DbResult<usize> {
         log::debug!("Clean market offers unsubscribes: start");
         let num_deleted =
             do_with_transaction(self.pool, "offer_dao_clean_unsubscribes", move |conn| {
@@ -336,7 +393,7 @@ impl<'c> OfferDao<'c> {
             log::info!("Clean market offers unsubscribes: {} cleaned", num_deleted);
         }
         log::debug!("Clean market offers unsubscribes: done");
-        Ok(())
+        Ok(num_deleted)
     }
 }
 
@@ -139,8 +139,10 @@ impl<'c> ProposalDao<'c> {
         .await
     }
 
-    pub async fn clean(&self) -> DbResult<()> {
+    /// Returns numbers of removed Proposals and Negotiations.
+    pub async fn clean(&self) -> DbResult<(usize, usize)> {
         log::debug!("Clean market proposals: start");
+        let (mut total_p, mut total_n) = (0, 0);
         loop {
             let (num_deleted_p, num_deleted_n) =
                 do_with_transaction(self.pool, "proposal_dao_clean", move |conn| {
@@ -174,6 +176,8 @@ impl<'c> ProposalDao<'c> {
                     Result::<(usize, usize), DbError>::Ok((ndp, ndn))
                 })
                 .await?;
+            total_p += num_deleted_p;
+            total_n += num_deleted_n;
             if (num_deleted_p > 0) || (num_deleted_n > 0) {
                 log::info!(
                     "Clean market proposals: {}({} negotiations) cleaned",
@@ -185,7 +189,7 @@ impl<'c> ProposalDao<'c> {
             }
         }
         log::debug!("Clean market proposals: done");
-        Ok(())
+        Ok((total_p, total_n))
     }
 }
 
@@ -78,6 +78,46 @@ impl Offer {
         })
     }
 
+    /// Creates next revision of this Offer with partially updated properties.
+    /// Properties from `update` replace existing ones. `null` removes a property
+    /// together with all properties in its namespace, e.g. `{"golem.inf.gpu": null}`.
+    pub fn revise(
+        &self,
+        update: serde_json::Value,
+        creation_ts: NaiveDateTime,
+    ) -> Result<Offer, serde_json::error::Error> {
+        let mut properties: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&self.properties)?;
+
+        for (name, value) in ya_agreement_utils::agreement::flatten(update) {
+            if value.is_null() {
+                let namespace = format!("{}.", name);
+                properties.retain(|k, _| k != &name && !k.starts_with(&namespace));
+            } else {
+                properties.insert(name, value);
+            }
+        }
+
+        let properties = serde_json::to_string(&properties)?;
+        let id = self.id.revise(
+            &properties,
+            &self.constraints,
+            &self.node_id,
+            &creation_ts,
+            &self.expiration_ts,
+        );
+
+        Ok(Offer {
+            id,
+            properties,
+            constraints: self.constraints.clone(),
+            node_id: self.node_id,
+            creation_ts,
+            insertion_ts: None,
+            expiration_ts: self.expiration_ts,
+        })
+    }
+
     pub fn into_client_offer(&self) -> Result<ClientOffer, ErrorMessage> {
         Ok(ClientOffer {
             offer_id: self.id.to_string(),
@@ -190,5 +230,39 @@ mod tests {
         offer.validate().unwrap();
     }
 
+    #[test]
+    fn test_offer_revision() {
+        let node_id = NodeId::from_str("0xbabe000000000000000000000000000000000000").unwrap();
+        let ts = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+            NaiveTime::from_hms_opt(0, 1, 1).unwrap(),
+        );
+        let properties = r#"{"golem.inf.cpu.threads":4,"golem.inf.gpu.model":"x","golem.inf.gpu.mem":8,"golem.queue":1}"#;
+        let offer = Offer {
+            id: SubscriptionId::generate_id(properties, "()", &node_id, &ts, &ts),
+            properties: properties.to_string(),
+            constraints: "()".to_string(),
+            node_id,
+            creation_ts: ts,
+            insertion_ts: None,
+            expiration_ts: ts,
+        };
+
+        let update = serde_json::json!({
+            "golem": {
+                "queue": 5,
+                "inf.gpu": null,
+            }
+        });
+        let revision = offer.revise(update, ts).unwrap();
+
+        assert!(revision.id.is_revision_of(&offer.id));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&revision.properties).unwrap(),
+            serde_json::json!({"golem.inf.cpu.threads": 4, "golem.queue": 5})
+        );
+        revision.validate().unwrap();
+    }
+
     // TODO: test from_new
 }
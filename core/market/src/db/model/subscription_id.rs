@@ -59,6 +59,35 @@ impl SubscriptionId {
         }
     }
 
+    /// Id of the next revision of the same subscription. Revisions share
+    /// the random part of the id, only the content hash changes.
+    pub fn revise(
+        &self,
+        properties: &str,
+        constraints: &str,
+        node_id: &NodeId,
+        creation_ts: &NaiveDateTime,
+        expiration_ts: &NaiveDateTime,
+    ) -> SubscriptionId {
+        SubscriptionId {
+            random_id: self.random_id.clone(),
+            hash: hash(properties, constraints, node_id, creation_ts, expiration_ts),
+        }
+    }
+
+    /// Checks if both ids are different revisions of the same subscription.
+    pub fn is_revision_of(&self, other: &SubscriptionId) -> bool {
+        self.same_subscription(other) && self.hash != other.hash
+    }
+
+    pub fn same_subscription(&self, other: &SubscriptionId) -> bool {
+        self.random_id == other.random_id
+    }
+
+    pub fn random_id(&self) -> &str {
+        &self.random_id
+    }
+
     pub fn validate(
         &self,
         properties: &str,
@@ -172,6 +201,28 @@ mod tests {
         assert_eq!(&sub_id.random_id, "c76161077d0343ab85ac986eb5f6ea38");
     }
 
+    #[test]
+    fn revision_keeps_random_id() {
+        let node_id = NodeId::from_str("0xbabe000000000000000000000000000000000000").unwrap();
+        let ts = NaiveDate::from_ymd_opt(1970, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 1, 1)
+            .unwrap();
+
+        let id = SubscriptionId::generate_id("{}", "()", &node_id, &ts, &ts);
+        let revision = id.revise("{\"a\":1}", "()", &node_id, &ts, &ts);
+
+        assert_eq!(id.random_id(), revision.random_id());
+        assert!(revision.is_revision_of(&id));
+        assert!(!id.is_revision_of(&id));
+        assert!(
+            !revision.is_revision_of(&SubscriptionId::generate_id("{}", "()", &node_id, &ts, &ts))
+        );
+        revision
+            .validate("{\"a\":1}", "()", &node_id, &ts, &ts)
+            .unwrap();
+    }
+
     #[test]
     fn should_be_case_sensitive_subscription_id() {
         assert_ne!(
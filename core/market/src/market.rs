@@ -13,7 +13,6 @@ use ya_client::model::market::{
 use ya_core_model::market::{local, BUS_ID};
 use ya_service_api_interfaces::{Provider, Service};
 use ya_service_api_web::middleware::Identity;
-use ya_service_api_web::scope::ExtendableScope;
 
 use super::db::model::AgreementState;
 use crate::config::Config;
@@ -33,6 +32,7 @@ use crate::negotiation::{EventNotifier, ProviderBroker, RequestorBroker, Scanner
 use crate::rest_api;
 
 pub mod agreement;
+pub mod gc;
 
 #[derive(Error, Debug)]
 pub enum MarketError {
@@ -71,6 +71,7 @@ pub struct MarketService {
     pub provider_engine: ProviderBroker,
     pub requestor_engine: RequestorBroker,
     pub scan_set: Data<ScannerSet>,
+    config: Arc<Config>,
 }
 
 impl MarketService {
@@ -114,8 +115,9 @@ impl MarketService {
             config.clone(),
         )?;
         let cleaner_db = db.clone();
+        let cleaner_config = config.db.clone();
         tokio::spawn(async move {
-            crate::db::dao::cleaner::clean_forever(cleaner_db, config.db.clone()).await;
+            crate::db::dao::cleaner::clean_forever(cleaner_db, cleaner_config).await;
         });
 
         Ok(MarketService {
@@ -124,6 +126,7 @@ impl MarketService {
             provider_engine,
             requestor_engine,
             scan_set,
+            config,
         })
     }
 
@@ -140,6 +143,7 @@ impl MarketService {
             .bind_gsb(public_prefix, local_prefix)
             .await?;
         agreement::bind_gsb(self.db.clone(), public_prefix, local_prefix).await;
+        gc::bind_gsb(self.db.clone(), self.config.db.clone(), local_prefix).await;
         Ok(())
     }
 
@@ -161,14 +165,16 @@ impl MarketService {
     }
 
     pub fn bind_rest(myself: Arc<MarketService>) -> actix_web::Scope {
-        actix_web::web::scope(ya_client::model::market::MARKET_API_PATH)
+        rest_api::api_scope()
+            .into_scope()
             .app_data(myself.scan_set.clone())
             .app_data(Data::new(myself))
             .app_data(Data::new(rest_api::path_config()))
             .app_data(Data::new(rest_api::json_config()))
-            .extend(rest_api::common::register_endpoints)
-            .extend(rest_api::provider::register_endpoints)
-            .extend(rest_api::requestor::register_endpoints)
+    }
+
+    pub fn api_spec() -> ya_service_api_web::openapi::ScopeSpec {
+        rest_api::api_spec()
     }
 
     // TODO: (re)move this
@@ -206,6 +212,11 @@ impl MarketService {
         id: &Identity,
     ) -> Result<(), MarketError> {
         // TODO: Authorize unsubscribe caller.
+        let offer_id = &self
+            .matcher
+            .store
+            .latest_offer_revision(offer_id, id.identity)
+            .await;
         self.provider_engine.unsubscribe_offer(offer_id).await?;
         self.matcher.unsubscribe_offer(offer_id, id).await?;
 
@@ -213,6 +224,28 @@ impl MarketService {
         Ok(())
     }
 
+    /// Updates a subset of Offer properties by issuing a new Offer revision.
+    /// `offer_id` can refer to any revision of the subscription.
+    pub async fn revise_offer(
+        &self,
+        offer_id: &SubscriptionId,
+        id: &Identity,
+        update: serde_json::Value,
+    ) -> Result<SubscriptionId, MarketError> {
+        let offer_id = self
+            .matcher
+            .store
+            .latest_offer_revision(offer_id, id.identity)
+            .await;
+        let revision = self.matcher.revise_offer(&offer_id, id, update).await?;
+        self.provider_engine
+            .revise_offer(&offer_id, &revision)
+            .await?;
+
+        counter!("market.offers.revised", 1);
+        Ok(revision.id)
+    }
+
     pub async fn subscribe_demand(
         &self,
         demand: &NewDemand,
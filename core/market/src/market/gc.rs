@@ -0,0 +1,22 @@
+use ya_core_model::market::{CollectGarbage, GcStats, RpcMessageError};
+use ya_service_bus::typed::ServiceBinder;
+
+use crate::config::DbConfig;
+use crate::db::dao::cleaner;
+use crate::db::DbMixedExecutor;
+
+pub async fn bind_gsb(db: DbMixedExecutor, db_config: DbConfig, local_prefix: &str) {
+    log::trace!("Binding market garbage collection to service bus");
+    ServiceBinder::new(local_prefix, &db, db_config).bind_with_processor(collect_garbage);
+    log::debug!("Successfully bound market garbage collection to service bus");
+}
+
+async fn collect_garbage(
+    db: DbMixedExecutor,
+    db_config: DbConfig,
+    _sender_id: String,
+    _msg: CollectGarbage,
+) -> Result<GcStats, RpcMessageError> {
+    log::info!("Market database garbage collection triggered");
+    Ok(cleaner::clean(db, &db_config).await)
+}
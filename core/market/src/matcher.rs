@@ -276,6 +276,61 @@ impl Matcher {
         Ok(())
     }
 
+    /// Replaces Offer with its next revision and broadcasts both
+    /// the revision and unsubscription of the previous one.
+    pub async fn revise_offer(
+        &self,
+        offer_id: &SubscriptionId,
+        id: &Identity,
+        update: serde_json::Value,
+    ) -> Result<Offer, MatcherError> {
+        let revision = self.store.revise_offer(offer_id, id, update).await?;
+        self.resolver.receive(&revision);
+
+        log::info!(
+            "Revised Offer: [{}] -> [{}] using identity: {} [{}]",
+            offer_id,
+            &revision.id,
+            id.name,
+            id.identity
+        );
+
+        self.expiration_tracker
+            .send(StopTracking {
+                category: Some("Offer".to_string()),
+                id: offer_id.to_string(),
+            })
+            .await
+            .ok();
+        self.expiration_tracker
+            .send(TrackDeadline {
+                category: "Offer".to_string(),
+                deadline: Utc.from_utc_datetime(&revision.expiration_ts),
+                id: revision.id.to_string(),
+            })
+            .await
+            .ok();
+
+        // Errors are ignored for the same reasons as in (un)subscribe.
+        let _ = self
+            .discovery
+            .bcast_offers(vec![revision.id.clone()])
+            .await
+            .map_err(|e| log::warn!("Failed to bcast offer [{}]. Error: {}.", revision.id, e));
+        let _ = self
+            .discovery
+            .bcast_unsubscribes(vec![offer_id.clone()])
+            .await
+            .map_err(|e| {
+                log::warn!(
+                    "Failed to bcast unsubscribe offer [{}]. Error: {}.",
+                    offer_id,
+                    e
+                )
+            });
+        Ok(revision)
+    }
+
     pub async fn subscribe_demand(
         &self,
         demand: &NewDemand,
@@ -83,6 +83,10 @@ pub enum ModifyOfferError {
     Remove(DbError, SubscriptionId),
     #[error("Offer [{0}] marked as unsubscribed, but not removed")]
     UnsubscribedNotRemoved(SubscriptionId),
+    #[error("Invalid properties update of Offer [{1}]: {0}.")]
+    InvalidProperties(String, SubscriptionId),
+    #[error("Failed to revise Offer [{1}]. Error: {0}.")]
+    Revise(DbError, SubscriptionId),
 }
 
 impl From<QueryOfferError> for ModifyOfferError {
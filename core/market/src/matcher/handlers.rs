@@ -29,6 +29,7 @@ pub(super) async fn filter_out_known_offer_ids(
 
 /// Returns only ids of those from input offers, that was successfully stored locally.
 /// Also triggers Resolver to match newly stored Offers against local Demands.
+/// Offer revisions replace previous revisions of the same subscription.
 pub(super) async fn receive_remote_offers(
     resolver: Resolver,
     caller: String,
@@ -38,16 +39,21 @@ pub(super) async fn receive_remote_offers(
         .filter_map(|offer| {
             let resolver = resolver.clone();
             async move {
-                resolver
+                let offer = resolver
                     .store
                     .save_offer(offer)
                     .await
-                    .map(|offer| {
-                        resolver.receive(&offer);
-                        offer.id
-                    })
                     .map_err(|e| log::info!("Skipping foreign Offer: {}", e))
-                    .ok()
+                    .ok()?;
+
+                resolver
+                    .store
+                    .remove_superseded_offers(&offer)
+                    .await
+                    .map_err(|e| log::warn!("Failed to remove superseded Offers: {}", e))
+                    .ok();
+                resolver.receive(&offer);
+                Some(offer.id)
             }
         })
         .collect::<Vec<SubscriptionId>>()
@@ -1,6 +1,6 @@
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use ya_market_resolver::{match_demand_offer, Match};
+use ya_market_resolver::{match_demand_offer, match_demand_offer_explained, Match};
 
 use super::{error::ResolverError, RawProposal, SubscriptionStore};
 use crate::db::model::{Demand, Offer, SubscriptionId};
@@ -129,7 +129,25 @@ fn matches(offer: &Offer, demand: &Demand) -> bool {
             log::warn!("Matching [{:?}] vs [{:?}] error: {}", offer, demand, e);
             false
         }
-        _ => false,
+        _ => {
+            // Explaining is expensive, and most of the pairs don't match.
+            if log::log_enabled!(log::Level::Trace) {
+                if let Ok((_, Some(explanation))) = match_demand_offer_explained(
+                    &demand.properties,
+                    &demand.constraints,
+                    &offer.properties,
+                    &offer.constraints,
+                ) {
+                    log::trace!(
+                        "Offer [{}] doesn't match Demand [{}]. {}",
+                        offer.id,
+                        demand.id,
+                        explanation
+                    );
+                }
+            }
+            false
+        }
     }
 }
 
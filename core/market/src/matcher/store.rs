@@ -234,6 +234,103 @@ impl SubscriptionStore {
             })
     }
 
+    /// Replaces our Offer with its next revision with partially updated properties.
+    pub async fn revise_offer(
+        &self,
+        offer_id: &SubscriptionId,
+        id: &Identity,
+        update: serde_json::Value,
+    ) -> Result<Offer, ModifyOfferError> {
+        let offer = self.get_offer(offer_id).await?;
+        if offer.node_id != id.identity {
+            return Err(ModifyOfferError::NotFound(offer_id.clone()));
+        }
+        if !update.is_object() {
+            return Err(ModifyOfferError::InvalidProperties(
+                "JSON object expected".to_string(),
+                offer_id.clone(),
+            ));
+        }
+
+        let revision = offer
+            .revise(update, Utc::now().naive_utc())
+            .map_err(|e| ModifyOfferError::InvalidProperties(e.to_string(), offer_id.clone()))?;
+
+        match self
+            .db
+            .as_dao::<OfferDao>()
+            .revise(offer_id, revision, Utc::now().naive_utc())
+            .await
+            .map_err(|e| ModifyOfferError::Revise(e, offer_id.clone()))?
+        {
+            OfferState::Active(revision) => {
+                self.scan_set.notify();
+                Ok(revision)
+            }
+            OfferState::NotFound => Err(ModifyOfferError::NotFound(offer_id.clone())),
+            OfferState::Unsubscribed(_) => {
+                Err(ModifyOfferError::AlreadyUnsubscribed(offer_id.clone()))
+            }
+            OfferState::Expired(_) => Err(ModifyOfferError::Expired(offer_id.clone())),
+        }
+    }
+
+    /// Returns id of the latest active revision of Offer subscription owned by `node_id`,
+    /// or the same id if Offer was never revised.
+    pub async fn latest_offer_revision(
+        &self,
+        offer_id: &SubscriptionId,
+        node_id: NodeId,
+    ) -> SubscriptionId {
+        match self
+            .db
+            .as_dao::<OfferDao>()
+            .get_latest_revision(offer_id, node_id, Utc::now().naive_utc())
+            .await
+        {
+            Ok(Some(offer)) => offer.id,
+            Ok(None) => offer_id.clone(),
+            Err(e) => {
+                log::warn!("Failed to get latest revision of Offer [{offer_id}]. Error: {e}");
+                offer_id.clone()
+            }
+        }
+    }
+
+    /// Removes previous revisions of foreign Offer, which were superseded by `offer`.
+    pub async fn remove_superseded_offers(&self, offer: &Offer) -> Result<(), QueryOffersError> {
+        let superseded = self
+            .db
+            .as_dao::<OfferDao>()
+            .get_offers(
+                None,
+                Some(vec![offer.node_id]),
+                None,
+                Utc::now().naive_utc(),
+            )
+            .await?
+            .into_iter()
+            .filter(|other| other.id.is_revision_of(&offer.id))
+            .filter(|other| other.creation_ts < offer.creation_ts);
+
+        for previous in superseded {
+            log::debug!(
+                "Offer [{}] superseded by revision [{}].",
+                previous.id,
+                offer.id
+            );
+            self.unsubscribe_offer(&previous.id, false, Some(offer.node_id))
+                .await
+                .or_else(|e| match e {
+                    ModifyOfferError::UnsubscribedNotRemoved(..) => Ok(()),
+                    e => Err(e),
+                })
+                .map_err(|e| log::warn!("Failed to remove superseded Offer. Error: {e}"))
+                .ok();
+        }
+        Ok(())
+    }
+
     /// Local Offers are kept after unsubscribe. Offers from other nodes are removed.
     pub async fn unsubscribe_offer(
         &self,
@@ -6,7 +6,7 @@ use std::time::{Duration, Instant};
 
 use ya_client::model::market::{proposal::Proposal as ClientProposal, reason::Reason, NewProposal};
 use ya_client::model::NodeId;
-use ya_market_resolver::{match_demand_offer, Match};
+use ya_market_resolver::{match_demand_offer_explained, Match};
 use ya_service_api_web::middleware::Identity;
 
 use crate::config::Config;
@@ -304,7 +304,13 @@ impl CommonBroker {
                     return true;
                 }
                 let subscription_id = subs_id.unwrap();
-                if &proposal.negotiation.subscription_id == subscription_id {
+                // Offer revisions keep the subscription, so Provider can refer
+                // to all of them using any revision id.
+                if proposal
+                    .negotiation
+                    .subscription_id
+                    .same_subscription(subscription_id)
+                {
                     return true;
                 }
                 log::warn!(
@@ -580,7 +586,16 @@ impl CommonBroker {
 
         self.validate_proposal(&prev_proposal, &caller_id, caller_role)
             .await?;
-        validate_match(&proposal, &prev_proposal)?;
+        if let Err(e) = validate_match(&proposal, &prev_proposal) {
+            // Explanation is sent back to the caller as a part of the error.
+            log::info!(
+                "Not matching counter Proposal for [{}] from [{}]: {}",
+                &msg.prev_proposal_id,
+                &caller_id,
+                e
+            );
+            return Err(e.into());
+        }
 
         self.db
             .as_dao::<ProposalDao>()
@@ -805,7 +820,7 @@ pub fn validate_match(
     new_proposal: &Proposal,
     prev_proposal: &Proposal,
 ) -> Result<(), MatchValidationError> {
-    match match_demand_offer(
+    let (result, explanation) = match_demand_offer_explained(
         &new_proposal.body.properties,
         &new_proposal.body.constraints,
         &prev_proposal.body.properties,
@@ -815,7 +830,9 @@ pub fn validate_match(
         new: new_proposal.body.id.clone(),
         prev: prev_proposal.body.id.clone(),
         error: e.to_string(),
-    })? {
+    })?;
+
+    match result {
         Match::Yes => Ok(()),
         Match::No {
             demand_mismatch,
@@ -828,9 +845,15 @@ pub fn validate_match(
             new: new_proposal.body.id.clone(),
             prev: prev_proposal.body.id.clone(),
             mismatches: format!(
-                "Mismatched constraints - Offer: {:?}, Demand: {:?}",
-                offer_mismatch, demand_mismatch
+                "Mismatched constraints - Offer: {:?}, Demand: {:?}. {}",
+                offer_mismatch,
+                demand_mismatch,
+                explanation
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default()
             ),
+            explanation,
         }),
     }
 }
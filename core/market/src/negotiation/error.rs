@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use thiserror::Error;
 
-use ya_client::model::{ErrorMessage, NodeId};
+use ya_client::model::NodeId;
+use ya_market_resolver::MatchExplanation;
+use ya_service_api_web::error::{self as api, ApiError, ErrorCategory};
 
 use crate::db::dao::AgreementDaoError;
 use crate::db::model::{
@@ -38,6 +40,9 @@ pub enum MatchValidationError {
         new: ProposalId,
         prev: ProposalId,
         mismatches: String,
+        /// Constraints of both Proposals resolved against properties of the other one.
+        #[serde(default)]
+        explanation: Option<MatchExplanation>,
     },
     #[error("Can't match Proposal [{new}] with previous Proposal [{prev}]. Error: {error}")]
     MatchingFailed {
@@ -239,7 +244,23 @@ impl ResponseError for ScanError {
     }
 
     fn error_response(&self) -> HttpResponse<BoxBody> {
-        HttpResponse::build(self.status_code()).json(ErrorMessage::new(self))
+        let (category, code) = match self {
+            Self::InternalDbError { .. } | Self::GsbError(_) => {
+                (ErrorCategory::Internal, api::INTERNAL_ERROR)
+            }
+            Self::NotFound { .. } => (ErrorCategory::NotFound, "SCAN_NOT_FOUND"),
+            Self::InvalidConstraint { .. } => (ErrorCategory::BadRequest, "INVALID_CONSTRAINT"),
+            Self::Forbidden => (ErrorCategory::Forbidden, api::FORBIDDEN),
+            Self::Gone { .. } => (ErrorCategory::Gone, "SCAN_REMOVED"),
+            Self::FetchTimeout => (ErrorCategory::Timeout, "SCAN_FETCH_TIMEOUT"),
+            Self::BadRequest { .. } => (ErrorCategory::BadRequest, api::BAD_REQUEST),
+            Self::DiscoveryRemoteError { .. } => (ErrorCategory::Remote, api::REMOTE_ERROR),
+            Self::OldPeer => (ErrorCategory::Remote, "PEER_NOT_SUPPORTED"),
+        };
+        let retryable = category.retryable() && !matches!(self, Self::OldPeer);
+        ApiError::new(category, code, self)
+            .with_retryable(retryable)
+            .response_with(self.status_code())
     }
 }
 
@@ -281,3 +302,40 @@ impl From<DemandError> for ProposalValidationError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::negotiation::error::RemoteProposalError;
+    use ya_market_resolver::match_demand_offer_explained;
+
+    #[test]
+    fn test_not_matching_explanation_reaches_caller() {
+        let (_, explanation) =
+            match_demand_offer_explained(r#"{"foo": "bar"}"#, "(mem>=8)", r#"{"mem": 4}"#, "()")
+                .unwrap();
+        let id: ProposalId = format!("P-{}", "a".repeat(64)).parse().unwrap();
+        let error: RemoteProposalError = MatchValidationError::NotMatching {
+            new: id.clone(),
+            prev: id,
+            mismatches: "Mismatched constraints".to_string(),
+            explanation,
+        }
+        .into();
+
+        // Error is serialized, when returned over GSB.
+        let json = serde_json::to_string(&error).unwrap();
+        match serde_json::from_str::<RemoteProposalError>(&json).unwrap() {
+            RemoteProposalError::Validation(ProposalValidationError::NotMatching(
+                MatchValidationError::NotMatching {
+                    explanation: Some(explanation),
+                    ..
+                },
+            )) => {
+                assert_eq!(explanation.demand.constraint, "(mem>=8)");
+                assert_eq!(explanation.demand.actual.as_deref(), Some("4"));
+            }
+            e => panic!("Expected NotMatching with explanation, got: {}", e),
+        }
+    }
+}
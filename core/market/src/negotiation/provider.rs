@@ -132,6 +132,31 @@ impl ProviderBroker {
         self.common.unsubscribe(id).await
     }
 
+    /// Pending events of the previous revision refer to Proposals, which can't be
+    /// negotiated anymore. New Proposals will be generated for the revision.
+    pub async fn revise_offer(
+        &self,
+        previous_id: &SubscriptionId,
+        _revision: &Offer,
+    ) -> Result<(), NegotiationError> {
+        // Not using `common.unsubscribe`, since Provider still listens for events
+        // on the previous revision id.
+        let _ = self
+            .common
+            .db
+            .as_dao::<NegotiationEventsDao>()
+            .remove_events(previous_id)
+            .await
+            .map_err(|e| {
+                log::warn!(
+                    "Failed to remove events of revised Offer [{}]. Error: {}.",
+                    previous_id,
+                    e
+                )
+            });
+        Ok(())
+    }
+
     pub async fn counter_proposal(
         &self,
         offer_id: &SubscriptionId,
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
 use metrics::counter;
+use std::cmp::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::UnboundedReceiver;
@@ -8,7 +9,10 @@ use tracing::Level;
 
 use ya_client::model::market::{event::RequestorEvent, NewProposal, Reason};
 use ya_client::model::NodeId;
+use ya_core_model::market::{ScoreProposals, PROPOSAL_SCORE_PROPERTY};
 use ya_service_api_web::middleware::Identity;
+use ya_service_bus::timeout::IntoTimeoutFuture;
+use ya_service_bus::{typed as bus, RpcEndpoint};
 use ya_std_utils::LogErr;
 
 use crate::db::{
@@ -21,7 +25,7 @@ use crate::matcher::{store::SubscriptionStore, RawProposal};
 use crate::protocol::negotiation::{error::*, messages::*, requestor::NegotiationApi};
 
 use super::{common::*, error::*, notifier::NotifierError, EventNotifier};
-use crate::config::Config;
+use crate::config::{Config, ScoringConfig};
 use crate::db::dao::AgreementEventsDao;
 use crate::db::model::ProposalState;
 use crate::utils::display::EnableDisplay;
@@ -232,6 +236,13 @@ impl RequestorBroker {
             .collect::<Vec<RequestorEvent>>()
             .await;
 
+        let events = match &self.common.config.scoring.proposal_scorer {
+            Some(scorer) => {
+                score_proposals(&self.common.config.scoring, scorer, demand_id, events).await
+            }
+            None => events,
+        };
+
         counter!("market.events.requestor.queried", events.len() as u64);
         Ok(events)
     }
@@ -750,3 +761,170 @@ pub async fn proposal_receiver_thread(
         }
     }
 }
+
+/// Asks external scorer to rank Proposals from collected events. Scored Proposals are
+/// annotated with `PROPOSAL_SCORE_PROPERTY` and ordered best first, before unscored ones.
+/// Proposals are reordered only among themselves, so other events keep their positions.
+/// Events are returned unchanged if scorer fails.
+async fn score_proposals(
+    config: &ScoringConfig,
+    scorer: &str,
+    demand_id: &SubscriptionId,
+    mut events: Vec<RequestorEvent>,
+) -> Vec<RequestorEvent> {
+    let proposals = events
+        .iter()
+        .filter_map(|event| match event {
+            RequestorEvent::ProposalEvent { proposal, .. } => Some(proposal.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if proposals.is_empty() {
+        return events;
+    }
+
+    let num_proposals = proposals.len();
+    let msg = ScoreProposals {
+        demand_id: demand_id.to_string(),
+        proposals,
+    };
+    let scores = match bus::service(scorer)
+        .send(msg)
+        .timeout(Some(config.proposal_scorer_timeout))
+        .await
+    {
+        Ok(Ok(Ok(scores))) if scores.len() == num_proposals => scores,
+        Ok(Ok(Ok(scores))) => {
+            log::warn!(
+                "Proposal scorer [{}] returned {} scores for {} Proposals of Demand [{}].",
+                scorer,
+                scores.len(),
+                num_proposals,
+                demand_id
+            );
+            return events;
+        }
+        Ok(Ok(Err(e))) => {
+            log::warn!("Proposal scorer [{}] failed: {}", scorer, e);
+            return events;
+        }
+        Ok(Err(e)) => {
+            log::warn!("Failed to call Proposal scorer [{}]: {}", scorer, e);
+            return events;
+        }
+        Err(_) => {
+            log::warn!("Proposal scorer [{}] timed out.", scorer);
+            return events;
+        }
+    };
+
+    rank_proposals(&mut events, scores);
+    events
+}
+
+/// Annotates `ProposalEvent`s with `scores` and sorts them in the slots they occupy.
+fn rank_proposals(events: &mut [RequestorEvent], scores: Vec<Option<f64>>) {
+    let slots = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| matches!(event, RequestorEvent::ProposalEvent { .. }))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let mut proposals = slots
+        .iter()
+        .zip(scores)
+        .map(|(&idx, score)| {
+            let mut event = events[idx].clone();
+            if let RequestorEvent::ProposalEvent { proposal, .. } = &mut event {
+                if let (Some(score), Some(properties)) =
+                    (score, proposal.properties.as_object_mut())
+                {
+                    properties.insert(PROPOSAL_SCORE_PROPERTY.to_string(), score.into());
+                }
+            }
+            event
+        })
+        .collect::<Vec<_>>();
+
+    // Sorting is stable, so unscored Proposals keep their order.
+    proposals.sort_by(|a, b| {
+        event_score(b)
+            .partial_cmp(&event_score(a))
+            .unwrap_or(Ordering::Equal)
+    });
+    for (idx, event) in slots.into_iter().zip(proposals) {
+        events[idx] = event;
+    }
+}
+
+fn event_score(event: &RequestorEvent) -> Option<f64> {
+    match event {
+        RequestorEvent::ProposalEvent { proposal, .. } => proposal
+            .properties
+            .get(PROPOSAL_SCORE_PROPERTY)
+            .and_then(|score| score.as_f64()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ya_client::model::market::proposal::{Proposal as ClientProposal, State};
+
+    fn proposal_event(id: &str) -> RequestorEvent {
+        RequestorEvent::ProposalEvent {
+            event_date: Utc::now(),
+            proposal: ClientProposal {
+                properties: serde_json::json!({}),
+                constraints: String::new(),
+                proposal_id: id.to_string(),
+                issuer_id: NodeId::default(),
+                state: State::Initial,
+                timestamp: Utc::now(),
+                prev_proposal_id: None,
+            },
+        }
+    }
+
+    fn rejection_event(id: &str) -> RequestorEvent {
+        RequestorEvent::ProposalRejectedEvent {
+            event_date: Utc::now(),
+            proposal_id: id.to_string(),
+            reason: None,
+        }
+    }
+
+    fn event_id(event: &RequestorEvent) -> String {
+        match event {
+            RequestorEvent::ProposalEvent { proposal, .. } => proposal.proposal_id.clone(),
+            RequestorEvent::ProposalRejectedEvent { proposal_id, .. } => {
+                format!("rejected-{}", proposal_id)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_rank_proposals_keeps_rejections_in_place() {
+        let mut events = vec![
+            rejection_event("r1"),
+            proposal_event("p1"),
+            proposal_event("p2"),
+            rejection_event("r2"),
+            proposal_event("p3"),
+            proposal_event("p4"),
+        ];
+
+        rank_proposals(&mut events, vec![Some(0.5), None, Some(1.0), Some(0.5)]);
+
+        let order = events.iter().map(event_id).collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec!["rejected-r1", "p3", "p1", "rejected-r2", "p4", "p2"]
+        );
+        assert_eq!(event_score(&events[1]), Some(1.0));
+        assert_eq!(event_score(&events[5]), None);
+    }
+}
@@ -2,7 +2,7 @@
 use chrono::{DateTime, Utc};
 use futures::TryFutureExt;
 use metrics::{counter, timing, value};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Instant;
@@ -52,12 +52,12 @@ pub struct DiscoveryImpl {
     identity: Arc<dyn IdentityApi>,
 
     /// Sending queues.
-    offer_sending_queue: Mutex<Vec<SubscriptionId>>,
-    unsub_sending_queue: Mutex<Vec<SubscriptionId>>,
+    offer_sending_queue: Mutex<SendingQueue>,
+    unsub_sending_queue: Mutex<SendingQueue>,
     lazy_binder_prefix: Mutex<Option<String>>,
 
-    /// Receiving queue.
-    offers_receiving_queue: mpsc::Sender<(NodeId, OffersBcast)>,
+    /// Receiving queue, with TTL for propagating received Offers.
+    offers_receiving_queue: mpsc::Sender<(NodeId, OffersBcast, Option<u8>)>,
     offer_handlers: OfferHandlers,
 
     config: DiscoveryConfig,
@@ -69,6 +69,30 @@ pub struct DiscoveryImpl {
     last_bcast_ts: Mutex<DateTime<Utc>>,
 }
 
+/// Offer ids waiting to be broadcast, grouped by TTL. `None` is the default TTL
+/// (our own Offers). Groups are broadcast separately, so propagated Offers
+/// never travel further than they were allowed to.
+#[derive(Default)]
+pub(super) struct SendingQueue {
+    groups: BTreeMap<Option<u8>, Vec<SubscriptionId>>,
+}
+
+impl SendingQueue {
+    /// Returns whether the queue was empty.
+    fn push(&mut self, offer_ids: &[SubscriptionId], ttl: Option<u8>) -> bool {
+        let was_empty = self.groups.is_empty();
+        self.groups
+            .entry(ttl)
+            .or_default()
+            .extend_from_slice(offer_ids);
+        was_empty
+    }
+
+    fn take(&mut self) -> Vec<(Option<u8>, Vec<SubscriptionId>)> {
+        std::mem::take(&mut self.groups).into_iter().collect()
+    }
+}
+
 struct BanCache {
     inner: Arc<PlMutex<BanCacheInner>>,
 }
@@ -132,17 +156,25 @@ impl Discovery {
     }
 
     pub async fn bcast_offers(&self, offer_ids: Vec<SubscriptionId>) -> Result<(), DiscoveryError> {
+        self.queue_offers(offer_ids, None).await
+    }
+
+    /// Queues Offers for broadcast limited to `ttl` hops, or the default TTL.
+    async fn queue_offers(
+        &self,
+        offer_ids: Vec<SubscriptionId>,
+        ttl: Option<u8>,
+    ) -> Result<(), DiscoveryError> {
         if offer_ids.is_empty() {
             return Ok(());
         }
         // When there are 0 items in the queue we should schedule a send job.
-        let must_schedule = {
-            let mut queue = self.inner.offer_sending_queue.lock().await;
-            let result = queue.len() == 0;
-
-            queue.append(&mut offer_ids.clone());
-            result
-        };
+        let must_schedule = self
+            .inner
+            .offer_sending_queue
+            .lock()
+            .await
+            .push(&offer_ids, ttl);
         log::trace!(
             "bcast_offers done appending {} offers. must_schedule={}",
             offer_ids.len(),
@@ -164,11 +196,11 @@ impl Discovery {
     /// get call to function bound at `OfferBcast`.
     async fn send_bcast_offers(&self) {
         // `...offer_queue` MUST be empty to trigger the sending again
-        let offer_ids: Vec<SubscriptionId> =
-            std::mem::take(&mut *self.inner.offer_sending_queue.lock().await);
+        let groups = self.inner.offer_sending_queue.lock().await.take();
 
         // Should never happen, but just to be certain.
-        if offer_ids.is_empty() {
+        let size: usize = groups.iter().map(|(_, offer_ids)| offer_ids.len()).sum();
+        if size == 0 {
             return;
         }
 
@@ -182,24 +214,25 @@ impl Discovery {
                 return;
             }
         };
-        let size = offer_ids.len();
         log::debug!("Broadcasting offers. count={}", size);
 
         counter!("market.offers.broadcasts.net", 1);
         value!("market.offers.broadcasts.len", size as u64);
 
-        if self.is_hybrid_net() {
-            let mut iter = offer_ids.into_iter().peekable();
-            while iter.peek().is_some() {
-                let chunk = iter.by_ref().take(MAX_OFFER_IDS_PER_BROADCAST).collect();
-                broadcast_offers(default_id, chunk).await;
-
-                // Spread broadcasts into longer time frame. This way we avoid dropping Offers
-                // on the other side and reduce peak network usage.
-                tokio::time::sleep(self.inner.config.bcast_tile_time_margin).await;
+        for (ttl, offer_ids) in groups {
+            if self.is_hybrid_net() {
+                let mut iter = offer_ids.into_iter().peekable();
+                while iter.peek().is_some() {
+                    let chunk = iter.by_ref().take(MAX_OFFER_IDS_PER_BROADCAST).collect();
+                    broadcast_offers(default_id, chunk, ttl).await;
+
+                    // Spread broadcasts into longer time frame. This way we avoid dropping Offers
+                    // on the other side and reduce peak network usage.
+                    tokio::time::sleep(self.inner.config.bcast_tile_time_margin).await;
+                }
+            } else {
+                broadcast_offers(default_id, offer_ids, ttl).await;
             }
-        } else {
-            broadcast_offers(default_id, offer_ids).await;
         }
     }
 
@@ -234,19 +267,27 @@ impl Discovery {
     pub async fn bcast_unsubscribes(
         &self,
         offer_ids: Vec<SubscriptionId>,
+    ) -> Result<(), DiscoveryError> {
+        self.queue_unsubscribes(offer_ids, None).await
+    }
+
+    /// Queues unsubscribes for broadcast limited to `ttl` hops, or the default TTL.
+    async fn queue_unsubscribes(
+        &self,
+        offer_ids: Vec<SubscriptionId>,
+        ttl: Option<u8>,
     ) -> Result<(), DiscoveryError> {
         if offer_ids.is_empty() {
             return Ok(());
         }
 
         // When there are 0 items in the queue we should schedule a send job.
-        let must_schedule = {
-            let mut queue = self.inner.unsub_sending_queue.lock().await;
-            let result = queue.len() == 0;
-
-            queue.append(&mut offer_ids.clone());
-            result
-        };
+        let must_schedule = self
+            .inner
+            .unsub_sending_queue
+            .lock()
+            .await
+            .push(&offer_ids, ttl);
 
         log::trace!(
             "bcast_unsubscribes done appending {} offers. must_schedule={}",
@@ -267,16 +308,11 @@ impl Discovery {
 
     async fn send_bcast_unsubscribes(&self) {
         // `...unsub_queue` MUST be empty to trigger the sending again
-        let offer_ids: Vec<SubscriptionId> = self
-            .inner
-            .unsub_sending_queue
-            .lock()
-            .await
-            .drain(..)
-            .collect();
+        let groups = self.inner.unsub_sending_queue.lock().await.take();
 
         // Should never happen, but just to be certain.
-        if offer_ids.is_empty() {
+        let size: usize = groups.iter().map(|(_, offer_ids)| offer_ids.len()).sum();
+        if size == 0 {
             return;
         }
         let default_id = match self.default_identity().await {
@@ -290,19 +326,20 @@ impl Discovery {
             }
         };
 
-        let size = offer_ids.len();
         log::debug!("Broadcasting unsubscribes. count={}", size);
         counter!("market.offers.unsubscribes.broadcasts.net", 1);
         value!("market.offers.unsubscribes.broadcasts.len", size as u64);
 
-        if self.is_hybrid_net() {
-            let mut iter = offer_ids.into_iter().peekable();
-            while iter.peek().is_some() {
-                let chunk = iter.by_ref().take(MAX_OFFER_IDS_PER_BROADCAST).collect();
-                broadcast_unsubscribed(default_id, chunk).await;
+        for (ttl, offer_ids) in groups {
+            if self.is_hybrid_net() {
+                let mut iter = offer_ids.into_iter().peekable();
+                while iter.peek().is_some() {
+                    let chunk = iter.by_ref().take(MAX_OFFER_IDS_PER_BROADCAST).collect();
+                    broadcast_unsubscribed(default_id, chunk, ttl).await;
+                }
+            } else {
+                broadcast_unsubscribed(default_id, offer_ids, ttl).await;
             }
-        } else {
-            broadcast_unsubscribed(default_id, offer_ids).await;
         }
     }
 
@@ -367,7 +404,7 @@ impl Discovery {
             &bcast_address,
             move |caller, msg: SendBroadcastMessage<OffersBcast>| {
                 let myself = myself.clone();
-                myself.on_bcast_offers(caller, msg.body().to_owned())
+                myself.on_bcast_offers(caller, msg.body().to_owned(), Some(msg.next_ttl()))
             },
         )
         .await
@@ -380,7 +417,7 @@ impl Discovery {
             &bcast_address,
             move |caller, msg: SendBroadcastMessage<UnsubscribedOffersBcast>| {
                 let myself = myself.clone();
-                myself.on_bcast_unsubscribes(caller, msg.body().to_owned())
+                myself.on_bcast_unsubscribes(caller, msg.body().to_owned(), Some(msg.next_ttl()))
             },
         )
         .await
@@ -388,10 +425,13 @@ impl Discovery {
         Ok(())
     }
 
-    async fn bcast_receiver_loop(self, mut offers_channel: mpsc::Receiver<(NodeId, OffersBcast)>) {
-        while let Some((caller, msg)) = offers_channel.recv().await {
+    async fn bcast_receiver_loop(
+        self,
+        mut offers_channel: mpsc::Receiver<(NodeId, OffersBcast, Option<u8>)>,
+    ) {
+        while let Some((caller, msg, ttl)) = offers_channel.recv().await {
             if !self.inner.ban_cache.is_banned_node(&caller) {
-                self.bcast_receiver_loop_step(caller, msg).await.ok();
+                self.bcast_receiver_loop_step(caller, msg, ttl).await.ok();
             } else {
                 log::trace!("banned node: {caller}");
             }
@@ -400,7 +440,12 @@ impl Discovery {
         log::debug!("Broadcast receiver loop stopped.");
     }
 
-    async fn bcast_receiver_loop_step(&self, caller: NodeId, msg: OffersBcast) -> Result<(), ()> {
+    async fn bcast_receiver_loop_step(
+        &self,
+        caller: NodeId,
+        msg: OffersBcast,
+        ttl: Option<u8>,
+    ) -> Result<(), ()> {
         let start = Instant::now();
         let num_ids_received = msg.offer_ids.len();
 
@@ -441,13 +486,13 @@ impl Discovery {
             vec![]
         };
 
-        if self.re_broadcast_enabled() && !new_offer_ids.is_empty() {
+        if self.re_broadcast_enabled() && ttl != Some(0) && !new_offer_ids.is_empty() {
             log::trace!(
                 "Propagating {}/{num_ids_received} Offers received from [{caller}].",
                 new_offer_ids.len(),
             );
 
-            self.bcast_offers(new_offer_ids)
+            self.queue_offers(new_offer_ids, ttl)
                 .await
                 .map_err(|e| log::warn!("Failed to broadcast. Error: {e}"))?;
         }
@@ -457,7 +502,12 @@ impl Discovery {
         Ok(())
     }
 
-    async fn on_bcast_offers(self, caller: String, msg: OffersBcast) -> Result<(), ()> {
+    async fn on_bcast_offers(
+        self,
+        caller: String,
+        msg: OffersBcast,
+        ttl: Option<u8>,
+    ) -> Result<(), ()> {
         let num_ids_received = msg.offer_ids.len();
         log::trace!("Received {num_ids_received} Offers from [{caller}].");
 
@@ -470,7 +520,11 @@ impl Discovery {
         let caller: NodeId = caller.parse().map_err(|_| ())?;
         // We don't want to get overwhelmed by incoming broadcasts, that's why we drop them,
         // if the queue is full.
-        match self.inner.offers_receiving_queue.try_send((caller, msg)) {
+        match self
+            .inner
+            .offers_receiving_queue
+            .try_send((caller, msg, ttl))
+        {
             Ok(_) => Ok(()),
             Err(_) => {
                 log::trace!("Already handling to many broadcasts, skipping...");
@@ -494,6 +548,7 @@ impl Discovery {
         self,
         caller: String,
         msg: UnsubscribedOffersBcast,
+        ttl: Option<u8>,
     ) -> Result<(), ()> {
         let start = Instant::now();
         let num_received_ids = msg.offer_ids.len();
@@ -506,14 +561,14 @@ impl Discovery {
         let offer_unsubscribe_handler = self.inner.offer_handlers.offer_unsubscribe_handler.clone();
         let unsubscribed_offer_ids = offer_unsubscribe_handler.call(caller.clone(), msg).await?;
 
-        if self.re_broadcast_enabled() && !unsubscribed_offer_ids.is_empty() {
+        if self.re_broadcast_enabled() && ttl != Some(0) && !unsubscribed_offer_ids.is_empty() {
             log::trace!(
                 "Propagating {}/{num_received_ids} unsubscribed Offers received from [{caller}].",
                 unsubscribed_offer_ids.len(),
             );
 
             // No need to retry broadcasting, since we send cyclic broadcasts.
-            if let Err(error) = self.bcast_unsubscribes(unsubscribed_offer_ids).await {
+            if let Err(error) = self.queue_unsubscribes(unsubscribed_offer_ids, ttl).await {
                 log::error!("Error propagating unsubscribed Offers further: {error}");
             }
         }
@@ -527,16 +582,71 @@ impl Discovery {
     }
 }
 
-async fn broadcast_offers(node_id: NodeId, offer_ids: Vec<SubscriptionId>) {
-    if let Err(e) = net::broadcast(node_id, OffersBcast { offer_ids }).await {
+async fn broadcast_offers(node_id: NodeId, offer_ids: Vec<SubscriptionId>, ttl: Option<u8>) {
+    if let Err(e) = net::broadcast_with_ttl(node_id, OffersBcast { offer_ids }, ttl).await {
         log::error!("Error broadcasting offers: {e}");
         counter!("market.offers.broadcasts.net_errors", 1);
     };
 }
 
-async fn broadcast_unsubscribed(node_id: NodeId, offer_ids: Vec<SubscriptionId>) {
-    if let Err(e) = net::broadcast(node_id, UnsubscribedOffersBcast { offer_ids }).await {
+async fn broadcast_unsubscribed(node_id: NodeId, offer_ids: Vec<SubscriptionId>, ttl: Option<u8>) {
+    if let Err(e) =
+        net::broadcast_with_ttl(node_id, UnsubscribedOffersBcast { offer_ids }, ttl).await
+    {
         log::error!("Error broadcasting unsubscribed offers: {e}");
         counter!("market.offers.unsubscribes.broadcasts.net_errors", 1);
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<SubscriptionId> {
+        (0..n)
+            .map(|i| {
+                let id = format!("{:032x}-{:064x}", i, i);
+                SubscriptionId::from_str(&id).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sending_queue_ttl() {
+        let mut queue = SendingQueue::default();
+        assert!(queue.push(&ids(1), Some(2)));
+        assert!(!queue.push(&ids(2), Some(5)));
+        assert!(!queue.push(&ids(1), Some(2)));
+
+        assert_eq!(
+            queue.take(),
+            vec![(Some(2), [ids(1), ids(1)].concat()), (Some(5), ids(2))]
+        );
+
+        // Queue is reset after sending.
+        assert!(queue.push(&ids(1), Some(1)));
+        assert_eq!(queue.take(), vec![(Some(1), ids(1))]);
+    }
+
+    #[test]
+    fn test_sending_queue_default_ttl() {
+        let mut queue = SendingQueue::default();
+        queue.push(&ids(1), Some(1));
+        queue.push(&ids(2), None);
+
+        // Propagated Offers keep their TTL, when queued together with our own.
+        assert_eq!(queue.take(), vec![(None, ids(2)), (Some(1), ids(1))]);
+    }
+
+    #[test]
+    fn test_legacy_ttl_propagation() {
+        let mut msg = SendBroadcastMessage::new(OffersBcast { offer_ids: ids(1) });
+        let mut queue = SendingQueue::default();
+
+        // Broadcast from an older Node reaches only our neighbours.
+        queue.push(&ids(1), Some(msg.next_ttl()));
+        let (ttl, _) = queue.take().remove(0);
+        msg.set_ttl(ttl.unwrap());
+        assert_eq!(msg.next_ttl(), 0);
+    }
+}
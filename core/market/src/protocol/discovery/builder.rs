@@ -8,7 +8,7 @@ use crate::protocol::callback::{CallbackFuture, OutputFuture};
 use crate::protocol::callback::{CallbackHandler, CallbackMessage, HandlerSlot};
 use ya_net::{self as net};
 
-use super::{BanCache, Discovery, DiscoveryImpl};
+use super::{BanCache, Discovery, DiscoveryImpl, SendingQueue};
 use crate::config::DiscoveryConfig;
 use crate::protocol::discovery::OfferHandlers;
 
@@ -81,8 +81,8 @@ impl DiscoveryBuilder {
             inner: Arc::new(DiscoveryImpl {
                 identity: self.get_data(),
                 offer_handlers,
-                offer_sending_queue: Mutex::new(vec![]),
-                unsub_sending_queue: Mutex::new(vec![]),
+                offer_sending_queue: Mutex::new(SendingQueue::default()),
+                unsub_sending_queue: Mutex::new(SendingQueue::default()),
                 lazy_binder_prefix: Mutex::new(None),
                 config: self.config.clone().unwrap(),
                 net_type: net::Config::from_env().unwrap().net_type,
@@ -9,8 +9,11 @@ use actix_web::{error::InternalError, http::StatusCode, web::PathConfig, HttpRes
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
-use ya_client::model::{market::agreement::State, ErrorMessage};
+use ya_client::model::market::agreement::State;
 use ya_core_model::NodeId;
+use ya_service_api_web::error::ApiError;
+use ya_service_api_web::openapi::{ApiScope, ScopeSpec};
+use ya_service_api_web::scope::ExtendableScope;
 
 use crate::db::model::{
     AgreementId, AppSessionId, Owner, ProposalId, ProposalIdParseError, SubscriptionId,
@@ -27,16 +30,28 @@ const DEFAULT_QUERY_TIMEOUT: f32 = 5.0;
 pub fn path_config() -> PathConfig {
     PathConfig::default().error_handler(|err, _req| {
         InternalError::new(
-            serde_json::to_string(&ErrorMessage::new(err.to_string())).unwrap(),
+            serde_json::to_string(&ApiError::bad_request("INVALID_PATH", &err)).unwrap(),
             StatusCode::BAD_REQUEST,
         )
         .into()
     })
 }
 
+/// Routes mounted by `MarketService::bind_rest`
+pub fn api_scope() -> ApiScope {
+    ApiScope::new("market", ya_client::model::market::MARKET_API_PATH)
+        .extend(common::register_endpoints)
+        .extend(provider::register_endpoints)
+        .extend(requestor::register_endpoints)
+}
+
+pub fn api_spec() -> ScopeSpec {
+    api_scope().into_spec()
+}
+
 pub fn json_config() -> JsonConfig {
     JsonConfig::default().error_handler(|err, _req| {
-        let resp = HttpResponse::BadRequest().json(ErrorMessage::new(&err));
+        let resp = ApiError::bad_request("INVALID_JSON", &err).response();
         InternalError::from_response(err, resp).into()
     })
 }
@@ -1,14 +1,17 @@
 use actix_web::http::header;
 use actix_web::http::header::CacheDirective;
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::{web, Either, HttpResponse, Responder, Scope};
+use actix_web::{web, Either, HttpResponse, Responder};
 use chrono::{TimeZone, Utc};
 use std::convert::TryInto;
 use std::sync::Arc;
 
 use ya_client::model::market::scan::NewScan;
-use ya_client::model::market::{Offer, Reason};
+use ya_client::model::market::{
+    Agreement, AgreementListEntry, AgreementOperationEvent, Offer, Reason,
+};
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_std_utils::LogErr;
 
@@ -21,19 +24,53 @@ use crate::rest_api::{QueryAgreementEvents, QueryAgreementList};
 use futures::prelude::*;
 use tracing::Level;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .service(list_agreements)
-        .service(collect_agreement_events)
-        .service(get_agreement)
-        .service(terminate_agreement)
-        .service(get_agreement_terminate_reason)
-        .service(scan_begin)
-        .service(scan_collect)
-        .service(scan_end)
+        .route(
+            Operation::get("/agreements", "listAgreements")
+                .response(Schema::array_of_model::<AgreementListEntry>()),
+            list_agreements,
+        )
+        .route(
+            Operation::get("/agreementEvents", "collectAgreementEvents")
+                .response(Schema::array_of_model::<AgreementOperationEvent>()),
+            collect_agreement_events,
+        )
+        .route(
+            Operation::get("/agreements/{agreement_id}", "getAgreement")
+                .response(Schema::model::<Agreement>()),
+            get_agreement,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/terminate", "terminateAgreement")
+                .body(Schema::model::<Reason>()),
+            terminate_agreement,
+        )
+        .route(
+            Operation::get(
+                "/agreements/{agreement_id}/terminate/reason",
+                "getAgreementTerminationReason",
+            )
+            .response(Schema::model::<Reason>()),
+            get_agreement_terminate_reason,
+        )
+        .route(
+            Operation::post("/scan", "beginScan")
+                .body(Schema::model::<NewScan>())
+                .response(Schema::of::<String>()),
+            scan_begin,
+        )
+        .route(
+            Operation::get("/scan/{scanId}/events", "collectScanResults")
+                .response(Schema::array_of_model::<Offer>()),
+            scan_collect,
+        )
+        .route(
+            Operation::delete("/scan/{subscriptionId}", "endScan"),
+            scan_end,
+        )
 }
 
-#[actix_web::get("/agreements")]
 async fn list_agreements(
     market: Data<Arc<MarketService>>,
     query: Query<QueryAgreementList>,
@@ -53,7 +90,6 @@ async fn list_agreements(
         .map(|list| HttpResponse::Ok().json(list))
 }
 
-#[actix_web::get("/agreements/{agreement_id}")]
 async fn get_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -81,7 +117,6 @@ async fn get_agreement(
     }
 }
 
-#[actix_web::get("/agreementEvents")]
 async fn collect_agreement_events(
     market: Data<Arc<MarketService>>,
     query: Query<QueryAgreementEvents>,
@@ -105,7 +140,6 @@ async fn collect_agreement_events(
         .map(|events| HttpResponse::Ok().json(events))
 }
 
-#[actix_web::post("/agreements/{agreement_id}/terminate")]
 async fn terminate_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -120,7 +154,6 @@ async fn terminate_agreement(
         .map(|_| HttpResponse::Ok().finish())
 }
 
-#[actix_web::get("/agreements/{agreement_id}/terminate/reason")]
 async fn get_agreement_terminate_reason(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -134,7 +167,6 @@ async fn get_agreement_terminate_reason(
         .map(|reason| HttpResponse::Ok().json(reason))
 }
 
-#[actix_web::post("/scan")]
 async fn scan_begin(
     id: Identity,
     Json(spec): Json<NewScan>,
@@ -144,7 +176,6 @@ async fn scan_begin(
     Ok(HttpResponse::Created().json(id))
 }
 
-#[actix_web::get("/scan/{scanId}/events")]
 async fn scan_collect(
     id: Identity,
     path: Path<(String,)>,
@@ -235,7 +266,6 @@ async fn scan_collect(
     }
 }
 
-#[actix_web::delete("/scan/{subscriptionId}")]
 async fn scan_end(
     id: Identity,
     path: Path<(String,)>,
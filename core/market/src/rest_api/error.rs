@@ -1,10 +1,12 @@
 use actix_web::{HttpResponse, ResponseError};
 
-use ya_client::model::ErrorMessage;
+use ya_service_api_web::error::{self as api, ApiError, ErrorCategory};
 
 use crate::db::dao::{AgreementDaoError, SaveProposalError};
 use crate::db::model::AgreementState;
-use crate::negotiation::error::{AgreementEventsError, ProposalValidationError};
+use crate::negotiation::error::{
+    AgreementEventsError, MatchValidationError, ProposalValidationError,
+};
 use crate::protocol::negotiation::error::RejectProposalError;
 use crate::{
     db::dao::TakeEventsError,
@@ -52,215 +54,276 @@ impl ResponseError for MatcherError {
 
 impl ResponseError for NegotiationError {}
 
+fn respond(category: ErrorCategory, code: &str, e: &impl std::fmt::Display) -> HttpResponse {
+    ApiError::new(category, code, e).response()
+}
+
+fn internal(e: &impl std::fmt::Display) -> HttpResponse {
+    respond(ErrorCategory::Internal, api::INTERNAL_ERROR, e)
+}
+
 impl ResponseError for ResolverError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(ErrorMessage::new(self.to_string()))
+        internal(self)
     }
 }
 
 impl ResponseError for DemandError {
     fn error_response(&self) -> HttpResponse {
         match self {
-            DemandError::NotFound(_) => {
-                HttpResponse::NotFound().json(ErrorMessage::new(self.to_string()))
-            }
-            _ => HttpResponse::InternalServerError().json(ErrorMessage::new(self.to_string())),
+            DemandError::NotFound(_) => respond(ErrorCategory::NotFound, "DEMAND_NOT_FOUND", self),
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for QueryDemandsError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(ErrorMessage::new(self.to_string()))
+        internal(self)
     }
 }
 
 impl ResponseError for QueryOffersError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::InternalServerError().json(ErrorMessage::new(self.to_string()))
+        internal(self)
     }
 }
 
 impl ResponseError for QueryOfferError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            QueryOfferError::NotFound(_) => HttpResponse::NotFound().json(msg),
-            _ => HttpResponse::InternalServerError().json(msg),
+            QueryOfferError::NotFound(_) => {
+                respond(ErrorCategory::NotFound, "OFFER_NOT_FOUND", self)
+            }
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for SaveOfferError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            SaveOfferError::Unsubscribed(_) | SaveOfferError::Expired(_) => {
-                HttpResponse::Gone().json(msg)
+            SaveOfferError::Unsubscribed(_) => {
+                respond(ErrorCategory::Gone, "SUBSCRIPTION_UNSUBSCRIBED", self)
             }
-            _ => HttpResponse::InternalServerError().json(msg),
+            SaveOfferError::Expired(_) => {
+                respond(ErrorCategory::Gone, "SUBSCRIPTION_EXPIRED", self)
+            }
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for ModifyOfferError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            ModifyOfferError::NotFound(_) => HttpResponse::NotFound().json(msg),
-            ModifyOfferError::AlreadyUnsubscribed(_) | ModifyOfferError::Expired(_) => {
-                HttpResponse::Gone().json(msg)
+            ModifyOfferError::NotFound(_) => {
+                respond(ErrorCategory::NotFound, "OFFER_NOT_FOUND", self)
+            }
+            ModifyOfferError::AlreadyUnsubscribed(_) => {
+                respond(ErrorCategory::Gone, "SUBSCRIPTION_UNSUBSCRIBED", self)
             }
-            _ => HttpResponse::InternalServerError().json(msg),
+            ModifyOfferError::Expired(_) => {
+                respond(ErrorCategory::Gone, "SUBSCRIPTION_EXPIRED", self)
+            }
+            ModifyOfferError::InvalidProperties(..) => {
+                respond(ErrorCategory::BadRequest, "INVALID_PROPERTIES", self)
+            }
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for QueryEventsError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            QueryEventsError::TakeEvents(TakeEventsError::NotFound(_))
-            | QueryEventsError::TakeEvents(TakeEventsError::Expired(_)) => {
-                HttpResponse::NotFound().json(msg)
+            QueryEventsError::TakeEvents(TakeEventsError::NotFound(_)) => {
+                respond(ErrorCategory::NotFound, "SUBSCRIPTION_NOT_FOUND", self)
+            }
+            QueryEventsError::TakeEvents(TakeEventsError::Expired(_)) => {
+                respond(ErrorCategory::NotFound, "SUBSCRIPTION_EXPIRED", self)
             }
-            QueryEventsError::InvalidSubscriptionId(_) | QueryEventsError::InvalidMaxEvents(..) => {
-                HttpResponse::BadRequest().json(msg)
+            QueryEventsError::InvalidSubscriptionId(_) => {
+                respond(ErrorCategory::BadRequest, "INVALID_SUBSCRIPTION_ID", self)
             }
-            _ => HttpResponse::InternalServerError().json(msg),
+            QueryEventsError::InvalidMaxEvents(..) => {
+                respond(ErrorCategory::BadRequest, "INVALID_MAX_EVENTS", self)
+            }
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for ProposalError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
             ProposalError::Validation(e) => e.error_response(),
             ProposalError::Save(SaveProposalError::AlreadyCountered(..)) => {
-                HttpResponse::Gone().json(msg)
+                respond(ErrorCategory::Gone, "PROPOSAL_ALREADY_COUNTERED", self)
             }
             ProposalError::Get(e) => e.error_response(),
             ProposalError::Reject(e) => e.error_response(),
             // TODO: get rid of those `_` patterns as they do not break when error is extended
-            _ => HttpResponse::InternalServerError().json(msg),
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for RejectProposalError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            RejectProposalError::Validation(_) => HttpResponse::BadRequest().json(msg),
+            RejectProposalError::Validation(_) => {
+                respond(ErrorCategory::BadRequest, "INVALID_PROPOSAL", self)
+            }
             RejectProposalError::Gsb(_)
             | RejectProposalError::Get(_)
             | RejectProposalError::ChangeState(_)
-            | RejectProposalError::CallerParse(_) => HttpResponse::InternalServerError().json(msg),
+            | RejectProposalError::CallerParse(_) => internal(self),
         }
     }
 }
 
 impl ResponseError for ProposalValidationError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            ProposalValidationError::NoSubscription(_)
-            | ProposalValidationError::Unsubscribed(_)
-            | ProposalValidationError::NotMatching(_)
-            | ProposalValidationError::OwnProposal(_) => HttpResponse::BadRequest().json(msg),
-            ProposalValidationError::SubscriptionExpired(_) => HttpResponse::Gone().json(msg),
-            ProposalValidationError::Unauthorized(_, _) => HttpResponse::Unauthorized().json(msg),
-            ProposalValidationError::Internal(_) => HttpResponse::InternalServerError().json(msg),
+            ProposalValidationError::NoSubscription(_) => {
+                respond(ErrorCategory::BadRequest, "SUBSCRIPTION_NOT_FOUND", self)
+            }
+            ProposalValidationError::Unsubscribed(_) => {
+                respond(ErrorCategory::BadRequest, "SUBSCRIPTION_UNSUBSCRIBED", self)
+            }
+            ProposalValidationError::NotMatching(MatchValidationError::NotMatching {
+                explanation: Some(explanation),
+                ..
+            }) => ApiError::new(ErrorCategory::BadRequest, "PROPOSAL_NOT_MATCHING", self)
+                .with_detail("explanation", explanation)
+                .response(),
+            ProposalValidationError::NotMatching(_) => {
+                respond(ErrorCategory::BadRequest, "PROPOSAL_NOT_MATCHING", self)
+            }
+            ProposalValidationError::OwnProposal(_) => {
+                respond(ErrorCategory::BadRequest, "OWN_PROPOSAL", self)
+            }
+            ProposalValidationError::SubscriptionExpired(_) => {
+                respond(ErrorCategory::Gone, "SUBSCRIPTION_EXPIRED", self)
+            }
+            ProposalValidationError::Unauthorized(_, _) => {
+                respond(ErrorCategory::Unauthorized, api::UNAUTHORIZED, self)
+            }
+            ProposalValidationError::Internal(_) => internal(self),
         }
     }
 }
 
 impl ResponseError for GetProposalError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            GetProposalError::NotFound(..) => HttpResponse::NotFound().json(msg),
-            _ => HttpResponse::InternalServerError().json(msg),
+            GetProposalError::NotFound(..) => {
+                respond(ErrorCategory::NotFound, "PROPOSAL_NOT_FOUND", self)
+            }
+            _ => internal(self),
         }
     }
 }
 
 impl ResponseError for AgreementError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
+        let bad_request = |code| respond(ErrorCategory::BadRequest, code, self);
         match self {
-            AgreementError::NotFound(_) => HttpResponse::NotFound().json(msg),
-            AgreementError::Expired(_) => HttpResponse::Gone().json(msg),
-            AgreementError::ProposalAlreadyAccepted(..) => HttpResponse::Conflict().json(msg),
+            AgreementError::NotFound(_) => {
+                respond(ErrorCategory::NotFound, "AGREEMENT_NOT_FOUND", self)
+            }
+            AgreementError::Expired(_) => respond(ErrorCategory::Gone, "AGREEMENT_EXPIRED", self),
+            AgreementError::ProposalAlreadyAccepted(..) => {
+                respond(ErrorCategory::Conflict, "PROPOSAL_ALREADY_ACCEPTED", self)
+            }
             AgreementError::UpdateState(_, e) => e.error_response(),
-            AgreementError::NoNegotiations(_)
-            | AgreementError::ProposalRejected(..)
-            | AgreementError::OwnProposal(..)
-            | AgreementError::ProposalNotFound(..)
-            | AgreementError::ProposalCountered(..)
-            | AgreementError::InvalidDate(..)
-            | AgreementError::InvalidAgreementState(..)
-            | AgreementError::InvalidId(..)
-            | AgreementError::NotTerminated(..) => HttpResponse::BadRequest().json(msg),
+            AgreementError::NoNegotiations(_) => bad_request("NO_NEGOTIATIONS"),
+            AgreementError::ProposalRejected(..) => bad_request("PROPOSAL_REJECTED"),
+            AgreementError::OwnProposal(..) => bad_request("OWN_PROPOSAL"),
+            AgreementError::ProposalNotFound(..) => bad_request("PROPOSAL_NOT_FOUND"),
+            AgreementError::ProposalCountered(..) => bad_request("PROPOSAL_ALREADY_COUNTERED"),
+            AgreementError::InvalidDate(..) => bad_request("INVALID_DATE"),
+            AgreementError::InvalidAgreementState(..) => bad_request("INVALID_AGREEMENT_STATE"),
+            AgreementError::InvalidId(..) => bad_request("INVALID_AGREEMENT_ID"),
+            AgreementError::NotTerminated(..) => bad_request("AGREEMENT_NOT_TERMINATED"),
+            AgreementError::ProtocolCreate(_)
+            | AgreementError::Protocol(_)
+            | AgreementError::ProtocolTerminate(_)
+            | AgreementError::ProtocolCommit(_) => {
+                ApiError::new(ErrorCategory::Internal, "AGREEMENT_PROTOCOL_ERROR", self).response()
+            }
             AgreementError::GetProposal(..)
             | AgreementError::Save(..)
             | AgreementError::Get(..)
             | AgreementError::Gsb(_)
-            | AgreementError::ProtocolCreate(_)
-            | AgreementError::Protocol(_)
-            | AgreementError::ProtocolTerminate(_)
-            | AgreementError::ProtocolCommit(_)
-            | AgreementError::Internal(_) => HttpResponse::InternalServerError().json(msg),
+            | AgreementError::Internal(_) => internal(self),
         }
     }
 }
 
 impl ResponseError for AgreementDaoError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
             AgreementDaoError::InvalidTransition { from, .. } => match from {
-                AgreementState::Proposal => HttpResponse::Conflict().json(msg),
+                AgreementState::Proposal => respond(
+                    ErrorCategory::Conflict,
+                    "INVALID_AGREEMENT_TRANSITION",
+                    self,
+                ),
                 AgreementState::Pending
                 | AgreementState::Approving
                 | AgreementState::Cancelled
                 | AgreementState::Rejected
                 | AgreementState::Expired
                 | AgreementState::Approved
-                | AgreementState::Terminated => HttpResponse::Gone().json(msg),
+                | AgreementState::Terminated => {
+                    respond(ErrorCategory::Gone, "INVALID_AGREEMENT_TRANSITION", self)
+                }
             },
-            AgreementDaoError::InvalidId(_) => HttpResponse::BadRequest().json(msg),
+            AgreementDaoError::InvalidId(_) => {
+                respond(ErrorCategory::BadRequest, "INVALID_AGREEMENT_ID", self)
+            }
             AgreementDaoError::DbError(_)
             | AgreementDaoError::SessionId(_)
-            | AgreementDaoError::EventError(_) => HttpResponse::InternalServerError().json(msg),
+            | AgreementDaoError::EventError(_) => internal(self),
         }
     }
 }
 
 impl ResponseError for WaitForApprovalError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            WaitForApprovalError::NotFound(_) => HttpResponse::NotFound().json(msg),
-            WaitForApprovalError::Expired(_) => HttpResponse::Gone().json(msg),
-            WaitForApprovalError::Terminated(_)
-            | WaitForApprovalError::NotConfirmed(_)
-            | WaitForApprovalError::InvalidId(..) => HttpResponse::BadRequest().json(msg),
-            WaitForApprovalError::Timeout(_) => HttpResponse::RequestTimeout().json(msg),
-            WaitForApprovalError::Internal(_) | WaitForApprovalError::Get(..) => {
-                HttpResponse::InternalServerError().json(msg)
+            WaitForApprovalError::NotFound(_) => {
+                respond(ErrorCategory::NotFound, "AGREEMENT_NOT_FOUND", self)
+            }
+            WaitForApprovalError::Expired(_) => {
+                respond(ErrorCategory::Gone, "AGREEMENT_EXPIRED", self)
+            }
+            WaitForApprovalError::Terminated(_) => {
+                respond(ErrorCategory::BadRequest, "AGREEMENT_TERMINATED", self)
             }
+            WaitForApprovalError::NotConfirmed(_) => {
+                respond(ErrorCategory::BadRequest, "AGREEMENT_NOT_CONFIRMED", self)
+            }
+            WaitForApprovalError::InvalidId(..) => {
+                respond(ErrorCategory::BadRequest, "INVALID_AGREEMENT_ID", self)
+            }
+            WaitForApprovalError::Timeout(_) => {
+                respond(ErrorCategory::Timeout, "APPROVAL_TIMEOUT", self)
+            }
+            WaitForApprovalError::Internal(_) | WaitForApprovalError::Get(..) => internal(self),
         }
     }
 }
 
 impl ResponseError for AgreementEventsError {
     fn error_response(&self) -> HttpResponse {
-        let msg = ErrorMessage::new(self.to_string());
         match self {
-            AgreementEventsError::InvalidMaxEvents(..) => HttpResponse::BadRequest().json(msg),
-            AgreementEventsError::Internal(_) => HttpResponse::InternalServerError().json(msg),
+            AgreementEventsError::InvalidMaxEvents(..) => {
+                respond(ErrorCategory::BadRequest, "INVALID_MAX_EVENTS", self)
+            }
+            AgreementEventsError::Internal(_) => internal(self),
         }
     }
 }
@@ -1,9 +1,11 @@
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::{HttpResponse, Responder, Scope};
+use actix_web::{HttpResponse, Responder};
 use std::sync::Arc;
 
-use ya_client::model::market::{NewOffer, NewProposal, Reason};
+use ya_client::model::market::{NewOffer, NewProposal, Offer, Proposal, ProviderEvent, Reason};
+use ya_service_api_web::error::{ApiError, ErrorCategory};
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_std_utils::LogErr;
 
 use crate::db::model::Owner;
@@ -12,22 +14,70 @@ use crate::market::MarketService;
 use super::{PathAgreement, PathSubscription, PathSubscriptionProposal, QueryTimeoutMaxEvents};
 use crate::negotiation::ApprovalResult;
 use crate::rest_api::QueryTimeoutAppSessionId;
-use ya_client::model::ErrorMessage;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .service(subscribe)
-        .service(get_offers)
-        .service(unsubscribe)
-        .service(collect)
-        .service(counter_proposal)
-        .service(get_proposal)
-        .service(reject_proposal)
-        .service(approve_agreement)
-        .service(reject_agreement)
+        .route(
+            Operation::post("/offers", "subscribeOffer")
+                .body(Schema::model::<NewOffer>())
+                .response(Schema::of::<String>()),
+            subscribe,
+        )
+        .route(
+            Operation::get("/offers", "getOffers").response(Schema::array_of_model::<Offer>()),
+            get_offers,
+        )
+        .route(
+            Operation::delete("/offers/{subscription_id}", "unsubscribeOffer"),
+            unsubscribe,
+        )
+        .route(
+            Operation::patch("/offers/{subscription_id}/properties", "reviseOffer")
+                .body(Schema::of::<serde_json::Value>())
+                .response(Schema::of::<String>()),
+            revise,
+        )
+        .route(
+            Operation::get("/offers/{subscription_id}/events", "collectDemands")
+                .response(Schema::array_of_model::<ProviderEvent>()),
+            collect,
+        )
+        .route(
+            Operation::post(
+                "/offers/{subscription_id}/proposals/{proposal_id}",
+                "counterProposalOffer",
+            )
+            .body(Schema::model::<NewProposal>())
+            .response(Schema::of::<String>()),
+            counter_proposal,
+        )
+        .route(
+            Operation::get(
+                "/offers/{subscription_id}/proposals/{proposal_id}",
+                "getProposalDemand",
+            )
+            .response(Schema::model::<Proposal>()),
+            get_proposal,
+        )
+        .route(
+            Operation::post(
+                "/offers/{subscription_id}/proposals/{proposal_id}/reject",
+                "rejectProposalDemand",
+            )
+            .body(Schema::model::<Reason>()),
+            reject_proposal,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/approve", "approveAgreement"),
+            approve_agreement,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/reject", "rejectAgreement")
+                .body(Schema::model::<Reason>()),
+            reject_agreement,
+        )
 }
 
-#[actix_web::post("/offers")]
 async fn subscribe(
     market: Data<Arc<MarketService>>,
     body: Json<NewOffer>,
@@ -40,7 +90,6 @@ async fn subscribe(
         .map(|id| HttpResponse::Created().json(id))
 }
 
-#[actix_web::get("/offers")]
 async fn get_offers(market: Data<Arc<MarketService>>, id: Identity) -> impl Responder {
     market
         .get_offers(Some(id))
@@ -49,7 +98,6 @@ async fn get_offers(market: Data<Arc<MarketService>>, id: Identity) -> impl Resp
         .map(|offers| HttpResponse::Ok().json(offers))
 }
 
-#[actix_web::delete("/offers/{subscription_id}")]
 async fn unsubscribe(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
@@ -62,14 +110,32 @@ async fn unsubscribe(
         .map(|_| HttpResponse::NoContent())
 }
 
-#[actix_web::get("/offers/{subscription_id}/events")]
+/// Partially updates Offer properties. Responds with the id of the new Offer revision.
+async fn revise(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathSubscription>,
+    body: Json<serde_json::Value>,
+    id: Identity,
+) -> impl Responder {
+    market
+        .revise_offer(&path.into_inner().subscription_id, &id, body.into_inner())
+        .await
+        .log_err()
+        .map(|id| HttpResponse::Ok().json(id))
+}
+
 async fn collect(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
     query: Query<QueryTimeoutMaxEvents>,
-    _id: Identity,
+    id: Identity,
 ) -> impl Responder {
-    let subscription_id = path.into_inner().subscription_id;
+    // Provider keeps listening on the id returned by subscribe, after the Offer was revised.
+    let subscription_id = market
+        .matcher
+        .store
+        .latest_offer_revision(&path.into_inner().subscription_id, id.identity)
+        .await;
     let timeout = query.timeout;
     let max_events = query.max_events;
     market
@@ -80,7 +146,6 @@ async fn collect(
         .map(|events| HttpResponse::Ok().json(events))
 }
 
-#[actix_web::post("/offers/{subscription_id}/proposals/{proposal_id}")]
 async fn counter_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -100,7 +165,6 @@ async fn counter_proposal(
         .map(|proposal_id| HttpResponse::Ok().json(proposal_id))
 }
 
-#[actix_web::get("/offers/{subscription_id}/proposals/{proposal_id}")]
 async fn get_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -120,7 +184,6 @@ async fn get_proposal(
         .map(|proposal| HttpResponse::Ok().json(proposal))
 }
 
-#[actix_web::post("/offers/{subscription_id}/proposals/{proposal_id}/reject")]
 async fn reject_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -140,7 +203,6 @@ async fn reject_proposal(
         .map(|_| HttpResponse::NoContent().finish())
 }
 
-#[actix_web::post("/agreements/{agreement_id}/approve")]
 async fn approve_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -157,11 +219,10 @@ async fn approve_agreement(
         .log_err()
         .map(|result| match result {
             ApprovalResult::Approved => HttpResponse::NoContent().finish(),
-            _ => HttpResponse::Gone().json(ErrorMessage::new(result)),
+            _ => ApiError::new(ErrorCategory::Gone, "AGREEMENT_NOT_APPROVED", result).response(),
         })
 }
 
-#[actix_web::post("/agreements/{agreement_id}/reject")]
 async fn reject_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
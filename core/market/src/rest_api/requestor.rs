@@ -1,11 +1,14 @@
 use actix_web::web::{Data, Json, Path, Query};
-use actix_web::{HttpResponse, Responder, Scope};
+use actix_web::{HttpResponse, Responder};
 use std::str::FromStr;
 use std::sync::Arc;
 
-use ya_client::model::market::{AgreementProposal, NewDemand, NewProposal, Reason};
-use ya_client::model::ErrorMessage;
+use ya_client::model::market::{
+    AgreementProposal, Demand, NewDemand, NewProposal, Proposal, Reason, RequestorEvent,
+};
+use ya_service_api_web::error::{ApiError, ErrorCategory};
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_std_utils::LogErr;
 
@@ -19,22 +22,73 @@ use super::{
 use crate::negotiation::ApprovalStatus;
 use crate::rest_api::QueryAppSessionId;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .service(subscribe)
-        .service(get_demands)
-        .service(unsubscribe)
-        .service(collect)
-        .service(counter_proposal)
-        .service(get_proposal)
-        .service(reject_proposal)
-        .service(create_agreement)
-        .service(confirm_agreement)
-        .service(wait_for_approval)
-        .service(cancel_agreement)
+        .route(
+            Operation::post("/demands", "subscribeDemand")
+                .body(Schema::model::<NewDemand>())
+                .response(Schema::of::<String>()),
+            subscribe,
+        )
+        .route(
+            Operation::get("/demands", "getDemands").response(Schema::array_of_model::<Demand>()),
+            get_demands,
+        )
+        .route(
+            Operation::delete("/demands/{subscription_id}", "unsubscribeDemand"),
+            unsubscribe,
+        )
+        .route(
+            Operation::get("/demands/{subscription_id}/events", "collectOffers")
+                .response(Schema::array_of_model::<RequestorEvent>()),
+            collect,
+        )
+        .route(
+            Operation::post(
+                "/demands/{subscription_id}/proposals/{proposal_id}",
+                "counterProposalDemand",
+            )
+            .body(Schema::model::<NewProposal>())
+            .response(Schema::of::<String>()),
+            counter_proposal,
+        )
+        .route(
+            Operation::get(
+                "/demands/{subscription_id}/proposals/{proposal_id}",
+                "getProposalOffer",
+            )
+            .response(Schema::model::<Proposal>()),
+            get_proposal,
+        )
+        .route(
+            Operation::post(
+                "/demands/{subscription_id}/proposals/{proposal_id}/reject",
+                "rejectProposalOffer",
+            )
+            .body(Schema::model::<Reason>()),
+            reject_proposal,
+        )
+        .route(
+            Operation::post("/agreements", "createAgreement")
+                .body(Schema::model::<AgreementProposal>())
+                .response(Schema::of::<String>()),
+            create_agreement,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/confirm", "confirmAgreement"),
+            confirm_agreement,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/wait", "waitForApproval"),
+            wait_for_approval,
+        )
+        .route(
+            Operation::post("/agreements/{agreement_id}/cancel", "cancelAgreement")
+                .body(Schema::model::<Reason>()),
+            cancel_agreement,
+        )
 }
 
-#[actix_web::post("/demands")]
 async fn subscribe(
     market: Data<Arc<MarketService>>,
     body: Json<NewDemand>,
@@ -47,7 +101,6 @@ async fn subscribe(
         .map(|id| HttpResponse::Created().json(id))
 }
 
-#[actix_web::get("/demands")]
 async fn get_demands(market: Data<Arc<MarketService>>, id: Identity) -> impl Responder {
     log::info!("get_demands");
     market
@@ -56,7 +109,6 @@ async fn get_demands(market: Data<Arc<MarketService>>, id: Identity) -> impl Res
         .map(|demands| HttpResponse::Ok().json(demands))
 }
 
-#[actix_web::delete("/demands/{subscription_id}")]
 async fn unsubscribe(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
@@ -70,7 +122,6 @@ async fn unsubscribe(
         .map(|_| HttpResponse::NoContent())
 }
 
-#[actix_web::get("/demands/{subscription_id}/events")]
 async fn collect(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
@@ -88,7 +139,6 @@ async fn collect(
         .map(|events| HttpResponse::Ok().json(events))
 }
 
-#[actix_web::post("/demands/{subscription_id}/proposals/{proposal_id}")]
 async fn counter_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -117,7 +167,6 @@ async fn counter_proposal(
     }
 }
 
-#[actix_web::get("/demands/{subscription_id}/proposals/{proposal_id}")]
 async fn get_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -137,7 +186,6 @@ async fn get_proposal(
         .map(|proposal| HttpResponse::Ok().json(proposal))
 }
 
-#[actix_web::post("/demands/{subscription_id}/proposals/{proposal_id}/reject")]
 async fn reject_proposal(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscriptionProposal>,
@@ -157,7 +205,6 @@ async fn reject_proposal(
         .map(|_| HttpResponse::NoContent().finish())
 }
 
-#[actix_web::post("/agreements")]
 async fn create_agreement(
     market: Data<Arc<MarketService>>,
     body: Json<AgreementProposal>,
@@ -173,7 +220,6 @@ async fn create_agreement(
         .map(|agreement_id| HttpResponse::Ok().json(agreement_id.into_client()))
 }
 
-#[actix_web::post("/agreements/{agreement_id}/confirm")]
 async fn confirm_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -189,7 +235,6 @@ async fn confirm_agreement(
         .map(|_| HttpResponse::NoContent().finish())
 }
 
-#[actix_web::post("/agreements/{agreement_id}/wait")]
 async fn wait_for_approval(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
@@ -205,11 +250,10 @@ async fn wait_for_approval(
         .log_err()
         .map(|status| match status {
             ApprovalStatus::Approved => HttpResponse::NoContent().finish(),
-            _ => HttpResponse::Gone().json(ErrorMessage::new(status)),
+            _ => ApiError::new(ErrorCategory::Gone, "AGREEMENT_NOT_APPROVED", status).response(),
         })
 }
 
-#[actix_web::post("/agreements/{agreement_id}/cancel")]
 async fn cancel_agreement(
     market: Data<Arc<MarketService>>,
     path: Path<PathAgreement>,
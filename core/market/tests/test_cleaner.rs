@@ -86,7 +86,8 @@ async fn test_demand() {
     let demand_dao = db.as_dao::<DemandDao>();
     demand_dao.insert(&valid_demand).await.unwrap();
     demand_dao.insert(&expired_demand).await.unwrap();
-    clean(db.clone(), &db_config()).await;
+    let stats = clean(db.clone(), &db_config()).await;
+    assert_eq!(stats.demands, 1);
     assert!(<PoolType as TestingDao<Demand>>::exists(&db.ram_db.pool, valid_demand.id).await);
     assert!(Not::not(
         <PoolType as TestingDao<Demand>>::exists(&db.ram_db.pool, expired_demand.id).await
@@ -6,11 +6,14 @@ use std::sync::Arc;
 use tokio::time::Duration;
 use ya_framework_mocks::net::MockNet;
 
+use ya_client::model::NodeId;
 use ya_market::assert_err_eq;
 use ya_market::testing::discovery::{message::*, Discovery};
 use ya_market::testing::mock_node::{assert_offers_broadcasted, assert_unsunbscribes_broadcasted};
-use ya_market::testing::mock_offer::{client, sample_offer, sample_offer_with_expiration};
-use ya_market::testing::{MarketServiceExt, MarketsNetwork};
+use ya_market::testing::mock_offer::{
+    client, generate_offer, sample_offer, sample_offer_with_expiration,
+};
+use ya_market::testing::{MarketServiceExt, MarketsNetwork, OfferDao};
 use ya_market::testing::{QueryOfferError, SubscriptionId};
 
 /// Test adds offer. It should be broadcasted to other nodes in the network.
@@ -64,6 +67,104 @@ async fn test_broadcast_offer() {
     assert_unsunbscribes_broadcasted(&[&mkt2, &mkt3], &[offer_id]).await;
 }
 
+/// Revised Offer should replace its previous revision on other nodes.
+/// Provider can keep using the original subscription id.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_broadcast_offer_revision() {
+    let _ = env_logger::builder().try_init();
+    let network = MarketsNetwork::new(None, MockNet::new())
+        .await
+        .add_market_instance("Node-1")
+        .await
+        .add_market_instance("Node-2")
+        .await;
+    let mkt2 = network.get_market("Node-2");
+    let id2 = network.get_default_id("Node-2");
+    mkt2.subscribe_demand(&client::sample_demand(), &id2)
+        .await
+        .unwrap();
+
+    let mkt1 = network.get_market("Node-1");
+    let id1 = network.get_default_id("Node-1");
+    let offer_id = mkt1
+        .subscribe_offer(&client::sample_offer(), &id1)
+        .await
+        .unwrap();
+    assert_offers_broadcasted(&[&mkt2], &[offer_id.clone()]).await;
+
+    let update = serde_json::json!({ "golem.inf.queue": 3 });
+    let revision_id = mkt1.revise_offer(&offer_id, &id1, update).await.unwrap();
+    assert!(revision_id.is_revision_of(&offer_id));
+
+    let revision = mkt1.get_offer(&revision_id).await.unwrap();
+    assert_eq!(
+        revision.into_client_offer().unwrap().properties["golem.inf.queue"],
+        3
+    );
+    assert_err_eq!(
+        QueryOfferError::Unsubscribed(offer_id.clone()),
+        mkt1.get_offer(&offer_id).await
+    );
+
+    assert_offers_broadcasted(&[&mkt2], &[revision_id.clone()]).await;
+    assert_unsunbscribes_broadcasted(&[&mkt2], &[offer_id.clone()]).await;
+
+    // Unsubscribing using the original id removes the latest revision.
+    mkt1.unsubscribe_offer(&offer_id, &id1).await.unwrap();
+    assert_err_eq!(
+        QueryOfferError::Unsubscribed(revision_id.clone()),
+        mkt1.get_offer(&revision_id).await
+    );
+}
+
+/// Latest revision lookup must ignore Offers of other nodes reusing the same random id
+/// and must not trust sender-chosen creation timestamps.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_latest_offer_revision_owned_by_node() {
+    let db = MarketsNetwork::new(None, MockNet::new())
+        .await
+        .init_database("test_latest_offer_revision");
+    let offer_dao = db.as_dao::<OfferDao>();
+    let validation_ts = Utc::now().naive_utc();
+    let expiration_ts = (Utc::now() + chrono::Duration::days(1)).naive_utc();
+
+    let original = generate_offer(
+        "c76161077d0343ab85ac986eb5f6ea38-edb0016d9f8bafb54540da34f05a8d510de8114488f23916276bdead05509a53",
+        expiration_ts,
+    );
+    let mut revision = generate_offer(
+        "c76161077d0343ab85ac986eb5f6ea38-edb0016d9f8bafb54540da34f05a8d510de8114488f23916276bdead05509a54",
+        expiration_ts,
+    );
+    revision.creation_ts = original.creation_ts - chrono::Duration::hours(1);
+    let mut foreign = generate_offer(
+        "c76161077d0343ab85ac986eb5f6ea38-edb0016d9f8bafb54540da34f05a8d510de8114488f23916276bdead05509a55",
+        expiration_ts,
+    );
+    foreign.node_id = NodeId::from_str("0xbeef000000000000000000000000000000000000").unwrap();
+    foreign.creation_ts = original.creation_ts + chrono::Duration::hours(1);
+
+    for offer in [original.clone(), revision.clone(), foreign.clone()] {
+        offer_dao.put(offer, validation_ts).await.unwrap();
+    }
+
+    let latest = offer_dao
+        .get_latest_revision(&original.id, original.node_id, validation_ts)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest.id, revision.id);
+
+    let latest = offer_dao
+        .get_latest_revision(&original.id, foreign.node_id, validation_ts)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest.id, foreign.id);
+}
+
 /// This test checks, if Discovery interface calls expected sequence of callbacks.
 /// In result Offer should be available on Node, that received broadcast.
 /// Note: We don't need this test to check, if broadcasting works. test_broadcast_offer
@@ -0,0 +1,343 @@
+//! Local aggregation of exported metrics before they're pushed.
+//!
+//! Labels listed for rollup are removed from every sample, and samples which become
+//! indistinguishable are merged. Counters, gauges and summary `_sum`/`_count` series
+//! are summed. Quantiles of merged summaries are estimated from the mixture of the
+//! source distributions: each source's CDF is interpolated between its exported
+//! quantiles and weighted by the source's `_count`.
+use std::collections::HashMap;
+
+const QUANTILE_LABEL: &str = "quantile";
+const COUNT_SUFFIX: &str = "_count";
+
+enum Line<'a> {
+    Other(&'a str),
+    Sample(String),
+}
+
+struct Sample<'a> {
+    name: &'a str,
+    labels: Vec<(&'a str, &'a str)>,
+    value: f64,
+}
+
+impl<'a> Sample<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        if line.starts_with('#') {
+            return None;
+        }
+        let (series, value) = line.trim_end().rsplit_once(' ')?;
+        let value = value.parse().ok()?;
+        let (name, labels) = match series.split_once('{') {
+            Some((name, labels)) => (name, parse_labels(labels.strip_suffix('}')?)?),
+            None => (series, Vec::new()),
+        };
+        Some(Sample {
+            name,
+            labels,
+            value,
+        })
+    }
+
+    fn quantile(&self) -> Option<f64> {
+        self.labels
+            .iter()
+            .find(|(k, _)| *k == QUANTILE_LABEL)
+            .and_then(|(_, v)| v.parse().ok())
+    }
+
+    /// Summary the sample belongs to, i.e. its series without the quantile label
+    fn summary(&self) -> String {
+        let name = self.name.strip_suffix(COUNT_SUFFIX).unwrap_or(self.name);
+        let labels = self
+            .labels
+            .iter()
+            .filter(|(k, _)| *k != QUANTILE_LABEL)
+            .copied()
+            .collect();
+        Sample {
+            name,
+            labels,
+            value: self.value,
+        }
+        .series()
+    }
+
+    fn series(&self) -> String {
+        if self.labels.is_empty() {
+            return self.name.to_string();
+        }
+        let labels = self
+            .labels
+            .iter()
+            .map(|(k, v)| format!("{k}=\"{v}\""))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}{{{}}}", self.name, labels)
+    }
+}
+
+/// Splits `k1="v1",k2="v2"`, keeping escape sequences within values intact
+fn parse_labels(s: &str) -> Option<Vec<(&str, &str)>> {
+    let mut labels = Vec::new();
+    let mut rest = s.trim_start_matches(',');
+    while !rest.is_empty() {
+        let (key, tail) = rest.split_once("=\"")?;
+        let mut escaped = false;
+        let end = tail.char_indices().find_map(|(i, c)| match c {
+            '\\' if !escaped => {
+                escaped = true;
+                None
+            }
+            '"' if !escaped => Some(i),
+            _ => {
+                escaped = false;
+                None
+            }
+        })?;
+        labels.push((key.trim(), &tail[..end]));
+        rest = tail[end + 1..].trim_start_matches(',');
+    }
+    Some(labels)
+}
+
+/// Quantiles exported for a single source summary, ordered by quantile
+#[derive(Default)]
+struct Sketch {
+    points: Vec<(f64, f64)>,
+}
+
+impl Sketch {
+    fn insert(&mut self, quantile: f64, value: f64) {
+        let idx = self.points.partition_point(|(q, _)| *q < quantile);
+        self.points.insert(idx, (quantile, value));
+    }
+
+    fn first(&self) -> f64 {
+        self.points.first().map(|(_, v)| *v).unwrap_or_default()
+    }
+
+    fn last(&self) -> f64 {
+        self.points.last().map(|(_, v)| *v).unwrap_or_default()
+    }
+
+    /// Fraction of observations `<= x`, interpolated between exported quantiles
+    fn cdf(&self, x: f64) -> f64 {
+        if x < self.first() {
+            return 0.;
+        }
+        if x >= self.last() {
+            return 1.;
+        }
+        self.interpolate(x)
+    }
+
+    /// Fraction of observations `< x`
+    fn cdf_left(&self, x: f64) -> f64 {
+        if x <= self.first() {
+            return 0.;
+        }
+        if x > self.last() {
+            return 1.;
+        }
+        self.interpolate(x)
+    }
+
+    fn interpolate(&self, x: f64) -> f64 {
+        self.points
+            .windows(2)
+            .find_map(|w| {
+                let ((q0, v0), (q1, v1)) = (w[0], w[1]);
+                match x {
+                    x if x < v0 || x > v1 => None,
+                    _ if v1 == v0 => Some(q1),
+                    x => Some(q0 + (q1 - q0) * (x - v0) / (v1 - v0)),
+                }
+            })
+            .unwrap_or_else(|| self.points.last().map(|(q, _)| *q).unwrap_or(1.))
+    }
+}
+
+/// Estimates `quantile` of the mixture of `sources` weighted by their observation counts
+fn mixture_quantile(quantile: f64, sources: &[(&Sketch, f64)]) -> f64 {
+    let total = sources.iter().map(|(_, weight)| weight).sum::<f64>();
+    let cdf = |x: f64| sources.iter().map(|(s, w)| w * s.cdf(x)).sum::<f64>() / total;
+    let cdf_left = |x: f64| sources.iter().map(|(s, w)| w * s.cdf_left(x)).sum::<f64>() / total;
+
+    let mut breakpoints = sources
+        .iter()
+        .flat_map(|(s, _)| s.points.iter().map(|(_, v)| *v))
+        .collect::<Vec<_>>();
+    breakpoints.sort_by(f64::total_cmp);
+    breakpoints.dedup();
+
+    let mut prev = match breakpoints.first() {
+        Some(first) => *first,
+        None => return f64::NAN,
+    };
+    if cdf(prev) >= quantile {
+        return prev;
+    }
+    for x in breakpoints.into_iter().skip(1) {
+        let (lo, hi) = (cdf(prev), cdf_left(x));
+        if quantile <= hi && hi > lo {
+            return prev + (x - prev) * (quantile - lo) / (hi - lo);
+        }
+        if quantile <= cdf(x) {
+            return x;
+        }
+        prev = x;
+    }
+    prev
+}
+
+/// Removes `labels` from samples of the Prometheus text exposition and merges duplicates
+pub fn rollup(metrics: &str, labels: &[String]) -> String {
+    if labels.is_empty() {
+        return metrics.to_string();
+    }
+
+    let mut lines = Vec::new();
+    let mut values: HashMap<String, f64> = HashMap::new();
+    // Source summaries merged into each quantile series
+    let mut quantiles: HashMap<String, (f64, Vec<String>)> = HashMap::new();
+    let mut sketches: HashMap<String, Sketch> = HashMap::new();
+    let mut counts: HashMap<String, f64> = HashMap::new();
+
+    for line in metrics.lines() {
+        let mut sample = match Sample::parse(line) {
+            Some(sample) => sample,
+            None => {
+                lines.push(Line::Other(line));
+                continue;
+            }
+        };
+        let quantile = sample.quantile();
+        let source = sample.summary();
+        if let Some(quantile) = quantile {
+            sketches
+                .entry(source.clone())
+                .or_default()
+                .insert(quantile, sample.value);
+        } else if sample.name.ends_with(COUNT_SUFFIX) {
+            counts.insert(source.clone(), sample.value);
+        }
+
+        sample
+            .labels
+            .retain(|(k, _)| !labels.iter().any(|label| label == k));
+
+        let series = sample.series();
+        if let Some(quantile) = quantile {
+            quantiles
+                .entry(series.clone())
+                .or_insert_with(|| (quantile, Vec::new()))
+                .1
+                .push(source);
+        }
+        match values.get_mut(&series) {
+            Some(_) if quantile.is_some() => (),
+            Some(value) => *value += sample.value,
+            None => {
+                values.insert(series.clone(), sample.value);
+                lines.push(Line::Sample(series));
+            }
+        }
+    }
+
+    for (series, (quantile, sources)) in quantiles {
+        if sources.len() < 2 {
+            continue;
+        }
+        // Summaries without observations don't contribute to the distribution
+        let sources = sources
+            .iter()
+            .map(|source| (&sketches[source], counts.get(source).copied().unwrap_or(1.)))
+            .filter(|(_, count)| *count > 0.)
+            .collect::<Vec<_>>();
+        if sources.is_empty() {
+            continue;
+        }
+        values.insert(series, mixture_quantile(quantile, &sources));
+    }
+
+    let mut result = lines
+        .into_iter()
+        .map(|line| match line {
+            Line::Other(line) => line.to_string(),
+            Line::Sample(series) => format!("{} {}", series, values[&series]),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if metrics.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: &str = r#"# metrics snapshot (ts=1) (prometheus exposition format)
+# TYPE payment_amount counter
+payment_amount{driver="erc20",platform="a"} 10
+payment_amount{driver="erc20",platform="b"} 5
+
+# TYPE activity_usage gauge
+activity_usage{activity_id="1"} 2
+activity_usage{activity_id="2"} 3.5
+
+# TYPE request_time summary
+request_time{quantile="0",activity_id="1"} 2
+request_time{quantile="0.5",activity_id="1"} 4
+request_time{quantile="0",activity_id="2"} 6
+request_time{quantile="0.5",activity_id="2"} 9
+request_time_sum{activity_id="1"} 3
+request_time_sum{activity_id="2"} 24
+request_time_count{activity_id="1"} 1
+request_time_count{activity_id="2"} 3
+"#;
+
+    #[test]
+    fn no_rollup() {
+        assert_eq!(rollup(METRICS, &[]), METRICS);
+    }
+
+    #[test]
+    fn rollup_labels() {
+        let labels = vec!["activity_id".to_string(), "platform".to_string()];
+        assert_eq!(
+            rollup(METRICS, &labels),
+            r#"# metrics snapshot (ts=1) (prometheus exposition format)
+# TYPE payment_amount counter
+payment_amount{driver="erc20"} 15
+
+# TYPE activity_usage gauge
+activity_usage 5.5
+
+# TYPE request_time summary
+request_time{quantile="0"} 2
+request_time{quantile="0.5"} 8
+request_time_sum 27
+request_time_count 4
+"#
+        );
+    }
+
+    #[test]
+    fn quantiles_of_empty_summaries_are_ignored() {
+        let metrics = "t{quantile=\"0.5\",a=\"1\"} 4\nt{quantile=\"0.5\",a=\"2\"} 0\n\
+                       t_count{a=\"1\"} 5\nt_count{a=\"2\"} 0";
+        assert_eq!(
+            rollup(metrics, &["a".to_string()]),
+            "t{quantile=\"0.5\"} 4\nt_count 5"
+        );
+    }
+
+    #[test]
+    fn escaped_label_values() {
+        let metrics = "m{a=\"x\\\"y,z\",b=\"1\"} 1\nm{a=\"x\\\"y,z\",b=\"2\"} 2";
+        assert_eq!(rollup(metrics, &["b".to_string()]), "m{a=\"x\\\"y,z\"} 3");
+    }
+}
@@ -1,9 +1,10 @@
+mod aggregate;
 mod exporter;
 mod metrics;
 pub(crate) mod pusher;
 mod service;
 
-pub use service::{MetricsLabels, MetricsPusherOpts, MetricsService};
+pub use service::{MetricsLabels, MetricsPusherOpts, MetricsService, PushLabel};
 
 pub mod utils {
     const CRYPTOCURRENCY_PRECISION: u64 = 1000000000;
@@ -4,6 +4,7 @@ use lazy_static::lazy_static;
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use tokio::time::{self, Duration, Instant};
 
+use crate::aggregate::rollup;
 use crate::service::export_metrics_for_push;
 use ya_core_model::identity::{self, IdentityInfo};
 use ya_service_api::MetricsCtx;
@@ -52,7 +53,7 @@ pub async fn push_forever(host_url: &str, ctx: &MetricsCtx) {
     };
 
     let start = Instant::now() + Duration::from_secs(5);
-    let mut push_interval = time::interval_at(start, Duration::from_secs(60));
+    let mut push_interval = time::interval_at(start, ctx.push_interval);
     let client = Client::builder().timeout(Duration::from_secs(30)).finish();
 
     log::info!(
@@ -60,15 +61,16 @@ pub async fn push_forever(host_url: &str, ctx: &MetricsCtx) {
     );
     loop {
         push_interval.tick().await;
-        push(&client, push_url.clone()).await;
+        push(&client, push_url.clone(), &ctx.rollup_labels).await;
     }
 }
 
-pub async fn push(client: &Client, push_url: String) {
+pub async fn push(client: &Client, push_url: String, rollup_labels: &[String]) {
     let metrics = export_metrics_for_push().await;
     if metrics.is_empty() {
         return;
     }
+    let metrics = rollup(&metrics, rollup_labels);
     let res = client
         .put(push_url.as_str())
         .send_body(metrics.clone())
@@ -126,19 +128,23 @@ async fn try_get_default_id() -> anyhow::Result<IdentityInfo> {
 
 fn get_push_url(host_url: &str, id: &IdentityInfo, ctx: &MetricsCtx) -> anyhow::Result<String> {
     let base = url::Url::parse(host_url)?;
-    let mut url = base
-        .join(&format!(
-            "/metrics/job/{}/",
-            utf8_percent_encode(&ctx.job, &UNSAFE_CHAR_SET)
-        ))?
-        .join(&format!("instance/{}/", &id.node_id))?
-        .join(&format!(
-            "hostname/{}",
+    let mut path = format!(
+        "/metrics/job/{}",
+        utf8_percent_encode(&ctx.job, &UNSAFE_CHAR_SET)
+    );
+    if ctx.instance_label {
+        path.push_str(&format!("/instance/{}", &id.node_id));
+    }
+    if ctx.hostname_label {
+        path.push_str(&format!(
+            "/hostname/{}",
             id.alias
                 .as_ref()
                 .map(|alias| utf8_percent_encode(alias, &UNSAFE_CHAR_SET).to_string())
                 .unwrap_or_else(|| id.node_id.to_string())
-        ))?;
+        ));
+    }
+    let mut url = base.join(&path)?;
 
     for (label, value) in &ctx.labels {
         url.path_segments_mut()
@@ -237,6 +243,19 @@ mod test {
         assert!(url.starts_with("http://a/metrics/job/community.1/instance/0x0000000000000000000000000000000000000000/hostname/node1/"));
     }
 
+    #[test]
+    fn test_get_push_url_without_identity_labels() {
+        let ctx = MetricsCtx {
+            job: "community.1".into(),
+            labels: labels(&[("version", "0.16.0")]),
+            instance_label: false,
+            hostname_label: false,
+            ..MetricsCtx::default()
+        };
+        let url = get_push_url("http://a", &default_id_info(), &ctx).unwrap();
+        assert_eq!("http://a/metrics/job/community.1/version/0.16.0", url);
+    }
+
     #[test]
     fn test_get_push_url_label_with_pletters() {
         let ctx = MetricsCtx {
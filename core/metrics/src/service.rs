@@ -2,7 +2,9 @@ use actix_web::web::Path;
 use futures::lock::Mutex;
 use lazy_static::lazy_static;
 use std::collections::HashMap;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use structopt::StructOpt;
 use url::Url;
 
@@ -32,6 +34,26 @@ pub struct MetricsPusherOpts {
     /// Metrics job name, which allows to distinguish different groups of Nodes.
     #[structopt(long, env = "YAGNA_METRICS_JOB_NAME", default_value = "community.1")]
     pub metrics_job_name: String,
+    /// Interval between consecutive metrics pushes
+    #[structopt(
+        long,
+        env = "YAGNA_METRICS_PUSH_INTERVAL",
+        parse(try_from_str = parse_push_interval),
+        default_value = "1m",
+    )]
+    pub metrics_push_interval: Duration,
+    /// Comma-separated label dimensions attached to pushed metrics:
+    /// `node_id`, `hostname`, `version`, `driver`
+    #[structopt(
+        long,
+        env = "YAGNA_METRICS_PUSH_LABELS",
+        use_delimiter = true,
+        default_value = "node_id,hostname"
+    )]
+    pub metrics_push_labels: Vec<PushLabel>,
+    /// Comma-separated series labels aggregated away before push, e.g. `activity_id,driver`
+    #[structopt(long, env = "YAGNA_METRICS_ROLLUP_LABELS", use_delimiter = true)]
+    pub metrics_rollup_labels: Vec<String>,
     #[structopt(flatten)]
     pub labels: MetricsLabels,
 }
@@ -44,18 +66,60 @@ pub struct MetricsLabels {
     pub group: Option<String>,
 }
 
+/// Label dimension identifying the pushing node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushLabel {
+    NodeId,
+    Hostname,
+    Version,
+    /// Payment drivers the node was built with
+    Driver,
+}
+
+impl FromStr for PushLabel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "node_id" => Ok(PushLabel::NodeId),
+            "hostname" => Ok(PushLabel::Hostname),
+            "version" => Ok(PushLabel::Version),
+            "driver" => Ok(PushLabel::Driver),
+            other => anyhow::bail!("Unknown metrics label: {other}"),
+        }
+    }
+}
+
+fn parse_push_interval(s: &str) -> anyhow::Result<Duration> {
+    let interval = humantime::parse_duration(s)?;
+    if interval.is_zero() {
+        anyhow::bail!("Metrics push interval must be greater than zero");
+    }
+    Ok(interval)
+}
+
 impl From<&MetricsPusherOpts> for MetricsCtx {
     fn from(opts: &MetricsPusherOpts) -> Self {
         let mut labels = HashMap::new();
         if let Some(group) = &opts.labels.group {
             labels.insert("group".to_string(), group.to_string());
         }
+        if opts.metrics_push_labels.contains(&PushLabel::Version) {
+            labels.insert(
+                "version".to_string(),
+                ya_compile_time_utils::semver_str!().to_string(),
+            );
+        }
 
         MetricsCtx {
             push_enabled: !opts.disable_metrics_push,
             push_host_url: Some(opts.metrics_push_url.clone()),
+            push_interval: opts.metrics_push_interval,
             job: opts.metrics_job_name.clone(),
             labels,
+            instance_label: opts.metrics_push_labels.contains(&PushLabel::NodeId),
+            hostname_label: opts.metrics_push_labels.contains(&PushLabel::Hostname),
+            rollup_labels: opts.metrics_rollup_labels.clone(),
         }
     }
 }
@@ -183,3 +247,35 @@ pub async fn export_metrics_for_push() -> String {
 pub async fn export_metrics_local() -> String {
     export_metrics_sorted().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_push_interval_is_rejected() {
+        let opts = |interval: &str| {
+            MetricsPusherOpts::from_iter_safe(&["", "--metrics-push-interval", interval])
+        };
+        assert!(opts("0s").is_err());
+        assert_eq!(
+            opts("30s").unwrap().metrics_push_interval,
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn push_labels() {
+        let opts = MetricsPusherOpts::from_iter_safe(&[
+            "",
+            "--metrics-push-labels",
+            "node_id,version,driver",
+        ])
+        .unwrap();
+        assert_eq!(
+            opts.metrics_push_labels,
+            vec![PushLabel::NodeId, PushLabel::Version, PushLabel::Driver]
+        );
+        assert!(MetricsPusherOpts::from_iter_safe(&["", "--metrics-push-labels", "os"]).is_err());
+    }
+}
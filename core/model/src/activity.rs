@@ -2,6 +2,7 @@
 //!
 //! Top level objects constitutes public activity API.
 //! Local and Exeunit are in dedicated submodules.
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
@@ -143,6 +144,24 @@ impl RpcMessage for GetUsage {
     type Error = RpcMessageError;
 }
 
+/// Notify the Requestor about an Activity state transition.
+///
+/// Sent by the Provider whenever the ExeUnit reports a new state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChanged {
+    pub activity_id: String,
+    pub agreement_id: String,
+    pub state: ActivityState,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl RpcMessage for StateChanged {
+    const ID: &'static str = "ActivityStateChanged";
+    type Item = ();
+    type Error = RpcMessageError;
+}
+
 /// Update remote network configuration
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -206,6 +225,29 @@ pub mod sgx {
         type Item = Vec<u8>;
         type Error = RpcMessageError;
     }
+
+    /// Get the current attestation evidence of the enclave running the activity.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetAttestationEvidence {
+        pub activity_id: String,
+        pub timeout: Option<f32>,
+    }
+
+    impl RpcMessage for GetAttestationEvidence {
+        const ID: &'static str = "GetAttestationEvidence";
+        type Item = AttestationEvidence;
+        type Error = RpcMessageError;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AttestationEvidence {
+        pub credentials: super::local::Credentials,
+        /// IAS report status of the platform's TCB, e.g. `OK`
+        pub quote_status: String,
+        pub issued_at: DateTime<Utc>,
+    }
 }
 
 /// Execute a script within the activity. Returns `batch_id`.
@@ -245,6 +287,47 @@ impl RpcMessage for GetExecBatchResults {
     type Error = RpcMessageError;
 }
 
+/// Get the state of all script batches executed within the activity.
+///
+/// Single call replacement for polling `GetExecBatchResults` per batch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetExecBatches {
+    pub activity_id: String,
+    pub timeout: Option<f32>,
+}
+
+impl RpcMessage for GetExecBatches {
+    const ID: &'static str = "GetExecBatches";
+    type Item = Vec<ExecBatchState>;
+    type Error = RpcMessageError;
+}
+
+/// Progress of a single script batch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExecBatchState {
+    pub batch_id: String,
+    /// Number of commands in the batch.
+    pub commands_total: usize,
+    /// Number of already executed commands.
+    pub commands_done: usize,
+    pub is_batch_finished: bool,
+    /// Currently executed command, along with its progress.
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::client_model::<ExeScriptCommandState>")
+    )]
+    pub running_command: Option<ExeScriptCommandState>,
+    /// Results of executed commands, without captured output.
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::client_model_array::<ExeScriptCommandResult>")
+    )]
+    pub results: Vec<ExeScriptCommandResult>,
+}
+
 /// Stream script execution events.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StreamExecBatchResults {
@@ -272,12 +355,63 @@ impl RpcMessage for GetRunningCommand {
     type Error = RpcMessageError;
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Upload the full output log of a command to `to`, using the ExeUnit transfer
+/// protocols (e.g. a GFTP URL published by the caller). Responds with the log size in bytes.
+///
+/// Full logs are available only when the Provider enabled spilling output to disk.
+/// Requires control access to the activity; `to` is validated against the app manifest
+/// like destinations of `transfer` commands.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCommandLog {
+    pub activity_id: String,
+    pub batch_id: String,
+    pub idx: usize,
+    pub stream: OutputStream,
+    pub to: String,
+    pub timeout: Option<f32>,
+}
+
+impl RpcMessage for GetCommandLog {
+    const ID: &'static str = "GetCommandLog";
+    type Item = u64;
+    type Error = RpcMessageError;
+}
+
+/// Write `data` to stdin of a running `Run` command. With `eof` set, stdin is closed
+/// after writing.
+///
+/// Stdin is kept open only by runtimes running in service mode, when the Deploy command
+/// sets `GOLEM_STDIN=true` in its `env`. Requires control access to the activity.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteStdin {
+    pub activity_id: String,
+    pub batch_id: String,
+    pub idx: usize,
+    pub data: Vec<u8>,
+    pub eof: bool,
+    pub timeout: Option<f32>,
+}
+
+impl RpcMessage for WriteStdin {
+    const ID: &'static str = "WriteStdin";
+    type Item = ();
+    type Error = RpcMessageError;
+}
+
 /// Local activity bus API (used by ExeUnit).
 ///
 /// Should be accessible only from local service bus (not via net ie. from remote hosts).
 pub mod local {
     use super::*;
-    use chrono::{DateTime, Utc};
     use std::collections::BTreeMap;
     use ya_client_model::market::Role;
 
@@ -366,6 +500,54 @@ pub mod local {
         type Error = RpcMessageError;
     }
 
+    /// Report a call rejected by the ExeUnit's access control list.
+    /// Outbound violations are calls the activity attempted to make to `endpoint`.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ReportViolation {
+        pub activity_id: String,
+        pub caller: String,
+        pub endpoint: String,
+        pub reason: String,
+        #[serde(default)]
+        pub outbound: bool,
+        pub timestamp: DateTime<Utc>,
+    }
+
+    impl RpcMessage for ReportViolation {
+        const ID: &'static str = "ReportActivityViolation";
+        type Item = ();
+        type Error = RpcMessageError;
+    }
+
+    /// Local Exe Unit bus address for given `activity_id`.
+    pub fn exeunit_bus_id(activity_id: &str) -> String {
+        format!("/local/exeunit/{}", activity_id)
+    }
+
+    /// Get host names resolved by the ExeUnit on behalf of the activity.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetDnsResolutions {
+        pub activity_id: String,
+    }
+
+    impl RpcMessage for GetDnsResolutions {
+        const ID: &'static str = "GetDnsResolutions";
+        type Item = Vec<DnsResolution>;
+        type Error = RpcMessageError;
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DnsResolution {
+        pub host: String,
+        pub addresses: Vec<std::net::IpAddr>,
+        /// `false` if the host name was rejected by the provider's domain filter.
+        pub allowed: bool,
+        pub timestamp: DateTime<Utc>,
+    }
+
     /// Get agreement ID of the activity.
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
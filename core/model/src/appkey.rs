@@ -51,6 +51,9 @@ pub struct Create {
     pub role: String,
     pub identity: NodeId,
     pub allow_origins: Vec<String>,
+    /// App-key is rejected after this date.
+    #[serde(default)]
+    pub expires: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -96,6 +99,30 @@ pub struct Remove {
     pub identity: Option<String>,
 }
 
+/// Replaces the key of an app-key with a newly generated one.
+/// Previous key remains valid for `grace_period_secs`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rotate {
+    pub name: String,
+    pub identity: Option<String>,
+    pub grace_period_secs: u64,
+    /// Expiration date of the new key. New key doesn't expire if not set.
+    pub expires: Option<NaiveDateTime>,
+    /// Key of the caller, when an app-key rotates itself. Rotation is rejected
+    /// unless it's the current key, and the new key can't expire later than it.
+    #[serde(default)]
+    pub current_key: Option<String>,
+}
+
+/// Key replaced by rotation, valid until `expires_date`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviousKey {
+    pub key: String,
+    pub expires_date: NaiveDateTime,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppKey {
@@ -105,6 +132,28 @@ pub struct AppKey {
     pub identity: NodeId,
     pub created_date: NaiveDateTime,
     pub allow_origins: Vec<String>,
+    #[serde(default)]
+    pub expires_date: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_key: Option<PreviousKey>,
+}
+
+impl AppKey {
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        matches!(self.expires_date, Some(expires) if expires <= now)
+    }
+
+    /// Checks whether `key` authorizes as this app-key at `now`:
+    /// it's either the current, not expired key, or the previous key within its grace period.
+    pub fn accepts(&self, key: &str, now: NaiveDateTime) -> bool {
+        if self.key == key {
+            return !self.is_expired(now);
+        }
+        match &self.previous_key {
+            Some(previous) => previous.key == key && previous.expires_date > now,
+            None => false,
+        }
+    }
 }
 
 impl RpcMessage for Create {
@@ -131,6 +180,12 @@ impl RpcMessage for List {
     type Error = Error;
 }
 
+impl RpcMessage for Rotate {
+    const ID: &'static str = "Rotate";
+    type Item = AppKey;
+    type Error = Error;
+}
+
 impl RpcMessage for Remove {
     const ID: &'static str = "Remove";
     type Item = ();
@@ -168,3 +223,36 @@ pub mod event {
         type Error = Error;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_accepts() {
+        let now = Utc::now().naive_utc();
+        let mut app_key = AppKey {
+            name: "test".to_string(),
+            key: "new".to_string(),
+            role: DEFAULT_ROLE.to_string(),
+            identity: NodeId::default(),
+            created_date: now,
+            allow_origins: vec![],
+            expires_date: None,
+            previous_key: Some(PreviousKey {
+                key: "old".to_string(),
+                expires_date: now + Duration::minutes(5),
+            }),
+        };
+
+        assert!(app_key.accepts("new", now));
+        assert!(app_key.accepts("old", now));
+        assert!(!app_key.accepts("old", now + Duration::minutes(10)));
+        assert!(!app_key.accepts("other", now));
+
+        app_key.expires_date = Some(now + Duration::hours(1));
+        assert!(app_key.accepts("new", now));
+        assert!(!app_key.accepts("new", now + Duration::hours(1)));
+    }
+}
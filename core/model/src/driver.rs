@@ -562,6 +562,36 @@ impl RpcMessage for Transfer {
     type Error = GenericError;
 }
 
+// ************************** SWEEP **************************
+
+/// Transfers whole token balance (and optionally native token balance, except the amount
+/// reserved for gas) from `sender` account to `to` address.
+/// Funds needed by payments, which are scheduled but not confirmed yet, are left on the account.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sweep {
+    pub sender: String,
+    pub to: String,
+    pub network: Option<String>,
+    pub native: bool,
+    pub pending_amount: BigDecimal,
+    pub pending_transfers: u32,
+}
+
+impl RpcMessage for Sweep {
+    const ID: &'static str = "Sweep";
+    type Item = SweepResult;
+    type Error = GenericError;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepResult {
+    pub token_amount: BigDecimal,
+    pub native_amount: Option<BigDecimal>,
+    /// Identifiers of scheduled transfers.
+    pub transfer_ids: Vec<String>,
+}
+
 // ************************ SIGN PAYMENT ************************
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -650,6 +680,124 @@ impl RpcMessage for DriverReleaseDeposit {
     type Error = GenericError;
 }
 
+// ************************* ALLOWANCE *************************
+
+/// Amount of tokens `spender` is allowed to transfer from `owner` account.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GetAllowance {
+    pub owner: String,
+    pub spender: String,
+    pub network: Option<String>,
+}
+
+impl RpcMessage for GetAllowance {
+    const ID: &'static str = "GetAllowance";
+    type Item = BigDecimal;
+    type Error = GenericError;
+}
+
+/// Signs EIP-2612 permit allowing `spender` to transfer `amount` from `owner` account
+/// until `deadline`. Permit can be submitted by anyone, so the approval doesn't
+/// require owner to hold native token for gas.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignPermit {
+    pub owner: String,
+    pub spender: String,
+    pub amount: BigDecimal,
+    pub deadline: DateTime<Utc>,
+    pub network: Option<String>,
+}
+
+impl RpcMessage for SignPermit {
+    const ID: &'static str = "SignPermit";
+    type Item = Permit;
+    type Error = GenericError;
+}
+
+/// Arguments of token contract `permit` function.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit {
+    pub token: String,
+    pub owner: String,
+    pub spender: String,
+    /// Amount in token base units.
+    pub value: String,
+    pub nonce: String,
+    pub deadline: i64,
+    pub v: u8,
+    pub r: String,
+    pub s: String,
+}
+
+/// Sends token contract `approve` transaction allowing `spender` to transfer `amount`
+/// from `owner` account. Zero `amount` revokes the approval. Gas is paid by `owner`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Approve {
+    pub owner: String,
+    pub spender: String,
+    pub amount: BigDecimal,
+    pub network: Option<String>,
+}
+
+impl RpcMessage for Approve {
+    const ID: &'static str = "Approve";
+    type Item = String;
+    type Error = GenericError;
+}
+
+/// Sends token contract `permit` transaction, so the permit signed by its owner
+/// takes effect, eg. before a deposit is created. Gas is paid by `sender`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubmitPermit {
+    pub sender: String,
+    pub permit: Permit,
+    pub network: Option<String>,
+}
+
+impl RpcMessage for SubmitPermit {
+    const ID: &'static str = "SubmitPermit";
+    type Item = String;
+    type Error = GenericError;
+}
+
+// ************************* BATCH CYCLE *************************
+
+/// Estimates the cost of sending the next batch of payments scheduled from `sender`.
+/// Payments already being sent are not part of it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EstimateBatch {
+    pub sender: String,
+    pub network: Option<String>,
+}
+
+impl RpcMessage for EstimateBatch {
+    const ID: &'static str = "EstimateBatch";
+    type Item = BatchEstimate;
+    type Error = GenericError;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchEstimate {
+    pub network: String,
+    /// Longest time a scheduled payment waits to be batched with others.
+    pub batch_interval: Option<Duration>,
+    /// Maximum number of transfers sent in a single transaction.
+    pub max_batch_size: Option<u32>,
+    /// Scheduled payments waiting for the batch.
+    pub payments: u32,
+    /// Payments to the same recipient are sent as a single transfer.
+    pub transfers: u32,
+    pub amount: BigDecimal,
+    pub transactions: u32,
+    pub gas_limit: u64,
+    /// Current gas price in Gwei.
+    pub gas_price: BigDecimal,
+    pub fee: BigDecimal,
+    pub fee_currency: String,
+}
+
 // ************************* SHUT DOWN *************************
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -7,6 +7,15 @@ pub fn file_bus_id(hash: &str) -> String {
     format!("{}/gftp/{}", crate::net::PUBLIC_PREFIX, hash)
 }
 
+/// Address of a published file, optionally restricted with an access token.
+/// Restricted shares are not reachable without the token.
+pub fn share_bus_id(hash: &str, token: Option<&str>) -> String {
+    match token {
+        Some(token) => file_bus_id(&format!("{}-{}", hash, token)),
+        None => file_bus_id(hash),
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Error)]
 pub enum Error {
     #[error("Can't read from file. {0}")]
@@ -57,6 +66,54 @@ impl RpcMessage for GetChunk {
     type Error = Error;
 }
 
+// =========================================== //
+// Directory messages
+// =========================================== //
+
+/// Lists files of directory published through gftp.
+/// Returns GftpDirectory structure.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetDirectory {}
+
+impl RpcMessage for GetDirectory {
+    const ID: &'static str = "GetDirectory";
+    type Item = GftpDirectory;
+    type Error = Error;
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpDirectory {
+    pub files: Vec<GftpFileEntry>,
+}
+
+/// File of published directory. `path` is relative to directory root
+/// and uses `/` as separator.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GftpFileEntry {
+    pub path: String,
+    pub file_size: u64,
+    pub hash: String,
+}
+
+/// Gets chunk of file from published directory. Returns GftpChunk.
+/// Semantics of `offset` and `size` are the same as in GetChunk.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFileChunk {
+    pub path: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl RpcMessage for GetFileChunk {
+    const ID: &'static str = "GetFileChunk";
+    type Item = GftpChunk;
+    type Error = Error;
+}
+
 // =========================================== //
 // Upload messages
 // =========================================== //
@@ -21,6 +21,10 @@ pub mod net;
 
 #[cfg(feature = "payment")]
 pub mod payment;
+#[cfg(feature = "persistence")]
+pub mod persistence;
+#[cfg(feature = "schema")]
+pub mod schema;
 #[cfg(feature = "payment")]
 pub mod signable;
 
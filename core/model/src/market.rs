@@ -3,7 +3,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use ya_client_model::market::{agreement::State, Role};
-pub use ya_client_model::market::{Agreement, AgreementListEntry};
+pub use ya_client_model::market::{Agreement, AgreementListEntry, Proposal};
 use ya_service_bus::RpcMessage;
 
 /// Public Market bus address.
@@ -69,6 +69,53 @@ impl RpcMessage for GetLastBcastTs {
     type Error = RpcMessageError;
 }
 
+/// Removes expired Offers, Demands, Proposals and Negotiations, and Agreements
+/// and events older than their retention window, from the market database.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectGarbage;
+
+impl RpcMessage for CollectGarbage {
+    const ID: &'static str = "CollectGarbage";
+    type Item = GcStats;
+    type Error = RpcMessageError;
+}
+
+/// Numbers of rows removed by the market database garbage collection.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcStats {
+    pub offers: u64,
+    pub offer_unsubscribes: u64,
+    pub demands: u64,
+    pub proposals: u64,
+    pub negotiations: u64,
+    pub negotiation_events: u64,
+    pub agreements: u64,
+    pub agreement_events: u64,
+}
+
+/// Property added by Market to Proposals scored by external component.
+pub const PROPOSAL_SCORE_PROPERTY: &str = "yagna.market.proposal-score";
+
+/// Asks external component to score Proposals received for a Demand.
+///
+/// Market sends this message to the address configured with `MARKET_PROPOSAL_SCORER`
+/// when Requestor collects events. Scorer returns one score for each Proposal in the same
+/// order; higher score means better Proposal. `None` leaves Proposal unscored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreProposals {
+    pub demand_id: String,
+    pub proposals: Vec<Proposal>,
+}
+
+impl RpcMessage for ScoreProposals {
+    const ID: &'static str = "ScoreProposals";
+    type Item = Vec<Option<f64>>;
+    type Error = RpcMessageError;
+}
+
 /// Error message for market service bus API.
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +17,7 @@ pub const DIAGNOSTIC: &str = "/public/diagnostic/net";
 /// TODO: fill docs
 ///
 pub mod local {
+    use chrono::{DateTime, Utc};
     use std::net::SocketAddr;
     use std::time::Duration;
 
@@ -29,6 +30,11 @@ pub mod local {
 
     pub const BUS_ID: &str = "/local/net";
 
+    /// TTL for propagating broadcasts from older Nodes, which don't send TTL. Their
+    /// broadcasts reach only our neighbours, otherwise every hop through an older
+    /// Node would reset TTL and broadcasts could circulate in loops.
+    pub const LEGACY_BROADCAST_TTL: u8 = 1;
+
     pub trait BroadcastMessage: Serialize + DeserializeOwned {
         const TOPIC: &'static str;
     }
@@ -37,6 +43,8 @@ pub mod local {
     pub struct SendBroadcastStub {
         pub id: Option<String>,
         pub topic: String,
+        #[serde(default)]
+        pub ttl: Option<u8>,
     }
 
     #[derive(Serialize, Deserialize)]
@@ -44,13 +52,23 @@ pub mod local {
         id: Option<String>,
         topic: String,
         body: M,
+        /// Number of hops the broadcast may still travel, decreased by every Node
+        /// propagating it further. Missing in broadcasts from older Nodes.
+        #[serde(default)]
+        ttl: Option<u8>,
     }
 
     impl<M: BroadcastMessage> SendBroadcastMessage<M> {
         pub fn new(body: M) -> Self {
             let id = None;
             let topic = M::TOPIC.to_owned();
-            Self { id, topic, body }
+            let ttl = None;
+            Self {
+                id,
+                topic,
+                body,
+                ttl,
+            }
         }
 
         pub fn body(&self) -> &M {
@@ -63,9 +81,29 @@ pub mod local {
             self.topic.as_ref()
         }
 
+        pub fn id(&self) -> Option<&str> {
+            self.id.as_deref()
+        }
+
         pub fn set_id(&mut self, id: String) {
             self.id = Some(id)
         }
+
+        pub fn ttl(&self) -> Option<u8> {
+            self.ttl
+        }
+
+        pub fn set_ttl(&mut self, ttl: u8) {
+            self.ttl = Some(ttl)
+        }
+
+        /// TTL of a broadcast propagating this one further. Broadcasts with TTL 0
+        /// are not sent. Net caps it with the configured TTL when sending.
+        pub fn next_ttl(&self) -> u8 {
+            self.ttl
+                .map(|ttl| ttl.saturating_sub(1))
+                .unwrap_or(LEGACY_BROADCAST_TTL)
+        }
     }
 
     impl<M: Send + Sync + Serialize + DeserializeOwned + 'static> RpcMessage
@@ -255,6 +293,40 @@ pub mod local {
         pub is_p2p: bool,
     }
 
+    /// Measures connection quality to a single Node.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Diagnose {
+        pub node_id: NodeId,
+        /// Number of ping packets used to measure round trip time and packet loss.
+        pub samples: u32,
+    }
+
+    impl RpcMessage for Diagnose {
+        const ID: &'static str = "Diagnose";
+        type Item = DiagnoseResponse;
+        type Error = StatusError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DiagnoseResponse {
+        pub node_id: NodeId,
+        /// `p2p`, `relay` or `none` if there is no session with the Node.
+        pub session_type: String,
+        pub remote_address: Option<SocketAddr>,
+        pub session_ping: Option<Duration>,
+        pub rtt_min: Option<Duration>,
+        pub rtt_avg: Option<Duration>,
+        pub rtt_max: Option<Duration>,
+        pub packets_sent: u32,
+        pub packets_lost: u32,
+        /// Largest payload in bytes delivered over the unreliable channel.
+        pub mtu: Option<usize>,
+        pub last_error: Option<String>,
+        pub last_error_time: Option<DateTime<Utc>>,
+    }
+
     #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
     #[serde(rename_all = "camelCase")]
     pub struct Connect {
@@ -319,6 +391,133 @@ pub mod local {
     }
 }
 
+/// Management of VPNs created by the local Requestor.
+pub mod vpn {
+    use serde::{Deserialize, Serialize};
+    use std::net::SocketAddr;
+
+    use crate::net::GenericNetError;
+    use ya_client_model::NodeId;
+    use ya_service_bus::RpcMessage;
+
+    pub const BUS_ID: &str = "/local/vpn";
+
+    /// TCP port on the Requestor's host forwarded to an address within the VPN.
+    /// Forward is removed together with the VPN or the Node owning destination address.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PortForward {
+        pub id: String,
+        pub network_id: String,
+        /// Local address accepting connections.
+        pub listen: SocketAddr,
+        /// Destination IP address within the VPN.
+        pub ip: String,
+        pub port: u16,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreatePortForward {
+        pub owner: NodeId,
+        pub network_id: String,
+        pub ip: String,
+        pub port: u16,
+        /// Local port to listen on. Random port is chosen when not set.
+        pub listen_port: Option<u16>,
+    }
+
+    impl RpcMessage for CreatePortForward {
+        const ID: &'static str = "CreatePortForward";
+        type Item = PortForward;
+        type Error = GenericNetError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ListPortForwards {
+        pub owner: NodeId,
+        pub network_id: String,
+    }
+
+    impl RpcMessage for ListPortForwards {
+        const ID: &'static str = "ListPortForwards";
+        type Item = Vec<PortForward>;
+        type Error = GenericNetError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RemovePortForward {
+        pub owner: NodeId,
+        pub network_id: String,
+        pub id: String,
+    }
+
+    impl RpcMessage for RemovePortForward {
+        const ID: &'static str = "RemovePortForward";
+        type Item = ();
+        type Error = GenericNetError;
+    }
+
+    /// Sent by the Activity service when the Requestor destroys an Activity.
+    /// The Provider's Node is removed from all networks of the Requestor,
+    /// together with port forwards to its addresses.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ActivityDestroyed {
+        pub owner: NodeId,
+        pub activity_id: String,
+        pub provider_id: NodeId,
+    }
+
+    impl RpcMessage for ActivityDestroyed {
+        const ID: &'static str = "ActivityDestroyed";
+        type Item = ();
+        type Error = GenericNetError;
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Traffic {
+        pub bytes: u64,
+        pub packets: u64,
+    }
+
+    /// Traffic exchanged with a single Node within the VPN.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeStats {
+        pub node_id: String,
+        pub tx: Traffic,
+        pub rx: Traffic,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NetworkStats {
+        pub network_id: String,
+        pub tx: Traffic,
+        pub rx: Traffic,
+        /// Active TCP connections of Requestor's host to Nodes within the VPN.
+        pub connections: usize,
+        pub nodes: Vec<NodeStats>,
+    }
+
+    /// Statistics of all VPNs created on this Node, or of a single VPN.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetNetworkStats {
+        pub network_id: Option<String>,
+    }
+
+    impl RpcMessage for GetNetworkStats {
+        const ID: &'static str = "GetNetworkStats";
+        type Item = Vec<NetworkStats>;
+        type Error = GenericNetError;
+    }
+}
+
 /// For documentation check local::GsbPing
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
@@ -330,6 +529,20 @@ impl RpcMessage for GsbRemotePing {
     type Error = GenericNetError;
 }
 
+/// Returns size of the received payload. Used to probe the largest
+/// message which can be delivered over the unreliable channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GsbRemoteEcho {
+    pub payload: Vec<u8>,
+}
+
+impl RpcMessage for GsbRemoteEcho {
+    const ID: &'static str = "GsbRemoteEcho";
+    type Item = usize;
+    type Error = GenericNetError;
+}
+
 #[derive(thiserror::Error, Debug, Serialize, Deserialize)]
 #[error("{0}")]
 pub struct GenericNetError(pub String);
@@ -516,6 +729,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_broadcast_next_ttl() {
+        #[derive(Serialize, Deserialize)]
+        struct Bcast;
+        impl local::BroadcastMessage for Bcast {
+            const TOPIC: &'static str = "test";
+        }
+
+        let mut msg = local::SendBroadcastMessage::new(Bcast);
+        assert_eq!(msg.next_ttl(), local::LEGACY_BROADCAST_TTL);
+
+        msg.set_ttl(3);
+        assert_eq!(msg.next_ttl(), 2);
+        msg.set_ttl(0);
+        assert_eq!(msg.next_ttl(), 0);
+    }
+
     #[test]
     fn test_transfer_service() {
         let node_id: NodeId = "0xbabe000000000000000000000000000000000000"
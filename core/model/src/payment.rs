@@ -19,9 +19,12 @@ pub enum RpcMessageError {
 
 pub mod local {
     use super::{public::Ack, *};
-    use crate::driver::{AccountMode, GasDetails, PaymentConfirmation, ValidateAllocationResult};
+    use crate::driver::{
+        AccountMode, BatchEstimate, GasDetails, PaymentConfirmation, SweepResult,
+        ValidateAllocationResult,
+    };
     use bigdecimal::{BigDecimal, Zero};
-    use chrono::{DateTime, Utc};
+    use chrono::{DateTime, NaiveDate, Utc};
     use std::fmt::Display;
     use std::time::Duration;
     use structopt::*;
@@ -151,6 +154,50 @@ pub mod local {
     pub struct RegisterDriver {
         pub driver_name: String,
         pub details: DriverDetails,
+        /// Drivers registering without capabilities get the defaults.
+        #[serde(default)]
+        pub capabilities: DriverCapabilities,
+    }
+
+    /// Features of the payment driver, letting the payment service and SDKs adapt
+    /// their behavior to the driver.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DriverCapabilities {
+        /// Scheduled payments are gathered and sent in batches.
+        pub batch_transfers: bool,
+        /// Allocations can be backed by deposits.
+        pub deposits: bool,
+        /// Payments can be made for accepted part of the amount due.
+        pub partial_payments: bool,
+        pub confirmation_latency: ConfirmationLatency,
+        pub fee_asset: FeeAsset,
+    }
+
+    /// Expected time between sending a payment and its confirmation.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ConfirmationLatency {
+        /// Confirmed when sent.
+        Instant,
+        /// Confirmed within seconds.
+        Seconds,
+        /// Confirmed within minutes.
+        #[default]
+        Minutes,
+    }
+
+    /// Asset paying for transaction fees.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum FeeAsset {
+        /// No fees.
+        None,
+        /// Native token of the network, e.g. ETH or POL.
+        #[default]
+        Native,
+        /// Fees are paid with the payment token.
+        Token,
     }
 
     #[derive(Clone, Debug, Serialize, Deserialize, thiserror::Error)]
@@ -302,8 +349,10 @@ pub mod local {
     }
 
     #[derive(Clone, Debug, Serialize, Deserialize, Default)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     #[serde(rename_all = "camelCase")]
     pub struct StatValue {
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
         pub total_amount: BigDecimal,
         pub agreements_count: u64,
     }
@@ -421,6 +470,58 @@ pub mod local {
         pub provider: InvoiceStatusNotes,
     }
 
+    /// Provider's earnings from Invoices issued since given time,
+    /// grouped by day of issue and payment platform.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[non_exhaustive]
+    pub struct GetRevenueStats {
+        pub node_id: NodeId,
+        pub since: DateTime<Utc>,
+    }
+
+    impl GetRevenueStats {
+        pub fn new(node_id: NodeId, since: DateTime<Utc>) -> Self {
+            Self { node_id, since }
+        }
+    }
+
+    impl RpcMessage for GetRevenueStats {
+        const ID: &'static str = "GetRevenueStats";
+        type Item = Vec<RevenueStats>;
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(rename_all = "camelCase")]
+    pub struct RevenueStats {
+        pub day: NaiveDate,
+        pub platform: String,
+        /// Invoices received by Requestors, which weren't rejected.
+        pub invoiced: StatValue,
+        /// Invoices accepted by Requestors, including settled ones.
+        pub accepted: StatValue,
+        pub settled: StatValue,
+        /// Invoices not settled before payment due date.
+        pub overdue: StatValue,
+        /// Number of Requestors with overdue Invoices.
+        pub delinquent_requestors: u64,
+    }
+
+    impl RevenueStats {
+        pub fn new(day: NaiveDate, platform: String) -> Self {
+            Self {
+                day,
+                platform,
+                invoiced: Default::default(),
+                accepted: Default::default(),
+                settled: Default::default(),
+                overdue: Default::default(),
+                delinquent_requestors: 0,
+            }
+        }
+    }
+
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct ValidateAllocation {
         pub platform: String,
@@ -479,6 +580,18 @@ pub mod local {
         InternalTimeout,
     }
 
+    /// Capabilities of registered drivers, or of a single driver.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct GetDriverCapabilities {
+        pub driver: Option<String>,
+    }
+
+    impl RpcMessage for GetDriverCapabilities {
+        const ID: &'static str = "GetDriverCapabilities";
+        type Item = HashMap<String, DriverCapabilities>;
+        type Error = GetDriversError;
+    }
+
     impl RpcMessage for GetDrivers {
         const ID: &'static str = "GetDrivers";
         type Item = HashMap<String, DriverDetails>;
@@ -536,6 +649,232 @@ pub mod local {
         type Error = GenericError;
     }
 
+    /// Spending limits of an app-key, so a leaked key can only cause bounded damage.
+    /// Limits apply to each payment platform separately.
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(rename_all = "camelCase")]
+    pub struct AppKeyLimits {
+        /// Limit of amounts allocated within the last 24 hours.
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        pub daily: Option<BigDecimal>,
+        /// Limit of amounts allocated in total.
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        pub total: Option<BigDecimal>,
+        /// Limit of amount accepted for a single Agreement.
+        #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+        pub per_agreement: Option<BigDecimal>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(rename_all = "camelCase")]
+    pub struct AppKeyLimitStatus {
+        pub app_key: String,
+        pub limits: AppKeyLimits,
+        /// Allocated amounts on payment platforms used by the app-key.
+        pub allocated: Vec<AppKeyAllocated>,
+    }
+
+    /// Amounts of released allocations count as spent amounts,
+    /// amounts of active ones count as total amounts.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(rename_all = "camelCase")]
+    pub struct AppKeyAllocated {
+        pub platform: String,
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        pub daily: BigDecimal,
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        pub total: BigDecimal,
+    }
+
+    /// Sets or, when all limits are empty, removes limits.
+    /// Only the node operator can set limits, app-keys calling through REST API can't.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetAppKeyLimits {
+        pub owner: NodeId,
+        pub app_key: String,
+        pub limits: AppKeyLimits,
+    }
+
+    impl RpcMessage for SetAppKeyLimits {
+        const ID: &'static str = "SetAppKeyLimits";
+        type Item = ();
+        type Error = GenericError;
+    }
+
+    /// Lists limits of all app-keys of `owner`, or of a single app-key.
+    /// App-keys calling through REST API can only list limits of their identity.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct GetAppKeyLimits {
+        pub owner: NodeId,
+        pub app_key: Option<String>,
+    }
+
+    impl RpcMessage for GetAppKeyLimits {
+        const ID: &'static str = "GetAppKeyLimits";
+        type Item = Vec<AppKeyLimitStatus>;
+        type Error = GenericError;
+    }
+
+    /// Moves documents and payments of agreements settled more than `older_than` ago
+    /// to archive tables.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ArchiveDocuments {
+        pub older_than: Duration,
+    }
+
+    impl RpcMessage for ArchiveDocuments {
+        const ID: &'static str = "ArchiveDocuments";
+        type Item = ArchiveStats;
+        type Error = GenericError;
+    }
+
+    /// Totals and estimated cost of payments from `address`, which are scheduled
+    /// but not yet confirmed by the driver.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PreviewPaymentCycle {
+        pub address: String,
+        pub driver: String,
+        pub network: Option<String>,
+    }
+
+    impl RpcMessage for PreviewPaymentCycle {
+        const ID: &'static str = "PreviewPaymentCycle";
+        type Item = PaymentCyclePreview;
+        type Error = GenericError;
+    }
+
+    /// Transfers whole balance of `address` to `to` address, except funds needed
+    /// by payments, which are scheduled but not yet confirmed by the driver.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Sweep {
+        pub address: String,
+        pub driver: String,
+        pub network: Option<String>,
+        pub to: String,
+        /// Transfer also native token, except the amount reserved for gas
+        pub native: bool,
+    }
+
+    impl RpcMessage for Sweep {
+        const ID: &'static str = "Sweep";
+        type Item = SweepResult;
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PaymentCyclePreview {
+        pub platform: String,
+        pub estimate: BatchEstimate,
+    }
+
+    /// Numbers of archived rows.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ArchiveStats {
+        pub invoices: u64,
+        pub debit_notes: u64,
+        pub payments: u64,
+        #[serde(default)]
+        pub allocation_events: u64,
+    }
+
+    /// Change of the amount available in an Allocation.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(tag = "eventType")]
+    pub enum AllocationEventType {
+        /// Amount reserved when accepted Debit Note or Invoice was scheduled for payment.
+        #[serde(rename_all = "camelCase")]
+        AllocationReservedEvent {
+            agreement_id: Option<String>,
+            activity_id: Option<String>,
+            invoice_id: Option<String>,
+            debit_note_id: Option<String>,
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            amount: BigDecimal,
+        },
+        /// Amount actually paid out of the Allocation.
+        #[serde(rename_all = "camelCase")]
+        AllocationSpentEvent {
+            agreement_id: Option<String>,
+            activity_id: Option<String>,
+            payment_id: String,
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            amount: BigDecimal,
+        },
+        /// Allocation released, remaining amount returned to the budget.
+        #[serde(rename_all = "camelCase")]
+        AllocationReleasedEvent {
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            remaining_amount: BigDecimal,
+        },
+    }
+
+    impl AllocationEventType {
+        pub fn discriminant(&self) -> &'static str {
+            match self {
+                AllocationEventType::AllocationReservedEvent { .. } => "RESERVED",
+                AllocationEventType::AllocationSpentEvent { .. } => "SPENT",
+                AllocationEventType::AllocationReleasedEvent { .. } => "RELEASED",
+            }
+        }
+    }
+
+    /// Also sent to endpoints subscribed with `SubscribeAllocationEvents`.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(rename_all = "camelCase")]
+    pub struct AllocationEvent {
+        pub allocation_id: String,
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        pub owner_id: NodeId,
+        pub event_date: DateTime<Utc>,
+        #[serde(flatten)]
+        pub event_type: AllocationEventType,
+    }
+
+    impl RpcMessage for AllocationEvent {
+        const ID: &'static str = "AllocationEvent";
+        type Item = ();
+        type Error = GenericError;
+    }
+
+    /// Subscribes `endpoint` to Allocation events of `owner_id`.
+    /// Returns subscription id.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SubscribeAllocationEvents {
+        pub endpoint: String,
+        pub owner_id: NodeId,
+    }
+
+    impl RpcMessage for SubscribeAllocationEvents {
+        const ID: &'static str = "SubscribeAllocationEvents";
+        type Item = u64;
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct UnsubscribeAllocationEvents {
+        pub subscription_id: u64,
+    }
+
+    impl RpcMessage for UnsubscribeAllocationEvents {
+        const ID: &'static str = "UnsubscribeAllocationEvents";
+        type Item = ();
+        type Error = GenericError;
+    }
+
     /// Experimental. In future releases this might change or be removed.
     #[derive(
         EnumString,
@@ -573,6 +912,9 @@ pub mod local {
         Mumbai,
         #[strum(props(token = "tGLM"))]
         Amoy,
+        /// Simulated network of the `sim` driver, without any chain behind it.
+        #[strum(props(token = "tGLM"))]
+        Devnet,
     }
 
     impl NetworkName {
@@ -616,6 +958,8 @@ pub mod local {
     #[non_exhaustive]
     pub enum DriverName {
         Erc20,
+        /// Simulated driver for load testing, see `ya-devnet-sim-driver`.
+        Sim,
     }
 
     #[derive(StructOpt, Debug, Clone)]
@@ -647,6 +991,14 @@ pub mod local {
         pub fn token(&self) -> String {
             self.network.get_str("token").unwrap().to_string()
         }
+
+        /// Whether `yagna payment fund` can mint tokens on the network with the driver.
+        pub fn is_fundable(&self) -> bool {
+            match self.driver {
+                DriverName::Sim => self.network == NetworkName::Devnet,
+                _ => self.network.is_fundable(),
+            }
+        }
     }
 
     #[cfg(test)]
@@ -661,6 +1013,35 @@ pub mod local {
             assert_eq!("holesky", a.network());
             assert_eq!("tGLM", a.token());
         }
+
+        #[test]
+        fn test_fundable_networks() {
+            let fundable = |args: &[&str]| AccountCli::from_iter(args).is_fundable();
+            assert!(fundable(&[""]));
+            assert!(fundable(&["", "--driver", "sim", "--network", "devnet"]));
+            assert!(!fundable(&["", "--network", "devnet"]));
+            assert!(!fundable(&["", "--driver", "sim", "--network", "holesky"]));
+            assert!(!fundable(&["", "--network", "polygon"]));
+        }
+
+        #[test]
+        fn test_allocation_event_format() {
+            let event = AllocationEvent {
+                allocation_id: "allocation".to_string(),
+                owner_id: NodeId::default(),
+                event_date: Utc::now(),
+                event_type: AllocationEventType::AllocationReleasedEvent {
+                    remaining_amount: BigDecimal::from(5),
+                },
+            };
+            let json = serde_json::to_value(&event).unwrap();
+            assert_eq!(json["eventType"], "AllocationReleasedEvent");
+            assert_eq!(json["remainingAmount"], "5");
+            assert_eq!(
+                serde_json::from_value::<AllocationEvent>(json).unwrap(),
+                event
+            );
+        }
     }
 }
 
@@ -799,12 +1180,41 @@ pub mod public {
         type Error = AcceptRejectError;
     }
 
+    /// Structured reason of Invoice rejection, which opens a dispute.
+    /// Dispute is resolved when the Provider issues a corrected Invoice.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[serde(tag = "type", rename_all = "camelCase")]
+    pub enum DisputeReason {
+        /// Usage doesn't match Requestor's records.
+        UsageMismatch,
+        /// Amount doesn't follow prices from the Agreement.
+        PriceMismatch,
+        /// Invoice duplicates another one.
+        #[serde(rename_all = "camelCase")]
+        Duplicate { invoice_id: String },
+    }
+
+    impl DisputeReason {
+        pub fn rejection_reason(&self) -> RejectionReason {
+            match self {
+                DisputeReason::UsageMismatch | DisputeReason::PriceMismatch => {
+                    RejectionReason::IncorrectAmount
+                }
+                DisputeReason::Duplicate { .. } => RejectionReason::UnsolicitedService,
+            }
+        }
+    }
+
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
     pub struct RejectInvoiceV2 {
         pub invoice_id: String,
         pub rejection: Rejection,
         pub issuer_id: NodeId,
+        /// Not set by older Requestors, which send free-text rejections only.
+        #[serde(default)]
+        pub dispute: Option<DisputeReason>,
     }
 
     impl RejectInvoiceV2 {
@@ -813,8 +1223,14 @@ pub mod public {
                 invoice_id,
                 rejection,
                 issuer_id,
+                dispute: None,
             }
         }
+
+        pub fn with_dispute(mut self, dispute: Option<DisputeReason>) -> Self {
+            self.dispute = dispute;
+            self
+        }
     }
 
     impl RpcMessage for RejectInvoiceV2 {
@@ -836,6 +1252,22 @@ pub mod public {
         type Error = CancelError;
     }
 
+    /// Sent by the Provider to close a dispute over rejected Invoice.
+    /// Corrected Invoice is sent separately with `SendInvoice`.
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ResolveInvoiceDispute {
+        pub invoice_id: String,
+        pub corrected_invoice_id: String,
+        pub recipient_id: NodeId,
+    }
+
+    impl RpcMessage for ResolveInvoiceDispute {
+        const ID: &'static str = "ResolveInvoiceDispute";
+        type Item = Ack;
+        type Error = AcceptRejectError;
+    }
+
     // *************************** PAYMENT ****************************
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
     pub struct SendPayment {
@@ -964,4 +1396,35 @@ pub mod public {
         type Item = Ack;
         type Error = SendError;
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn test_reject_without_dispute() {
+            let rejection = Rejection {
+                rejection_reason: RejectionReason::BadService,
+                total_amount_accepted: Default::default(),
+                message: None,
+            };
+            let mut json = serde_json::to_value(RejectInvoiceV2::new(
+                "invoice".to_string(),
+                rejection,
+                NodeId::default(),
+            ))
+            .unwrap();
+            // Message sent by older Requestor
+            json.as_object_mut().unwrap().remove("dispute");
+            let msg: RejectInvoiceV2 = serde_json::from_value(json).unwrap();
+            assert_eq!(msg.dispute, None);
+
+            let msg = msg.with_dispute(Some(DisputeReason::Duplicate {
+                invoice_id: "original".to_string(),
+            }));
+            let json = serde_json::to_value(&msg).unwrap();
+            assert_eq!(json["dispute"]["type"], "duplicate");
+            assert_eq!(json["dispute"]["invoiceId"], "original");
+        }
+    }
 }
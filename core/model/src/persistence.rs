@@ -0,0 +1,51 @@
+//! Database maintenance service bus API.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::ErrorMessage;
+use ya_service_bus::RpcMessage;
+
+pub const BUS_ID: &str = "/local/persistence";
+
+/// Copies all databases to `dir` using SQLite online backup.
+/// Database writes are paused while each snapshot is taken.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub dir: PathBuf,
+}
+
+impl RpcMessage for Backup {
+    const ID: &'static str = "backup";
+    type Item = Vec<BackupInfo>;
+    type Error = ErrorMessage;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub database: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Runs `PRAGMA integrity_check` on all databases.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {}
+
+impl RpcMessage for Check {
+    const ID: &'static str = "check";
+    type Item = Vec<CheckInfo>;
+    type Error = ErrorMessage;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckInfo {
+    pub database: String,
+    /// Problems reported by SQLite. Empty if the database is intact.
+    pub errors: Vec<String>,
+}
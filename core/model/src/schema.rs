@@ -0,0 +1,70 @@
+//! JSON schemas of messages exposed by the REST APIs.
+//!
+//! `ya-client-model` types don't implement `JsonSchema`. They follow the OpenAPI specs
+//! published with ya-client, so their schemas refer to the specs of the revision in use.
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{ArrayValidation, InstanceType, Schema, SchemaObject};
+use std::any::type_name;
+
+/// Revision of ya-client the workspace depends on
+pub const YA_CLIENT_REV: &str = "653e7ed3ff8836837b660a76e604055e167b1f2e";
+
+/// Schema of a `ya-client-model` type
+pub fn client_model<T: ?Sized>(_: &mut SchemaGenerator) -> Schema {
+    Schema::new_ref(client_model_ref(type_name::<T>()))
+}
+
+/// Schema of a list of `ya-client-model` objects
+pub fn client_model_array<T>(gen: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(client_model::<T>(gen).into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// `ya_client_model::market::offer::Offer` -> `.../specs/market-api.yaml#/components/schemas/Offer`
+fn client_model_ref(rust_type: &str) -> String {
+    let base = rust_type.split('<').next().unwrap_or(rust_type);
+    let name = base.rsplit("::").next().unwrap_or(base);
+    let spec = match base.split("::").nth(1) {
+        Some("activity") => "activity-api",
+        Some("market") => "market-api",
+        Some("payment") => "payment-api",
+        _ => "common",
+    };
+    format!(
+        "https://raw.githubusercontent.com/golemfactory/ya-client/{}/specs/{}.yaml#/components/schemas/{}",
+        YA_CLIENT_REV, spec, name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_model_ref() {
+        assert!(client_model_ref("ya_client_model::market::offer::Offer")
+            .ends_with("/specs/market-api.yaml#/components/schemas/Offer"));
+        assert!(
+            client_model_ref("ya_client_model::payment::invoice::Invoice")
+                .ends_with("/specs/payment-api.yaml#/components/schemas/Invoice")
+        );
+        assert!(client_model_ref("ya_client_model::NodeId")
+            .ends_with("/specs/common.yaml#/components/schemas/NodeId"));
+    }
+
+    #[test]
+    fn test_ya_client_rev() {
+        let manifest = include_str!("../../../Cargo.toml");
+        assert!(
+            manifest.contains(&format!("ya-client.git\", rev = \"{}\"", YA_CLIENT_REV)),
+            "YA_CLIENT_REV differs from the ya-client revision in the workspace manifest"
+        );
+    }
+}
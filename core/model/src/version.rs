@@ -1,6 +1,6 @@
 //! Version handling service bus API.
 
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use ya_client_model::ErrorMessage;
@@ -41,6 +41,17 @@ impl RpcMessage for Get {
     type Error = ErrorMessage;
 }
 
+/// Get pending update status, including update channel and maintenance windows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetUpdateStatus {}
+
+impl RpcMessage for GetUpdateStatus {
+    const ID: &'static str = "update-status";
+    type Item = UpdateStatus;
+    type Error = ErrorMessage;
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
 #[serde(rename_all = "camelCase")]
 #[error("Version {version} '{name}' released {}", release_ts.format("%Y-%m-%d"))]
@@ -60,6 +71,19 @@ pub struct VersionInfo {
     pub pending: Option<Release>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateStatus {
+    pub channel: String,
+    pub current: Release,
+    pub pending: Option<Release>,
+    /// Maintenance windows, empty if updates are always allowed
+    pub update_windows: Vec<String>,
+    /// Whether the node is within a maintenance window
+    pub update_allowed: bool,
+    pub next_window: Option<DateTime<Utc>>,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
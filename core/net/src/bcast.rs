@@ -1,10 +1,17 @@
 // Broadcast support service
 
-use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use tokio::sync::RwLock;
-use ya_core_model::net::local as local_net;
+use ya_service_bus::serialization;
+
+/// Upper bound of remembered broadcasts, oldest are forgotten first.
+const MAX_SEEN_BROADCASTS: usize = 100_000;
+const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(120);
 
 #[derive(Clone, Default)]
 pub struct BCastService {
@@ -15,6 +22,8 @@ pub struct BCastService {
 struct BCastServiceInner {
     last_id: u64,
     topics: BTreeMap<String, Vec<(u64, Arc<str>)>>,
+    ttl: Option<u8>,
+    seen: SeenBroadcasts,
 }
 
 impl BCastService {
@@ -44,4 +53,156 @@ impl BCastService {
             })
             .unwrap_or_default()
     }
+
+    pub async fn configure(&self, ttl: u8, dedup_window: Duration) {
+        let mut me = self.inner.write().await;
+        me.ttl = Some(ttl);
+        me.seen.window = dedup_window;
+    }
+
+    /// TTL of a broadcast sent from this Node. Configured TTL is used when not requested
+    /// and caps the requested one, so propagated broadcasts travel no further than ours.
+    pub async fn ttl(&self, requested: Option<u8>) -> Option<u8> {
+        outgoing_ttl(requested, self.inner.read().await.ttl)
+    }
+
+    /// Checks whether the broadcast was already received within the deduplication window
+    pub async fn is_duplicate(&self, topic: &str, data: &[u8]) -> bool {
+        let key = broadcast_key(topic, data);
+        let mut me = self.inner.write().await;
+        !me.seen.insert(key, Instant::now())
+    }
+}
+
+pub fn new_broadcast_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+fn outgoing_ttl(requested: Option<u8>, configured: Option<u8>) -> Option<u8> {
+    match (requested, configured) {
+        (Some(requested), Some(configured)) => Some(requested.min(configured)),
+        (requested, configured) => requested.or(configured),
+    }
+}
+
+/// Broadcasts are identified by topic and body. Sender, id and TTL are left out,
+/// so the same content propagated by many neighbours, or sent again by its origin,
+/// is handled once per window.
+fn broadcast_key(topic: &str, data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    topic.hash(&mut hasher);
+    match serialization::from_slice::<serde_json::Value>(data) {
+        Ok(message) => message
+            .get("body")
+            .map(ToString::to_string)
+            .hash(&mut hasher),
+        Err(_) => data.hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+struct SeenBroadcasts {
+    window: Duration,
+    keys: HashSet<u64>,
+    order: VecDeque<(Instant, u64)>,
+}
+
+impl Default for SeenBroadcasts {
+    fn default() -> Self {
+        SeenBroadcasts {
+            window: DEFAULT_DEDUP_WINDOW,
+            keys: Default::default(),
+            order: Default::default(),
+        }
+    }
+}
+
+impl SeenBroadcasts {
+    /// Returns `false` if the key was seen within the window
+    fn insert(&mut self, key: u64, now: Instant) -> bool {
+        while let Some(&(ts, old)) = self.order.front() {
+            if now.duration_since(ts) < self.window && self.order.len() < MAX_SEEN_BROADCASTS {
+                break;
+            }
+            self.keys.remove(&old);
+            self.order.pop_front();
+        }
+
+        if !self.keys.insert(key) {
+            return false;
+        }
+        self.order.push_back((now, key));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ya_core_model::net::local::{BroadcastMessage, SendBroadcastMessage};
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Bcast(Vec<String>);
+
+    impl BroadcastMessage for Bcast {
+        const TOPIC: &'static str = "topic";
+    }
+
+    fn message(body: &[&str], id: &str, ttl: Option<u8>) -> Vec<u8> {
+        let mut msg =
+            SendBroadcastMessage::new(Bcast(body.iter().map(ToString::to_string).collect()));
+        msg.set_id(id.to_string());
+        if let Some(ttl) = ttl {
+            msg.set_ttl(ttl);
+        }
+        serialization::to_vec(&msg).unwrap()
+    }
+
+    #[test]
+    fn dedup_within_window() {
+        let mut seen = SeenBroadcasts {
+            window: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let key = broadcast_key("topic", &message(&["a"], "1", Some(3)));
+
+        assert!(seen.insert(key, now));
+        assert!(!seen.insert(key, now + Duration::from_secs(5)));
+        assert!(seen.insert(
+            broadcast_key("topic", &message(&["b"], "1", Some(3))),
+            now + Duration::from_secs(5)
+        ));
+        assert!(seen.insert(key, now + Duration::from_secs(10)));
+        assert_eq!(seen.keys.len(), seen.order.len());
+    }
+
+    #[test]
+    fn broadcasts_by_content() {
+        let key = broadcast_key("topic", &message(&["a", "b"], "1", Some(3)));
+        // re-sent, or propagated by another Node
+        assert_eq!(
+            key,
+            broadcast_key("topic", &message(&["a", "b"], "2", Some(2)))
+        );
+        assert_eq!(
+            key,
+            broadcast_key("topic", &message(&["a", "b"], "3", None))
+        );
+        assert_ne!(key, broadcast_key("topic", &message(&["a"], "1", Some(3))));
+        assert_ne!(
+            key,
+            broadcast_key("other", &message(&["a", "b"], "1", Some(3)))
+        );
+    }
+
+    #[test]
+    fn ttl_capped_by_config() {
+        assert_eq!(outgoing_ttl(None, Some(4)), Some(4));
+        assert_eq!(outgoing_ttl(Some(2), Some(4)), Some(2));
+        assert_eq!(outgoing_ttl(Some(255), Some(4)), Some(4));
+        assert_eq!(outgoing_ttl(Some(2), None), Some(2));
+        assert_eq!(outgoing_ttl(None, None), None);
+    }
 }
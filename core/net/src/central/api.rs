@@ -3,6 +3,7 @@ use ya_core_model::net::local::ToEndpoint;
 use ya_core_model::net::local::{BindBroadcastError, BroadcastMessage, SendBroadcastMessage};
 use ya_service_bus::{typed as bus, RpcEndpoint, RpcMessage};
 
+use crate::bcast::new_broadcast_id;
 use crate::central::SUBSCRIPTIONS;
 
 pub async fn broadcast<M, S>(
@@ -19,9 +20,34 @@ where
     M: BroadcastMessage + Send + Sync + Unpin + 'static,
     S: ToString + 'static,
 {
+    broadcast_with_ttl(caller, message, None).await
+}
+
+/// Central Net doesn't propagate broadcasts, so TTL is only passed to receivers.
+pub async fn broadcast_with_ttl<M, S>(
+    caller: S,
+    message: M,
+    ttl: Option<u8>,
+) -> Result<
+    Result<
+        <SendBroadcastMessage<M> as RpcMessage>::Item,
+        <SendBroadcastMessage<M> as RpcMessage>::Error,
+    >,
+    ya_service_bus::Error,
+>
+where
+    M: BroadcastMessage + Send + Sync + Unpin + 'static,
+    S: ToString + 'static,
+{
+    let mut message = SendBroadcastMessage::new(message);
+    message.set_id(new_broadcast_id());
+    if let Some(ttl) = ttl {
+        message.set_ttl(ttl);
+    }
+
     // TODO: We shouldn't use send_as. Put identity inside broadcasted message instead.
     bus::service(net::local::BUS_ID)
-        .send_as(caller, SendBroadcastMessage::new(message))
+        .send_as(caller, message)
         .await
 }
 
@@ -22,6 +22,10 @@ pub(crate) fn bind_service() {
         futures::future::err(err.clone())
     });
     let err = error.clone();
+    let _ = bus::bind(model::BUS_ID, move |_: model::Diagnose| {
+        futures::future::err(err.clone())
+    });
+    let err = error.clone();
     let _ = bus::bind(model::BUS_ID, move |_: model::FindNode| {
         futures::future::err(err.clone())
     });
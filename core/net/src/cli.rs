@@ -10,6 +10,7 @@ use structopt::*;
 use ya_client_model::NodeId;
 
 use ya_core_model::net::local as model;
+use ya_core_model::net::vpn;
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
@@ -33,6 +34,13 @@ pub enum NetCommand {
         /// If None, all connected Nodes will be pinged.
         node_id: Option<String>,
     },
+    /// Diagnose connection to other Node
+    Diag {
+        node_id: String,
+        /// Number of ping packets used to measure round trip time and packet loss
+        #[structopt(long, default_value = "10")]
+        samples: u32,
+    },
     /// Establish connection to other Node
     Connect {
         node_id: String,
@@ -44,6 +52,18 @@ pub enum NetCommand {
     Disconnect { node_id: String },
     /// List current neighbors of this Node.
     ListNeighbors { size: u32 },
+    /// Manage virtual private networks
+    Vpn(VpnCommand),
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum VpnCommand {
+    /// Show traffic statistics of networks and their nodes
+    Stats {
+        /// If None, statistics of all networks will be shown.
+        network_id: Option<String>,
+    },
 }
 
 impl NetCommand {
@@ -192,6 +212,39 @@ impl NetCommand {
                 }
                 .into())
             }
+            NetCommand::Diag { node_id, samples } => {
+                let diag = bus::service(model::BUS_ID)
+                    .send(model::Diagnose {
+                        node_id: NodeId::from_str(&node_id)?,
+                        samples,
+                    })
+                    .await
+                    .map_err(anyhow::Error::msg)??;
+
+                let ms = |d: Option<Duration>| to_ms(d.map(|d| d.as_secs_f64() * 1000.0), is_json);
+                let loss = match diag.packets_sent {
+                    0 => 0.,
+                    sent => 100. * diag.packets_lost as f64 / sent as f64,
+                };
+
+                CommandOutput::object(serde_json::json!({
+                    "nodeId": diag.node_id,
+                    "type": diag.session_type,
+                    "remoteAddress": diag.remote_address.map(|a| a.to_string()),
+                    "sessionPing": ms(diag.session_ping),
+                    "rtt": {
+                        "min": ms(diag.rtt_min),
+                        "avg": ms(diag.rtt_avg),
+                        "max": ms(diag.rtt_max),
+                    },
+                    "packetsSent": diag.packets_sent,
+                    "packetsLost": diag.packets_lost,
+                    "packetLossPercent": format_number(loss, is_json),
+                    "mtu": diag.mtu,
+                    "lastError": diag.last_error,
+                    "lastErrorTime": diag.last_error_time.map(|t| t.to_string()),
+                }))
+            }
             NetCommand::Connect {
                 node_id,
                 keep_alive,
@@ -224,6 +277,51 @@ impl NetCommand {
                     .map_err(anyhow::Error::msg)??;
                 CommandOutput::object(serde_json::json!(list))
             }
+            NetCommand::Vpn(VpnCommand::Stats { network_id }) => {
+                let stats = bus::service(vpn::BUS_ID)
+                    .send(vpn::GetNetworkStats { network_id })
+                    .await
+                    .map_err(anyhow::Error::msg)??;
+
+                Ok(ResponseTable {
+                    columns: vec![
+                        "network".into(),
+                        "node".into(),
+                        "connections".into(),
+                        "out [MiB]".into(),
+                        "out packets".into(),
+                        "in [MiB]".into(),
+                        "in packets".into(),
+                    ],
+                    values: stats
+                        .into_iter()
+                        .flat_map(|net| {
+                            let total = serde_json::json! {[
+                                net.network_id,
+                                "*",
+                                net.connections,
+                                to_mib(net.tx.bytes as usize, is_json),
+                                net.tx.packets,
+                                to_mib(net.rx.bytes as usize, is_json),
+                                net.rx.packets,
+                            ]};
+                            let nodes = net.nodes.into_iter().map(move |node| {
+                                serde_json::json! {[
+                                    "",
+                                    node.node_id,
+                                    "",
+                                    to_mib(node.tx.bytes as usize, is_json),
+                                    node.tx.packets,
+                                    to_mib(node.rx.bytes as usize, is_json),
+                                    node.rx.packets,
+                                ]}
+                            });
+                            std::iter::once(total).chain(nodes)
+                        })
+                        .collect(),
+                }
+                .into())
+            }
         }
     }
 }
@@ -1,3 +1,4 @@
+use std::str::FromStr;
 use std::time::Duration;
 use structopt::StructOpt;
 use strum::VariantNames;
@@ -29,23 +30,50 @@ pub enum NetType {
     Hybrid,
 }
 
+/// Transport protocol of the hybrid net, selected by `YA_NET_BIND_URL` scheme.
+/// Relay client establishes sessions over UDP only.
+#[derive(EnumString, IntoStaticStr, Copy, Clone, Eq, PartialEq, Debug)]
+#[strum(serialize_all = "kebab-case")]
+pub enum NetTransport {
+    Udp,
+}
+
 #[derive(StructOpt, Clone)]
 #[structopt(rename_all = "kebab-case")]
 pub struct Config {
     #[structopt(env = "YA_NET_TYPE", possible_values = NetType::VARIANTS, default_value = NetType::default().into())]
     pub net_type: NetType,
+    /// Comma-separated relay servers (`host:port`). The one with the lowest handshake
+    /// time is used, the others are fallbacks. Resolved from DNS SRV record when not set.
     #[structopt(env = "YA_NET_RELAY_HOST")]
     pub host: Option<String>,
+    /// How often handshake time of relay servers is measured again. The Node switches to
+    /// the fastest server when the active one is unreachable or much slower. `0s` disables it.
+    #[structopt(env = "YA_NET_RELAY_PROBE_INTERVAL", parse(try_from_str = humantime::parse_duration), default_value = "5min")]
+    pub relay_probe_interval: Duration,
     #[structopt(env = "YA_NET_BIND_URL", default_value = "udp://0.0.0.0:11500")]
     pub bind_url: Url,
     #[structopt(env = "YA_NET_BROADCAST_SIZE", default_value = "5")]
     pub broadcast_size: u32,
     #[structopt(env = "YA_NET_PUB_BROADCAST_SIZE", default_value = "30")]
     pub pub_broadcast_size: u32,
+    /// Number of hops broadcasts from this Node may travel when propagated by other Nodes.
+    #[structopt(env = "YA_NET_BROADCAST_TTL", default_value = "4")]
+    pub broadcast_ttl: u8,
+    /// How long received broadcasts are remembered to drop their duplicates.
+    #[structopt(env = "YA_NET_BROADCAST_DEDUP_WINDOW", parse(try_from_str = humantime::parse_duration), default_value = "2min")]
+    pub broadcast_dedup_window: Duration,
     #[structopt(env = "YA_NET_SESSION_EXPIRATION", parse(try_from_str = humantime::parse_duration), default_value = "15s")]
     pub session_expiration: Duration,
     #[structopt(env = "YA_NET_SESSION_REQUEST_TIMEOUT", parse(try_from_str = humantime::parse_duration), default_value = "3s")]
     pub session_request_timeout: Duration,
+    /// How long outgoing messages are retried when session with the remote Node is lost.
+    /// Messages are dropped with an error after this time. `0s` disables retries.
+    #[structopt(env = "YA_NET_RETRY_WINDOW", parse(try_from_str = humantime::parse_duration), default_value = "10s")]
+    pub retry_window: Duration,
+    /// Maximum number of outgoing messages waiting for session re-establishment.
+    #[structopt(env = "YA_NET_RETRY_QUEUE_SIZE", default_value = "1000")]
+    pub retry_queue_size: usize,
 }
 
 impl Config {
@@ -54,4 +82,47 @@ impl Config {
         // or default values if ENV variables are not set.
         Config::from_iter_safe(&[""])
     }
+
+    pub fn relay_hosts(&self) -> Vec<String> {
+        self.host
+            .iter()
+            .flat_map(|hosts| hosts.split(','))
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    pub fn transport(&self) -> anyhow::Result<NetTransport> {
+        NetTransport::from_str(self.bind_url.scheme()).map_err(|_| {
+            anyhow::anyhow!(
+                "Unsupported YA_NET_BIND_URL scheme: '{}'. Expected 'udp'",
+                self.bind_url.scheme()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("udp://0.0.0.0:11500", Some(NetTransport::Udp))]
+    #[test_case("quic://0.0.0.0:11500", None)]
+    #[test_case("tcp://0.0.0.0:11500", None)]
+    fn transport_from_bind_url(bind_url: &str, expected: Option<NetTransport>) {
+        let mut config = Config::from_env().unwrap();
+        config.bind_url = Url::parse(bind_url).unwrap();
+        assert_eq!(config.transport().ok(), expected);
+    }
+
+    #[test_case(None, &[])]
+    #[test_case(Some("127.0.0.1:7464"), &["127.0.0.1:7464"])]
+    #[test_case(Some("a.golem.network:7477, b.golem.network:7477,"), &["a.golem.network:7477", "b.golem.network:7477"])]
+    fn relay_hosts(host: Option<&str>, expected: &[&str]) {
+        let mut config = Config::from_env().unwrap();
+        config.host = host.map(ToString::to_string);
+        assert_eq!(config.relay_hosts(), expected);
+    }
 }
@@ -4,6 +4,9 @@ use ya_core_model::net::local::{
 };
 use ya_service_bus::{typed as bus, Error, RpcEndpoint, RpcMessage};
 
+use crate::bcast::new_broadcast_id;
+use crate::hybrid::service::BCAST;
+
 pub async fn broadcast<M, S>(
     caller: S,
     message: M,
@@ -18,9 +21,34 @@ where
     M: BroadcastMessage + Send + Sync + Unpin + 'static,
     S: ToString + 'static,
 {
+    broadcast_with_ttl(caller, message, None).await
+}
+
+/// Broadcasts limited to `ttl` hops, capped by the configured TTL, which is also used when not set.
+pub async fn broadcast_with_ttl<M, S>(
+    caller: S,
+    message: M,
+    ttl: Option<u8>,
+) -> Result<
+    Result<
+        <SendBroadcastMessage<M> as RpcMessage>::Item,
+        <SendBroadcastMessage<M> as RpcMessage>::Error,
+    >,
+    Error,
+>
+where
+    M: BroadcastMessage + Send + Sync + Unpin + 'static,
+    S: ToString + 'static,
+{
+    let mut message = SendBroadcastMessage::new(message);
+    message.set_id(new_broadcast_id());
+    if let Some(ttl) = BCAST.ttl(ttl).await {
+        message.set_ttl(ttl);
+    }
+
     // TODO: We shouldn't use send_as. Put identity inside broadcasted message instead.
     bus::service(net::local::BUS_ID)
-        .send_as(caller, SendBroadcastMessage::new(message))
+        .send_as(caller, message)
         .await
 }
 
@@ -1,8 +1,11 @@
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use futures::future::join_all;
 use futures::TryFutureExt;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use ya_client_model::node_id::InvalidLengthError;
 use ya_client_model::NodeId;
@@ -10,7 +13,7 @@ use ya_client_model::NodeId;
 use ya_core_model::net as ya_net;
 use ya_core_model::net::local::{FindNodeResponse, GsbPingResponse, StatusError};
 use ya_core_model::net::{
-    local as model, GenericNetError, GsbRemotePing, RemoteEndpoint, DIAGNOSTIC,
+    local as model, GenericNetError, GsbRemoteEcho, GsbRemotePing, RemoteEndpoint, DIAGNOSTIC,
 };
 use ya_relay_client::metrics::ChannelMetrics;
 use ya_relay_client::Client;
@@ -18,21 +21,84 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::typed::ServiceBinder;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
-pub(crate) fn bind_service(base_client: Client) {
+use crate::hybrid::service::RelayClient;
+
+const DIAG_PING_TIMEOUT: Duration = Duration::from_secs(5);
+const DIAG_ECHO_TIMEOUT: Duration = Duration::from_secs(2);
+const MTU_MIN_PROBE: usize = 64;
+const MTU_MAX_PROBE: usize = 65_000;
+const MTU_PRECISION: usize = 16;
+const MAX_LAST_ERRORS: usize = 1000;
+const LAST_ERROR_TTL: Duration = Duration::from_secs(3600);
+
+lazy_static::lazy_static! {
+    static ref LAST_ERRORS: Mutex<LastErrors> = Default::default();
+}
+
+/// Last communication errors by Node, forgotten after `LAST_ERROR_TTL`.
+/// When `MAX_LAST_ERRORS` is reached, the oldest error is dropped.
+#[derive(Default)]
+struct LastErrors {
+    errors: HashMap<NodeId, (DateTime<Utc>, String)>,
+}
+
+impl LastErrors {
+    fn insert(&mut self, node_id: NodeId, time: DateTime<Utc>, error: String) {
+        self.errors.retain(|_, (ts, _)| !expired(*ts, time));
+        if self.errors.len() >= MAX_LAST_ERRORS && !self.errors.contains_key(&node_id) {
+            let oldest = self
+                .errors
+                .iter()
+                .min_by_key(|(_, (ts, _))| *ts)
+                .map(|(id, _)| *id);
+            if let Some(oldest) = oldest {
+                self.errors.remove(&oldest);
+            }
+        }
+        self.errors.insert(node_id, (time, error));
+    }
+
+    fn get(&self, node_id: &NodeId, now: DateTime<Utc>) -> Option<(DateTime<Utc>, String)> {
+        self.errors
+            .get(node_id)
+            .filter(|(ts, _)| !expired(*ts, now))
+            .cloned()
+    }
+}
+
+fn expired(time: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    (now - time)
+        .to_std()
+        .map(|age| age >= LAST_ERROR_TTL)
+        .unwrap_or(false)
+}
+
+/// Remembers the last communication error with `node_id` for `yagna net diag`.
+pub(crate) fn record_error(node_id: NodeId, error: impl ToString) {
+    LAST_ERRORS
+        .lock()
+        .unwrap()
+        .insert(node_id, Utc::now(), error.to_string());
+}
+
+fn last_error(node_id: &NodeId) -> Option<(DateTime<Utc>, String)> {
+    LAST_ERRORS.lock().unwrap().get(node_id, Utc::now())
+}
+
+pub(crate) fn bind_service(base_client: RelayClient) {
     let client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |ping: model::GsbPing| {
-        cli_ping(client.clone(), ping.nodes)
-            .map_err(|e| StatusError::RuntimeException(e.to_string()))
+        cli_ping(client.get(), ping.nodes).map_err(|e| StatusError::RuntimeException(e.to_string()))
     });
 
     let client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |msg: model::Connect| {
-        connect(client.clone(), msg).map_err(|e| GenericNetError(e.to_string()))
+        connect(client.get(), msg).map_err(|e| GenericNetError(e.to_string()))
     });
 
     let client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |msg: model::Disconnect| {
-        let client = client.clone();
+        let client = client.get();
         async move {
             client
                 .disconnect(msg.node)
@@ -42,11 +108,17 @@ pub(crate) fn bind_service(base_client: Client) {
     });
 
     ServiceBinder::new(DIAGNOSTIC, &(), ())
-        .bind(move |_, _caller: String, _msg: GsbRemotePing| async move { Ok(GsbRemotePing {}) });
+        .bind(move |_, _caller: String, _msg: GsbRemotePing| async move { Ok(GsbRemotePing {}) })
+        .bind(move |_, _caller: String, msg: GsbRemoteEcho| async move { Ok(msg.payload.len()) });
+
+    let client = base_client.clone();
+    let _ = bus::bind(model::BUS_ID, move |msg: model::Diagnose| {
+        diagnose(client.get(), msg).map_err(status_err)
+    });
 
     let client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |_: model::Status| {
-        let client = client.clone();
+        let client = client.get();
         async move {
             Ok(model::StatusResponse {
                 node_id: client.node_id(),
@@ -61,7 +133,7 @@ pub(crate) fn bind_service(base_client: Client) {
 
     let sessions_client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |_: model::Sessions| {
-        let client = sessions_client.clone();
+        let client = sessions_client.get();
         async move {
             let mut responses = Vec::new();
             let now = Instant::now();
@@ -105,7 +177,7 @@ pub(crate) fn bind_service(base_client: Client) {
 
     let sockets_client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |_: model::Sockets| {
-        let client = sockets_client.clone();
+        let client = sockets_client.get();
         async move {
             let sockets = client
                 .sockets()
@@ -127,7 +199,7 @@ pub(crate) fn bind_service(base_client: Client) {
 
     let find_node_client = base_client.clone();
     let _ = bus::bind(model::BUS_ID, move |find: model::FindNode| {
-        let client = find_node_client.clone();
+        let client = find_node_client.get();
         async move {
             let node_id: NodeId = find.node_id.parse()?;
             let node = client.find_node(node_id).await?;
@@ -155,7 +227,7 @@ pub(crate) fn bind_service(base_client: Client) {
     });
     let client_ = base_client;
     let _ = bus::bind(model::BUS_ID, move |list: model::ListNeighbours| {
-        let client = client_.clone();
+        let client = client_.get();
 
         async move { client.neighbours(list.size).await.map_err(status_err) }
     });
@@ -272,9 +344,11 @@ async fn cli_ping(client: Client, nodes: Vec<NodeId>) -> anyhow::Result<Vec<GsbP
     .map(|(idx, results)| {
         if let Err(e) = &results.0 {
             log::warn!("Failed to ping node: {} {e}", nodes[idx].0);
+            record_error(nodes[idx].0, e);
         }
         if let Err(e) = &results.1 {
             log::warn!("Failed to ping node: {} {e}", nodes[idx].0);
+            record_error(nodes[idx].0, e);
         }
 
         let udp_ping = results.0.ok();
@@ -296,7 +370,164 @@ async fn cli_ping(client: Client, nodes: Vec<NodeId>) -> anyhow::Result<Vec<GsbP
     Ok(results)
 }
 
+async fn diagnose(client: Client, msg: model::Diagnose) -> anyhow::Result<model::DiagnoseResponse> {
+    let node_id = msg.node_id;
+    let our_node_id = client.node_id();
+
+    log::info!("Diagnosing connection to Node: {node_id}");
+    client.ping_sessions().await;
+
+    let mut rtts = Vec::new();
+    let mut packets_lost = 0;
+    for _ in 0..msg.samples {
+        let before = Instant::now();
+        match udp_ping(our_node_id, node_id).await {
+            Ok(_) => rtts.push(before.elapsed()),
+            Err(e) => {
+                log::debug!("Diagnose: ping to {node_id} failed: {e}");
+                record_error(node_id, format!("(Udp ping). {e}"));
+                packets_lost += 1;
+            }
+        }
+    }
+
+    let mtu = match rtts.is_empty() {
+        true => None,
+        false => find_mtu(our_node_id, node_id).await,
+    };
+
+    let mut session = None;
+    for s in client.sessions().await {
+        if client.remote_id(&s.remote).await == Some(node_id) {
+            session = Some(s);
+            break;
+        }
+    }
+    let session_type = match &session {
+        Some(_) if client.is_p2p(node_id).await => "p2p",
+        Some(_) => "relay",
+        None => "none",
+    };
+
+    let rtt_avg = match rtts.is_empty() {
+        true => None,
+        false => Some(rtts.iter().sum::<Duration>() / rtts.len() as u32),
+    };
+    let (last_error_time, last_error) = last_error(&node_id).unzip();
+
+    Ok(model::DiagnoseResponse {
+        node_id,
+        session_type: session_type.to_string(),
+        remote_address: session.as_ref().map(|s| s.remote),
+        session_ping: session.map(|s| s.last_ping),
+        rtt_min: rtts.iter().min().copied(),
+        rtt_avg,
+        rtt_max: rtts.iter().max().copied(),
+        packets_sent: msg.samples,
+        packets_lost,
+        mtu,
+        last_error,
+        last_error_time,
+    })
+}
+
+async fn udp_ping(our_node_id: NodeId, node_id: NodeId) -> anyhow::Result<()> {
+    ya_net::from(our_node_id)
+        .to(node_id)
+        .service_udp(ya_net::DIAGNOSTIC)
+        .send(GsbRemotePing {})
+        .timeout(Some(DIAG_PING_TIMEOUT))
+        .await???;
+    Ok(())
+}
+
+async fn echo(our_node_id: NodeId, node_id: NodeId, size: usize) -> bool {
+    let result = async {
+        let received = ya_net::from(our_node_id)
+            .to(node_id)
+            .service_udp(ya_net::DIAGNOSTIC)
+            .send(GsbRemoteEcho {
+                payload: vec![0u8; size],
+            })
+            .timeout(Some(DIAG_ECHO_TIMEOUT))
+            .await???;
+        anyhow::Ok(received)
+    }
+    .await;
+
+    match result {
+        Ok(received) => received == size,
+        Err(e) => {
+            log::trace!("Diagnose: echo of {size} B to {node_id} failed: {e}");
+            false
+        }
+    }
+}
+
+/// Finds the largest payload which can be delivered over the unreliable channel
+/// using binary search. Lost probes are treated as too large.
+async fn find_mtu(our_node_id: NodeId, node_id: NodeId) -> Option<usize> {
+    if !echo(our_node_id, node_id, MTU_MIN_PROBE).await {
+        return None;
+    }
+    if echo(our_node_id, node_id, MTU_MAX_PROBE).await {
+        return Some(MTU_MAX_PROBE);
+    }
+
+    let (mut low, mut high) = (MTU_MIN_PROBE, MTU_MAX_PROBE);
+    while high - low > MTU_PRECISION {
+        let mid = low + (high - low) / 2;
+        match echo(our_node_id, node_id, mid).await {
+            true => low = mid,
+            false => high = mid,
+        }
+    }
+    Some(low)
+}
+
 #[inline]
 fn status_err(e: anyhow::Error) -> StatusError {
     StatusError::RuntimeException(e.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(idx: usize) -> NodeId {
+        format!("0x{:040x}", idx).parse().unwrap()
+    }
+
+    #[test]
+    fn last_errors_expire() {
+        let mut errors = LastErrors::default();
+        let now = Utc::now();
+        let later = now + chrono::Duration::from_std(LAST_ERROR_TTL).unwrap();
+
+        errors.insert(node(1), now, "error".to_string());
+        assert_eq!(errors.get(&node(1), now).unwrap().1, "error");
+        assert!(errors.get(&node(1), later).is_none());
+
+        errors.insert(node(2), later, "other".to_string());
+        assert_eq!(errors.errors.len(), 1);
+    }
+
+    #[test]
+    fn last_errors_capped() {
+        let mut errors = LastErrors::default();
+        let now = Utc::now();
+
+        for idx in 0..MAX_LAST_ERRORS {
+            let time = now + chrono::Duration::seconds(idx as i64);
+            errors.insert(node(idx), time, idx.to_string());
+        }
+        let later = now + chrono::Duration::seconds(MAX_LAST_ERRORS as i64);
+        errors.insert(node(0), later, "again".to_string());
+        assert_eq!(errors.errors.len(), MAX_LAST_ERRORS);
+
+        errors.insert(node(MAX_LAST_ERRORS), later, "new".to_string());
+        assert_eq!(errors.errors.len(), MAX_LAST_ERRORS);
+        assert!(errors.get(&node(1), later).is_none());
+        assert_eq!(errors.get(&node(0), later).unwrap().1, "again");
+    }
+}
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::net::{SocketAddr, ToSocketAddrs};
@@ -8,13 +8,16 @@ use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 use std::task::Poll;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context as AnyhowContext};
 use futures::channel::{mpsc, oneshot};
+use futures::future::join_all;
 use futures::stream::LocalBoxStream;
 use futures::{FutureExt, SinkExt, Stream, StreamExt, TryFutureExt, TryStreamExt};
 use metrics::counter;
 use tokio::sync::RwLock;
+use tokio::time::MissedTickBehavior;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use url::Url;
 
@@ -39,7 +42,8 @@ use ya_service_bus::{
 use ya_utils_networking::resolver;
 
 use crate::bcast::BCastService;
-use crate::config::Config;
+use crate::config::{Config, NetTransport};
+use crate::hybrid::cli;
 use crate::hybrid::codec;
 use crate::hybrid::codec::encode_message;
 use crate::hybrid::crypto::IdentityCryptoProvider;
@@ -52,6 +56,12 @@ type NetSender = mpsc::Sender<Payload>;
 type NetSinkKind = ForwardSender;
 type NetSinkKey = (NodeId, TransportType);
 
+const RETRY_INITIAL_DELAY: Duration = Duration::from_millis(250);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(2);
+/// Active relay server is replaced, when its handshake time is that many times longer
+/// than handshake time of the fastest one.
+const RELAY_SWITCH_RATIO: u32 = 2;
+
 lazy_static::lazy_static! {
     pub(crate) static ref BCAST: BCastService = Default::default();
     pub(crate) static ref SHUTDOWN_TX: Arc<RwLock<Option<oneshot::Sender<()>>>> = Default::default();
@@ -116,19 +126,31 @@ pub async fn start_network(
     log::info!("Starting network (hybrid) with identity: {default_id}");
 
     let broadcast_size = (config.broadcast_size, config.pub_broadcast_size);
+    BCAST
+        .configure(config.broadcast_ttl, config.broadcast_dedup_window)
+        .await;
+    let retry = RetryConfig {
+        window: config.retry_window,
+        queue_size: config.retry_queue_size,
+    };
     let crypto = IdentityCryptoProvider::new(default_id);
-    let client = build_client(config, crypto.clone()).await?;
+    let bind_url = bind_url(&config)?;
+    let relays = resolve_relay_addrs(&config)
+        .await
+        .map_err(|e| anyhow!("Resolving hybrid NET relay server failed. Error: {}", e))?;
+    let (client, relay) = build_client(&relays, bind_url.clone(), &config, crypto.clone()).await?;
+    let receiver = forward_receiver(&client).await?;
+    let client = RelayClient::new(client);
 
     super::cli::bind_service(client.clone());
 
-    let receiver = client.clone().forward_receiver().await.unwrap();
     let mut services: HashSet<_> = Default::default();
     ids.iter().for_each(|id| {
         services.insert(net::net_service_udp(id));
         services.insert(net::net_service(id));
         services.insert(net::net_transfer_service(id));
     });
-    let state = State::new(ids, services);
+    let state = State::new(ids, services, retry);
 
     // outbound traffic
     let net_handler = || {
@@ -143,7 +165,7 @@ pub async fn start_network(
         typed::bind(
             ya_core_model::net::local::BUS_ID,
             move |_: ya_core_model::net::local::Shutdown| {
-                let mut client = client.clone();
+                let client = client.clone();
                 async move {
                     client
                         .shutdown()
@@ -213,38 +235,302 @@ pub async fn start_network(
     tokio::task::spawn_local(forward_handler(client.clone(), receiver, state.clone()));
 
     bind_broadcast_handlers(client.clone(), broadcast_size);
-    bind_identity_event_handler(client.clone(), crypto).await;
+    bind_identity_event_handler(client.clone(), crypto.clone()).await;
     bind_neighbourhood_bcast(client.clone()).await?;
 
+    log_public_addr(&client.get()).await;
+
+    if relays.len() > 1 && !config.relay_probe_interval.is_zero() {
+        tokio::task::spawn_local(monitor_relays(
+            client, relays, relay, bind_url, config, crypto, state,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Relay client shared by all bound handlers. Handlers take the current client on
+/// every call, so they keep working after the Node switches to another relay server.
+#[derive(Clone)]
+pub(crate) struct RelayClient {
+    client: Rc<RefCell<Client>>,
+    shutdown: Rc<Cell<bool>>,
+}
+
+impl RelayClient {
+    fn new(client: Client) -> Self {
+        Self {
+            client: Rc::new(RefCell::new(client)),
+            shutdown: Default::default(),
+        }
+    }
+
+    pub(crate) fn get(&self) -> Client {
+        self.client.borrow().clone()
+    }
+
+    fn replace(&self, client: Client) -> Client {
+        self.client.replace(client)
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.get()
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.shutdown.set(true);
+        self.get().shutdown().await
+    }
+}
+
+async fn log_public_addr(client: &Client) {
     if let Some(address) = client.public_addr().await {
         log::info!("Public address: {}", address);
         counter!("net.public-addresses", 1);
     } else {
         counter!("net.public-addresses", 0);
     }
+}
 
-    Ok(())
+async fn forward_receiver(client: &Client) -> anyhow::Result<ForwardReceiver> {
+    client
+        .clone()
+        .forward_receiver()
+        .await
+        .ok_or_else(|| anyhow!("Relay client forward receiver already taken"))
 }
 
+/// Connects to the relay server with the lowest handshake time.
 async fn build_client(
+    relays: &[SocketAddr],
+    bind_url: Url,
+    config: &Config,
+    crypto: impl CryptoProvider + Clone + 'static,
+) -> anyhow::Result<(Client, SocketAddr)> {
+    if let [addr] = relays {
+        let client = connect_relay(*addr, bind_url, config, crypto, FailFast::No).await?;
+        return Ok((client, *addr));
+    }
+
+    let probes = probe_relays(relays, config).await;
+    connect_best(&rank_relays(&probes), bind_url, config, crypto).await
+}
+
+/// Connects to the first reachable relay server from `ranked`. When none of them
+/// is reachable, connects to the first one anyway and lets the client re-establish
+/// the session in the background, the same as with a single relay server.
+async fn connect_best(
+    ranked: &[SocketAddr],
+    bind_url: Url,
+    config: &Config,
+    crypto: impl CryptoProvider + Clone + 'static,
+) -> anyhow::Result<(Client, SocketAddr)> {
+    if let Ok(connected) = connect_reachable(ranked, bind_url.clone(), config, crypto.clone()).await
+    {
+        return Ok(connected);
+    }
+
+    let addr = *ranked.first().context("No relay servers configured")?;
+    log::warn!("None of configured relay servers is reachable. Waiting for udp://{addr}");
+    let client = connect_relay(addr, bind_url, config, crypto, FailFast::No).await?;
+    Ok((client, addr))
+}
+
+/// Connects to the first reachable relay server from `ranked`.
+async fn connect_reachable(
+    ranked: &[SocketAddr],
+    bind_url: Url,
+    config: &Config,
+    crypto: impl CryptoProvider + Clone + 'static,
+) -> anyhow::Result<(Client, SocketAddr)> {
+    for addr in ranked {
+        match connect_relay(
+            *addr,
+            bind_url.clone(),
+            config,
+            crypto.clone(),
+            FailFast::Yes,
+        )
+        .await
+        {
+            Ok(client) => {
+                log::info!("Hybrid NET connected to relay server: udp://{addr}");
+                return Ok((client, *addr));
+            }
+            Err(e) => {
+                log::warn!("Failed to connect to relay server udp://{addr}: {e}. Trying next one");
+                counter!("net.relay.failover", 1);
+            }
+        }
+    }
+    anyhow::bail!("None of relay servers is reachable")
+}
+
+/// Re-measures handshake time of relay servers every `relay_probe_interval` and switches
+/// to the fastest one, when the active server is unreachable or much slower.
+async fn monitor_relays(
+    client: RelayClient,
+    relays: Vec<SocketAddr>,
+    mut active: SocketAddr,
+    bind_url: Url,
     config: Arc<Config>,
+    crypto: IdentityCryptoProvider,
+    state: State,
+) {
+    let mut interval = tokio::time::interval(config.relay_probe_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        if client.is_shutdown() {
+            break;
+        }
+
+        let probes = probe_relays(&relays, &config).await;
+        match select_relay(active, &probes) {
+            Some(addr) => log::info!("Switching from relay server udp://{active} to udp://{addr}"),
+            None => continue,
+        }
+        if client.is_shutdown() {
+            break;
+        }
+
+        // The active server is never among candidates, so the Node doesn't open
+        // a second session with it using the same identity.
+        let candidates: Vec<_> = rank_relays(&probes)
+            .into_iter()
+            .filter(|addr| *addr != active)
+            .collect();
+        match switch_relay(&client, &candidates, &bind_url, &config, &crypto, &state).await {
+            Ok(addr) => active = addr,
+            Err(e) => log::warn!("Staying on relay server udp://{active}: {e}"),
+        }
+    }
+}
+
+/// Connects a new relay client to the first reachable server from `ranked` and only
+/// then replaces the shared one, so a failed switch leaves the Node on its current
+/// server. The configured listen port is still taken by the current client, so the new
+/// one listens on an ephemeral port. Messages sent during the switch wait in the retry queue.
+async fn switch_relay(
+    client: &RelayClient,
+    ranked: &[SocketAddr],
+    bind_url: &Url,
+    config: &Config,
+    crypto: &(impl CryptoProvider + Clone + 'static),
+    state: &State,
+) -> anyhow::Result<SocketAddr> {
+    let mut listen_url = bind_url.clone();
+    let _ = listen_url.set_port(Some(0));
+
+    let (new, addr) = connect_reachable(ranked, listen_url, config, crypto.clone()).await?;
+    let receiver = forward_receiver(&new).await?;
+    let mut old = client.replace(new);
+    tokio::task::spawn_local(forward_handler(client.clone(), receiver, state.clone()));
+
+    if let Err(e) = old.shutdown().await {
+        log::debug!("Failed to shut down previous relay client: {e}");
+    }
+    log_public_addr(&client.get()).await;
+    Ok(addr)
+}
+
+/// Relay server to switch to from the `active` one, based on handshake times of all servers.
+fn select_relay(
+    active: SocketAddr,
+    probes: &[(SocketAddr, Option<Duration>)],
+) -> Option<SocketAddr> {
+    let (best_rtt, best) = probes
+        .iter()
+        .filter_map(|(addr, rtt)| rtt.map(|rtt| (rtt, *addr)))
+        .min()?;
+    if best == active {
+        return None;
+    }
+
+    let active_rtt = probes
+        .iter()
+        .find(|(addr, _)| *addr == active)
+        .and_then(|(_, rtt)| *rtt);
+    match active_rtt {
+        Some(rtt) if rtt <= best_rtt * RELAY_SWITCH_RATIO => None,
+        _ => Some(best),
+    }
+}
+
+fn bind_url(config: &Config) -> anyhow::Result<Url> {
+    match config.transport()? {
+        NetTransport::Udp => counter!("net.transport.udp", 1),
+    }
+    Ok(config.bind_url.clone())
+}
+
+async fn connect_relay(
+    addr: SocketAddr,
+    bind_url: Url,
+    config: &Config,
     crypto: impl CryptoProvider + 'static,
+    fail_fast: FailFast,
 ) -> anyhow::Result<Client> {
-    let addr = resolve_relay_addr(&config)
-        .await
-        .map_err(|e| anyhow!("Resolving hybrid NET relay server failed. Error: {}", e))?;
-    let url = Url::parse(&format!("udp://{addr}"))?;
-
-    ClientBuilder::from_url(url)
+    ClientBuilder::from_url(Url::parse(&format!("udp://{addr}"))?)
         .crypto(crypto)
-        .listen(config.bind_url.clone())
+        .listen(bind_url)
         .expire_session_after(config.session_expiration)
         .session_request_timeout(config.session_request_timeout)
-        .connect(FailFast::No)
+        .connect(fail_fast)
         .build()
         .await
 }
 
+/// Measures handshake time of all relay servers concurrently.
+/// Unreachable servers have no handshake time.
+async fn probe_relays(
+    relays: &[SocketAddr],
+    config: &Config,
+) -> Vec<(SocketAddr, Option<Duration>)> {
+    join_all(relays.iter().map(|addr| async move {
+        match probe_relay(*addr, config).await {
+            Ok(rtt) => {
+                log::info!("Relay server udp://{addr} handshake time: {rtt:?}");
+                (*addr, Some(rtt))
+            }
+            Err(e) => {
+                log::warn!("Relay server udp://{addr} is unreachable: {e}");
+                (*addr, None)
+            }
+        }
+    }))
+    .await
+}
+
+/// Orders relay servers by their handshake time. Unreachable servers are placed last.
+fn rank_relays(probes: &[(SocketAddr, Option<Duration>)]) -> Vec<SocketAddr> {
+    let mut ranked = probes.to_vec();
+    ranked.sort_by_key(|(_, rtt)| rtt.unwrap_or(Duration::MAX));
+    ranked.into_iter().map(|(addr, _)| addr).collect()
+}
+
+/// Measures time needed to establish a session with the relay server, using
+/// a short-lived client with a random key, listening on an ephemeral port.
+/// Probes never use the Node identity, so they don't interfere with its session
+/// on the active relay server.
+async fn probe_relay(addr: SocketAddr, config: &Config) -> anyhow::Result<Duration> {
+    let started = Instant::now();
+    let mut client = ClientBuilder::from_url(Url::parse(&format!("udp://{addr}"))?)
+        .listen(Url::parse("udp://0.0.0.0:0")?)
+        .session_request_timeout(config.session_request_timeout)
+        .connect(FailFast::Yes)
+        .build()
+        .await?;
+    let rtt = started.elapsed();
+
+    if let Err(e) = client.shutdown().await {
+        log::debug!("Failed to shut down relay server udp://{addr} probe: {e}");
+    }
+    Ok(rtt)
+}
+
 struct RetryArgs {
     max_retries: u64,
     start_retry_timeout: u64,
@@ -278,10 +564,9 @@ async fn resolve_srv_record_with_retries(prefix: &str, args: RetryArgs) -> anyho
     }
 }
 
-async fn resolve_relay_addr(config: &Config) -> anyhow::Result<SocketAddr> {
-    let host_port = match &config.host {
-        Some(val) => val.to_string(),
-        None => {
+async fn resolve_relay_addrs(config: &Config) -> anyhow::Result<Vec<SocketAddr>> {
+    let hosts = match config.relay_hosts() {
+        hosts if hosts.is_empty() => vec![
             resolve_srv_record_with_retries(
                 "_net_relay._udp",
                 RetryArgs {
@@ -290,10 +575,32 @@ async fn resolve_relay_addr(config: &Config) -> anyhow::Result<SocketAddr> {
                     add_seconds_every_retry: 5,
                 },
             )
-            .await?
-        }
+            .await?,
+        ],
+        hosts => hosts,
     };
 
+    if let [host_port] = hosts.as_slice() {
+        return Ok(vec![resolve_relay_addr(host_port).await?]);
+    }
+
+    let mut addrs = Vec::with_capacity(hosts.len());
+    for host_port in &hosts {
+        match resolve_relay_addr(host_port).await {
+            Ok(addr) => addrs.push(addr),
+            Err(e) => log::warn!("Skipping relay server {host_port}: {e}"),
+        }
+    }
+    if addrs.is_empty() {
+        anyhow::bail!(
+            "None of relay servers could be resolved: {}",
+            hosts.join(", ")
+        );
+    }
+    Ok(addrs)
+}
+
+async fn resolve_relay_addr(host_port: &str) -> anyhow::Result<SocketAddr> {
     log::info!("Hybrid NET relay server configured on url: udp://{host_port}");
 
     let (host, port) = &host_port
@@ -308,7 +615,7 @@ async fn resolve_relay_addr(config: &Config) -> anyhow::Result<SocketAddr> {
 }
 
 fn bind_local_bus<F>(
-    base_client: Client,
+    base_client: RelayClient,
     address: &'static str,
     state: State,
     transport: TransportType,
@@ -477,14 +784,14 @@ fn bind_local_bus<F>(
 }
 
 /// Handle identity changes
-async fn bind_identity_event_handler(client: Client, crypto: IdentityCryptoProvider) {
+async fn bind_identity_event_handler(client: RelayClient, crypto: IdentityCryptoProvider) {
     let endpoint = format!("{}/id", net::BUS_ID);
 
     typed::bind(endpoint.as_str(), move |event: IdentityEvent| {
         log::debug!("Identity event received: {:?}", event);
 
         crypto.reset_alias_cache();
-        let client = client.clone();
+        let client = client.get();
 
         async move {
             match event {
@@ -548,7 +855,7 @@ fn push_bus_to_local(caller: NodeId, addr: &str, data: &[u8], state: &State) {
 
 /// Forward requests from local bus to the network
 fn forward_bus_to_net(
-    client: Client,
+    client: RelayClient,
     caller_id: NodeId,
     remote_id: NodeId,
     address: impl ToString,
@@ -597,25 +904,21 @@ fn forward_bus_to_net(
             msg.len()
         );
 
-        match state.forward_sink(client, remote_id, transport).await {
-            Ok(mut sink) => {
-                let _ = sink.send(msg.into()).await.map_err(|_| {
-                    let err = "Net: error sending message: session closed".to_string();
-                    handler_reply_service_err(request_id, err, tx);
-                });
-            }
-            Err(error) => {
-                let err = format!("Net: error forwarding message: {:?}", error);
-                handler_reply_service_err(request_id, err, tx);
-            }
-        };
+        if let Err(error) = state
+            .forward_with_retry(client, remote_id, transport, msg)
+            .await
+        {
+            let err = format!("Net: error forwarding message: {:?}", error);
+            cli::record_error(remote_id, &err);
+            handler_reply_service_err(request_id, err, tx);
+        }
     });
 
     rx
 }
 
 fn push_bus_to_net(
-    client: Client,
+    client: RelayClient,
     caller_id: NodeId,
     remote_id: NodeId,
     address: impl ToString,
@@ -651,19 +954,13 @@ fn push_bus_to_net(
             msg.len()
         );
 
-        match state
-            .forward_sink(client.clone(), remote_id, transport)
+        if let Err(error) = state
+            .forward_with_retry(client, remote_id, transport, msg)
             .await
         {
-            Ok(mut sink) => {
-                let _ = sink.send(msg.into()).await.map_err(|_| {
-                    log::debug!("Net: error sending message: session closed");
-                });
-            }
-            Err(error) => {
-                log::debug!("Net: error forwarding message: {}", error);
-            }
-        };
+            log::debug!("Net: error forwarding message: {}", error);
+            cli::record_error(remote_id, format!("Net: error forwarding message: {error}"));
+        }
     });
 }
 
@@ -682,6 +979,15 @@ fn broadcast_handler(
         let stub: SendBroadcastStub = serialization::from_slice(&message)
             .map_err(|e| Error::GsbFailure(format!("Invalid broadcast message: {e}")))?;
 
+        if stub.ttl == Some(0) {
+            log::trace!(
+                "Dropping broadcast to topic {} with exhausted TTL",
+                stub.topic
+            );
+            counter!("net.broadcasts.ttl-exhausted", 1);
+            return Ok(serialization::to_vec(&Ok::<(), ()>(())).unwrap());
+        }
+
         let request = GsbMessage::BroadcastRequest(ya_sb_proto::BroadcastRequest {
             //data: serialization::to_vec(&message)?,
             data: message,
@@ -712,7 +1018,7 @@ fn broadcast_handler(
     })
 }
 
-fn bind_broadcast_handlers(client: Client, broadcast_size: (u32, u32)) {
+fn bind_broadcast_handlers(client: RelayClient, broadcast_size: (u32, u32)) {
     let _ = typed::bind(
         net::local::BUS_ID,
         move |subscribe: net::local::Subscribe| {
@@ -734,7 +1040,7 @@ fn bind_broadcast_handlers(client: Client, broadcast_size: (u32, u32)) {
     let _ = local_bus::subscribe(
         &format!("{}/{}", net::local::BUS_ID, bcast_service_id),
         move |caller: &str, addr: &str, msg: &[u8]| {
-            broadcast_handler(client.clone(), caller, addr, msg, broadcast_size)
+            broadcast_handler(client.get(), caller, addr, msg, broadcast_size)
         },
         (),
     );
@@ -742,7 +1048,7 @@ fn bind_broadcast_handlers(client: Client, broadcast_size: (u32, u32)) {
 
 /// Handle incoming forward messages
 fn forward_handler(
-    client: Client,
+    client: RelayClient,
     receiver: ForwardReceiver,
     state: State,
 ) -> impl Future<Output = ()> + Unpin + 'static {
@@ -812,7 +1118,7 @@ fn forward_channel<'a>(
 
 /// Forward node GSB messages from the network to the local bus
 fn inbound_handler(
-    client: Client,
+    client: RelayClient,
     rx: impl Stream<Item = Payload> + 'static,
     remote_id: NodeId,
     transport: TransportType,
@@ -832,7 +1138,7 @@ fn inbound_handler(
                     if request.no_reply {
                         handle_push(request, remote_id, state)
                     } else {
-                        handle_request(client, request, remote_id, state, transport)
+                        handle_request(client.get(), request, remote_id, state, transport)
                     }
                 }
                 Ok(Some(GsbMessage::CallReply(reply @ ya_sb_proto::CallReply { .. }))) => {
@@ -1067,11 +1373,19 @@ fn handle_broadcast(
     );
 
     let caller = caller_id.unwrap().to_string();
+    serialization::from_slice::<SendBroadcastStub>(&request.data)
+        .map_err(|e| anyhow!("Invalid broadcast message from [{caller}]: {e}"))?;
 
     tokio::task::spawn_local(async move {
         let data = request.data;
         let topic = request.topic;
 
+        if BCAST.is_duplicate(&topic, &data).await {
+            log::trace!("Dropping duplicate broadcast from [{caller}] (topic: {topic})");
+            counter!("net.broadcasts.duplicates", 1);
+            return;
+        }
+
         for endpoint in BCAST
             .resolve(&topic)
             .await
@@ -1104,19 +1418,179 @@ struct StateInner {
     routes: HashMap<NetSinkKey, NetSender>,
     ids: HashSet<NodeId>,
     services: HashSet<String>,
+    retry: RetryConfig,
+    /// Number of messages waiting for session re-establishment.
+    retry_queued: usize,
+    lanes: HashMap<NodeId, Rc<Lane>>,
+    /// Nodes with a session already counted in the `net.connections.*` metrics.
+    sessions: HashSet<NodeId>,
+}
+
+/// Messages to a single Node are sent one at a time, so they're replayed in order.
+#[derive(Default)]
+struct Lane {
+    lock: tokio::sync::Mutex<()>,
+    /// Message holding the lock waits for session re-establishment.
+    retrying: Cell<bool>,
+}
+
+fn expired(window: Duration) -> String {
+    format!(
+        "message expired after {}",
+        humantime::format_duration(window)
+    )
+}
+
+#[derive(Clone, Copy, Default)]
+struct RetryConfig {
+    window: Duration,
+    queue_size: usize,
 }
 
 impl State {
-    fn new(ids: impl IntoIterator<Item = NodeId>, services: HashSet<String>) -> Self {
+    fn new(
+        ids: impl IntoIterator<Item = NodeId>,
+        services: HashSet<String>,
+        retry: RetryConfig,
+    ) -> Self {
         Self {
             inner: Rc::new(RefCell::new(StateInner {
                 ids: ids.into_iter().collect(),
                 services,
+                retry,
                 ..Default::default()
             })),
         }
     }
 
+    /// Sends message to the remote Node. When the session is lost, message is kept
+    /// in the bounded retry queue and re-sent after the session is re-established.
+    /// Fails when the session isn't restored within the retry window.
+    async fn forward_with_retry(
+        &self,
+        client: RelayClient,
+        remote_id: NodeId,
+        transport: TransportType,
+        msg: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let (client, msg) = (&client, &msg);
+        // Errors come from ya-relay-client failing to establish or keep a session
+        // with the remote Node, which is expected to recover after a relay switch.
+        self.retry(remote_id, || async move {
+            let result = async {
+                let mut sink = self
+                    .forward_sink(client.get(), remote_id, transport)
+                    .await?;
+                sink.send(msg.clone().into())
+                    .await
+                    .map_err(|_| anyhow!("session closed"))
+            }
+            .await;
+            if result.is_err() {
+                self.session_lost(remote_id);
+            }
+            result
+        })
+        .await
+    }
+
+    /// Repeats `attempt` until it succeeds or the retry window elapses.
+    /// Messages to the same Node wait for the earlier ones, including the retried ones.
+    async fn retry<F, Fut>(&self, remote_id: NodeId, mut attempt: F) -> anyhow::Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let retry = self.inner.borrow().retry;
+        let deadline = Instant::now() + retry.window;
+        let mut delay = RETRY_INITIAL_DELAY;
+        let mut queued = false;
+
+        let lane = self.lane(remote_id);
+        let result = match lane.lock.try_lock() {
+            Ok(guard) => Ok(guard),
+            Err(_) if retry.window.is_zero() || !lane.retrying.get() => Ok(lane.lock.lock().await),
+            Err(_) if !self.enqueue_retry() => Err(anyhow!("retry queue is full")),
+            Err(_) => {
+                queued = true;
+                let wait = deadline.saturating_duration_since(Instant::now());
+                tokio::time::timeout(wait, lane.lock.lock())
+                    .await
+                    .map_err(|_| anyhow!(expired(retry.window)))
+            }
+        };
+
+        let result = match result {
+            Ok(_guard) => {
+                let result = loop {
+                    let error = match attempt().await {
+                        Ok(_) => break Ok(()),
+                        Err(error) => error,
+                    };
+
+                    if !queued {
+                        if retry.window.is_zero() {
+                            break Err(error);
+                        }
+                        if !self.enqueue_retry() {
+                            break Err(error.context("retry queue is full"));
+                        }
+                        queued = true;
+                    }
+                    if Instant::now() + delay > deadline {
+                        break Err(error.context(expired(retry.window)));
+                    }
+                    lane.retrying.set(true);
+
+                    log::debug!(
+                        "Net: session with {remote_id} lost ({error}). Retrying in {delay:?}"
+                    );
+                    counter!("net.retry.attempts", 1);
+                    tokio::time::sleep(delay).await;
+                    delay = (delay * 2).min(RETRY_MAX_DELAY);
+                };
+                lane.retrying.set(false);
+                result
+            }
+            Err(error) => Err(error),
+        };
+
+        drop(lane);
+        self.release_lane(remote_id);
+        if queued {
+            self.inner.borrow_mut().retry_queued -= 1;
+            match &result {
+                Ok(_) => counter!("net.retry.replayed", 1),
+                Err(_) => counter!("net.retry.expired", 1),
+            }
+        }
+        result
+    }
+
+    fn enqueue_retry(&self) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        if inner.retry_queued >= inner.retry.queue_size {
+            return false;
+        }
+        inner.retry_queued += 1;
+        true
+    }
+
+    fn lane(&self, remote_id: NodeId) -> Rc<Lane> {
+        let mut inner = self.inner.borrow_mut();
+        inner.lanes.entry(remote_id).or_default().clone()
+    }
+
+    /// Forgets the lane, when no message to the Node is pending.
+    fn release_lane(&self, remote_id: NodeId) {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(lane) = inner.lanes.get(&remote_id) {
+            if Rc::strong_count(lane) == 1 {
+                inner.lanes.remove(&remote_id);
+            }
+        }
+    }
+
     async fn forward_sink(
         &self,
         client: Client,
@@ -1128,17 +1602,28 @@ impl State {
             TransportType::Reliable => client.forward_reliable(remote_id).await?.framed(),
             TransportType::Transfer => client.forward_transfer(remote_id).await?.framed(),
         };
-
-        // FIXME: yagna daemon doesn't handle connections; ya-relay-client does
-        // if client.sessions.has_p2p_connection(remote_id).await {
-        //     counter!("net.connections.p2p", 1)
-        // } else {
-        //     counter!("net.connections.relay", 1)
-        // };
+        self.session_established(&client, remote_id).await;
 
         Ok(forward)
     }
 
+    /// Counts the session with `remote_id` once, when the first sink is opened over it.
+    async fn session_established(&self, client: &Client, remote_id: NodeId) {
+        if !self.inner.borrow_mut().sessions.insert(remote_id) {
+            return;
+        }
+        if client.is_p2p(remote_id).await {
+            counter!("net.connections.p2p", 1)
+        } else {
+            counter!("net.connections.relay", 1)
+        }
+    }
+
+    /// Session re-established after a forwarding failure is counted again.
+    fn session_lost(&self, remote_id: NodeId) {
+        self.inner.borrow_mut().sessions.remove(&remote_id);
+    }
+
     fn get_public_service(&self, addr: &str) -> Option<String> {
         let inner = self.inner.borrow();
         RevPrefixes(addr)
@@ -1234,12 +1719,12 @@ fn gen_id() -> u64 {
     rng.gen::<u64>() & 0x001f_ffff_ffff_ffff_u64
 }
 
-async fn bind_neighbourhood_bcast(client: Client) -> anyhow::Result<(), BindBroadcastError> {
+async fn bind_neighbourhood_bcast(client: RelayClient) -> anyhow::Result<(), BindBroadcastError> {
     let bcast_address = format!("{}/{}", net::local::BUS_ID, NewNeighbour::TOPIC);
     crate::hybrid::bind_broadcast_with_caller(
         &bcast_address,
         move |caller, _msg: SendBroadcastMessage<NewNeighbour>| {
-            let client = client.clone();
+            let client = client.get();
             async move {
                 log::debug!(
                     "NewNeighbour notification fron [{caller}] - invalidating neighborhood cache."
@@ -1269,6 +1754,211 @@ mod tests {
     use super::*;
     use test_case::test_case;
 
+    fn relay(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn probes(rtts: &[Option<u64>]) -> Vec<(SocketAddr, Option<Duration>)> {
+        rtts.iter()
+            .enumerate()
+            .map(|(i, rtt)| (relay(7464 + i as u16), rtt.map(Duration::from_millis)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rank_relays() {
+        let ranked = rank_relays(&probes(&[None, Some(30), Some(10), None, Some(20)]));
+        let ports: Vec<_> = ranked.iter().map(SocketAddr::port).collect();
+        assert_eq!(ports, [7466, 7468, 7465, 7464, 7467]);
+    }
+
+    #[test_case(&[Some(10), Some(15)], None; "active is the fastest")]
+    #[test_case(&[Some(20), Some(15)], None; "active is slightly slower")]
+    #[test_case(&[Some(40), Some(15)], Some(7465); "active is much slower")]
+    #[test_case(&[None, Some(100), Some(50)], Some(7466); "active is unreachable")]
+    #[test_case(&[None, None], None; "all are unreachable")]
+    fn test_select_relay(rtts: &[Option<u64>], expected: Option<u16>) {
+        let selected = select_relay(relay(7464), &probes(rtts));
+        assert_eq!(selected.map(|addr| addr.port()), expected);
+    }
+
+    #[actix_rt::test]
+    async fn test_failed_switch_keeps_active_client() {
+        let mut config = Config::from_env().unwrap();
+        config.session_request_timeout = Duration::from_millis(200);
+        // Relay server, which never answers session requests
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let unreachable = silent.local_addr().unwrap();
+        let crypto = ya_relay_client::crypto::FallbackCryptoProvider::default();
+
+        let active = connect_relay(
+            unreachable,
+            Url::parse("udp://127.0.0.1:0").unwrap(),
+            &config,
+            crypto.clone(),
+            FailFast::No,
+        )
+        .await
+        .unwrap();
+        let listen_addr = active.bind_addr().await.unwrap();
+        let client = RelayClient::new(active);
+
+        let state = State::new(vec![], HashSet::new(), RetryConfig::default());
+        let bind_url = Url::parse(&format!("udp://{listen_addr}")).unwrap();
+        let result =
+            switch_relay(&client, &[unreachable], &bind_url, &config, &crypto, &state).await;
+
+        assert!(result.is_err());
+        assert_eq!(client.get().bind_addr().await.unwrap(), listen_addr);
+    }
+
+    fn retry_state(window_ms: u64, queue_size: usize) -> State {
+        let retry = RetryConfig {
+            window: Duration::from_millis(window_ms),
+            queue_size,
+        };
+        State::new(vec![], HashSet::new(), retry)
+    }
+
+    /// Fails with given errors, then succeeds
+    async fn retry(state: &State, errors: Vec<anyhow::Error>) -> (anyhow::Result<()>, usize) {
+        let remote_id = NodeId::from_str("0x95369fc6fd02afeca110b9c32a21fb8ad899ee0a").unwrap();
+        let errors = RefCell::new(errors.into_iter());
+        let attempts = Cell::new(0);
+        let result = state
+            .retry(remote_id, || {
+                attempts.set(attempts.get() + 1);
+                let result = errors.borrow_mut().next().map_or(Ok(()), Err);
+                async move { result }
+            })
+            .await;
+        assert_eq!(state.inner.borrow().retry_queued, 0);
+        (result, attempts.get())
+    }
+
+    fn transport_error() -> anyhow::Error {
+        anyhow!("session lost")
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_transport_errors() {
+        let state = retry_state(1000, 1);
+        let (result, attempts) = retry(&state, vec![transport_error(), transport_error()]).await;
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_relay_errors() {
+        let mut config = Config::from_env().unwrap();
+        config.session_request_timeout = Duration::from_millis(100);
+        // Relay server, which never answers session requests
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client = connect_relay(
+            silent.local_addr().unwrap(),
+            Url::parse("udp://127.0.0.1:0").unwrap(),
+            &config,
+            ya_relay_client::crypto::FallbackCryptoProvider::default(),
+            FailFast::No,
+        )
+        .await
+        .unwrap();
+
+        let state = retry_state(600, 1);
+        let remote_id = NodeId::from_str("0x95369fc6fd02afeca110b9c32a21fb8ad899ee0a").unwrap();
+        let result = state
+            .forward_with_retry(
+                RelayClient::new(client),
+                remote_id,
+                TransportType::Reliable,
+                b"message".to_vec(),
+            )
+            .await;
+
+        // Error of ya-relay-client is retried until the message expires
+        assert!(result.unwrap_err().to_string().contains("expired"));
+        assert_eq!(state.inner.borrow().retry_queued, 0);
+        assert!(state.inner.borrow().lanes.is_empty());
+    }
+
+    /// Fails given number of times, then records the message as sent
+    async fn send(
+        state: &State,
+        sent: &RefCell<Vec<usize>>,
+        id: usize,
+        failures: usize,
+    ) -> anyhow::Result<()> {
+        let remote_id = NodeId::from_str("0x95369fc6fd02afeca110b9c32a21fb8ad899ee0a").unwrap();
+        let failures = Cell::new(failures);
+        state
+            .retry(remote_id, || {
+                let result = match failures.get() {
+                    0 => {
+                        sent.borrow_mut().push(id);
+                        Ok(())
+                    }
+                    n => {
+                        failures.set(n - 1);
+                        Err(transport_error())
+                    }
+                };
+                async move { result }
+            })
+            .await
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_keeps_order() {
+        let state = retry_state(1000, 3);
+        let sent = RefCell::new(Vec::new());
+
+        // First message waits for the session, the following ones wait for the first one
+        let (first, second, third) = futures::join!(
+            send(&state, &sent, 1, 2),
+            send(&state, &sent, 2, 0),
+            send(&state, &sent, 3, 0)
+        );
+        assert!(first.is_ok() && second.is_ok() && third.is_ok());
+        assert_eq!(*sent.borrow(), [1, 2, 3]);
+        assert_eq!(state.inner.borrow().retry_queued, 0);
+        assert!(state.inner.borrow().lanes.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_expires() {
+        let state = retry_state(300, 1);
+        let errors = (0..5).map(|_| transport_error()).collect();
+        let (result, attempts) = retry(&state, errors).await;
+        assert!(result.unwrap_err().to_string().contains("expired"));
+        assert_eq!(attempts, 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_queue_full() {
+        let state = retry_state(1000, 0);
+        let (result, attempts) = retry(&state, vec![transport_error()]).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("retry queue is full"));
+        assert_eq!(attempts, 1);
+
+        // Retrying is disabled
+        let state = retry_state(0, 1);
+        let (result, attempts) = retry(&state, vec![transport_error()]).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test_case("udp://0.0.0.0:11500", true)]
+    #[test_case("quic://0.0.0.0:11500", false)]
+    #[test_case("tcp://0.0.0.0:11500", false)]
+    fn test_bind_url(url: &str, accepted: bool) {
+        let mut config = Config::from_env().unwrap();
+        config.bind_url = Url::parse(url).unwrap();
+        assert_eq!(bind_url(&config).is_ok(), accepted);
+    }
+
     #[test_case(
         "/net/0x95369fc6fd02afeca110b9c32a21fb8ad899ee0a/vpn/VpnControl",
         NodeId::from_str("0x95369fc6fd02afeca110b9c32a21fb8ad899ee0a").unwrap();
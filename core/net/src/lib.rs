@@ -3,7 +3,7 @@ pub use ya_core_model::net::{
 };
 
 pub use config::{Config, NetType};
-pub use service::{bind_broadcast_with_caller, broadcast, Net};
+pub use service::{bind_broadcast_with_caller, broadcast, broadcast_with_ttl, Net};
 
 mod bcast;
 pub mod central;
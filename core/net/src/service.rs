@@ -1,8 +1,10 @@
 use std::sync::{Arc, RwLock};
 
-use ya_core_model::net::local::{BindBroadcastError, BroadcastMessage, SendBroadcastMessage};
+use ya_core_model::net::local::{
+    BindBroadcastError, BroadcastMessage, SendBroadcastMessage, Status, BUS_ID as NET_BUS_ID,
+};
 use ya_core_model::{identity, NodeId};
-use ya_service_api_interfaces::{Provider, Service};
+use ya_service_api_interfaces::{HealthStatus, Provider, Service};
 use ya_service_bus::{Error, RpcEndpoint, RpcMessage};
 
 use crate::config::{Config, NetType};
@@ -65,6 +67,25 @@ impl Net {
         }
     }
 
+    pub async fn health() -> HealthStatus {
+        let net_type = { *NET_TYPE.read().unwrap() };
+        if net_type == NetType::Central {
+            return HealthStatus::Healthy;
+        }
+
+        match ya_service_bus::typed::service(NET_BUS_ID)
+            .send(Status {})
+            .await
+        {
+            Ok(Ok(status)) if status.sessions == 0 => {
+                HealthStatus::Degraded("no active network sessions".to_string())
+            }
+            Ok(Ok(_)) => HealthStatus::Healthy,
+            Ok(Err(e)) => HealthStatus::Unhealthy(e.to_string()),
+            Err(e) => HealthStatus::Unhealthy(e.to_string()),
+        }
+    }
+
     pub async fn shutdown() -> anyhow::Result<()> {
         let config = Config::from_env()?;
 
@@ -102,6 +123,30 @@ where
     }
 }
 
+/// Chooses one of implementations of `broadcast_with_ttl` function
+/// for Hybrid Net or for Central Net.
+pub async fn broadcast_with_ttl<M, S>(
+    caller: S,
+    message: M,
+    ttl: Option<u8>,
+) -> Result<
+    Result<
+        <SendBroadcastMessage<M> as RpcMessage>::Item,
+        <SendBroadcastMessage<M> as RpcMessage>::Error,
+    >,
+    Error,
+>
+where
+    M: BroadcastMessage + Send + Sync + Unpin + 'static,
+    S: ToString + 'static,
+{
+    let net_type = { *NET_TYPE.read().unwrap() };
+    match net_type {
+        NetType::Central => crate::central::broadcast_with_ttl(caller, message, ttl).await,
+        NetType::Hybrid => crate::hybrid::broadcast_with_ttl(caller, message, ttl).await,
+    }
+}
+
 /// Chooses one of implementations of `bind_broadcast_with_caller` function
 /// for Hybrid Net or for Central Net.
 pub async fn bind_broadcast_with_caller<M, T, F>(
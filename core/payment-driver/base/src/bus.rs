@@ -86,6 +86,24 @@ pub async fn bind_service<Driver: PaymentDriver + 'static>(
         .bind_with_processor(
             move |_, dr, c, m| async move { dr.release_deposit( c, m).await }
         )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.sweep( c, m).await }
+        )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.get_allowance( c, m).await }
+        )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.sign_permit( c, m).await }
+        )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.approve( c, m).await }
+        )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.submit_permit( c, m).await }
+        )
+        .bind_with_processor(
+            move |_, dr, c, m| async move { dr.estimate_batch( c, m).await }
+        )
         .bind_with_processor(
             move |_, dr, c, m| async move { dr.shut_down( c, m).await }
         );
@@ -105,6 +123,7 @@ pub async fn bind_service<Driver: PaymentDriver + 'static>(
             networks: driver.get_networks(),
             recv_init_required: driver.recv_init_required(),
         },
+        capabilities: driver.get_capabilities(),
     };
     service(payment_srv::BUS_ID).send(message).await?.unwrap(); // Unwrap on purpose because it's NoError
     log::debug!("Successfully registered driver in payment service.");
@@ -22,6 +22,7 @@ pub use ya_client_model::payment::network::Network;
 pub use ya_client_model::NodeId;
 pub use ya_core_model::identity::event::IdentityEvent;
 pub use ya_core_model::identity::Error as IdentityError;
+pub use ya_core_model::payment::local::{ConfirmationLatency, DriverCapabilities, FeeAsset};
 use ya_core_model::signable::{prepare_signature_hash, Signable};
 
 #[async_trait(?Send)]
@@ -50,6 +51,9 @@ pub trait PaymentDriver {
     fn get_default_network(&self) -> String;
     fn get_networks(&self) -> HashMap<String, Network>;
     fn recv_init_required(&self) -> bool;
+    fn get_capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities::default()
+    }
 
     /// There is no guarentee that this method will be called only once
     /// AccountMode in Init message should be incremental i.e. :
@@ -86,6 +90,60 @@ pub trait PaymentDriver {
         msg: DriverReleaseDeposit,
     ) -> Result<(), GenericError>;
 
+    async fn sweep(&self, _caller: String, _msg: Sweep) -> Result<SweepResult, GenericError> {
+        Err(GenericError::new(format!(
+            "Sweep is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
+    async fn get_allowance(
+        &self,
+        _caller: String,
+        _msg: GetAllowance,
+    ) -> Result<BigDecimal, GenericError> {
+        Err(GenericError::new(format!(
+            "Allowance is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
+    async fn sign_permit(&self, _caller: String, _msg: SignPermit) -> Result<Permit, GenericError> {
+        Err(GenericError::new(format!(
+            "Permit is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
+    async fn approve(&self, _caller: String, _msg: Approve) -> Result<String, GenericError> {
+        Err(GenericError::new(format!(
+            "Approve is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
+    async fn submit_permit(
+        &self,
+        _caller: String,
+        _msg: SubmitPermit,
+    ) -> Result<String, GenericError> {
+        Err(GenericError::new(format!(
+            "Permit is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
+    async fn estimate_batch(
+        &self,
+        _caller: String,
+        _msg: EstimateBatch,
+    ) -> Result<BatchEstimate, GenericError> {
+        Err(GenericError::new(format!(
+            "Batch estimation is not supported by {} driver",
+            self.get_name()
+        )))
+    }
+
     async fn sign_payment(
         &self,
         _caller: String,
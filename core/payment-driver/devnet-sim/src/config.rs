@@ -0,0 +1,94 @@
+/*
+    Simulation parameters, read from environment variables.
+*/
+
+use bigdecimal::BigDecimal;
+use rand::Rng;
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    /// Time between the end of a batching window and sending its transactions.
+    pub send_delay: Duration,
+    /// Time between simulated blocks.
+    pub block_time: Duration,
+    /// Number of blocks after which a sent transaction is confirmed.
+    pub confirmations: u32,
+    /// Probability of a transaction failing and being retried with the next batch.
+    pub failure_rate: f64,
+    /// Longest time a scheduled payment waits to be batched with others.
+    pub batch_interval: Duration,
+    /// Balance of an account seen for the first time.
+    pub initial_balance: BigDecimal,
+    /// Amount minted by `yagna payment fund`.
+    pub fund_amount: BigDecimal,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            send_delay: Duration::from_millis(500),
+            block_time: Duration::from_secs(1),
+            confirmations: 1,
+            failure_rate: 0.0,
+            batch_interval: Duration::from_secs(5),
+            initial_balance: BigDecimal::from(1000),
+            fund_amount: BigDecimal::from(1000),
+        }
+    }
+}
+
+impl SimConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(ms) = parse_env("DEVNET_SIM_SEND_DELAY_MS") {
+            config.send_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = parse_env("DEVNET_SIM_BLOCK_TIME_MS") {
+            config.block_time = Duration::from_millis(ms);
+        }
+        if let Some(confirmations) = parse_env("DEVNET_SIM_CONFIRMATIONS") {
+            config.confirmations = confirmations;
+        }
+        if let Some(rate) = parse_env::<f64>("DEVNET_SIM_FAILURE_RATE") {
+            config.failure_rate = rate.clamp(0.0, 1.0);
+        }
+        if let Some(secs) = parse_env("DEVNET_SIM_BATCH_INTERVAL_SECS") {
+            config.batch_interval = Duration::from_secs(secs);
+        }
+        if let Some(balance) = parse_env("DEVNET_SIM_INITIAL_BALANCE") {
+            config.initial_balance = balance;
+        }
+        if let Some(amount) = parse_env("DEVNET_SIM_FUND_AMOUNT") {
+            config.fund_amount = amount;
+        }
+        config
+    }
+
+    /// Time between sending a transaction and its confirmation.
+    pub fn confirmation_delay(&self) -> Duration {
+        self.block_time * self.confirmations
+    }
+
+    /// Draws whether the next transaction fails.
+    pub fn inject_failure(&self) -> bool {
+        self.failure_rate > 0.0 && rand::thread_rng().gen_bool(self.failure_rate)
+    }
+}
+
+fn parse_env<T>(name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = env::var(name).ok()?;
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            log::warn!("Value {value} for {name} is not valid: {e}");
+            None
+        }
+    }
+}
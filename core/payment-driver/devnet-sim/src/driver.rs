@@ -0,0 +1,515 @@
+/*
+    SimDriver to handle payments on the simulated devnet.
+
+    Scheduled payments are kept in the in-memory ledger and sent by a background
+    job, which mimics batching, send delay, confirmations and failing transactions.
+*/
+
+// External crates
+use chrono::{Duration, Utc};
+use maplit::hashmap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use uuid::Uuid;
+use ya_client_model::payment::DriverStatusProperty;
+
+// Workspace uses
+use ya_payment_driver::{
+    bus,
+    driver::{
+        async_trait, BigDecimal, ConfirmationLatency, DriverCapabilities, FeeAsset, IdentityError,
+        IdentityEvent, Network as NetworkConfig, PaymentDriver,
+    },
+    model::*,
+};
+
+// Local uses
+use crate::config::SimConfig;
+use crate::ledger::{Batch, Ledger, Order, Transaction};
+use crate::{DEVNET_NETWORK, DEVNET_PLATFORM, DEVNET_TOKEN, DRIVER_NAME};
+
+/// How often the send job looks for due payments.
+const SEND_TICK: std::time::Duration = std::time::Duration::from_millis(200);
+
+pub struct SimDriver {
+    config: SimConfig,
+    ledger: Mutex<Ledger>,
+    /// Batches taken from the ledger, but not settled or requeued yet.
+    sending: AtomicUsize,
+}
+
+impl SimDriver {
+    pub fn new(config: SimConfig) -> Arc<Self> {
+        let ledger = Ledger::new(config.initial_balance.clone(), config.block_time);
+        let this = Arc::new(Self {
+            config,
+            ledger: Mutex::new(ledger),
+            sending: AtomicUsize::new(0),
+        });
+
+        let this_ = Arc::clone(&this);
+        tokio::task::spawn_local(Self::send_job(this_));
+
+        this
+    }
+
+    async fn send_job(this: Arc<Self>) {
+        let mut interval = tokio::time::interval(SEND_TICK);
+        loop {
+            interval.tick().await;
+            let batches = this.ledger.lock().unwrap().take_due(Utc::now());
+            for batch in batches {
+                this.sending.fetch_add(1, Ordering::SeqCst);
+                let this = Arc::clone(&this);
+                tokio::task::spawn_local(async move {
+                    this.send_batch(batch).await;
+                    this.sending.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        }
+    }
+
+    async fn send_batch(&self, batch: Batch) {
+        tokio::time::sleep(self.config.send_delay).await;
+        let tracked = batch.tracked;
+
+        if self.config.inject_failure() {
+            log::warn!(
+                "Simulated failure of transaction sending {} payment(s) from {} to {}. Retrying with the next batch.",
+                batch.orders.len(),
+                batch.sender,
+                batch.recipient
+            );
+            self.ledger.lock().unwrap().requeue(batch);
+            return;
+        }
+
+        let settled = self.ledger.lock().unwrap().settle(batch);
+        let tx = match settled {
+            Ok(tx) => tx,
+            Err(batch) => {
+                log::warn!(
+                    "Insufficient funds on {} to send {} tGLM. Retrying with the next batch.",
+                    batch.sender,
+                    batch.amount()
+                );
+                self.ledger.lock().unwrap().requeue(batch);
+                return;
+            }
+        };
+        log::debug!("Sent transaction {}: {}", tx.tx_id, tx.details);
+        if !tracked {
+            return;
+        }
+
+        tokio::time::sleep(self.config.confirmation_delay()).await;
+        if let Err(e) = self.notify_payment(&tx).await {
+            log::error!("Failed to notify about transaction {}: {e}", tx.tx_id);
+        }
+    }
+
+    async fn notify_payment(&self, tx: &Transaction) -> Result<(), GenericError> {
+        let confirmation = serde_json::to_vec(tx).map_err(GenericError::new)?;
+        bus::notify_payment(
+            DRIVER_NAME,
+            DEVNET_PLATFORM,
+            tx.order_ids.clone(),
+            &tx.details,
+            confirmation,
+        )
+        .await
+    }
+
+    fn is_idle(&self) -> bool {
+        self.sending.load(Ordering::SeqCst) == 0 && self.ledger.lock().unwrap().pending() == 0
+    }
+
+    fn check_network(network: Option<String>) -> Result<(), GenericError> {
+        match network {
+            Some(network) if network != DEVNET_NETWORK => {
+                Err(GenericError::new(format!("Unsupported network: {network}")))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl PaymentDriver for SimDriver {
+    async fn account_event(
+        &self,
+        _caller: String,
+        _msg: IdentityEvent,
+    ) -> Result<(), IdentityError> {
+        Ok(())
+    }
+
+    async fn get_rpc_endpoints(
+        &self,
+        _caller: String,
+        _msg: GetRpcEndpoints,
+    ) -> Result<GetRpcEndpointsResult, GenericError> {
+        Ok(GetRpcEndpointsResult::default())
+    }
+
+    async fn get_account_balance(
+        &self,
+        _caller: String,
+        msg: GetAccountBalance,
+    ) -> Result<GetAccountBalanceResult, GenericError> {
+        let mut ledger = self.ledger.lock().unwrap();
+        Ok(GetAccountBalanceResult {
+            gas_details: None,
+            token_balance: ledger.balance(&msg.address()),
+            block_number: ledger.block_number(),
+            block_datetime: Utc::now(),
+        })
+    }
+
+    async fn enter(&self, _caller: String, msg: Enter) -> Result<String, GenericError> {
+        log::info!("ENTER = Not Implemented: {:?}", msg);
+        Ok("NOT_IMPLEMENTED".to_string())
+    }
+
+    async fn exit(&self, _caller: String, msg: Exit) -> Result<String, GenericError> {
+        log::info!("EXIT = Not Implemented: {:?}", msg);
+        Ok("NOT_IMPLEMENTED".to_string())
+    }
+
+    fn get_name(&self) -> String {
+        DRIVER_NAME.to_string()
+    }
+
+    fn get_default_network(&self) -> String {
+        DEVNET_NETWORK.to_string()
+    }
+
+    fn get_networks(&self) -> HashMap<String, NetworkConfig> {
+        hashmap! {
+            DEVNET_NETWORK.to_string() => NetworkConfig {
+                default_token: DEVNET_TOKEN.to_string(),
+                tokens: hashmap! {
+                    DEVNET_TOKEN.to_string() => DEVNET_PLATFORM.to_string()
+                }
+            }
+        }
+    }
+
+    fn recv_init_required(&self) -> bool {
+        false
+    }
+
+    fn get_capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            batch_transfers: true,
+            deposits: false,
+            partial_payments: true,
+            confirmation_latency: ConfirmationLatency::Seconds,
+            fee_asset: FeeAsset::None,
+        }
+    }
+
+    async fn init(&self, _caller: String, msg: Init) -> Result<Ack, GenericError> {
+        log::debug!("init: {:?}", msg);
+        Self::check_network(msg.network())?;
+
+        let mode = msg.mode();
+        let address = msg.address();
+        bus::register_account(self, &address, DEVNET_NETWORK, DEVNET_TOKEN, mode).await?;
+
+        log::info!(
+            "Initialised payment account. mode={:?}, address={}, driver={}, network={}, token={}",
+            mode,
+            address,
+            DRIVER_NAME,
+            DEVNET_NETWORK,
+            DEVNET_TOKEN
+        );
+        Ok(Ack {})
+    }
+
+    async fn fund(&self, _caller: String, msg: Fund) -> Result<String, GenericError> {
+        Self::check_network(msg.network())?;
+
+        let address = msg.address();
+        self.ledger
+            .lock()
+            .unwrap()
+            .credit(&address, &self.config.fund_amount);
+        Ok(format!(
+            "Minted {} {DEVNET_TOKEN} on simulated {DEVNET_NETWORK} network for {address}",
+            self.config.fund_amount
+        ))
+    }
+
+    async fn transfer(&self, _caller: String, msg: Transfer) -> Result<String, GenericError> {
+        Self::check_network(msg.network.clone())?;
+
+        let order_id = Uuid::new_v4().to_string();
+        self.ledger.lock().unwrap().schedule(Order {
+            id: order_id.clone(),
+            sender: msg.sender,
+            recipient: msg.to,
+            amount: msg.amount,
+            deadline: Utc::now(),
+            tracked: false,
+        });
+        Ok(order_id)
+    }
+
+    async fn schedule_payment(
+        &self,
+        _caller: String,
+        msg: SchedulePayment,
+    ) -> Result<String, GenericError> {
+        log::debug!("schedule_payment: {:?}", msg);
+
+        let now = Utc::now();
+        let interval = Duration::from_std(self.config.batch_interval).map_err(GenericError::new)?;
+        let order_id = Uuid::new_v4().to_string();
+        self.ledger.lock().unwrap().schedule(Order {
+            id: order_id.clone(),
+            sender: msg.sender(),
+            recipient: msg.recipient(),
+            amount: msg.amount(),
+            deadline: msg.due_date().min(now + interval),
+            tracked: true,
+        });
+        Ok(order_id)
+    }
+
+    async fn verify_payment(
+        &self,
+        _caller: String,
+        msg: VerifyPayment,
+    ) -> Result<PaymentDetails, GenericError> {
+        log::debug!("verify_payment: {:?}", msg);
+
+        let tx: Transaction = serde_json::from_slice(&msg.confirmation().confirmation)
+            .map_err(|e| GenericError::new(format!("Malformed confirmation: {e}")))?;
+        let details = tx.details;
+        if !details.sender.eq_ignore_ascii_case(&msg.details.payer_addr)
+            || !details
+                .recipient
+                .eq_ignore_ascii_case(&msg.details.payee_addr)
+        {
+            return Err(GenericError::new(format!(
+                "Payment {} rejected: transaction {} is between different accounts",
+                msg.details.payment_id, tx.tx_id
+            )));
+        }
+        Ok(details)
+    }
+
+    async fn validate_allocation(
+        &self,
+        _caller: String,
+        msg: ValidateAllocation,
+    ) -> Result<ValidateAllocationResult, GenericError> {
+        log::debug!("validate_allocation: {:?}", msg);
+
+        if msg.deposit.is_some() {
+            return Err(GenericError::new(format!(
+                "Deposits are not supported by {DRIVER_NAME} driver"
+            )));
+        }
+
+        let balance = self.ledger.lock().unwrap().balance(&msg.address);
+        let reserved: BigDecimal = msg
+            .active_allocations
+            .into_iter()
+            .filter(|allocation| allocation.payment_platform == msg.platform)
+            .map(|allocation| allocation.remaining_amount)
+            .sum();
+
+        Ok(if msg.amount > balance.clone() - reserved.clone() {
+            ValidateAllocationResult::InsufficientAccountFunds {
+                requested_funds: msg.amount,
+                available_funds: balance - reserved.clone(),
+                reserved_funds: reserved,
+            }
+        } else {
+            ValidateAllocationResult::Valid
+        })
+    }
+
+    async fn release_deposit(
+        &self,
+        _caller: String,
+        _msg: DriverReleaseDeposit,
+    ) -> Result<(), GenericError> {
+        Err(GenericError::new(format!(
+            "Deposits are not supported by {DRIVER_NAME} driver"
+        )))
+    }
+
+    async fn estimate_batch(
+        &self,
+        _caller: String,
+        msg: EstimateBatch,
+    ) -> Result<BatchEstimate, GenericError> {
+        Self::check_network(msg.network)?;
+
+        let ledger = self.ledger.lock().unwrap();
+        let queued = ledger.queued(&msg.sender).collect::<Vec<_>>();
+        let recipients = queued
+            .iter()
+            .map(|order| order.recipient.to_lowercase())
+            .collect::<HashSet<_>>()
+            .len() as u32;
+
+        Ok(BatchEstimate {
+            network: DEVNET_NETWORK.to_string(),
+            batch_interval: Some(self.config.batch_interval),
+            max_batch_size: None,
+            payments: queued.len() as u32,
+            transfers: recipients,
+            amount: queued.iter().map(|order| order.amount.clone()).sum(),
+            // Payments to distinct recipients are sent in separate transactions.
+            transactions: recipients,
+            gas_limit: 0,
+            gas_price: BigDecimal::from(0),
+            fee: BigDecimal::from(0),
+            fee_currency: DEVNET_TOKEN.to_string(),
+        })
+    }
+
+    async fn status(
+        &self,
+        _caller: String,
+        msg: DriverStatus,
+    ) -> Result<Vec<DriverStatusProperty>, DriverStatusError> {
+        match msg.network {
+            Some(network) if network != DEVNET_NETWORK => {
+                Err(DriverStatusError::NetworkNotFound(network))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    async fn shut_down(&self, _caller: String, msg: ShutDown) -> Result<(), GenericError> {
+        // Send everything scheduled instead of waiting for batching windows to end.
+        self.ledger.lock().unwrap().expedite();
+
+        let deadline = Instant::now() + msg.timeout;
+        while !self.is_idle() && Instant::now() < deadline {
+            tokio::time::sleep(SEND_TICK).await;
+        }
+        if !self.is_idle() {
+            log::warn!("Simulated payments left unsent on shutdown");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ya_client_model::payment::Payment;
+
+    const SENDER: &str = "0xaAaAaAaaAaAaAaaAaAAAAAAAAaaaAaAaAaaAaaAa";
+    const RECIPIENT: &str = "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB";
+
+    fn driver(failure_rate: f64) -> SimDriver {
+        let config = SimConfig {
+            send_delay: std::time::Duration::ZERO,
+            failure_rate,
+            ..Default::default()
+        };
+        let ledger = Ledger::new(BigDecimal::from(10), config.block_time);
+        SimDriver {
+            config,
+            ledger: Mutex::new(ledger),
+            sending: AtomicUsize::new(0),
+        }
+    }
+
+    fn transfer(driver: &SimDriver, amount: u32) -> Batch {
+        let mut ledger = driver.ledger.lock().unwrap();
+        ledger.schedule(Order {
+            id: Uuid::new_v4().to_string(),
+            sender: SENDER.to_string(),
+            recipient: RECIPIENT.to_string(),
+            amount: BigDecimal::from(amount),
+            deadline: Utc::now(),
+            tracked: false,
+        });
+        ledger.take_due(Utc::now()).remove(0)
+    }
+
+    fn verify_msg(tx: &Transaction, payer_addr: &str, payee_addr: &str) -> VerifyPayment {
+        let payment = Payment {
+            payment_id: "payment".to_string(),
+            payer_id: Default::default(),
+            payee_id: Default::default(),
+            payer_addr: payer_addr.to_string(),
+            payee_addr: payee_addr.to_string(),
+            payment_platform: DEVNET_PLATFORM.to_string(),
+            amount: tx.details.amount.clone(),
+            timestamp: Utc::now(),
+            activity_payments: vec![],
+            agreement_payments: vec![],
+            details: tx.tx_id.clone(),
+        };
+        let confirmation = PaymentConfirmation {
+            confirmation: serde_json::to_vec(tx).unwrap(),
+        };
+        VerifyPayment::new(confirmation, DEVNET_PLATFORM.to_string(), payment)
+    }
+
+    #[tokio::test]
+    async fn sends_batch() {
+        let driver = driver(0.0);
+        let batch = transfer(&driver, 4);
+        driver.send_batch(batch).await;
+
+        let mut ledger = driver.ledger.lock().unwrap();
+        assert_eq!(ledger.pending(), 0);
+        assert_eq!(ledger.balance(SENDER), BigDecimal::from(6));
+        assert_eq!(ledger.balance(RECIPIENT), BigDecimal::from(14));
+    }
+
+    #[tokio::test]
+    async fn requeues_failed_transaction() {
+        let driver = driver(1.0);
+        let batch = transfer(&driver, 4);
+        driver.send_batch(batch).await;
+
+        let mut ledger = driver.ledger.lock().unwrap();
+        assert_eq!(ledger.pending(), 1);
+        assert_eq!(ledger.balance(SENDER), BigDecimal::from(10));
+        assert_eq!(ledger.balance(RECIPIENT), BigDecimal::from(10));
+    }
+
+    #[tokio::test]
+    async fn requeues_batch_exceeding_balance() {
+        let driver = driver(0.0);
+        let batch = transfer(&driver, 11);
+        driver.send_batch(batch).await;
+
+        let mut ledger = driver.ledger.lock().unwrap();
+        assert_eq!(ledger.pending(), 1);
+        assert_eq!(ledger.balance(SENDER), BigDecimal::from(10));
+    }
+
+    #[tokio::test]
+    async fn verifies_payment_from_confirmation() {
+        let driver = driver(0.0);
+        let batch = transfer(&driver, 4);
+        let tx = driver.ledger.lock().unwrap().settle(batch).unwrap();
+
+        // Addresses are compared case-insensitively
+        let msg = verify_msg(&tx, &SENDER.to_lowercase(), RECIPIENT);
+        let details = driver.verify_payment("".into(), msg).await.unwrap();
+        assert_eq!(details, tx.details);
+
+        let msg = verify_msg(&tx, SENDER, SENDER);
+        assert!(driver.verify_payment("".into(), msg).await.is_err());
+
+        let mut msg = verify_msg(&tx, SENDER, RECIPIENT);
+        msg.confirmation.confirmation = b"not a transaction".to_vec();
+        assert!(driver.verify_payment("".into(), msg).await.is_err());
+    }
+}
@@ -0,0 +1,246 @@
+/*
+    In-memory state of the simulated chain.
+
+    Balances are known only to the yagna process running the driver, so payments
+    are verified from the transaction embedded in the confirmation.
+*/
+
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use ya_payment_driver::model::PaymentDetails;
+
+#[derive(Clone, Debug)]
+pub struct Order {
+    pub id: String,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: BigDecimal,
+    /// Time at which the order is sent, batched with others due by then.
+    pub deadline: DateTime<Utc>,
+    /// Scheduled by the payment service, which is notified once the order is sent.
+    /// Plain transfers are unknown to the payment service.
+    pub tracked: bool,
+}
+
+/// Payments from a single sender to a single recipient, sent as one transaction.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub sender: String,
+    pub recipient: String,
+    pub tracked: bool,
+    pub orders: Vec<Order>,
+}
+
+impl Batch {
+    pub fn amount(&self) -> BigDecimal {
+        self.orders.iter().map(|order| order.amount.clone()).sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub tx_id: String,
+    pub order_ids: Vec<String>,
+    pub details: PaymentDetails,
+}
+
+pub struct Ledger {
+    balances: HashMap<String, BigDecimal>,
+    pending: Vec<Order>,
+    initial_balance: BigDecimal,
+    started: Instant,
+    block_time: Duration,
+}
+
+impl Ledger {
+    pub fn new(initial_balance: BigDecimal, block_time: Duration) -> Self {
+        Ledger {
+            balances: Default::default(),
+            pending: Default::default(),
+            initial_balance,
+            started: Instant::now(),
+            block_time,
+        }
+    }
+
+    pub fn balance(&mut self, address: &str) -> BigDecimal {
+        self.account(address).clone()
+    }
+
+    pub fn credit(&mut self, address: &str, amount: &BigDecimal) {
+        *self.account(address) += amount;
+    }
+
+    pub fn block_number(&self) -> u64 {
+        let block_ms = self.block_time.as_millis().max(1);
+        (self.started.elapsed().as_millis() / block_ms) as u64
+    }
+
+    pub fn schedule(&mut self, order: Order) {
+        self.pending.push(order);
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Orders of `sender` waiting for the next batch.
+    pub fn queued<'a>(&'a self, sender: &'a str) -> impl Iterator<Item = &'a Order> {
+        self.pending
+            .iter()
+            .filter(move |order| order.sender.eq_ignore_ascii_case(sender))
+    }
+
+    /// Makes all pending orders due now.
+    pub fn expedite(&mut self) {
+        let now = Utc::now();
+        for order in self.pending.iter_mut() {
+            order.deadline = order.deadline.min(now);
+        }
+    }
+
+    /// Removes orders due by `now`, grouped into batches.
+    /// Tracked orders are never batched with transfers.
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<Batch> {
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|order| order.deadline <= now);
+        self.pending = pending;
+
+        let mut batches = BTreeMap::<(String, String, bool), Vec<Order>>::new();
+        for order in due {
+            batches
+                .entry((order.sender.clone(), order.recipient.clone(), order.tracked))
+                .or_default()
+                .push(order);
+        }
+        batches
+            .into_iter()
+            .map(|((sender, recipient, tracked), orders)| Batch {
+                sender,
+                recipient,
+                tracked,
+                orders,
+            })
+            .collect()
+    }
+
+    /// Returns orders of a failed batch to be sent with the next one.
+    pub fn requeue(&mut self, batch: Batch) {
+        self.pending.extend(batch.orders);
+    }
+
+    /// Moves funds of the batch, returning the batch back on insufficient funds.
+    pub fn settle(&mut self, batch: Batch) -> Result<Transaction, Batch> {
+        let amount = batch.amount();
+        if *self.account(&batch.sender) < amount {
+            return Err(batch);
+        }
+        *self.account(&batch.sender) -= &amount;
+        *self.account(&batch.recipient) += &amount;
+
+        let tx_id: [u8; 32] = rand::thread_rng().gen();
+        Ok(Transaction {
+            tx_id: format!("0x{}", hex::encode(tx_id)),
+            order_ids: batch.orders.into_iter().map(|order| order.id).collect(),
+            details: PaymentDetails {
+                recipient: batch.recipient,
+                sender: batch.sender,
+                amount,
+                date: Some(Utc::now()),
+            },
+        })
+    }
+
+    fn account(&mut self, address: &str) -> &mut BigDecimal {
+        self.balances
+            .entry(address.to_lowercase())
+            .or_insert_with(|| self.initial_balance.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(id: &str, sender: &str, recipient: &str, amount: u32, deadline: i64) -> Order {
+        Order {
+            id: id.to_string(),
+            sender: sender.to_string(),
+            recipient: recipient.to_string(),
+            amount: BigDecimal::from(amount),
+            deadline: DateTime::from_timestamp(deadline, 0).unwrap(),
+            tracked: true,
+        }
+    }
+
+    #[test]
+    fn batches_due_orders() {
+        let mut ledger = Ledger::new(BigDecimal::from(10), Duration::from_secs(1));
+        ledger.schedule(order("1", "0xa", "0xb", 1, 10));
+        ledger.schedule(order("2", "0xa", "0xc", 2, 10));
+        ledger.schedule(order("3", "0xa", "0xb", 3, 20));
+        ledger.schedule(order("4", "0xa", "0xb", 4, 30));
+
+        let now = DateTime::from_timestamp(20, 0).unwrap();
+        let batches = ledger.take_due(now);
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].recipient, "0xb");
+        assert_eq!(batches[0].amount(), BigDecimal::from(4));
+        assert_eq!(batches[1].recipient, "0xc");
+        assert_eq!(ledger.pending(), 1);
+        assert_eq!(ledger.queued("0xA").count(), 1);
+        assert_eq!(ledger.queued("0xd").count(), 0);
+
+        ledger.expedite();
+        assert_eq!(ledger.take_due(now).len(), 1);
+        assert_eq!(ledger.pending(), 0);
+    }
+
+    #[test]
+    fn batches_transfers_separately() {
+        let mut ledger = Ledger::new(BigDecimal::from(10), Duration::from_secs(1));
+        ledger.schedule(order("1", "0xa", "0xb", 1, 0));
+        ledger.schedule(Order {
+            tracked: false,
+            ..order("2", "0xa", "0xb", 2, 0)
+        });
+
+        let batches = ledger.take_due(DateTime::from_timestamp(0, 0).unwrap());
+        assert_eq!(batches.len(), 2);
+        assert!(!batches[0].tracked);
+        assert_eq!(batches[0].orders[0].id, "2");
+        assert!(batches[1].tracked);
+        assert_eq!(batches[1].orders[0].id, "1");
+    }
+
+    #[test]
+    fn settles_with_sufficient_funds() {
+        let mut ledger = Ledger::new(BigDecimal::from(10), Duration::from_secs(1));
+        ledger.schedule(order("1", "0xA", "0xb", 6, 0));
+        ledger.schedule(order("2", "0xa", "0xb", 3, 0));
+        ledger.schedule(order("3", "0xa", "0xc", 4, 0));
+
+        let now = DateTime::from_timestamp(0, 0).unwrap();
+        let mut batches = ledger.take_due(now);
+        assert_eq!(batches.len(), 3);
+
+        let tx = ledger.settle(batches.remove(0)).unwrap();
+        assert_eq!(tx.order_ids, vec!["1"]);
+        assert_eq!(ledger.balance("0xa"), BigDecimal::from(4));
+
+        let tx = ledger.settle(batches.remove(0)).unwrap();
+        assert_eq!(tx.details.amount, BigDecimal::from(3));
+        assert_eq!(ledger.balance("0xb"), BigDecimal::from(19));
+
+        let batch = ledger.settle(batches.remove(0)).unwrap_err();
+        ledger.requeue(batch);
+        assert_eq!(ledger.balance("0xa"), BigDecimal::from(1));
+        assert_eq!(ledger.pending(), 1);
+    }
+}
@@ -0,0 +1,25 @@
+/*
+    Simulated payment driver for load testing.
+
+    Mimics the full driver API without any chain: scheduled payments are batched,
+    sent after a delay, confirmed after a number of simulated blocks and may fail
+    at random, so payment flows can be tested without faucets.
+
+    This file only contains constants and imports.
+*/
+
+// Public
+pub const DRIVER_NAME: &str = "sim";
+
+pub const DEVNET_NETWORK: &str = "devnet";
+pub const DEVNET_TOKEN: &str = "tGLM";
+pub const DEVNET_PLATFORM: &str = "sim-devnet-tglm";
+
+pub use config::SimConfig;
+pub use service::SimService as PaymentDriverService;
+
+// Private
+mod config;
+mod driver;
+mod ledger;
+mod service;
@@ -0,0 +1,28 @@
+/*
+    The service that binds the simulated payment driver into yagna via GSB.
+*/
+
+// Workspace uses
+use ya_payment_driver::bus;
+
+// Local uses
+use crate::{config::SimConfig, driver::SimDriver};
+
+pub struct SimService;
+
+impl SimService {
+    pub async fn gsb<Context>(_context: &Context) -> anyhow::Result<()> {
+        log::debug!("Connecting SimService to gsb...");
+
+        let config = SimConfig::from_env();
+        log::warn!(
+            "Simulated payment driver enabled, payments are not sent to any chain. {config:?}"
+        );
+
+        let driver = SimDriver::new(config);
+        bus::bind_service(driver).await?;
+
+        log::debug!("Successfully connected SimService to gsb.");
+        Ok(())
+    }
+}
@@ -43,6 +43,13 @@ pub async fn register_in_payment_service() -> anyhow::Result<()> {
     let message = payment_srv::RegisterDriver {
         driver_name: DRIVER_NAME.to_string(),
         details,
+        capabilities: payment_srv::DriverCapabilities {
+            batch_transfers: false,
+            deposits: false,
+            partial_payments: true,
+            confirmation_latency: payment_srv::ConfirmationLatency::Instant,
+            fee_asset: payment_srv::FeeAsset::None,
+        },
     };
     service(payment_srv::BUS_ID).send(message).await?.unwrap(); // Unwrap on purpose because it's NoError
     log::debug!("Successfully registered driver in payment service.");
@@ -0,0 +1,272 @@
+/*
+    Per-network batching windows of scheduled payments.
+
+    Payments are gathered into multi-transfer transactions by the payment runtime.
+    A batching window caps how long a scheduled payment may wait for others,
+    so fee-vs-latency tradeoff can be tuned separately for every network.
+
+    Payments scheduled by the driver are tracked until confirmed, so that
+    the next batch can be previewed before it is sent.
+*/
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::time::Duration;
+
+use ethereum_types::U256;
+use ya_payment_driver::db::models::Network;
+
+use crate::erc20::ethereum::{GLM_POLYGON_GAS_LIMIT, GLM_TRANSFER_GAS};
+
+lazy_static::lazy_static! {
+    /// Rough gas cost of every additional transfer in a multi-transfer transaction.
+    pub static ref BATCH_TRANSFER_GAS: U256 = U256::from(35_000);
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchCycle {
+    /// Longest time a scheduled payment waits to be batched with others.
+    pub interval: Option<Duration>,
+    /// Maximum number of transfers in a single transaction, `None` when
+    /// the network has no multi-transfer contract.
+    pub max_batch_size: Option<u32>,
+    /// Gas limit of a transaction with a single transfer.
+    pub transfer_gas: U256,
+}
+
+impl Default for BatchCycle {
+    fn default() -> Self {
+        BatchCycle {
+            interval: None,
+            max_batch_size: None,
+            transfer_gas: *GLM_TRANSFER_GAS,
+        }
+    }
+}
+
+/// Gas limit of a single token transfer on `network`.
+pub fn transfer_gas(network: &str) -> U256 {
+    match Network::from_str(network) {
+        Ok(Network::Polygon | Network::Mumbai | Network::Amoy) => *GLM_POLYGON_GAS_LIMIT,
+        _ => *GLM_TRANSFER_GAS,
+    }
+}
+
+impl BatchCycle {
+    /// Payment is sent by its due date, or at the end of the batching window.
+    pub fn deadline(&self, due: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self
+            .interval
+            .and_then(|interval| chrono::Duration::from_std(interval).ok())
+        {
+            Some(interval) => due.min(now + interval),
+            None => due,
+        }
+    }
+
+    /// Number of transactions needed to send `transfers` payments.
+    pub fn transactions(&self, transfers: u32) -> u32 {
+        match self.max_batch_size {
+            Some(size) if size > 0 => transfers.div_ceil(size),
+            _ => transfers,
+        }
+    }
+
+    /// Estimated gas limit of all transactions sending `transfers` payments.
+    pub fn gas_limit(&self, transfers: u32) -> U256 {
+        let transactions = self.transactions(transfers);
+        let extra_transfers = transfers - transactions;
+        self.transfer_gas * transactions + *BATCH_TRANSFER_GAS * extra_transfers
+    }
+}
+
+/// Payment scheduled by the driver and not confirmed yet.
+#[derive(Clone, Debug)]
+pub struct QueuedPayment {
+    pub sender: String,
+    pub network: String,
+    pub receiver: String,
+    pub amount: BigDecimal,
+    pub scheduled: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+}
+
+/// Payments waiting for the next batch of a single account.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NextBatch {
+    pub payments: u32,
+    /// Payments to the same receiver are sent as a single transfer.
+    pub transfers: u32,
+    pub amount: BigDecimal,
+}
+
+/// Payments scheduled since the driver started, by payment id.
+///
+/// The payment runtime sends all pending payments of an account as soon as the
+/// deadline of any of them passes, so payments scheduled before the latest
+/// passed deadline are already being sent and aren't part of the next batch.
+#[derive(Debug, Default)]
+pub struct BatchQueue {
+    payments: HashMap<String, QueuedPayment>,
+    /// Latest passed deadline of every (sender, network).
+    sent_out: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl BatchQueue {
+    pub fn push(&mut self, payment_id: String, mut payment: QueuedPayment) {
+        payment.sender = payment.sender.to_lowercase();
+        self.payments.insert(payment_id, payment);
+    }
+
+    pub fn remove(&mut self, payment_id: &str) {
+        self.payments.remove(payment_id);
+    }
+
+    pub fn next_batch(&mut self, sender: &str, network: &str, now: DateTime<Utc>) -> NextBatch {
+        let sender = sender.to_lowercase();
+        let account = self
+            .payments
+            .values()
+            .filter(|payment| payment.sender == sender && payment.network == network)
+            .collect::<Vec<_>>();
+
+        let key = (sender.clone(), network.to_owned());
+        let passed = account
+            .iter()
+            .map(|payment| payment.deadline)
+            .filter(|deadline| *deadline <= now)
+            .max();
+        if let Some(passed) = passed {
+            let sent_out = self.sent_out.entry(key.clone()).or_insert(passed);
+            *sent_out = (*sent_out).max(passed);
+        }
+        let sent_out = self.sent_out.get(&key).copied();
+
+        let queued = account
+            .into_iter()
+            .filter(|payment| payment.deadline > now)
+            .filter(|payment| sent_out.map_or(true, |sent_out| payment.scheduled > sent_out))
+            .collect::<Vec<_>>();
+        NextBatch {
+            payments: queued.len() as u32,
+            transfers: queued
+                .iter()
+                .map(|payment| payment.receiver.to_lowercase())
+                .collect::<HashSet<_>>()
+                .len() as u32,
+            amount: queued
+                .iter()
+                .fold(BigDecimal::zero(), |total, payment| total + &payment.amount),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn deadline_capped_by_interval() {
+        let now = Utc::now();
+        let due = now + chrono::Duration::hours(1);
+        let cycle = BatchCycle {
+            interval: Some(Duration::from_secs(300)),
+            ..Default::default()
+        };
+        assert_eq!(cycle.deadline(due, now), now + chrono::Duration::minutes(5));
+        assert_eq!(BatchCycle::default().deadline(due, now), due);
+
+        let soon = now + chrono::Duration::minutes(1);
+        assert_eq!(cycle.deadline(soon, now), soon);
+    }
+
+    #[test]
+    fn gas_of_batches() {
+        let cycle = BatchCycle {
+            max_batch_size: Some(10),
+            transfer_gas: *GLM_POLYGON_GAS_LIMIT,
+            ..Default::default()
+        };
+        assert_eq!(cycle.transactions(0), 0);
+        assert_eq!(cycle.transactions(10), 1);
+        assert_eq!(cycle.transactions(11), 2);
+        assert_eq!(
+            cycle.gas_limit(11),
+            *GLM_POLYGON_GAS_LIMIT * 2 + *BATCH_TRANSFER_GAS * 9
+        );
+
+        let single = BatchCycle::default();
+        assert_eq!(single.transactions(3), 3);
+        assert_eq!(single.gas_limit(3), *GLM_TRANSFER_GAS * 3);
+    }
+
+    #[test]
+    fn gas_per_network() {
+        assert_eq!(transfer_gas("polygon"), *GLM_POLYGON_GAS_LIMIT);
+        assert_eq!(transfer_gas("amoy"), *GLM_POLYGON_GAS_LIMIT);
+        assert_eq!(transfer_gas("mainnet"), *GLM_TRANSFER_GAS);
+        assert_eq!(transfer_gas("holesky"), *GLM_TRANSFER_GAS);
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    fn payment(sender: &str, receiver: &str, scheduled: i64, deadline: i64) -> QueuedPayment {
+        QueuedPayment {
+            sender: sender.to_owned(),
+            network: "holesky".to_owned(),
+            receiver: receiver.to_owned(),
+            amount: BigDecimal::from(1),
+            scheduled: at(scheduled),
+            deadline: at(deadline),
+        }
+    }
+
+    #[test]
+    fn next_batch_consolidates_receivers() {
+        let mut queue = BatchQueue::default();
+        queue.push("a".into(), payment("0xSender", "0x01", 0, 100));
+        queue.push("b".into(), payment("0xsender", "0x01", 1, 100));
+        queue.push("c".into(), payment("0xsender", "0x02", 2, 100));
+        queue.push("d".into(), payment("0xother", "0x02", 2, 100));
+
+        let batch = queue.next_batch("0xSENDER", "holesky", at(10));
+        assert_eq!(
+            batch,
+            NextBatch {
+                payments: 3,
+                transfers: 2,
+                amount: BigDecimal::from(3),
+            }
+        );
+        assert_eq!(
+            queue.next_batch("0xsender", "polygon", at(10)),
+            NextBatch::default()
+        );
+
+        queue.remove("c");
+        assert_eq!(queue.next_batch("0xsender", "holesky", at(10)).transfers, 1);
+    }
+
+    #[test]
+    fn next_batch_skips_sent_payments() {
+        let mut queue = BatchQueue::default();
+        queue.push("a".into(), payment("0xsender", "0x01", 0, 50));
+        // Sent together with "a", though its own deadline is later
+        queue.push("b".into(), payment("0xsender", "0x02", 10, 100));
+        queue.push("c".into(), payment("0xsender", "0x03", 60, 150));
+
+        assert_eq!(queue.next_batch("0xsender", "holesky", at(20)).payments, 2);
+        let batch = queue.next_batch("0xsender", "holesky", at(70));
+        assert_eq!(batch.payments, 1);
+        assert_eq!(batch.amount, BigDecimal::from(1));
+
+        // Still in flight after the payment which triggered sending is confirmed
+        queue.remove("a");
+        assert_eq!(queue.next_batch("0xsender", "holesky", at(80)).payments, 1);
+    }
+}
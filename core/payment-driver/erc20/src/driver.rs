@@ -21,7 +21,7 @@ use num_bigint::BigInt;
 use std::collections::HashMap;
 use std::env;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::sync::mpsc::Receiver;
 use uuid::Uuid;
@@ -33,15 +33,21 @@ use ya_payment_driver::driver::IdentityError;
 
 use ya_payment_driver::{
     bus,
-    driver::{async_trait, BigDecimal, IdentityEvent, Network as NetworkConfig, PaymentDriver},
+    driver::{
+        async_trait, BigDecimal, ConfirmationLatency, DriverCapabilities, FeeAsset, IdentityEvent,
+        Network as NetworkConfig, NodeId, PaymentDriver,
+    },
     model::*,
 };
 
 // Local uses
+use crate::batching::{BatchCycle, BatchQueue, QueuedPayment};
 use crate::erc20::utils;
 use crate::erc20::utils::{big_dec_to_u256, u256_to_big_dec};
+use crate::erc20::{eth_utils, ethereum};
 use crate::network::platform_to_currency;
 use crate::signer::IdentitySigner;
+use crate::sweep;
 use crate::{driver::PaymentDetails, network, HOLESKY_NETWORK};
 use crate::{network::SUPPORTED_NETWORKS, DRIVER_NAME};
 
@@ -49,11 +55,25 @@ mod cli;
 
 pub struct Erc20Driver {
     payment_runtime: PaymentRuntime,
+    batch_cycles: HashMap<String, BatchCycle>,
+    /// Configured max fee per gas (in wei) of every network.
+    max_fees_per_gas: HashMap<String, U256>,
+    batch_queue: Mutex<BatchQueue>,
 }
 
 impl Erc20Driver {
-    pub fn new(payment_runtime: PaymentRuntime, recv: Receiver<DriverEvent>) -> Arc<Self> {
-        let this = Arc::new(Self { payment_runtime });
+    pub fn new(
+        payment_runtime: PaymentRuntime,
+        recv: Receiver<DriverEvent>,
+        batch_cycles: HashMap<String, BatchCycle>,
+        max_fees_per_gas: HashMap<String, U256>,
+    ) -> Arc<Self> {
+        let this = Arc::new(Self {
+            payment_runtime,
+            batch_cycles,
+            max_fees_per_gas,
+            batch_queue: Default::default(),
+        });
 
         let this_ = Arc::clone(&this);
         tokio::task::spawn_local(Self::payment_confirm_job(this_, recv));
@@ -123,6 +143,7 @@ impl Erc20Driver {
         network: &str,
         deadline: Option<DateTime<Utc>>,
         deposit_id: Option<Deposit>,
+        tx_type: TransferType,
     ) -> Result<String, GenericError> {
         self.is_account_active(sender).await?;
         let sender = H160::from_str(sender)
@@ -153,7 +174,7 @@ impl Erc20Driver {
                 network: network.to_string(),
                 from: sender,
                 receiver,
-                tx_type: TransferType::Token,
+                tx_type,
                 amount,
                 payment_id: payment_id.clone(),
                 deadline,
@@ -169,6 +190,9 @@ impl Erc20Driver {
         while let Some(event) = events.recv().await {
             match &event.content {
                 DriverEventContent::TransferFinished(transfer_finished) => {
+                    if let Some(payment_id) = &transfer_finished.token_transfer_dao.payment_id {
+                        this.batch_queue.lock().unwrap().remove(payment_id);
+                    }
                     match this
                         .confirm_payments(
                             &transfer_finished.token_transfer_dao,
@@ -856,6 +880,16 @@ impl PaymentDriver for Erc20Driver {
         false
     }
 
+    fn get_capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities {
+            batch_transfers: true,
+            deposits: true,
+            partial_payments: true,
+            confirmation_latency: ConfirmationLatency::Minutes,
+            fee_asset: FeeAsset::Native,
+        }
+    }
+
     async fn init(&self, _caller: String, msg: Init) -> Result<Ack, GenericError> {
         cli::init(self, msg).await?;
         Ok(Ack {})
@@ -1085,6 +1119,7 @@ impl PaymentDriver for Erc20Driver {
             &network,
             Some(Utc::now()),
             None,
+            TransferType::Token,
         )
         .await
     }
@@ -1103,16 +1138,37 @@ impl PaymentDriver for Erc20Driver {
         )))?;
 
         let transfer_margin = Duration::minutes(2);
-
-        self.do_transfer(
-            &msg.sender(),
-            &msg.recipient(),
-            &msg.amount(),
-            network,
-            Some(msg.due_date() - transfer_margin),
-            msg.deposit_id(),
-        )
-        .await
+        let now = Utc::now();
+        let deadline = self
+            .batch_cycles
+            .get(network)
+            .cloned()
+            .unwrap_or_default()
+            .deadline(msg.due_date() - transfer_margin, now);
+
+        let payment_id = self
+            .do_transfer(
+                &msg.sender(),
+                &msg.recipient(),
+                &msg.amount(),
+                network,
+                Some(deadline),
+                msg.deposit_id(),
+                TransferType::Token,
+            )
+            .await?;
+        self.batch_queue.lock().unwrap().push(
+            payment_id.clone(),
+            QueuedPayment {
+                sender: msg.sender(),
+                network: network.to_string(),
+                receiver: msg.recipient(),
+                amount: msg.amount(),
+                scheduled: now,
+                deadline,
+            },
+        );
+        Ok(payment_id)
     }
 
     async fn verify_payment(
@@ -1212,6 +1268,258 @@ impl PaymentDriver for Erc20Driver {
         Ok(())
     }
 
+    async fn sweep(&self, _caller: String, msg: Sweep) -> Result<SweepResult, GenericError> {
+        let network = msg
+            .network
+            .clone()
+            .unwrap_or_else(|| self.get_default_network());
+        let sender = H160::from_str(&msg.sender)
+            .map_err(|err| GenericError::new(format!("Error when parsing sender {err:?}")))?;
+        let balance = self
+            .payment_runtime
+            .get_token_balance(network.clone(), sender, None)
+            .await
+            .map_err(|e| GenericError::new(e.to_string()))?;
+        let token_balance = balance
+            .token_balance
+            .ok_or_else(|| GenericError::new("Error getting token balance"))?;
+        let gas_balance = balance
+            .gas_balance
+            .ok_or_else(|| GenericError::new("Error getting gas balance"))?;
+
+        let cycle = self.batch_cycles.get(&network).cloned().unwrap_or_default();
+        let max_fee_per_gas = self
+            .max_fees_per_gas
+            .get(&network)
+            .cloned()
+            .ok_or_else(|| {
+                GenericError::new(format!("Missing max fee per gas for network {network}"))
+            })?;
+        let amounts = sweep::sweep_amounts(
+            &sweep::Balance {
+                token: token_balance,
+                gas: gas_balance,
+            },
+            &sweep::Pending {
+                amount: big_dec_to_u256(&msg.pending_amount)?,
+                transfers: msg.pending_transfers,
+            },
+            &cycle,
+            max_fee_per_gas,
+            msg.native,
+        )
+        .map_err(GenericError::new)?;
+
+        // Transfers go through the same sender queue as payments, so nonces don't conflict
+        // with transactions which are already scheduled.
+        let mut transfer_ids = Vec::new();
+        let token_amount = u256_to_big_dec(amounts.token)?;
+        if !amounts.token.is_zero() {
+            let transfer_id = self
+                .do_transfer(
+                    &msg.sender,
+                    &msg.to,
+                    &token_amount,
+                    &network,
+                    Some(Utc::now()),
+                    None,
+                    TransferType::Token,
+                )
+                .await?;
+            transfer_ids.push(transfer_id);
+        }
+
+        let native_amount = amounts.native.map(u256_to_big_dec).transpose()?;
+        if let Some(native_amount) = &native_amount {
+            let transfer_id = self
+                .do_transfer(
+                    &msg.sender,
+                    &msg.to,
+                    native_amount,
+                    &network,
+                    Some(Utc::now()),
+                    None,
+                    TransferType::Gas,
+                )
+                .await?;
+            transfer_ids.push(transfer_id);
+        }
+
+        log::info!(
+            "Sweeping {} GLM{} from {} to {} on {}",
+            token_amount,
+            native_amount
+                .as_ref()
+                .map(|amount| format!(" and {} native token", amount))
+                .unwrap_or_default(),
+            msg.sender,
+            msg.to,
+            network
+        );
+        Ok(SweepResult {
+            token_amount,
+            native_amount,
+            transfer_ids,
+        })
+    }
+
+    async fn get_allowance(
+        &self,
+        _caller: String,
+        msg: GetAllowance,
+    ) -> Result<BigDecimal, GenericError> {
+        let network = network::network_like_to_network(msg.network);
+        let allowance = ethereum::get_glm_allowance(
+            utils::str_to_addr(&msg.owner)?,
+            utils::str_to_addr(&msg.spender)?,
+            network,
+        )
+        .await?;
+        u256_to_big_dec(allowance)
+    }
+
+    async fn sign_permit(&self, caller: String, msg: SignPermit) -> Result<Permit, GenericError> {
+        let network = network::network_like_to_network(msg.network);
+        let owner = utils::str_to_addr(&msg.owner)?;
+        check_caller(&caller, owner)?;
+        let spender = utils::str_to_addr(&msg.spender)?;
+        let value = big_dec_to_u256(&msg.amount)?;
+        let deadline = msg.deadline.timestamp();
+        if deadline <= Utc::now().timestamp() {
+            return Err(GenericError::new("Permit deadline is in the past"));
+        }
+
+        let nonce = ethereum::get_permit_nonce(owner, network).await?;
+        let eip712_message = ethereum::encode_permit_to_eip712(
+            owner,
+            spender,
+            value,
+            nonce,
+            U256::from(deadline),
+            network,
+        )
+        .await?;
+        let signature =
+            ethereum::sign_hash_of_data(owner, eth_utils::keccak256_hash(&eip712_message)).await?;
+        if signature.len() != 65 {
+            return Err(GenericError::new("Invalid permit signature length"));
+        }
+
+        // Identity signatures carry recovery id, while contracts expect 27 or 28.
+        let v = match signature[0] {
+            v @ 0..=1 => v + 27,
+            v => v,
+        };
+        Ok(Permit {
+            token: format!("{:#x}", ethereum::glm_contract_address(network)),
+            owner: format!("{:#x}", owner),
+            spender: format!("{:#x}", spender),
+            value: value.to_string(),
+            nonce: nonce.to_string(),
+            deadline,
+            v,
+            r: format!("0x{}", hex::encode(&signature[1..33])),
+            s: format!("0x{}", hex::encode(&signature[33..65])),
+        })
+    }
+
+    async fn approve(&self, caller: String, msg: Approve) -> Result<String, GenericError> {
+        let network = network::network_like_to_network(msg.network);
+        let owner = utils::str_to_addr(&msg.owner)?;
+        check_caller(&caller, owner)?;
+
+        let tx_hash = ethereum::approve_glm(
+            owner,
+            utils::str_to_addr(&msg.spender)?,
+            big_dec_to_u256(&msg.amount)?,
+            network,
+        )
+        .await?;
+        Ok(format!("{:#x}", tx_hash))
+    }
+
+    async fn submit_permit(
+        &self,
+        caller: String,
+        msg: SubmitPermit,
+    ) -> Result<String, GenericError> {
+        let network = network::network_like_to_network(msg.network);
+        let sender = utils::str_to_addr(&msg.sender)?;
+        check_caller(&caller, sender)?;
+
+        let permit = msg.permit;
+        if utils::str_to_addr(&permit.token)? != ethereum::glm_contract_address(network) {
+            return Err(GenericError::new(format!(
+                "Permit for token {} can't be used on {network:?}",
+                permit.token
+            )));
+        }
+        if permit.deadline <= Utc::now().timestamp() {
+            return Err(GenericError::new("Permit has expired"));
+        }
+        let parse_u256 = |value: &str| {
+            U256::from_dec_str(value)
+                .map_err(|e| GenericError::new(format!("Invalid permit value {value}: {e}")))
+        };
+        let parse_h256 = |value: &str| {
+            H256::from_str(value.trim_start_matches("0x"))
+                .map_err(|e| GenericError::new(format!("Invalid permit signature {value}: {e}")))
+        };
+
+        let tx_hash = ethereum::submit_permit(
+            sender,
+            utils::str_to_addr(&permit.owner)?,
+            utils::str_to_addr(&permit.spender)?,
+            parse_u256(&permit.value)?,
+            U256::from(permit.deadline),
+            (permit.v, parse_h256(&permit.r)?, parse_h256(&permit.s)?),
+            network,
+        )
+        .await?;
+        Ok(format!("{:#x}", tx_hash))
+    }
+
+    async fn estimate_batch(
+        &self,
+        _caller: String,
+        msg: EstimateBatch,
+    ) -> Result<BatchEstimate, GenericError> {
+        let network = msg.network.unwrap_or_else(|| self.get_default_network());
+        let platform = SUPPORTED_NETWORKS
+            .get(&network)
+            .and_then(|n| n.tokens.get(&n.default_token))
+            .ok_or_else(|| GenericError::new(format!("Unsupported network: {network}")))?;
+        let (fee_currency, _) = platform_to_currency(platform.clone())?;
+        self.is_account_active(&msg.sender).await?;
+
+        let cycle = self.batch_cycles.get(&network).cloned().unwrap_or_default();
+        let batch = self
+            .batch_queue
+            .lock()
+            .unwrap()
+            .next_batch(&msg.sender, &network, Utc::now());
+        let gas_limit = cycle.gas_limit(batch.transfers);
+        let gas_price =
+            ethereum::get_gas_price(network::network_like_to_network(Some(network.clone())))
+                .await?;
+        let fee = u256_to_big_dec(gas_price * gas_limit)?;
+        let gas_price = u256_to_big_dec(gas_price)? * BigDecimal::from(1_000_000_000u64);
+
+        Ok(BatchEstimate {
+            network,
+            batch_interval: cycle.interval,
+            max_batch_size: cycle.max_batch_size,
+            payments: batch.payments,
+            transfers: batch.transfers,
+            amount: batch.amount,
+            transactions: cycle.transactions(batch.transfers),
+            gas_limit: gas_limit.low_u64(),
+            gas_price,
+            fee,
+            fee_currency,
+        })
+    }
+
     async fn status(
         &self,
         _caller: String,
@@ -1225,3 +1533,26 @@ impl PaymentDriver for Erc20Driver {
         Ok(())
     }
 }
+
+/// Messages acting on behalf of an account must be sent by its identity.
+fn check_caller(caller: &str, account: H160) -> Result<(), GenericError> {
+    match caller.parse::<NodeId>() {
+        Ok(node_id) if node_id == NodeId::from(account.as_ref()) => Ok(()),
+        _ => Err(GenericError::new(format!(
+            "{caller} is not allowed to act on behalf of {account:#x}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_caller() {
+        let account = H160::from_str("0x7e5f4552091a69125d5dfcb7b8c2659029395bdf").unwrap();
+        assert!(check_caller("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf", account).is_ok());
+        assert!(check_caller("0x2b5ad5c4795c026514f8317c7a215e218dccd6cf", account).is_err());
+        assert!(check_caller("local", account).is_err());
+    }
+}
@@ -74,6 +74,8 @@ lazy_static! {
     pub static ref GLM_FAUCET_GAS: U256 = U256::from(90_000);
     pub static ref GLM_TRANSFER_GAS: U256 = U256::from(55_000);
     pub static ref GLM_POLYGON_GAS_LIMIT: U256 = U256::from(100_000);
+    pub static ref NATIVE_TRANSFER_GAS: U256 = U256::from(21_000);
+    pub static ref GLM_APPROVE_GAS: U256 = U256::from(100_000);
     static ref WEB3_CLIENT_MAP: Arc<RwLock<HashMap<String, Web3<Http>>>> = Default::default();
 }
 const CREATE_FAUCET_FUNCTION: &str = "create";
@@ -81,6 +83,11 @@ const BALANCE_ERC20_FUNCTION: &str = "balanceOf";
 const TRANSFER_ERC20_FUNCTION: &str = "transfer";
 const GET_DOMAIN_SEPARATOR_FUNCTION: &str = "getDomainSeperator";
 const GET_NONCE_FUNCTION: &str = "getNonce";
+const ALLOWANCE_ERC20_FUNCTION: &str = "allowance";
+const PERMIT_DOMAIN_SEPARATOR_FUNCTION: &str = "DOMAIN_SEPARATOR";
+const PERMIT_NONCES_FUNCTION: &str = "nonces";
+const APPROVE_ERC20_FUNCTION: &str = "approve";
+const PERMIT_FUNCTION: &str = "permit";
 
 pub fn get_polygon_starting_price() -> f64 {
     match get_polygon_priority() {
@@ -166,6 +173,97 @@ async fn get_glm_balance_with(
         .map_err(Into::into)
 }
 
+pub fn glm_contract_address(network: Network) -> H160 {
+    get_env(network).glm_contract_address
+}
+
+pub async fn get_glm_allowance(
+    owner: H160,
+    spender: H160,
+    network: Network,
+) -> Result<U256, GenericError> {
+    let env = get_env(network);
+    with_clients(network, |client| async move {
+        let glm_contract = prepare_erc20_contract(&client, &env)?;
+        glm_contract
+            .query(
+                ALLOWANCE_ERC20_FUNCTION,
+                (owner, spender),
+                None,
+                Options::default(),
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    })
+    .await
+}
+
+/// Sends GLM contract `approve` transaction, paid by the `owner`.
+pub async fn approve_glm(
+    owner: H160,
+    spender: H160,
+    value: U256,
+    network: Network,
+) -> Result<H256, GenericError> {
+    let env = get_env(network);
+    with_clients(network, |client| async move {
+        let glm_contract = prepare_erc20_contract(&client, &env)?;
+        let data =
+            eth_utils::contract_encode(&glm_contract, APPROVE_ERC20_FUNCTION, (spender, value))
+                .map_err(GenericError::new)?;
+        send_contract_tx_with(client, owner, glm_contract.address(), data, network).await
+    })
+    .await
+}
+
+/// Sends GLM contract `permit` transaction with the owner's signature, paid by the `sender`.
+pub async fn submit_permit(
+    sender: H160,
+    owner: H160,
+    spender: H160,
+    value: U256,
+    deadline: U256,
+    signature: (u8, H256, H256),
+    network: Network,
+) -> Result<H256, GenericError> {
+    let env = get_env(network);
+    let (v, r, s) = signature;
+    with_clients(network, |client| async move {
+        let permit_contract = prepare_permit_contract(&client, &env)?;
+        let data = eth_utils::contract_encode(
+            &permit_contract,
+            PERMIT_FUNCTION,
+            (owner, spender, value, deadline, v, r, s),
+        )
+        .map_err(GenericError::new)?;
+        send_contract_tx_with(client, sender, permit_contract.address(), data, network).await
+    })
+    .await
+}
+
+async fn send_contract_tx_with(
+    client: Web3<Http>,
+    sender: H160,
+    contract: H160,
+    data: Vec<u8>,
+    network: Network,
+) -> Result<H256, ClientError> {
+    let nonce = get_next_nonce_pending_with(client.clone(), sender).await?;
+    let gas_price = client.eth().gas_price().await?;
+    let tx = YagnaRawTransaction {
+        nonce,
+        to: Some(contract),
+        value: U256::zero(),
+        gas_price,
+        gas: *GLM_APPROVE_GAS,
+        data,
+    };
+    let signature = sign_raw_transfer_transaction(sender, network, &tx).await?;
+    let signed = eth_utils::encode_signed_tx(&tx, signature, network as u64);
+    send_tx_with(client, signed).await
+}
+
 pub async fn get_balance(address: H160, network: Network) -> Result<U256, GenericError> {
     with_clients(network, |client| get_balance_with(address, client)).await
 }
@@ -178,6 +276,13 @@ async fn get_balance_with(address: H160, client: Web3<Http>) -> Result<U256, Cli
         .map_err(Into::into)
 }
 
+pub async fn get_gas_price(network: Network) -> Result<U256, GenericError> {
+    with_clients(network, |client| async move {
+        client.eth().gas_price().await.map_err(Into::into)
+    })
+    .await
+}
+
 pub async fn get_next_nonce_pending(address: H160, network: Network) -> Result<U256, GenericError> {
     with_clients(network, |client| {
         get_next_nonce_pending_with(client, address)
@@ -658,6 +763,17 @@ fn prepare_eip712_contract(
     )
 }
 
+fn prepare_permit_contract(
+    ethereum_client: &Web3<Http>,
+    env: &config::EnvConfiguration,
+) -> Result<Contract<Http>, GenericError> {
+    prepare_contract(
+        ethereum_client,
+        env.glm_contract_address,
+        include_bytes!("../contracts/permit.json"),
+    )
+}
+
 fn prepare_meta_transaction_contract(
     ethereum_client: &Web3<Http>,
     env: &config::EnvConfiguration,
@@ -814,6 +930,82 @@ pub async fn encode_meta_transaction_to_eip712(
     .await
 }
 
+pub async fn get_permit_nonce(owner: H160, network: Network) -> Result<U256, GenericError> {
+    let env = get_env(network);
+    with_clients(network, |client| async move {
+        let permit_contract = prepare_permit_contract(&client, &env)?;
+        permit_contract
+            .query(
+                PERMIT_NONCES_FUNCTION,
+                (owner,),
+                None,
+                Options::default(),
+                None,
+            )
+            .await
+            .map_err(Into::into)
+    })
+    .await
+}
+
+/// Creates EIP712 message for token's EIP-2612 `permit` function.
+/// Signed message allows `spender` to transfer `value` from `owner` account until `deadline`.
+pub async fn encode_permit_to_eip712(
+    owner: H160,
+    spender: H160,
+    value: U256,
+    nonce: U256,
+    deadline: U256,
+    network: Network,
+) -> Result<Vec<u8>, GenericError> {
+    info!("Creating permit for owner {owner:02X?}, spender {spender:02X?}, value {value:?}, nonce {nonce:?}, network {network:?}");
+
+    let env = get_env(network);
+
+    with_clients(network, |client| async move {
+        let permit_contract = prepare_permit_contract(&client, &env)?;
+        let domain_separator: Vec<u8> = permit_contract
+            .query(
+                PERMIT_DOMAIN_SEPARATOR_FUNCTION,
+                (),
+                None,
+                Options::default(),
+                None,
+            )
+            .await
+            .map_err(|e| GenericError::new(format!("Unable to query contract, reason: {e}")))?;
+
+        let eip712_message =
+            permit_eip712_message(&domain_separator, owner, spender, value, nonce, deadline);
+        debug!("full eip712 message: {eip712_message:02X?}");
+
+        Ok(eip712_message)
+    })
+    .await
+}
+
+fn permit_eip712_message(
+    domain_separator: &[u8],
+    owner: H160,
+    spender: H160,
+    value: U256,
+    nonce: U256,
+    deadline: U256,
+) -> Vec<u8> {
+    const PERMIT_SIGNATURE: &str =
+        "Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)";
+    const MAGIC: [u8; 2] = [0x19, 0x1];
+
+    let encoded_data = ethabi::encode(&(owner, spender, value, nonce, deadline).into_tokens());
+    let type_hash = keccak256_hash(PERMIT_SIGNATURE.as_bytes());
+    let hash_struct = keccak256_hash(&[type_hash, encoded_data].concat());
+
+    let mut eip712_message = Vec::from(MAGIC);
+    eip712_message.extend_from_slice(domain_separator);
+    eip712_message.extend_from_slice(&hash_struct);
+    eip712_message
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -847,4 +1039,33 @@ mod tests {
         assert_eq!(hex::encode(transfer_abi), "a9059cbb000000000000000000000000d4ea255b238e214a9a0e5656ec36fe27cd14adac00000000000000000000000000000000000000000000000000000b2fd1217800");
         assert_eq!(hex::encode(encoded_meta_transfer), "1901804e8c6f5926bd56018ff8fa95b472e09d8b3612bf1b892f2d5e5f4365a5e95e7bc74d293cbaa554151b05ad958d04d7c19f2552a6315fe4a99f6aef60a887fd");
     }
+
+    #[test]
+    fn test_permit_eip712_message() {
+        // Domain separator of the EIP-712 specification example
+        let domain_separator =
+            hex::decode("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+                .unwrap();
+        let owner = H160::from_str("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf").unwrap();
+        let spender = H160::from_str("0x2B5AD5c4795c026514f8317c7a215E218DcCD6cF").unwrap();
+        let value = U256::exp10(18);
+
+        let message = permit_eip712_message(
+            &domain_separator,
+            owner,
+            spender,
+            value,
+            U256::zero(),
+            U256::from(1_700_000_000u64),
+        );
+
+        assert_eq!(
+            hex::encode(&message[34..]),
+            "a290823bc633c73eb80e5557ef755d8ffa98e61d8b702ceb447f5b88e5f8b208"
+        );
+        assert_eq!(
+            hex::encode(keccak256_hash(&message)),
+            "016a0e4613326b987d3979291431cada083bf60236e0d5e6c5f279d44711d90b"
+        );
+    }
 }
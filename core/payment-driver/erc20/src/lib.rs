@@ -7,6 +7,9 @@
 // Public
 pub const DRIVER_NAME: &str = "erc20";
 
+/// Default driver configuration, overridden by `config-payments.toml` in the data dir.
+pub const DEFAULT_CONFIG: &str = include_str!("../config-payments.toml");
+
 pub const RINKEBY_NETWORK: &str = "rinkeby";
 pub const RINKEBY_TOKEN: &str = "tGLM";
 pub const RINKEBY_PLATFORM: &str = "erc20-rinkeby-tglm";
@@ -61,9 +64,11 @@ pub use service::Erc20Service as PaymentDriverService;
 #[macro_use]
 extern crate log;
 
+mod batching;
 mod dao;
 mod driver;
 pub mod erc20;
 mod network;
 mod service;
 mod signer;
+mod sweep;
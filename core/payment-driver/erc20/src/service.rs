@@ -2,7 +2,9 @@
     The service that binds this payment driver into yagna via GSB.
 */
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{env, path::PathBuf, str::FromStr};
 // External crates
 use erc20_payment_lib::config;
@@ -11,13 +13,14 @@ use erc20_payment_lib::config::{
     WrapperContractSettings,
 };
 use erc20_payment_lib::runtime::{PaymentRuntime, PaymentRuntimeArgs};
+use erc20_payment_lib::utils::DecimalConvExt;
 use ethereum_types::H160;
 
 // Workspace uses
 use ya_payment_driver::bus;
 
 // Local uses
-use crate::{driver::Erc20Driver, signer::IdentitySigner};
+use crate::{batching, batching::BatchCycle, driver::Erc20Driver, signer::IdentitySigner};
 
 pub struct Erc20Service;
 
@@ -41,7 +44,7 @@ impl Erc20Service {
                 skip_service_loop: false,
             };
 
-            let mut config = config::Config::load_from_str(include_str!("../config-payments.toml"))
+            let mut config = config::Config::load_from_str(crate::DEFAULT_CONFIG)
                 .expect("Default erc20 config doesn't parse");
 
             // Load config from file if it exists giving the possibility of overwriting the default config
@@ -84,6 +87,8 @@ impl Erc20Service {
                 }
             }
 
+            let mut batch_cycles = HashMap::new();
+            let mut max_fees_per_gas = HashMap::new();
             for (network, chain) in &mut config.chain {
                 let prefix = network.to_ascii_uppercase();
                 let symbol = chain.token.symbol.to_ascii_uppercase();
@@ -96,6 +101,11 @@ impl Erc20Service {
                 let multi_payment_addr_env = format!("{prefix}_MULTI_PAYMENT_CONTRACT_ADDRESS");
                 let lock_payment_addr_env = format!("{prefix}_LOCK_PAYMENT_CONTRACT_ADDRESS");
                 let confirmations_env = format!("ERC20_{prefix}_REQUIRED_CONFIRMATIONS");
+                let rpc_max_timeout_env = format!("{prefix}_RPC_MAX_TIMEOUT_MS");
+                let rpc_max_errors_env = format!("{prefix}_RPC_MAX_CONSECUTIVE_ERRORS");
+                let rpc_verify_interval_env = format!("{prefix}_RPC_VERIFY_INTERVAL_SECS");
+                let batch_interval_env = format!("{prefix}_BATCH_INTERVAL_SECS");
+                let max_batch_size_env = format!("{prefix}_MAX_BATCH_SIZE");
 
                 if let Ok(addr) = env::var(&rpc_env) {
                     chain.rpc_endpoints = addr
@@ -120,6 +130,28 @@ impl Erc20Service {
                         &chain.rpc_endpoints
                     )
                 }
+                // Endpoints are scored by the payment runtime, which switches to the next one
+                // after timeouts or consecutive errors. Allow tuning it for all endpoints at once.
+                if let Some(timeout) = parse_env(&rpc_max_timeout_env) {
+                    log::info!("{network} rpc endpoints max timeout set to {timeout}ms");
+                    for rpc in chain.rpc_endpoints.iter_mut() {
+                        rpc.max_timeout_ms = Some(timeout);
+                    }
+                }
+                if let Some(max_errors) = parse_env(&rpc_max_errors_env) {
+                    log::info!(
+                        "{network} rpc endpoints max consecutive errors set to {max_errors}"
+                    );
+                    for rpc in chain.rpc_endpoints.iter_mut() {
+                        rpc.max_consecutive_errors = Some(max_errors);
+                    }
+                }
+                if let Some(interval) = parse_env(&rpc_verify_interval_env) {
+                    log::info!("{network} rpc endpoints verify interval set to {interval}s");
+                    for rpc in chain.rpc_endpoints.iter_mut() {
+                        rpc.verify_interval_secs = Some(interval);
+                    }
+                }
                 if let Ok(fee) = env::var(&priority_fee_env) {
                     match rust_decimal::Decimal::from_str(&fee) {
                         Ok(fee) => {
@@ -201,6 +233,46 @@ impl Erc20Service {
                         }
                     };
                 }
+                // Multi payment contract may be set by the env above, so batch size is applied after it.
+                if let Some(size) = parse_env::<u32>(&max_batch_size_env) {
+                    match chain.multi_contract.as_mut() {
+                        Some(multi_contract) if size > 0 => {
+                            log::info!("{network} max batch size set to {size}");
+                            multi_contract.max_at_once = size as _;
+                        }
+                        Some(_) => log::warn!("Value 0 for {max_batch_size_env} is not valid"),
+                        None => log::warn!(
+                            "{network} has no multi payment contract, {max_batch_size_env} is ignored"
+                        ),
+                    }
+                }
+                let batch_interval = parse_env::<u64>(&batch_interval_env).map(Duration::from_secs);
+                if let Some(interval) = batch_interval {
+                    log::info!("{network} batching window set to {}s", interval.as_secs());
+                }
+                batch_cycles.insert(
+                    network.clone(),
+                    BatchCycle {
+                        interval: batch_interval,
+                        max_batch_size: chain
+                            .multi_contract
+                            .as_ref()
+                            .map(|multi_contract| multi_contract.max_at_once as u32),
+                        transfer_gas: batching::transfer_gas(network),
+                    },
+                );
+                // Configured in Gwei
+                match (chain.max_fee_per_gas / rust_decimal::Decimal::from(1_000_000_000u64))
+                    .to_u256_from_eth()
+                {
+                    Ok(max_fee) => {
+                        max_fees_per_gas.insert(network.clone(), max_fee);
+                    }
+                    Err(e) => log::warn!(
+                        "Invalid {network} max fee per gas {}: {e}",
+                        chain.max_fee_per_gas
+                    ),
+                }
                 if let Ok(wrapper_contract_addr) = env::var(&wrapper_contract_env) {
                     match H160::from_str(&wrapper_contract_addr) {
                         Ok(parsed) => {
@@ -251,7 +323,7 @@ impl Erc20Service {
             //    .await?;
 
             log::debug!("Bind erc20 driver");
-            let driver = Erc20Driver::new(pr, recv);
+            let driver = Erc20Driver::new(pr, recv, batch_cycles, max_fees_per_gas);
             driver.load_active_accounts().await;
             bus::bind_service(driver).await?;
 
@@ -260,3 +332,18 @@ impl Erc20Service {
         }
     }
 }
+
+fn parse_env<T>(name: &str) -> Option<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = env::var(name).ok()?;
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            log::warn!("Value {value} for {name} is not valid: {e}");
+            None
+        }
+    }
+}
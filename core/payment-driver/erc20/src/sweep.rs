@@ -0,0 +1,148 @@
+/*
+    Amounts transferred by `yagna payment sweep`.
+
+    Payments scheduled, but not yet confirmed, are sent from the same account
+    later on, so their tokens and gas are left on the account. Gas is reserved
+    at the configured max fee per gas, which caps the price of every
+    transaction sent by the payment runtime.
+*/
+
+use ethereum_types::U256;
+
+use crate::batching::BatchCycle;
+use crate::erc20::ethereum::NATIVE_TRANSFER_GAS;
+
+#[derive(Clone, Debug, Default)]
+pub struct Balance {
+    pub token: U256,
+    pub gas: U256,
+}
+
+/// Payments from the account, which are scheduled, but not confirmed yet.
+#[derive(Clone, Debug, Default)]
+pub struct Pending {
+    pub amount: U256,
+    pub transfers: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SweepAmounts {
+    pub token: U256,
+    /// `None` if native token isn't swept.
+    pub native: Option<U256>,
+}
+
+/// Computes amounts of both transfers up front, so that either all of them
+/// are scheduled, or none.
+pub fn sweep_amounts(
+    balance: &Balance,
+    pending: &Pending,
+    cycle: &BatchCycle,
+    max_fee_per_gas: U256,
+    native: bool,
+) -> Result<SweepAmounts, String> {
+    if balance.token < pending.amount {
+        return Err(format!(
+            "Token balance {} is lower than {} needed by scheduled payments",
+            balance.token, pending.amount
+        ));
+    }
+    let token = balance.token - pending.amount;
+
+    // Scheduled payments and the token transfer, plus the native transfer
+    let token_transfers = pending.transfers + u32::from(!token.is_zero());
+    let mut gas = cycle.gas_limit(token_transfers);
+    if native {
+        gas += *NATIVE_TRANSFER_GAS;
+    }
+    let reserve = max_fee_per_gas * gas;
+    if balance.gas < reserve {
+        return Err(format!(
+            "Native token balance {} is not enough to cover gas reserve {}",
+            balance.gas, reserve
+        ));
+    }
+
+    let native = match native {
+        true => Some(balance.gas - reserve).filter(|amount| !amount.is_zero()),
+        false => None,
+    };
+    if token.is_zero() && native.is_none() {
+        return Err("Nothing to sweep".to_string());
+    }
+    Ok(SweepAmounts { token, native })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batching::BATCH_TRANSFER_GAS;
+    use crate::erc20::ethereum::GLM_POLYGON_GAS_LIMIT;
+
+    const MAX_FEE: u64 = 10;
+
+    fn balance(token: u64, gas: u64) -> Balance {
+        Balance {
+            token: token.into(),
+            gas: gas.into(),
+        }
+    }
+
+    fn pending(amount: u64, transfers: u32) -> Pending {
+        Pending {
+            amount: amount.into(),
+            transfers,
+        }
+    }
+
+    fn cycle() -> BatchCycle {
+        BatchCycle {
+            max_batch_size: Some(10),
+            transfer_gas: *GLM_POLYGON_GAS_LIMIT,
+            ..Default::default()
+        }
+    }
+
+    fn sweep(balance: Balance, pending: Pending, native: bool) -> Result<SweepAmounts, String> {
+        sweep_amounts(&balance, &pending, &cycle(), MAX_FEE.into(), native)
+    }
+
+    #[test]
+    fn leaves_pending_payments() {
+        let gas = (*GLM_POLYGON_GAS_LIMIT + *BATCH_TRANSFER_GAS * 2).as_u64() * MAX_FEE;
+        let amounts = sweep(balance(1000, gas), pending(300, 2), false).unwrap();
+        assert_eq!(amounts.token, U256::from(700));
+        assert_eq!(amounts.native, None);
+
+        // Not enough gas for scheduled payments and the sweep
+        assert!(sweep(balance(1000, gas - 1), pending(300, 2), false).is_err());
+        // Scheduled payments exceed balance
+        assert!(sweep(balance(200, gas), pending(300, 2), false).is_err());
+    }
+
+    #[test]
+    fn reserves_gas_at_max_fee() {
+        let reserve = (*GLM_POLYGON_GAS_LIMIT + *NATIVE_TRANSFER_GAS).as_u64() * MAX_FEE;
+        let amounts = sweep(balance(1000, reserve + 5), Pending::default(), true).unwrap();
+        assert_eq!(amounts.token, U256::from(1000));
+        assert_eq!(amounts.native, Some(U256::from(5)));
+
+        // Only native token
+        let reserve = NATIVE_TRANSFER_GAS.as_u64() * MAX_FEE;
+        let amounts = sweep(balance(0, reserve + 5), Pending::default(), true).unwrap();
+        assert_eq!(amounts.token, U256::zero());
+        assert_eq!(amounts.native, Some(U256::from(5)));
+    }
+
+    #[test]
+    fn fails_without_scheduling_anything() {
+        let reserve = (*GLM_POLYGON_GAS_LIMIT + *NATIVE_TRANSFER_GAS).as_u64() * MAX_FEE;
+        // Token transfer would fit, but native one wouldn't
+        assert!(sweep(balance(1000, reserve - 1), Pending::default(), true).is_err());
+        assert_eq!(
+            sweep(balance(0, 0), Pending::default(), false),
+            Err("Nothing to sweep".to_string())
+        );
+        assert!(sweep(balance(500, 10_000_000), pending(500, 1), false).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(clippy::type_complexity)]
 
 use actix_web::web::Data;
-use actix_web::{middleware, App, HttpServer, Scope};
+use actix_web::{middleware, App, HttpServer};
 use chrono::Utc;
 use ethsign::keyfile::Bytes;
 use ethsign::{KeyFile, Protected, SecretKey};
@@ -29,6 +29,7 @@ use ya_payment::{migrations, utils, PaymentService};
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::auth::dummy::DummyAuth;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::ApiScope;
 use ya_service_api_web::rest_api_addr;
 use ya_service_api_web::scope::ExtendableScope;
 use ya_service_bus::typed as bus;
@@ -369,14 +370,17 @@ async fn main() -> anyhow::Result<()> {
             role: "".to_string(),
         };
 
-        let provider_api_scope = Scope::new(&format!("provider{}", PAYMENT_API_PATH))
+        let provider_api_scope = ApiScope::new("payment", &format!("provider{}", PAYMENT_API_PATH))
             .app_data(Data::new(db.clone()))
             .extend(ya_payment::api::api_scope)
+            .into_scope()
             .wrap(DummyAuth::new(provider_identity));
-        let requestor_api_scope = Scope::new(&format!("requestor{}", PAYMENT_API_PATH))
-            .app_data(Data::new(db.clone()))
-            .extend(ya_payment::api::api_scope)
-            .wrap(DummyAuth::new(requestor_identity));
+        let requestor_api_scope =
+            ApiScope::new("payment", &format!("requestor{}", PAYMENT_API_PATH))
+                .app_data(Data::new(db.clone()))
+                .extend(ya_payment::api::api_scope)
+                .into_scope()
+                .wrap(DummyAuth::new(requestor_identity));
         App::new()
             .wrap(middleware::Logger::default())
             .service(provider_api_scope)
@@ -386,7 +390,7 @@ async fn main() -> anyhow::Result<()> {
     .run()
     .await?;
 
-    PaymentService::shut_down().await;
+    PaymentService::shut_down().await?;
 
     Ok(())
 }
@@ -0,0 +1 @@
+DROP TABLE pay_invoice_dispute;
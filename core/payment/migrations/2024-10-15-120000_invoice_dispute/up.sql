@@ -0,0 +1,11 @@
+CREATE TABLE pay_invoice_dispute(
+    invoice_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    reason TEXT NOT NULL,
+    message TEXT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    corrected_invoice_id VARCHAR(50) NULL,
+    resolved_ts DATETIME NULL,
+    PRIMARY KEY(owner_id, invoice_id),
+    FOREIGN KEY(owner_id, invoice_id) REFERENCES pay_invoice (owner_id, id)
+);
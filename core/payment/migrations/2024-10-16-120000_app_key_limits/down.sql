@@ -0,0 +1,3 @@
+DROP INDEX pay_allocation_app_key_idx;
+DROP TABLE pay_allocation_app_key;
+DROP TABLE pay_app_key_limit;
@@ -0,0 +1,16 @@
+CREATE TABLE pay_app_key_limit(
+    owner_id VARCHAR(50) NOT NULL,
+    app_key VARCHAR(50) NOT NULL,
+    daily_limit VARCHAR(32) NULL,
+    total_limit VARCHAR(32) NULL,
+    agreement_limit VARCHAR(32) NULL,
+    PRIMARY KEY(owner_id, app_key)
+);
+
+CREATE TABLE pay_allocation_app_key(
+    allocation_id VARCHAR(50) NOT NULL PRIMARY KEY,
+    app_key VARCHAR(50) NOT NULL,
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation (id)
+);
+
+CREATE INDEX pay_allocation_app_key_idx ON pay_allocation_app_key (app_key);
@@ -0,0 +1,7 @@
+DROP TABLE pay_agreement_payment_archive;
+DROP TABLE pay_activity_payment_archive;
+DROP TABLE pay_payment_archive;
+DROP TABLE pay_debit_note_event_archive;
+DROP TABLE pay_debit_note_archive;
+DROP TABLE pay_invoice_event_archive;
+DROP TABLE pay_invoice_archive;
@@ -0,0 +1,82 @@
+-- Archive tables hold settled documents and payments moved out of working tables.
+-- They have no foreign keys, so archived rows don't constrain working tables.
+CREATE TABLE pay_invoice_archive(
+    id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    role CHAR(1) NOT NULL,
+    agreement_id VARCHAR(50) NOT NULL,
+    status VARCHAR(50) NOT NULL,
+    timestamp DATETIME NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    payment_due_date DATETIME NOT NULL,
+    archived_ts DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    PRIMARY KEY(owner_id, id)
+);
+
+CREATE TABLE pay_invoice_event_archive(
+    invoice_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    timestamp DATETIME NOT NULL,
+    details TEXT NULL,
+    PRIMARY KEY(owner_id, invoice_id, event_type)
+);
+
+CREATE TABLE pay_debit_note_archive(
+    id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    role CHAR(1) NOT NULL,
+    previous_debit_note_id VARCHAR(50) NULL,
+    activity_id VARCHAR(50) NOT NULL,
+    status VARCHAR(50) NOT NULL,
+    timestamp DATETIME NOT NULL,
+    total_amount_due VARCHAR(32) NOT NULL,
+    usage_counter_vector BLOB NULL,
+    payment_due_date DATETIME NULL,
+    archived_ts DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    PRIMARY KEY(owner_id, id)
+);
+
+CREATE TABLE pay_debit_note_event_archive(
+    debit_note_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    timestamp DATETIME NOT NULL,
+    details TEXT NULL,
+    PRIMARY KEY(owner_id, debit_note_id, event_type)
+);
+
+CREATE TABLE pay_payment_archive(
+    id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    peer_id VARCHAR(50) NOT NULL,
+    payee_addr VARCHAR(50) NOT NULL,
+    payer_addr VARCHAR(50) NOT NULL,
+    payment_platform VARCHAR(50) NOT NULL,
+    role CHAR(1) NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    timestamp DATETIME NOT NULL,
+    details BLOB NOT NULL,
+    signature BLOB NULL,
+    signed_bytes BLOB NULL,
+    archived_ts DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    PRIMARY KEY(owner_id, id)
+);
+
+CREATE TABLE pay_activity_payment_archive(
+    payment_id VARCHAR(50) NOT NULL,
+    activity_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    allocation_id VARCHAR(50) NULL,
+    PRIMARY KEY(owner_id, payment_id, activity_id)
+);
+
+CREATE TABLE pay_agreement_payment_archive(
+    payment_id VARCHAR(50) NOT NULL,
+    agreement_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    amount VARCHAR(32) NOT NULL,
+    allocation_id VARCHAR(50) NULL,
+    PRIMARY KEY(owner_id, payment_id, agreement_id)
+);
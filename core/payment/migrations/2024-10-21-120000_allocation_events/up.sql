@@ -0,0 +1,11 @@
+CREATE TABLE pay_allocation_event(
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    allocation_id VARCHAR(50) NOT NULL,
+    owner_id VARCHAR(50) NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    details TEXT NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW'))
+);
+
+CREATE INDEX pay_allocation_event_owner_ts ON pay_allocation_event (owner_id, timestamp);
+CREATE INDEX pay_allocation_event_allocation ON pay_allocation_event (allocation_id);
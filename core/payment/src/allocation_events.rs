@@ -0,0 +1,247 @@
+//! Allocation events pushed to GSB subscribers.
+//!
+//! DAOs store an event whenever an amount is reserved, spent or released from an allocation,
+//! and wake up the job, which forwards events stored since its last run to endpoints
+//! subscribed with `SubscribeAllocationEvents`. The same events are served by the REST API.
+use futures::future;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{
+    AllocationEvent, GenericError, SubscribeAllocationEvents, UnsubscribeAllocationEvents, BUS_ID,
+};
+use ya_persistence::executor::DbExecutor;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::dao::AllocationEventDao;
+
+/// Upper bound of delay between storing an event and sending it out.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Subscribers not responding in time are unsubscribed, so they don't hold up the others.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_EVENTS_PER_QUERY: u32 = 100;
+
+lazy_static::lazy_static! {
+    pub static ref ALLOCATION_EVENTS_NOTIFY: Notify = Notify::new();
+}
+
+#[derive(Clone)]
+struct Subscriber {
+    endpoint: String,
+    owner_id: NodeId,
+}
+
+#[derive(Default)]
+struct Subscriptions {
+    subscribers: HashMap<u64, Subscriber>,
+    last_id: u64,
+}
+
+impl Subscriptions {
+    fn subscribe(&mut self, msg: SubscribeAllocationEvents) -> u64 {
+        let id = self.last_id;
+        self.last_id += 1;
+        self.subscribers.insert(
+            id,
+            Subscriber {
+                endpoint: msg.endpoint,
+                owner_id: msg.owner_id,
+            },
+        );
+        id
+    }
+
+    fn matching(&self, event: &AllocationEvent) -> Vec<(u64, Subscriber)> {
+        self.subscribers
+            .iter()
+            .filter(|(_, s)| s.owner_id == event.owner_id)
+            .map(|(id, s)| (*id, s.clone()))
+            .collect()
+    }
+}
+
+pub fn bind_service(db: DbExecutor) {
+    let subscriptions = Rc::new(RefCell::new(Subscriptions::default()));
+
+    {
+        let subscriptions = subscriptions.clone();
+        let _ = bus::bind_with_caller(BUS_ID, move |caller, msg: SubscribeAllocationEvents| {
+            if !is_owner(&caller, msg.owner_id) {
+                return future::err(GenericError::new(format!(
+                    "{caller} is not allowed to subscribe to allocation events of {}",
+                    msg.owner_id
+                )));
+            }
+            log::debug!("Allocation events subscribed by {}", msg.endpoint);
+            let id = subscriptions.borrow_mut().subscribe(msg);
+            future::ok(id)
+        });
+    }
+    {
+        let subscriptions = subscriptions.clone();
+        let _ = bus::bind_with_caller(BUS_ID, move |caller, msg: UnsubscribeAllocationEvents| {
+            let mut subscriptions = subscriptions.borrow_mut();
+            let owned = subscriptions
+                .subscribers
+                .get(&msg.subscription_id)
+                .map_or(false, |subscriber| is_owner(&caller, subscriber.owner_id));
+            let removed = if owned {
+                subscriptions.subscribers.remove(&msg.subscription_id)
+            } else {
+                None
+            };
+            future::ready(match removed {
+                Some(_) => Ok(()),
+                None => Err(GenericError::new(format!(
+                    "Unknown subscription {}",
+                    msg.subscription_id
+                ))),
+            })
+        });
+    }
+
+    send_events_job(db, subscriptions);
+}
+
+fn is_owner(caller: &str, owner_id: NodeId) -> bool {
+    caller.parse::<NodeId>().ok() == Some(owner_id)
+}
+
+fn send_events_job(db: DbExecutor, subscriptions: Rc<RefCell<Subscriptions>>) {
+    tokio::task::spawn_local(async move {
+        let dao = db.as_dao::<AllocationEventDao>();
+        let mut last_id = loop {
+            match dao.last_id().await {
+                Ok(id) => break id,
+                Err(e) => log::error!("Unable to query allocation events: {e}"),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => { },
+                _ = ALLOCATION_EVENTS_NOTIFY.notified() => { },
+            }
+
+            // Events stored without subscribers are only available through the REST API.
+            if subscriptions.borrow().subscribers.is_empty() {
+                match dao.last_id().await {
+                    Ok(id) => last_id = id,
+                    Err(e) => log::error!("Unable to query allocation events: {e}"),
+                }
+                continue;
+            }
+
+            loop {
+                let events = match dao.get_after_id(last_id, MAX_EVENTS_PER_QUERY).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        log::error!("Unable to query allocation events: {e}");
+                        break;
+                    }
+                };
+                if events.is_empty() {
+                    break;
+                }
+                for (id, event) in events {
+                    last_id = Some(id);
+                    send_event(&subscriptions, event).await;
+                }
+            }
+        }
+    });
+}
+
+async fn send_event(subscriptions: &Rc<RefCell<Subscriptions>>, event: AllocationEvent) {
+    let subscribers = subscriptions.borrow().matching(&event);
+    let sends = subscribers.into_iter().map(|(id, subscriber)| {
+        let event = event.clone();
+        async move {
+            let call = bus::service(&subscriber.endpoint).call(event);
+            match tokio::time::timeout(SEND_TIMEOUT, call).await {
+                Ok(Ok(Ok(()))) => (),
+                Ok(Ok(Err(e))) => {
+                    log::debug!("Allocation event rejected by {}: {e}", subscriber.endpoint)
+                }
+                Ok(Err(e)) => {
+                    log::warn!(
+                        "Unable to send allocation event to {}, unsubscribing: {e}",
+                        subscriber.endpoint
+                    );
+                    subscriptions.borrow_mut().subscribers.remove(&id);
+                }
+                Err(_) => {
+                    log::warn!(
+                        "Allocation event not received by {} in time, unsubscribing",
+                        subscriber.endpoint
+                    );
+                    subscriptions.borrow_mut().subscribers.remove(&id);
+                }
+            }
+        }
+    });
+    future::join_all(sends).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+    use chrono::Utc;
+    use ya_core_model::payment::local::AllocationEventType;
+
+    #[test]
+    fn test_subscribers_receive_only_own_events() {
+        let owner: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let other: NodeId = "0x0000000000000000000000000000000000000002"
+            .parse()
+            .unwrap();
+
+        let mut subscriptions = Subscriptions::default();
+        let id = subscriptions.subscribe(SubscribeAllocationEvents {
+            endpoint: "/local/owner".to_string(),
+            owner_id: owner,
+        });
+        subscriptions.subscribe(SubscribeAllocationEvents {
+            endpoint: "/local/other".to_string(),
+            owner_id: other,
+        });
+
+        let event = AllocationEvent {
+            allocation_id: "allocation".to_string(),
+            owner_id: owner,
+            event_date: Utc::now(),
+            event_type: AllocationEventType::AllocationReleasedEvent {
+                remaining_amount: BigDecimal::from(1),
+            },
+        };
+        let matching = subscriptions.matching(&event);
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].0, id);
+        assert_eq!(matching[0].1.endpoint, "/local/owner");
+    }
+
+    #[test]
+    fn test_only_owner_is_allowed() {
+        let owner: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+
+        assert!(is_owner(
+            "0x0000000000000000000000000000000000000001",
+            owner
+        ));
+        assert!(!is_owner(
+            "0x0000000000000000000000000000000000000002",
+            owner
+        ));
+        assert!(!is_owner("local", owner));
+    }
+}
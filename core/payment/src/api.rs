@@ -2,8 +2,11 @@ use actix_web::web::{self, Data};
 use actix_web::Scope;
 use ya_client_model::payment::PAYMENT_API_PATH;
 use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::openapi::{ApiScope, ScopeSpec};
 use ya_service_api_web::scope::ExtendableScope;
 
+use crate::Config;
+
 mod accounts;
 pub mod allocations;
 mod debit_notes;
@@ -12,7 +15,7 @@ mod payments;
 
 mod guard;
 
-pub fn api_scope(scope: Scope) -> Scope {
+pub fn api_scope(scope: ApiScope) -> ApiScope {
     scope
         .app_data(web::Data::new(guard::AgreementLock::arc()))
         .extend(accounts::register_endpoints)
@@ -23,9 +26,16 @@ pub fn api_scope(scope: Scope) -> Scope {
 }
 
 pub fn web_scope(db: &DbExecutor) -> Scope {
-    Scope::new(PAYMENT_API_PATH)
+    // Invalid configuration is reported earlier, when binding GSB services.
+    let settlement = Config::from_env()
+        .map(|config| config.debit_note_settlement)
+        .unwrap_or_default();
+    api_scope(ApiScope::new("payment", PAYMENT_API_PATH))
+        .into_scope()
         .app_data(Data::new(db.clone()))
-        .service(api_scope(Scope::new("")))
-    // TODO: TEST
-    // Scope::new(PAYMENT_API_PATH).extend(api_scope).app_data(Data::new(db.clone()))
+        .app_data(Data::new(settlement))
+}
+
+pub fn api_spec() -> ScopeSpec {
+    api_scope(ApiScope::new("payment", PAYMENT_API_PATH)).into_spec()
 }
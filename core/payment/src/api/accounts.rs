@@ -1,10 +1,11 @@
 // Extrnal crates
-use actix_web::{HttpResponse, Scope};
+use actix_web::HttpResponse;
 
 // Workspace uses
 use ya_client_model::payment::*;
 use ya_core_model::payment::local::{GetAccounts, BUS_ID as LOCAL_SERVICE};
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
@@ -13,13 +14,20 @@ use crate::utils::*;
 use actix_web::web::Data;
 use ya_persistence::executor::DbExecutor;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .service(get_provider_accounts)
-        .service(get_requestor_accounts)
+        .route(
+            Operation::get("/providerAccounts", "getProviderAccounts")
+                .response(Schema::array_of_model::<Account>()),
+            get_provider_accounts,
+        )
+        .route(
+            Operation::get("/requestorAccounts", "getRequestorAccounts")
+                .response(Schema::array_of_model::<Account>()),
+            get_requestor_accounts,
+        )
 }
 
-#[actix_web::get("/providerAccounts")]
 async fn get_provider_accounts(id: Identity) -> HttpResponse {
     let node_id = id.identity.to_string();
     let all_accounts = match bus::service(LOCAL_SERVICE).send(GetAccounts {}).await {
@@ -35,7 +43,6 @@ async fn get_provider_accounts(id: Identity) -> HttpResponse {
     response::ok(recv_accounts)
 }
 
-#[actix_web::get("/requestorAccounts")]
 async fn get_requestor_accounts(db: Data<DbExecutor>, id: Identity) -> HttpResponse {
     let node_id = id.identity.to_string();
     let all_accounts = match bus::service(LOCAL_SERVICE).send(GetAccounts {}).await {
@@ -1,9 +1,10 @@
 use std::time::Duration;
 // External crates
-use actix_web::web::{delete, get, post, put, Data, Json, Path, Query};
-use actix_web::{HttpResponse, Scope};
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::HttpResponse;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use metrics::counter;
 use serde_json::value::Value::Null;
 use ya_client_model::NodeId;
 
@@ -12,19 +13,22 @@ use ya_agreement_utils::{ClauseOperator, ConstraintKey, Constraints};
 use ya_client_model::payment::allocation::PaymentPlatformEnum;
 use ya_client_model::payment::*;
 use ya_core_model::payment::local::{
-    DriverName, NetworkName, ReleaseDeposit, ValidateAllocation, ValidateAllocationError,
-    BUS_ID as LOCAL_SERVICE,
+    AllocationEvent, AppKeyLimitStatus, DriverName, NetworkName, ReleaseDeposit,
+    ValidateAllocation, ValidateAllocationError, BUS_ID as LOCAL_SERVICE,
 };
 use ya_core_model::payment::RpcMessageError;
 use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::error::ApiError;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
 use crate::accounts::{init_account, Account};
 use crate::dao::*;
 use crate::error::Error;
-use crate::utils::response;
+use crate::limits::{self, LimitError};
+use crate::utils::{listen_for_events, response};
 
 const DEFAULT_TESTNET_NETWORK: NetworkName = NetworkName::Holesky;
 const DEFAULT_MAINNET_NETWORK: NetworkName = NetworkName::Polygon;
@@ -36,17 +40,53 @@ mod token_name;
 
 use platform_triple::PaymentPlatformTriple;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .route("/allocations", post().to(create_allocation))
-        .route("/allocations", get().to(get_allocations))
-        .route("/allocations/{allocation_id}", get().to(get_allocation))
-        .route("/allocations/{allocation_id}", put().to(amend_allocation))
         .route(
-            "/allocations/{allocation_id}",
-            delete().to(release_allocation),
+            Operation::post("/allocations", "createAllocation")
+                .body(Schema::model::<NewAllocation>())
+                .response(Schema::model::<Allocation>()),
+            create_allocation,
+        )
+        .route(
+            Operation::get("/allocations", "getAllocations")
+                .response(Schema::array_of_model::<Allocation>()),
+            get_allocations,
+        )
+        .route(
+            Operation::get("/allocations/{allocation_id}", "getAllocation")
+                .response(Schema::model::<Allocation>()),
+            get_allocation,
+        )
+        .route(
+            Operation::get("/allocations/{allocation_id}/events", "getAllocationEvents")
+                .response(Schema::of::<Vec<AllocationEvent>>()),
+            get_allocation_events,
+        )
+        .route(
+            Operation::get("/allocationEvents", "getAllAllocationEvents")
+                .response(Schema::of::<Vec<AllocationEvent>>()),
+            get_all_allocation_events,
+        )
+        .route(
+            Operation::put("/allocations/{allocation_id}", "amendAllocation")
+                .body(Schema::model::<AllocationUpdate>()),
+            amend_allocation,
+        )
+        .route(
+            Operation::delete("/allocations/{allocation_id}", "releaseAllocation"),
+            release_allocation,
+        )
+        .route(
+            Operation::get("/demandDecorations", "getDemandDecorations")
+                .response(Schema::model::<MarketDecoration>()),
+            get_demand_decorations,
+        )
+        .route(
+            Operation::get("/appKeyLimits", "getAppKeyLimits")
+                .response(Schema::of::<Vec<AppKeyLimitStatus>>()),
+            get_app_key_limits,
         )
-        .route("/demandDecorations", get().to(get_demand_decorations))
 }
 
 async fn create_allocation(
@@ -156,11 +196,12 @@ async fn create_allocation(
     let dao = db.as_dao::<AllocationDao>();
 
     match dao
-        .create(
+        .create_for_app_key(
             allocation.clone(),
             node_id,
             payment_triple.to_string(),
             address,
+            id.name,
         )
         .await
     {
@@ -184,7 +225,7 @@ async fn create_allocation(
             Ok(AllocationStatus::Gone) => api_error::server_error(&allocation, &"Database Error"),
             Err(e) => api_error::server_error(&allocation, &e.to_string()),
         },
-        Err(e) => response::server_error(&e),
+        Err(e) => limit_error(e),
     }
 }
 
@@ -217,15 +258,54 @@ async fn get_allocation(
 
     match dao.get(allocation_id.clone(), node_id).await {
         Ok(AllocationStatus::Active(allocation)) => response::ok(allocation),
-        Ok(AllocationStatus::Gone) => response::gone(&format!(
-            "Allocation {} has been already released",
-            allocation_id
-        )),
+        Ok(AllocationStatus::Gone) => response::allocation_released(&allocation_id),
         Ok(AllocationStatus::NotFound) => response::not_found(),
         Err(e) => response::server_error(&e),
     }
 }
 
+async fn get_allocation_events(
+    db: Data<DbExecutor>,
+    path: Path<params::AllocationId>,
+    query: Query<params::EventParams>,
+    id: Identity,
+) -> HttpResponse {
+    allocation_events(db, Some(path.allocation_id.clone()), query, id).await
+}
+
+async fn get_all_allocation_events(
+    db: Data<DbExecutor>,
+    query: Query<params::EventParams>,
+    id: Identity,
+) -> HttpResponse {
+    allocation_events(db, None, query, id).await
+}
+
+async fn allocation_events(
+    db: Data<DbExecutor>,
+    allocation_id: Option<String>,
+    query: Query<params::EventParams>,
+    id: Identity,
+) -> HttpResponse {
+    counter!("payment.allocations.events.query", 1);
+
+    let node_id = id.identity;
+    let timeout_secs = query.timeout.unwrap_or(params::DEFAULT_EVENT_TIMEOUT);
+    let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
+    let max_events = query.max_events;
+
+    let dao: AllocationEventDao = db.as_dao();
+    let getter = || async {
+        dao.get_for_node_id(node_id, allocation_id.clone(), after_timestamp, max_events)
+            .await
+    };
+
+    match listen_for_events(getter, timeout_secs).await {
+        Ok(events) => response::ok(events),
+        Err(e) => response::server_error(&e),
+    }
+}
+
 fn amend_allocation_fields(
     old_allocation: Allocation,
     update: AllocationUpdate,
@@ -278,11 +358,7 @@ async fn amend_allocation(
 
     let current_allocation = match dao.get(allocation_id.clone(), node_id).await {
         Ok(AllocationStatus::Active(allocation)) => allocation,
-        Ok(AllocationStatus::Gone) => {
-            return response::gone(&format!(
-                "Allocation {allocation_id} has been already released",
-            ))
-        }
+        Ok(AllocationStatus::Gone) => return response::allocation_released(&allocation_id),
         Ok(AllocationStatus::NotFound) => return response::not_found(),
         Err(e) => return response::server_error(&e),
     };
@@ -341,7 +417,10 @@ async fn amend_allocation(
         Err(e) => return api_error::server_error(&allocation_update, &e.to_string()),
     }
 
-    match dao.replace(amended_allocation, node_id).await {
+    match dao
+        .replace_for_app_key(amended_allocation, node_id, id.name.clone())
+        .await
+    {
         Ok(true) => {}
         Ok(false) => {
             return api_error::server_error(
@@ -349,6 +428,7 @@ async fn amend_allocation(
                 &"Allocation not present despite preconditions being already ensured",
             );
         }
+        Err(e @ LimitError::Exceeded { .. }) => return limit_error(e),
         Err(e) => return api_error::server_error(&allocation_update, &e.to_string()),
     }
 
@@ -385,10 +465,7 @@ async fn release_allocation(
             }
         }
         Ok(AllocationReleaseStatus::NotFound) => response::not_found(),
-        Ok(AllocationReleaseStatus::Gone) => response::gone(&format!(
-            "Allocation {} has been already released",
-            allocation_id
-        )),
+        Ok(AllocationReleaseStatus::Gone) => response::allocation_released(&allocation_id),
         Err(e) => response::server_error(&e),
     }
 }
@@ -511,3 +588,22 @@ pub async fn forced_release_allocation(
         _ => (),
     }
 }
+
+/// Limits and allocated amounts of the app-key used for the call.
+async fn get_app_key_limits(db: Data<DbExecutor>, id: Identity) -> HttpResponse {
+    match limits::list(&db, id.identity, Some(id.name)).await {
+        Ok(mut status) if !status.is_empty() => response::ok(status.remove(0)),
+        Ok(_) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+pub(super) fn limit_error(e: LimitError) -> HttpResponse {
+    match e {
+        LimitError::Exceeded { .. } => {
+            log::warn!("{e}");
+            response::error(ApiError::bad_request("APP_KEY_LIMIT_EXCEEDED", e))
+        }
+        LimitError::Db(e) => response::server_error(&e),
+    }
+}
@@ -1,8 +1,9 @@
 use std::borrow::Cow;
 use std::sync::Arc;
 // Extrnal crates
-use actix_web::web::{get, post, Data, Json, Path, Query};
-use actix_web::{HttpResponse, Scope};
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::HttpResponse;
+use chrono::Utc;
 use serde_json::value::Value::Null;
 use std::time::Instant;
 
@@ -17,46 +18,74 @@ use ya_core_model::payment::RpcMessageError;
 use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_persistence::types::Role;
+use ya_service_api_web::error::ApiError;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::allocations::limit_error;
 use super::guard::AgreementLock;
+use crate::config::DebitNoteSettlementConfig;
 use crate::dao::*;
 use crate::error::{DbError, Error};
+use crate::limits;
 use crate::payment_sync::SYNC_NOTIFS_NOTIFY;
 use crate::utils::provider::get_agreement_for_activity;
 use crate::utils::*;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
         // Shared
-        .route("/debitNotes", get().to(get_debit_notes))
-        .route("/debitNotes/{debit_note_id}", get().to(get_debit_note))
         .route(
-            "/debitNotes/{debit_note_id}/payments",
-            get().to(get_debit_note_payments),
+            Operation::get("/debitNotes", "getDebitNotes")
+                .response(Schema::array_of_model::<DebitNote>()),
+            get_debit_notes,
+        )
+        .route(
+            Operation::get("/debitNotes/{debit_note_id}", "getDebitNote")
+                .response(Schema::model::<DebitNote>()),
+            get_debit_note,
+        )
+        .route(
+            Operation::get(
+                "/debitNotes/{debit_note_id}/payments",
+                "getPaymentsForDebitNote",
+            )
+            .response(Schema::array_of_model::<Payment>()),
+            get_debit_note_payments,
+        )
+        .route(
+            Operation::get("/debitNoteEvents", "getDebitNoteEvents")
+                .response(Schema::array_of_model::<DebitNoteEvent>()),
+            get_debit_note_events,
         )
-        .route("/debitNoteEvents", get().to(get_debit_note_events))
         // Provider
-        .route("/debitNotes", post().to(issue_debit_note))
         .route(
-            "/debitNotes/{debit_note_id}/send",
-            post().to(send_debit_note),
+            Operation::post("/debitNotes", "issueDebitNote")
+                .body(Schema::model::<NewDebitNote>())
+                .response(Schema::model::<DebitNote>()),
+            issue_debit_note,
         )
         .route(
-            "/debitNotes/{debit_note_id}/cancel",
-            post().to(cancel_debit_note),
+            Operation::post("/debitNotes/{debit_note_id}/send", "sendDebitNote"),
+            send_debit_note,
+        )
+        .route(
+            Operation::post("/debitNotes/{debit_note_id}/cancel", "cancelDebitNote"),
+            cancel_debit_note,
         )
         // Requestor
         .route(
-            "/debitNotes/{debit_note_id}/accept",
-            post().to(accept_debit_note),
+            Operation::post("/debitNotes/{debit_note_id}/accept", "acceptDebitNote")
+                .body(Schema::model::<Acceptance>()),
+            accept_debit_note,
         )
         .route(
-            "/debitNotes/{debit_note_id}/reject",
-            post().to(reject_debit_note),
+            Operation::post("/debitNotes/{debit_note_id}/reject", "rejectDebitNote")
+                .body(Schema::model::<Rejection>()),
+            reject_debit_note,
         )
 }
 
@@ -292,6 +321,7 @@ async fn cancel_debit_note(
 async fn accept_debit_note(
     db: Data<DbExecutor>,
     agreement_lock: Data<Arc<AgreementLock>>,
+    settlement: Data<DebitNoteSettlementConfig>,
     path: Path<params::DebitNoteId>,
     query: Query<params::Timeout>,
     body: Json<Acceptance>,
@@ -311,7 +341,7 @@ async fn accept_debit_note(
     let sync_dao: SyncNotifsDao = db.as_dao();
 
     log::trace!("Querying DB for Debit Note [{}]", debit_note_id);
-    let debit_note: DebitNote = match dao.get(debit_note_id.clone(), node_id).await {
+    let mut debit_note: DebitNote = match dao.get(debit_note_id.clone(), node_id).await {
         Ok(Some(debit_note)) => debit_note,
         Ok(None) => return response::not_found(),
         Err(e) => return response::server_error(&e),
@@ -394,6 +424,17 @@ async fn accept_debit_note(
         }
         Err(e) => return response::server_error(&e),
     };
+    if let Err(e) = limits::check_agreement(
+        &db,
+        node_id,
+        &id.name,
+        &activity.agreement_id,
+        &(&debit_note.total_amount_due - &activity.total_amount_accepted.0),
+    )
+    .await
+    {
+        return limit_error(e);
+    }
     let amount_to_pay = &debit_note.total_amount_due - &activity.total_amount_scheduled.0;
 
     log::trace!(
@@ -407,14 +448,15 @@ async fn accept_debit_note(
         .await
     {
         Ok(AllocationStatus::Active(allocation)) => allocation,
-        Ok(AllocationStatus::Gone) => {
-            return response::gone(&format!(
-                "Allocation {} has been already released",
-                allocation_id
-            ))
-        }
+        Ok(AllocationStatus::Gone) => return response::allocation_released(&allocation_id),
         Ok(AllocationStatus::NotFound) => {
-            return response::bad_request(&format!("Allocation {} not found", allocation_id))
+            return response::error(
+                ApiError::bad_request(
+                    "ALLOCATION_NOT_FOUND",
+                    format!("Allocation {} not found", allocation_id),
+                )
+                .with_detail("allocationId", &allocation_id),
+            )
         }
         Err(e) => return response::server_error(&e),
     };
@@ -423,7 +465,36 @@ async fn accept_debit_note(
             "Not enough funds. Allocated: {} Needed: {}",
             allocation.remaining_amount, amount_to_pay
         );
-        return response::bad_request(&msg);
+        return response::error(
+            ApiError::bad_request("INSUFFICIENT_FUNDS", msg)
+                .with_detail("remainingAmount", &allocation.remaining_amount)
+                .with_detail("amountDue", &amount_to_pay),
+        );
+    }
+
+    // Debit Notes without payment due date are paid together with Invoice,
+    // unless settlement policy decides to pay for the Activity earlier.
+    if debit_note.payment_due_date.is_none() && settlement.is_enabled() {
+        let last_settlement = match dao
+            .last_settlement_timestamp(activity_id.clone(), node_id)
+            .await
+        {
+            Ok(timestamp) => timestamp,
+            Err(e) => return response::server_error(&e),
+        };
+        let now = Utc::now();
+        let since_last_settlement = last_settlement
+            .map(|timestamp| now.naive_utc() - timestamp)
+            .unwrap_or_else(chrono::Duration::zero);
+        if settlement.should_settle(&amount_to_pay, since_last_settlement) {
+            log::debug!(
+                "Settling Activity [{}] with Debit Note [{}]. Amount: {}",
+                activity_id,
+                debit_note_id,
+                amount_to_pay
+            );
+            debit_note.payment_due_date = Some(now);
+        }
     }
 
     let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
@@ -1,6 +1,8 @@
 // External crates
-use actix_web::web::{get, post, Data, Json, Path, Query};
-use actix_web::{HttpResponse, Scope};
+use actix_web::web::{Data, Json, Path, Query};
+use actix_web::HttpResponse;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
 use serde_json::value::Value::Null;
 use std::borrow::Cow;
 use std::sync::Arc;
@@ -9,44 +11,105 @@ use std::time::Instant;
 // Workspace uses
 use metrics::{counter, timing};
 use ya_client_model::payment::*;
-use ya_core_model::payment::local::{SchedulePayment, BUS_ID as LOCAL_SERVICE};
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{RevenueStats, SchedulePayment, BUS_ID as LOCAL_SERVICE};
 use ya_core_model::payment::public::{
-    AcceptInvoice, AcceptRejectError, CancelError, CancelInvoice, RejectInvoiceV2, SendError,
-    SendInvoice, BUS_ID as PUBLIC_SERVICE,
+    AcceptInvoice, AcceptRejectError, CancelError, CancelInvoice, DisputeReason, RejectInvoiceV2,
+    ResolveInvoiceDispute, SendError, SendInvoice, BUS_ID as PUBLIC_SERVICE,
 };
 use ya_core_model::payment::RpcMessageError;
 use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_persistence::types::Role;
+use ya_service_api_web::error::ApiError;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::allocations::limit_error;
 use super::guard::AgreementLock;
 use crate::dao::*;
 use crate::error::{DbError, Error};
+use crate::limits;
+use crate::models::invoice_dispute::{DisputeResolution, InvoiceDispute, NewInvoiceDispute};
 use crate::payment_sync::SYNC_NOTIFS_NOTIFY;
 use crate::utils::provider::get_agreement_id;
 use crate::utils::*;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
         // Shared
-        .route("/invoices", get().to(get_invoices))
-        .route("/invoices/{invoice_id}", get().to(get_invoice))
         .route(
-            "/invoices/{invoice_id}/payments",
-            get().to(get_invoice_payments),
+            Operation::get("/invoices", "getInvoices")
+                .response(Schema::array_of_model::<Invoice>()),
+            get_invoices,
+        )
+        .route(
+            Operation::get("/invoices/{invoice_id}", "getInvoice")
+                .response(Schema::model::<Invoice>()),
+            get_invoice,
+        )
+        .route(
+            Operation::get("/invoices/{invoice_id}/payments", "getPaymentsForInvoice")
+                .response(Schema::array_of_model::<Payment>()),
+            get_invoice_payments,
+        )
+        .route(
+            Operation::get("/invoices/{invoice_id}/dispute", "getInvoiceDispute")
+                .response(Schema::of::<InvoiceDispute>()),
+            get_invoice_dispute,
+        )
+        .route(
+            Operation::get("/invoiceEvents", "getInvoiceEvents")
+                .response(Schema::array_of_model::<InvoiceEvent>()),
+            get_invoice_events,
         )
-        .route("/invoiceEvents", get().to(get_invoice_events))
         // Provider
-        .route("/invoices", post().to(issue_invoice))
-        .route("/invoices/{invoice_id}/send", post().to(send_invoice))
-        .route("/invoices/{invoice_id}/cancel", post().to(cancel_invoice))
+        .route(
+            Operation::post("/invoices", "issueInvoice")
+                .body(Schema::model::<NewInvoice>())
+                .response(Schema::model::<Invoice>()),
+            issue_invoice,
+        )
+        .route(
+            Operation::post("/invoices/{invoice_id}/send", "sendInvoice"),
+            send_invoice,
+        )
+        .route(
+            Operation::post("/invoices/{invoice_id}/cancel", "cancelInvoice"),
+            cancel_invoice,
+        )
+        .route(
+            Operation::get("/revenueStats", "getRevenueStats")
+                .response(Schema::of::<Vec<RevenueStats>>()),
+            get_revenue_stats,
+        )
+        .route(
+            Operation::post(
+                "/invoices/{invoice_id}/dispute/resolve",
+                "resolveInvoiceDispute",
+            )
+            .body(Schema::of::<DisputeResolution>()),
+            resolve_invoice_dispute,
+        )
         // Requestor
-        .route("/invoices/{invoice_id}/accept", post().to(accept_invoice))
-        .route("/invoices/{invoice_id}/reject", post().to(reject_invoice))
+        .route(
+            Operation::post("/invoices/{invoice_id}/accept", "acceptInvoice")
+                .body(Schema::model::<Acceptance>()),
+            accept_invoice,
+        )
+        .route(
+            Operation::post("/invoices/{invoice_id}/reject", "rejectInvoice")
+                .body(Schema::model::<Rejection>()),
+            reject_invoice,
+        )
+        .route(
+            Operation::post("/invoices/{invoice_id}/dispute", "disputeInvoice")
+                .body(Schema::of::<NewInvoiceDispute>()),
+            dispute_invoice,
+        )
 }
 
 async fn get_invoices(
@@ -130,6 +193,27 @@ async fn get_invoice_payments(
     }
 }
 
+/// Revenue from Invoices issued within the last week, unless `since` is given.
+#[derive(Deserialize)]
+struct RevenueStatsParams {
+    since: Option<DateTime<Utc>>,
+}
+
+async fn get_revenue_stats(
+    db: Data<DbExecutor>,
+    query: Query<RevenueStatsParams>,
+    id: Identity,
+) -> HttpResponse {
+    let since = query
+        .since
+        .unwrap_or_else(|| Utc::now() - Duration::days(7));
+    let dao: InvoiceDao = db.as_dao();
+    match dao.revenue_stats(id.identity, since).await {
+        Ok(stats) => response::ok(stats),
+        Err(e) => response::server_error(&e),
+    }
+}
+
 async fn get_invoice_events(
     db: Data<DbExecutor>,
     query: Query<params::EventParams>,
@@ -467,7 +551,18 @@ async fn accept_invoice(
             &invoice_id, &invoice.amount, &agreement.total_amount_accepted
         );
         log::warn!("{}", msg);
-        return response::bad_request(&msg);
+        return response::error(ApiError::bad_request("INVOICE_AMOUNT_TOO_LOW", msg));
+    }
+    if let Err(e) = limits::check_agreement(
+        &db,
+        node_id,
+        &id.name,
+        &agreement_id,
+        &(&invoice.amount - &agreement.total_amount_accepted.0),
+    )
+    .await
+    {
+        return limit_error(e);
     }
     let amount_to_pay = &invoice.amount - &agreement.total_amount_scheduled.0;
 
@@ -482,14 +577,15 @@ async fn accept_invoice(
         .await
     {
         Ok(AllocationStatus::Active(allocation)) => allocation,
-        Ok(AllocationStatus::Gone) => {
-            return response::gone(&format!(
-                "Allocation {} has been already released",
-                allocation_id
-            ))
-        }
+        Ok(AllocationStatus::Gone) => return response::allocation_released(&allocation_id),
         Ok(AllocationStatus::NotFound) => {
-            return response::bad_request(&format!("Allocation {} not found", allocation_id))
+            return response::error(
+                ApiError::bad_request(
+                    "ALLOCATION_NOT_FOUND",
+                    format!("Allocation {} not found", allocation_id),
+                )
+                .with_detail("allocationId", &allocation_id),
+            )
         }
         Err(e) => return response::server_error(&e),
     };
@@ -500,7 +596,11 @@ async fn accept_invoice(
         );
 
         counter!("payment.invoices.requestor.not-enough-funds", 1);
-        return response::bad_request(&msg);
+        return response::error(
+            ApiError::bad_request("INSUFFICIENT_FUNDS", msg)
+                .with_detail("remainingAmount", &allocation.remaining_amount)
+                .with_detail("amountDue", &amount_to_pay),
+        );
     }
 
     let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
@@ -575,11 +675,54 @@ async fn reject_invoice(
     body: Json<Rejection>,
     id: Identity,
 ) -> HttpResponse {
-    let start = Instant::now();
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    reject(
+        db,
+        path.into_inner().invoice_id,
+        id.identity,
+        body.into_inner(),
+        None,
+        timeout,
+    )
+    .await
+}
 
-    let invoice_id = path.invoice_id.clone();
-    let node_id = id.identity;
-    let rejection = body.into_inner();
+/// Rejects Invoice with structured reason and keeps a dispute record,
+/// which is resolved when the Provider re-issues the Invoice.
+async fn dispute_invoice(
+    db: Data<DbExecutor>,
+    path: Path<params::InvoiceId>,
+    query: Query<params::Timeout>,
+    body: Json<NewInvoiceDispute>,
+    id: Identity,
+) -> HttpResponse {
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    let dispute = body.into_inner();
+    let rejection = Rejection {
+        rejection_reason: dispute.reason.rejection_reason(),
+        total_amount_accepted: dispute.total_amount_accepted,
+        message: dispute.message,
+    };
+    reject(
+        db,
+        path.into_inner().invoice_id,
+        id.identity,
+        rejection,
+        Some(dispute.reason),
+        timeout,
+    )
+    .await
+}
+
+async fn reject(
+    db: Data<DbExecutor>,
+    invoice_id: String,
+    node_id: NodeId,
+    rejection: Rejection,
+    dispute: Option<DisputeReason>,
+    timeout: f64,
+) -> HttpResponse {
+    let start = Instant::now();
 
     log::debug!("Requested reject invoice [{}]", invoice_id);
     counter!("payment.invoices.requestor.rejected.call", 1);
@@ -596,6 +739,10 @@ async fn reject_invoice(
 
     match invoice.status {
         DocumentStatus::Received => (),
+        // Dispute rejects the Invoice, so it can be opened only once
+        DocumentStatus::Rejected if dispute.is_some() => {
+            return response::conflict(&"Invoice already rejected")
+        }
         DocumentStatus::Rejected => return response::ok(Null),
         DocumentStatus::Failed => (),
         DocumentStatus::Accepted => return response::bad_request(&"Invoice accepted"),
@@ -604,13 +751,15 @@ async fn reject_invoice(
         DocumentStatus::Issued => return response::server_error(&"Illegal status: issued"),
     }
 
-    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
     let result = async move {
         let issuer_id = invoice.issuer_id;
-        let reject_msg = RejectInvoiceV2::new(invoice_id.clone(), rejection.clone(), issuer_id);
+        let reject_msg = RejectInvoiceV2::new(invoice_id.clone(), rejection.clone(), issuer_id)
+            .with_dispute(dispute.clone());
+        let log_invoice_id = invoice_id.clone();
         match async move {
             log::trace!("Rejecting Invoice [{}] in DB", invoice_id);
-            dao.reject(invoice_id.clone(), node_id, rejection).await?;
+            dao.reject(invoice_id.clone(), node_id, rejection, dispute)
+                .await?;
             log::trace!("Invoice rejected successfully for [{}]", invoice_id);
 
             log::debug!(
@@ -641,7 +790,7 @@ async fn reject_invoice(
         {
             Ok(Ok(_)) => {
                 counter!("payment.invoices.requestor.rejected", 1);
-                log::info!("Invoice [{}] rejected.", path.invoice_id);
+                log::info!("Invoice [{}] rejected.", log_invoice_id);
                 response::ok(Null)
             }
             Ok(Err(Error::Rpc(RpcMessageError::AcceptReject(AcceptRejectError::BadRequest(
@@ -660,3 +809,97 @@ async fn reject_invoice(
     );
     result
 }
+
+async fn get_invoice_dispute(
+    db: Data<DbExecutor>,
+    path: Path<params::InvoiceId>,
+    id: Identity,
+) -> HttpResponse {
+    let dao: InvoiceDisputeDao = db.as_dao();
+    match dao.get(path.invoice_id.clone(), id.identity).await {
+        Ok(Some(dispute)) => response::ok(dispute),
+        Ok(None) => response::not_found(),
+        Err(e) => response::server_error(&e),
+    }
+}
+
+async fn resolve_invoice_dispute(
+    db: Data<DbExecutor>,
+    path: Path<params::InvoiceId>,
+    query: Query<params::Timeout>,
+    body: Json<DisputeResolution>,
+    id: Identity,
+) -> HttpResponse {
+    let invoice_id = path.invoice_id.clone();
+    let corrected_invoice_id = body.into_inner().corrected_invoice_id;
+    let node_id = id.identity;
+
+    log::debug!(
+        "Requested resolving dispute over invoice [{}] with [{}]",
+        invoice_id,
+        corrected_invoice_id
+    );
+
+    let dao: InvoiceDao = db.as_dao();
+    let dispute_dao: InvoiceDisputeDao = db.as_dao();
+
+    let invoice = match dao.get(invoice_id.clone(), node_id).await {
+        Ok(Some(invoice)) if invoice.issuer_id == node_id => invoice,
+        Ok(_) => return response::not_found(),
+        Err(e) => return response::server_error(&e),
+    };
+    if invoice.status != DocumentStatus::Rejected {
+        return response::bad_request(&"Invoice not rejected");
+    }
+
+    match dispute_dao.get(invoice_id.clone(), node_id).await {
+        Ok(Some(dispute)) if dispute.corrected_invoice_id.is_none() => (),
+        Ok(Some(_)) => return response::bad_request(&"Dispute already resolved"),
+        Ok(None) => return response::bad_request(&"Invoice is not disputed"),
+        Err(e) => return response::server_error(&e),
+    }
+
+    let corrected = match dao.get(corrected_invoice_id.clone(), node_id).await {
+        Ok(Some(corrected)) => corrected,
+        Ok(None) => return response::bad_request(&"Corrected invoice not found"),
+        Err(e) => return response::server_error(&e),
+    };
+    if corrected.invoice_id == invoice.invoice_id || corrected.agreement_id != invoice.agreement_id
+    {
+        return response::bad_request(&"Corrected invoice must be issued for the same Agreement");
+    }
+    if corrected.status == DocumentStatus::Issued {
+        return response::bad_request(&"Corrected invoice must be sent first");
+    }
+
+    let timeout = query.timeout.unwrap_or(params::DEFAULT_ACK_TIMEOUT);
+    let msg = ResolveInvoiceDispute {
+        invoice_id: invoice_id.clone(),
+        corrected_invoice_id: corrected_invoice_id.clone(),
+        recipient_id: invoice.recipient_id,
+    };
+    match async move {
+        ya_net::from(node_id)
+            .to(invoice.recipient_id)
+            .service(PUBLIC_SERVICE)
+            .call(msg)
+            .await??;
+        dispute_dao
+            .resolve(invoice_id.clone(), node_id, corrected_invoice_id)
+            .await?;
+        Ok::<_, Error>(())
+    }
+    .timeout(Some(timeout))
+    .await
+    {
+        Ok(Ok(_)) => {
+            log::info!("Dispute over invoice [{}] resolved.", path.invoice_id);
+            response::ok(Null)
+        }
+        Ok(Err(Error::Rpc(RpcMessageError::AcceptReject(AcceptRejectError::BadRequest(e))))) => {
+            response::bad_request(&e)
+        }
+        Ok(Err(e)) => response::server_error(&e),
+        Err(_) => response::timeout(&"Timeout resolving dispute on remote Node."),
+    }
+}
@@ -1,6 +1,6 @@
 // External crates
-use actix_web::web::{get, Data, Path, Query};
-use actix_web::{HttpResponse, Scope};
+use actix_web::web::{Data, Path, Query};
+use actix_web::HttpResponse;
 use std::str::FromStr;
 use ya_service_bus::typed::service;
 
@@ -12,16 +12,29 @@ use ya_core_model::payment::local::{
 };
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
+use ya_service_api_web::openapi::{ApiScope, Operation, Schema};
 
 // Local uses
 use crate::dao::*;
 use crate::utils::*;
 
-pub fn register_endpoints(scope: Scope) -> Scope {
+pub fn register_endpoints(scope: ApiScope) -> ApiScope {
     scope
-        .route("/payments", get().to(get_payments))
-        .route("/payments/status", get().to(payment_status))
-        .route("/payments/{payment_id}", get().to(get_payment))
+        .route(
+            Operation::get("/payments", "getPayments")
+                .response(Schema::array_of_model::<Payment>()),
+            get_payments,
+        )
+        .route(
+            Operation::get("/payments/status", "getPaymentStatus")
+                .response(Schema::array_of_model::<DriverStatusProperty>()),
+            payment_status,
+        )
+        .route(
+            Operation::get("/payments/{payment_id}", "getPayment")
+                .response(Schema::model::<Payment>()),
+            get_payment,
+        )
 }
 
 async fn get_payments(
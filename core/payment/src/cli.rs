@@ -33,6 +33,12 @@ pub enum DriverSubcommand {
         account: pay::AccountCli,
     },
 
+    /// Display capabilities of registered drivers
+    Capabilities {
+        #[structopt(long, help = "Only show capabilities of this driver")]
+        driver: Option<String>,
+    },
+
     /// Display Web3 RPC endpoints and their status for the driver
     Rpc {
         #[structopt(flatten)]
@@ -133,6 +139,62 @@ pub enum PaymentCli {
         )]
         gasless: bool,
     },
+    /// Transfer whole GLM balance of the account to an external address
+    Sweep {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+        #[structopt(long, help = "Recipient address")]
+        to: String,
+        #[structopt(
+            long,
+            help = "Transfer also native token, except the amount reserved for gas"
+        )]
+        native: bool,
+    },
+
+    /// Display amount of GLM the spender is allowed to transfer from the account
+    Allowance {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+        #[structopt(long, help = "Spender address, e.g. deposit contract")]
+        spender: String,
+    },
+
+    /// Sign permit (EIP-2612) approving the spender without sending a transaction
+    Permit {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+        #[structopt(long, help = "Spender address, e.g. deposit contract")]
+        spender: String,
+        #[structopt(long, help = "Amount in GLM for example 1.45")]
+        amount: String,
+        #[structopt(long, help = "Permit validity period", default_value = "1h")]
+        valid_for: humantime::Duration,
+        #[structopt(
+            long,
+            help = "Submit the permit from this account, which pays for gas instead of the owner"
+        )]
+        submit_from: Option<String>,
+    },
+
+    /// Approve the spender to transfer GLM from the account (requires gas)
+    Approve {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+        #[structopt(long, help = "Spender address, e.g. deposit contract")]
+        spender: String,
+        #[structopt(long, help = "Amount in GLM for example 1.45")]
+        amount: String,
+    },
+
+    /// Revoke approval of the spender to transfer GLM from the account (requires gas)
+    Revoke {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+        #[structopt(long, help = "Spender address, e.g. deposit contract")]
+        spender: String,
+    },
+
     Invoice {
         address: Option<String>,
         #[structopt(subcommand)]
@@ -141,6 +203,54 @@ pub enum PaymentCli {
 
     /// Clear all existing allocations
     ReleaseAllocations,
+
+    /// Manage spending limits of app-keys
+    Limits {
+        address: Option<String>,
+        #[structopt(subcommand)]
+        command: LimitsCommand,
+    },
+
+    /// Inspect the payment cycle, which sends scheduled payments in batches
+    Cycle {
+        #[structopt(subcommand)]
+        command: CycleCommand,
+    },
+
+    /// Move documents and payments of settled agreements to archive tables
+    Archive {
+        #[structopt(
+            long,
+            help = "Archive agreements settled at least this long ago",
+            default_value = "90d"
+        )]
+        older_than: humantime::Duration,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum LimitsCommand {
+    /// Set spending limits of the app-key. Limits which are not given are removed
+    Set {
+        app_key: String,
+        #[structopt(long, help = "Limit of amounts allocated within the last 24 hours")]
+        daily: Option<BigDecimal>,
+        #[structopt(long, help = "Limit of amounts allocated in total")]
+        total: Option<BigDecimal>,
+        #[structopt(long, help = "Limit of amount accepted for a single agreement")]
+        per_agreement: Option<BigDecimal>,
+    },
+    /// Display spending limits and allocated amounts of app-keys
+    Show { app_key: Option<String> },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum CycleCommand {
+    /// Estimate totals and gas cost of payments waiting for the next batch
+    Preview {
+        #[structopt(flatten)]
+        account: pay::AccountCli,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -149,6 +259,11 @@ pub enum InvoiceCommand {
         #[structopt(long, help = "Display invoice status from the given period of time")]
         last: Option<humantime::Duration>,
     },
+    /// Display daily earnings from issued invoices per payment platform
+    Revenue {
+        #[structopt(long, help = "Display revenue from the given period of time")]
+        last: Option<humantime::Duration>,
+    },
 }
 
 impl PaymentCli {
@@ -159,7 +274,7 @@ impl PaymentCli {
 
                 let onboarding_supported =
                     matches!(account.network, NetworkName::Polygon | NetworkName::Mainnet);
-                if !account.network.is_fundable() && !onboarding_supported {
+                if !account.is_fundable() && !onboarding_supported {
                     log::error!(
                         "Network {} does not support automatic funding. Consider using one of the following: {:?}",
                         account.network,
@@ -414,6 +529,45 @@ Typically operation should take less than 1 minute.
                         .await??,
                 )
             }
+            PaymentCli::Invoice {
+                address,
+                command: InvoiceCommand::Revenue { last },
+            } => {
+                let seconds = last.map(|d| d.as_secs() as i64).unwrap_or(7 * 24 * 3600);
+                let address = resolve_address(address).await?;
+                let stats = bus::service(pay::BUS_ID)
+                    .call(pay::GetRevenueStats::new(
+                        address.parse()?,
+                        Utc::now() + chrono::Duration::seconds(-seconds),
+                    ))
+                    .await??;
+                Ok(ResponseTable {
+                    columns: vec![
+                        "day".to_owned(),
+                        "platform".to_owned(),
+                        "invoiced".to_owned(),
+                        "accepted".to_owned(),
+                        "settled".to_owned(),
+                        "overdue".to_owned(),
+                        "delinquent requestors".to_owned(),
+                    ],
+                    values: stats
+                        .into_iter()
+                        .map(|entry| {
+                            serde_json::json! {[
+                                entry.day.to_string(),
+                                entry.platform,
+                                entry.invoiced.total_amount.to_string(),
+                                entry.accepted.total_amount.to_string(),
+                                entry.settled.total_amount.to_string(),
+                                entry.overdue.total_amount.to_string(),
+                                entry.delinquent_requestors,
+                            ]}
+                        })
+                        .collect(),
+                }
+                .into())
+            }
             PaymentCli::Enter { account, amount } => CommandOutput::object(
                 wallet::enter(
                     BigDecimal::from_str(&amount)?,
@@ -491,6 +645,94 @@ Typically operation should take less than 1 minute.
                     .await?,
                 )
             }
+            PaymentCli::Sweep {
+                account,
+                to,
+                native,
+            } => {
+                let address = resolve_address(account.address()).await?;
+                CommandOutput::object(
+                    bus::service(pay::BUS_ID)
+                        .call(pay::Sweep {
+                            address,
+                            driver: account.driver(),
+                            network: Some(account.network()),
+                            to,
+                            native,
+                        })
+                        .await??,
+                )
+            }
+            PaymentCli::Allowance { account, spender } => {
+                let address = resolve_address(account.address()).await?;
+                let allowance =
+                    wallet::allowance(address, spender, account.driver(), Some(account.network()))
+                        .await?;
+                CommandOutput::object(allowance.to_string())
+            }
+            PaymentCli::Permit {
+                account,
+                spender,
+                amount,
+                valid_for,
+                submit_from,
+            } => {
+                let address = resolve_address(account.address()).await?;
+                let amount = BigDecimal::from_str(&amount)?;
+                let deadline = Utc::now() + chrono::Duration::from_std(valid_for.into())?;
+                let permit = wallet::permit(
+                    address,
+                    spender,
+                    amount,
+                    deadline,
+                    account.driver(),
+                    Some(account.network()),
+                )
+                .await?;
+                match submit_from {
+                    Some(sender) => CommandOutput::object(
+                        wallet::submit_permit(
+                            sender,
+                            permit,
+                            account.driver(),
+                            Some(account.network()),
+                        )
+                        .await?,
+                    ),
+                    None => CommandOutput::object(permit),
+                }
+            }
+            PaymentCli::Approve {
+                account,
+                spender,
+                amount,
+            } => {
+                let address = resolve_address(account.address()).await?;
+                let amount = BigDecimal::from_str(&amount)?;
+                CommandOutput::object(
+                    wallet::approve(
+                        address,
+                        spender,
+                        amount,
+                        account.driver(),
+                        Some(account.network()),
+                    )
+                    .await?,
+                )
+            }
+            PaymentCli::Revoke { account, spender } => {
+                let address = resolve_address(account.address()).await?;
+                CommandOutput::object(
+                    wallet::approve(
+                        address,
+                        spender,
+                        BigDecimal::from(0),
+                        account.driver(),
+                        Some(account.network()),
+                    )
+                    .await?,
+                )
+            }
             PaymentCli::Driver { command } => match command {
                 DriverSubcommand::Rpc {
                     account,
@@ -553,6 +795,42 @@ Typically operation should take less than 1 minute.
                         ok_msg
                     )))
                 }
+                DriverSubcommand::Capabilities { driver } => {
+                    let capabilities = bus::service(pay::BUS_ID)
+                        .call(pay::GetDriverCapabilities { driver })
+                        .await??;
+                    if ctx.json_output {
+                        return CommandOutput::object(capabilities);
+                    }
+
+                    let mut values: Vec<Value> = capabilities
+                        .into_iter()
+                        .map(|(driver, c)| {
+                            serde_json::json! {[
+                                driver,
+                                c.batch_transfers,
+                                c.deposits,
+                                c.partial_payments,
+                                c.confirmation_latency,
+                                c.fee_asset,
+                            ]}
+                        })
+                        .collect();
+                    values.sort_by_key(|v| v[0].as_str().unwrap_or_default().to_owned());
+
+                    Ok(ResponseTable {
+                        columns: vec![
+                            "driver".to_owned(),
+                            "batch transfers".to_owned(),
+                            "deposits".to_owned(),
+                            "partial payments".to_owned(),
+                            "confirmation latency".to_owned(),
+                            "fee asset".to_owned(),
+                        ],
+                        values,
+                    }
+                    .into())
+                }
                 DriverSubcommand::List => {
                     let drivers: HashMap<String, DriverDetails> = bus::service(pay::BUS_ID)
                         .call(pay::GetDrivers {
@@ -606,6 +884,149 @@ Typically operation should take less than 1 minute.
                     .into())
                 }
             },
+            PaymentCli::Limits {
+                address,
+                command:
+                    LimitsCommand::Set {
+                        app_key,
+                        daily,
+                        total,
+                        per_agreement,
+                    },
+            } => {
+                let address = resolve_address(address).await?;
+                bus::service(pay::BUS_ID)
+                    .call(pay::SetAppKeyLimits {
+                        owner: address.parse()?,
+                        app_key,
+                        limits: pay::AppKeyLimits {
+                            daily,
+                            total,
+                            per_agreement,
+                        },
+                    })
+                    .await??;
+                CommandOutput::none()
+            }
+            PaymentCli::Limits {
+                address,
+                command: LimitsCommand::Show { app_key },
+            } => {
+                let address = resolve_address(address).await?;
+                let limits = bus::service(pay::BUS_ID)
+                    .call(pay::GetAppKeyLimits {
+                        owner: address.parse()?,
+                        app_key,
+                    })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(limits);
+                }
+
+                let limit = |limit: Option<BigDecimal>| {
+                    limit
+                        .map(|l| l.to_string())
+                        .unwrap_or_else(|| "-".to_owned())
+                };
+                Ok(ResponseTable {
+                    columns: vec![
+                        "app-key".to_owned(),
+                        "daily".to_owned(),
+                        "total".to_owned(),
+                        "per agreement".to_owned(),
+                        "platform".to_owned(),
+                        "allocated daily".to_owned(),
+                        "allocated total".to_owned(),
+                    ],
+                    values: limits
+                        .into_iter()
+                        .flat_map(|status| {
+                            let row = |platform: String, daily: String, total: String| {
+                                serde_json::json! {[
+                                    &status.app_key,
+                                    limit(status.limits.daily.clone()),
+                                    limit(status.limits.total.clone()),
+                                    limit(status.limits.per_agreement.clone()),
+                                    platform,
+                                    daily,
+                                    total,
+                                ]}
+                            };
+                            match status.allocated.is_empty() {
+                                true => vec![row("-".into(), "0".into(), "0".into())],
+                                false => status
+                                    .allocated
+                                    .iter()
+                                    .map(|a| {
+                                        row(
+                                            a.platform.clone(),
+                                            a.daily.to_string(),
+                                            a.total.to_string(),
+                                        )
+                                    })
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                }
+                .into())
+            }
+            PaymentCli::Cycle {
+                command: CycleCommand::Preview { account },
+            } => {
+                let address = resolve_address(account.address()).await?;
+                let preview = bus::service(pay::BUS_ID)
+                    .call(pay::PreviewPaymentCycle {
+                        address,
+                        driver: account.driver(),
+                        network: Some(account.network()),
+                    })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(preview);
+                }
+
+                let estimate = preview.estimate;
+                Ok(ResponseTable {
+                    columns: vec![
+                        "platform".to_owned(),
+                        "payments".to_owned(),
+                        "transfers".to_owned(),
+                        "total amount".to_owned(),
+                        "batching window".to_owned(),
+                        "max batch size".to_owned(),
+                        "transactions".to_owned(),
+                        "gas price (Gwei)".to_owned(),
+                        "estimated fee".to_owned(),
+                    ],
+                    values: vec![serde_json::json! {[
+                        preview.platform,
+                        estimate.payments,
+                        estimate.transfers,
+                        estimate.amount.to_string(),
+                        estimate
+                            .batch_interval
+                            .map(|interval| humantime::format_duration(interval).to_string())
+                            .unwrap_or_else(|| "-".to_owned()),
+                        estimate
+                            .max_batch_size
+                            .map(|size| size.to_string())
+                            .unwrap_or_else(|| "-".to_owned()),
+                        estimate.transactions,
+                        estimate.gas_price.round(3).to_string(),
+                        format!("{} {}", estimate.fee.round(8), estimate.fee_currency),
+                    ]}],
+                }
+                .with_header(format!("Next batch on {} network", estimate.network)))
+            }
+            PaymentCli::Archive { older_than } => {
+                let stats = bus::service(pay::BUS_ID)
+                    .call(pay::ArchiveDocuments {
+                        older_than: older_than.into(),
+                    })
+                    .await??;
+                CommandOutput::object(stats)
+            }
             PaymentCli::ReleaseAllocations => {
                 let _ = bus::service(pay::BUS_ID)
                     .call(pay::ReleaseAllocations {})
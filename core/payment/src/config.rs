@@ -1,9 +1,14 @@
+use bigdecimal::BigDecimal;
 use structopt::*;
 
 #[derive(StructOpt, Clone)]
 pub struct Config {
     #[structopt(flatten)]
     pub sync_notif_backoff: SyncNotifBackoffConfig,
+    #[structopt(flatten)]
+    pub debit_note_settlement: DebitNoteSettlementConfig,
+    #[structopt(flatten)]
+    pub retention: RetentionConfig,
 }
 
 #[derive(StructOpt, Clone)]
@@ -39,6 +44,60 @@ pub struct SyncNotifBackoffConfig {
     pub error_delay: std::time::Duration,
 }
 
+/// Requestor side settlement of Debit Notes, which don't specify payment due date.
+///
+/// Such Debit Notes are paid together with the Invoice by default. When any of the limits
+/// below is set, payment for accepted amount is scheduled immediately and sent by the driver
+/// with the next batch.
+#[derive(StructOpt, Clone, Debug, Default)]
+pub struct DebitNoteSettlementConfig {
+    /// Pays for the Activity if this much time has passed since its last payment.
+    #[structopt(long, env = "YA_PAYMENT_DEBIT_NOTE_SETTLEMENT_INTERVAL", parse(try_from_str = humantime::parse_duration))]
+    pub settlement_interval: Option<std::time::Duration>,
+
+    /// Pays for the Activity if accepted, but not yet paid, amount reaches this value.
+    #[structopt(long, env = "YA_PAYMENT_DEBIT_NOTE_SETTLEMENT_MIN_AMOUNT")]
+    pub settlement_min_amount: Option<BigDecimal>,
+}
+
+/// Archival of settled documents, which keeps working tables small.
+///
+/// Disabled by default. Archiving can also be triggered with `yagna payment archive`.
+#[derive(StructOpt, Clone, Debug)]
+pub struct RetentionConfig {
+    /// Archives documents and payments of agreements settled this long ago.
+    #[structopt(long, env = "YA_PAYMENT_ARCHIVE_AFTER", parse(try_from_str = humantime::parse_duration))]
+    pub archive_after: Option<std::time::Duration>,
+
+    #[structopt(long, env = "YA_PAYMENT_ARCHIVE_INTERVAL", parse(try_from_str = humantime::parse_duration), default_value = "24h")]
+    pub archive_interval: std::time::Duration,
+}
+
+impl DebitNoteSettlementConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.settlement_interval.is_some() || self.settlement_min_amount.is_some()
+    }
+
+    /// Decides if `amount` should be paid now, given time `since_last_settlement`.
+    pub fn should_settle(
+        &self,
+        amount: &BigDecimal,
+        since_last_settlement: chrono::Duration,
+    ) -> bool {
+        let interval_elapsed = self
+            .settlement_interval
+            .and_then(|interval| chrono::Duration::from_std(interval).ok())
+            .map(|interval| since_last_settlement >= interval)
+            .unwrap_or(false);
+        let amount_reached = self
+            .settlement_min_amount
+            .as_ref()
+            .map(|min_amount| amount >= min_amount)
+            .unwrap_or(false);
+        interval_elapsed || amount_reached
+    }
+}
+
 impl Config {
     pub fn from_env() -> Result<Config, structopt::clap::Error> {
         // Empty command line arguments, because we want to use ENV fallback
@@ -51,3 +110,28 @@ impl Config {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_settlement_disabled_by_default() {
+        let config = DebitNoteSettlementConfig::default();
+        assert!(!config.is_enabled());
+        assert!(!config.should_settle(&BigDecimal::from(1000), chrono::Duration::days(1)));
+    }
+
+    #[test]
+    fn test_settlement_limits() {
+        let config = DebitNoteSettlementConfig {
+            settlement_interval: Some(Duration::from_secs(600)),
+            settlement_min_amount: Some(BigDecimal::from(5)),
+        };
+        assert!(config.is_enabled());
+        assert!(!config.should_settle(&BigDecimal::from(1), chrono::Duration::minutes(5)));
+        assert!(config.should_settle(&BigDecimal::from(1), chrono::Duration::minutes(10)));
+        assert!(config.should_settle(&BigDecimal::from(5), chrono::Duration::zero()));
+    }
+}
@@ -1,22 +1,33 @@
 mod activity;
 mod agreement;
 mod allocation;
+mod allocation_event;
+pub(crate) mod app_key_limit;
+mod archive;
 mod debit_note;
 mod debit_note_event;
 mod invoice;
+mod invoice_dispute;
 mod invoice_event;
 mod order;
 mod payment;
 mod sync_notifs;
 
+#[cfg(test)]
+mod fixtures;
+
 pub use self::activity::ActivityDao;
 pub use self::agreement::AgreementDao;
 pub use self::allocation::AllocationDao;
 pub use self::allocation::AllocationReleaseStatus;
 pub use self::allocation::AllocationStatus;
+pub use self::allocation_event::AllocationEventDao;
+pub use self::app_key_limit::AppKeyLimitDao;
+pub use self::archive::ArchiveDao;
 pub use self::debit_note::DebitNoteDao;
 pub use self::debit_note_event::DebitNoteEventDao;
 pub use self::invoice::InvoiceDao;
+pub use self::invoice_dispute::InvoiceDisputeDao;
 pub use self::invoice_event::InvoiceEventDao;
 pub use self::order::OrderDao;
 pub use self::payment::PaymentDao;
@@ -1,4 +1,7 @@
+use crate::allocation_events::ALLOCATION_EVENTS_NOTIFY;
+use crate::dao::{allocation_event, app_key_limit};
 use crate::error::{DbError, DbResult};
+use crate::limits::{self, LimitError};
 use crate::models::allocation::{ReadObj, WriteObj};
 use crate::schema::pay_allocation::dsl;
 use bigdecimal::BigDecimal;
@@ -7,6 +10,7 @@ use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
 use ya_client_model::payment::allocation::Deposit;
 use ya_client_model::payment::{Allocation, NewAllocation};
 use ya_client_model::NodeId;
+use ya_core_model::payment::local::AllocationEventType;
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -64,6 +68,40 @@ impl<'c> AllocationDao<'c> {
         .await
     }
 
+    /// Creates allocation assigned to `app_key`. App-key limits are checked
+    /// in the same transaction, so concurrent requests can't exceed them together.
+    pub async fn create_for_app_key(
+        &self,
+        allocation: NewAllocation,
+        owner_id: NodeId,
+        payment_platform: String,
+        address: String,
+        app_key: String,
+    ) -> Result<String, LimitError> {
+        let allocation = WriteObj::new(allocation, owner_id, payment_platform, address);
+        let allocation_id = allocation.id.clone();
+        do_with_transaction(
+            self.pool,
+            "allocation_dao_create_for_app_key",
+            move |conn| {
+                limits::check_allocation(
+                    conn,
+                    owner_id,
+                    &app_key,
+                    &allocation.payment_platform,
+                    &allocation.total_amount.0,
+                    None,
+                )?;
+                diesel::insert_into(dsl::pay_allocation)
+                    .values(allocation)
+                    .execute(conn)?;
+                app_key_limit::assign_allocation(conn, allocation_id.clone(), app_key)?;
+                Ok(allocation_id)
+            },
+        )
+        .await
+    }
+
     pub async fn replace(&self, allocation: Allocation, owner_id: NodeId) -> DbResult<bool> {
         do_with_transaction(self.pool, "allocation_dao_replace", move |conn| {
             let count = diesel::update(dsl::pay_allocation)
@@ -78,6 +116,38 @@ impl<'c> AllocationDao<'c> {
         .await
     }
 
+    /// Replaces allocation amended by `app_key`, if the new amount fits into app-key limits.
+    pub async fn replace_for_app_key(
+        &self,
+        allocation: Allocation,
+        owner_id: NodeId,
+        app_key: String,
+    ) -> Result<bool, LimitError> {
+        do_with_transaction(
+            self.pool,
+            "allocation_dao_replace_for_app_key",
+            move |conn| {
+                limits::check_allocation(
+                    conn,
+                    owner_id,
+                    &app_key,
+                    &allocation.payment_platform,
+                    &allocation.total_amount,
+                    Some(&allocation.allocation_id),
+                )?;
+                let count = diesel::update(dsl::pay_allocation)
+                    .filter(dsl::id.eq(allocation.allocation_id.clone()))
+                    .filter(dsl::owner_id.eq(&owner_id))
+                    .filter(dsl::released.eq(false))
+                    .set(WriteObj::from_allocation(allocation, owner_id))
+                    .execute(conn)?;
+
+                Ok(count == 1)
+            },
+        )
+        .await
+    }
+
     pub async fn get(&self, allocation_id: String, owner_id: NodeId) -> DbResult<AllocationStatus> {
         readonly_transaction(self.pool, "allocation_dao_get", move |conn| {
             let allocation: Option<ReadObj> = dsl::pay_allocation
@@ -191,7 +261,7 @@ impl<'c> AllocationDao<'c> {
         owner_id: Option<NodeId>,
     ) -> DbResult<AllocationReleaseStatus> {
         let id = allocation_id.clone();
-        do_with_transaction(self.pool, "allocation_dao_release", move |conn| {
+        let result = do_with_transaction(self.pool, "allocation_dao_release", move |conn| {
             let allocation: Option<ReadObj> = dsl::pay_allocation
                 .find(id.clone())
                 .first(conn)
@@ -209,6 +279,11 @@ impl<'c> AllocationDao<'c> {
                         return Ok(AllocationReleaseStatus::Gone);
                     }
 
+                    let released = AllocationEventType::AllocationReleasedEvent {
+                        remaining_amount: allocation.remaining_amount.0.clone(),
+                    };
+                    allocation_event::create(&id, &allocation.owner_id, released, conn)?;
+
                     let allocation = Allocation::from(allocation);
 
                     (allocation.deposit, allocation.payment_platform)
@@ -230,7 +305,9 @@ impl<'c> AllocationDao<'c> {
                 ))),
             }
         })
-        .await
+        .await;
+        ALLOCATION_EVENTS_NOTIFY.notify_one();
+        result
     }
 
     pub async fn total_remaining_allocation(
@@ -0,0 +1,325 @@
+use crate::error::DbResult;
+use crate::models::allocation_event::{ReadObj, WriteObj};
+use crate::schema::pay_allocation_event::dsl;
+use chrono::NaiveDateTime;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+use std::convert::TryInto;
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{AllocationEvent, AllocationEventType};
+use ya_persistence::executor::{readonly_transaction, AsDao, ConnType, PoolType};
+use ya_persistence::types::AdaptTimestamp;
+
+pub fn create(
+    allocation_id: &str,
+    owner_id: &NodeId,
+    event_type: AllocationEventType,
+    conn: &ConnType,
+) -> DbResult<()> {
+    let event = WriteObj::new(allocation_id.to_owned(), *owner_id, &event_type)?;
+    diesel::insert_into(dsl::pay_allocation_event)
+        .values(event)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub struct AllocationEventDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for AllocationEventDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AllocationEventDao<'c> {
+    pub async fn get_for_node_id(
+        &self,
+        node_id: NodeId,
+        allocation_id: Option<String>,
+        after_timestamp: Option<NaiveDateTime>,
+        max_events: Option<u32>,
+    ) -> DbResult<Vec<AllocationEvent>> {
+        readonly_transaction(self.pool, "allocation_event_get_for_node_id", move |conn| {
+            let mut query = dsl::pay_allocation_event
+                .filter(dsl::owner_id.eq(node_id))
+                .order_by(dsl::id.asc())
+                .into_boxed();
+            if let Some(allocation_id) = allocation_id {
+                query = query.filter(dsl::allocation_id.eq(allocation_id));
+            }
+            if let Some(timestamp) = after_timestamp {
+                query = query.filter(dsl::timestamp.gt(timestamp.adapt()));
+            }
+            if let Some(limit) = max_events {
+                query = query.limit(limit.into());
+            }
+            let events: Vec<ReadObj> = query.load(conn)?;
+            events.into_iter().map(TryInto::try_into).collect()
+        })
+        .await
+    }
+
+    /// Events of all identities stored after event `last_id`, along with their ids.
+    pub async fn get_after_id(
+        &self,
+        last_id: Option<i32>,
+        max_events: u32,
+    ) -> DbResult<Vec<(i32, AllocationEvent)>> {
+        readonly_transaction(self.pool, "allocation_event_get_after_id", move |conn| {
+            let mut query = dsl::pay_allocation_event
+                .order_by(dsl::id.asc())
+                .limit(max_events.into())
+                .into_boxed();
+            if let Some(last_id) = last_id {
+                query = query.filter(dsl::id.gt(last_id));
+            }
+            let events: Vec<ReadObj> = query.load(conn)?;
+            events
+                .into_iter()
+                .map(|event| Ok((event.id, event.try_into()?)))
+                .collect()
+        })
+        .await
+    }
+
+    pub async fn last_id(&self) -> DbResult<Option<i32>> {
+        readonly_transaction(self.pool, "allocation_event_last_id", move |conn| {
+            Ok(dsl::pay_allocation_event
+                .select(diesel::dsl::max(dsl::id))
+                .first::<Option<i32>>(conn)?)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::fixtures::{self, agreement, OWNER, PEER, PLATFORM};
+    use crate::dao::{AllocationDao, OrderDao, PaymentDao};
+    use crate::utils::listen_for_events;
+    use bigdecimal::BigDecimal;
+    use chrono::Utc;
+    use std::time::Duration;
+    use ya_client_model::payment::ActivityPayment;
+    use ya_core_model::payment::local::{DebitNotePayment, PaymentTitle, SchedulePayment};
+    use ya_persistence::executor::DbExecutor;
+
+    fn allocation(id: &str, owner: &str) -> String {
+        format!(
+            "INSERT INTO pay_allocation (id, owner_id, payment_platform, address, total_amount, \
+                spent_amount, remaining_amount, timestamp, timeout, make_deposit, deposit, \
+                released) \
+             VALUES ('{id}', '{owner}', '{PLATFORM}', 'payer', '10', '0', '10', \
+                '2024-06-01 12:00:00', NULL, 0, NULL, 0);"
+        )
+    }
+
+    fn activity(id: &str) -> String {
+        let agreement = agreement(&format!("agreement-{id}"), "R", PEER);
+        format!(
+            "{agreement} \
+             INSERT INTO pay_activity (id, owner_id, role, agreement_id, total_amount_due, \
+                total_amount_accepted, total_amount_scheduled, total_amount_paid) \
+             VALUES ('{id}', '{OWNER}', 'R', 'agreement-{id}', '0', '0', '0', '0');"
+        )
+    }
+
+    fn event(allocation_id: &str, owner: &str, timestamp: &str) -> String {
+        let details = serde_json::to_string(&AllocationEventType::AllocationReleasedEvent {
+            remaining_amount: BigDecimal::from(1),
+        })
+        .unwrap();
+        format!(
+            "INSERT INTO pay_allocation_event (allocation_id, owner_id, event_type, details, \
+                timestamp) \
+             VALUES ('{allocation_id}', '{owner}', 'RELEASED', '{details}', '{timestamp}');"
+        )
+    }
+
+    async fn events(db: &DbExecutor, allocation_id: &str) -> Vec<AllocationEventType> {
+        db.as_dao::<AllocationEventDao>()
+            .get_for_node_id(
+                OWNER.parse().unwrap(),
+                Some(allocation_id.to_string()),
+                None,
+                None,
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|event| event.event_type)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_payment_reserves_amount() {
+        let db = fixtures::db_with(
+            "allocation_event_reserved",
+            &[allocation("allocation", OWNER), activity("activity")],
+        );
+
+        let msg = SchedulePayment {
+            title: PaymentTitle::DebitNote(DebitNotePayment {
+                debit_note_id: "debit-note".to_string(),
+                activity_id: "activity".to_string(),
+            }),
+            payer_id: OWNER.parse().unwrap(),
+            payee_id: PEER.parse().unwrap(),
+            payer_addr: "payer".to_string(),
+            payee_addr: "payee".to_string(),
+            payment_platform: PLATFORM.to_string(),
+            allocation_id: "allocation".to_string(),
+            amount: BigDecimal::from(3),
+            due_date: Utc::now(),
+        };
+        db.as_dao::<OrderDao>()
+            .create(msg, "order".to_string(), "erc20".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events(&db, "allocation").await,
+            vec![AllocationEventType::AllocationReservedEvent {
+                agreement_id: None,
+                activity_id: Some("activity".to_string()),
+                invoice_id: None,
+                debit_note_id: Some("debit-note".to_string()),
+                amount: BigDecimal::from(3),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_payment_spends_amount() {
+        let db = fixtures::db_with(
+            "allocation_event_spent",
+            &[allocation("allocation", OWNER), activity("activity")],
+        );
+
+        let payment_id = db
+            .as_dao::<PaymentDao>()
+            .create_new(
+                OWNER.parse().unwrap(),
+                PEER.parse().unwrap(),
+                "payer".to_string(),
+                "payee".to_string(),
+                PLATFORM.to_string(),
+                BigDecimal::from(2),
+                vec![],
+                vec![ActivityPayment {
+                    activity_id: "activity".to_string(),
+                    amount: BigDecimal::from(2),
+                    allocation_id: Some("allocation".to_string()),
+                }],
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events(&db, "allocation").await,
+            vec![AllocationEventType::AllocationSpentEvent {
+                agreement_id: None,
+                activity_id: Some("activity".to_string()),
+                payment_id,
+                amount: BigDecimal::from(2),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_release_returns_remaining_amount() {
+        let db = fixtures::db_with(
+            "allocation_event_released",
+            &[allocation("allocation", OWNER)],
+        );
+
+        let dao = db.as_dao::<AllocationDao>();
+        dao.release("allocation".to_string(), OWNER.parse().ok())
+            .await
+            .unwrap();
+        // Releasing twice doesn't emit another event.
+        dao.release("allocation".to_string(), OWNER.parse().ok())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events(&db, "allocation").await,
+            vec![AllocationEventType::AllocationReleasedEvent {
+                remaining_amount: BigDecimal::from(10),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_for_node_id_filters() {
+        let db = fixtures::db_with(
+            "allocation_event_filters",
+            &[
+                event("allocation-1", OWNER, "2024-06-01 12:00:00"),
+                event("allocation-1", OWNER, "2024-06-01 12:10:00"),
+                event("allocation-2", OWNER, "2024-06-01 12:20:00"),
+                event("allocation-3", PEER, "2024-06-01 12:30:00"),
+            ],
+        );
+        let dao = db.as_dao::<AllocationEventDao>();
+        let owner: NodeId = OWNER.parse().unwrap();
+        let allocations = |events: Vec<AllocationEvent>| {
+            events
+                .into_iter()
+                .map(|event| event.allocation_id)
+                .collect::<Vec<_>>()
+        };
+
+        let all = dao.get_for_node_id(owner, None, None, None).await.unwrap();
+        assert_eq!(
+            allocations(all),
+            vec!["allocation-1", "allocation-1", "allocation-2"]
+        );
+
+        let allocation = dao
+            .get_for_node_id(owner, Some("allocation-2".to_string()), None, None)
+            .await
+            .unwrap();
+        assert_eq!(allocations(allocation), vec!["allocation-2"]);
+
+        let after =
+            NaiveDateTime::parse_from_str("2024-06-01 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let later = dao
+            .get_for_node_id(owner, None, Some(after), Some(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            later[0].event_date.naive_utc().to_string(),
+            "2024-06-01 12:10:00"
+        );
+        assert_eq!(allocations(later), vec!["allocation-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_listen_for_events_returns_new_event() {
+        let db = fixtures::db_with(
+            "allocation_event_listen",
+            &[allocation("allocation", OWNER)],
+        );
+        let dao = db.as_dao::<AllocationEventDao>();
+        let getter = || async {
+            dao.get_for_node_id(OWNER.parse().unwrap(), None, None, None)
+                .await
+        };
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            db.as_dao::<AllocationDao>()
+                .release("allocation".to_string(), None)
+                .await
+                .unwrap();
+        };
+
+        let (events, _) = tokio::join!(listen_for_events(getter, 10), release);
+        let events = events.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].allocation_id, "allocation");
+    }
+}
@@ -0,0 +1,302 @@
+use crate::error::DbResult;
+use crate::models::app_key_limit::{AllocationAppKey, LimitObj};
+use crate::schema::pay_allocation::dsl as allocation_dsl;
+use crate::schema::pay_allocation_app_key::dsl as allocation_app_key_dsl;
+use crate::schema::pay_app_key_limit::dsl;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use std::collections::BTreeMap;
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{AppKeyAllocated, AppKeyLimits};
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
+use ya_persistence::types::BigDecimalField;
+
+pub struct AppKeyLimitDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for AppKeyLimitDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> AppKeyLimitDao<'c> {
+    /// Empty limits remove the entry.
+    pub async fn set(
+        &self,
+        owner_id: NodeId,
+        app_key: String,
+        limits: AppKeyLimits,
+    ) -> DbResult<()> {
+        let remove = limits == AppKeyLimits::default();
+        let limit = LimitObj::new(owner_id, app_key.clone(), limits);
+        do_with_transaction(self.pool, "app_key_limit_dao_set", move |conn| {
+            if remove {
+                diesel::delete(dsl::pay_app_key_limit.find((owner_id, app_key))).execute(conn)?;
+            } else {
+                diesel::replace_into(dsl::pay_app_key_limit)
+                    .values(limit)
+                    .execute(conn)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get(&self, owner_id: NodeId, app_key: String) -> DbResult<Option<AppKeyLimits>> {
+        readonly_transaction(self.pool, "app_key_limit_dao_get", move |conn| {
+            get_limits(conn, owner_id, &app_key)
+        })
+        .await
+    }
+
+    pub async fn list(&self, owner_id: NodeId) -> DbResult<Vec<(String, AppKeyLimits)>> {
+        readonly_transaction(self.pool, "app_key_limit_dao_list", move |conn| {
+            let limits: Vec<LimitObj> = dsl::pay_app_key_limit
+                .filter(dsl::owner_id.eq(owner_id))
+                .order(dsl::app_key)
+                .load(conn)?;
+            Ok(limits
+                .into_iter()
+                .map(|limit| (limit.app_key.clone(), limit.into()))
+                .collect())
+        })
+        .await
+    }
+
+    /// Allocated amounts of the app-key on each payment platform.
+    pub async fn allocated(
+        &self,
+        owner_id: NodeId,
+        app_key: String,
+        since: NaiveDateTime,
+    ) -> DbResult<Vec<AppKeyAllocated>> {
+        readonly_transaction(self.pool, "app_key_limit_dao_allocated", move |conn| {
+            let allocations: Vec<(
+                String,
+                BigDecimalField,
+                BigDecimalField,
+                bool,
+                NaiveDateTime,
+            )> = allocation_dsl::pay_allocation
+                .inner_join(allocation_app_key_dsl::pay_allocation_app_key)
+                .filter(allocation_dsl::owner_id.eq(owner_id))
+                .filter(allocation_app_key_dsl::app_key.eq(app_key))
+                .select((
+                    allocation_dsl::payment_platform,
+                    allocation_dsl::total_amount,
+                    allocation_dsl::spent_amount,
+                    allocation_dsl::released,
+                    allocation_dsl::timestamp,
+                ))
+                .load(conn)?;
+
+            let mut allocated = BTreeMap::<String, AppKeyAllocated>::new();
+            for (platform, total, spent, released, timestamp) in allocations {
+                let amount = if released { spent.0 } else { total.0 };
+                let entry = allocated
+                    .entry(platform.clone())
+                    .or_insert_with(|| AppKeyAllocated {
+                        platform,
+                        daily: BigDecimal::zero(),
+                        total: BigDecimal::zero(),
+                    });
+                if timestamp > since {
+                    entry.daily += &amount;
+                }
+                entry.total += amount;
+            }
+            Ok(allocated.into_values().collect())
+        })
+        .await
+    }
+}
+
+pub fn get_limits(
+    conn: &ConnType,
+    owner_id: NodeId,
+    app_key: &str,
+) -> DbResult<Option<AppKeyLimits>> {
+    let limit: Option<LimitObj> = dsl::pay_app_key_limit
+        .find((owner_id, app_key))
+        .first(conn)
+        .optional()?;
+    Ok(limit.map(Into::into))
+}
+
+pub fn assign_allocation(conn: &ConnType, allocation_id: String, app_key: String) -> DbResult<()> {
+    diesel::insert_into(allocation_app_key_dsl::pay_allocation_app_key)
+        .values(AllocationAppKey {
+            allocation_id,
+            app_key,
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Amount allocated by the app-key on the payment platform: spent amount
+/// of released allocations and total amount of active ones.
+pub fn allocated(
+    conn: &ConnType,
+    owner_id: NodeId,
+    app_key: &str,
+    platform: &str,
+    since: Option<NaiveDateTime>,
+    except_allocation: Option<&str>,
+) -> DbResult<BigDecimal> {
+    let mut query = allocation_dsl::pay_allocation
+        .inner_join(allocation_app_key_dsl::pay_allocation_app_key)
+        .filter(allocation_dsl::owner_id.eq(owner_id))
+        .filter(allocation_dsl::payment_platform.eq(platform))
+        .filter(allocation_app_key_dsl::app_key.eq(app_key))
+        .select((
+            allocation_dsl::total_amount,
+            allocation_dsl::spent_amount,
+            allocation_dsl::released,
+        ))
+        .into_boxed();
+    if let Some(since) = since {
+        query = query.filter(allocation_dsl::timestamp.gt(since));
+    }
+    if let Some(allocation_id) = except_allocation {
+        query = query.filter(allocation_dsl::id.ne(allocation_id));
+    }
+
+    let allocations: Vec<(BigDecimalField, BigDecimalField, bool)> = query.load(conn)?;
+    Ok(allocations
+        .into_iter()
+        .fold(
+            BigDecimal::zero(),
+            |sum, (total, spent, released)| match released {
+                true => sum + spent.0,
+                false => sum + total.0,
+            },
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::AllocationDao;
+    use crate::limits::LimitError;
+    use chrono::Utc;
+    use ya_client_model::payment::NewAllocation;
+    use ya_persistence::executor::DbExecutor;
+
+    const APP_KEY: &str = "agent";
+
+    fn owner() -> NodeId {
+        "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap()
+    }
+
+    fn new_allocation(amount: u32) -> NewAllocation {
+        NewAllocation {
+            address: None,
+            payment_platform: None,
+            total_amount: amount.into(),
+            timeout: None,
+            make_deposit: false,
+            deposit: None,
+            extend_timeout: None,
+        }
+    }
+
+    async fn create(db: &DbExecutor, platform: &str, amount: u32) -> Result<String, LimitError> {
+        db.as_dao::<AllocationDao>()
+            .create_for_app_key(
+                new_allocation(amount),
+                owner(),
+                platform.to_string(),
+                owner().to_string(),
+                APP_KEY.to_string(),
+            )
+            .await
+    }
+
+    async fn db_with_limits(name: &str) -> DbExecutor {
+        let db = DbExecutor::in_memory(name).unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        db.as_dao::<AppKeyLimitDao>()
+            .set(
+                owner(),
+                APP_KEY.to_string(),
+                AppKeyLimits {
+                    total: Some(10.into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_limits_per_platform() {
+        let db = db_with_limits("app_key_limit_per_platform").await;
+
+        create(&db, "erc20-holesky-tglm", 6).await.unwrap();
+        assert!(matches!(
+            create(&db, "erc20-holesky-tglm", 5).await,
+            Err(LimitError::Exceeded { .. })
+        ));
+        create(&db, "erc20-polygon-glm", 5).await.unwrap();
+
+        let allocated = db
+            .as_dao::<AppKeyLimitDao>()
+            .allocated(owner(), APP_KEY.to_string(), Utc::now().naive_utc())
+            .await
+            .unwrap();
+        assert_eq!(allocated.len(), 2);
+        assert_eq!(allocated[0].platform, "erc20-holesky-tglm");
+        assert_eq!(allocated[0].total, 6.into());
+        assert_eq!(allocated[0].daily, 0.into());
+        assert_eq!(allocated[1].platform, "erc20-polygon-glm");
+        assert_eq!(allocated[1].total, 5.into());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_allocations() {
+        let db = db_with_limits("app_key_limit_concurrent").await;
+
+        let (first, second) = futures::join!(
+            create(&db, "erc20-holesky-tglm", 6),
+            create(&db, "erc20-holesky-tglm", 6),
+        );
+        assert_eq!(first.is_ok() as u8 + second.is_ok() as u8, 1);
+    }
+
+    #[tokio::test]
+    async fn test_amend_allocation() {
+        let db = db_with_limits("app_key_limit_amend").await;
+        let dao = db.as_dao::<AllocationDao>();
+
+        let allocation_id = create(&db, "erc20-holesky-tglm", 6).await.unwrap();
+        let allocation = match dao.get(allocation_id, owner()).await.unwrap() {
+            crate::dao::AllocationStatus::Active(allocation) => allocation,
+            _ => panic!("allocation not found"),
+        };
+
+        // amended allocation doesn't count towards the limit
+        let amended = |amount: u32| ya_client_model::payment::Allocation {
+            total_amount: amount.into(),
+            remaining_amount: amount.into(),
+            ..allocation.clone()
+        };
+        assert!(dao
+            .replace_for_app_key(amended(10), owner(), APP_KEY.to_string())
+            .await
+            .unwrap());
+        assert!(matches!(
+            dao.replace_for_app_key(amended(11), owner(), APP_KEY.to_string())
+                .await,
+            Err(LimitError::Exceeded { .. })
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::error::DbResult;
+use chrono::NaiveDateTime;
+use diesel::sql_types::Timestamp;
+use diesel::{self, RunQueryDsl};
+use ya_core_model::payment::local::ArchiveStats;
+use ya_persistence::executor::{do_with_transaction, AsDao, ConnType, PoolType};
+
+/// Agreements which are fully settled before the cutoff: all their invoices are settled,
+/// or, if there is no invoice, all their debit notes are settled. A document counts as
+/// settled at the time of its SETTLED event, not at the time it was issued.
+const ARCHIVED_AGREEMENTS: &str = r#"
+    CREATE TEMP TABLE archived_agreement AS
+    SELECT a.owner_id, a.id FROM pay_agreement a
+    WHERE NOT EXISTS (
+        SELECT 1 FROM pay_invoice i
+        WHERE i.owner_id = a.owner_id AND i.agreement_id = a.id
+            AND (i.status <> 'SETTLED' OR NOT EXISTS (
+                SELECT 1 FROM pay_invoice_event e
+                WHERE e.owner_id = i.owner_id AND e.invoice_id = i.id
+                    AND e.event_type = 'SETTLED' AND e.timestamp < ?1
+            ))
+    ) AND NOT EXISTS (
+        SELECT 1 FROM pay_debit_note d
+        JOIN pay_activity act ON act.owner_id = d.owner_id AND act.id = d.activity_id
+        WHERE act.owner_id = a.owner_id AND act.agreement_id = a.id AND d.timestamp >= ?1
+    ) AND (
+        EXISTS (
+            SELECT 1 FROM pay_invoice i
+            WHERE i.owner_id = a.owner_id AND i.agreement_id = a.id
+        ) OR (
+            EXISTS (
+                SELECT 1 FROM pay_debit_note d
+                JOIN pay_activity act ON act.owner_id = d.owner_id AND act.id = d.activity_id
+                WHERE act.owner_id = a.owner_id AND act.agreement_id = a.id
+            ) AND NOT EXISTS (
+                SELECT 1 FROM pay_debit_note d
+                JOIN pay_activity act ON act.owner_id = d.owner_id AND act.id = d.activity_id
+                WHERE act.owner_id = a.owner_id AND act.agreement_id = a.id
+                    AND (d.status <> 'SETTLED' OR NOT EXISTS (
+                        SELECT 1 FROM pay_debit_note_event e
+                        WHERE e.owner_id = d.owner_id AND e.debit_note_id = d.id
+                            AND e.event_type = 'SETTLED' AND e.timestamp < ?1
+                    ))
+            )
+        )
+    )
+"#;
+
+const ARCHIVED_DOCUMENTS: &[&str] = &[
+    r#"
+    CREATE TEMP TABLE archived_invoice AS
+    SELECT i.owner_id, i.id FROM pay_invoice i
+    JOIN archived_agreement a ON a.owner_id = i.owner_id AND a.id = i.agreement_id
+    "#,
+    r#"
+    CREATE TEMP TABLE archived_debit_note AS
+    SELECT d.owner_id, d.id FROM pay_debit_note d
+    JOIN pay_activity act ON act.owner_id = d.owner_id AND act.id = d.activity_id
+    JOIN archived_agreement a ON a.owner_id = act.owner_id AND a.id = act.agreement_id
+    "#,
+];
+
+/// Payments made before the cutoff, which paid for archived agreements only.
+const ARCHIVED_PAYMENTS: &str = r#"
+    CREATE TEMP TABLE archived_payment AS
+    SELECT p.owner_id, p.id FROM pay_payment p
+    WHERE p.timestamp < ?1
+    AND (
+        EXISTS (
+            SELECT 1 FROM pay_activity_payment ap
+            WHERE ap.owner_id = p.owner_id AND ap.payment_id = p.id
+        ) OR EXISTS (
+            SELECT 1 FROM pay_agreement_payment ap
+            WHERE ap.owner_id = p.owner_id AND ap.payment_id = p.id
+        )
+    ) AND NOT EXISTS (
+        SELECT 1 FROM pay_activity_payment ap
+        JOIN pay_activity act ON act.owner_id = ap.owner_id AND act.id = ap.activity_id
+        WHERE ap.owner_id = p.owner_id AND ap.payment_id = p.id
+            AND (act.owner_id, act.agreement_id) NOT IN (SELECT owner_id, id FROM archived_agreement)
+    ) AND NOT EXISTS (
+        SELECT 1 FROM pay_agreement_payment ap
+        WHERE ap.owner_id = p.owner_id AND ap.payment_id = p.id
+            AND (ap.owner_id, ap.agreement_id) NOT IN (SELECT owner_id, id FROM archived_agreement)
+    )
+"#;
+
+const COPY_TO_ARCHIVE: &[&str] = &[
+    r#"
+    INSERT INTO pay_invoice_archive
+        (id, owner_id, role, agreement_id, status, timestamp, amount, payment_due_date)
+    SELECT id, owner_id, role, agreement_id, status, timestamp, amount, payment_due_date
+    FROM pay_invoice WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_invoice)
+    "#,
+    r#"
+    INSERT INTO pay_invoice_event_archive
+        (invoice_id, owner_id, event_type, timestamp, details)
+    SELECT invoice_id, owner_id, event_type, timestamp, details
+    FROM pay_invoice_event WHERE (owner_id, invoice_id) IN (SELECT owner_id, id FROM archived_invoice)
+    "#,
+    r#"
+    INSERT INTO pay_debit_note_archive
+        (id, owner_id, role, previous_debit_note_id, activity_id, status, timestamp,
+         total_amount_due, usage_counter_vector, payment_due_date)
+    SELECT id, owner_id, role, previous_debit_note_id, activity_id, status, timestamp,
+         total_amount_due, usage_counter_vector, payment_due_date
+    FROM pay_debit_note WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_debit_note)
+    "#,
+    r#"
+    INSERT INTO pay_debit_note_event_archive
+        (debit_note_id, owner_id, event_type, timestamp, details)
+    SELECT debit_note_id, owner_id, event_type, timestamp, details
+    FROM pay_debit_note_event WHERE (owner_id, debit_note_id) IN (SELECT owner_id, id FROM archived_debit_note)
+    "#,
+    r#"
+    INSERT INTO pay_payment_archive
+        (id, owner_id, peer_id, payee_addr, payer_addr, payment_platform, role, amount,
+         timestamp, details, signature, signed_bytes)
+    SELECT id, owner_id, peer_id, payee_addr, payer_addr, payment_platform, role, amount,
+         timestamp, details, signature, signed_bytes
+    FROM pay_payment WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_payment)
+    "#,
+    r#"
+    INSERT INTO pay_activity_payment_archive
+        (payment_id, activity_id, owner_id, amount, allocation_id)
+    SELECT payment_id, activity_id, owner_id, amount, allocation_id
+    FROM pay_activity_payment WHERE (owner_id, payment_id) IN (SELECT owner_id, id FROM archived_payment)
+    "#,
+    r#"
+    INSERT INTO pay_agreement_payment_archive
+        (payment_id, agreement_id, owner_id, amount, allocation_id)
+    SELECT payment_id, agreement_id, owner_id, amount, allocation_id
+    FROM pay_agreement_payment WHERE (owner_id, payment_id) IN (SELECT owner_id, id FROM archived_payment)
+    "#,
+];
+
+/// Orders and disputes of archived documents are dropped without archiving.
+const PRUNE_INVOICES: &[&str] = &[
+    "DELETE FROM pay_order WHERE (payer_id, invoice_id) IN (SELECT owner_id, id FROM archived_invoice)",
+    "DELETE FROM pay_invoice_dispute WHERE (owner_id, invoice_id) IN (SELECT owner_id, id FROM archived_invoice)",
+    "DELETE FROM pay_invoice_event WHERE (owner_id, invoice_id) IN (SELECT owner_id, id FROM archived_invoice)",
+    "DELETE FROM pay_invoice_x_activity WHERE (owner_id, invoice_id) IN (SELECT owner_id, id FROM archived_invoice)",
+];
+const PRUNE_DEBIT_NOTES: &[&str] = &[
+    "DELETE FROM pay_order WHERE (payer_id, debit_note_id) IN (SELECT owner_id, id FROM archived_debit_note)",
+    "DELETE FROM pay_debit_note_event WHERE (owner_id, debit_note_id) IN (SELECT owner_id, id FROM archived_debit_note)",
+];
+const PRUNE_PAYMENTS: &[&str] = &[
+    "DELETE FROM pay_activity_payment WHERE (owner_id, payment_id) IN (SELECT owner_id, id FROM archived_payment)",
+    "DELETE FROM pay_agreement_payment WHERE (owner_id, payment_id) IN (SELECT owner_id, id FROM archived_payment)",
+];
+
+const TEMP_TABLES: &[&str] = &[
+    "archived_agreement",
+    "archived_invoice",
+    "archived_debit_note",
+    "archived_payment",
+];
+
+fn drop_temp_tables(conn: &ConnType) -> DbResult<()> {
+    for table in TEMP_TABLES {
+        diesel::sql_query(format!("DROP TABLE IF EXISTS temp.{}", table)).execute(conn)?;
+    }
+    Ok(())
+}
+
+fn execute_all(conn: &ConnType, statements: &[&str]) -> DbResult<()> {
+    for statement in statements {
+        diesel::sql_query(*statement).execute(conn)?;
+    }
+    Ok(())
+}
+
+pub struct ArchiveDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for ArchiveDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> ArchiveDao<'c> {
+    /// Moves documents and payments of agreements settled before `cutoff` to archive tables.
+    /// Agreements and activities stay in working tables, since they keep the settled totals.
+    pub async fn archive(&self, cutoff: NaiveDateTime) -> DbResult<ArchiveStats> {
+        do_with_transaction(self.pool, "archive_dao_archive", move |conn| {
+            drop_temp_tables(conn)?;
+            diesel::sql_query(ARCHIVED_AGREEMENTS)
+                .bind::<Timestamp, _>(cutoff)
+                .execute(conn)?;
+            execute_all(conn, ARCHIVED_DOCUMENTS)?;
+            diesel::sql_query(ARCHIVED_PAYMENTS)
+                .bind::<Timestamp, _>(cutoff)
+                .execute(conn)?;
+            execute_all(conn, COPY_TO_ARCHIVE)?;
+
+            execute_all(conn, PRUNE_INVOICES)?;
+            let invoices = diesel::sql_query(
+                "DELETE FROM pay_invoice WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_invoice)",
+            )
+            .execute(conn)?;
+            execute_all(conn, PRUNE_DEBIT_NOTES)?;
+            let debit_notes = diesel::sql_query(
+                "DELETE FROM pay_debit_note WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_debit_note)",
+            )
+            .execute(conn)?;
+            execute_all(conn, PRUNE_PAYMENTS)?;
+            let payments = diesel::sql_query(
+                "DELETE FROM pay_payment WHERE (owner_id, id) IN (SELECT owner_id, id FROM archived_payment)",
+            )
+            .execute(conn)?;
+            // Allocation events are not archived, they only matter until delivered.
+            let allocation_events =
+                diesel::sql_query("DELETE FROM pay_allocation_event WHERE timestamp < ?")
+                    .bind::<Timestamp, _>(cutoff)
+                    .execute(conn)?;
+
+            drop_temp_tables(conn)?;
+            Ok(ArchiveStats {
+                invoices: invoices as u64,
+                debit_notes: debit_notes as u64,
+                payments: payments as u64,
+                allocation_events: allocation_events as u64,
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::fixtures::{self, OWNER, PEER, PLATFORM};
+
+    fn agreement(id: &str) -> String {
+        fixtures::agreement(id, "P", PEER)
+    }
+
+    fn invoice(id: &str, agreement_id: &str, status: &str, timestamp: &str) -> String {
+        fixtures::invoice(id, agreement_id, status, 1, timestamp, timestamp)
+    }
+
+    fn settled_invoice(id: &str, agreement_id: &str, issued: &str, settled: &str) -> String {
+        let invoice = invoice(id, agreement_id, "SETTLED", issued);
+        format!(
+            "{invoice} \
+             INSERT INTO pay_invoice_event (invoice_id, owner_id, event_type, timestamp) \
+             VALUES ('{id}', '{OWNER}', 'SETTLED', '{settled}');"
+        )
+    }
+
+    /// Activity of the agreement with a single debit note.
+    fn settled_debit_note(agreement_id: &str, issued: &str, settled: &str) -> String {
+        format!(
+            "INSERT INTO pay_activity (id, owner_id, role, agreement_id, total_amount_due, \
+                total_amount_accepted, total_amount_scheduled, total_amount_paid) \
+             VALUES ('activity-{agreement_id}', '{OWNER}', 'P', '{agreement_id}', '1', '1', \
+                '1', '1'); \
+             INSERT INTO pay_debit_note (id, owner_id, role, activity_id, status, timestamp, \
+                total_amount_due) \
+             VALUES ('debit-note-{agreement_id}', '{OWNER}', 'P', 'activity-{agreement_id}', \
+                'SETTLED', '{issued}', '1'); \
+             INSERT INTO pay_debit_note_event (debit_note_id, owner_id, event_type, timestamp) \
+             VALUES ('debit-note-{agreement_id}', '{OWNER}', 'SETTLED', '{settled}');"
+        )
+    }
+
+    fn payment(id: &str, agreement_id: &str, timestamp: &str) -> String {
+        format!(
+            "INSERT INTO pay_payment (id, owner_id, peer_id, payee_addr, payer_addr, \
+                payment_platform, role, amount, timestamp, details) \
+             VALUES ('{id}', '{OWNER}', '{PEER}', 'payee', 'payer', '{PLATFORM}', 'P', '1', \
+                '{timestamp}', x'00'); \
+             INSERT INTO pay_agreement_payment (payment_id, agreement_id, owner_id, amount) \
+             VALUES ('{id}', '{agreement_id}', '{OWNER}', '1');"
+        )
+    }
+
+    fn allocation_event(allocation_id: &str, timestamp: &str) -> String {
+        format!(
+            "INSERT INTO pay_allocation_event (allocation_id, owner_id, event_type, details, \
+                timestamp) \
+             VALUES ('{allocation_id}', '{OWNER}', 'AllocationReleasedEvent', '{{}}', \
+                '{timestamp}');"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_archive_settled_agreements() {
+        let old = "2023-01-01 00:00:00.000";
+        let new = "2024-06-01 00:00:00.000";
+        let db = fixtures::db_with(
+            "archive_dao_test",
+            &[
+                agreement("settled"),
+                settled_invoice("settled-invoice", "settled", old, old),
+                payment("settled-payment", "settled", old),
+                agreement("accepted"),
+                invoice("accepted-invoice", "accepted", "ACCEPTED", old),
+                agreement("recent"),
+                settled_invoice("recent-invoice", "recent", new, new),
+                payment("recent-payment", "recent", new),
+                // Issued before the cutoff, but settled after it
+                agreement("settled-late"),
+                settled_invoice("settled-late-invoice", "settled-late", old, new),
+                payment("settled-late-payment", "settled-late", new),
+                agreement("debit-notes"),
+                settled_debit_note("debit-notes", old, old),
+                agreement("debit-notes-settled-late"),
+                settled_debit_note("debit-notes-settled-late", old, new),
+                allocation_event("old-allocation", old),
+                allocation_event("new-allocation", new),
+            ],
+        );
+
+        let cutoff = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let stats = db.as_dao::<ArchiveDao>().archive(cutoff).await.unwrap();
+        assert_eq!(
+            stats,
+            ArchiveStats {
+                invoices: 1,
+                debit_notes: 1,
+                payments: 1,
+                allocation_events: 1,
+            }
+        );
+
+        // Nothing left to archive.
+        let stats = db.as_dao::<ArchiveDao>().archive(cutoff).await.unwrap();
+        assert_eq!(stats, ArchiveStats::default());
+    }
+}
@@ -4,6 +4,7 @@ use crate::models::debit_note::{ReadObj, WriteObj};
 use crate::schema::pay_activity::dsl as activity_dsl;
 use crate::schema::pay_agreement::dsl as agreement_dsl;
 use crate::schema::pay_debit_note::dsl;
+use crate::schema::pay_order::dsl as order_dsl;
 use bigdecimal::BigDecimal;
 use chrono::NaiveDateTime;
 use diesel::{
@@ -249,6 +250,44 @@ impl<'c> DebitNoteDao<'c> {
         .await
     }
 
+    /// Returns timestamp of the last Debit Note of the Activity, which payment was scheduled for,
+    /// or timestamp of the first Debit Note, if no payment was scheduled yet.
+    pub async fn last_settlement_timestamp(
+        &self,
+        activity_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<NaiveDateTime>> {
+        readonly_transaction(
+            self.pool,
+            "debit_note_dao_last_settlement_timestamp",
+            move |conn| {
+                let settled = order_dsl::pay_order
+                    .filter(order_dsl::payer_id.eq(owner_id))
+                    .select(order_dsl::debit_note_id);
+                let last_settled: Option<NaiveDateTime> = dsl::pay_debit_note
+                    .filter(dsl::activity_id.eq(&activity_id))
+                    .filter(dsl::owner_id.eq(owner_id))
+                    .filter(dsl::id.nullable().eq_any(settled))
+                    .select(dsl::timestamp)
+                    .order_by(dsl::timestamp.desc())
+                    .first(conn)
+                    .optional()?;
+                if last_settled.is_some() {
+                    return Ok(last_settled);
+                }
+
+                Ok(dsl::pay_debit_note
+                    .filter(dsl::activity_id.eq(&activity_id))
+                    .filter(dsl::owner_id.eq(owner_id))
+                    .select(dsl::timestamp)
+                    .order_by(dsl::timestamp.asc())
+                    .first(conn)
+                    .optional()?)
+            },
+        )
+        .await
+    }
+
     pub async fn mark_received(&self, debit_note_id: String, owner_id: NodeId) -> DbResult<()> {
         do_with_transaction(self.pool, "debit_note_dao_mark_received", move |conn| {
             diesel::update(dsl::pay_debit_note.find((debit_note_id, owner_id)))
@@ -417,3 +456,74 @@ impl<'c> DebitNoteDao<'c> {
     //     .await
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::fixtures::{self, OWNER, PEER, PLATFORM};
+    use ya_persistence::executor::DbExecutor;
+
+    fn debit_note(id: &str, activity_id: &str, timestamp: &str) -> String {
+        format!(
+            "INSERT INTO pay_debit_note (id, owner_id, role, activity_id, status, timestamp, \
+                total_amount_due) \
+             VALUES ('{id}', '{OWNER}', 'R', '{activity_id}', 'ACCEPTED', '{timestamp}', '1');"
+        )
+    }
+
+    fn order(debit_note_id: &str) -> String {
+        format!(
+            "INSERT INTO pay_order (id, driver, amount, payee_id, payer_id, payee_addr, \
+                payer_addr, payment_platform, debit_note_id, allocation_id) \
+             VALUES ('order-{debit_note_id}', 'erc20', '1', '{PEER}', '{OWNER}', 'payee', \
+                'payer', '{PLATFORM}', '{debit_note_id}', 'allocation');"
+        )
+    }
+
+    async fn last_settlement(db: &DbExecutor, activity_id: &str) -> Option<String> {
+        db.as_dao::<DebitNoteDao>()
+            .last_settlement_timestamp(activity_id.to_string(), OWNER.parse().unwrap())
+            .await
+            .unwrap()
+            .map(|timestamp| timestamp.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_last_settlement_timestamp() {
+        let db = fixtures::db_with(
+            "debit_note_last_settlement",
+            &[
+                debit_note("dn-1", "activity-1", "2024-06-01 12:00:00"),
+                debit_note("dn-2", "activity-1", "2024-06-01 12:10:00"),
+                debit_note("dn-3", "activity-1", "2024-06-01 12:20:00"),
+                debit_note("dn-4", "activity-2", "2024-06-01 12:30:00"),
+                order("dn-1"),
+                order("dn-2"),
+                // Other activity settled later
+                order("dn-4"),
+            ],
+        );
+
+        assert_eq!(
+            last_settlement(&db, "activity-1").await.as_deref(),
+            Some("2024-06-01 12:10:00")
+        );
+        assert_eq!(last_settlement(&db, "missing").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_last_settlement_timestamp_falls_back_to_first_debit_note() {
+        let db = fixtures::db_with(
+            "debit_note_first_settlement",
+            &[
+                debit_note("dn-1", "activity-1", "2024-06-01 12:00:00"),
+                debit_note("dn-2", "activity-1", "2024-06-01 12:10:00"),
+            ],
+        );
+
+        assert_eq!(
+            last_settlement(&db, "activity-1").await.as_deref(),
+            Some("2024-06-01 12:00:00")
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Payment database populated with plain SQL, for testing queries on states
+//! which are tedious to reach through the DAOs.
+use diesel::connection::SimpleConnection;
+use diesel_migrations::RunMigrationsError;
+use ya_persistence::executor::DbExecutor;
+
+pub const OWNER: &str = "0x0000000000000000000000000000000000000001";
+pub const PEER: &str = "0x0000000000000000000000000000000000000002";
+pub const PLATFORM: &str = "erc20-holesky-tglm";
+
+/// In-memory database with all migrations applied and `fixtures` executed.
+pub fn db_with(name: &str, fixtures: &[String]) -> DbExecutor {
+    let db = DbExecutor::in_memory(name).unwrap();
+    db.apply_migration(crate::migrations::run_with_output)
+        .unwrap();
+    let fixtures = fixtures.concat();
+    db.apply_migration(|conn, _| {
+        conn.batch_execute(&fixtures)
+            .map_err(RunMigrationsError::QueryError)
+    })
+    .unwrap();
+    db
+}
+
+/// Agreement of `OWNER` acting as `role` ('P' or 'R').
+pub fn agreement(id: &str, role: &str, peer_id: &str) -> String {
+    format!(
+        "INSERT INTO pay_agreement (id, owner_id, role, peer_id, payee_addr, payer_addr, \
+            payment_platform, total_amount_due, total_amount_accepted, total_amount_scheduled, \
+            total_amount_paid) \
+         VALUES ('{id}', '{OWNER}', '{role}', '{peer_id}', 'payee', 'payer', '{PLATFORM}', \
+            '0', '0', '0', '0');"
+    )
+}
+
+/// Invoice issued by `OWNER` as a Provider.
+pub fn invoice(
+    id: &str,
+    agreement_id: &str,
+    status: &str,
+    amount: u32,
+    timestamp: &str,
+    payment_due_date: &str,
+) -> String {
+    format!(
+        "INSERT INTO pay_invoice (id, owner_id, role, agreement_id, status, timestamp, amount, \
+            payment_due_date) \
+         VALUES ('{id}', '{OWNER}', 'P', '{agreement_id}', '{status}', '{timestamp}', \
+            '{amount}', '{payment_due_date}');"
+    )
+}
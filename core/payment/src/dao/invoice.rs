@@ -1,19 +1,20 @@
-use crate::dao::{agreement, invoice_event};
+use crate::dao::{agreement, invoice_dispute, invoice_event};
 use crate::error::{DbError, DbResult};
 use crate::models::invoice::{equivalent, InvoiceXActivity, ReadObj, WriteObj};
 use crate::schema::pay_agreement::dsl as agreement_dsl;
 use crate::schema::pay_invoice::dsl;
 use crate::schema::pay_invoice_x_activity::dsl as activity_dsl;
 use bigdecimal::{BigDecimal, Zero};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use diesel::{
     BoolExpressionMethods, ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl, RunQueryDsl,
 };
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
 use ya_client_model::payment::{DocumentStatus, Invoice, InvoiceEventType, NewInvoice, Rejection};
 use ya_client_model::NodeId;
-use ya_core_model::payment::local::StatValue;
+use ya_core_model::payment::local::{RevenueStats, StatValue};
+use ya_core_model::payment::public::DisputeReason;
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -295,6 +296,69 @@ impl<'c> InvoiceDao<'c> {
         Ok(stats)
     }
 
+    pub async fn revenue_stats(
+        &self,
+        node_id: NodeId,
+        since: DateTime<Utc>,
+    ) -> DbResult<Vec<RevenueStats>> {
+        // Invoices owed by Requestors: delivered and not rejected
+        let statuses = vec![
+            DocumentStatus::Received.to_string(),
+            DocumentStatus::Accepted.to_string(),
+            DocumentStatus::Settled.to_string(),
+        ];
+        let results = readonly_transaction(self.pool, "invoice_dao_revenue_stats", move |conn| {
+            let invoices: Vec<ReadObj> = query!()
+                .filter(dsl::owner_id.eq(node_id))
+                .filter(dsl::status.eq_any(statuses))
+                .filter(dsl::role.eq(Role::Provider.to_string()))
+                .filter(dsl::timestamp.gt(since.naive_utc()))
+                .load(conn)?;
+            Ok::<_, DbError>(invoices)
+        })
+        .await?;
+
+        let now = Utc::now().naive_utc();
+        let mut stats = BTreeMap::<(NaiveDate, String), RevenueStats>::new();
+        let mut delinquents = HashMap::<(NaiveDate, String), HashSet<NodeId>>::new();
+        for invoice in results {
+            let status = DocumentStatus::try_from(invoice.status)?;
+            let key = (invoice.timestamp.date(), invoice.payment_platform);
+            let entry = stats
+                .entry(key.clone())
+                .or_insert_with(|| RevenueStats::new(key.0, key.1.clone()));
+            let value = StatValue {
+                total_amount: invoice.amount.0,
+                agreements_count: 1,
+            };
+
+            entry.invoiced += value.clone();
+            match status {
+                DocumentStatus::Accepted => entry.accepted += value.clone(),
+                DocumentStatus::Settled => {
+                    entry.accepted += value.clone();
+                    entry.settled += value.clone();
+                }
+                _ => (),
+            }
+            if status != DocumentStatus::Settled && invoice.payment_due_date < now {
+                entry.overdue += value;
+                delinquents.entry(key).or_default().insert(invoice.peer_id);
+            }
+        }
+
+        Ok(stats
+            .into_iter()
+            .map(|(key, mut entry)| {
+                entry.delinquent_requestors = delinquents
+                    .get(&key)
+                    .map(|ids| ids.len() as u64)
+                    .unwrap_or(0);
+                entry
+            })
+            .collect())
+    }
+
     pub async fn mark_received(&self, invoice_id: String, owner_id: NodeId) -> DbResult<()> {
         do_with_transaction(self.pool, "invoice_dao_mark_received", move |conn| {
             update_status(&invoice_id, &owner_id, &DocumentStatus::Received, conn)
@@ -421,11 +485,13 @@ impl<'c> InvoiceDao<'c> {
         .await
     }
 
+    /// Rejects the Invoice, opening a dispute if the reason is given.
     pub async fn reject(
         &self,
         invoice_id: String,
         owner_id: NodeId,
         rejection: Rejection,
+        dispute: Option<DisputeReason>,
     ) -> DbResult<()> {
         do_with_transaction(self.pool, "invoice_reject", move |conn| {
             let (agreement_id, amount, role): (String, BigDecimalField, Role) = dsl::pay_invoice
@@ -442,6 +508,15 @@ impl<'c> InvoiceDao<'c> {
                 .set(dsl::send_reject.eq(true))
                 .execute(conn)?;
             }
+            if let Some(reason) = dispute {
+                invoice_dispute::create(
+                    invoice_id.clone(),
+                    owner_id,
+                    &reason,
+                    rejection.message.clone(),
+                    conn,
+                )?;
+            }
             invoice_event::create(
                 invoice_id,
                 owner_id,
@@ -554,3 +629,84 @@ fn join_invoices_with_activities(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::fixtures::{self, agreement, OWNER, PEER};
+    use ya_persistence::executor::DbExecutor;
+
+    const OTHER_PEER: &str = "0x0000000000000000000000000000000000000003";
+
+    fn invoice(id: &str, agreement_id: &str, status: &str, amount: u32, due: &str) -> String {
+        fixtures::invoice(
+            id,
+            agreement_id,
+            status,
+            amount,
+            "2024-06-01 12:00:00.000",
+            due,
+        )
+    }
+
+    fn db_with_invoices(name: &str) -> DbExecutor {
+        const PAST: &str = "2024-06-02 00:00:00.000";
+        const FUTURE: &str = "2999-01-01 00:00:00.000";
+
+        fixtures::db_with(
+            name,
+            &[
+                agreement("agreement-1", "P", PEER),
+                agreement("agreement-2", "P", OTHER_PEER),
+                invoice("received", "agreement-1", "RECEIVED", 1, PAST),
+                invoice("accepted", "agreement-2", "ACCEPTED", 2, PAST),
+                invoice("settled", "agreement-1", "SETTLED", 4, PAST),
+                invoice("not-due", "agreement-1", "RECEIVED", 8, FUTURE),
+                invoice("rejected", "agreement-2", "REJECTED", 16, PAST),
+                invoice("failed", "agreement-2", "FAILED", 32, PAST),
+                invoice("issued", "agreement-2", "ISSUED", 64, PAST),
+                invoice("cancelled", "agreement-2", "CANCELLED", 128, PAST),
+            ],
+        )
+    }
+
+    fn assert_stat(value: &StatValue, amount: u32, count: u64) {
+        assert_eq!(value.total_amount, BigDecimal::from(amount));
+        assert_eq!(value.agreements_count, count);
+    }
+
+    #[tokio::test]
+    async fn test_revenue_stats_counts_only_owed_invoices() {
+        let db = db_with_invoices("invoice_revenue_stats");
+        let since = "2024-01-01T00:00:00Z".parse().unwrap();
+
+        let stats = db
+            .as_dao::<InvoiceDao>()
+            .revenue_stats(OWNER.parse().unwrap(), since)
+            .await
+            .unwrap();
+        assert_eq!(stats.len(), 1);
+        let stats = &stats[0];
+        assert_eq!(stats.platform, "erc20-holesky-tglm");
+
+        // Rejected, failed, not sent and cancelled Invoices aren't owed
+        assert_stat(&stats.invoiced, 1 + 2 + 4 + 8, 4);
+        assert_stat(&stats.accepted, 2 + 4, 2);
+        assert_stat(&stats.settled, 4, 1);
+        assert_stat(&stats.overdue, 1 + 2, 2);
+        assert_eq!(stats.delinquent_requestors, 2);
+    }
+
+    #[tokio::test]
+    async fn test_revenue_stats_since() {
+        let db = db_with_invoices("invoice_revenue_stats_since");
+        let since = "2024-06-02T00:00:00Z".parse().unwrap();
+
+        let stats = db
+            .as_dao::<InvoiceDao>()
+            .revenue_stats(OWNER.parse().unwrap(), since)
+            .await
+            .unwrap();
+        assert!(stats.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::error::DbResult;
+use crate::models::invoice_dispute::{InvoiceDispute, ReadObj, WriteObj};
+use crate::schema::pay_invoice_dispute::dsl;
+use chrono::Utc;
+use diesel::{self, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use std::convert::TryInto;
+use ya_client_model::NodeId;
+use ya_core_model::payment::public::DisputeReason;
+use ya_persistence::executor::{
+    do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
+};
+
+/// Opens a dispute. Repeated calls (e.g. on sync) keep the existing record,
+/// so a dispute already resolved is not reopened.
+pub fn create(
+    invoice_id: String,
+    owner_id: NodeId,
+    reason: &DisputeReason,
+    message: Option<String>,
+    conn: &ConnType,
+) -> DbResult<()> {
+    let dispute = WriteObj::new(invoice_id, owner_id, reason, message)?;
+    diesel::insert_or_ignore_into(dsl::pay_invoice_dispute)
+        .values(dispute)
+        .execute(conn)?;
+    Ok(())
+}
+
+pub struct InvoiceDisputeDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for InvoiceDisputeDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> InvoiceDisputeDao<'c> {
+    pub async fn create(
+        &self,
+        invoice_id: String,
+        owner_id: NodeId,
+        reason: DisputeReason,
+        message: Option<String>,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, "invoice_dispute_dao_create", move |conn| {
+            create(invoice_id, owner_id, &reason, message, conn)
+        })
+        .await
+    }
+
+    pub async fn get(
+        &self,
+        invoice_id: String,
+        owner_id: NodeId,
+    ) -> DbResult<Option<InvoiceDispute>> {
+        readonly_transaction(self.pool, "invoice_dispute_dao_get", move |conn| {
+            let dispute: Option<ReadObj> = dsl::pay_invoice_dispute
+                .find((owner_id, invoice_id))
+                .first(conn)
+                .optional()?;
+            dispute.map(TryInto::try_into).transpose()
+        })
+        .await
+    }
+
+    pub async fn resolve(
+        &self,
+        invoice_id: String,
+        owner_id: NodeId,
+        corrected_invoice_id: String,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, "invoice_dispute_dao_resolve", move |conn| {
+            diesel::update(dsl::pay_invoice_dispute.find((owner_id, invoice_id)))
+                .set((
+                    dsl::corrected_invoice_id.eq(corrected_invoice_id),
+                    dsl::resolved_ts.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::fixtures::{self, agreement, invoice, OWNER, PEER};
+    use crate::dao::InvoiceDao;
+    use ya_client_model::payment::{DocumentStatus, Rejection, RejectionReason};
+    use ya_persistence::executor::DbExecutor;
+
+    fn owner() -> NodeId {
+        OWNER.parse().unwrap()
+    }
+
+    fn db_with_invoice(name: &str) -> DbExecutor {
+        let timestamp = "2024-06-01 00:00:00.000";
+        fixtures::db_with(
+            name,
+            &[
+                agreement("agreement", "P", PEER),
+                invoice("invoice", "agreement", "RECEIVED", 1, timestamp, timestamp),
+            ],
+        )
+    }
+
+    fn rejection() -> Rejection {
+        Rejection {
+            rejection_reason: RejectionReason::IncorrectAmount,
+            total_amount_accepted: 0.into(),
+            message: Some("too many hours".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_opens_dispute() {
+        let db = db_with_invoice("invoice_dispute_reject");
+
+        db.as_dao::<InvoiceDao>()
+            .reject(
+                "invoice".to_string(),
+                owner(),
+                rejection(),
+                Some(DisputeReason::UsageMismatch),
+            )
+            .await
+            .unwrap();
+
+        let invoice = db
+            .as_dao::<InvoiceDao>()
+            .get("invoice".to_string(), owner())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(invoice.status, DocumentStatus::Rejected);
+
+        let dispute = db
+            .as_dao::<InvoiceDisputeDao>()
+            .get("invoice".to_string(), owner())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dispute.reason, DisputeReason::UsageMismatch);
+        assert_eq!(dispute.message.as_deref(), Some("too many hours"));
+        assert_eq!(dispute.corrected_invoice_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_reject_without_dispute() {
+        let db = db_with_invoice("invoice_dispute_plain_reject");
+
+        db.as_dao::<InvoiceDao>()
+            .reject("invoice".to_string(), owner(), rejection(), None)
+            .await
+            .unwrap();
+
+        let dispute = db
+            .as_dao::<InvoiceDisputeDao>()
+            .get("invoice".to_string(), owner())
+            .await
+            .unwrap();
+        assert_eq!(dispute, None);
+    }
+
+    /// Dispute delivered again (e.g. by payment sync) doesn't reopen a resolved one.
+    #[tokio::test]
+    async fn test_create_keeps_existing_dispute() {
+        let db = db_with_invoice("invoice_dispute_create_twice");
+        let dao = db.as_dao::<InvoiceDisputeDao>();
+
+        dao.create(
+            "invoice".to_string(),
+            owner(),
+            DisputeReason::PriceMismatch,
+            None,
+        )
+        .await
+        .unwrap();
+        dao.resolve("invoice".to_string(), owner(), "corrected".to_string())
+            .await
+            .unwrap();
+        dao.create(
+            "invoice".to_string(),
+            owner(),
+            DisputeReason::UsageMismatch,
+            Some("again".to_string()),
+        )
+        .await
+        .unwrap();
+
+        let dispute = dao
+            .get("invoice".to_string(), owner())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dispute.reason, DisputeReason::PriceMismatch);
+        assert_eq!(dispute.message, None);
+        assert_eq!(dispute.corrected_invoice_id.as_deref(), Some("corrected"));
+        assert!(dispute.resolved_ts.is_some());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::dao::{activity, agreement, allocation};
+use crate::allocation_events::ALLOCATION_EVENTS_NOTIFY;
+use crate::dao::{activity, agreement, allocation, allocation_event};
 use crate::error::DbResult;
 use crate::models::order::{ReadObj, WriteObj};
 use crate::schema::pay_debit_note::dsl as debit_note_dsl;
@@ -9,9 +10,10 @@ use diesel::{
     RunQueryDsl,
 };
 use ya_core_model::payment::local::{
-    DebitNotePayment, InvoicePayment, PaymentTitle, SchedulePayment,
+    AllocationEventType, DebitNotePayment, InvoicePayment, PaymentTitle, SchedulePayment,
 };
 use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+use ya_persistence::types::BigDecimalField;
 
 pub struct OrderDao<'c> {
     pool: &'c PoolType,
@@ -25,7 +27,29 @@ impl<'c> AsDao<'c> for OrderDao<'c> {
 
 impl<'c> OrderDao<'c> {
     pub async fn create(&self, msg: SchedulePayment, id: String, driver: String) -> DbResult<()> {
-        do_with_transaction(self.pool, "order_dao_create", move |conn| {
+        let result = do_with_transaction(self.pool, "order_dao_create", move |conn| {
+            let reserved = match &msg.title {
+                PaymentTitle::DebitNote(DebitNotePayment {
+                    debit_note_id,
+                    activity_id,
+                }) => AllocationEventType::AllocationReservedEvent {
+                    agreement_id: None,
+                    activity_id: Some(activity_id.clone()),
+                    invoice_id: None,
+                    debit_note_id: Some(debit_note_id.clone()),
+                    amount: msg.amount.clone(),
+                },
+                PaymentTitle::Invoice(InvoicePayment {
+                    invoice_id,
+                    agreement_id,
+                }) => AllocationEventType::AllocationReservedEvent {
+                    agreement_id: Some(agreement_id.clone()),
+                    activity_id: None,
+                    invoice_id: Some(invoice_id.clone()),
+                    debit_note_id: None,
+                    amount: msg.amount.clone(),
+                },
+            };
             match &msg.title {
                 PaymentTitle::DebitNote(DebitNotePayment { activity_id, .. }) => {
                     activity::increase_amount_scheduled(
@@ -46,12 +70,15 @@ impl<'c> OrderDao<'c> {
             };
             let order = WriteObj::new(msg, id, driver);
             allocation::spend_from_allocation(&order.allocation_id, &order.amount, conn)?;
+            allocation_event::create(&order.allocation_id, &order.payer_id, reserved, conn)?;
             diesel::insert_into(dsl::pay_order)
                 .values(order)
                 .execute(conn)?;
             Ok(())
         })
-        .await
+        .await;
+        ALLOCATION_EVENTS_NOTIFY.notify_one();
+        result
     }
 
     pub async fn get_many(&self, ids: Vec<String>, driver: String) -> DbResult<Vec<ReadObj>> {
@@ -90,4 +117,24 @@ impl<'c> OrderDao<'c> {
         })
         .await
     }
+
+    /// Amounts and payee addresses of orders not yet confirmed by the driver.
+    pub async fn get_unpaid(
+        &self,
+        driver: String,
+        payer_addr: String,
+        platform: String,
+    ) -> DbResult<Vec<(BigDecimalField, String)>> {
+        readonly_transaction(self.pool, "order_dao_get_unpaid", move |conn| {
+            let orders = dsl::pay_order
+                .filter(dsl::driver.eq(driver))
+                .filter(dsl::payer_addr.eq(payer_addr))
+                .filter(dsl::payment_platform.eq(platform))
+                .filter(dsl::is_paid.eq(false))
+                .select((dsl::amount, dsl::payee_addr))
+                .load(conn)?;
+            Ok(orders)
+        })
+        .await
+    }
 }
@@ -1,4 +1,5 @@
-use crate::dao::{activity, agreement};
+use crate::allocation_events::ALLOCATION_EVENTS_NOTIFY;
+use crate::dao::{activity, agreement, allocation_event};
 use crate::error::DbResult;
 use crate::models::payment::{
     ActivityPayment as DbActivityPayment, AgreementPayment as DbAgreementPayment, ReadObj, WriteObj,
@@ -17,7 +18,7 @@ use diesel::{
 use std::collections::HashMap;
 use ya_client_model::payment::{ActivityPayment, AgreementPayment, Payment, Signed};
 use ya_client_model::NodeId;
-use ya_core_model::payment::local::{DriverName, NetworkName};
+use ya_core_model::payment::local::{AllocationEventType, DriverName, NetworkName};
 use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
@@ -83,6 +84,33 @@ fn insert_agreement_payments(
     Ok(())
 }
 
+/// Amounts paid out of Requestor's allocations.
+fn spent_events(
+    payment_id: &str,
+    activity_payments: &[ActivityPayment],
+    agreement_payments: &[AgreementPayment],
+) -> Vec<(String, AllocationEventType)> {
+    let activity_events = activity_payments.iter().filter_map(|payment| {
+        let event_type = AllocationEventType::AllocationSpentEvent {
+            agreement_id: None,
+            activity_id: Some(payment.activity_id.clone()),
+            payment_id: payment_id.to_string(),
+            amount: payment.amount.clone(),
+        };
+        Some((payment.allocation_id.clone()?, event_type))
+    });
+    let agreement_events = agreement_payments.iter().filter_map(|payment| {
+        let event_type = AllocationEventType::AllocationSpentEvent {
+            agreement_id: Some(payment.agreement_id.clone()),
+            activity_id: None,
+            payment_id: payment_id.to_string(),
+            amount: payment.amount.clone(),
+        };
+        Some((payment.allocation_id.clone()?, event_type))
+    });
+    activity_events.chain(agreement_events).collect()
+}
+
 impl<'c> AsDao<'c> for PaymentDao<'c> {
     fn as_dao(pool: &'c PoolType) -> Self {
         Self { pool }
@@ -99,8 +127,12 @@ impl<'c> PaymentDao<'c> {
         let payment_id = payment.id.clone();
         let owner_id = payment.owner_id;
         let amount = payment.amount.clone();
+        let spent = match payment.role {
+            Role::Requestor => spent_events(&payment_id, &activity_payments, &agreement_payments),
+            Role::Provider => Vec::new(),
+        };
 
-        do_with_transaction(self.pool, "payment_dao_insert", move |conn| {
+        let result = do_with_transaction(self.pool, "payment_dao_insert", move |conn| {
             log::trace!("Inserting payment...");
             diesel::insert_into(dsl::pay_payment)
                 .values(payment)
@@ -110,9 +142,14 @@ impl<'c> PaymentDao<'c> {
             insert_activity_payments(activity_payments, &payment_id, &owner_id, conn)?;
             insert_agreement_payments(agreement_payments, &payment_id, &owner_id, conn)?;
 
+            for (allocation_id, event_type) in spent {
+                allocation_event::create(&allocation_id, &owner_id, event_type, conn)?;
+            }
             Ok(())
         })
-        .await
+        .await;
+        ALLOCATION_EVENTS_NOTIFY.notify_one();
+        result
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -376,6 +376,8 @@ pub mod processor {
         Driver(#[from] ya_core_model::driver::GenericError),
         #[error("Internal timeout")]
         InternalTimeout(#[from] Elapsed),
+        #[error("Database error: {0}")]
+        Database(#[from] DbError),
     }
 
     #[derive(thiserror::Error, Debug)]
@@ -3,7 +3,6 @@
 pub use crate::config::Config;
 use crate::processor::PaymentProcessor;
 
-use futures::FutureExt;
 use std::{sync::Arc, time::Duration};
 
 use ya_core_model::payment::local as pay_local;
@@ -15,14 +14,17 @@ use ya_service_bus::typed as bus;
 extern crate diesel;
 
 pub mod accounts;
+pub mod allocation_events;
 pub mod api;
 mod cli;
 pub mod config;
 pub mod dao;
 pub mod error;
+pub mod limits;
 pub mod models;
 pub mod payment_sync;
 pub mod processor;
+pub mod retention;
 pub mod schema;
 pub mod service;
 pub mod timeout_lock;
@@ -59,7 +61,9 @@ impl PaymentService {
         let config = Arc::new(Config::from_env()?);
 
         let processor = Arc::new(PaymentProcessor::new(db.clone()));
-        self::service::bind_service(&db, processor.clone(), config);
+        self::service::bind_service(&db, processor.clone(), config.clone());
+        self::retention::archive_job(db.clone(), config.retention.clone());
+        self::allocation_events::bind_service(db.clone());
 
         tokio::task::spawn(async move {
             processor.release_allocations(false).await;
@@ -72,21 +76,23 @@ impl PaymentService {
         api::web_scope(&ctx.component())
     }
 
-    pub async fn shut_down() {
-        log::info!(
-            "Stopping payment service... Hit Ctrl+C again to interrupt and shut down immediately."
-        );
+    pub fn api_spec() -> ya_service_api_web::openapi::ScopeSpec {
+        api::api_spec()
+    }
 
-        let timeout = tokio::time::timeout(
-            *PAYMENT_SHUTDOWN_TIMEOUT,
-            bus::service(pay_local::BUS_ID)
-                .call(pay_local::ShutDown::new(*PAYMENT_SHUTDOWN_TIMEOUT)),
-        );
+    /// Time given to payment drivers to send scheduled payments.
+    pub fn shutdown_timeout() -> Duration {
+        *PAYMENT_SHUTDOWN_TIMEOUT
+    }
+
+    pub async fn shut_down() -> anyhow::Result<()> {
+        log::info!("Stopping payment service...");
+
+        bus::service(pay_local::BUS_ID)
+            .call(pay_local::ShutDown::new(*PAYMENT_SHUTDOWN_TIMEOUT))
+            .await??;
 
-        tokio::select! {
-            _ = timeout => {},
-            _ = tokio::signal::ctrl_c().boxed() => {},
-        }
         log::info!("Payment service stopped.");
+        Ok(())
     }
 }
@@ -0,0 +1,169 @@
+//! Spending limits of app-keys.
+//!
+//! Allocations created through REST API are assigned to the app-key used for the call,
+//! so their amounts count towards its limits on the payment platform of the allocation. Allocations created before setting
+//! the limits are counted as well, but those created without app-key are not limited.
+
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{AppKeyLimitStatus, AppKeyLimits};
+use ya_persistence::executor::{ConnType, DbExecutor};
+
+use crate::dao::{app_key_limit, AgreementDao, AppKeyLimitDao};
+use crate::error::{DbError, DbResult};
+
+#[derive(thiserror::Error, Debug)]
+pub enum LimitError {
+    #[error("App-key '{app_key}' exceeds its {limit} limit {max} (requested: {amount})")]
+    Exceeded {
+        app_key: String,
+        limit: &'static str,
+        max: BigDecimal,
+        amount: BigDecimal,
+    },
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<diesel::result::Error> for LimitError {
+    fn from(e: diesel::result::Error) -> Self {
+        LimitError::Db(e.into())
+    }
+}
+
+impl From<r2d2::Error> for LimitError {
+    fn from(e: r2d2::Error) -> Self {
+        LimitError::Db(e.into())
+    }
+}
+
+impl From<tokio::task::JoinError> for LimitError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        LimitError::Db(e.into())
+    }
+}
+
+fn ensure(
+    app_key: &str,
+    limit: &'static str,
+    max: Option<BigDecimal>,
+    amount: BigDecimal,
+) -> Result<(), LimitError> {
+    match max {
+        Some(max) if amount > max => Err(LimitError::Exceeded {
+            app_key: app_key.to_string(),
+            limit,
+            max,
+            amount,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks whether allocating `amount` on the payment platform fits into app-key limits.
+/// `amended_allocation` is not counted, since its amount is replaced.
+///
+/// Must be called in the transaction creating or amending the allocation,
+/// so that concurrent requests can't exceed limits together.
+pub fn check_allocation(
+    conn: &ConnType,
+    owner_id: NodeId,
+    app_key: &str,
+    platform: &str,
+    amount: &BigDecimal,
+    amended_allocation: Option<&str>,
+) -> Result<(), LimitError> {
+    let limits = match app_key_limit::get_limits(conn, owner_id, app_key)? {
+        Some(limits) => limits,
+        None => return Ok(()),
+    };
+
+    if limits.total.is_some() {
+        let allocated =
+            app_key_limit::allocated(conn, owner_id, app_key, platform, None, amended_allocation)?;
+        ensure(app_key, "total", limits.total, allocated + amount)?;
+    }
+    if limits.daily.is_some() {
+        let since = (Utc::now() - Duration::days(1)).naive_utc();
+        let allocated = app_key_limit::allocated(
+            conn,
+            owner_id,
+            app_key,
+            platform,
+            Some(since),
+            amended_allocation,
+        )?;
+        ensure(app_key, "daily", limits.daily, allocated + amount)?;
+    }
+    Ok(())
+}
+
+/// Checks whether accepting additional `amount` for the Agreement fits into app-key limits.
+pub async fn check_agreement(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    app_key: &str,
+    agreement_id: &str,
+    amount: &BigDecimal,
+) -> Result<(), LimitError> {
+    let dao: AppKeyLimitDao = db.as_dao();
+    let max = match dao.get(owner_id, app_key.to_string()).await? {
+        Some(AppKeyLimits {
+            per_agreement: Some(max),
+            ..
+        }) => max,
+        _ => return Ok(()),
+    };
+
+    let accepted = match db
+        .as_dao::<AgreementDao>()
+        .get(agreement_id.to_string(), owner_id)
+        .await?
+    {
+        Some(agreement) => agreement.total_amount_accepted.0,
+        None => Default::default(),
+    };
+    ensure(app_key, "per-agreement", Some(max), accepted + amount)
+}
+
+pub async fn status(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    app_key: String,
+    limits: AppKeyLimits,
+) -> DbResult<AppKeyLimitStatus> {
+    let since = (Utc::now() - Duration::days(1)).naive_utc();
+    Ok(AppKeyLimitStatus {
+        allocated: db
+            .as_dao::<AppKeyLimitDao>()
+            .allocated(owner_id, app_key.clone(), since)
+            .await?,
+        app_key,
+        limits,
+    })
+}
+
+/// Limits of all app-keys of `owner_id`, or of a single app-key.
+pub async fn list(
+    db: &DbExecutor,
+    owner_id: NodeId,
+    app_key: Option<String>,
+) -> DbResult<Vec<AppKeyLimitStatus>> {
+    let dao: AppKeyLimitDao = db.as_dao();
+    let limits = match app_key {
+        Some(app_key) => dao
+            .get(owner_id, app_key.clone())
+            .await?
+            .map(|limits| vec![(app_key, limits)])
+            .unwrap_or_default(),
+        None => dao.list(owner_id).await?,
+    };
+
+    let mut result = Vec::with_capacity(limits.len());
+    for (app_key, limits) in limits {
+        result.push(status(db, owner_id, app_key, limits).await?);
+    }
+    Ok(result)
+}
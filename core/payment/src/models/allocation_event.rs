@@ -0,0 +1,60 @@
+use crate::error::{DbError, DbResult};
+use crate::schema::pay_allocation_event;
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use std::convert::TryFrom;
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::{AllocationEvent, AllocationEventType};
+use ya_persistence::types::{AdaptTimestamp, TimestampAdapter};
+
+#[derive(Debug, Insertable)]
+#[table_name = "pay_allocation_event"]
+pub struct WriteObj {
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub event_type: String,
+    pub details: String,
+    pub timestamp: TimestampAdapter,
+}
+
+impl WriteObj {
+    pub fn new(
+        allocation_id: String,
+        owner_id: NodeId,
+        event_type: &AllocationEventType,
+    ) -> DbResult<Self> {
+        Ok(Self {
+            allocation_id,
+            owner_id,
+            event_type: event_type.discriminant().to_owned(),
+            details: serde_json::to_string(event_type)?,
+            timestamp: Utc::now().adapt(),
+        })
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "pay_allocation_event"]
+pub struct ReadObj {
+    pub id: i32,
+    pub allocation_id: String,
+    pub owner_id: NodeId,
+    pub event_type: String,
+    pub details: String,
+    pub timestamp: NaiveDateTime,
+}
+
+impl TryFrom<ReadObj> for AllocationEvent {
+    type Error = DbError;
+
+    fn try_from(event: ReadObj) -> DbResult<Self> {
+        let event_type = serde_json::from_str::<AllocationEventType>(&event.details)
+            .map_err(|e| DbError::Integrity(e.to_string()))?;
+
+        Ok(Self {
+            allocation_id: event.allocation_id,
+            owner_id: event.owner_id,
+            event_date: Utc.from_utc_datetime(&event.timestamp),
+            event_type,
+        })
+    }
+}
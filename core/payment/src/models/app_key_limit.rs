@@ -0,0 +1,45 @@
+use crate::schema::{pay_allocation_app_key, pay_app_key_limit};
+use ya_client_model::NodeId;
+use ya_core_model::payment::local::AppKeyLimits;
+use ya_persistence::types::BigDecimalField;
+
+#[derive(Queryable, Debug, Identifiable, Insertable)]
+#[table_name = "pay_app_key_limit"]
+#[primary_key(owner_id, app_key)]
+pub struct LimitObj {
+    pub owner_id: NodeId,
+    pub app_key: String,
+    pub daily_limit: Option<BigDecimalField>,
+    pub total_limit: Option<BigDecimalField>,
+    pub agreement_limit: Option<BigDecimalField>,
+}
+
+impl LimitObj {
+    pub fn new(owner_id: NodeId, app_key: String, limits: AppKeyLimits) -> Self {
+        Self {
+            owner_id,
+            app_key,
+            daily_limit: limits.daily.map(Into::into),
+            total_limit: limits.total.map(Into::into),
+            agreement_limit: limits.per_agreement.map(Into::into),
+        }
+    }
+}
+
+impl From<LimitObj> for AppKeyLimits {
+    fn from(obj: LimitObj) -> Self {
+        Self {
+            daily: obj.daily_limit.map(Into::into),
+            total: obj.total_limit.map(Into::into),
+            per_agreement: obj.agreement_limit.map(Into::into),
+        }
+    }
+}
+
+/// App-key which created the allocation.
+#[derive(Queryable, Debug, Insertable)]
+#[table_name = "pay_allocation_app_key"]
+pub struct AllocationAppKey {
+    pub allocation_id: String,
+    pub app_key: String,
+}
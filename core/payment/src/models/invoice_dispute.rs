@@ -0,0 +1,97 @@
+use crate::error::{DbError, DbResult};
+use crate::schema::pay_invoice_dispute;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use ya_client_model::NodeId;
+use ya_core_model::payment::public::DisputeReason;
+use ya_persistence::types::{AdaptTimestamp, TimestampAdapter};
+
+#[derive(Debug, Identifiable, Insertable)]
+#[table_name = "pay_invoice_dispute"]
+#[primary_key(owner_id, invoice_id)]
+pub struct WriteObj {
+    pub invoice_id: String,
+    pub owner_id: NodeId,
+    pub reason: String,
+    pub message: Option<String>,
+    pub timestamp: TimestampAdapter,
+}
+
+impl WriteObj {
+    pub fn new(
+        invoice_id: String,
+        owner_id: NodeId,
+        reason: &DisputeReason,
+        message: Option<String>,
+    ) -> DbResult<Self> {
+        Ok(Self {
+            invoice_id,
+            owner_id,
+            reason: serde_json::to_string(reason)?,
+            message,
+            timestamp: Utc::now().adapt(),
+        })
+    }
+}
+
+#[derive(Queryable, Debug, Identifiable)]
+#[table_name = "pay_invoice_dispute"]
+#[primary_key(owner_id, invoice_id)]
+pub struct ReadObj {
+    pub invoice_id: String,
+    pub owner_id: NodeId,
+    pub reason: String,
+    pub message: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub corrected_invoice_id: Option<String>,
+    pub resolved_ts: Option<NaiveDateTime>,
+}
+
+/// Dispute over rejected Invoice, as seen by either side.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceDispute {
+    pub invoice_id: String,
+    pub reason: DisputeReason,
+    pub message: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    /// Set once the Provider re-issued the Invoice.
+    pub corrected_invoice_id: Option<String>,
+    pub resolved_ts: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<ReadObj> for InvoiceDispute {
+    type Error = DbError;
+
+    fn try_from(dispute: ReadObj) -> DbResult<Self> {
+        Ok(Self {
+            invoice_id: dispute.invoice_id,
+            reason: serde_json::from_str(&dispute.reason)?,
+            message: dispute.message,
+            timestamp: Utc.from_utc_datetime(&dispute.timestamp),
+            corrected_invoice_id: dispute.corrected_invoice_id,
+            resolved_ts: dispute.resolved_ts.map(|ts| Utc.from_utc_datetime(&ts)),
+        })
+    }
+}
+
+/// Body of Requestor's request opening a dispute.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NewInvoiceDispute {
+    pub reason: DisputeReason,
+    #[schemars(with = "String")]
+    pub total_amount_accepted: BigDecimal,
+    pub message: Option<String>,
+}
+
+/// Body of Provider's request resolving a dispute.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DisputeResolution {
+    /// Corrected Invoice, which must be already sent to the Requestor.
+    pub corrected_invoice_id: String,
+}
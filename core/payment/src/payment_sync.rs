@@ -25,7 +25,9 @@ use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_service_bus::{timeout::IntoTimeoutFuture, typed, RpcEndpoint};
 
-use crate::dao::{DebitNoteDao, InvoiceDao, InvoiceEventDao, PaymentDao, SyncNotifsDao};
+use crate::dao::{
+    DebitNoteDao, InvoiceDao, InvoiceDisputeDao, InvoiceEventDao, PaymentDao, SyncNotifsDao,
+};
 use crate::Config;
 
 const REMOTE_CALL_TIMEOUT: Duration = Duration::from_secs(30);
@@ -39,6 +41,7 @@ async fn payment_sync(
     let invoice_dao: InvoiceDao = db.as_dao();
     let debit_note_dao: DebitNoteDao = db.as_dao();
     let invoice_event_dao: InvoiceEventDao = db.as_dao();
+    let invoice_dispute_dao: InvoiceDisputeDao = db.as_dao();
 
     let mut payments = Vec::default();
     let mut payments_canonicalized = Vec::default();
@@ -85,13 +88,17 @@ async fn payment_sync(
             )
             .await
             .map_err(GenericError::new)?;
+        let dispute = invoice_dispute_dao
+            .get(invoice.invoice_id.clone(), owner)
+            .await
+            .map_err(GenericError::new)?
+            .map(|dispute| dispute.reason);
         if let Some(event) = events.into_iter().last() {
             if let InvoiceEventType::InvoiceRejectedEvent { rejection } = event.event_type {
-                invoice_rejects.push(RejectInvoiceV2 {
-                    invoice_id: invoice.invoice_id,
-                    rejection,
-                    issuer_id: peer_id,
-                });
+                invoice_rejects.push(
+                    RejectInvoiceV2::new(invoice.invoice_id, rejection, peer_id)
+                        .with_dispute(dispute),
+                );
             };
         };
     }
@@ -8,7 +8,7 @@ use crate::error::processor::{
 };
 use crate::models::order::ReadObj as DbOrder;
 use crate::payment_sync::SYNC_NOTIFS_NOTIFY;
-use crate::timeout_lock::{MutexTimeoutExt, RwLockTimeoutExt};
+use crate::timeout_lock::RwLockTimeoutExt;
 
 use actix_web::web::Data;
 use bigdecimal::{BigDecimal, Zero};
@@ -21,21 +21,23 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::{Mutex, RwLock};
+use tokio::sync::RwLock;
+use tokio::time::error::Elapsed;
 
 use ya_client_model::payment::allocation::Deposit;
 use ya_client_model::payment::{
     Account, ActivityPayment, AgreementPayment, DriverDetails, Network, Payment,
 };
 use ya_core_model::driver::{
-    self, driver_bus_id, AccountMode, DriverReleaseDeposit, GetAccountBalanceResult,
-    GetRpcEndpointsResult, PaymentConfirmation, PaymentDetails, ShutDown, ValidateAllocation,
-    ValidateAllocationResult,
+    self, driver_bus_id, AccountMode, DriverReleaseDeposit, EstimateBatch, GetAccountBalanceResult,
+    GetRpcEndpointsResult, PaymentConfirmation, PaymentDetails, ShutDown, SweepResult,
+    ValidateAllocation, ValidateAllocationResult,
 };
 use ya_core_model::payment::local::{
-    GenericError, GetAccountsError, GetDriversError, NotifyPayment, RegisterAccount,
-    RegisterAccountError, RegisterDriver, RegisterDriverError, ReleaseDeposit, SchedulePayment,
-    UnregisterAccount, UnregisterAccountError, UnregisterDriver, UnregisterDriverError,
+    DriverCapabilities, GenericError, GetAccountsError, GetDriversError, NotifyPayment,
+    PaymentCyclePreview, RegisterAccount, RegisterAccountError, RegisterDriver,
+    RegisterDriverError, ReleaseDeposit, SchedulePayment, UnregisterAccount,
+    UnregisterAccountError, UnregisterDriver, UnregisterDriverError,
 };
 use ya_core_model::payment::public::{SendPayment, SendSignedPayment, BUS_ID};
 use ya_core_model::NodeId;
@@ -45,6 +47,10 @@ use ya_persistence::types::Role;
 use ya_service_bus::typed::Endpoint;
 use ya_service_bus::{typed as bus, RpcEndpoint, RpcMessage};
 
+mod pipeline;
+
+use pipeline::{Pipeline, PipelineKey, Pipelines};
+
 fn driver_endpoint(driver: &str) -> Endpoint {
     bus::service(driver_bus_id(driver))
 }
@@ -101,6 +107,8 @@ struct DriverRegistry {
     // (platform, address) -> details
     drivers: HashMap<String, DriverDetails>,
     // driver_name -> details
+    capabilities: HashMap<String, DriverCapabilities>,
+    // driver_name -> capabilities
     platforms: HashMap<String, HashMap<String, bool>>, // platform -> (driver_name -> recv_init_required)
 }
 
@@ -109,6 +117,7 @@ impl DriverRegistry {
         let RegisterDriver {
             driver_name,
             details,
+            capabilities,
         } = msg;
         log::trace!(
             "register_driver: driver_name={} details={:?}",
@@ -135,12 +144,14 @@ impl DriverRegistry {
                     .insert(driver_name.clone(), details.recv_init_required);
             }
         }
+        self.capabilities.insert(driver_name.clone(), capabilities);
         self.drivers.insert(driver_name, details);
         Ok(())
     }
 
     pub fn unregister_driver(&mut self, msg: UnregisterDriver) {
         let driver_name = msg.0;
+        self.capabilities.remove(&driver_name);
         let details = self.drivers.remove(&driver_name);
         if let Some(details) = details {
             for (network_name, network) in details.networks.iter() {
@@ -244,6 +255,18 @@ impl DriverRegistry {
         drivers
     }
 
+    pub fn get_capabilities(&self, driver: Option<String>) -> HashMap<String, DriverCapabilities> {
+        match driver {
+            Some(driver) => self
+                .capabilities
+                .get_key_value(&driver)
+                .map(|(name, capabilities)| (name.clone(), capabilities.clone()))
+                .into_iter()
+                .collect(),
+            None => self.capabilities.clone(),
+        }
+    }
+
     pub fn get_network(
         &self,
         driver: String,
@@ -312,14 +335,30 @@ impl DriverRegistry {
     pub fn iter_drivers(&self) -> impl Iterator<Item = &String> {
         self.drivers.keys()
     }
+
+    /// Network of the platform supported by the driver.
+    pub fn platform_network(&self, driver: &str, platform: &str) -> Option<String> {
+        self.drivers
+            .get(driver)?
+            .networks
+            .iter()
+            .find_map(|(name, network)| {
+                network
+                    .tokens
+                    .values()
+                    .any(|p| p == platform)
+                    .then(|| name.clone())
+            })
+    }
 }
 
-const DB_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+const PIPELINE_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
 const REGISTRY_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct PaymentProcessor {
-    db_executor: Arc<Mutex<DbExecutor>>,
+    db_executor: DbExecutor,
     registry: RwLock<DriverRegistry>,
+    pipelines: Pipelines,
     in_shutdown: AtomicBool,
 }
 
@@ -336,8 +375,9 @@ enum PaymentSendToGsbError {
 impl PaymentProcessor {
     pub fn new(db_executor: DbExecutor) -> Self {
         Self {
-            db_executor: Arc::new(Mutex::new(db_executor)),
+            db_executor,
             registry: Default::default(),
+            pipelines: Default::default(),
             in_shutdown: AtomicBool::new(false),
         }
     }
@@ -402,6 +442,17 @@ impl PaymentProcessor {
             .map_err(|_| GetDriversError::InternalTimeout)
     }
 
+    pub async fn get_driver_capabilities(
+        &self,
+        driver: Option<String>,
+    ) -> Result<HashMap<String, DriverCapabilities>, GetDriversError> {
+        self.registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await
+            .map(|registry| registry.get_capabilities(driver))
+            .map_err(|_| GetDriversError::InternalTimeout)
+    }
+
     pub async fn get_network(
         &self,
         driver: String,
@@ -427,6 +478,37 @@ impl PaymentProcessor {
             .get_platform(driver, network, token)
     }
 
+    /// Pipeline processing payments of the platform with the driver.
+    async fn pipeline(&self, driver: &str, platform: &str) -> Result<Arc<Pipeline>, Elapsed> {
+        let network = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await?
+            .platform_network(driver, platform)
+            // Unknown platforms get a pipeline of their own.
+            .unwrap_or_else(|| platform.to_string());
+        Ok(self.pipelines.get(PipelineKey {
+            driver: driver.to_string(),
+            network,
+        }))
+    }
+
+    /// Pipeline of the allocation's platform. Platforms without a registered
+    /// driver get a pipeline of their own.
+    async fn allocation_pipeline(
+        &self,
+        platform: &str,
+        address: &str,
+    ) -> Result<Arc<Pipeline>, Elapsed> {
+        let driver = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await?
+            .driver(platform, address, AccountMode::empty())
+            .unwrap_or_default();
+        self.pipeline(&driver, platform).await
+    }
+
     pub async fn notify_payment(&self, msg: NotifyPayment) -> Result<(), NotifyPaymentError> {
         let driver = msg.driver;
         let payment_platform = msg.platform;
@@ -441,8 +523,10 @@ impl PaymentProcessor {
         let payee_id: NodeId;
         let payment_id: String;
 
+        let pipeline = self.pipeline(&driver, &payment_platform).await?;
         let payment: Payment = {
-            let db_executor = self.db_executor.timeout_lock(DB_LOCK_TIMEOUT).await?;
+            let _guard = pipeline.enter(PIPELINE_LOCK_TIMEOUT).await?;
+            let db_executor = &self.db_executor;
 
             let orders = db_executor
                 .as_dao::<OrderDao>()
@@ -518,7 +602,7 @@ impl PaymentProcessor {
         let msg = SendPayment::new(payment.clone(), signature);
         let msg_with_bytes = SendSignedPayment::new(payment.clone(), signature_canonical);
 
-        let db_executor = Arc::clone(&self.db_executor);
+        let db_executor = self.db_executor.clone();
 
         tokio::task::spawn_local(
             async move {
@@ -539,8 +623,6 @@ impl PaymentProcessor {
                     Err(_) => false,
                 };
 
-                let db_executor = db_executor.timeout_lock(DB_LOCK_TIMEOUT).await?;
-
                 let payment_dao: PaymentDao = db_executor.as_dao();
                 let sync_dao: SyncNotifsDao = db_executor.as_dao();
 
@@ -607,25 +689,28 @@ impl PaymentProcessor {
             )));
         }
 
-        let allocation_status = self
-            .db_executor
-            .timeout_lock(DB_LOCK_TIMEOUT)
+        let driver = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
             .await?
-            .as_dao::<AllocationDao>()
-            .get(msg.allocation_id.clone(), msg.payer_id)
-            .await?;
+            .driver(&msg.payment_platform, &msg.payer_addr, AccountMode::SEND)?;
+
+        let pipeline = self.pipeline(&driver, &msg.payment_platform).await?;
+        let _queued = pipeline.enqueue(PIPELINE_LOCK_TIMEOUT).await?;
+
+        let allocation_status = {
+            let _guard = pipeline.enter(PIPELINE_LOCK_TIMEOUT).await?;
+            self.db_executor
+                .as_dao::<AllocationDao>()
+                .get(msg.allocation_id.clone(), msg.payer_id)
+                .await?
+        };
         let deposit_id = if let AllocationStatus::Active(allocation) = allocation_status {
             allocation.deposit
         } else {
             None
         };
 
-        let driver = self
-            .registry
-            .timeout_read(REGISTRY_LOCK_TIMEOUT)
-            .await?
-            .driver(&msg.payment_platform, &msg.payer_addr, AccountMode::SEND)?;
-
         let order_id = driver_endpoint(&driver)
             .send(driver::SchedulePayment::new(
                 amount,
@@ -637,9 +722,8 @@ impl PaymentProcessor {
             ))
             .await??;
 
+        let _guard = pipeline.enter(PIPELINE_LOCK_TIMEOUT).await?;
         self.db_executor
-            .timeout_lock(DB_LOCK_TIMEOUT)
-            .await?
             .as_dao::<OrderDao>()
             .create(msg, order_id, driver)
             .await?;
@@ -714,7 +798,9 @@ impl PaymentProcessor {
         }
 
         {
-            let db_executor = self.db_executor.timeout_lock(DB_LOCK_TIMEOUT).await?;
+            let pipeline = self.pipeline(&driver, &platform).await?;
+            let _guard = pipeline.enter(PIPELINE_LOCK_TIMEOUT).await?;
+            let db_executor = &self.db_executor;
 
             // Verify agreement payments
             let agreement_dao: AgreementDao = db_executor.as_dao();
@@ -847,6 +933,71 @@ impl PaymentProcessor {
         Ok(res)
     }
 
+    /// Totals of scheduled payments waiting for the next batch and estimated cost
+    /// of sending them. Only the driver knows which of the unpaid orders are
+    /// already being sent, so totals come from the driver.
+    pub async fn preview_payment_cycle(
+        &self,
+        platform: String,
+        address: String,
+        network: String,
+    ) -> Result<PaymentCyclePreview, GetStatusError> {
+        let driver = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await?
+            .driver(&platform, &address, AccountMode::empty())?;
+        let estimate = driver_endpoint(&driver)
+            .send(EstimateBatch {
+                sender: address,
+                network: Some(network),
+            })
+            .await??;
+
+        Ok(PaymentCyclePreview { platform, estimate })
+    }
+
+    /// Transfers funds of `address` to `to` address. Payments scheduled in the meantime
+    /// wait, so that the driver knows about all funds which have to stay on the account.
+    pub async fn sweep(
+        &self,
+        platform: String,
+        address: String,
+        network: String,
+        to: String,
+        native: bool,
+    ) -> Result<SweepResult, GetStatusError> {
+        let driver = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await?
+            .driver(&platform, &address, AccountMode::SEND)?;
+
+        let pipeline = self.pipeline(&driver, &platform).await?;
+        let _queued = pipeline.enqueue(PIPELINE_LOCK_TIMEOUT).await?;
+        let orders = self
+            .db_executor
+            .as_dao::<OrderDao>()
+            .get_unpaid(driver.clone(), address.clone(), platform)
+            .await?;
+        let pending_amount = orders
+            .iter()
+            .map(|(amount, _)| &amount.0)
+            .fold(BigDecimal::zero(), |total, amount| total + amount);
+
+        let result = driver_endpoint(&driver)
+            .send(driver::Sweep {
+                sender: address,
+                to,
+                network: Some(network),
+                native,
+                pending_amount,
+                pending_transfers: orders.len() as u32,
+            })
+            .await??;
+        Ok(result)
+    }
+
     pub async fn validate_allocation(
         &self,
         platform: String,
@@ -866,9 +1017,16 @@ impl PaymentProcessor {
             }
         }
 
+        let driver = self
+            .registry
+            .timeout_read(REGISTRY_LOCK_TIMEOUT)
+            .await?
+            .driver(&platform, &address, AccountMode::empty())?;
+
         let (active_allocations, past_allocations) = {
-            let db = self.db_executor.timeout_lock(DB_LOCK_TIMEOUT).await?;
-            let dao = db.as_dao::<AllocationDao>();
+            let pipeline = self.pipeline(&driver, &platform).await?;
+            let _guard = pipeline.enter(PIPELINE_LOCK_TIMEOUT).await?;
+            let dao = self.db_executor.as_dao::<AllocationDao>();
 
             let active = dao
                 .get_for_address(platform.clone(), address.clone(), Some(false))
@@ -880,11 +1038,6 @@ impl PaymentProcessor {
             (active, past)
         };
 
-        let driver = self
-            .registry
-            .timeout_read(REGISTRY_LOCK_TIMEOUT)
-            .await?
-            .driver(&platform, &address, AccountMode::empty())?;
         let msg = ValidateAllocation {
             address,
             platform,
@@ -903,16 +1056,7 @@ impl PaymentProcessor {
     /// When `bool` is `true` all existing allocations are released immediately.
     /// For `false` each allocation timestamp is respected.
     pub async fn release_allocations(&self, force: bool) {
-        // keep this lock alive for the entirety of this function for now
-        let db_executor = match self.db_executor.timeout_lock(DB_LOCK_TIMEOUT).await {
-            Ok(db) => db,
-            Err(_) => {
-                log::error!("Timed out waiting for db lock");
-                return;
-            }
-        };
-
-        let db = Data::new(db_executor.clone());
+        let db = Data::new(self.db_executor.clone());
         let active_allocations = db
             .clone()
             .as_dao::<AllocationDao>()
@@ -929,6 +1073,27 @@ impl PaymentProcessor {
             Ok(allocations) => {
                 if !allocations.is_empty() {
                     for allocation in allocations {
+                        let pipeline = match self
+                            .allocation_pipeline(&allocation.payment_platform, &allocation.address)
+                            .await
+                        {
+                            Ok(pipeline) => pipeline,
+                            Err(_) => {
+                                log::error!("Timed out waiting for driver registry lock");
+                                return;
+                            }
+                        };
+                        let _guard = match pipeline.enter(PIPELINE_LOCK_TIMEOUT).await {
+                            Ok(guard) => guard,
+                            Err(_) => {
+                                log::error!(
+                                    "Timed out waiting for payment processor lock of {}",
+                                    allocation.payment_platform
+                                );
+                                return;
+                            }
+                        };
+
                         if force {
                             forced_release_allocation(db.clone(), allocation.allocation_id, None)
                                 .await
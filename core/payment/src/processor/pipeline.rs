@@ -0,0 +1,93 @@
+//! Payment pipelines of (driver, network) pairs.
+//!
+//! Confirming and verifying payments, as well as validating and releasing allocations,
+//! requires DB operations to be atomic with respect to other payments of the same network,
+//! so they are serialized by the pipeline's processor lock. Orders are handed to the driver
+//! for batching through a separate queue, so scheduling doesn't hold the processor lock
+//! while waiting for the driver. Pipelines don't share locks, so a slow or stuck network
+//! doesn't delay payments on other networks.
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::time::error::Elapsed;
+
+use crate::timeout_lock::MutexTimeoutExt;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub driver: String,
+    pub network: String,
+}
+
+/// Operations of a single pipeline are queued in FIFO order.
+#[derive(Default)]
+pub struct Pipeline {
+    lock: Mutex<()>,
+    batching: Mutex<()>,
+}
+
+impl Pipeline {
+    /// Processor lock, held for DB operations of the network.
+    pub async fn enter(&self, timeout: Duration) -> Result<MutexGuard<'_, ()>, Elapsed> {
+        self.lock.timeout_lock(timeout).await
+    }
+
+    /// Queue of orders scheduled for batching by the driver.
+    pub async fn enqueue(&self, timeout: Duration) -> Result<MutexGuard<'_, ()>, Elapsed> {
+        self.batching.timeout_lock(timeout).await
+    }
+}
+
+#[derive(Default)]
+pub struct Pipelines {
+    pipelines: std::sync::Mutex<HashMap<PipelineKey, Arc<Pipeline>>>,
+}
+
+impl Pipelines {
+    /// Pipelines are created on first use.
+    pub fn get(&self, key: PipelineKey) -> Arc<Pipeline> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+        pipelines.entry(key).or_default().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(network: &str) -> PipelineKey {
+        PipelineKey {
+            driver: "erc20".to_string(),
+            network: network.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipelines_independent() {
+        let pipelines = Pipelines::default();
+        let polygon = pipelines.get(key("polygon"));
+        let holesky = pipelines.get(key("holesky"));
+        let timeout = Duration::from_millis(50);
+
+        let _guard = polygon.enter(timeout).await.unwrap();
+        assert!(holesky.enter(timeout).await.is_ok());
+        assert!(pipelines.get(key("polygon")).enter(timeout).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batching_queue() {
+        let pipelines = Pipelines::default();
+        let polygon = pipelines.get(key("polygon"));
+        let timeout = Duration::from_millis(50);
+
+        let queued = polygon.enqueue(timeout).await.unwrap();
+        // Orders waiting for the driver don't block the processor lock
+        assert!(polygon.enter(timeout).await.is_ok());
+        assert!(polygon.enqueue(timeout).await.is_err());
+
+        drop(queued);
+        assert!(polygon.enqueue(timeout).await.is_ok());
+    }
+}
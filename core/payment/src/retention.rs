@@ -0,0 +1,55 @@
+//! Archival of settled documents.
+//!
+//! Invoices, debit notes and payments of fully settled agreements are moved to archive tables,
+//! so queries on working tables don't slow down as the database grows.
+use chrono::Utc;
+use metrics::counter;
+use std::time::Duration;
+
+use ya_core_model::payment::local::ArchiveStats;
+use ya_persistence::executor::DbExecutor;
+
+use crate::config::RetentionConfig;
+use crate::dao::ArchiveDao;
+use crate::error::{DbError, DbResult};
+
+/// Archives documents of agreements settled more than `older_than` ago.
+pub async fn archive(db: &DbExecutor, older_than: Duration) -> DbResult<ArchiveStats> {
+    let older_than =
+        chrono::Duration::from_std(older_than).map_err(|e| DbError::Query(e.to_string()))?;
+    let cutoff = (Utc::now() - older_than).naive_utc();
+    let stats = db.as_dao::<ArchiveDao>().archive(cutoff).await?;
+
+    counter!("payment.archive.invoices", stats.invoices);
+    counter!("payment.archive.debit_notes", stats.debit_notes);
+    counter!("payment.archive.payments", stats.payments);
+    counter!("payment.archive.allocation_events", stats.allocation_events);
+    log::info!(
+        "Archived {} invoices, {} debit notes and {} payments settled before {}, \
+        removed {} allocation events",
+        stats.invoices,
+        stats.debit_notes,
+        stats.payments,
+        cutoff,
+        stats.allocation_events
+    );
+    Ok(stats)
+}
+
+pub fn archive_job(db: DbExecutor, config: RetentionConfig) {
+    let older_than = match config.archive_after {
+        Some(older_than) => older_than,
+        None => return,
+    };
+
+    tokio::task::spawn_local(async move {
+        let mut interval = tokio::time::interval(config.archive_interval);
+        loop {
+            interval.tick().await;
+            log::debug!("Payment archive job started");
+            if let Err(e) = archive(&db, older_than).await {
+                log::error!("Payment archive job failed: {e}");
+            }
+        }
+    });
+}
@@ -65,6 +65,34 @@ table! {
     }
 }
 
+table! {
+    pay_allocation_app_key (allocation_id) {
+        allocation_id -> Text,
+        app_key -> Text,
+    }
+}
+
+table! {
+    pay_allocation_event (id) {
+        id -> Integer,
+        allocation_id -> Text,
+        owner_id -> Text,
+        event_type -> Text,
+        details -> Text,
+        timestamp -> Timestamp,
+    }
+}
+
+table! {
+    pay_app_key_limit (owner_id, app_key) {
+        owner_id -> Text,
+        app_key -> Text,
+        daily_limit -> Nullable<Text>,
+        total_limit -> Nullable<Text>,
+        agreement_limit -> Nullable<Text>,
+    }
+}
+
 table! {
     pay_debit_note (id, owner_id) {
         id -> Text,
@@ -131,6 +159,18 @@ table! {
     }
 }
 
+table! {
+    pay_invoice_dispute (owner_id, invoice_id) {
+        invoice_id -> Text,
+        owner_id -> Text,
+        reason -> Text,
+        message -> Nullable<Text>,
+        timestamp -> Timestamp,
+        corrected_invoice_id -> Nullable<Text>,
+        resolved_ts -> Nullable<Timestamp>,
+    }
+}
+
 table! {
     pay_invoice_event (invoice_id, event_type) {
         invoice_id -> Text,
@@ -206,6 +246,7 @@ table! {
 
 joinable!(pay_activity_payment -> pay_allocation (allocation_id));
 joinable!(pay_agreement_payment -> pay_allocation (allocation_id));
+joinable!(pay_allocation_app_key -> pay_allocation (allocation_id));
 joinable!(pay_debit_note -> pay_document_status (status));
 joinable!(pay_debit_note_event -> pay_event_type (event_type));
 joinable!(pay_invoice -> pay_document_status (status));
@@ -218,12 +259,16 @@ allow_tables_to_appear_in_same_query!(
     pay_agreement,
     pay_agreement_payment,
     pay_allocation,
+    pay_allocation_app_key,
+    pay_allocation_event,
+    pay_app_key_limit,
     pay_debit_note,
     pay_debit_note_event,
     pay_debit_note_event_read,
     pay_document_status,
     pay_event_type,
     pay_invoice,
+    pay_invoice_dispute,
     pay_invoice_event,
     pay_invoice_event_read,
     pay_invoice_x_activity,
@@ -42,7 +42,7 @@ mod local {
     use ya_core_model::driver::ValidateAllocationResult;
     use ya_core_model::payment::public::Ack;
     use ya_core_model::{
-        driver::{driver_bus_id, DriverStatus, DriverStatusError},
+        driver::{driver_bus_id, DriverStatus, DriverStatusError, SweepResult},
         payment::local::*,
     };
     use ya_persistence::types::Role;
@@ -60,13 +60,20 @@ mod local {
             .bind_with_processor(get_rpc_endpoints)
             .bind_with_processor(get_status)
             .bind_with_processor(get_invoice_stats)
+            .bind_with_processor(get_revenue_stats)
             .bind_with_processor(get_accounts)
             .bind_with_processor(validate_allocation)
             .bind_with_processor(release_allocations)
             .bind_with_processor(get_drivers)
+            .bind_with_processor(get_driver_capabilities)
             .bind_with_processor(payment_driver_status)
             .bind_with_processor(handle_status_change)
             .bind_with_processor(release_deposit)
+            .bind_with_processor(set_app_key_limits)
+            .bind_with_processor(get_app_key_limits)
+            .bind_with_processor(archive_documents)
+            .bind_with_processor(preview_payment_cycle)
+            .bind_with_processor(sweep)
             .bind_with_processor(shut_down);
 
         // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
@@ -90,6 +97,7 @@ mod local {
 
         counter!("payment.debit_notes.events.query", 0);
         counter!("payment.invoices.events.query", 0);
+        counter!("payment.allocations.events.query", 0);
 
         counter!("payment.invoices.provider.issued", 0);
         counter!("payment.invoices.provider.sent", 0);
@@ -456,6 +464,18 @@ mod local {
         Ok(output_stats)
     }
 
+    async fn get_revenue_stats(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: GetRevenueStats,
+    ) -> Result<Vec<RevenueStats>, GenericError> {
+        db.as_dao::<InvoiceDao>()
+            .revenue_stats(msg.node_id, msg.since)
+            .await
+            .map_err(GenericError::new)
+    }
+
     async fn validate_allocation(
         db: DbExecutor,
         processor: Arc<PaymentProcessor>,
@@ -495,6 +515,15 @@ mod local {
         processor.get_drivers(msg.ignore_legacy_networks).await
     }
 
+    async fn get_driver_capabilities(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: GetDriverCapabilities,
+    ) -> Result<HashMap<String, DriverCapabilities>, GetDriversError> {
+        processor.get_driver_capabilities(msg.driver).await
+    }
+
     async fn payment_driver_status(
         db: DbExecutor,
         processor: Arc<PaymentProcessor>,
@@ -779,6 +808,108 @@ mod local {
         res
     }
 
+    /// REST API bridge (`/_gsb`) calls local services on behalf of app-keys
+    /// as `/local/<identity>`.
+    fn app_key_identity(caller: &str) -> Option<&str> {
+        caller.strip_prefix("/local/")
+    }
+
+    async fn set_app_key_limits(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        caller: String,
+        msg: SetAppKeyLimits,
+    ) -> Result<(), GenericError> {
+        // otherwise an app-key could raise its own limits
+        if app_key_identity(&caller).is_some() {
+            return Err(GenericError::new(format!(
+                "{caller} is not allowed to set app-key limits"
+            )));
+        }
+        db.as_dao::<AppKeyLimitDao>()
+            .set(msg.owner, msg.app_key, msg.limits)
+            .await
+            .map_err(GenericError::new)
+    }
+
+    async fn get_app_key_limits(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        caller: String,
+        msg: GetAppKeyLimits,
+    ) -> Result<Vec<AppKeyLimitStatus>, GenericError> {
+        if let Some(identity) = app_key_identity(&caller) {
+            if identity.parse::<NodeId>().ok() != Some(msg.owner) {
+                return Err(GenericError::new(format!(
+                    "{caller} is not allowed to get app-key limits of {}",
+                    msg.owner
+                )));
+            }
+        }
+        crate::limits::list(&db, msg.owner, msg.app_key)
+            .await
+            .map_err(GenericError::new)
+    }
+
+    async fn archive_documents(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: ArchiveDocuments,
+    ) -> Result<ArchiveStats, GenericError> {
+        crate::retention::archive(&db, msg.older_than)
+            .await
+            .map_err(GenericError::new)
+    }
+
+    async fn preview_payment_cycle(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: PreviewPaymentCycle,
+    ) -> Result<PaymentCyclePreview, GenericError> {
+        let (network, network_details) = processor
+            .get_network(msg.driver.clone(), msg.network)
+            .await
+            .map_err(GenericError::new)?;
+        let token = &network_details.default_token;
+        let platform = network_details.tokens.get(token).cloned().ok_or_else(|| {
+            GenericError::new(format!(
+                "Unsupported token. driver={} network={} token={}",
+                msg.driver, network, token
+            ))
+        })?;
+
+        processor
+            .preview_payment_cycle(platform, msg.address, network)
+            .await
+            .map_err(GenericError::new)
+    }
+
+    async fn sweep(
+        db: DbExecutor,
+        processor: Arc<PaymentProcessor>,
+        _caller: String,
+        msg: Sweep,
+    ) -> Result<SweepResult, GenericError> {
+        let (network, network_details) = processor
+            .get_network(msg.driver.clone(), msg.network)
+            .await
+            .map_err(GenericError::new)?;
+        let token = &network_details.default_token;
+        let platform = network_details.tokens.get(token).cloned().ok_or_else(|| {
+            GenericError::new(format!(
+                "Unsupported token. driver={} network={} token={}",
+                msg.driver, network, token
+            ))
+        })?;
+
+        processor
+            .sweep(platform, msg.address, network, msg.to, msg.native)
+            .await
+            .map_err(GenericError::new)
+    }
+
     async fn shut_down(
         db: DbExecutor,
         processor: Arc<PaymentProcessor>,
@@ -823,6 +954,7 @@ mod public {
             .bind(accept_invoice)
             .bind(reject_invoice)
             .bind(cancel_invoice)
+            .bind(resolve_invoice_dispute)
             .bind(sync_request)
             .bind_with_processor(send_payment)
             .bind_with_processor(send_payment_with_bytes)
@@ -1150,6 +1282,7 @@ mod public {
         let invoice_id = msg.invoice_id;
         let rejection = msg.rejection;
         let owner_id = msg.issuer_id;
+        let dispute = msg.dispute;
 
         log::debug!(
             "Got RejectInvoiceV2 [{}] from Node [{}].",
@@ -1181,7 +1314,13 @@ mod public {
             _ => (),
         }
 
-        match dao.reject(invoice_id.clone(), owner_id, rejection).await {
+        if let Some(reason) = &dispute {
+            log::info!("Invoice [{}] disputed: {:?}", invoice_id, reason);
+        }
+        match dao
+            .reject(invoice_id.clone(), owner_id, rejection, dispute)
+            .await
+        {
             Ok(_) => {
                 log::info!(
                     "Node [{}] rejected invoice [{}] for Agreement [{}].",
@@ -1197,6 +1336,76 @@ mod public {
         }
     }
 
+    async fn resolve_invoice_dispute(
+        db: DbExecutor,
+        sender_id: String,
+        msg: ResolveInvoiceDispute,
+    ) -> Result<Ack, AcceptRejectError> {
+        let invoice_id = msg.invoice_id;
+        let owner_id = msg.recipient_id;
+
+        log::debug!(
+            "Got ResolveInvoiceDispute [{}] from Node [{}].",
+            invoice_id,
+            sender_id
+        );
+
+        let dao: InvoiceDao = db.as_dao();
+        let dispute_dao: InvoiceDisputeDao = db.as_dao();
+        let invoice: Invoice = match dao.get(invoice_id.clone(), owner_id).await {
+            Ok(Some(invoice)) => invoice,
+            Ok(None) => return Err(AcceptRejectError::ObjectNotFound),
+            Err(e) => return Err(AcceptRejectError::ServiceError(e.to_string())),
+        };
+
+        if sender_id != invoice.issuer_id.to_string() {
+            return Err(AcceptRejectError::Forbidden);
+        }
+
+        match dispute_dao.get(invoice_id.clone(), owner_id).await {
+            Ok(Some(dispute)) if dispute.corrected_invoice_id.is_some() => return Ok(Ack {}),
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                return Err(AcceptRejectError::BadRequest(format!(
+                    "Invoice {invoice_id} is not disputed"
+                )))
+            }
+            Err(e) => return Err(AcceptRejectError::ServiceError(e.to_string())),
+        }
+
+        // Corrected Invoice is sent before resolving the dispute.
+        match dao.get(msg.corrected_invoice_id.clone(), owner_id).await {
+            Ok(Some(corrected)) if corrected.agreement_id == invoice.agreement_id => (),
+            Ok(_) => {
+                return Err(AcceptRejectError::BadRequest(format!(
+                    "Corrected invoice {} not received",
+                    msg.corrected_invoice_id
+                )))
+            }
+            Err(e) => return Err(AcceptRejectError::ServiceError(e.to_string())),
+        }
+
+        match dispute_dao
+            .resolve(
+                invoice_id.clone(),
+                owner_id,
+                msg.corrected_invoice_id.clone(),
+            )
+            .await
+        {
+            Ok(_) => {
+                log::info!(
+                    "Node [{}] resolved dispute over invoice [{}] with invoice [{}].",
+                    sender_id,
+                    invoice_id,
+                    msg.corrected_invoice_id
+                );
+                Ok(Ack {})
+            }
+            Err(e) => Err(AcceptRejectError::ServiceError(e.to_string())),
+        }
+    }
+
     async fn cancel_invoice(
         db: DbExecutor,
         sender_id: String,
@@ -174,9 +174,10 @@ pub async fn listen_for_events<T: EventGetter>(
 }
 
 pub mod response {
+    use actix_web::http::StatusCode;
     use actix_web::HttpResponse;
     use serde::Serialize;
-    use ya_client_model::ErrorMessage;
+    use ya_service_api_web::error::{ApiError, ErrorCategory};
 
     pub fn ok<T: Serialize>(t: T) -> HttpResponse {
         HttpResponse::Ok().json(t)
@@ -186,46 +187,67 @@ pub mod response {
         HttpResponse::Created().json(t)
     }
 
+    pub fn error(e: ApiError) -> HttpResponse {
+        if e.category == ErrorCategory::Internal {
+            log::error!("Payment API server error: {}", e);
+        }
+        e.response()
+    }
+
+    fn with_message(category: ErrorCategory, e: &impl ToString) -> HttpResponse {
+        error(ApiError::new(category, category.code(), e.to_string()))
+    }
+
     pub fn not_implemented() -> HttpResponse {
-        HttpResponse::NotImplemented().json(ErrorMessage { message: None })
+        error(ApiError::empty(ErrorCategory::NotImplemented))
     }
 
     pub fn not_found() -> HttpResponse {
-        HttpResponse::NotFound().json(ErrorMessage { message: None })
+        error(ApiError::empty(ErrorCategory::NotFound))
     }
 
     pub fn not_found_with_messsage(e: &impl ToString) -> HttpResponse {
-        HttpResponse::NotFound().json(ErrorMessage {
-            message: Some(e.to_string()),
-        })
+        with_message(ErrorCategory::NotFound, e)
     }
 
     pub fn unauthorized() -> HttpResponse {
-        HttpResponse::Unauthorized().json(ErrorMessage { message: None })
+        error(ApiError::empty(ErrorCategory::Unauthorized))
     }
 
     pub fn timeout(e: &impl ToString) -> HttpResponse {
-        HttpResponse::GatewayTimeout().json(ErrorMessage {
-            message: Some(e.to_string()),
-        })
+        ApiError::new(
+            ErrorCategory::Timeout,
+            ErrorCategory::Timeout.code(),
+            e.to_string(),
+        )
+        .response_with(StatusCode::GATEWAY_TIMEOUT)
     }
 
     pub fn server_error(e: &impl ToString) -> HttpResponse {
-        let e = e.to_string();
-        log::error!("Payment API server error: {}", e);
-        HttpResponse::InternalServerError().json(ErrorMessage::new(e))
+        with_message(ErrorCategory::Internal, e)
     }
 
     pub fn bad_request(e: &impl ToString) -> HttpResponse {
-        HttpResponse::BadRequest().json(ErrorMessage::new(e.to_string()))
+        with_message(ErrorCategory::BadRequest, e)
     }
 
     pub fn conflict(e: &impl ToString) -> HttpResponse {
-        HttpResponse::Conflict().json(ErrorMessage::new(e.to_string()))
+        with_message(ErrorCategory::Conflict, e)
     }
 
     pub fn gone(e: &impl ToString) -> HttpResponse {
-        HttpResponse::Gone().json(ErrorMessage::new(e.to_string()))
+        with_message(ErrorCategory::Gone, e)
+    }
+
+    pub fn allocation_released(allocation_id: &str) -> HttpResponse {
+        error(
+            ApiError::new(
+                ErrorCategory::Gone,
+                "ALLOCATION_RELEASED",
+                format!("Allocation {allocation_id} has been already released"),
+            )
+            .with_detail("allocationId", allocation_id),
+        )
     }
 }
 
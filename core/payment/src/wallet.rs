@@ -1,9 +1,14 @@
 // External crates
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 
 // Workspace uses
-use ya_core_model::driver::{driver_bus_id, Enter, Exit, Fund, Transfer};
+use ya_core_model::driver::{
+    driver_bus_id, Approve, Enter, Exit, Fund, GetAllowance, Permit, SignPermit, SubmitPermit,
+    Transfer,
+};
 use ya_service_bus::typed as bus;
+use ya_service_bus::RpcEndpoint;
 
 pub async fn fund(
     address: String,
@@ -73,3 +78,74 @@ pub async fn transfer(
     let tx_id = bus::service(driver_id).call(message).await??;
     Ok(tx_id)
 }
+
+pub async fn allowance(
+    owner: String,
+    spender: String,
+    driver: String,
+    network: Option<String>,
+) -> anyhow::Result<BigDecimal> {
+    let driver_id = driver_bus_id(driver);
+    let message = GetAllowance {
+        owner,
+        spender,
+        network,
+    };
+    let allowance = bus::service(driver_id).call(message).await??;
+    Ok(allowance)
+}
+
+pub async fn permit(
+    owner: String,
+    spender: String,
+    amount: BigDecimal,
+    deadline: DateTime<Utc>,
+    driver: String,
+    network: Option<String>,
+) -> anyhow::Result<Permit> {
+    let driver_id = driver_bus_id(driver);
+    let message = SignPermit {
+        owner: owner.clone(),
+        spender,
+        amount,
+        deadline,
+        network,
+    };
+    // Driver signs only on behalf of the caller
+    let permit = bus::service(driver_id).send_as(owner, message).await??;
+    Ok(permit)
+}
+
+pub async fn submit_permit(
+    sender: String,
+    permit: Permit,
+    driver: String,
+    network: Option<String>,
+) -> anyhow::Result<String> {
+    let driver_id = driver_bus_id(driver);
+    let message = SubmitPermit {
+        sender: sender.clone(),
+        permit,
+        network,
+    };
+    let tx_hash = bus::service(driver_id).send_as(sender, message).await??;
+    Ok(tx_hash)
+}
+
+pub async fn approve(
+    owner: String,
+    spender: String,
+    amount: BigDecimal,
+    driver: String,
+    network: Option<String>,
+) -> anyhow::Result<String> {
+    let driver_id = driver_bus_id(driver);
+    let message = Approve {
+        owner: owner.clone(),
+        spender,
+        amount,
+        network,
+    };
+    let tx_hash = bus::service(driver_id).send_as(owner, message).await??;
+    Ok(tx_hash)
+}
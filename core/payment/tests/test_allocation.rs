@@ -4,7 +4,7 @@ use test_context::test_context;
 
 use ya_client_model::payment::allocation::{PaymentPlatform, PaymentPlatformEnum};
 use ya_client_model::payment::{Acceptance, NewAllocation, NewInvoice};
-use ya_core_model::payment::local::GetStatus;
+use ya_core_model::payment::local::{AppKeyLimits, GetAppKeyLimits, GetStatus, SetAppKeyLimits};
 use ya_framework_basic::async_drop::DroppableTestContext;
 use ya_framework_basic::log::enable_logs;
 use ya_framework_basic::{resource, temp_dir};
@@ -280,3 +280,94 @@ async fn test_validate_allocation(ctx: &mut DroppableTestContext) -> anyhow::Res
     log::info!(" 👍🏻 Example completed successfully ❤️");
     Ok(())
 }
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_app_key_limits(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("test_app_key_limits")?;
+
+    let net = MockNet::new().bind();
+
+    let node = MockNode::new(net, "node-1", dir.path())
+        .with_identity()
+        .with_payment(None)
+        .with_fake_market();
+    node.bind_gsb().await?;
+    node.start_server(ctx).await?;
+
+    let appkey_req = node
+        .get_identity()?
+        .create_from_private_key(&resource!("ci-requestor-1.key.priv"))
+        .await?;
+    let requestor = node.rest_payments(&appkey_req.key)?;
+
+    let payment = node.get_payment()?;
+    payment
+        .fund_account(Driver::Erc20, &appkey_req.identity.to_string())
+        .await?;
+
+    let set_limits = SetAppKeyLimits {
+        owner: appkey_req.identity,
+        app_key: appkey_req.name.clone(),
+        limits: AppKeyLimits {
+            total: Some(BigDecimal::from(10u64)),
+            ..Default::default()
+        },
+    };
+
+    log::info!("App-key can't set its own limits through REST GSB bridge...");
+    let bridge_caller = format!("/local/{}", appkey_req.identity);
+    let result = payment
+        .gsb_local_endpoint()
+        .send_as(bridge_caller.clone(), set_limits.clone())
+        .await?;
+    assert!(result.is_err());
+
+    log::info!("...nor list limits of other identities.");
+    let result = payment
+        .gsb_local_endpoint()
+        .send_as(
+            bridge_caller,
+            GetAppKeyLimits {
+                owner: "0x0000000000000000000000000000000000000001".parse()?,
+                app_key: None,
+            },
+        )
+        .await?;
+    assert!(result.is_err());
+
+    log::info!("Setting limits...");
+    payment.gsb_local_endpoint().send(set_limits).await??;
+
+    let new_allocation = |amount: u64| NewAllocation {
+        address: Some(appkey_req.identity.to_string()),
+        payment_platform: Some(PaymentPlatformEnum::PaymentPlatformName(
+            "erc20-holesky-tglm".to_string(),
+        )),
+        total_amount: BigDecimal::from(amount),
+        timeout: None,
+        make_deposit: false,
+        deposit: None,
+        extend_timeout: None,
+    };
+
+    requestor.create_allocation(&new_allocation(6)).await?;
+    let result = requestor.create_allocation(&new_allocation(5)).await;
+    assert!(result.is_err());
+    log::info!("Failed to exceed total limit (as expected).");
+
+    let limits = payment
+        .gsb_local_endpoint()
+        .send(GetAppKeyLimits {
+            owner: appkey_req.identity,
+            app_key: Some(appkey_req.name.clone()),
+        })
+        .await??;
+    assert_eq!(limits.len(), 1);
+    assert_eq!(limits[0].allocated.len(), 1);
+    assert_eq!(limits[0].allocated[0].total, BigDecimal::from(6u64));
+    Ok(())
+}
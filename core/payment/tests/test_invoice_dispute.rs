@@ -0,0 +1,141 @@
+use awc::http::StatusCode;
+use bigdecimal::BigDecimal;
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use test_context::test_context;
+use url::Url;
+
+use ya_client_model::payment::{DocumentStatus, NewInvoice, PAYMENT_API_PATH};
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::{resource, temp_dir};
+use ya_framework_mocks::market::FakeMarket;
+use ya_framework_mocks::net::MockNet;
+use ya_framework_mocks::node::MockNode;
+
+/// Dispute endpoints are not available in `ya-client`.
+async fn call(
+    rest_url: &Url,
+    token: &str,
+    path: &str,
+    body: Option<Value>,
+) -> anyhow::Result<(StatusCode, Value)> {
+    let url = format!(
+        "{}{}{path}",
+        rest_url.as_str().trim_end_matches('/'),
+        PAYMENT_API_PATH
+    );
+    let client = awc::Client::default();
+    let mut response = match body {
+        Some(body) => client.post(url).bearer_auth(token).send_json(&body).await,
+        None => client.get(url).bearer_auth(token).send().await,
+    }
+    .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+    let body = response.body().await?;
+    let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    Ok((response.status(), body))
+}
+
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_invoice_dispute_flow(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("test_invoice_dispute_flow")?;
+    let dir = dir.path();
+
+    let net = MockNet::new().bind();
+
+    let node = MockNode::new(net, "node-1", dir)
+        .with_identity()
+        .with_payment(None)
+        .with_fake_market();
+    node.bind_gsb().await?;
+    node.start_server(ctx).await?;
+
+    let appkey_prov = node.get_identity()?.create_identity_key("provider").await?;
+    let appkey_req = node
+        .get_identity()?
+        .create_from_private_key(&resource!("ci-requestor-1.key.priv"))
+        .await?;
+
+    let agreement =
+        FakeMarket::create_fake_agreement(appkey_req.identity, appkey_prov.identity).unwrap();
+    node.get_market()?.add_agreement(agreement.clone()).await;
+
+    let requestor = node.rest_payments(&appkey_req.key)?;
+    let provider = node.rest_payments(&appkey_prov.key)?;
+    let rest_url = node.rest_url();
+
+    let new_invoice = NewInvoice {
+        agreement_id: agreement.agreement_id.to_string(),
+        activity_ids: None,
+        amount: BigDecimal::from_str("1.5")?,
+        payment_due_date: Utc::now(),
+    };
+    let invoice = provider.issue_invoice(&new_invoice).await?;
+    provider.send_invoice(&invoice.invoice_id).await?;
+
+    log::info!("Disputing invoice...");
+    let dispute = json!({
+        "reason": { "type": "usageMismatch" },
+        "totalAmountAccepted": "1",
+        "message": "Activity ran for 2 hours, not 3",
+    });
+    let path = format!("/invoices/{}/dispute", invoice.invoice_id);
+    let (status, _) = call(&rest_url, &appkey_req.key, &path, Some(dispute.clone())).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let invoice_status = provider.get_invoice(&invoice.invoice_id).await?.status;
+    assert_eq!(invoice_status, DocumentStatus::Rejected);
+
+    // Both sides keep the same dispute record
+    for key in [&appkey_req.key, &appkey_prov.key] {
+        let (status, record) = call(&rest_url, key, &path, None).await?;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(record["reason"], json!({ "type": "usageMismatch" }));
+        assert_eq!(record["message"], dispute["message"]);
+        assert_eq!(record["correctedInvoiceId"], Value::Null);
+    }
+
+    // Invoice can be disputed only once
+    let (status, _) = call(&rest_url, &appkey_req.key, &path, Some(dispute)).await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    log::info!("Resolving dispute...");
+    let corrected = provider
+        .issue_invoice(&NewInvoice {
+            amount: BigDecimal::from_str("1")?,
+            ..new_invoice
+        })
+        .await?;
+    let resolution = json!({ "correctedInvoiceId": corrected.invoice_id });
+    let resolve_path = format!("{path}/resolve");
+
+    // Corrected invoice must reach the Requestor first
+    let (status, _) = call(
+        &rest_url,
+        &appkey_prov.key,
+        &resolve_path,
+        Some(resolution.clone()),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    provider.send_invoice(&corrected.invoice_id).await?;
+    let (status, _) = call(&rest_url, &appkey_prov.key, &resolve_path, Some(resolution)).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    for key in [&appkey_req.key, &appkey_prov.key] {
+        let (_, record) = call(&rest_url, key, &path, None).await?;
+        assert_eq!(record["correctedInvoiceId"], json!(corrected.invoice_id));
+        assert_ne!(record["resolvedTs"], Value::Null);
+    }
+
+    let corrected = requestor.get_invoice(&corrected.invoice_id).await?;
+    assert_eq!(corrected.status, DocumentStatus::Received);
+    Ok(())
+}
@@ -4,10 +4,12 @@ use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::{Connection, SqliteConnection};
 use dotenv::dotenv;
 use r2d2::CustomizeConnection;
+use std::collections::HashMap;
 use std::env;
 use std::fmt::Display;
-use std::path::Path;
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct ProtectedPool {
@@ -26,6 +28,16 @@ type TxLock = Arc<RwLock<u64>>;
 pub type ConnType = PooledConnection<ConnectionManager<InnerConnType>>;
 pub type InnerConnType = SqliteConnection;
 
+/// Transaction locks of databases opened from data directory.
+/// Used to pause writes while maintenance tasks are running.
+static DATA_DIR_DBS: OnceLock<Mutex<HashMap<PathBuf, TxLock>>> = OnceLock::new();
+
+/// Queries taking longer than this many milliseconds are logged along with the time spent
+/// waiting for a connection. Defaults to 1000.
+pub const SLOW_QUERY_THRESHOLD_ENV_VAR: &str = "YAGNA_DB_SLOW_QUERY_MS";
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_secs(1);
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+
 const CONNECTION_INIT: &str = r"
 PRAGMA busy_timeout = 15000;
 PRAGMA synchronous = NORMAL;
@@ -130,14 +142,21 @@ impl DbExecutor {
 
     pub fn from_data_dir(data_dir: &Path, name: &str) -> Result<Self, Error> {
         let db = data_dir.join(name).with_extension("db");
-        Self::new(db.to_string_lossy())
+        let executor = Self::new(db.to_string_lossy())?;
+
+        DATA_DIR_DBS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .insert(db_key(&db), executor.pool.tx_lock.clone());
+        Ok(executor)
     }
 
     pub fn in_memory(name: &str) -> Result<Self, Error> {
         Self::new_with_pool_size(format!("file:{}?mode=memory&cache=shared", name), Some(1))
     }
 
-    fn conn(&self) -> Result<ConnType, Error> {
+    pub(crate) fn conn(&self) -> Result<ConnType, Error> {
         Ok(self.pool.get()?)
     }
 
@@ -195,9 +214,79 @@ impl DbExecutor {
     }
 }
 
+fn db_key(db_file: &Path) -> PathBuf {
+    db_file
+        .canonicalize()
+        .unwrap_or_else(|_| db_file.to_path_buf())
+}
+
+/// Runs `f` while write transactions to `db_file` opened by this process are on hold.
+/// Read-only transactions are not affected.
+pub fn with_writes_paused<R>(db_file: &Path, f: impl FnOnce() -> R) -> R {
+    let tx_lock = DATA_DIR_DBS
+        .get()
+        .and_then(|dbs| dbs.lock().unwrap().get(&db_key(db_file)).cloned());
+
+    match tx_lock {
+        Some(tx_lock) => {
+            let _guard = tx_lock.read().unwrap();
+            log::debug!("Writes to {} paused", db_file.display());
+            f()
+        }
+        None => f(),
+    }
+}
+
 pub trait AsDao<'a> {
     fn as_dao(pool: &'a PoolType) -> Self;
 }
+
+/// Overrides the threshold read from [`SLOW_QUERY_THRESHOLD_ENV_VAR`].
+/// Has no effect once any query has been executed.
+pub fn set_slow_query_threshold(threshold: Duration) -> bool {
+    SLOW_QUERY_THRESHOLD.set(threshold).is_ok()
+}
+
+fn slow_query_threshold() -> Duration {
+    *SLOW_QUERY_THRESHOLD.get_or_init(|| {
+        env::var(SLOW_QUERY_THRESHOLD_ENV_VAR)
+            .ok()
+            .and_then(|ms| ms.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD)
+    })
+}
+
+/// DAO name from a transaction label, e.g. `allocation_dao_get` -> `allocation`
+fn dao_name(label: &'static str) -> &'static str {
+    label.split("_dao").next().unwrap_or(label)
+}
+
+/// `requested` is the time the connection was requested from the pool,
+/// `started` the time it was obtained together with the transaction lock.
+fn record_query(
+    kind: &'static str,
+    label: &'static str,
+    requested: Instant,
+    started: Instant,
+    finished: Instant,
+) {
+    let dao = dao_name(label);
+    metrics::timing!("db.pool.wait.time", requested, started, "dao" => dao, "kind" => kind);
+    metrics::timing!("db.query.time", started, finished, "dao" => dao, "kind" => kind);
+
+    let query_time = finished.duration_since(started);
+    if query_time >= slow_query_threshold() {
+        metrics::counter!("db.query.slow", 1, "dao" => dao, "kind" => kind);
+        log::warn!(
+            "Slow {} query {}: {}ms, waited {}ms for connection",
+            kind,
+            label,
+            query_time.as_millis(),
+            started.duration_since(requested).as_millis()
+        );
+    }
+}
 static RO_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
 async fn do_with_ro_connection<R: Send + 'static, Error, F>(
@@ -213,16 +302,18 @@ where
 
     let count_no = RO_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     match tokio::task::spawn_blocking(move || {
+        let requested = Instant::now();
         let conn = pool.get()?;
         log::trace!("Start ro transaction no {}: {}", count_no, label);
 
         let rw_cnt = pool.tx_lock.read().unwrap();
         //log::info!("start ro tx: {}", *rw_cnt);
-        let start_query = std::time::Instant::now();
+        let start_query = Instant::now();
         let ret = f(&conn);
-        let end_query = std::time::Instant::now();
+        let end_query = Instant::now();
         //log::trace!("done ro tx: {}", *rw_cnt);
         drop(rw_cnt);
+        record_query("ro", label, requested, start_query, end_query);
         if ret.is_err() {
             log::trace!(
                 "Error in ro transaction no: {}: {}, time: {}ms",
@@ -264,13 +355,15 @@ where
     //log::warn!("Do_with_rw_connection {count_no}");
     let pool = pool.clone();
     match tokio::task::spawn_blocking(move || {
+        let requested = Instant::now();
         let conn = pool.get()?;
         log::trace!("Start rw transaction no {}: {}", count_no, label);
         let _guard = pool.tx_lock.write().unwrap();
-        let start_query = std::time::Instant::now();
+        let start_query = Instant::now();
         let res = f(&conn);
-        let end_query = std::time::Instant::now();
+        let end_query = Instant::now();
         drop(_guard);
+        record_query("rw", label, requested, start_query, end_query);
         if res.is_err() {
             log::trace!(
                 "Error in rw transaction no: {}: {}, time: {}ms",
@@ -360,3 +453,18 @@ impl DbMixedExecutor {
         AsMixedDao::as_dao(&self.disk_db.pool, &self.ram_db.pool)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dao_names() {
+        assert_eq!(dao_name("allocation_dao_get"), "allocation");
+        assert_eq!(dao_name("app_key_dao_list"), "app_key");
+        assert_eq!(
+            dao_name("activity_credentials_get"),
+            "activity_credentials_get"
+        );
+    }
+}
@@ -2,6 +2,7 @@
 extern crate diesel;
 
 pub mod executor;
+pub mod maintenance;
 #[cfg(feature = "service")]
 pub mod service;
 mod timestamp;
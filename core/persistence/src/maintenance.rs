@@ -0,0 +1,114 @@
+//! Online backup and integrity check of SQLite databases.
+
+use anyhow::bail;
+use libsqlite3_sys as ffi;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::path::Path;
+
+use crate::executor::{DbExecutor, Error};
+
+const BUSY_TIMEOUT_MS: c_int = 15000;
+const BACKUP_RETRY_MS: c_int = 100;
+const BACKUP_MAX_RETRIES: usize = 300;
+
+#[derive(QueryableByName)]
+struct IntegrityCheckRow {
+    #[sql_type = "diesel::sql_types::Text"]
+    integrity_check: String,
+}
+
+/// Runs `PRAGMA integrity_check` and returns reported problems.
+/// Empty result means the database is intact.
+pub fn integrity_check(db_file: &Path) -> Result<Vec<String>, Error> {
+    use diesel::RunQueryDsl;
+
+    let db = DbExecutor::new(db_file.display())?;
+    let rows: Vec<IntegrityCheckRow> =
+        diesel::sql_query("PRAGMA integrity_check;").load(&db.conn()?)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| row.integrity_check)
+        .filter(|msg| msg != "ok")
+        .collect())
+}
+
+/// Copies `src` database to `dst` file using SQLite online backup API.
+///
+/// The snapshot is consistent even if the database is being used,
+/// but writes from other processes will restart the copy.
+pub fn backup(src: &Path, dst: &Path) -> anyhow::Result<()> {
+    let src_conn = RawConnection::open(src, ffi::SQLITE_OPEN_READONLY)?;
+    let dst_conn = RawConnection::open(dst, ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE)?;
+    let main = CString::new("main")?;
+
+    unsafe {
+        ffi::sqlite3_busy_timeout(src_conn.0, BUSY_TIMEOUT_MS);
+
+        let backup = ffi::sqlite3_backup_init(dst_conn.0, main.as_ptr(), src_conn.0, main.as_ptr());
+        if backup.is_null() {
+            bail!(
+                "Failed to start backup of '{}': {}",
+                src.display(),
+                dst_conn.error_message()
+            );
+        }
+
+        let mut rc = ffi::sqlite3_backup_step(backup, -1);
+        let mut retries = 0;
+        while (rc == ffi::SQLITE_BUSY || rc == ffi::SQLITE_LOCKED) && retries < BACKUP_MAX_RETRIES {
+            ffi::sqlite3_sleep(BACKUP_RETRY_MS);
+            rc = ffi::sqlite3_backup_step(backup, -1);
+            retries += 1;
+        }
+        ffi::sqlite3_backup_finish(backup);
+
+        if rc != ffi::SQLITE_DONE {
+            bail!(
+                "Backup of '{}' failed: {}",
+                src.display(),
+                CStr::from_ptr(ffi::sqlite3_errstr(rc)).to_string_lossy()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Plain SQLite connection. Diesel doesn't expose handles required by backup API.
+struct RawConnection(*mut ffi::sqlite3);
+
+impl RawConnection {
+    fn open(path: &Path, flags: c_int) -> anyhow::Result<Self> {
+        let c_path = CString::new(path.to_string_lossy().as_bytes())?;
+        let mut db = std::ptr::null_mut();
+        let rc = unsafe { ffi::sqlite3_open_v2(c_path.as_ptr(), &mut db, flags, std::ptr::null()) };
+        // Handle is allocated even if opening fails and has to be closed anyway.
+        let conn = RawConnection(db);
+        if rc != ffi::SQLITE_OK {
+            bail!(
+                "Failed to open database '{}': {}",
+                path.display(),
+                conn.error_message()
+            );
+        }
+        Ok(conn)
+    }
+
+    fn error_message(&self) -> String {
+        if self.0.is_null() {
+            return "out of memory".to_string();
+        }
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.0)) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl Drop for RawConnection {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::sqlite3_close(self.0);
+        }
+    }
+}
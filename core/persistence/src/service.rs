@@ -3,11 +3,14 @@
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
-use ya_service_api::{CliCtx, CommandOutput};
+use ya_core_model::persistence as model;
+use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_api_interfaces::{Provider, Service};
+use ya_service_bus::{typed as bus, RpcEndpoint};
 use ya_utils_process::lock::ProcLock;
 
-use crate::executor::DbExecutor;
+use crate::executor::{with_writes_paused, DbExecutor};
+use crate::maintenance;
 
 /// Persistence service
 pub struct Persistence;
@@ -21,6 +24,28 @@ impl Persistence {
     pub async fn gsb<Context: Provider<Self, CliCtx>>(context: &Context) -> anyhow::Result<()> {
         let ctx = context.component();
         vacuum(&ctx.data_dir, filter::wal_larger_than_db, true).await?;
+
+        let data_dir = ctx.data_dir.clone();
+        let _ = bus::bind(model::BUS_ID, move |msg: model::Backup| {
+            let data_dir = data_dir.clone();
+            async move {
+                tokio::task::spawn_blocking(move || backup(&data_dir, &msg.dir))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string().into())
+            }
+        });
+
+        let data_dir = ctx.data_dir.clone();
+        let _ = bus::bind(model::BUS_ID, move |_: model::Check| {
+            let data_dir = data_dir.clone();
+            async move {
+                tokio::task::spawn_blocking(move || check(&data_dir))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string().into())
+            }
+        });
         Ok(())
     }
 }
@@ -35,22 +60,75 @@ pub enum Command {
         #[structopt(long)]
         force: bool,
     },
+    /// Copy databases to given directory. Safe to use while the daemon is running
+    Backup {
+        /// Directory to store database copies in
+        dir: PathBuf,
+    },
+    /// Verify integrity of databases
+    Check,
 }
 
 impl Command {
     pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
         match self {
             Command::Vacuum { force } => vacuum(&ctx.data_dir, filter::any, force).await,
+            Command::Backup { dir } => {
+                std::fs::create_dir_all(&dir)?;
+                // Daemon may be running in a different working directory.
+                let dir = dir.canonicalize()?;
+
+                let backups = if ProcLock::contains_locks(&ctx.data_dir)? {
+                    bus::service(model::BUS_ID)
+                        .send(model::Backup { dir })
+                        .await??
+                } else {
+                    let data_dir = ctx.data_dir.clone();
+                    tokio::task::spawn_blocking(move || backup(&data_dir, &dir)).await??
+                };
+
+                Ok(ResponseTable {
+                    columns: vec!["database".into(), "path".into(), "size [B]".into()],
+                    values: backups
+                        .into_iter()
+                        .map(|b| serde_json::json! {[b.database, b.path, b.size]})
+                        .collect(),
+                }
+                .into())
+            }
+            Command::Check => {
+                let checks = if ProcLock::contains_locks(&ctx.data_dir)? {
+                    bus::service(model::BUS_ID).send(model::Check {}).await??
+                } else {
+                    let data_dir = ctx.data_dir.clone();
+                    tokio::task::spawn_blocking(move || check(&data_dir)).await??
+                };
+
+                Ok(ResponseTable {
+                    columns: vec!["database".into(), "status".into()],
+                    values: checks
+                        .into_iter()
+                        .map(|c| {
+                            let status = match c.errors.is_empty() {
+                                true => "ok".to_string(),
+                                false => c.errors.join("\n"),
+                            };
+                            serde_json::json! {[c.database, status]}
+                        })
+                        .collect(),
+                }
+                .into())
+            }
         }
     }
 }
 
-async fn vacuum<F, P>(data_dir: P, filter: F, force: bool) -> anyhow::Result<CommandOutput>
+fn db_files<F, P>(data_dir: P, filter: F) -> anyhow::Result<Vec<PathBuf>>
 where
     F: Fn(&PathBuf) -> bool,
     P: AsRef<Path>,
 {
-    let db_files = std::fs::read_dir(&data_dir)?
+    Ok(std::fs::read_dir(&data_dir)?
         .filter_map(|r| r.map(|e| e.path()).ok())
         .filter(|p| !p.is_dir())
         .filter(|p| {
@@ -62,7 +140,58 @@ where
                 .unwrap_or(false)
         })
         .filter(filter)
-        .collect::<Vec<_>>();
+        .collect())
+}
+
+fn db_name(db_file: &Path) -> String {
+    db_file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn backup(data_dir: &Path, dir: &Path) -> anyhow::Result<Vec<model::BackupInfo>> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut backups = Vec::new();
+    for db_file in db_files(data_dir, filter::any)? {
+        let database = db_name(&db_file);
+        let path = dir.join(&database);
+        if path.exists() {
+            anyhow::bail!("Backup file '{}' already exists", path.display());
+        }
+
+        log::info!("backing up {} to {}", db_file.display(), path.display());
+        with_writes_paused(&db_file, || maintenance::backup(&db_file, &path))?;
+
+        backups.push(model::BackupInfo {
+            database,
+            size: path.metadata()?.len(),
+            path,
+        });
+    }
+    Ok(backups)
+}
+
+fn check(data_dir: &Path) -> anyhow::Result<Vec<model::CheckInfo>> {
+    db_files(data_dir, filter::any)?
+        .into_iter()
+        .map(|db_file| {
+            log::debug!("checking integrity of {}", db_file.display());
+            Ok(model::CheckInfo {
+                database: db_name(&db_file),
+                errors: maintenance::integrity_check(&db_file)?,
+            })
+        })
+        .collect()
+}
+
+async fn vacuum<F, P>(data_dir: P, filter: F, force: bool) -> anyhow::Result<CommandOutput>
+where
+    F: Fn(&PathBuf) -> bool,
+    P: AsRef<Path>,
+{
+    let db_files = db_files(&data_dir, filter)?;
 
     if db_files.is_empty() {
         return Ok(CommandOutput::Object(serde_json::Value::String(
@@ -118,8 +247,9 @@ mod tests {
     use ya_service_api::CommandOutput;
     use ya_utils_process::lock::ProcLock;
 
+    use crate::executor::DbExecutor;
     use crate::service::filter;
-    use crate::service::vacuum;
+    use crate::service::{backup, check, vacuum};
 
     fn touch_db<P: AsRef<Path>>(path: P, name: &str) -> anyhow::Result<()> {
         OpenOptions::new()
@@ -186,4 +316,36 @@ mod tests {
 
         Ok(())
     }
+    #[tokio::test]
+    async fn backup_and_check() -> anyhow::Result<()> {
+        let temp_dir = tempdir::TempDir::new("backup")?;
+        let data_dir = temp_dir.path().join("data");
+        let backup_dir = temp_dir.path().join("backup");
+        std::fs::create_dir_all(&data_dir)?;
+
+        let db = DbExecutor::from_data_dir(&data_dir, "test")?;
+        db.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, value TEXT);")
+            .await?;
+        db.execute("INSERT INTO test (value) VALUES ('backup');")
+            .await?;
+
+        let backups = backup(&data_dir, &backup_dir)?;
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].database, "test.db");
+        assert!(backups[0].size > 0);
+
+        let checks = check(&backup_dir)?;
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].errors.is_empty());
+
+        let copy = DbExecutor::new(backups[0].path.display())?;
+        assert_eq!(
+            copy.execute("UPDATE test SET value = 'copy' WHERE value = 'backup';")
+                .await?,
+            1
+        );
+
+        assert!(backup(&data_dir, &backup_dir).is_err());
+        Ok(())
+    }
 }
@@ -16,6 +16,7 @@ pub enum Component {
     Cli { flatten: bool },
     Db,
     Gsb,
+    Health,
     Rest,
 }
 
@@ -55,6 +56,7 @@ impl TryFrom<String> for Component {
             "cli" => Component::Cli { flatten: false },
             "db" => Component::Db,
             "gsb" => Component::Gsb,
+            "health" => Component::Health,
             "rest" => Component::Rest,
             _ => {
                 let message = format!("Unknown component: {}", name);
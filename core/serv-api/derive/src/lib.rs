@@ -65,6 +65,7 @@ fn define_enum(
     let cli = define_cli_services(&item.vis, &ident, &services);
     let gsb = define_gsb_services(&services, context);
     let rest = define_rest_services(&services, context);
+    let health = define_health_services(&services);
 
     quote! {
         #cli
@@ -72,6 +73,7 @@ fn define_enum(
         impl #ident {
             #gsb
             #rest
+            #health
         }
     }
 }
@@ -206,3 +208,28 @@ fn define_rest_services(
         }
     }
 }
+
+fn define_health_services(services: &[Service]) -> proc_macro2::TokenStream {
+    let mut inner = proc_macro2::TokenStream::new();
+    for service in services
+        .iter()
+        .filter(|service| service.supports(Component::Health))
+    {
+        let path = &service.path;
+        let service_name = format!("{}", &service.name);
+        inner.extend(quote! {
+            statuses.push((#service_name, #path::health().await));
+        });
+    }
+
+    quote! {
+        /// Health of services with the `health` component enabled, in declaration order.
+        #[allow(clippy::vec_init_then_push)]
+        pub async fn health() -> Vec<(&'static str, ya_service_api_interfaces::HealthStatus)> {
+            #[allow(unused_mut)]
+            let mut statuses = Vec::new();
+            #inner
+            statuses
+        }
+    }
+}
@@ -6,7 +6,7 @@ use actix_web::{
 };
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 use ya_service_api_derive::services;
-use ya_service_api_interfaces::Provider;
+use ya_service_api_interfaces::{HealthStatus, Provider};
 
 pub struct CommandOutput;
 pub struct CliCtx;
@@ -48,6 +48,10 @@ pub mod gsb_rest {
             actix_web::Scope::new("/gsb-rest-api")
                 .service(web::resource("/test").to(HttpResponse::Ok))
         }
+
+        pub async fn health() -> HealthStatus {
+            HealthStatus::Healthy
+        }
     }
 }
 
@@ -142,15 +146,19 @@ pub mod rest_cli {
             actix_web::Scope::new("/rest-cli-api")
                 .service(web::resource("/tester").to(HttpResponse::Ok))
         }
+
+        pub async fn health() -> HealthStatus {
+            HealthStatus::Degraded("no tester".into())
+        }
     }
 }
 
 #[services(ServiceContext)]
 #[derive(PartialEq)]
 enum Services {
-    #[enable(gsb, rest)]
+    #[enable(gsb, rest, health)]
     GsbRest(gsb_rest::GsbRest),
-    #[enable(rest, cli)]
+    #[enable(rest, cli, health)]
     RestCli(rest_cli::RestCli),
     #[enable(cli(flatten), gsb)]
     GsbCli(gsb_cli::GsbCli),
@@ -199,6 +207,17 @@ async fn test_rest() {
     assert_eq!(resp.status(), StatusCode::NOT_FOUND);
 }
 
+#[actix_rt::test]
+async fn test_health() {
+    assert_eq!(
+        Services::health().await,
+        vec![
+            ("GsbRest", HealthStatus::Healthy),
+            ("RestCli", HealthStatus::Degraded("no tester".into())),
+        ]
+    );
+}
+
 #[test]
 fn test_cli_help() {
     // given
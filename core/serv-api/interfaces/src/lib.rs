@@ -11,3 +11,25 @@ impl<Service, Component: Clone> Provider<Service, Component> for Component {
         self.clone()
     }
 }
+
+/// Result of a service health check, see the `health` component of the `#[services]` macro.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy,
+    /// Service works with limited functionality.
+    Degraded(String),
+    Unhealthy(String),
+}
+
+impl HealthStatus {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            HealthStatus::Healthy => None,
+            HealthStatus::Degraded(details) | HealthStatus::Unhealthy(details) => Some(details),
+        }
+    }
+}
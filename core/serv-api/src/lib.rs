@@ -1,15 +1,41 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use ya_core_model::bus::GsbBindPoints;
 pub use ya_utils_cli::{CommandOutput, ResponseTable};
 
-#[derive(Clone, Debug, Default)]
+mod shutdown;
+pub use shutdown::ShutdownCoordinator;
+
+#[derive(Clone, Debug)]
 pub struct MetricsCtx {
     pub push_enabled: bool,
     pub push_host_url: Option<url::Url>,
+    pub push_interval: Duration,
     pub job: String,
     pub labels: HashMap<String, String>,
+    /// Group pushed metrics by the node id (`instance` label)
+    pub instance_label: bool,
+    /// Group pushed metrics by the identity alias (`hostname` label)
+    pub hostname_label: bool,
+    /// Series labels aggregated away before push
+    pub rollup_labels: Vec<String>,
+}
+
+impl Default for MetricsCtx {
+    fn default() -> Self {
+        MetricsCtx {
+            push_enabled: false,
+            push_host_url: None,
+            push_interval: Duration::from_secs(60),
+            job: Default::default(),
+            labels: Default::default(),
+            instance_label: true,
+            hostname_label: true,
+            rollup_labels: Default::default(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -0,0 +1,132 @@
+use futures::future::LocalBoxFuture;
+use futures::{Future, FutureExt};
+use std::time::{Duration, Instant};
+
+struct ShutdownStage {
+    name: String,
+    timeout: Duration,
+    stop: LocalBoxFuture<'static, anyhow::Result<()>>,
+}
+
+/// Stops Yagna services one after another in the order of registration, so
+/// services can still use their dependencies while stopping (e.g. payments are
+/// sent before Net sessions are closed).
+///
+/// Stage which doesn't finish in its timeout is abandoned and the next one starts.
+/// Ctrl+C during shutdown abandons all remaining stages.
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    stages: Vec<ShutdownStage>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stage<F>(mut self, name: impl ToString, timeout: Duration, stop: F) -> Self
+    where
+        F: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        self.stages.push(ShutdownStage {
+            name: name.to_string(),
+            timeout,
+            stop: stop.boxed_local(),
+        });
+        self
+    }
+
+    pub async fn run(self) {
+        let total = self.stages.len();
+        let interrupted = tokio::signal::ctrl_c().fuse();
+        futures::pin_mut!(interrupted);
+
+        for (idx, stage) in self.stages.into_iter().enumerate() {
+            let started = Instant::now();
+            log::info!(
+                "Shutdown [{}/{}]: stopping {} (timeout: {:?})... Hit Ctrl+C to shut down immediately.",
+                idx + 1,
+                total,
+                stage.name,
+                stage.timeout
+            );
+
+            tokio::select! {
+                result = tokio::time::timeout(stage.timeout, stage.stop) => match result {
+                    Ok(Ok(())) => log::info!(
+                        "Shutdown [{}/{}]: {} stopped in {:.1?}.",
+                        idx + 1,
+                        total,
+                        stage.name,
+                        started.elapsed()
+                    ),
+                    Ok(Err(e)) => log::error!(
+                        "Shutdown [{}/{}]: error stopping {}: {}",
+                        idx + 1,
+                        total,
+                        stage.name,
+                        e
+                    ),
+                    Err(_) => log::warn!(
+                        "Shutdown [{}/{}]: {} didn't stop in {:?}, continuing.",
+                        idx + 1,
+                        total,
+                        stage.name,
+                        stage.timeout
+                    ),
+                },
+                _ = &mut interrupted => {
+                    log::warn!(
+                        "Shutdown interrupted while stopping {}, skipping remaining stages.",
+                        stage.name
+                    );
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[tokio::test]
+    async fn test_stages_order_and_timeout() {
+        let stopped = Rc::new(RefCell::new(Vec::new()));
+        let stage = |name: &'static str, delay: Duration| {
+            let stopped = stopped.clone();
+            async move {
+                tokio::time::sleep(delay).await;
+                stopped.borrow_mut().push(name);
+                Ok(())
+            }
+        };
+
+        ShutdownCoordinator::new()
+            .stage(
+                "rest",
+                Duration::from_secs(1),
+                stage("rest", Duration::ZERO),
+            )
+            .stage(
+                "slow",
+                Duration::from_millis(10),
+                stage("slow", Duration::from_secs(5)),
+            )
+            .stage("failing", Duration::from_secs(1), async {
+                anyhow::bail!("failure")
+            })
+            .stage(
+                "net",
+                Duration::from_secs(1),
+                stage("net", Duration::from_millis(5)),
+            )
+            .run()
+            .await;
+
+        assert_eq!(*stopped.borrow(), vec!["rest", "net"]);
+    }
+}
@@ -84,6 +84,7 @@ async fn main() -> anyhow::Result<()> {
                         role: model::DEFAULT_ROLE.to_string(),
                         identity,
                         allow_origins: vec![],
+                        expires: None,
                     };
 
                     let app_key = bus::service(model::BUS_ID)
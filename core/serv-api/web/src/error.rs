@@ -0,0 +1,206 @@
+//! Error envelope returned by REST APIs.
+//!
+//! Superset of `ya-client-model`'s `ErrorMessage`: the `message` field is kept as is,
+//! while `code`, `category` and `retryable` let clients handle failures without
+//! parsing messages. Codes are `SCREAMING_SNAKE_CASE` and stable across releases.
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+pub const BAD_REQUEST: &str = "BAD_REQUEST";
+pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
+pub const FORBIDDEN: &str = "FORBIDDEN";
+pub const NOT_FOUND: &str = "NOT_FOUND";
+pub const CONFLICT: &str = "CONFLICT";
+pub const GONE: &str = "GONE";
+pub const TIMEOUT: &str = "TIMEOUT";
+pub const NOT_IMPLEMENTED: &str = "NOT_IMPLEMENTED";
+pub const REMOTE_ERROR: &str = "REMOTE_ERROR";
+pub const INTERNAL_ERROR: &str = "INTERNAL_ERROR";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCategory {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Gone,
+    Timeout,
+    NotImplemented,
+    /// Failure of a remote node the request was forwarded to
+    Remote,
+    Internal,
+}
+
+impl ErrorCategory {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ErrorCategory::BadRequest => StatusCode::BAD_REQUEST,
+            ErrorCategory::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCategory::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCategory::NotFound => StatusCode::NOT_FOUND,
+            ErrorCategory::Conflict => StatusCode::CONFLICT,
+            ErrorCategory::Gone => StatusCode::GONE,
+            ErrorCategory::Timeout => StatusCode::REQUEST_TIMEOUT,
+            ErrorCategory::NotImplemented => StatusCode::NOT_IMPLEMENTED,
+            ErrorCategory::Remote => StatusCode::BAD_GATEWAY,
+            ErrorCategory::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Whether repeating the same request later may succeed
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            ErrorCategory::Timeout | ErrorCategory::Remote | ErrorCategory::Internal
+        )
+    }
+
+    /// Code used when there is no more specific one
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCategory::BadRequest => BAD_REQUEST,
+            ErrorCategory::Unauthorized => UNAUTHORIZED,
+            ErrorCategory::Forbidden => FORBIDDEN,
+            ErrorCategory::NotFound => NOT_FOUND,
+            ErrorCategory::Conflict => CONFLICT,
+            ErrorCategory::Gone => GONE,
+            ErrorCategory::Timeout => TIMEOUT,
+            ErrorCategory::NotImplemented => NOT_IMPLEMENTED,
+            ErrorCategory::Remote => REMOTE_ERROR,
+            ErrorCategory::Internal => INTERNAL_ERROR,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub message: Option<String>,
+    pub code: String,
+    pub category: ErrorCategory,
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, Value>,
+}
+
+impl ApiError {
+    pub fn new(category: ErrorCategory, code: impl Into<String>, message: impl ToString) -> Self {
+        ApiError {
+            message: Some(message.to_string()),
+            code: code.into(),
+            category,
+            retryable: category.retryable(),
+            details: Default::default(),
+        }
+    }
+
+    /// Error with the generic code of the category and no message
+    pub fn empty(category: ErrorCategory) -> Self {
+        ApiError {
+            message: None,
+            code: category.code().to_string(),
+            category,
+            retryable: category.retryable(),
+            details: Default::default(),
+        }
+    }
+
+    pub fn bad_request(code: impl Into<String>, message: impl ToString) -> Self {
+        Self::new(ErrorCategory::BadRequest, code, message)
+    }
+
+    pub fn not_found(code: impl Into<String>, message: impl ToString) -> Self {
+        Self::new(ErrorCategory::NotFound, code, message)
+    }
+
+    pub fn internal(message: impl ToString) -> Self {
+        Self::new(ErrorCategory::Internal, INTERNAL_ERROR, message)
+    }
+
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.details.insert(key.into(), value);
+        self
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Response with the status code of the error category
+    pub fn response(&self) -> HttpResponse {
+        self.response_with(self.category.status_code())
+    }
+
+    /// Response with a status code other than the default one of the category
+    pub fn response_with(&self, status: StatusCode) -> HttpResponse {
+        HttpResponse::build(status).json(self)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {}", self.code, message),
+            None => write!(f, "{}", self.code),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.category.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        self.response()
+    }
+}
+
+impl From<ApiError> for HttpResponse {
+    fn from(e: ApiError) -> Self {
+        e.response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compatible_with_error_message() {
+        let e = ApiError::not_found("AGREEMENT_NOT_FOUND", "Agreement [a] not found")
+            .with_detail("agreementId", "a");
+        let json = serde_json::to_value(&e).unwrap();
+
+        assert_eq!(json["message"], "Agreement [a] not found");
+        assert_eq!(json["code"], "AGREEMENT_NOT_FOUND");
+        assert_eq!(json["category"], "notFound");
+        assert_eq!(json["retryable"], false);
+        assert_eq!(json["details"]["agreementId"], "a");
+
+        let msg: ya_client::model::ErrorMessage = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(msg.message.as_deref(), Some("Agreement [a] not found"));
+        assert_eq!(serde_json::from_value::<ApiError>(json).unwrap(), e);
+    }
+
+    #[test]
+    fn category_defaults() {
+        let e = ApiError::empty(ErrorCategory::Timeout);
+        assert_eq!(e.code, TIMEOUT);
+        assert!(e.retryable);
+        assert_eq!(e.response().status(), StatusCode::REQUEST_TIMEOUT);
+        assert!(serde_json::to_value(&e).unwrap().get("details").is_none());
+        assert!(!ApiError::bad_request(BAD_REQUEST, "x").retryable);
+    }
+}
@@ -1,4 +1,6 @@
+pub mod error;
 pub mod middleware;
+pub mod openapi;
 pub mod scope;
 
 pub use ya_client::web::{rest_api_url, DEFAULT_YAGNA_API_URL, YAGNA_API_URL_ENV_VAR};
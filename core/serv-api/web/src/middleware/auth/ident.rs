@@ -71,6 +71,26 @@ impl FromRequest for Identity {
     }
 }
 
+/// Key the request was authorized with. After rotation it can be
+/// the previous key of the app-key, during its grace period.
+#[derive(Clone)]
+pub struct AuthKey(pub String);
+
+impl FromRequest for AuthKey {
+    type Error = EmptyError;
+    type Future = future::Ready<Result<Self, Self::Error>>;
+
+    fn from_request(
+        req: &HttpRequest,
+        _payload: &mut Payload<Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>>,
+    ) -> Self::Future {
+        match req.extensions().get::<AuthKey>() {
+            Some(v) => future::ok(v.clone()),
+            None => future::err(EmptyError {}),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct EmptyError;
 
@@ -2,7 +2,7 @@ pub mod dummy;
 pub mod ident;
 pub mod resolver;
 
-pub use crate::middleware::auth::ident::Identity;
+pub use crate::middleware::auth::ident::{AuthKey, Identity};
 pub use crate::middleware::auth::resolver::AppKeyCache;
 
 use actix_service::{Service, Transform};
@@ -98,6 +98,7 @@ where
                 Some(key) => match cache.get_appkey(&key) {
                     Some(app_key) => {
                         req.extensions_mut().insert(Identity::from(app_key));
+                        req.extensions_mut().insert(AuthKey(key));
                         let fut = { service.borrow_mut().call(req) };
                         Ok(fut.await?)
                     }
@@ -1,4 +1,5 @@
 use anyhow::anyhow;
+use chrono::Utc;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
@@ -39,14 +40,12 @@ impl AppKeyCache {
             }
         }
 
-        let mapping = appkeys
-            .into_iter()
-            .map(|appkey| (appkey.key.clone(), appkey))
-            .collect::<HashMap<_, _>>();
-
         let appkey_cache = AppKeyCache {
-            appkeys: Arc::new(RwLock::new(mapping)),
+            appkeys: Default::default(),
         };
+        for appkey in appkeys {
+            appkey_cache.update(appkey);
+        }
         appkey_cache
             .listen_events()
             .await
@@ -54,9 +53,13 @@ impl AppKeyCache {
         Ok(appkey_cache)
     }
 
+    /// Returns app-key authorized by `key`, unless the key has expired.
     pub fn get_appkey(&self, key: &str) -> Option<AppKey> {
         match self.appkeys.read() {
-            Ok(keymap) => keymap.get(key).cloned(),
+            Ok(keymap) => keymap
+                .get(key)
+                .filter(|appkey| appkey.accepts(key, Utc::now().naive_utc()))
+                .cloned(),
             Err(_) => None,
         }
     }
@@ -80,12 +83,21 @@ impl AppKeyCache {
             .collect()
     }
 
-    fn update(&self, key: &str, appkey: Option<AppKey>) {
+    /// Registers app-key under its current and previous key, replacing
+    /// the keys it was registered under before rotation.
+    fn update(&self, appkey: AppKey) {
+        if let Ok(mut keymap) = self.appkeys.write() {
+            keymap.retain(|_, cached| cached.name != appkey.name);
+            if let Some(previous) = &appkey.previous_key {
+                keymap.insert(previous.key.clone(), appkey.clone());
+            }
+            keymap.insert(appkey.key.clone(), appkey);
+        }
+    }
+
+    fn remove(&self, appkey: &AppKey) {
         if let Ok(mut keymap) = self.appkeys.write() {
-            match appkey {
-                Some(appkey) => keymap.insert(key.to_string(), appkey),
-                None => keymap.remove(key),
-            };
+            keymap.retain(|_, cached| cached.name != appkey.name);
         }
     }
 
@@ -106,11 +118,11 @@ impl AppKeyCache {
                             appkey.name,
                             appkey.allow_origins
                         );
-                        this.update(&appkey.key.clone(), Some(appkey))
+                        this.update(appkey)
                     }
                     AppKeyEvent::DroppedKey(appkey) => {
                         log::debug!("Removing CORS for app-key: {}", appkey.name);
-                        this.update(&appkey.key, None)
+                        this.remove(&appkey)
                     }
                 };
                 Ok(())
@@ -0,0 +1,510 @@
+//! OpenAPI 3 documents describing the REST scopes mounted by the service.
+//!
+//! REST modules register their handlers through an [`ApiScope`], which describes each route
+//! with the same path and method it mounts, so the spec can't drift from the routes.
+//! Schemas are generated from the `JsonSchema` implementations of request and response bodies.
+//! `ya-client-model` types refer to the ya-client specs instead, see [`ya_core_model::schema`].
+use actix_web::dev::Handler;
+use actix_web::{web, FromRequest, Responder, Scope};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use ya_core_model::schema::{client_model, client_model_array};
+
+use crate::scope::ExtendableScope;
+
+pub const API_SPEC_PATH: &str = "/api-spec";
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+type SchemaFn = fn(&mut SchemaGenerator) -> schemars::schema::Schema;
+
+/// Schema of a request or response body
+#[derive(Clone, Copy)]
+pub enum Schema {
+    /// JSON document
+    Json(SchemaFn),
+    /// Raw bytes
+    Binary,
+    /// Server-Sent Events stream
+    EventStream,
+}
+
+impl Schema {
+    pub fn of<T: JsonSchema>() -> Self {
+        Schema::Json(subschema::<T>)
+    }
+
+    /// `ya-client-model` object
+    pub fn model<T>() -> Self {
+        Schema::Json(client_model::<T>)
+    }
+
+    /// List of `ya-client-model` objects
+    pub fn array_of_model<T>() -> Self {
+        Schema::Json(client_model_array::<T>)
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Schema::Json(_) => "application/json",
+            Schema::Binary => "application/octet-stream",
+            Schema::EventStream => "text/event-stream",
+        }
+    }
+
+    fn to_content(self, gen: &mut SchemaGenerator) -> Value {
+        let schema = match self {
+            Schema::Json(schema) => serde_json::to_value(schema(gen)).unwrap_or_default(),
+            Schema::Binary => json!({ "type": "string", "format": "binary" }),
+            Schema::EventStream => json!({ "type": "string" }),
+        };
+        json!({ self.content_type(): { "schema": schema } })
+    }
+}
+
+fn subschema<T: JsonSchema>(gen: &mut SchemaGenerator) -> schemars::schema::Schema {
+    gen.subschema_for::<T>()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+    Options,
+    Trace,
+}
+
+impl Method {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Post => "post",
+            Method::Put => "put",
+            Method::Patch => "patch",
+            Method::Delete => "delete",
+            Method::Head => "head",
+            Method::Options => "options",
+            Method::Trace => "trace",
+        }
+    }
+
+    fn route(&self) -> actix_web::Route {
+        match self {
+            Method::Get => web::get(),
+            Method::Post => web::post(),
+            Method::Put => web::put(),
+            Method::Patch => web::patch(),
+            Method::Delete => web::delete(),
+            Method::Head => web::head(),
+            Method::Options => web::method(actix_web::http::Method::OPTIONS),
+            Method::Trace => web::trace(),
+        }
+    }
+}
+
+/// Description of a single route. `path` is relative to the scope.
+#[derive(Clone)]
+pub struct Operation {
+    method: Method,
+    path: String,
+    operation_id: String,
+    body: Option<Schema>,
+    response: Option<Schema>,
+}
+
+impl Operation {
+    pub fn new(method: Method, path: &str, operation_id: &str) -> Self {
+        Operation {
+            method,
+            path: path.to_string(),
+            operation_id: operation_id.to_string(),
+            body: None,
+            response: None,
+        }
+    }
+
+    pub fn get(path: &str, operation_id: &str) -> Self {
+        Self::new(Method::Get, path, operation_id)
+    }
+
+    pub fn post(path: &str, operation_id: &str) -> Self {
+        Self::new(Method::Post, path, operation_id)
+    }
+
+    pub fn put(path: &str, operation_id: &str) -> Self {
+        Self::new(Method::Put, path, operation_id)
+    }
+
+    pub fn patch(path: &str, operation_id: &str) -> Self {
+        Self::new(Method::Patch, path, operation_id)
+    }
+
+    pub fn delete(path: &str, operation_id: &str) -> Self {
+        Self::new(Method::Delete, path, operation_id)
+    }
+
+    pub fn body(mut self, schema: Schema) -> Self {
+        self.body = Some(schema);
+        self
+    }
+
+    pub fn response(mut self, schema: Schema) -> Self {
+        self.response = Some(schema);
+        self
+    }
+}
+
+/// Routes of a single REST scope, e.g. `/market-api/v1`
+#[derive(Clone)]
+pub struct ScopeSpec {
+    tag: String,
+    base_path: String,
+    operations: Vec<Operation>,
+}
+
+impl ScopeSpec {
+    fn new(tag: &str, base_path: &str) -> Self {
+        let base_path = base_path.trim_end_matches('/');
+        let base_path = match base_path.starts_with('/') {
+            true => base_path.to_string(),
+            false => format!("/{}", base_path),
+        };
+        ScopeSpec {
+            tag: tag.to_string(),
+            base_path,
+            operations: Default::default(),
+        }
+    }
+}
+
+/// actix `Scope`, which describes the routes registered with [`ApiScope::route`]
+pub struct ApiScope {
+    scope: Scope,
+    spec: ScopeSpec,
+}
+
+impl ApiScope {
+    pub fn new(tag: &str, path: &str) -> Self {
+        ApiScope {
+            scope: web::scope(path),
+            spec: ScopeSpec::new(tag, path),
+        }
+    }
+
+    /// Mounts `handler` at the path and method of the `operation`
+    pub fn route<F, Args>(mut self, operation: Operation, handler: F) -> Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        self.scope = self
+            .scope
+            .route(&operation.path, operation.method.route().to(handler));
+        self.spec.operations.push(operation);
+        self
+    }
+
+    /// Mounts `handler` without describing it. Only for methods OpenAPI can't express: CONNECT.
+    pub fn undescribed_route<F, Args>(
+        mut self,
+        path: &str,
+        method: actix_web::http::Method,
+        handler: F,
+    ) -> Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        self.scope = self.scope.route(path, web::method(method).to(handler));
+        self
+    }
+
+    pub fn app_data<U: 'static>(mut self, data: U) -> Self {
+        self.scope = self.scope.app_data(data);
+        self
+    }
+
+    pub fn into_scope(self) -> Scope {
+        self.scope
+    }
+
+    pub fn into_spec(self) -> ScopeSpec {
+        self.spec
+    }
+}
+
+impl ExtendableScope for ApiScope {
+    fn extend<F>(self, f: F) -> Self
+    where
+        Self: Sized,
+        F: FnOnce(Self) -> Self,
+    {
+        f(self)
+    }
+}
+
+/// OpenAPI document merged from the specs of all mounted scopes
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiSpec {
+    openapi: &'static str,
+    info: Value,
+    paths: BTreeMap<String, BTreeMap<&'static str, Value>>,
+    components: Value,
+}
+
+impl ApiSpec {
+    pub fn new(title: &str, version: &str, scopes: impl IntoIterator<Item = ScopeSpec>) -> Self {
+        let mut paths: BTreeMap<String, BTreeMap<&'static str, Value>> = BTreeMap::new();
+        let mut gen = SchemaSettings::openapi3().into_generator();
+
+        for scope in scopes {
+            for op in &scope.operations {
+                let path = format!("{}{}", scope.base_path, op.path);
+                paths.entry(openapi_path(&path)).or_default().insert(
+                    op.method.as_str(),
+                    operation(&scope.tag, &path, op, &mut gen),
+                );
+            }
+        }
+
+        ApiSpec {
+            openapi: OPENAPI_VERSION,
+            info: json!({ "title": title, "version": version }),
+            paths,
+            components: json!({
+                "schemas": gen.take_definitions(),
+                "securitySchemes": {
+                    "app_key": { "type": "http", "scheme": "bearer" }
+                }
+            }),
+        }
+    }
+}
+
+fn operation(tag: &str, path: &str, op: &Operation, gen: &mut SchemaGenerator) -> Value {
+    let mut result = Map::new();
+    result.insert("operationId".into(), json!(op.operation_id));
+    result.insert("tags".into(), json!([tag]));
+    result.insert("security".into(), json!([{ "app_key": [] }]));
+
+    let parameters = path_params(path)
+        .into_iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            })
+        })
+        .collect::<Vec<_>>();
+    if !parameters.is_empty() {
+        result.insert("parameters".into(), Value::Array(parameters));
+    }
+
+    if let Some(body) = op.body {
+        result.insert(
+            "requestBody".into(),
+            json!({
+                "required": true,
+                "content": body.to_content(gen),
+            }),
+        );
+    }
+
+    let response = match op.response {
+        Some(schema) => json!({
+            "description": "OK",
+            "content": schema.to_content(gen),
+        }),
+        None => json!({ "description": "OK" }),
+    };
+    let error = Schema::of::<crate::error::ApiError>();
+    result.insert(
+        "responses".into(),
+        json!({
+            "200": response,
+            "default": {
+                "description": "Error",
+                "content": error.to_content(gen),
+            },
+        }),
+    );
+
+    Value::Object(result)
+}
+
+/// Path parameters declared as `{name}` or `{name:regex}`
+fn path_params(path: &str) -> Vec<String> {
+    path.split('{')
+        .skip(1)
+        .filter_map(|s| s.split('}').next())
+        .map(|s| s.split(':').next().unwrap_or(s).to_string())
+        .collect()
+}
+
+/// actix `{name:regex}` segments are not valid in OpenAPI paths
+fn openapi_path(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    let (mut param, mut regex) = (false, false);
+    for c in path.chars() {
+        match c {
+            '{' if !param => param = true,
+            ':' if param => regex = true,
+            '}' if param => (param, regex) = (false, false),
+            _ => (),
+        }
+        if !regex {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App, HttpResponse};
+
+    #[allow(dead_code)]
+    #[derive(Serialize, JsonSchema)]
+    struct Offer {
+        offer_id: String,
+        properties: Value,
+    }
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    fn market_scope() -> ApiScope {
+        ApiScope::new("market", "/market-api/v1")
+            .route(
+                Operation::post("/offers", "subscribeOffer")
+                    .body(Schema::of::<Offer>())
+                    .response(Schema::of::<String>()),
+                ok,
+            )
+            .route(
+                Operation::get("/offers", "getOffers").response(Schema::of::<Vec<Offer>>()),
+                ok,
+            )
+            .route(
+                Operation::get("/offers/ids", "getOfferIds").response(Schema::of::<Vec<String>>()),
+                ok,
+            )
+            .route(
+                Operation::delete("/offers/{subscription_id}", "unsubscribeOffer"),
+                ok,
+            )
+            .route(
+                Operation::get(
+                    "/activity/{activity_id}/proxy-ws/{url:.*}",
+                    "proxyWebsocket",
+                )
+                .response(Schema::Binary),
+                ok,
+            )
+    }
+
+    #[test]
+    fn build_spec() {
+        let spec = ApiSpec::new("yagna", "0.0.0", vec![market_scope().into_spec()]);
+        let spec = serde_json::to_value(spec).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        let schemas = &spec["components"]["schemas"];
+
+        assert_eq!(spec["openapi"], OPENAPI_VERSION);
+        assert_eq!(paths["/market-api/v1/offers"].as_object().unwrap().len(), 2);
+        assert_eq!(
+            paths["/market-api/v1/offers"]["get"]["responses"]["200"]["content"]
+                ["application/json"]["schema"]["items"]["$ref"],
+            "#/components/schemas/Offer"
+        );
+        assert_eq!(
+            paths["/market-api/v1/offers/{subscription_id}"]["delete"]["parameters"][0]["name"],
+            "subscription_id"
+        );
+        assert!(paths.contains_key("/market-api/v1/activity/{activity_id}/proxy-ws/{url}"));
+        assert_eq!(
+            paths["/market-api/v1/offers/ids"]["get"]["responses"]["200"]["content"]
+                ["application/json"]["schema"]["items"]["type"],
+            "string"
+        );
+        assert_eq!(schemas["Offer"]["properties"]["offer_id"]["type"], "string");
+        assert_eq!(
+            paths["/market-api/v1/offers"]["get"]["responses"]["default"]["content"]
+                ["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ApiError"
+        );
+        assert!(schemas["ApiError"]["properties"]["code"].is_object());
+        assert!(schemas.get("String").is_none());
+    }
+
+    #[test]
+    fn client_model_schemas() {
+        #[allow(dead_code)]
+        struct Invoice;
+
+        let scope = ApiScope::new("payment", "/payment-api/v1").route(
+            Operation::get("/invoices", "getInvoices")
+                .response(Schema::array_of_model::<Invoice>()),
+            ok,
+        );
+        let spec = ApiSpec::new("yagna", "0.0.0", vec![scope.into_spec()]);
+        let spec = serde_json::to_value(spec).unwrap();
+
+        let items = &spec["paths"]["/payment-api/v1/invoices"]["get"]["responses"]["200"]
+            ["content"]["application/json"]["schema"]["items"]["$ref"];
+        assert!(items
+            .as_str()
+            .unwrap()
+            .ends_with("#/components/schemas/Invoice"));
+    }
+
+    #[actix_rt::test]
+    async fn described_routes_are_mounted() {
+        use actix_web::http::Method as HttpMethod;
+
+        let scope = market_scope()
+            .route(
+                Operation::new(Method::Options, "/offers", "getOfferOptions"),
+                ok,
+            )
+            .undescribed_route("/offers", HttpMethod::CONNECT, ok);
+        let app = test::init_service(App::new().service(scope.into_scope())).await;
+
+        for (method, path) in [
+            (HttpMethod::POST, "/market-api/v1/offers"),
+            (HttpMethod::GET, "/market-api/v1/offers"),
+            (HttpMethod::OPTIONS, "/market-api/v1/offers"),
+            (HttpMethod::CONNECT, "/market-api/v1/offers"),
+            (HttpMethod::DELETE, "/market-api/v1/offers/1234"),
+            (HttpMethod::GET, "/market-api/v1/activity/1/proxy-ws/a/b"),
+        ] {
+            let req = test::TestRequest::default()
+                .method(method)
+                .uri(path)
+                .to_request();
+            assert!(test::call_service(&app, req).await.status().is_success());
+        }
+
+        // Methods not described are not mounted either
+        let req = test::TestRequest::put()
+            .uri("/market-api/v1/offers")
+            .to_request();
+        assert!(test::call_service(&app, req)
+            .await
+            .status()
+            .is_client_error());
+    }
+}
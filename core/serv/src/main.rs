@@ -4,11 +4,12 @@ use actix_web::{middleware, web, App, HttpResponse, HttpServer, Responder};
 use anyhow::{Context, Result};
 use futures::prelude::*;
 use metrics::{counter, gauge};
+use ya_healthcheck::model as health;
 use ya_healthcheck::HealthcheckService;
 #[cfg(feature = "static-openssl")]
 extern crate openssl_probe;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::{
     any::TypeId,
     collections::HashMap,
@@ -21,20 +22,25 @@ use std::{
 use structopt::{clap, StructOpt};
 use url::Url;
 use ya_activity::service::Activity as ActivityService;
-use ya_file_logging::start_logger;
+use ya_file_logging::{reset_log_spec, start_logger, update_log_spec, LoggerHandle};
 use ya_gsb_api::GsbApiService;
 use ya_identity::service::Identity as IdentityService;
 use ya_market::MarketService;
-use ya_metrics::{MetricsPusherOpts, MetricsService};
+use ya_metrics::{MetricsPusherOpts, MetricsService, PushLabel};
 use ya_net::Net as NetService;
 use ya_payment::PaymentService;
 use ya_persistence::executor::{DbExecutor, DbMixedExecutor};
 use ya_persistence::service::Persistence as PersistenceService;
 use ya_sb_proto::{DEFAULT_GSB_URL, GSB_URL_ENV_VAR};
-use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
+use ya_service_api::{CliCtx, CommandOutput, MetricsCtx, ResponseTable, ShutdownCoordinator};
 use ya_service_api_interfaces::Provider;
 use ya_service_api_web::{
-    middleware::{auth, cors::CorsConfig, Identity},
+    middleware::{
+        auth::{self, AuthKey},
+        cors::CorsConfig,
+        Identity,
+    },
+    openapi::{ApiSpec, API_SPEC_PATH},
     rest_api_host_port, DEFAULT_YAGNA_API_URL, YAGNA_API_URL_ENV_VAR,
 };
 use ya_sgx::SgxService;
@@ -43,6 +49,7 @@ use ya_utils_process::lock::ProcLock;
 use ya_version::VersionService;
 use ya_vpn::VpnService;
 
+use ya_core_model::appkey;
 use ya_service_bus::typed as gsb;
 
 mod autocomplete;
@@ -63,6 +70,10 @@ lazy_static::lazy_static! {
 }
 
 const FD_METRICS_INTERVAL: Duration = Duration::from_secs(60);
+/// Additional time for a shutdown stage on top of the time given to the service itself.
+const SHUTDOWN_STAGE_MARGIN: Duration = Duration::from_secs(5);
+const ACTIVITY_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+const NET_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(StructOpt, Debug)]
 #[structopt(about = clap::crate_description!())]
@@ -125,8 +136,18 @@ impl CliArgs {
     pub async fn run_command(self) -> Result<()> {
         let ctx: CliCtx = (&self).try_into()?;
 
-        ctx.output(self.command.run_command(&ctx).await?)?;
-        Ok(())
+        match self.command.run_command(&ctx).await {
+            Ok(output) => ctx.output(output),
+            Err(err) => {
+                // Scripts parsing `--json` output get the error in the same format.
+                if ctx.json_output {
+                    ctx.output(CommandOutput::object(
+                        serde_json::json!({ "error": err.to_string() }),
+                    )?)?;
+                }
+                Err(err)
+            }
+        }
     }
 }
 
@@ -208,7 +229,16 @@ impl ServiceContext {
     }
 
     fn set_metrics_ctx(&mut self, metrics_opts: &MetricsPusherOpts) {
-        self.ctx.metrics_ctx = Some(metrics_opts.into())
+        let mut metrics_ctx: MetricsCtx = metrics_opts.into();
+        if metrics_opts
+            .metrics_push_labels
+            .contains(&PushLabel::Driver)
+        {
+            metrics_ctx
+                .labels
+                .insert("driver".to_string(), payment_driver_names().join(","));
+        }
+        self.ctx.metrics_ctx = Some(metrics_ctx)
     }
 }
 
@@ -256,7 +286,7 @@ enum Services {
     Metrics(MetricsService),
     #[enable(gsb, rest, cli)]
     Version(VersionService),
-    #[enable(gsb, rest, cli)]
+    #[enable(gsb, rest, cli, health)]
     Net(NetService),
     //TODO enable VpnService::rest for v2 / or create common scope for v1 and v2
     #[enable(rest)]
@@ -275,9 +305,24 @@ enum Services {
     GsbApi(GsbApiService),
 }
 
-#[cfg(not(any(feature = "dummy-driver", feature = "erc20-driver",)))]
+#[cfg(not(any(
+    feature = "dummy-driver",
+    feature = "erc20-driver",
+    feature = "devnet-sim-driver",
+)))]
 compile_error!("At least one payment driver needs to be enabled in order to make payments.");
 
+fn payment_driver_names() -> Vec<&'static str> {
+    vec![
+        #[cfg(feature = "dummy-driver")]
+        ya_dummy_driver::DRIVER_NAME,
+        #[cfg(feature = "erc20-driver")]
+        ya_erc20_driver::DRIVER_NAME,
+        #[cfg(feature = "devnet-sim-driver")]
+        ya_devnet_sim_driver::DRIVER_NAME,
+    ]
+}
+
 async fn start_payment_drivers(data_dir: &Path) -> anyhow::Result<Vec<String>> {
     let mut drivers = vec![];
     #[cfg(feature = "dummy-driver")]
@@ -292,6 +337,12 @@ async fn start_payment_drivers(data_dir: &Path) -> anyhow::Result<Vec<String>> {
         PaymentDriverService::gsb(data_dir.to_path_buf()).await?;
         drivers.push(DRIVER_NAME.to_owned());
     }
+    #[cfg(feature = "devnet-sim-driver")]
+    {
+        use ya_devnet_sim_driver::{PaymentDriverService, DRIVER_NAME};
+        PaymentDriverService::gsb(&()).await?;
+        drivers.push(DRIVER_NAME.to_owned());
+    }
     Ok(drivers)
 }
 
@@ -314,6 +365,13 @@ enum CliCommand {
     #[structopt(setting = clap::AppSettings::DeriveDisplayOrder)]
     Extension(ExtensionCommand),
 
+    /// Miscellaneous daemon utilities
+    #[structopt(setting = clap::AppSettings::DeriveDisplayOrder)]
+    Misc(MiscCommand),
+
+    /// Show status of the running daemon
+    Status(StatusCommand),
+
     #[structopt(external_subcommand)]
     #[structopt(setting = structopt::clap::AppSettings::Hidden)]
     Other(Vec<String>),
@@ -329,6 +387,8 @@ impl CliCommand {
             CliCommand::Complete(complete) => complete.run_command(ctx),
             CliCommand::Service(service) => service.run_command(ctx).await,
             CliCommand::Extension(ext) => ext.run_command(ctx).await,
+            CliCommand::Misc(misc) => misc.run_command().await,
+            CliCommand::Status(status) => status.run_command(ctx).await,
             CliCommand::Other(args) => extension::run::<CliArgs>(ctx, args).await,
         }
     }
@@ -410,6 +470,119 @@ impl ExtensionCommand {
     }
 }
 
+#[derive(StructOpt, Debug)]
+struct StatusCommand {
+    /// Include health of individual services
+    #[structopt(long)]
+    health: bool,
+}
+
+impl StatusCommand {
+    pub async fn run_command(self, ctx: &CliCtx) -> Result<CommandOutput> {
+        let statuses = match gsb::service(health::BUS_ID)
+            .call(health::GetHealth::default())
+            .await
+        {
+            Ok(result) => result.map_err(anyhow::Error::msg)?,
+            Err(e) => {
+                log::debug!("Unable to reach daemon: {}", e);
+                return CommandOutput::object(serde_json::json!({ "running": false }));
+            }
+        };
+
+        if !self.health {
+            return CommandOutput::object(serde_json::json!({ "running": true }));
+        }
+        if ctx.json_output {
+            return CommandOutput::object(statuses);
+        }
+        Ok(ResponseTable {
+            columns: vec!["service".into(), "state".into(), "details".into()],
+            values: statuses
+                .into_iter()
+                .map(|status| {
+                    serde_json::json! {[
+                        status.service,
+                        status.state,
+                        status.details.unwrap_or_default(),
+                    ]}
+                })
+                .collect(),
+        }
+        .into())
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum MiscCommand {
+    /// Change log levels of the running daemon
+    LogLevel(LogLevelCommand),
+}
+
+#[derive(StructOpt, Debug)]
+enum LogLevelCommand {
+    /// Show log levels in effect
+    Show,
+    /// Change log levels, e.g. `ya_net=trace` or `debug,ya_market=info`
+    Set { spec: String },
+    /// Restore log levels set at daemon start
+    Reset,
+}
+
+impl MiscCommand {
+    pub async fn run_command(self) -> Result<CommandOutput> {
+        match self {
+            MiscCommand::LogLevel(command) => {
+                let spec = match command {
+                    LogLevelCommand::Show => {
+                        gsb::service(model::BUS_ID)
+                            .call(model::GetLogLevel::default())
+                            .await?
+                    }
+                    LogLevelCommand::Set { spec } => {
+                        gsb::service(model::BUS_ID)
+                            .call(model::SetLogLevel { spec })
+                            .await?
+                    }
+                    LogLevelCommand::Reset => {
+                        gsb::service(model::BUS_ID)
+                            .call(model::ResetLogLevel::default())
+                            .await?
+                    }
+                };
+                CommandOutput::object(spec.map_err(anyhow::Error::msg)?)
+            }
+        }
+    }
+}
+
+fn bind_log_level_service(logger_handle: Arc<Mutex<LoggerHandle>>) {
+    let handle = logger_handle.clone();
+    gsb::bind(model::BUS_ID, move |request: model::SetLogLevel| {
+        let result = update_log_spec(&mut handle.lock().unwrap(), &request.spec);
+        future::ready(result.map_err(|e| e.to_string()))
+    });
+    gsb::bind(model::BUS_ID, move |_: model::ResetLogLevel| {
+        let result = reset_log_spec(&mut logger_handle.lock().unwrap());
+        future::ready(result.map_err(|e| e.to_string()))
+    });
+    gsb::bind(model::BUS_ID, move |_: model::GetLogLevel| {
+        future::ready(
+            ya_file_logging::current_log_spec().ok_or_else(|| "Logger not started".to_string()),
+        )
+    });
+}
+
+fn bind_health_service() {
+    gsb::bind(health::BUS_ID, |_: health::GetHealth| async {
+        Ok(Services::health()
+            .await
+            .into_iter()
+            .map(|(service, status)| health::ServiceHealth::new(service, status))
+            .collect())
+    });
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(StructOpt, Debug)]
 enum ServiceCommand {
@@ -528,7 +701,7 @@ impl ServiceCommand {
                     module_filters.push(("ya_payment::service", log::LevelFilter::Debug));
                 }
 
-                let logger_handle = start_logger(
+                let logger_handle = Arc::new(Mutex::new(start_logger(
                     "info",
                     log_dir.as_deref().or(Some(&ctx.data_dir)).and_then(|path| {
                         match path.components().count() {
@@ -538,7 +711,7 @@ impl ServiceCommand {
                     }),
                     &module_filters,
                     force_debug,
-                )?;
+                )?));
 
                 let app_name = clap::crate_name!();
                 log::info!(
@@ -556,10 +729,13 @@ impl ServiceCommand {
                 ya_sb_router::bind_gsb_router(ctx.gsb_url.clone())
                     .await
                     .context("binding service bus router")?;
+                bind_log_level_service(logger_handle.clone());
+                bind_health_service();
 
                 let mut context: ServiceContext = ctx.clone().try_into()?;
                 context.set_metrics_ctx(metrics_opts);
                 Services::gsb(&context).await?;
+                let mut activity_tracker = context.activity_tracker.clone();
 
                 ya_compile_time_utils::report_version_to_metrics();
 
@@ -582,7 +758,10 @@ impl ServiceCommand {
                         .wrap(cors.cors())
                         .route("/dashboard", web::get().to(redirect_to_dashboard))
                         .route("/dashboard/{_:.*}", web::get().to(dashboard_serve))
+                        .route(API_SPEC_PATH, web::get().to(api_spec))
                         .route("/me", web::get().to(me))
+                        .route("/me/appKey", web::get().to(me_app_key))
+                        .route("/me/appKey/rotate", web::post().to(rotate_me_app_key))
                         .service(forward_gsb);
                     let rest = Services::rest(app, &context);
                     if count_started.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
@@ -642,13 +821,22 @@ impl ServiceCommand {
 
                 log::info!("{} service successfully finished!", app_name);
 
-                PaymentService::shut_down().await;
-                NetService::shutdown()
-                    .await
-                    .map_err(|e| log::error!("Error shutting down NET: {}", e))
-                    .ok();
-
-                logger_handle.shutdown();
+                // REST API is already stopped. Finished activities are paid for,
+                // and payments need Net to reach other nodes, so Net sessions are closed last.
+                ShutdownCoordinator::new()
+                    .stage("activities", ACTIVITY_DRAIN_TIMEOUT, async move {
+                        activity_tracker.drain().await
+                    })
+                    .stage(
+                        "payment service",
+                        PaymentService::shutdown_timeout() + SHUTDOWN_STAGE_MARGIN,
+                        PaymentService::shut_down(),
+                    )
+                    .stage("net", NET_SHUTDOWN_TIMEOUT, NetService::shutdown())
+                    .run()
+                    .await;
+
+                logger_handle.lock().unwrap().shutdown();
                 Ok(CommandOutput::NoOutput)
             }
             Self::Shutdown(opts) => {
@@ -697,6 +885,72 @@ async fn me(id: Identity) -> impl Responder {
     web::Json(id)
 }
 
+/// OpenAPI document of the mounted REST scopes
+async fn api_spec() -> impl Responder {
+    web::Json(ApiSpec::new(
+        clap::crate_name!(),
+        ya_compile_time_utils::semver_str!(),
+        [
+            ActivityService::api_spec(),
+            MarketService::api_spec(),
+            PaymentService::api_spec(),
+            GsbApiService::api_spec(),
+        ],
+    ))
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RotateAppKey {
+    #[serde(default = "default_grace_period_secs")]
+    grace_period_secs: u64,
+    #[serde(default)]
+    expires: Option<chrono::NaiveDateTime>,
+}
+
+fn default_grace_period_secs() -> u64 {
+    3600
+}
+
+fn app_key_error(e: appkey::Error) -> actix_web::Error {
+    let status = actix_web::http::StatusCode::from_u16(e.code as u16)
+        .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR);
+    actix_web::error::InternalError::new(e.message, status).into()
+}
+
+/// Current key and expiration date of the calling app-key. After rotation,
+/// the previous key can be used to fetch the new one until its grace period ends.
+async fn me_app_key(id: Identity) -> impl Responder {
+    let app_key = gsb::service(appkey::BUS_ID)
+        .call(appkey::GetByName { name: id.name })
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .map_err(app_key_error)?;
+    Ok::<_, actix_web::Error>(web::Json(app_key))
+}
+
+/// Rotates the calling app-key. Only the current key can rotate, and the new key
+/// expires no later than the current one.
+async fn rotate_me_app_key(
+    id: Identity,
+    key: AuthKey,
+    body: web::Json<RotateAppKey>,
+) -> impl Responder {
+    let body = body.into_inner();
+    let app_key = gsb::service(appkey::BUS_ID)
+        .call(appkey::Rotate {
+            name: id.name,
+            identity: Some(id.identity.to_string()),
+            grace_period_secs: body.grace_period_secs,
+            expires: body.expires,
+            current_key: Some(key.0),
+        })
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .map_err(app_key_error)?;
+    Ok::<_, actix_web::Error>(web::Json(app_key))
+}
+
 #[actix_web::post("/_gsb/{service:.*}")]
 async fn forward_gsb(
     id: Identity,
@@ -15,3 +15,35 @@ impl RpcMessage for ShutdownRequest {
     type Item = ();
     type Error = String;
 }
+
+/// Changes log levels of the running daemon. `spec` uses `RUST_LOG` syntax,
+/// e.g. `ya_net=trace`. Returns log specification in effect.
+#[derive(Serialize, Deserialize)]
+pub struct SetLogLevel {
+    pub spec: String,
+}
+
+impl RpcMessage for SetLogLevel {
+    const ID: &'static str = "SetLogLevel";
+    type Item = String;
+    type Error = String;
+}
+
+/// Restores log levels set at daemon start.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ResetLogLevel {}
+
+impl RpcMessage for ResetLogLevel {
+    const ID: &'static str = "ResetLogLevel";
+    type Item = String;
+    type Error = String;
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct GetLogLevel {}
+
+impl RpcMessage for GetLogLevel {
+    const ID: &'static str = "GetLogLevel";
+    type Item = String;
+    type Error = String;
+}
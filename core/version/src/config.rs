@@ -0,0 +1,225 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveTime, Utc, Weekday};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use structopt::StructOpt;
+
+/// Release channel the node follows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateChannel {
+    Stable,
+    Rc,
+    Nightly,
+}
+
+impl UpdateChannel {
+    /// Checks whether a release version belongs to the channel.
+    pub fn includes(&self, version: &str) -> bool {
+        let pre_release = version.split_once('-').map(|(_, pre)| pre);
+        match self {
+            UpdateChannel::Stable => pre_release.is_none(),
+            UpdateChannel::Rc => pre_release.map(|pre| pre.starts_with("rc")).unwrap_or(true),
+            UpdateChannel::Nightly => true,
+        }
+    }
+}
+
+impl FromStr for UpdateChannel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "stable" => Ok(UpdateChannel::Stable),
+            "rc" => Ok(UpdateChannel::Rc),
+            "nightly" => Ok(UpdateChannel::Nightly),
+            other => anyhow::bail!("Unknown update channel: {other}"),
+        }
+    }
+}
+
+impl fmt::Display for UpdateChannel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateChannel::Stable => write!(f, "stable"),
+            UpdateChannel::Rc => write!(f, "rc"),
+            UpdateChannel::Nightly => write!(f, "nightly"),
+        }
+    }
+}
+
+/// UTC time range during which updates are allowed, e.g. `Sat 02:00-04:00`.
+/// Windows without a day repeat daily. Ranges may span midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub day: Option<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl MaintenanceWindow {
+    fn length(&self) -> ChronoDuration {
+        match self.end > self.start {
+            true => self.end - self.start,
+            false => self.end - self.start + ChronoDuration::days(1),
+        }
+    }
+
+    /// Window starts within `days` days from `now`, in chronological order
+    fn starts(&self, now: DateTime<Utc>, days: i64) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        (-1..=days)
+            .map(move |offset| now.date_naive() + ChronoDuration::days(offset))
+            .filter(move |date| self.day.map(|day| date.weekday() == day).unwrap_or(true))
+            .map(move |date| date.and_time(self.start).and_utc())
+    }
+
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        self.starts(now, 0)
+            .any(|start| start <= now && now < start + self.length())
+    }
+
+    pub fn next_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.starts(now, 7).find(|start| *start > now)
+    }
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (day, range) = match s.split_once(' ') {
+            Some((day, range)) => (
+                Some(
+                    day.parse::<Weekday>()
+                        .map_err(|_| anyhow::anyhow!("Invalid day of week: {day}"))?,
+                ),
+                range,
+            ),
+            None => (None, s),
+        };
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("Invalid maintenance window: {s}"))?;
+
+        Ok(MaintenanceWindow {
+            day,
+            start: NaiveTime::parse_from_str(start.trim(), "%H:%M")?,
+            end: NaiveTime::parse_from_str(end.trim(), "%H:%M")?,
+        })
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "{day} ")?;
+        }
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+#[derive(StructOpt, Clone, Debug)]
+pub struct VersionConfig {
+    /// Release channel checked for updates: stable, rc or nightly
+    #[structopt(long, env = "YAGNA_UPDATE_CHANNEL", default_value = "stable")]
+    pub update_channel: UpdateChannel,
+    /// Interval between checks for a new release
+    #[structopt(
+        long,
+        env = "YAGNA_VERSION_CHECK_INTERVAL",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "24h"
+    )]
+    pub version_check_interval: Duration,
+    /// Comma-separated UTC windows during which update prompts are shown,
+    /// e.g. `Sat 02:00-04:00,Sun 02:00-04:00`. Always allowed when empty.
+    #[structopt(long, env = "YAGNA_UPDATE_WINDOWS", use_delimiter = true)]
+    pub update_windows: Vec<MaintenanceWindow>,
+}
+
+impl VersionConfig {
+    pub fn from_env() -> Result<Self, structopt::clap::Error> {
+        // Empty command line arguments, because we want to use ENV fallback
+        // or default values if ENV variables are not set.
+        VersionConfig::from_iter_safe(&[""])
+    }
+
+    pub fn update_allowed(&self, now: DateTime<Utc>) -> bool {
+        self.update_windows.is_empty() || self.update_windows.iter().any(|w| w.contains(now))
+    }
+
+    pub fn next_window(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.update_windows
+            .iter()
+            .filter_map(|w| w.next_start(now))
+            .min()
+    }
+}
+
+impl Default for VersionConfig {
+    fn default() -> Self {
+        VersionConfig {
+            update_channel: UpdateChannel::Stable,
+            version_check_interval: Duration::from_secs(3600 * 24),
+            update_windows: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        // 2024-06-01 is a Saturday
+        Utc.with_ymd_and_hms(2024, 6, d, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn channel_includes() {
+        assert!(UpdateChannel::Stable.includes("0.16.0"));
+        assert!(!UpdateChannel::Stable.includes("0.16.0-rc3"));
+        assert!(UpdateChannel::Rc.includes("0.16.0-rc3"));
+        assert!(UpdateChannel::Rc.includes("0.16.0"));
+        assert!(!UpdateChannel::Rc.includes("0.16.0-nightly.20240601"));
+        assert!(UpdateChannel::Nightly.includes("0.16.0-nightly.20240601"));
+    }
+
+    #[test]
+    fn weekly_window() {
+        let w: MaintenanceWindow = "Sat 02:00-04:00".parse().unwrap();
+        assert_eq!(w.to_string(), "Sat 02:00-04:00");
+        assert!(w.contains(at(1, 3, 0)));
+        assert!(!w.contains(at(1, 4, 0)));
+        assert!(!w.contains(at(2, 3, 0)));
+        assert_eq!(w.next_start(at(1, 3, 0)), Some(at(8, 2, 0)));
+        assert_eq!(w.next_start(at(1, 1, 0)), Some(at(1, 2, 0)));
+    }
+
+    #[test]
+    fn daily_window_over_midnight() {
+        let w: MaintenanceWindow = "23:00-01:00".parse().unwrap();
+        assert!(w.contains(at(1, 23, 30)));
+        assert!(w.contains(at(2, 0, 30)));
+        assert!(!w.contains(at(2, 1, 30)));
+        assert_eq!(w.next_start(at(2, 1, 30)), Some(at(2, 23, 0)));
+        assert!("Someday 01:00-02:00".parse::<MaintenanceWindow>().is_err());
+    }
+
+    #[test]
+    fn update_allowed() {
+        let mut config = VersionConfig::default();
+        assert!(config.update_allowed(at(3, 12, 0)));
+        assert_eq!(config.next_window(at(3, 12, 0)), None);
+
+        config.update_windows = vec!["Sat 02:00-04:00".parse().unwrap()];
+        assert!(!config.update_allowed(at(3, 12, 0)));
+        assert_eq!(config.next_window(at(3, 12, 0)), Some(at(8, 2, 0)));
+    }
+}
@@ -5,6 +5,7 @@ use ya_persistence::executor::{
     do_with_transaction, readonly_transaction, AsDao, ConnType, PoolType,
 };
 
+use crate::config::UpdateChannel;
 use crate::db::model::DBRelease;
 use crate::db::schema::version_release::dsl as release;
 use crate::db::schema::version_release::dsl::version_release;
@@ -46,29 +47,32 @@ impl<'c> ReleaseDAO<'c> {
         .await
     }
 
-    pub async fn pending_release(&self) -> anyhow::Result<Option<Release>> {
+    pub async fn pending_release(&self, channel: UpdateChannel) -> anyhow::Result<Option<Release>> {
         readonly_transaction(self.pool, "version_dao_pending_release", move |conn| {
-            get_pending_release(conn, false)
+            get_pending_release(conn, false, channel)
         })
         .await
     }
 
-    pub async fn version(&self) -> anyhow::Result<VersionInfo> {
+    pub async fn version(&self, channel: UpdateChannel) -> anyhow::Result<VersionInfo> {
         log::debug!("Getting Yagna version: current and pending from DB");
         readonly_transaction(self.pool, "version_dao_version", move |conn| {
             Ok(VersionInfo {
                 current: get_current_release(conn)?
                     .unwrap_or_else(|| DBRelease::current().unwrap().into()),
-                pending: get_pending_release(conn, true)?,
+                pending: get_pending_release(conn, true, channel)?,
             })
         })
         .await
     }
 
-    pub async fn skip_pending_release(&self) -> anyhow::Result<Option<Release>> {
+    pub async fn skip_pending_release(
+        &self,
+        channel: UpdateChannel,
+    ) -> anyhow::Result<Option<Release>> {
         log::debug!("Skipping latest pending Yagna release");
         do_with_transaction(self.pool, "version_dao_skip_pending_release", move |conn| {
-            let mut pending_rel = match get_pending_release(conn, false)? {
+            let mut pending_rel = match get_pending_release(conn, false, channel)? {
                 Some(rel) => rel,
                 None => return Ok(None),
             };
@@ -98,17 +102,25 @@ fn get_release(conn: &ConnType, ver: &str) -> anyhow::Result<Option<Release>> {
         .map(|db_rel| db_rel.into()))
 }
 
-fn get_pending_release(conn: &ConnType, include_seen: bool) -> anyhow::Result<Option<Release>> {
+fn get_pending_release(
+    conn: &ConnType,
+    include_seen: bool,
+    channel: UpdateChannel,
+) -> anyhow::Result<Option<Release>> {
     let mut query = version_release
         // insertion_ts is to distinguish among fake-entries of `DBRelease::current`
-        .filter(release::version.not_like("%rc%"))
         .order((release::release_ts.desc(), release::insertion_ts.desc()))
         .into_boxed();
     if !include_seen {
         query = query.filter(release::seen.eq(false));
     }
 
-    match query.first::<DBRelease>(conn).optional()? {
+    let latest = query
+        .load::<DBRelease>(conn)?
+        .into_iter()
+        .find(|db_rel| channel.includes(&db_rel.version));
+
+    match latest {
         Some(db_rel) => {
             let running_ver = ya_compile_time_utils::semver_str!();
             if !bump_is_greater(running_ver, &db_rel.version)
@@ -1,11 +1,14 @@
 use anyhow::anyhow;
+use chrono::Utc;
 use metrics::counter;
-use self_update::backends::github::UpdateBuilder;
+use self_update::backends::github::{ReleaseList, UpdateBuilder};
 use std::convert::TryFrom;
 
+use ya_compile_time_utils::tag2semver;
 use ya_core_model::version::Release;
 use ya_persistence::executor::DbExecutor;
 
+use crate::config::{UpdateChannel, VersionConfig};
 use crate::db::dao::ReleaseDAO;
 use crate::db::model::DBRelease;
 use crate::service::cli::ReleaseMessage;
@@ -13,19 +16,13 @@ use crate::service::cli::ReleaseMessage;
 const REPO_OWNER: &str = "golemfactory";
 const REPO_NAME: &str = "yagna";
 
-pub async fn check_latest_release(db: &DbExecutor) -> anyhow::Result<Release> {
-    log::debug!("Checking latest Yagna release");
-    let gh_rel = tokio::task::spawn_blocking(|| -> anyhow::Result<self_update::update::Release> {
-        Ok(UpdateBuilder::new()
-            .repo_owner(REPO_OWNER)
-            .repo_name(REPO_NAME)
-            .bin_name("") // seems required by builder but unused
-            .current_version("") // similar as above
-            .target_version_tag("latest")
-            .build()?
-            .get_latest_release()?)
-    })
-    .await??;
+pub async fn check_latest_release(
+    db: &DbExecutor,
+    config: &VersionConfig,
+) -> anyhow::Result<Release> {
+    let channel = config.update_channel;
+    log::debug!("Checking latest Yagna release on {} channel", channel);
+    let gh_rel = tokio::task::spawn_blocking(move || latest_release(channel)).await??;
 
     log::trace!("Got latest Yagna release {:?}", gh_rel);
 
@@ -49,11 +46,36 @@ pub async fn check_latest_release(db: &DbExecutor) -> anyhow::Result<Release> {
         })?
     {
         counter!("version.new", 1);
-        log::warn!("{}", ReleaseMessage::Available(&rel));
+        if config.update_allowed(Utc::now()) {
+            log::warn!("{}", ReleaseMessage::Available(&rel));
+        }
     };
     Ok(rel)
 }
 
+fn latest_release(channel: UpdateChannel) -> anyhow::Result<self_update::update::Release> {
+    if channel == UpdateChannel::Stable {
+        return Ok(UpdateBuilder::new()
+            .repo_owner(REPO_OWNER)
+            .repo_name(REPO_NAME)
+            .bin_name("") // seems required by builder but unused
+            .current_version("") // similar as above
+            .target_version_tag("latest")
+            .build()?
+            .get_latest_release()?);
+    }
+
+    // Releases are listed from the most recent one, pre-releases included
+    ReleaseList::configure()
+        .repo_owner(REPO_OWNER)
+        .repo_name(REPO_NAME)
+        .build()?
+        .fetch()?
+        .into_iter()
+        .find(|rel| channel.includes(tag2semver(&rel.version)))
+        .ok_or_else(|| anyhow!("No release found on {} channel", channel))
+}
+
 pub(crate) async fn check_running_release(db: &DbExecutor) -> anyhow::Result<Release> {
     if let Some(release) = db.as_dao::<ReleaseDAO>().current_release().await? {
         return Ok(release);
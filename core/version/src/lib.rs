@@ -3,6 +3,7 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+mod config;
 mod db;
 mod github;
 mod notifier;
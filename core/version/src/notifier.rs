@@ -1,47 +1,53 @@
+use chrono::Utc;
 use std::time::Duration;
 
 use ya_persistence::executor::DbExecutor;
 
+use crate::config::VersionConfig;
 use crate::db::dao::ReleaseDAO;
 use crate::github;
 use crate::github::check_running_release;
 use crate::service::cli::ReleaseMessage;
 
-pub async fn on_start(db: &DbExecutor) -> anyhow::Result<()> {
+pub async fn on_start(db: &DbExecutor, config: &VersionConfig) -> anyhow::Result<()> {
     check_running_release(db).await?;
 
-    if let Err(e) = github::check_latest_release(db).await {
+    if let Err(e) = github::check_latest_release(db, config).await {
         log::error!("Failed to check for new Yagna release: {}", e);
     };
 
     let worker_db = db.clone();
-    tokio::task::spawn_local(async move { crate::notifier::worker(worker_db).await });
+    let worker_config = config.clone();
+    tokio::task::spawn_local(
+        async move { crate::notifier::worker(worker_db, worker_config).await },
+    );
     let pinger_db = db.clone();
-    tokio::task::spawn_local(async move { crate::notifier::pinger(pinger_db).await });
+    let pinger_config = config.clone();
+    tokio::task::spawn_local(
+        async move { crate::notifier::pinger(pinger_db, pinger_config).await },
+    );
 
     Ok(())
 }
 
-pub(crate) async fn worker(db: DbExecutor) {
-    // TODO: make interval configurable
-    let interval = Duration::from_secs(3600 * 24);
+pub(crate) async fn worker(db: DbExecutor, config: VersionConfig) {
     loop {
-        tokio::time::sleep(interval).await;
-        if let Err(e) = github::check_latest_release(&db).await {
+        tokio::time::sleep(config.version_check_interval).await;
+        if let Err(e) = github::check_latest_release(&db, &config).await {
             log::error!("Failed to check for new Yagna release: {}", e);
         };
     }
 }
 
-pub(crate) async fn pinger(db: DbExecutor) -> ! {
+pub(crate) async fn pinger(db: DbExecutor, config: VersionConfig) -> ! {
     // TODO: make interval configurable
     let interval = Duration::from_secs(30 * 60);
     loop {
         let release_dao = db.as_dao::<ReleaseDAO>();
         tokio::time::sleep(interval).await;
-        match release_dao.pending_release().await {
+        match release_dao.pending_release(config.update_channel).await {
             Ok(Some(release)) => {
-                if !release.seen {
+                if !release.seen && config.update_allowed(Utc::now()) {
                     log::warn!("{}", ReleaseMessage::Available(&release))
                 }
             }
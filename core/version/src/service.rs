@@ -1,6 +1,7 @@
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_interfaces::{Provider, Service};
 
+use crate::config::VersionConfig;
 use crate::db::migrations;
 
 pub(crate) mod cli;
@@ -16,14 +17,19 @@ impl Service for VersionService {
 impl VersionService {
     pub async fn gsb<C: Provider<Self, DbExecutor>>(ctx: &C) -> anyhow::Result<()> {
         let db = ctx.component();
+        let config = VersionConfig::from_env()?;
         db.apply_migration(migrations::run_with_output)?;
-        crate::notifier::on_start(&db).await?;
-        gsb::bind_gsb(&db);
+        crate::notifier::on_start(&db, &config).await?;
+        gsb::bind_gsb(&db, config);
 
         Ok(())
     }
 
     pub fn rest<C: Provider<Self, DbExecutor>>(ctx: &C) -> actix_web::Scope {
-        rest::web_scope(ctx.component())
+        let config = VersionConfig::from_env().unwrap_or_else(|e| {
+            log::warn!("Invalid version service configuration: {e}. Using defaults");
+            VersionConfig::default()
+        });
+        rest::web_scope(ctx.component(), config)
     }
 }
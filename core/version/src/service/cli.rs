@@ -26,6 +26,8 @@ pub enum VersionCLI {
     Show,
     /// Checks if there is new Yagna version available and shows it.
     Check,
+    /// Show update channel, maintenance windows and pending update.
+    Status,
     /// Stop logging warnings about latest Yagna release availability.
     #[structopt(setting = AppSettings::Hidden)]
     Skip,
@@ -36,6 +38,11 @@ impl VersionCLI {
         match self {
             VersionCLI::Show => show(version::Get::show_only(), ctx).await,
             VersionCLI::Check => show(version::Get::with_check(), ctx).await,
+            VersionCLI::Status => CommandOutput::object(
+                bus::service(version::BUS_ID)
+                    .send(version::GetUpdateStatus {})
+                    .await??,
+            ),
             VersionCLI::Skip => CommandOutput::object(
                 match bus::service(version::BUS_ID)
                     .send(version::Skip())
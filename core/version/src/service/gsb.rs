@@ -1,18 +1,21 @@
+use chrono::Utc;
 use metrics::counter;
 
 use ya_core_model::version;
 use ya_persistence::executor::DbExecutor;
 use ya_service_bus::{typed as bus, RpcMessage};
 
+use crate::config::VersionConfig;
 use crate::db::dao::ReleaseDAO;
 use crate::service::cli::ReleaseMessage;
 
 pub type RpcMessageResult<T> = Result<<T as RpcMessage>::Item, <T as RpcMessage>::Error>;
 
-pub fn bind_gsb(db: &DbExecutor) {
-    bus::ServiceBinder::new(version::BUS_ID, db, ())
-        .bind(skip_version_gsb)
-        .bind(get_version_gsb);
+pub fn bind_gsb(db: &DbExecutor, config: VersionConfig) {
+    bus::ServiceBinder::new(version::BUS_ID, db, config)
+        .bind_with_processor(skip_version_gsb)
+        .bind_with_processor(get_version_gsb)
+        .bind_with_processor(get_update_status_gsb);
 
     // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
     // until first change to value will be made.
@@ -22,10 +25,15 @@ pub fn bind_gsb(db: &DbExecutor) {
 
 async fn skip_version_gsb(
     db: DbExecutor,
+    config: VersionConfig,
     _caller: String,
     _msg: version::Skip,
 ) -> RpcMessageResult<version::Skip> {
-    match db.as_dao::<ReleaseDAO>().skip_pending_release().await {
+    match db
+        .as_dao::<ReleaseDAO>()
+        .skip_pending_release(config.update_channel)
+        .await
+    {
         Ok(r) => Ok(r.inspect(|r| {
             log::info!("{}", ReleaseMessage::Skipped(r));
             counter!("version.skip", 1);
@@ -36,17 +44,53 @@ async fn skip_version_gsb(
 
 async fn get_version_gsb(
     db: DbExecutor,
+    config: VersionConfig,
     _caller: String,
     msg: version::Get,
 ) -> RpcMessageResult<version::Get> {
     if msg.check {
-        crate::github::check_latest_release(&db)
+        crate::github::check_latest_release(&db, &config)
             .await
             .map_err(|e| e.to_string())?;
     }
 
     db.as_dao::<ReleaseDAO>()
-        .version()
+        .version(config.update_channel)
+        .await
+        .map_err(|e| e.to_string().into())
+}
+
+async fn get_update_status_gsb(
+    db: DbExecutor,
+    config: VersionConfig,
+    _caller: String,
+    _msg: version::GetUpdateStatus,
+) -> RpcMessageResult<version::GetUpdateStatus> {
+    update_status(&db, &config)
         .await
         .map_err(|e| e.to_string().into())
 }
+
+pub(crate) async fn update_status(
+    db: &DbExecutor,
+    config: &VersionConfig,
+) -> anyhow::Result<version::UpdateStatus> {
+    let info = db
+        .as_dao::<ReleaseDAO>()
+        .version(config.update_channel)
+        .await?;
+    let now = Utc::now();
+
+    Ok(version::UpdateStatus {
+        channel: config.update_channel.to_string(),
+        current: info.current,
+        pending: info.pending,
+        update_windows: config
+            .update_windows
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        update_allowed: config.update_allowed(now),
+        next_window: config.next_window(now),
+    })
+}
@@ -1,6 +1,8 @@
 use ya_persistence::executor::DbExecutor;
 
+use crate::config::VersionConfig;
 use crate::db::dao::ReleaseDAO;
+use crate::service::gsb::update_status;
 
 use actix_web::web::Data;
 use actix_web::{web, HttpResponse, Responder};
@@ -9,16 +11,36 @@ use ya_client::model::ErrorMessage;
 
 pub const VERSION_API_PATH: &str = "/version";
 
-pub fn web_scope(db: DbExecutor) -> actix_web::Scope {
+pub fn web_scope(db: DbExecutor, config: VersionConfig) -> actix_web::Scope {
     actix_web::web::scope(VERSION_API_PATH)
         .app_data(Data::new(db))
+        .app_data(Data::new(config))
         .service(get_version)
+        .service(get_update_status)
 }
 
 #[actix_web::get("/get")]
-async fn get_version(db: web::Data<DbExecutor>) -> impl Responder {
-    match db.as_dao::<ReleaseDAO>().version().await {
+async fn get_version(
+    db: web::Data<DbExecutor>,
+    config: web::Data<VersionConfig>,
+) -> impl Responder {
+    match db
+        .as_dao::<ReleaseDAO>()
+        .version(config.update_channel)
+        .await
+    {
         Ok(v) => HttpResponse::Ok().json(v),
         Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
     }
 }
+
+#[actix_web::get("/status")]
+async fn get_update_status(
+    db: web::Data<DbExecutor>,
+    config: web::Data<VersionConfig>,
+) -> impl Responder {
+    match update_status(&db, &config).await {
+        Ok(status) => HttpResponse::Ok().json(status),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
@@ -0,0 +1,141 @@
+//! TCP port forwarding from the Requestor's host to addresses within the VPN.
+use actix::prelude::*;
+use futures::{future, StreamExt};
+use std::future::Future;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use ya_utils_networking::vpn::stack::connection::ConnectionMeta;
+use ya_utils_networking::vpn::{Error, Protocol};
+
+use crate::message::*;
+use crate::network::Vpn;
+use crate::Result;
+
+const READ_BUFFER_SIZE: usize = 65536;
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(10);
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// Forwards are bound to the loopback interface only.
+pub(crate) async fn listen(port: Option<u16>) -> Result<TcpListener> {
+    TcpListener::bind(("127.0.0.1", port.unwrap_or(0)))
+        .await
+        .map_err(|e| Error::Other(format!("Unable to listen on port {port:?}: {e}")))
+}
+
+/// Accepts connections until the forward is removed. Dropping the returned future
+/// closes the listener together with all connections accepted so far.
+pub(crate) async fn serve(listener: TcpListener, vpn: Addr<Vpn>, ip: String, port: u16) {
+    let mut connections = Connections::default();
+    let mut backoff = ACCEPT_BACKOFF_MIN;
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, peer)) => {
+                log::debug!("Port forward to {ip}:{port}: accepted connection from {peer}");
+                backoff = ACCEPT_BACKOFF_MIN;
+                stream
+            }
+            Err(e) => {
+                // Errors like running out of file descriptors persist for a while,
+                // retrying immediately would only spin the loop.
+                log::warn!(
+                    "Port forward to {ip}:{port}: accept error: {e}, retrying in {backoff:?}"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                continue;
+            }
+        };
+
+        let vpn = vpn.clone();
+        let ip = ip.clone();
+        connections.spawn(async move {
+            if let Err(e) = proxy(stream, vpn, &ip, port).await {
+                log::debug!("Port forward to {ip}:{port}: connection closed: {e}");
+            }
+        });
+    }
+}
+
+/// Proxied connections of a single forward, aborted when the forward is removed.
+#[derive(Default)]
+struct Connections(Vec<JoinHandle<()>>);
+
+impl Connections {
+    fn spawn(&mut self, fut: impl Future<Output = ()> + 'static) {
+        self.0.retain(|handle| !handle.is_finished());
+        self.0.push(tokio::task::spawn_local(fut));
+    }
+}
+
+impl Drop for Connections {
+    fn drop(&mut self) {
+        self.0.iter().for_each(JoinHandle::abort);
+    }
+}
+
+/// Closes the VPN connection when proxying ends, also when it's aborted.
+struct DisconnectOnDrop {
+    vpn: Addr<Vpn>,
+    meta: ConnectionMeta,
+}
+
+impl Drop for DisconnectOnDrop {
+    fn drop(&mut self) {
+        self.vpn.do_send(Disconnect::new(
+            self.meta.into(),
+            DisconnectReason::SocketClosed,
+        ));
+    }
+}
+
+async fn proxy(stream: TcpStream, vpn: Addr<Vpn>, ip: &str, port: u16) -> Result<()> {
+    let conn = vpn
+        .send(Connect {
+            protocol: Protocol::Tcp,
+            address: ip.to_string(),
+            port,
+        })
+        .await
+        .map_err(|_| Error::NetNotFound)??;
+
+    let meta = conn.stack_connection.meta;
+    let _disconnect = DisconnectOnDrop { vpn, meta };
+    let (mut reader, mut writer) = stream.into_split();
+    let mut rx = conn.rx;
+
+    let ingress = Box::pin(async move {
+        while let Some(data) = rx.next().await {
+            writer
+                .write_all(&data)
+                .await
+                .map_err(|e| Error::ConnectionError(e.to_string()))?;
+        }
+        Ok::<_, Error>(())
+    });
+
+    let sink = conn.vpn;
+    let egress = Box::pin(async move {
+        let mut buf = vec![0u8; READ_BUFFER_SIZE];
+        loop {
+            let read = reader
+                .read(&mut buf)
+                .await
+                .map_err(|e| Error::ConnectionError(e.to_string()))?;
+            if read == 0 {
+                return Ok(());
+            }
+            sink.send(Packet {
+                data: buf[..read].to_vec(),
+                meta,
+            })
+            .await
+            .map_err(|_| Error::NetNotFound)??;
+        }
+    });
+
+    future::select(ingress, egress).await.factor_first().0
+}
@@ -1,7 +1,9 @@
+mod forward;
 mod message;
 mod network;
 mod requestor;
 mod service;
+mod stats;
 
 pub use self::service::VpnService;
 
@@ -2,6 +2,7 @@ use crate::Result;
 use actix::{Message, Recipient};
 use futures::channel::mpsc;
 use ya_client_model::net::*;
+use ya_core_model::net::vpn::{NetworkStats, PortForward};
 use ya_utils_networking::vpn::{
     stack::{
         connection::{Connection, ConnectionMeta},
@@ -51,6 +52,28 @@ pub struct Connect {
     pub port: u16,
 }
 
+#[derive(Debug, Message)]
+#[rtype(result = "Result<PortForward>")]
+pub struct AddForward {
+    pub ip: String,
+    pub port: u16,
+    pub listen_port: Option<u16>,
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "Result<Vec<PortForward>>")]
+pub struct GetForwards;
+
+#[derive(Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct RemoveForward {
+    pub id: String,
+}
+
+#[derive(Debug, Message)]
+#[rtype(result = "Result<NetworkStats>")]
+pub struct GetStats;
+
 #[derive(Debug, Message)]
 #[rtype(result = "Result<()>")]
 pub struct Disconnect {
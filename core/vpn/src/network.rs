@@ -15,10 +15,13 @@ use uuid::Uuid;
 use ya_utils_networking::vpn::socket::TCP_CONN_TIMEOUT;
 use ya_utils_networking::vpn::stack::interface::{add_iface_address, add_iface_route, tap_iface};
 
+use crate::forward;
 use crate::message::*;
+use crate::stats::{frame_dst_ip, VpnStats};
 use crate::Result;
 
 use ya_core_model::activity::{VpnControl, VpnPacket};
+use ya_core_model::net::vpn::{NetworkStats, PortForward};
 use ya_core_model::NodeId;
 use ya_service_bus::typed::{self, Endpoint};
 use ya_service_bus::{actix_rpc, RpcEndpoint, RpcEnvelope, RpcRawCall};
@@ -173,6 +176,23 @@ impl VpnSupervisor {
         self.forward(vpn, RemoveNode { id })
     }
 
+    /// Removes the Node from all networks owned by `node_id`, e.g. after its Activity ended.
+    pub fn remove_node_from_networks<'a>(
+        &self,
+        node_id: &NodeId,
+        id: String,
+    ) -> BoxFuture<'a, Result<()>> {
+        let futs = self
+            .ownership
+            .get(node_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|network_id| self.networks.get(network_id).cloned())
+            .filter_map(|vpn| self.forward(vpn, RemoveNode { id: id.clone() }).ok())
+            .collect::<Vec<_>>();
+        Box::pin(future::try_join_all(futs).map_ok(|_| ()))
+    }
+
     fn forward<'a, M, T>(
         &self,
         vpn: Addr<Vpn>,
@@ -192,6 +212,22 @@ impl VpnSupervisor {
         }))
     }
 
+    /// Statistics of networks, regardless of their owner.
+    pub fn get_stats<'a>(
+        &self,
+        network_id: Option<&str>,
+    ) -> Result<BoxFuture<'a, Result<Vec<NetworkStats>>>> {
+        let networks = match network_id {
+            Some(id) => vec![self.vpn(id)?],
+            None => self.networks.values().cloned().collect(),
+        };
+        let futs = networks
+            .into_iter()
+            .map(|vpn| self.forward(vpn, GetStats))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Box::pin(future::try_join_all(futs)))
+    }
+
     fn vpn(&self, network_id: &str) -> Result<Addr<Vpn>> {
         self.networks
             .get(network_id)
@@ -214,6 +250,10 @@ pub struct Vpn {
     vpn: Network<network::DuoEndpoint<Endpoint>>,
     stack_network: net::Network,
     connections: HashMap<SocketDesc, InternalConnection>,
+    forwards: HashMap<String, (PortForward, SpawnHandle)>,
+    /// Node ids by their addresses, for attributing egress traffic.
+    node_ips: HashMap<IpAddr, String>,
+    stats: VpnStats,
 }
 
 impl Vpn {
@@ -227,6 +267,9 @@ impl Vpn {
             vpn,
             stack_network,
             connections: Default::default(),
+            forwards: Default::default(),
+            node_ips: Default::default(),
+            stats: Default::default(),
         }
     }
 }
@@ -355,7 +398,10 @@ impl Handler<AddNode> for Vpn {
         let ip = to_ip(&msg.address)?;
 
         match self.vpn.add_node(ip, &msg.id, gsb_remote_url) {
-            Ok(_) | Err(Error::IpAddrTaken(_)) => {}
+            Ok(_) => {
+                self.node_ips.insert(ip, msg.id.clone());
+            }
+            Err(Error::IpAddrTaken(_)) => {}
             Err(err) => return Err(err),
         }
 
@@ -386,8 +432,29 @@ impl Handler<AddNode> for Vpn {
 impl Handler<RemoveNode> for Vpn {
     type Result = <RemoveNode as Message>::Result;
 
-    fn handle(&mut self, msg: RemoveNode, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: RemoveNode, ctx: &mut Self::Context) -> Self::Result {
+        // Forwards to the removed Node (e.g. after its Activity ended) are no longer usable.
+        let node_ips: Vec<_> = match self.vpn.nodes().get(&msg.id) {
+            Some(ips) => ips.iter().map(|ip| ip.to_string()).collect(),
+            None => return Ok(()),
+        };
+        let forwards = self
+            .forwards
+            .iter()
+            .filter(|(_, (forward, _))| {
+                to_ip(&forward.ip)
+                    .map(|ip| node_ips.contains(&ip.to_string()))
+                    .unwrap_or(false)
+            })
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        for id in forwards {
+            self.remove_forward(&id, ctx);
+        }
+
         self.vpn.remove_node(&msg.id);
+        self.node_ips.retain(|_, id| id != &msg.id);
+        self.stats.remove_node(&msg.id);
 
         let vpn_id = self.vpn.id().clone();
         let futs = self
@@ -454,6 +521,101 @@ impl Handler<Connect> for Vpn {
     }
 }
 
+impl Vpn {
+    fn remove_forward(&mut self, id: &str, ctx: &mut Context<Self>) -> bool {
+        match self.forwards.remove(id) {
+            Some((forward, handle)) => {
+                log::info!(
+                    "VPN {}: removing port forward {} -> {}:{}",
+                    self.vpn.id(),
+                    forward.listen,
+                    forward.ip,
+                    forward.port
+                );
+                ctx.cancel_future(handle)
+            }
+            None => false,
+        }
+    }
+}
+
+impl Handler<AddForward> for Vpn {
+    type Result = ActorResponse<Self, Result<PortForward>>;
+
+    fn handle(&mut self, msg: AddForward, _: &mut Self::Context) -> Self::Result {
+        if let Err(err) = to_ip(&msg.ip) {
+            return ActorResponse::reply(Err(err));
+        }
+
+        let network_id = self.vpn.id().clone();
+        let fut =
+            forward::listen(msg.listen_port)
+                .into_actor(self)
+                .map(move |result, this, ctx| {
+                    let listener = result?;
+                    let listen = listener
+                        .local_addr()
+                        .map_err(|e| Error::Other(e.to_string()))?;
+                    let forward = PortForward {
+                        id: Uuid::new_v4().to_simple().to_string(),
+                        network_id,
+                        listen,
+                        ip: msg.ip,
+                        port: msg.port,
+                    };
+                    log::info!(
+                        "VPN {}: forwarding {} -> {}:{}",
+                        forward.network_id,
+                        forward.listen,
+                        forward.ip,
+                        forward.port
+                    );
+
+                    let serve =
+                        forward::serve(listener, ctx.address(), forward.ip.clone(), msg.port);
+                    let handle = ctx.spawn(serve.into_actor(this));
+                    this.forwards
+                        .insert(forward.id.clone(), (forward.clone(), handle));
+                    Ok(forward)
+                });
+
+        ActorResponse::r#async(fut)
+    }
+}
+
+impl Handler<GetStats> for Vpn {
+    type Result = <GetStats as Message>::Result;
+
+    fn handle(&mut self, _: GetStats, _: &mut Self::Context) -> Self::Result {
+        Ok(self
+            .stats
+            .report(self.vpn.id().clone(), self.connections.len()))
+    }
+}
+
+impl Handler<GetForwards> for Vpn {
+    type Result = <GetForwards as Message>::Result;
+
+    fn handle(&mut self, _: GetForwards, _: &mut Self::Context) -> Self::Result {
+        Ok(self
+            .forwards
+            .values()
+            .map(|(forward, _)| forward.clone())
+            .collect())
+    }
+}
+
+impl Handler<RemoveForward> for Vpn {
+    type Result = <RemoveForward as Message>::Result;
+
+    fn handle(&mut self, msg: RemoveForward, ctx: &mut Self::Context) -> Self::Result {
+        match self.remove_forward(&msg.id, ctx) {
+            true => Ok(()),
+            false => Err(Error::Other(format!("Port forward {} not found", msg.id))),
+        }
+    }
+}
+
 impl Handler<Disconnect> for Vpn {
     type Result = <Disconnect as Message>::Result;
 
@@ -538,7 +700,10 @@ impl Handler<RpcEnvelope<VpnPacket>> for Vpn {
     type Result = <RpcEnvelope<VpnPacket> as Message>::Result;
 
     fn handle(&mut self, msg: RpcEnvelope<VpnPacket>, _: &mut Self::Context) -> Self::Result {
-        self.stack_network.receive(msg.into_inner().0);
+        let caller = msg.caller().to_string();
+        let packet = msg.into_inner().0;
+        self.stats.ingress(&caller, packet.len());
+        self.stack_network.receive(packet);
         self.stack_network.poll();
         Ok(())
     }
@@ -548,6 +713,7 @@ impl Handler<RpcRawCall> for Vpn {
     type Result = std::result::Result<Vec<u8>, ya_service_bus::Error>;
 
     fn handle(&mut self, msg: RpcRawCall, _: &mut Self::Context) -> Self::Result {
+        self.stats.ingress(&msg.caller, msg.body.len());
         self.stack_network.receive(msg.body);
         self.stack_network.poll();
         Ok(Vec::new())
@@ -624,6 +790,10 @@ impl Handler<Egress> for Vpn {
 
         log::debug!("[vpn] egress -> runtime packet {} B", frame.len());
 
+        let node_ips = &self.node_ips;
+        let node_id = frame_dst_ip(&frame).and_then(|ip| node_ips.get(&ip));
+        self.stats.egress(node_id.map(String::as_str), frame.len());
+
         match self.vpn.endpoint(msg.event.remote) {
             Some(endpoint) => {
                 let fut = endpoint
@@ -816,10 +986,31 @@ fn create_stack_network(
 
 #[cfg(test)]
 mod tests {
-    use crate::network::VpnSupervisor;
+    use actix::Addr;
+    use std::time::Duration;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpStream;
+
+    use crate::message::{AddForward, AddNode, GetForwards, GetNodes, RemoveForward, RemoveNode};
+    use crate::network::{Vpn, VpnSupervisor};
     use ya_client_model::net::NewNetwork;
     use ya_core_model::NodeId;
 
+    async fn create_network(supervisor: &mut VpnSupervisor) -> anyhow::Result<Addr<Vpn>> {
+        let node_id = NodeId::default();
+        let network = supervisor
+            .create_network(
+                node_id,
+                NewNetwork {
+                    ip: "10.0.0.0".to_string(),
+                    mask: None,
+                    gateway: None,
+                },
+            )
+            .await?;
+        Ok(supervisor.get_network(&node_id, &network.id)?)
+    }
+
     #[actix_rt::test]
     async fn create_remove_network() -> anyhow::Result<()> {
         let node_id = NodeId::default();
@@ -883,4 +1074,126 @@ mod tests {
         assert!(supervisor.get_network(&node_id, &network2.id).is_ok());
         Ok(())
     }
+    #[actix_rt::test]
+    async fn remove_forward_closes_connections() -> anyhow::Result<()> {
+        let mut supervisor = VpnSupervisor::default();
+        let vpn = create_network(&mut supervisor).await?;
+
+        let forward = vpn
+            .send(AddForward {
+                ip: "10.0.0.2".to_string(),
+                port: 80,
+                listen_port: None,
+            })
+            .await??;
+        assert!(forward.listen.ip().is_loopback());
+        assert_eq!(vpn.send(GetForwards).await??, vec![forward.clone()]);
+
+        // Nothing listens on the destination, so the connection stays pending
+        let mut client = TcpStream::connect(forward.listen).await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        vpn.send(RemoveForward {
+            id: forward.id.clone(),
+        })
+        .await??;
+        assert!(vpn.send(GetForwards).await??.is_empty());
+
+        let mut buf = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_secs(2), client.read(&mut buf)).await?;
+        assert!(matches!(read, Ok(0) | Err(_)), "unexpected read: {read:?}");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(TcpStream::connect(forward.listen).await.is_err());
+        assert!(vpn.send(RemoveForward { id: forward.id }).await?.is_err());
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn remove_node_removes_forwards() -> anyhow::Result<()> {
+        let mut supervisor = VpnSupervisor::default();
+        let vpn = create_network(&mut supervisor).await?;
+
+        for (id, address) in [("0xa", "10.0.0.2"), ("0xb", "10.0.0.3")] {
+            vpn.send(AddNode {
+                id: id.to_string(),
+                address: address.to_string(),
+            })
+            .await??;
+            vpn.send(AddForward {
+                ip: address.to_string(),
+                port: 80,
+                listen_port: None,
+            })
+            .await??;
+        }
+
+        vpn.send(RemoveNode {
+            id: "0xa".to_string(),
+        })
+        .await??;
+
+        let forwards = vpn.send(GetForwards).await??;
+        assert_eq!(forwards.len(), 1);
+        assert_eq!(forwards[0].ip, "10.0.0.3");
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn remove_node_from_networks() -> anyhow::Result<()> {
+        let owner = NodeId::default();
+        let provider: NodeId = "0x000000000000000000000000000000000000000a".parse()?;
+
+        let mut supervisor = VpnSupervisor::default();
+        let mut networks = Vec::new();
+        for ip in ["10.0.0.0", "10.1.0.0"] {
+            let network = supervisor
+                .create_network(
+                    owner,
+                    NewNetwork {
+                        ip: ip.to_string(),
+                        mask: None,
+                        gateway: None,
+                    },
+                )
+                .await?;
+            networks.push(supervisor.get_network(&owner, &network.id)?);
+        }
+
+        for (vpn, address) in networks.iter().zip(["10.0.0.2", "10.1.0.2"]) {
+            vpn.send(AddNode {
+                id: provider.to_string(),
+                address: address.to_string(),
+            })
+            .await??;
+            vpn.send(AddNode {
+                id: "0xb".to_string(),
+                address: address.replace(".2", ".3"),
+            })
+            .await??;
+            vpn.send(AddForward {
+                ip: address.to_string(),
+                port: 80,
+                listen_port: None,
+            })
+            .await??;
+        }
+
+        // Networks of other owners are not affected
+        supervisor
+            .remove_node_from_networks(&provider, provider.to_string())
+            .await?;
+        assert_eq!(networks[0].send(GetNodes).await??.len(), 2);
+
+        supervisor
+            .remove_node_from_networks(&owner, provider.to_string())
+            .await?;
+        for vpn in networks {
+            let nodes = vpn.send(GetNodes).await??;
+            assert_eq!(nodes.len(), 1);
+            assert_eq!(nodes[0].id, "0xb");
+            assert!(vpn.send(GetForwards).await??.is_empty());
+        }
+        Ok(())
+    }
 }
@@ -50,6 +50,10 @@ fn vpn_web_scope(path: &str) -> actix_web::Scope {
         .service(get_nodes)
         .service(add_node)
         .service(remove_node)
+        .service(get_forwards)
+        .service(add_forward)
+        .service(remove_forward)
+        .service(get_stats)
         .service(connect_tcp)
 }
 
@@ -199,6 +203,82 @@ async fn remove_node(
     Ok::<_, ApiError>(web::Json(fut.await?))
 }
 
+/// Retrieves TCP port forwards from the requestor's host into a virtual private network.
+#[actix_web::get("/net/{net_id}/forwards")]
+async fn get_forwards(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathNetwork>,
+    identity: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    let vpn = {
+        let supervisor = vpn_sup.lock().await;
+        supervisor.get_network(&identity.identity, &path.net_id)?
+    };
+    let response = vpn.send(GetForwards).await??;
+    Ok::<_, ApiError>(web::Json(response))
+}
+
+/// Starts listening on a local TCP port and forwards connections to the destination address.
+#[actix_web::post("/net/{net_id}/forwards")]
+async fn add_forward(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathNetwork>,
+    model: web::Json<NewPortForward>,
+    identity: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    let vpn = {
+        let supervisor = vpn_sup.lock().await;
+        supervisor.get_network(&identity.identity, &path.net_id)?
+    };
+    let forward = model.into_inner();
+    let response = vpn
+        .send(AddForward {
+            ip: forward.ip,
+            port: forward.port,
+            listen_port: forward.listen_port,
+        })
+        .await??;
+    Ok::<_, ApiError>(web::Json(response))
+}
+
+/// Stops a TCP port forward.
+#[actix_web::delete("/net/{net_id}/forwards/{forward_id}")]
+async fn remove_forward(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathForward>,
+    identity: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    let vpn = {
+        let supervisor = vpn_sup.lock().await;
+        supervisor.get_network(&identity.identity, &path.net_id)?
+    };
+    let response = vpn
+        .send(RemoveForward {
+            id: path.forward_id,
+        })
+        .await??;
+    Ok::<_, ApiError>(web::Json(response))
+}
+
+/// Retrieves traffic statistics of a virtual private network.
+#[actix_web::get("/net/{net_id}/stats")]
+async fn get_stats(
+    vpn_sup: web::Data<Arc<Mutex<VpnSupervisor>>>,
+    path: web::Path<PathNetwork>,
+    identity: Identity,
+) -> impl Responder {
+    let path = path.into_inner();
+    let vpn = {
+        let supervisor = vpn_sup.lock().await;
+        supervisor.get_network(&identity.identity, &path.net_id)?
+    };
+    let response = vpn.send(GetStats).await??;
+    Ok::<_, ApiError>(web::Json(response))
+}
+
 /// Initiates a new TCP connection via WebSockets to the destination address.
 #[actix_web::get("/net/{net_id}/tcp/{ip}/{port}")]
 async fn connect_tcp(
@@ -376,6 +456,20 @@ struct PathNetworkNode {
     node_id: String,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct PathForward {
+    net_id: String,
+    forward_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NewPortForward {
+    ip: String,
+    port: u16,
+    listen_port: Option<u16>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct PathConnect {
     net_id: String,
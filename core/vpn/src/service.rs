@@ -1,8 +1,12 @@
+use crate::message::{AddForward, GetForwards, RemoveForward};
 use crate::network::VpnSupervisor;
 use futures::lock::Mutex;
 use std::sync::Arc;
+use ya_core_model::net::vpn;
+use ya_core_model::net::GenericNetError;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_interfaces::Provider;
+use ya_service_bus::typed as bus;
 
 lazy_static::lazy_static! {
     static ref VPN_SUPERVISOR: Arc<Mutex<VpnSupervisor>> = Default::default();
@@ -12,7 +16,68 @@ pub struct VpnService;
 
 impl VpnService {
     pub async fn gsb<Context: Provider<Self, DbExecutor>>(_: &Context) -> anyhow::Result<()> {
-        let _vpn = VPN_SUPERVISOR.clone();
+        let _ = bus::bind(vpn::BUS_ID, |msg: vpn::CreatePortForward| async move {
+            let vpn = VPN_SUPERVISOR
+                .lock()
+                .await
+                .get_network(&msg.owner, &msg.network_id)
+                .map_err(net_error)?;
+            vpn.send(AddForward {
+                ip: msg.ip,
+                port: msg.port,
+                listen_port: msg.listen_port,
+            })
+            .await
+            .map_err(net_error)?
+            .map_err(net_error)
+        });
+
+        let _ = bus::bind(vpn::BUS_ID, |msg: vpn::ListPortForwards| async move {
+            let vpn = VPN_SUPERVISOR
+                .lock()
+                .await
+                .get_network(&msg.owner, &msg.network_id)
+                .map_err(net_error)?;
+            vpn.send(GetForwards)
+                .await
+                .map_err(net_error)?
+                .map_err(net_error)
+        });
+
+        let _ = bus::bind(vpn::BUS_ID, |msg: vpn::RemovePortForward| async move {
+            let vpn = VPN_SUPERVISOR
+                .lock()
+                .await
+                .get_network(&msg.owner, &msg.network_id)
+                .map_err(net_error)?;
+            vpn.send(RemoveForward { id: msg.id })
+                .await
+                .map_err(net_error)?
+                .map_err(net_error)
+        });
+
+        let _ = bus::bind(vpn::BUS_ID, |msg: vpn::ActivityDestroyed| async move {
+            log::debug!(
+                "Activity {} destroyed, removing Node {} from VPNs",
+                msg.activity_id,
+                msg.provider_id
+            );
+            let fut = VPN_SUPERVISOR
+                .lock()
+                .await
+                .remove_node_from_networks(&msg.owner, msg.provider_id.to_string());
+            fut.await.map_err(net_error)
+        });
+
+        let _ = bus::bind(vpn::BUS_ID, |msg: vpn::GetNetworkStats| async move {
+            let fut = VPN_SUPERVISOR
+                .lock()
+                .await
+                .get_stats(msg.network_id.as_deref())
+                .map_err(net_error)?;
+            fut.await.map_err(net_error)
+        });
+
         Ok(())
     }
 
@@ -20,3 +85,7 @@ impl VpnService {
         crate::requestor::web_scope(VPN_SUPERVISOR.clone())
     }
 }
+
+fn net_error(e: impl ToString) -> GenericNetError {
+    GenericNetError(e.to_string())
+}
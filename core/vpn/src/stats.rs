@@ -0,0 +1,105 @@
+//! Traffic counters of a single VPN.
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use smoltcp::wire::{EthernetFrame, EthernetProtocol, Ipv4Packet, Ipv6Packet};
+
+use ya_core_model::net::vpn::{NetworkStats, NodeStats, Traffic};
+
+#[derive(Debug, Default)]
+pub(crate) struct VpnStats {
+    tx: Traffic,
+    rx: Traffic,
+    nodes: HashMap<String, (Traffic, Traffic)>,
+}
+
+impl VpnStats {
+    pub fn egress(&mut self, node_id: Option<&str>, bytes: usize) {
+        count(&mut self.tx, bytes);
+        if let Some(node_id) = node_id {
+            count(&mut self.node(node_id).0, bytes);
+        }
+    }
+
+    pub fn ingress(&mut self, node_id: &str, bytes: usize) {
+        count(&mut self.rx, bytes);
+        count(&mut self.node(node_id).1, bytes);
+    }
+
+    pub fn remove_node(&mut self, node_id: &str) {
+        self.nodes.remove(node_id);
+    }
+
+    pub fn report(&self, network_id: String, connections: usize) -> NetworkStats {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .map(|(node_id, (tx, rx))| NodeStats {
+                node_id: node_id.clone(),
+                tx: tx.clone(),
+                rx: rx.clone(),
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|l, r| l.node_id.cmp(&r.node_id));
+
+        NetworkStats {
+            network_id,
+            tx: self.tx.clone(),
+            rx: self.rx.clone(),
+            connections,
+            nodes,
+        }
+    }
+
+    fn node(&mut self, node_id: &str) -> &mut (Traffic, Traffic) {
+        self.nodes.entry(node_id.to_string()).or_default()
+    }
+}
+
+fn count(traffic: &mut Traffic, bytes: usize) {
+    traffic.bytes += bytes as u64;
+    traffic.packets += 1;
+}
+
+/// Destination address of an Ethernet frame emitted by the stack.
+pub(crate) fn frame_dst_ip(frame: &[u8]) -> Option<IpAddr> {
+    let frame = EthernetFrame::new_checked(frame).ok()?;
+    match frame.ethertype() {
+        EthernetProtocol::Ipv4 => {
+            let packet = Ipv4Packet::new_checked(frame.payload()).ok()?;
+            Some(Ipv4Addr::from(packet.dst_addr().0).into())
+        }
+        EthernetProtocol::Ipv6 => {
+            let packet = Ipv6Packet::new_checked(frame.payload()).ok()?;
+            Some(Ipv6Addr::from(packet.dst_addr().0).into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let mut stats = VpnStats::default();
+        stats.egress(Some("0xb"), 100);
+        stats.egress(None, 50);
+        stats.ingress("0xa", 10);
+        stats.ingress("0xb", 20);
+
+        let report = stats.report("net".to_string(), 2);
+        assert_eq!(report.tx.bytes, 150);
+        assert_eq!(report.tx.packets, 2);
+        assert_eq!(report.rx.bytes, 30);
+        assert_eq!(report.connections, 2);
+        assert_eq!(report.nodes.len(), 2);
+        assert_eq!(report.nodes[1].node_id, "0xb");
+        assert_eq!(report.nodes[1].tx.bytes, 100);
+        assert_eq!(report.nodes[1].rx.packets, 1);
+
+        stats.remove_node("0xa");
+        assert_eq!(stats.report("net".to_string(), 0).nodes.len(), 1);
+    }
+}
@@ -1,4 +1,6 @@
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use crate::error::{self, CounterError};
@@ -47,3 +49,27 @@ impl Counter for TimeCounter {
         self.frame()
     }
 }
+
+/// Reports network traffic (in MiB) accumulated by a shared byte counter.
+pub struct NetworkCounter {
+    bytes: Arc<AtomicU64>,
+}
+
+impl NetworkCounter {
+    pub const IN_ID: &'static str = "golem.usage.network.in-mib";
+    pub const OUT_ID: &'static str = "golem.usage.network.out-mib";
+
+    pub fn new(bytes: Arc<AtomicU64>) -> Self {
+        NetworkCounter { bytes }
+    }
+}
+
+impl Counter for NetworkCounter {
+    fn frame(&mut self) -> Result<CounterData> {
+        Ok(self.bytes.load(Ordering::Relaxed) as CounterData / (1024. * 1024.))
+    }
+
+    fn peak(&mut self) -> Result<CounterData> {
+        self.frame()
+    }
+}
@@ -14,6 +14,16 @@ pub struct SetCounter {
     pub value: f64,
 }
 
+/// Registers a custom counter reported by the runtime.
+/// Only counters included in the agreement usage vector are reported in usage.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Message)]
+#[rtype(result = "Result<()>")]
+pub struct RegisterCounter {
+    pub name: String,
+    pub unit: String,
+    pub monotonic: bool,
+}
+
 #[derive(Debug, Default, Message)]
 #[rtype(result = "Result<()>")]
 pub struct Shutdown;
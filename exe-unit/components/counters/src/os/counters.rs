@@ -96,23 +96,21 @@ impl StorageCounter {
         let running = self.running.clone();
 
         thread::spawn(move || {
-            if running.load(Ordering::Relaxed).not() {
-                return;
-            }
-
-            let (size, skipped) = match fs::read_dir(path.clone()) {
-                Ok(dir) => Self::read_dir_size(dir),
-                Err(err) => {
-                    log::error!("StorageCounter: unable to read '{:?}': {:?}", path, err);
-                    return;
+            while running.load(Ordering::Relaxed) {
+                let (size, skipped) = match fs::read_dir(path.clone()) {
+                    Ok(dir) => Self::read_dir_size(dir),
+                    Err(err) => {
+                        log::error!("StorageCounter: unable to read '{:?}': {:?}", path, err);
+                        return;
+                    }
+                };
+                if skipped > 0 {
+                    log::warn!("StorageCounter: skipped {} filesystem entries", skipped);
                 }
-            };
-            if skipped > 0 {
-                log::warn!("StorageCounter: skipped {} filesystem entries", skipped);
-            }
 
-            last.store(size, Ordering::Relaxed);
-            thread::sleep(interval);
+                last.store(size, Ordering::Relaxed);
+                thread::sleep(interval);
+            }
         });
     }
 
@@ -2,7 +2,7 @@
 
 use crate::counters::{Counter, CounterData, CounterReport};
 use crate::error::CounterError;
-use crate::message::{GetCounters, SetCounter, Shutdown};
+use crate::message::{GetCounters, RegisterCounter, SetCounter, Shutdown};
 
 use actix::prelude::*;
 use chrono::{DateTime, Utc};
@@ -63,7 +63,8 @@ impl CountersServiceBuilder {
         for custom_counter_id in custom_counters_ids {
             let usage_limit = usage_limits.get(&custom_counter_id).cloned();
             let counter = Box::<CustomCounter>::default();
-            let provider = CounterProvider::new(counter, self.backlog_limit, usage_limit);
+            let mut provider = CounterProvider::new(counter, self.backlog_limit, usage_limit);
+            provider.custom = true;
             counters.insert(custom_counter_id, provider);
         }
 
@@ -81,6 +82,7 @@ impl CountersServiceBuilder {
 pub struct CountersService {
     usage_vector: Vec<String>,
     counters: HashMap<String, CounterProvider>,
+    definitions: HashMap<String, RegisterCounter>,
 }
 
 impl CountersService {
@@ -88,6 +90,7 @@ impl CountersService {
         Self {
             usage_vector,
             counters,
+            definitions: Default::default(),
         }
     }
 }
@@ -124,14 +127,22 @@ impl Handler<GetCounters> for CountersService {
                 CounterReport::Frame(data) => counters[i] = data,
                 CounterReport::Error(error) => return Err(error),
                 CounterReport::LimitExceeded(data) => {
-                    return Err(CounterError::UsageLimitExceeded(format!(
-                        "{:?} exceeded the value of {:?}",
-                        name, data
-                    )))
+                    return Err(counter.limit_exceeded(name, data))
                 }
             }
         }
 
+        // Limits are enforced also for counters not included in the usage vector,
+        // e.g. storage quota of agreements not priced by storage usage
+        for (name, counter) in self.counters.iter_mut() {
+            if counter.usage_limit.is_none() || self.usage_vector.contains(name) {
+                continue;
+            }
+            if let CounterReport::LimitExceeded(data) = counter.report() {
+                return Err(counter.limit_exceeded(name, data));
+            }
+        }
+
         Ok::<_, CounterError>(counters)
     }
 }
@@ -147,10 +158,66 @@ impl Handler<SetCounter> for CountersService {
     }
 }
 
+impl Handler<RegisterCounter> for CountersService {
+    type Result = <RegisterCounter as Message>::Result;
+
+    fn handle(&mut self, msg: RegisterCounter, _: &mut Self::Context) -> Self::Result {
+        if let Some(definition) = self.definitions.get(&msg.name) {
+            if definition == &msg {
+                return Ok(());
+            }
+            return Err(CounterError::Other(format!(
+                "counter {} has already been registered as {:?}",
+                msg.name, definition
+            )));
+        }
+
+        match self.counters.get_mut(&msg.name) {
+            Some(provider) if !provider.custom => {
+                return Err(CounterError::Other(format!(
+                    "counter {} is provided by the ExeUnit",
+                    msg.name
+                )));
+            }
+            Some(provider) => {
+                // Keep values reported before the registration
+                let mut counter = CustomCounter::new(msg.monotonic);
+                counter.val = provider.counter.frame().unwrap_or_default();
+                counter.peak = provider.counter.peak().unwrap_or_default();
+                provider.counter = Box::new(counter);
+            }
+            None => log::info!(
+                "Runtime counter {} is not included in the agreement usage vector",
+                msg.name
+            ),
+        }
+
+        log::info!(
+            "Registered runtime counter {} [{}]{}",
+            msg.name,
+            msg.unit,
+            if msg.monotonic { " (monotonic)" } else { "" }
+        );
+        self.definitions.insert(msg.name.clone(), msg);
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 pub struct CustomCounter {
     val: CounterData,
     peak: CounterData,
+    monotonic: bool,
+}
+
+impl CustomCounter {
+    /// Values lower than the current one are ignored by a monotonic counter
+    pub fn new(monotonic: bool) -> Self {
+        CustomCounter {
+            monotonic,
+            ..Default::default()
+        }
+    }
 }
 
 impl Counter for CustomCounter {
@@ -163,6 +230,14 @@ impl Counter for CustomCounter {
     }
 
     fn set(&mut self, val: CounterData) {
+        if self.monotonic && val < self.val {
+            log::warn!(
+                "Ignoring decreasing value of a monotonic counter: {} < {}",
+                val,
+                self.val
+            );
+            return;
+        }
         if val > self.peak {
             self.peak = val;
         }
@@ -176,6 +251,7 @@ pub struct CounterProvider {
     backlog: Arc<Mutex<VecDeque<(DateTime<Utc>, CounterReport)>>>,
     backlog_limit: Option<usize>,
     usage_limit: Option<CounterData>,
+    custom: bool,
 }
 
 impl CounterProvider {
@@ -189,6 +265,7 @@ impl CounterProvider {
             backlog: Arc::new(Mutex::new(VecDeque::new())),
             backlog_limit,
             usage_limit,
+            custom: false,
         }
     }
 }
@@ -209,6 +286,15 @@ impl CounterProvider {
         }
     }
 
+    fn limit_exceeded(&self, name: &str, data: CounterData) -> CounterError {
+        CounterError::UsageLimitExceeded(format!(
+            "{} value of {:.3} exceeded the limit of {}",
+            name,
+            data,
+            self.usage_limit.unwrap_or_default()
+        ))
+    }
+
     fn log_report(&mut self, report: CounterReport) {
         let mut backlog = self.backlog.lock().unwrap();
         if let Some(limit) = self.backlog_limit {
@@ -219,3 +305,96 @@ impl CounterProvider {
         backlog.push_front((Utc::now(), report));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counters::TimeCounter;
+
+    const GPU: &str = "golem.usage.gpu-sec";
+
+    fn register(name: &str, monotonic: bool) -> RegisterCounter {
+        RegisterCounter {
+            name: name.to_string(),
+            unit: "s".to_string(),
+            monotonic,
+        }
+    }
+
+    fn set(name: &str, value: f64) -> SetCounter {
+        SetCounter {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn start_service() -> Addr<CountersService> {
+        let mut builder =
+            CountersServiceBuilder::new(vec![TimeCounter::ID.into(), GPU.into()], None);
+        builder.with_counter(TimeCounter::ID, Box::<TimeCounter>::default());
+        builder.build().start()
+    }
+
+    async fn gpu_value(service: &Addr<CountersService>) -> f64 {
+        service.send(GetCounters).await.unwrap().unwrap()[1]
+    }
+
+    #[actix_rt::test]
+    async fn test_register_keeps_counter_value() {
+        let service = start_service();
+
+        service.send(set(GPU, 5.0)).await.unwrap();
+        service.send(register(GPU, true)).await.unwrap().unwrap();
+        assert_eq!(gpu_value(&service).await, 5.0);
+
+        service.send(set(GPU, 7.0)).await.unwrap();
+        service.send(register(GPU, true)).await.unwrap().unwrap();
+        assert_eq!(gpu_value(&service).await, 7.0);
+    }
+
+    #[actix_rt::test]
+    async fn test_register_rejects_conflicting_definitions() {
+        let service = start_service();
+
+        service.send(register(GPU, true)).await.unwrap().unwrap();
+        assert!(service.send(register(GPU, false)).await.unwrap().is_err());
+        assert!(service
+            .send(register(TimeCounter::ID, true))
+            .await
+            .unwrap()
+            .is_err());
+        // Counters outside of the usage vector are accepted, but not reported
+        service
+            .send(register("golem.usage.tokens", true))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_monotonic_counter_ignores_decreasing_values() {
+        let service = start_service();
+
+        service.send(register(GPU, true)).await.unwrap().unwrap();
+        service.send(set(GPU, 5.0)).await.unwrap();
+        service.send(set(GPU, 3.0)).await.unwrap();
+        assert_eq!(gpu_value(&service).await, 5.0);
+        service.send(set(GPU, 6.0)).await.unwrap();
+        assert_eq!(gpu_value(&service).await, 6.0);
+    }
+
+    #[test]
+    fn test_custom_counter_set() {
+        let mut counter = CustomCounter::new(false);
+        counter.set(5.0);
+        counter.set(3.0);
+        assert_eq!(counter.frame().unwrap(), 3.0);
+        assert_eq!(counter.peak().unwrap(), 5.0);
+
+        let mut counter = CustomCounter::new(true);
+        counter.set(5.0);
+        counter.set(3.0);
+        assert_eq!(counter.frame().unwrap(), 5.0);
+        assert_eq!(counter.peak().unwrap(), 5.0);
+    }
+}
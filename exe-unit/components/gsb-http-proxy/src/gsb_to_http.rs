@@ -1,6 +1,10 @@
 use crate::counters::Counters;
+use crate::error::HttpProxyStatusError;
 use crate::headers;
-use crate::message::{GsbHttpCallMessage, GsbHttpCallStreamingMessage};
+use crate::message::{
+    GsbHttpCallMessage, GsbHttpCallStreamingMessage, GsbWsConnectMessage, GsbWsFrame,
+    GsbWsSendMessage,
+};
 use crate::response::{
     GsbHttpCallResponse, GsbHttpCallResponseBody, GsbHttpCallResponseHeader,
     GsbHttpCallResponseStreamChunk,
@@ -9,9 +13,10 @@ use std::collections::HashMap;
 
 use ya_counters::Counter;
 
+use crate::websocket::{self, WsConnections};
 use actix_http::Method;
 use async_stream::stream;
-use futures::{StreamExt, TryFutureExt};
+use futures::{SinkExt, StreamExt, TryFutureExt};
 use futures_core::stream::Stream;
 use http::StatusCode;
 use reqwest::{RequestBuilder, Response};
@@ -24,6 +29,7 @@ use ya_service_bus::{typed as bus, Handle};
 pub struct GsbToHttpProxy {
     base_url: String,
     counters: Counters,
+    websockets: WsConnections,
 }
 
 #[derive(Error, Debug)]
@@ -48,6 +54,7 @@ impl GsbToHttpProxy {
         GsbToHttpProxy {
             base_url,
             counters: Default::default(),
+            websockets: Default::default(),
         }
     }
 
@@ -67,6 +74,26 @@ impl GsbToHttpProxy {
         })
     }
 
+    /// Binds handlers tunneling WebSocket connections to the proxied service.
+    pub fn bind_websocket(&mut self, gsb_path: &str) -> Vec<Handle> {
+        let mut this = self.clone();
+        let connect = bus::bind_stream_with_caller(
+            gsb_path,
+            move |caller: String, message: GsbWsConnectMessage| {
+                Box::pin(this.pass_websocket(caller, message))
+            },
+        );
+        let websockets = self.websockets.clone();
+        let send = bus::bind_with_caller(
+            gsb_path,
+            move |caller: String, message: GsbWsSendMessage| {
+                let websockets = websockets.clone();
+                async move { websockets.send(&caller, message).await }
+            },
+        );
+        vec![connect, send]
+    }
+
     pub async fn pass(&mut self, message: GsbHttpCallMessage) -> GsbHttpCallResponse {
         let url = format!("{}{}", self.base_url, message.path);
         log::info!("Gsb to http call - Url: {url}");
@@ -168,10 +195,28 @@ impl GsbToHttpProxy {
 
                 log::debug!("Calling {}", &url);
                 let response_handler = counters.on_request();
-                let response = builder
-                    .send()
-                    .await
-                    .map_err(|e| GsbToHttpProxyError::ErrorInResponse(e.to_string()))?;
+                let response = match builder.send().await {
+                    Ok(response) => response,
+                    Err(err) => {
+                        log::debug!("Http call to {url} failed: {err}");
+                        tx.send(
+                            GsbHttpCallResponseHeader {
+                                response_headers: Default::default(),
+                                status_code: StatusCode::BAD_GATEWAY.as_u16(),
+                            }
+                            .into(),
+                        )
+                        .await?;
+                        tx.send(
+                            GsbHttpCallResponseBody {
+                                msg_bytes: format!("Error in response: {err}").into_bytes(),
+                            }
+                            .into(),
+                        )
+                        .await?;
+                        return Ok(());
+                    }
+                };
 
                 let response_headers = Self::collect_headers(&response);
                 let status_code = response.status().as_u16();
@@ -189,7 +234,10 @@ impl GsbToHttpProxy {
                 .await
                 .map_err(|e| GsbToHttpProxyError::InternalError(format!("SendError: {e}")))?;
 
-                while let Some(Ok(chunk)) = bytes.next().await {
+                while let Some(chunk) = bytes.next().await {
+                    let chunk = chunk.map_err(|e| {
+                        GsbToHttpProxyError::ErrorInResponse(format!("Body stream error: {e}"))
+                    })?;
                     log::trace!("Sending chunk of size: {}", chunk.len());
                     tx.send(
                         GsbHttpCallResponseBody {
@@ -222,11 +270,100 @@ impl GsbToHttpProxy {
         Box::pin(stream)
     }
 
+    /// Opens WebSocket connection to the proxied service and streams frames received from it.
+    /// Frames are sent to the service with `GsbWsSendMessage` using the same connection id,
+    /// by the same `caller`. The connection accepts frames already while being established.
+    pub fn pass_websocket(
+        &mut self,
+        caller: String,
+        message: GsbWsConnectMessage,
+    ) -> impl Stream<Item = Result<GsbWsFrame, HttpProxyStatusError>> {
+        let url = websocket::websocket_url(&self.base_url, &message.path);
+        let connection_id = message.connection_id;
+        let websockets = self.websockets.clone();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let (sink_tx, mut sink_rx) = mpsc::channel(websocket::WS_QUEUE_SIZE);
+        let registered = websockets.insert(connection_id.clone(), caller, sink_tx);
+
+        tokio::task::spawn_local(async move {
+            if let Err(e) = registered {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+            log::info!("Gsb to WebSocket connection - Url: {url}");
+
+            let mut request = awc::Client::new().ws(url.as_str());
+            for (name, values) in message.headers {
+                if websocket::is_handshake_header(&name) {
+                    continue;
+                }
+                for value in values {
+                    request = request.header(name.as_str(), value);
+                }
+            }
+
+            let framed = match request.connect().await {
+                Ok((_, framed)) => framed,
+                Err(e) => {
+                    websockets.remove(&connection_id);
+                    let _ = tx
+                        .send(Err(HttpProxyStatusError::RuntimeException(format!(
+                            "WebSocket connection to {url} failed: {e}"
+                        ))))
+                        .await;
+                    return;
+                }
+            };
+
+            let (mut sink, mut frames) = framed.split();
+
+            tokio::task::spawn_local(async move {
+                while let Some(message) = sink_rx.recv().await {
+                    if let Err(e) = sink.send(message).await {
+                        log::debug!("Failed to send WebSocket message: {e}");
+                        break;
+                    }
+                }
+            });
+
+            while let Some(frame) = frames.next().await {
+                let frame = match frame {
+                    Ok(frame) => GsbWsFrame::from_frame(frame),
+                    Err(e) => {
+                        let _ = tx
+                            .send(Err(HttpProxyStatusError::RuntimeException(format!(
+                                "WebSocket protocol error: {e}"
+                            ))))
+                            .await;
+                        break;
+                    }
+                };
+                if let Some(frame) = frame {
+                    let closed = matches!(frame, GsbWsFrame::Close(_));
+                    if tx.send(Ok(frame)).await.is_err() || closed {
+                        break;
+                    }
+                }
+            }
+
+            log::debug!("WebSocket connection {connection_id} closed");
+            websockets.remove(&connection_id);
+        });
+
+        Box::pin(stream! {
+            while let Some(frame) = rx.recv().await {
+                yield frame;
+            }
+        })
+    }
+
     fn collect_headers(response: &Response) -> HashMap<String, Vec<String>> {
         let mut response_headers: HashMap<String, Vec<String>> = HashMap::new();
         response
             .headers()
             .iter()
+            .filter(|(name, _)| !headers::is_hop_by_hop(name.as_str()))
             .map(|(name, val)| {
                 (
                     name.to_string(),
@@ -35,9 +35,10 @@ impl Headers {
         for (name, value) in hm.iter() {
             log::debug!("{} => {:?}", name, value);
 
-            if !self
-                .ignored
-                .contains(name.to_string().to_lowercase().as_str())
+            if !is_hop_by_hop(name.as_str())
+                && !self
+                    .ignored
+                    .contains(name.to_string().to_lowercase().as_str())
             {
                 headers
                     .entry(name.to_string())
@@ -51,6 +52,25 @@ impl Headers {
     }
 }
 
+/// Headers describing a single connection or message framing. They must not be forwarded,
+/// because the proxy re-frames the body on both ends of the GSB connection.
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "te",
+    "trailer",
+    "upgrade",
+    "content-length",
+];
+
+pub fn is_hop_by_hop(header: &str) -> bool {
+    HOP_BY_HOP_HEADERS
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(header))
+}
+
 pub fn add(mut builder: RequestBuilder, headers: HashMap<String, Vec<String>>) -> RequestBuilder {
     for (header_name, header_values) in headers {
         for value in header_values {
@@ -98,6 +118,35 @@ mod tests {
         assert_eq!(result.into_keys().collect::<Vec<_>>(), vec!["header-1"]);
     }
 
+    #[test]
+    fn test_hop_by_hop_headers() {
+        assert!(is_hop_by_hop("Transfer-Encoding"));
+        assert!(is_hop_by_hop("content-length"));
+        assert!(!is_hop_by_hop("content-type"));
+    }
+
+    #[test]
+    fn test_headers_hop_by_hop_filtered() {
+        let mut hm = mock_headers();
+        for name in [
+            "transfer-encoding",
+            "te",
+            "upgrade",
+            "keep-alive",
+            "trailer",
+        ] {
+            hm.insert(
+                HeaderName::from_str(name).unwrap(),
+                HeaderValue::from_str("value").unwrap(),
+            );
+        }
+
+        let mut result = Headers::new().filter(&hm).into_keys().collect::<Vec<_>>();
+        result.sort();
+
+        assert_eq!(result, vec!["header-1", "host"]);
+    }
+
     #[test]
     fn test_headers_new_with_ignore_filtered() {
         let hm = mock_headers();
@@ -1,12 +1,16 @@
 use crate::error::HttpProxyStatusError;
 use crate::headers::Headers;
-use crate::message::{GsbHttpCallMessage, GsbHttpCallStreamingMessage};
+use crate::message::{
+    GsbHttpCallMessage, GsbHttpCallStreamingMessage, GsbWsConnectMessage, GsbWsFrame,
+    GsbWsSendMessage,
+};
 use crate::response::GsbHttpCallResponseStreamChunk;
 use actix_http::body::MessageBody;
 use actix_http::header::HeaderMap;
 use actix_web::web::Bytes;
 use futures::{Stream, StreamExt};
 use http::StatusCode;
+use rand::Rng;
 use std::collections::HashMap;
 use ya_client_model::NodeId;
 use ya_core_model::net as ya_net;
@@ -47,6 +51,13 @@ pub struct HttpToGsbProxyStreamingResponse<T> {
     pub body: Result<T, Error>,
 }
 
+pub struct HttpToGsbWebSocket<T> {
+    /// Id used to send frames to the connection with `HttpToGsbProxy::send_websocket`.
+    pub connection_id: String,
+    /// Frames received from the proxied service.
+    pub frames: T,
+}
+
 #[derive(Clone, Debug)]
 pub enum BindingMode {
     Local,
@@ -169,6 +180,70 @@ impl HttpToGsbProxy {
             body: Ok(body_stream),
         }
     }
+
+    /// Opens WebSocket connection to the service behind `GsbToHttpProxy`.
+    /// Connection is established when the returned frame stream is polled for the first time.
+    pub fn pass_websocket(
+        &mut self,
+        path: String,
+        headers: HeaderMap,
+    ) -> HttpToGsbWebSocket<impl Stream<Item = Result<GsbWsFrame, Error>>> {
+        let path = match path.strip_prefix('/') {
+            Some(stripped_url) => stripped_url.to_string(),
+            None => path,
+        };
+        let connection_id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+
+        let msg = GsbWsConnectMessage {
+            connection_id: connection_id.clone(),
+            path,
+            headers: Headers::default().filter(&headers),
+        };
+
+        let stream = match &self.binding {
+            BindingMode::Local => bus::service(&self.bus_addr).call_streaming(msg),
+            BindingMode::Net(binding) => ya_net::from(binding.from)
+                .to(binding.to)
+                .service(&self.bus_addr)
+                .call_streaming(msg),
+        };
+
+        let frames = stream.map(|item| match item {
+            Ok(Ok(frame)) => Ok(frame),
+            Ok(Err(e)) => Err(Error::GsbFailure(e.to_string())),
+            Err(e) => Err(e),
+        });
+
+        HttpToGsbWebSocket {
+            connection_id,
+            frames,
+        }
+    }
+
+    /// Sends frame to the WebSocket connection opened with `pass_websocket`.
+    pub async fn send_websocket(
+        &self,
+        connection_id: String,
+        frame: GsbWsFrame,
+    ) -> Result<(), Error> {
+        let msg = GsbWsSendMessage {
+            connection_id,
+            frame,
+        };
+
+        let response = match &self.binding {
+            BindingMode::Local => bus::service(&self.bus_addr).call(msg).await,
+            BindingMode::Net(binding) => {
+                ya_net::from(binding.from)
+                    .to(binding.to)
+                    .service(&self.bus_addr)
+                    .call(msg)
+                    .await
+            }
+        };
+
+        response?.map_err(|e| Error::GsbFailure(e.to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ mod headers;
 pub mod http_to_gsb;
 pub mod message;
 pub mod response;
+pub mod websocket;
 
 /*
 Proxy http request through GSB
@@ -33,3 +33,50 @@ impl RpcStreamMessage for GsbHttpCallStreamingMessage {
     type Item = GsbHttpCallResponseStreamChunk;
     type Error = HttpProxyStatusError;
 }
+
+/// Opens WebSocket connection to the proxied service.
+/// Frames received from the service are streamed back until the connection is closed.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GsbWsConnectMessage {
+    pub connection_id: String,
+    pub path: String,
+    pub headers: HashMap<String, Vec<String>>,
+}
+
+impl RpcStreamMessage for GsbWsConnectMessage {
+    const ID: &'static str = "GsbWsConnectMessage";
+    type Item = GsbWsFrame;
+    type Error = HttpProxyStatusError;
+}
+
+/// Sends a frame to the proxied service over connection opened with `GsbWsConnectMessage`.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GsbWsSendMessage {
+    pub connection_id: String,
+    pub frame: GsbWsFrame,
+}
+
+impl RpcMessage for GsbWsSendMessage {
+    const ID: &'static str = "GsbWsSendMessage";
+    type Item = ();
+    type Error = HttpProxyStatusError;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum GsbWsFrame {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close(Option<GsbWsCloseReason>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GsbWsCloseReason {
+    pub code: u16,
+    pub description: Option<String>,
+}
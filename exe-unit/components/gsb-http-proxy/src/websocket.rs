@@ -0,0 +1,220 @@
+use crate::error::HttpProxyStatusError;
+use crate::headers;
+use crate::message::{GsbWsCloseReason, GsbWsFrame, GsbWsSendMessage};
+use awc::ws::{CloseCode, CloseReason, Frame, Message};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// Frames waiting to be sent to the proxied service, per connection.
+pub(crate) const WS_QUEUE_SIZE: usize = 16;
+
+/// WebSocket connections opened by `GsbToHttpProxy` indexed by connection id.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WsConnections {
+    inner: Arc<Mutex<HashMap<String, WsConnection>>>,
+}
+
+#[derive(Debug)]
+struct WsConnection {
+    /// Caller, which opened the connection
+    owner: String,
+    tx: mpsc::Sender<Message>,
+}
+
+impl WsConnections {
+    /// Registers connection opened by `owner`. Fails, when the id is already in use.
+    pub fn insert(
+        &self,
+        connection_id: String,
+        owner: String,
+        tx: mpsc::Sender<Message>,
+    ) -> Result<(), HttpProxyStatusError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.contains_key(&connection_id) {
+            return Err(HttpProxyStatusError::RuntimeException(format!(
+                "WebSocket connection already exists: {connection_id}"
+            )));
+        }
+        inner.insert(connection_id, WsConnection { owner, tx });
+        Ok(())
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.inner.lock().unwrap().remove(connection_id);
+    }
+
+    /// Sends frame to the connection. Only the caller, which opened it, can use it.
+    pub async fn send(
+        &self,
+        caller: &str,
+        message: GsbWsSendMessage,
+    ) -> Result<(), HttpProxyStatusError> {
+        let tx = self
+            .inner
+            .lock()
+            .unwrap()
+            .get(&message.connection_id)
+            .filter(|connection| connection.owner == caller)
+            .map(|connection| connection.tx.clone())
+            .ok_or_else(|| {
+                HttpProxyStatusError::RuntimeException(format!(
+                    "Unknown WebSocket connection: {}",
+                    message.connection_id
+                ))
+            })?;
+        tx.send(message.frame.into_message()).await.map_err(|_| {
+            HttpProxyStatusError::RuntimeException(format!(
+                "WebSocket connection closed: {}",
+                message.connection_id
+            ))
+        })
+    }
+}
+
+/// Builds WebSocket url of the proxied service from its http `base_url`.
+pub(crate) fn websocket_url(base_url: &str, path: &str) -> String {
+    let base_url = if let Some(url) = base_url.strip_prefix("https://") {
+        format!("wss://{url}")
+    } else if let Some(url) = base_url.strip_prefix("http://") {
+        format!("ws://{url}")
+    } else {
+        base_url.to_string()
+    };
+    format!("{base_url}{path}")
+}
+
+/// Handshake headers are generated by the WebSocket client and must not be forwarded.
+pub(crate) fn is_handshake_header(header: &str) -> bool {
+    let header = header.to_lowercase();
+    headers::is_hop_by_hop(&header)
+        || header == "host"
+        || (header.starts_with("sec-websocket-") && header != "sec-websocket-protocol")
+}
+
+impl GsbWsFrame {
+    /// Converts frame received from the WebSocket client.
+    /// Fragmented messages are not supported.
+    pub fn from_frame(frame: Frame) -> Option<Self> {
+        match frame {
+            Frame::Text(bytes) => Some(GsbWsFrame::Text(
+                String::from_utf8_lossy(&bytes).into_owned(),
+            )),
+            Frame::Binary(bytes) => Some(GsbWsFrame::Binary(bytes.to_vec())),
+            Frame::Ping(bytes) => Some(GsbWsFrame::Ping(bytes.to_vec())),
+            Frame::Pong(bytes) => Some(GsbWsFrame::Pong(bytes.to_vec())),
+            Frame::Close(reason) => Some(GsbWsFrame::Close(reason.map(Into::into))),
+            Frame::Continuation(_) => {
+                log::warn!("Fragmented WebSocket messages are not supported");
+                None
+            }
+        }
+    }
+
+    /// Converts message received by the WebSocket server.
+    /// Fragmented messages are not supported.
+    pub fn from_message(message: Message) -> Option<Self> {
+        match message {
+            Message::Text(text) => Some(GsbWsFrame::Text(text.to_string())),
+            Message::Binary(bytes) => Some(GsbWsFrame::Binary(bytes.to_vec())),
+            Message::Ping(bytes) => Some(GsbWsFrame::Ping(bytes.to_vec())),
+            Message::Pong(bytes) => Some(GsbWsFrame::Pong(bytes.to_vec())),
+            Message::Close(reason) => Some(GsbWsFrame::Close(reason.map(Into::into))),
+            Message::Continuation(_) => {
+                log::warn!("Fragmented WebSocket messages are not supported");
+                None
+            }
+            Message::Nop => None,
+        }
+    }
+
+    pub fn into_message(self) -> Message {
+        match self {
+            GsbWsFrame::Text(text) => Message::Text(text.into()),
+            GsbWsFrame::Binary(bytes) => Message::Binary(bytes.into()),
+            GsbWsFrame::Ping(bytes) => Message::Ping(bytes.into()),
+            GsbWsFrame::Pong(bytes) => Message::Pong(bytes.into()),
+            GsbWsFrame::Close(reason) => Message::Close(reason.map(Into::into)),
+        }
+    }
+}
+
+impl From<CloseReason> for GsbWsCloseReason {
+    fn from(reason: CloseReason) -> Self {
+        GsbWsCloseReason {
+            code: reason.code.into(),
+            description: reason.description,
+        }
+    }
+}
+
+impl From<GsbWsCloseReason> for CloseReason {
+    fn from(reason: GsbWsCloseReason) -> Self {
+        CloseReason {
+            code: CloseCode::from(reason.code),
+            description: reason.description,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_url() {
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8080/", "ws"),
+            "ws://127.0.0.1:8080/ws"
+        );
+        assert_eq!(
+            websocket_url("https://localhost/", "api/ws"),
+            "wss://localhost/api/ws"
+        );
+    }
+
+    #[test]
+    fn test_handshake_headers() {
+        assert!(is_handshake_header("Sec-WebSocket-Key"));
+        assert!(is_handshake_header("Upgrade"));
+        assert!(!is_handshake_header("Sec-WebSocket-Protocol"));
+        assert!(!is_handshake_header("Authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_send_by_owner_only() {
+        let connections = WsConnections::default();
+        let (tx, mut rx) = mpsc::channel(WS_QUEUE_SIZE);
+        connections
+            .insert("1".to_string(), "0xa".to_string(), tx.clone())
+            .unwrap();
+        assert!(connections
+            .insert("1".to_string(), "0xb".to_string(), tx)
+            .is_err());
+
+        let message = GsbWsSendMessage {
+            connection_id: "1".to_string(),
+            frame: GsbWsFrame::Text("text".to_string()),
+        };
+        assert!(connections.send("0xb", message.clone()).await.is_err());
+        connections.send("0xa", message).await.unwrap();
+        assert!(matches!(rx.recv().await, Some(Message::Text(_))));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_frame_message_roundtrip() {
+        let frames = vec![
+            GsbWsFrame::Text("text".to_string()),
+            GsbWsFrame::Binary(vec![1, 2, 3]),
+            GsbWsFrame::Close(Some(GsbWsCloseReason {
+                code: 1000,
+                description: Some("bye".to_string()),
+            })),
+        ];
+        for frame in frames {
+            let message = frame.clone().into_message();
+            assert_eq!(GsbWsFrame::from_message(message), Some(frame));
+        }
+    }
+}
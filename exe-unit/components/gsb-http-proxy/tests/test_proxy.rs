@@ -1,12 +1,16 @@
+use actix::{Actor, StreamHandler};
 use actix_http::header::HeaderMap;
-use actix_web::{web, Error, HttpResponse, Responder};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Responder};
 use actix_web::{App, HttpServer};
+use actix_web_actors::ws;
 use async_stream::stream;
 use bytes::Bytes;
+use futures::StreamExt;
 use test_context::test_context;
 use ya_framework_basic::async_drop::DroppableTestContext;
 use ya_gsb_http_proxy::gsb_to_http::GsbToHttpProxy;
-use ya_gsb_http_proxy::http_to_gsb::{BindingMode, HttpToGsbProxy};
+use ya_gsb_http_proxy::http_to_gsb::{BindingMode, HttpToGsbProxy, HttpToGsbWebSocket};
+use ya_gsb_http_proxy::message::GsbWsFrame;
 
 #[test_context(DroppableTestContext)]
 #[serial_test::serial]
@@ -53,6 +57,72 @@ pub async fn test_gsb_http_streaming_proxy(ctx: &mut DroppableTestContext) {
     assert_eq!(r, "All the chunks... 0 1 2 3 4");
 }
 
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+pub async fn test_gsb_websocket_proxy(ctx: &mut DroppableTestContext) {
+    start_target_server(ctx).await;
+
+    ya_sb_router::bind_gsb_router(None)
+        .await
+        .expect("should bind to gsb");
+
+    let mut gsb_proxy = GsbToHttpProxy::new("http://127.0.0.1:8082/".into());
+    gsb_proxy.bind_websocket(ya_gsb_http_proxy::BUS_ID);
+
+    let mut http_to_gsb = HttpToGsbProxy::new(BindingMode::Local);
+    let HttpToGsbWebSocket {
+        connection_id,
+        frames,
+    } = http_to_gsb.pass_websocket("/target-ws".to_string(), HeaderMap::default());
+    let mut frames = frames.boxed_local();
+
+    // Frames sent right after connecting are delivered, while the proxy is still
+    // establishing connection to the service.
+    let (echo, sent) = futures::join!(frames.next(), async {
+        tokio::task::yield_now().await;
+        http_to_gsb
+            .send_websocket(connection_id.clone(), GsbWsFrame::Text("hello".into()))
+            .await
+    });
+    sent.expect("frame should be sent");
+    assert_eq!(
+        echo.expect("echo expected").expect("valid frame"),
+        GsbWsFrame::Text("hello".into())
+    );
+
+    http_to_gsb
+        .send_websocket(connection_id.clone(), GsbWsFrame::Close(None))
+        .await
+        .expect("close should be sent");
+    assert!(matches!(
+        frames.next().await,
+        Some(Ok(GsbWsFrame::Close(_)))
+    ));
+    assert!(frames.next().await.is_none());
+
+    // Connection is gone after closing
+    assert!(http_to_gsb
+        .send_websocket(connection_id, GsbWsFrame::Text("late".into()))
+        .await
+        .is_err());
+}
+
+struct Echo;
+
+impl Actor for Echo {
+    type Context = ws::WebsocketContext<Self>;
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for Echo {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Text(text)) => ctx.text(text),
+            Ok(ws::Message::Close(reason)) => ctx.close(reason),
+            _ => (),
+        }
+    }
+}
+
 async fn start_proxy_http_server(ctx: &mut DroppableTestContext) {
     async fn proxy_endpoint() -> impl Responder {
         let mut http_to_gsb = HttpToGsbProxy::new(BindingMode::Local);
@@ -124,6 +194,12 @@ async fn start_target_server(ctx: &mut DroppableTestContext) {
                     HttpResponse::Ok().streaming(stream)
                 }),
             )
+            .route(
+                "/target-ws",
+                web::get().to(|request: HttpRequest, stream: web::Payload| async move {
+                    ws::start(Echo, &request, stream)
+                }),
+            )
     })
     .bind(("127.0.0.1", 8082))
     .expect("should bind correctly")
@@ -1,3 +1,4 @@
+pub mod scenario;
 pub mod testing;
 
 use futures::future::BoxFuture;
@@ -9,11 +10,14 @@ use std::time::Duration;
 
 use ya_runtime_api::server::*;
 
+use crate::scenario::Scenario;
+
 pub struct RuntimeMock<H>
 where
     H: RuntimeHandler,
 {
     pub handler: H,
+    pub scenario: Scenario,
 }
 
 impl<H: RuntimeHandler> RuntimeService for RuntimeMock<H> {
@@ -22,9 +26,20 @@ impl<H: RuntimeHandler> RuntimeService for RuntimeMock<H> {
         async { Ok("0.0.0-demo".to_owned()) }.boxed_local()
     }
 
-    fn run_process(&self, _run: RunProcess) -> AsyncResponse<RunProcessResp> {
+    fn run_process(&self, run: RunProcess) -> AsyncResponse<RunProcessResp> {
         async move {
             let resp = RunProcessResp { pid: 100 };
+            if run.stdin {
+                // process echoes its stdin and exits when it's closed
+                self.handler
+                    .on_process_status(ProcessStatus {
+                        pid: resp.pid,
+                        running: true,
+                        ..Default::default()
+                    })
+                    .await;
+                return Ok(resp);
+            }
             log::debug!("before sleep");
             tokio::time::sleep(Duration::from_secs(3)).await;
             log::debug!("after sleep");
@@ -37,17 +52,47 @@ impl<H: RuntimeHandler> RuntimeService for RuntimeMock<H> {
                     stderr: Vec::new(),
                 })
                 .await;
+            for stdout in self.scenario.burst_output() {
+                self.handler
+                    .on_process_status(ProcessStatus {
+                        pid: resp.pid,
+                        running: true,
+                        return_code: 0,
+                        stdout,
+                        stderr: Vec::new(),
+                    })
+                    .await;
+            }
             Ok(resp)
         }
         .boxed_local()
     }
 
+    fn write_stdin(&self, write: WriteStdin) -> AsyncResponse<()> {
+        async move {
+            self.handler
+                .on_process_status(ProcessStatus {
+                    pid: write.pid,
+                    running: !write.eof,
+                    return_code: 0,
+                    stdout: write.data,
+                    stderr: Vec::new(),
+                })
+                .await;
+            Ok(())
+        }
+        .boxed_local()
+    }
+
     fn kill_process(&self, kill: KillProcess) -> AsyncResponse<()> {
         log::debug!("got kill: {:?}", kill);
         future::ok(()).boxed_local()
     }
 
     fn create_network(&self, _: CreateNetwork) -> AsyncResponse<'_, CreateNetworkResp> {
+        if self.scenario.fail_network {
+            return future::err(ErrorResponse::msg("network creation failed")).boxed_local();
+        }
         unimplemented!()
     }
 
@@ -1,7 +1,9 @@
 use futures::future;
 use std::env;
 
+use ya_mock_runtime::scenario::Scenario;
 use ya_mock_runtime::{EventMock, RuntimeMock};
+use ya_runtime_api::deploy::{DeployResult, StartMode};
 use ya_runtime_api::server::{run, spawn, RunProcess, RuntimeService};
 
 #[tokio::main]
@@ -10,9 +12,26 @@ async fn main() -> anyhow::Result<()> {
         env::set_var("RUST_LOG", "debug")
     }
     env_logger::init();
+
+    let scenario = Scenario::from_env()?;
+    if scenario.service_mode {
+        return serve(scenario).await;
+    }
+
+    // Runtime service spawned by `start` is driven via stdin/stdout and gets no command.
+    let command = subcommand(env::args().skip(1));
+    if let Some(command @ ("start" | "run")) = command.as_deref() {
+        if command == "start" {
+            scenario.delay_start().await;
+        }
+        scenario.schedule_crash();
+        scenario.write_burst()?;
+    }
+
     if env::var("X_SERVER").is_ok() {
         run(|event_emitter| RuntimeMock {
             handler: event_emitter,
+            scenario: scenario.clone(),
         })
         .await
     } else {
@@ -40,3 +59,73 @@ async fn main() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// Runtime driven by ExeUnit over the runtime API, started in blocking mode.
+async fn serve(scenario: Scenario) -> anyhow::Result<()> {
+    match subcommand(env::args().skip(1)).as_deref() {
+        Some("deploy") => {
+            let result = DeployResult {
+                valid: Ok(Default::default()),
+                vols: Default::default(),
+                start_mode: StartMode::Blocking,
+            };
+            println!("{}", serde_json::to_string(&result)?);
+        }
+        Some("start") => {
+            scenario.delay_start().await;
+            scenario.schedule_crash();
+            run(|event_emitter| RuntimeMock {
+                handler: event_emitter,
+                scenario: scenario.clone(),
+            })
+            .await
+        }
+        _ => anyhow::bail!("Mock runtime in service mode supports only deploy and start"),
+    }
+    Ok(())
+}
+
+/// Finds the runtime subcommand, which follows options passed by ExeUnit.
+/// All of these options take a value, e.g. `--workdir <path>`.
+fn subcommand(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            return None;
+        }
+        if !arg.starts_with("--") {
+            return Some(arg);
+        }
+        if !arg.contains('=') {
+            args.next();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_subcommand() {
+        let start = args(&["--workdir", "/tmp/start", "start", "--", "run"]);
+        assert_eq!(subcommand(start).as_deref(), Some("start"));
+
+        let run = args(&["--workdir=/tmp", "run", "--entrypoint", "start", "--"]);
+        assert_eq!(subcommand(run).as_deref(), Some("run"));
+
+        let deploy = args(&["--workdir", "run", "--hostname", "start", "deploy", "--"]);
+        assert_eq!(subcommand(deploy).as_deref(), Some("deploy"));
+
+        assert_eq!(
+            subcommand(args(&["--workdir", "/tmp", "--", "start"])),
+            None
+        );
+        assert_eq!(subcommand(args(&[])), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Failure injection for ExeUnit supervisor tests.
+//!
+//! Runtime is spawned by ExeUnit as a separate process, so the scenario is passed
+//! as a JSON file, which path is read from `MOCK_RUNTIME_SCENARIO` environment variable.
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const SCENARIO_ENV_VAR: &str = "MOCK_RUNTIME_SCENARIO";
+
+/// Runtime process exits with `exit_code` after `after_ms` from command start.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Crash {
+    pub after_ms: u64,
+    pub exit_code: i32,
+}
+
+/// Runtime writes `chunks` lines of `chunk_size` bytes to stdout.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputBurst {
+    pub chunks: usize,
+    pub chunk_size: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Scenario {
+    /// Delays `start` command.
+    pub start_delay_ms: Option<u64>,
+    pub crash: Option<Crash>,
+    pub stdout_burst: Option<OutputBurst>,
+    /// `CreateNetwork` requests are answered with an error.
+    pub fail_network: bool,
+    /// Runtime deploys in blocking start mode and serves the runtime API after `start`.
+    pub service_mode: bool,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start_delay(mut self, delay: Duration) -> Self {
+        self.start_delay_ms = Some(delay.as_millis() as u64);
+        self
+    }
+
+    pub fn crash_after(mut self, after: Duration, exit_code: i32) -> Self {
+        self.crash = Some(Crash {
+            after_ms: after.as_millis() as u64,
+            exit_code,
+        });
+        self
+    }
+
+    pub fn stdout_burst(mut self, chunks: usize, chunk_size: usize) -> Self {
+        self.stdout_burst = Some(OutputBurst { chunks, chunk_size });
+        self
+    }
+
+    pub fn fail_network(mut self) -> Self {
+        self.fail_network = true;
+        self
+    }
+
+    pub fn service_mode(mut self) -> Self {
+        self.service_mode = true;
+        self
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&content)?)
+    }
+
+    /// Loads scenario from file pointed by `MOCK_RUNTIME_SCENARIO`.
+    /// Default scenario doesn't inject any failures.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(SCENARIO_ENV_VAR) {
+            Ok(path) => Self::load(Path::new(&path)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Saves scenario in `dir` and sets `MOCK_RUNTIME_SCENARIO`, so it will be
+    /// used by runtimes spawned by ExeUnits created afterwards.
+    pub fn install(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = dir.join("mock-runtime-scenario.json");
+        self.save(&path)?;
+        std::env::set_var(SCENARIO_ENV_VAR, &path);
+        Ok(path)
+    }
+
+    pub fn uninstall() {
+        std::env::remove_var(SCENARIO_ENV_VAR);
+    }
+
+    pub async fn delay_start(&self) {
+        if let Some(delay) = self.start_delay_ms {
+            log::debug!("Delaying start by {delay} ms");
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+    }
+
+    /// Schedules process exit, if scenario expects a crash.
+    pub fn schedule_crash(&self) {
+        if let Some(crash) = self.crash.clone() {
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(crash.after_ms)).await;
+                log::info!("Crashing with exit code {}", crash.exit_code);
+                std::process::exit(crash.exit_code);
+            });
+        }
+    }
+
+    pub fn burst_output(&self) -> Vec<Vec<u8>> {
+        match &self.stdout_burst {
+            Some(burst) => (0..burst.chunks)
+                .map(|i| {
+                    let mut chunk = vec![b'a' + (i % 26) as u8; burst.chunk_size];
+                    chunk.push(b'\n');
+                    chunk
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn write_burst(&self) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        for chunk in self.burst_output() {
+            stdout.write_all(&chunk)?;
+        }
+        stdout.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scenario_json() {
+        let scenario = Scenario::new()
+            .start_delay(Duration::from_millis(1500))
+            .crash_after(Duration::from_secs(2), 3)
+            .fail_network();
+
+        let json = serde_json::to_value(&scenario).unwrap();
+        assert_eq!(json["startDelayMs"], 1500);
+        assert_eq!(json["crash"]["exitCode"], 3);
+        assert_eq!(serde_json::from_value::<Scenario>(json).unwrap(), scenario);
+
+        let partial: Scenario = serde_json::from_str(r#"{"failNetwork": true}"#).unwrap();
+        assert_eq!(partial, Scenario::new().fail_network());
+    }
+
+    #[test]
+    fn test_burst_output() {
+        let output = Scenario::new().stdout_burst(3, 4).burst_output();
+        assert_eq!(output.len(), 3);
+        assert_eq!(output[1], b"bbbb\n".to_vec());
+        assert!(Scenario::new().burst_output().is_empty());
+    }
+}
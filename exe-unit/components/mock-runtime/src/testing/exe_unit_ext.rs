@@ -88,6 +88,13 @@ pub fn exe_unit_config(
         args: RunArgs {
             agreement: agreement_path.to_path_buf(),
             cache_dir: temp_dir.join("cache"),
+            cache_max_size: None,
+            idle_timeout: None,
+            transfer_min_throughput: None,
+            transfer_stall_window: std::time::Duration::from_secs(30),
+            output_buffer_size: bytesize::ByteSize::mib(8),
+            output_log_size: None,
+            output_log_total_size: bytesize::ByteSize::gib(1),
             work_dir: temp_dir.join("work"),
         },
         binary: binary.as_ref().to_path_buf(),
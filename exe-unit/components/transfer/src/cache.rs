@@ -1,15 +1,37 @@
+use fs2::FileExt;
 use sha3::Digest;
 use std::convert::TryFrom;
+use std::fs::File;
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
 
 use crate::{error::Error as TransferError, TransferUrl};
 
+/// Image cache shared by all ExeUnits of a Provider.
+///
+/// Cached files are addressed by their content hash. Last use of an entry
+/// is tracked by its modification time, which is used for LRU eviction.
+/// Entries locked by running ExeUnits are never evicted.
 #[derive(Debug, Clone)]
 pub struct Cache {
     dir: PathBuf,
-    #[allow(dead_code)]
     tmp_dir: PathBuf,
+    max_size: Option<u64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Shared lock on a cache entry, which protects it from eviction
+/// until dropped.
+#[derive(Debug)]
+pub struct EntryLock {
+    _file: File,
 }
 
 impl Cache {
@@ -17,7 +39,123 @@ impl Cache {
         let tmp_dir = dir.join("tmp");
         std::fs::create_dir_all(&tmp_dir)
             .unwrap_or_else(|_| panic!("Unable to create directory: {}", tmp_dir.display()));
-        Cache { dir, tmp_dir }
+        Cache {
+            dir,
+            tmp_dir,
+            max_size: None,
+        }
+    }
+
+    /// Limits total size of cached files. Least recently used entries
+    /// are evicted after deploying a new image.
+    pub fn with_max_size(mut self, max_size: Option<u64>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Finds a cached file with given content hash, regardless of its original name.
+    pub fn find(&self, hash: &[u8]) -> Option<PathBuf> {
+        let suffix = format!("_{}", hex::encode(hash));
+        self.entries()
+            .ok()?
+            .into_iter()
+            .map(|entry| entry.path)
+            .find(|path| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().ends_with(&suffix))
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Marks cache entry as recently used.
+    pub fn touch(&self, path: &Path) {
+        let result = std::fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        if let Err(e) = result {
+            log::warn!("Unable to update cache entry {}: {}", path.display(), e);
+        }
+    }
+
+    /// Marks cache entry as used until the returned lock is dropped.
+    pub fn lock(&self, path: &Path) -> std::io::Result<EntryLock> {
+        self.touch(path);
+        let file = File::open(path)?;
+        file.lock_shared()?;
+        Ok(EntryLock { _file: file })
+    }
+
+    /// Checks whether any ExeUnit holds a lock on the cache entry.
+    pub fn in_use(path: &Path) -> bool {
+        match File::open(path) {
+            Ok(file) => file.try_lock_exclusive().is_err(),
+            Err(_) => false,
+        }
+    }
+
+    /// Lists cached files, omitting partial downloads.
+    pub fn entries(&self) -> std::io::Result<Vec<CacheEntry>> {
+        let mut entries = read_entries(&self.dir)?;
+        entries.sort_by_key(|entry| entry.last_used);
+        Ok(entries)
+    }
+
+    /// Lists partially downloaded files.
+    pub fn partial_downloads(&self) -> std::io::Result<Vec<CacheEntry>> {
+        read_entries(&self.tmp_dir)
+    }
+
+    /// Selects least recently used entries, which have to be removed for the cache
+    /// not to exceed `max_size`. Entry at `keep` path and entries in use are skipped.
+    pub fn to_evict(&self, max_size: u64, keep: Option<&Path>) -> std::io::Result<Vec<CacheEntry>> {
+        let entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+        let mut selected = Vec::new();
+
+        for entry in entries {
+            if total <= max_size {
+                break;
+            }
+            if Some(entry.path.as_path()) == keep || Cache::in_use(&entry.path) {
+                continue;
+            }
+            total -= entry.size;
+            selected.push(entry);
+        }
+        Ok(selected)
+    }
+
+    /// Removes least recently used entries until total size of the cache
+    /// does not exceed `max_size`. Entry at `keep` path and entries in use
+    /// are never removed. Returns the number of freed bytes.
+    pub fn evict(&self, max_size: u64, keep: Option<&Path>) -> std::io::Result<u64> {
+        let mut freed = 0;
+        for entry in self.to_evict(max_size, keep)? {
+            log::info!("Evicting cached file: {}", entry.path.display());
+            std::fs::remove_file(&entry.path)?;
+            freed += entry.size;
+        }
+        Ok(freed)
+    }
+
+    /// Removes all cached files which aren't in use, including partial downloads.
+    /// Returns the number of freed bytes.
+    pub fn purge(&self) -> std::io::Result<u64> {
+        let mut freed = self.evict(0, None)?;
+        for entry in self.partial_downloads()? {
+            std::fs::remove_file(&entry.path)?;
+            freed += entry.size;
+        }
+        Ok(freed)
     }
 
     pub fn name(transfer_url: &TransferUrl) -> Result<CachePath, TransferError> {
@@ -144,6 +282,11 @@ impl CachePath {
     pub fn new(path: PathBuf, hash: Vec<u8>, nonce: String) -> Self {
         CachePath { path, hash, nonce }
     }
+
+    pub fn hash(&self) -> &[u8] {
+        &self.hash
+    }
+
     /// Creates the long version of path, including hash and the "random" token.
     pub fn temp_path(&self) -> PathBuf {
         let mut digest = sha3::Sha3_224::default();
@@ -172,6 +315,23 @@ impl CachePath {
     }
 }
 
+fn read_entries(dir: &Path) -> std::io::Result<Vec<CacheEntry>> {
+    let mut entries = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if !meta.is_file() {
+            continue;
+        }
+        entries.push(CacheEntry {
+            path: entry.path(),
+            size: meta.len(),
+            last_used: meta.modified()?,
+        });
+    }
+    Ok(entries)
+}
+
 /// Path flattening specific to the custom "container" scheme. Naively resolves all occurrences of
 /// ".." and strips all ".". Does not support symlinks.
 fn flatten_container_path(path: PathBuf) -> PathBuf {
@@ -228,6 +388,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_and_evict() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let cache = Cache::new(dir.path().to_path_buf());
+
+        let first = CachePath::new(path_buf("image.gvmi"), vec![1, 2], "a".into());
+        let second = CachePath::new(path_buf("other.gvmi"), vec![3, 4], "b".into());
+        let first_path = cache.to_final_path(&first).to_path_buf();
+        let second_path = cache.to_final_path(&second).to_path_buf();
+        std::fs::write(&first_path, [0u8; 10]).unwrap();
+        std::fs::write(&second_path, [0u8; 10]).unwrap();
+
+        assert_eq!(cache.find(&[1, 2]), Some(first_path.clone()));
+        assert_eq!(cache.find(&[5, 6]), None);
+
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&second_path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        assert_eq!(cache.evict(15, None).unwrap(), 10);
+        assert!(first_path.exists());
+        assert!(!second_path.exists());
+
+        assert_eq!(cache.evict(0, Some(&first_path)).unwrap(), 0);
+        assert!(first_path.exists());
+    }
+
+    #[test]
+    fn test_evict_skips_locked() {
+        let dir = tempdir::TempDir::new("cache").unwrap();
+        let cache = Cache::new(dir.path().to_path_buf());
+
+        let image = CachePath::new(path_buf("image.gvmi"), vec![1, 2], "a".into());
+        let path = cache.to_final_path(&image).to_path_buf();
+        std::fs::write(&path, [0u8; 10]).unwrap();
+        let partial = cache.to_temp_path(&image).to_path_buf();
+        std::fs::write(&partial, [0u8; 5]).unwrap();
+
+        let lock = cache.lock(&path).unwrap();
+        assert!(Cache::in_use(&path));
+        assert!(cache.to_evict(0, None).unwrap().is_empty());
+        assert_eq!(cache.purge().unwrap(), 5);
+        assert!(path.exists());
+        assert!(!partial.exists());
+
+        drop(lock);
+        assert!(!Cache::in_use(&path));
+        assert_eq!(cache.to_evict(0, None).unwrap().len(), 1);
+        assert!(path.exists());
+        assert_eq!(cache.evict(0, None).unwrap(), 10);
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_remove_base() {
         assert_eq!(path_buf(""), remove_container_path_base(path_buf("")));
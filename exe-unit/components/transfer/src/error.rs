@@ -121,6 +121,16 @@ pub enum Error {
     HexError(#[from] hex::FromHexError),
     #[error("Net API error: {0}")]
     NetApiError(#[from] ya_core_model::net::NetApiError),
+    #[error("Insufficient disk space in {}: {required} B required, {available} B available", path.display())]
+    InsufficientSpace {
+        path: std::path::PathBuf,
+        required: u64,
+        available: u64,
+    },
+    #[error("Image size {size} B exceeds cache capacity of {capacity} B")]
+    CacheCapacityExceeded { size: u64, capacity: u64 },
+    #[error("Transfer stalled: {throughput} B/s is below the minimum of {min} B/s")]
+    Stalled { throughput: u64, min: u64 },
     #[error("Cancelled")]
     Cancelled,
     #[error("{0}")]
@@ -26,6 +26,11 @@ impl TransferProvider<TransferData, Error> for FileTransferProvider {
         vec!["file"]
     }
 
+    fn source_size<'a>(&self, url: &Url) -> LocalBoxFuture<'a, Result<Option<u64>, Error>> {
+        let path = extract_file_url(url);
+        async move { Ok(Some(tokio::fs::metadata(path).await?.len())) }.boxed_local()
+    }
+
     fn source(&self, url: &Url, ctx: &TransferContext) -> TransferStream<TransferData, Error> {
         let (stream, tx, abort_reg) = TransferStream::<TransferData, Error>::create(1);
         let mut txc = tx.clone();
@@ -3,7 +3,7 @@ use crate::{abortable_sink, abortable_stream};
 use crate::{TransferContext, TransferData, TransferProvider, TransferSink, TransferStream};
 use bytes::Bytes;
 use futures::channel::mpsc;
-use futures::future::{ready, try_select, Either};
+use futures::future::{ready, try_select, Either, LocalBoxFuture};
 use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt, TryStreamExt};
 use gftp::DEFAULT_CHUNK_SIZE;
 use sha3::{Digest, Sha3_256};
@@ -44,17 +44,32 @@ impl TransferProvider<TransferData, Error> for GftpTransferProvider {
                 let (node_id, hash) = gftp::extract_url(&url)
                     .map_err(|_| Error::InvalidUrlError("Invalid gftp URL".to_owned()))?;
 
-                let remote = node_id.service_transfer(&model::file_bus_id(&hash));
-                let meta = remote.send(model::GetMetadata {}).await??;
-                state.set_size(Some(meta.file_size));
-                let n = (meta.file_size + chunk_size - 1) / chunk_size;
+                let (hash, _) = gftp::split_dir_path(&hash);
+                let token = gftp::extract_token(&url);
+                let remote = node_id.service_transfer(&model::share_bus_id(hash, token.as_deref()));
+
+                let (file_size, path) = file_info(&url).await?;
+                state.set_size(Some(file_size));
+                let n = (file_size + chunk_size - 1) / chunk_size;
 
                 futures::stream::iter(0..n)
                     .map(|chunk_number| {
-                        remote.call(model::GetChunk {
-                            offset: chunk_number * chunk_size,
-                            size: chunk_size,
-                        })
+                        let offset = chunk_number * chunk_size;
+                        match &path {
+                            Some(path) => remote
+                                .call(model::GetFileChunk {
+                                    path: path.clone(),
+                                    offset,
+                                    size: chunk_size,
+                                })
+                                .left_future(),
+                            None => remote
+                                .call(model::GetChunk {
+                                    offset,
+                                    size: chunk_size,
+                                })
+                                .right_future(),
+                        }
                     })
                     .buffered(concurrency)
                     .map_err(Error::from)
@@ -76,6 +91,11 @@ impl TransferProvider<TransferData, Error> for GftpTransferProvider {
         stream
     }
 
+    fn source_size<'a>(&self, url: &Url) -> LocalBoxFuture<'a, Result<Option<u64>, Error>> {
+        let url = url.clone();
+        async move { Ok(Some(file_info(&url).await?.0)) }.boxed_local()
+    }
+
     fn destination(&self, url: &Url, _: &TransferContext) -> TransferSink<TransferData, Error> {
         let url = url.clone();
         let concurrency = self.concurrency;
@@ -153,3 +173,32 @@ impl TransferProvider<TransferData, Error> for GftpTransferProvider {
         sink
     }
 }
+
+/// Resolves size of a file shared under gftp `url`. Url pointing inside of a published
+/// directory selects a single file, either by its path or by a glob pattern.
+async fn file_info(url: &Url) -> Result<(u64, Option<String>), Error> {
+    let (node_id, hash) = gftp::extract_url(url)
+        .map_err(|_| Error::InvalidUrlError("Invalid gftp URL".to_owned()))?;
+
+    let (hash, pattern) = gftp::split_dir_path(&hash);
+    let token = gftp::extract_token(url);
+    let remote = node_id.service_transfer(&model::share_bus_id(hash, token.as_deref()));
+
+    match pattern {
+        Some(pattern) => {
+            let listing = remote.send(model::GetDirectory {}).await??;
+            let patterns = [pattern.to_string()];
+            let entries = gftp::select_files(&listing, &patterns)
+                .map_err(|e| Error::InvalidUrlError(e.to_string()))?;
+            match entries.as_slice() {
+                [entry] => Ok((entry.file_size, Some(entry.path.clone()))),
+                _ => Err(Error::InvalidUrlError(format!(
+                    "'{}' matches {} files in published directory",
+                    pattern,
+                    entries.len()
+                ))),
+            }
+        }
+        None => Ok((remote.send(model::GetMetadata {}).await??.file_size, None)),
+    }
+}
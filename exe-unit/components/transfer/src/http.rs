@@ -114,6 +114,18 @@ impl TransferProvider<TransferData, Error> for HttpTransferProvider {
         sink
     }
 
+    fn source_size<'a>(&self, url: &Url) -> LocalBoxFuture<'a, Result<Option<u64>, Error>> {
+        let url = url.clone();
+        async move {
+            let response = DownloadRequest::head(url).send().await?;
+            Ok(response
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok().and_then(|s| u64::from_str(s).ok())))
+        }
+        .boxed_local()
+    }
+
     fn prepare_source<'a>(
         &self,
         url: &Url,
@@ -229,7 +241,7 @@ impl DownloadRequest {
     }
 }
 
-trait HttpErr<T>
+pub(crate) trait HttpErr<T>
 where
     Self: Sized,
 {
@@ -7,8 +7,10 @@ mod gftp;
 mod hash;
 mod http;
 mod location;
+mod oci;
 mod progress;
 mod retry;
+mod throughput;
 pub mod transfer;
 mod traverse;
 
@@ -33,9 +35,13 @@ pub use crate::gftp::GftpTransferProvider;
 use crate::hash::with_hash_stream;
 pub use crate::http::HttpTransferProvider;
 pub use crate::location::{TransferUrl, UrlExt};
+pub use crate::oci::{OciTransferProvider, OutboundPolicy};
 use crate::progress::{progress_report_channel, ProgressReporter};
 pub use crate::progress::{wrap_sink_with_progress_reporting, wrap_stream_with_progress_reporting};
+use crate::retry::can_retry;
 pub use crate::retry::Retry;
+pub use crate::throughput::MinThroughput;
+use crate::throughput::ThroughputStream;
 pub use crate::traverse::PathTraverse;
 
 use ya_client_model::activity::TransferArgs;
@@ -62,10 +68,35 @@ where
     S: TransferProvider<TransferData, Error> + ?Sized,
     D: TransferProvider<TransferData, Error> + ?Sized,
 {
-    let src = src.as_ref();
+    transfer_from_mirrors(&[(src, src_url.clone())], dst, dst_url, ctx).await
+}
+
+/// Transfers data from one of alternative sources (mirrors) of the same content.
+/// When a mirror fails or stalls, the transfer fails over to the next one,
+/// resuming from the current offset. Retries start over from the first mirror
+/// after all of them have failed.
+pub async fn transfer_from_mirrors<P, S, D>(
+    sources: &[(P, TransferUrl)],
+    dst: impl AsRef<D>,
+    dst_url: &TransferUrl,
+    ctx: &TransferContext,
+) -> Result<(), Error>
+where
+    P: AsRef<S>,
+    S: TransferProvider<TransferData, Error> + ?Sized,
+    D: TransferProvider<TransferData, Error> + ?Sized,
+{
+    if sources.is_empty() {
+        return Err(Error::InvalidUrlError("No source URL".to_owned()));
+    }
+
     let dst = dst.as_ref();
+    let mut idx = 0;
 
     loop {
+        let (src, src_url) = &sources[idx];
+        let src = src.as_ref();
+
         let fut = async {
             dst.prepare_destination(&dst_url.url, ctx).await?;
             src.prepare_source(&src_url.url, ctx).await?;
@@ -75,7 +106,10 @@ where
             let stream = with_hash_stream(src.source(&src_url.url, ctx), src_url, dst_url, ctx)?;
             let sink = progress_report_channel(dst.destination(&dst_url.url, ctx), ctx);
 
-            transfer(stream, sink).await?;
+            match ctx.state.min_throughput() {
+                Some(min) => transfer(ThroughputStream::new(stream, min), sink).await?,
+                None => transfer(stream, sink).await?,
+            }
             Ok::<_, Error>(())
         };
 
@@ -83,6 +117,15 @@ where
             Ok(val) => {
                 return Ok(val);
             }
+            Err(err) if idx + 1 < sources.len() && can_retry(&err) => {
+                idx += 1;
+                let msg = format!(
+                    "Switching to mirror {} because of error: {err}",
+                    sources[idx].1.url
+                );
+                log::warn!("{}", msg);
+                ctx.progress.report_message(msg);
+            }
             Err(err) => match ctx.state.delay(&err) {
                 Some(delay) => {
                     let msg = format!("Retry in {}s because of error: {err}", delay.as_secs_f32());
@@ -90,6 +133,7 @@ where
 
                     ctx.progress.report_message(msg);
                     tokio::time::sleep(delay).await;
+                    idx = 0;
                 }
                 None => return Err(err),
             },
@@ -118,6 +162,12 @@ pub trait TransferProvider<T, E> {
         futures::future::ok(()).boxed_local()
     }
 
+    /// Resolves size of the resource at `url` without transferring it.
+    /// `None` means that the size is unknown.
+    fn source_size<'a>(&self, _url: &Url) -> LocalBoxFuture<'a, Result<Option<u64>, Error>> {
+        futures::future::ok(None).boxed_local()
+    }
+
     /// Initializes the transfer context when acting as a sink.
     /// Executed prior to `destination` and `prepare_source`
     fn prepare_destination<'a>(
@@ -378,12 +428,21 @@ impl TransferState {
             .as_mut()
             .and_then(|r| r.delay(err))
     }
+
+    pub fn min_throughput(&self) -> Option<MinThroughput> {
+        self.inner.borrow().min_throughput
+    }
+
+    pub fn set_min_throughput(&self, min_throughput: Option<MinThroughput>) {
+        self.inner.borrow_mut().min_throughput = min_throughput;
+    }
 }
 
 struct TransferStateInner {
     offset: u64,
     size: Option<u64>,
     retry: Option<Retry>,
+    min_throughput: Option<MinThroughput>,
 }
 
 impl Default for TransferStateInner {
@@ -392,6 +451,7 @@ impl Default for TransferStateInner {
             offset: Default::default(),
             size: Default::default(),
             retry: Some(Retry::default()),
+            min_throughput: None,
         }
     }
 }
@@ -24,6 +24,11 @@ pub struct TransferHash {
     pub val: Vec<u8>,
 }
 
+/// Separates alternative source URLs (mirrors) of the same content
+pub const MIRROR_SEPARATOR: char = '|';
+
+const LOCAL_SCHEMES: [&str; 2] = ["container", "file"];
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TransferUrl {
     pub hash: Option<TransferHash>,
@@ -38,22 +43,51 @@ impl TransferUrl {
         }
 
         let (hash, url) = parse_hash(url)?;
-        let parsed = match Url::parse(url) {
-            Ok(parsed_url) => match parsed_url.scheme().len() {
-                // now this is dumb... Url::parse() will accept Windows absolute path, taking drive letter for scheme!
-                #[cfg(windows)]
-                1 => Url::parse(&format!("{}:{}", fallback_scheme, url))?,
-                _ => parsed_url,
-            },
-            Err(error) => match error {
-                ParseError::RelativeUrlWithoutBase => {
-                    Url::parse(&format!("{}:{}", fallback_scheme, url))?
-                }
-                _ => return Err(Error::from(error)),
-            },
-        };
+        let url = parse_url(url, fallback_scheme)?;
+        Ok(TransferUrl { hash, url })
+    }
+
+    /// Parses a list of alternative source URLs, separated with `MIRROR_SEPARATOR`.
+    /// The hash prefix, if present, applies to all of them, e.g.
+    /// `hash:sha3:<hex>:http://mirror-1/image.gvmi|http://mirror-2/image.gvmi`
+    pub fn parse_mirrors(url: &str, fallback_scheme: &str) -> Result<Vec<Self>, Error> {
+        let url = url.trim();
+        if url.is_empty() {
+            return Err(Error::InvalidUrlError("Empty URL".to_owned()));
+        }
 
-        Ok(TransferUrl { hash, url: parsed })
+        let (hash, urls) = parse_hash(url)?;
+        urls.split(MIRROR_SEPARATOR)
+            .map(|url| match url.trim() {
+                "" => Err(Error::InvalidUrlError("Empty mirror URL".to_owned())),
+                url => Ok(TransferUrl {
+                    hash: hash.clone(),
+                    url: parse_url(url, fallback_scheme)?,
+                }),
+            })
+            .collect()
+    }
+
+    /// Parses a transfer source. Only remote sources may list mirrors, local paths
+    /// are taken as a whole, since they may contain `MIRROR_SEPARATOR`.
+    pub fn parse_source(url: &str, fallback_scheme: &str) -> Result<Vec<Self>, Error> {
+        match Self::parse(url, fallback_scheme) {
+            Ok(parsed) if LOCAL_SCHEMES.contains(&parsed.url.scheme()) => Ok(vec![parsed]),
+            _ => Self::parse_mirrors(url, fallback_scheme),
+        }
+    }
+
+    /// Whether `url` points to the ExeUnit's own filesystem, not to a remote host.
+    pub fn is_local(url: &Url) -> bool {
+        LOCAL_SCHEMES.contains(&url.scheme())
+    }
+
+    pub fn parse_mirrors_with_hash(url: &str, fallback_scheme: &str) -> Result<Vec<Self>, Error> {
+        let parsed = Self::parse_mirrors(url, fallback_scheme)?;
+        match parsed.first().and_then(|url| url.hash.as_ref()) {
+            Some(_) => Ok(parsed),
+            None => Err(Error::InvalidUrlError("Missing hash".to_owned())),
+        }
     }
 
     pub fn parse_with_hash(url: &str, fallback_scheme: &str) -> Result<Self, Error> {
@@ -73,6 +107,23 @@ impl TransferUrl {
     }
 }
 
+fn parse_url(url: &str, fallback_scheme: &str) -> Result<Url, Error> {
+    match Url::parse(url) {
+        Ok(parsed_url) => match parsed_url.scheme().len() {
+            // now this is dumb... Url::parse() will accept Windows absolute path, taking drive letter for scheme!
+            #[cfg(windows)]
+            1 => Ok(Url::parse(&format!("{}:{}", fallback_scheme, url))?),
+            _ => Ok(parsed_url),
+        },
+        Err(error) => match error {
+            ParseError::RelativeUrlWithoutBase => {
+                Ok(Url::parse(&format!("{}:{}", fallback_scheme, url))?)
+            }
+            _ => Err(Error::from(error)),
+        },
+    }
+}
+
 fn parse_hash(url: &str) -> Result<(Option<TransferHash>, &str), Error> {
     lazy_static::lazy_static! {
         static ref RE: Regex = Regex::new(r"(?i)hash:(//)?([^:]+):(0x)?([a-f0-9]+):(.+)").unwrap();
@@ -171,6 +222,43 @@ mod test {
         should_succeed!("http:location.com");
     }
 
+    #[test]
+    fn mirrors() {
+        let urls = TransferUrl::parse_mirrors_with_hash(
+            "hash:sha3:ff00:http://mirror-1.com/img | https://mirror-2.com/img",
+            "file",
+        )
+        .unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].url.as_str(), "http://mirror-1.com/img");
+        assert_eq!(urls[1].url.as_str(), "https://mirror-2.com/img");
+        assert_eq!(urls[0].hash, urls[1].hash);
+        assert!(urls[1].hash.is_some());
+
+        assert_eq!(
+            TransferUrl::parse_mirrors("http://location.com", "file")
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(TransferUrl::parse_mirrors("http://a.com/img|", "file").is_err());
+        assert!(TransferUrl::parse_mirrors_with_hash("http://a.com|http://b.com", "file").is_err());
+    }
+
+    #[test]
+    fn local_source_with_separator() {
+        let urls = TransferUrl::parse_source("/out/a|b.txt", "container").unwrap();
+        assert_eq!(urls.len(), 1);
+        assert_eq!(urls[0].url.scheme(), "container");
+        assert_eq!(urls[0].file_name().unwrap(), "a|b.txt");
+
+        let urls = TransferUrl::parse_source("file:/tmp/a|b", "container").unwrap();
+        assert_eq!(urls.len(), 1);
+
+        let urls = TransferUrl::parse_source("http://a.com/img|gftp://b/img", "container").unwrap();
+        assert_eq!(urls.len(), 2);
+    }
+
     #[test]
     #[cfg(windows)]
     fn fallback_to_file_on_windows_path() {
@@ -61,8 +61,9 @@ impl Iterator for Retry {
     }
 }
 
-fn can_retry(err: &Error) -> bool {
+pub(crate) fn can_retry(err: &Error) -> bool {
     match err {
+        Error::Stalled { .. } => true,
         Error::HttpError(e) => match e {
             HttpError::Timeout(_) | HttpError::Connect(_) | HttpError::Server(_) => true,
             HttpError::Io(e) => matches!(
@@ -0,0 +1,159 @@
+use futures::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::{interval_at, Instant, Interval, MissedTickBehavior};
+
+use crate::error::Error;
+use crate::TransferData;
+
+/// Minimum transfer throughput. A source which stays below `bytes_per_sec`
+/// for a whole `window` is considered stalled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinThroughput {
+    pub bytes_per_sec: u64,
+    pub window: Duration,
+}
+
+impl MinThroughput {
+    pub fn new(bytes_per_sec: u64, window: Duration) -> Self {
+        MinThroughput {
+            bytes_per_sec,
+            window,
+        }
+    }
+
+    fn min_bytes(&self) -> u64 {
+        (self.bytes_per_sec as f64 * self.window.as_secs_f64()) as u64
+    }
+}
+
+/// Fails the wrapped stream with `Error::Stalled` when throughput drops below the minimum
+pub(crate) struct ThroughputStream<S> {
+    inner: S,
+    min: MinThroughput,
+    interval: Interval,
+    bytes: u64,
+    /// When the last item was returned to the consumer
+    yielded_at: Option<Instant>,
+    done: bool,
+}
+
+impl<S> ThroughputStream<S> {
+    pub fn new(inner: S, min: MinThroughput) -> Self {
+        let mut interval = interval_at(Instant::now() + min.window, min.window);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ThroughputStream {
+            inner,
+            min,
+            interval,
+            bytes: 0,
+            yielded_at: None,
+            done: false,
+        }
+    }
+}
+
+impl<S> Stream for ThroughputStream<S>
+where
+    S: Stream<Item = Result<TransferData, Error>> + Unpin,
+{
+    type Item = Result<TransferData, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        // The consumer didn't ask for data for a whole window (backpressure),
+        // so the source wasn't polled and measuring starts over.
+        let now = Instant::now();
+        if let Some(yielded_at) = self.yielded_at.take() {
+            if now.duration_since(yielded_at) >= self.min.window {
+                self.interval.reset();
+                self.bytes = 0;
+            }
+        }
+
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(data))) => {
+                self.bytes += AsRef::<bytes::Bytes>::as_ref(&data).len() as u64;
+                self.yielded_at = Some(now);
+                return Poll::Ready(Some(Ok(data)));
+            }
+            Poll::Ready(item) => {
+                self.done = item.is_none();
+                return Poll::Ready(item);
+            }
+            Poll::Pending => (),
+        }
+
+        while self.interval.poll_tick(cx).is_ready() {
+            let bytes = std::mem::take(&mut self.bytes);
+            if bytes < self.min.min_bytes() {
+                self.done = true;
+                let throughput = (bytes as f64 / self.min.window.as_secs_f64()) as u64;
+                return Poll::Ready(Some(Err(Error::Stalled {
+                    throughput,
+                    min: self.min.bytes_per_sec,
+                })));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    fn chunks(n: usize) -> impl Stream<Item = Result<TransferData, Error>> + Unpin {
+        futures::stream::iter((0..n).map(|_| Ok(TransferData::from(vec![0u8; 1024]))))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pass_through() {
+        let min = MinThroughput::new(1024, Duration::from_secs(1));
+        let items = ThroughputStream::new(chunks(4), min)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items.len(), 4);
+        assert!(items.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn detect_stall() {
+        let min = MinThroughput::new(1024, Duration::from_secs(1));
+        let stream = chunks(1).chain(futures::stream::pending());
+        let items = ThroughputStream::new(stream, min).collect::<Vec<_>>().await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(
+            items[1],
+            Err(Error::Stalled {
+                throughput: 0,
+                min: 1024
+            })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignore_backpressure() {
+        let min = MinThroughput::new(1024, Duration::from_secs(1));
+        // 10 kB/s
+        let source = futures::stream::unfold((), |_| async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Some((Ok(TransferData::from(vec![0u8; 1024])), ()))
+        })
+        .boxed_local();
+        let mut stream = ThroughputStream::new(source, min);
+
+        for _ in 0..5 {
+            assert!(stream.next().await.unwrap().is_ok());
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        for _ in 0..30 {
+            assert!(stream.next().await.unwrap().is_ok());
+        }
+    }
+}
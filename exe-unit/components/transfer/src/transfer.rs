@@ -8,13 +8,14 @@ use futures::future::Abortable;
 use futures::{Sink, StreamExt, TryStreamExt};
 use url::Url;
 
-use crate::cache::{Cache, CachePath};
+use crate::cache::{Cache, CachePath, EntryLock};
 use crate::error::Error;
 use crate::error::Error as TransferError;
 pub use crate::progress::ProgressConfig;
 use crate::{
-    transfer_with, ContainerTransferProvider, FileTransferProvider, GftpTransferProvider,
-    HttpTransferProvider, Retry, TransferContext, TransferData, TransferProvider, TransferUrl,
+    transfer_from_mirrors, ContainerTransferProvider, FileTransferProvider, GftpTransferProvider,
+    HttpTransferProvider, MinThroughput, OciTransferProvider, OutboundPolicy, Retry,
+    TransferContext, TransferData, TransferProvider, TransferUrl,
 };
 
 use ya_client_model::activity::exe_script_command::ProgressArgs;
@@ -147,6 +148,26 @@ impl DeployImage {
     }
 }
 
+/// Dry run of `DeployImage`: resolves image size and checks if it fits into the cache
+/// and the work dir, without downloading it.
+#[derive(Debug, Message, Default)]
+#[rtype(result = "Result<Option<PreflightReport>>")]
+pub struct PreflightDeploy {
+    pub task_package: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PreflightReport {
+    /// Image is already cached and won't be downloaded.
+    pub cached: bool,
+    /// `None` if source didn't report the size.
+    pub source_size: Option<u64>,
+    /// Free space in the cache dir.
+    pub available_space: u64,
+    /// Free space in the work dir.
+    pub work_dir_space: u64,
+}
+
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct AbortTransfers;
@@ -159,6 +180,8 @@ pub struct Shutdown;
 pub struct TransferServiceContext {
     pub work_dir: PathBuf,
     pub cache_dir: PathBuf,
+    /// Maximum size of `cache_dir` in bytes
+    pub cache_max_size: Option<u64>,
     /// TODO: `task_package` should be passed only as `Deploy` message param.
     ///       Problem is that current ExeUnit implementation doesn't have this information
     ///       directly available when sending Deploy, so temporarily we need this ugly solution.   
@@ -166,6 +189,10 @@ pub struct TransferServiceContext {
 
     pub deploy_retry: Option<Retry>,
     pub transfer_retry: Option<Retry>,
+    /// Source mirror is abandoned when its throughput drops below this minimum
+    pub min_throughput: Option<MinThroughput>,
+    /// Outbound rules applied to hosts reached on behalf of OCI registries
+    pub outbound: Option<OutboundPolicy>,
 }
 
 /// Handles resources transfers.
@@ -177,21 +204,30 @@ pub struct TransferService {
 
     deploy_retry: Retry,
     transfer_retry: Retry,
+    min_throughput: Option<MinThroughput>,
 
     abort_handles: Rc<RefCell<HashSet<Abort>>>,
+    /// Protects the deployed image from being evicted by other ExeUnits.
+    image_lock: Option<EntryLock>,
 }
 
 impl TransferService {
     pub fn new(ctx: TransferServiceContext) -> TransferService {
-        TransferService {
+        let mut service = TransferService {
             providers: Self::default_providers(),
-            cache: Cache::new(ctx.cache_dir),
+            cache: Cache::new(ctx.cache_dir).with_max_size(ctx.cache_max_size),
             work_dir: ctx.work_dir,
             task_package: ctx.task_package,
             deploy_retry: ctx.deploy_retry.unwrap_or_default(),
             transfer_retry: ctx.transfer_retry.unwrap_or_default(),
+            min_throughput: ctx.min_throughput,
             abort_handles: Default::default(),
+            image_lock: None,
+        };
+        if let Some(policy) = ctx.outbound {
+            service.register_provider(OciTransferProvider::default().with_outbound(policy));
         }
+        service
     }
 
     pub fn schemes() -> Vec<String> {
@@ -221,6 +257,7 @@ impl TransferService {
         let provider_vec: Vec<Rc<dyn TransferProvider<TransferData, TransferError>>> = vec![
             Rc::new(GftpTransferProvider::default()),
             Rc::new(HttpTransferProvider::default()),
+            Rc::new(OciTransferProvider::default()),
         ];
         for provider in provider_vec {
             for scheme in provider.schemes() {
@@ -242,14 +279,31 @@ impl TransferService {
             .clone())
     }
 
+    #[allow(clippy::type_complexity)]
+    fn providers_for(
+        &self,
+        transfer_urls: Vec<TransferUrl>,
+    ) -> Result<
+        Vec<(
+            Rc<dyn TransferProvider<TransferData, TransferError>>,
+            TransferUrl,
+        )>,
+    > {
+        transfer_urls
+            .into_iter()
+            .map(|url| Ok((self.provider(&url)?, url)))
+            .collect()
+    }
+
     #[cfg(feature = "sgx")]
     fn deploy_sgx(
         &self,
-        src_url: TransferUrl,
+        src_urls: Vec<TransferUrl>,
         _src_name: CachePath,
         path: PathBuf,
         _ctx: TransferContext,
     ) -> ActorResponse<Self, Result<Option<PathBuf>>> {
+        let src_url = src_urls.into_iter().next().unwrap();
         let fut = async move {
             let resp = reqwest::get(src_url.url)
                 .await
@@ -267,14 +321,14 @@ impl TransferService {
     #[allow(unused)]
     fn deploy_no_sgx(
         &self,
-        src_url: TransferUrl,
+        src_urls: Vec<TransferUrl>,
         src_name: CachePath,
         path: PathBuf,
         ctx: TransferContext,
     ) -> ActorResponse<Self, Result<Option<PathBuf>>> {
         let path_tmp = self.cache.to_temp_path(&src_name).to_path_buf();
 
-        let src = actor_try!(self.provider(&src_url));
+        let sources = actor_try!(self.providers_for(src_urls));
         let dst: Rc<FileTransferProvider> = Default::default();
         let dst_url = TransferUrl {
             url: Url::from_file_path(&path_tmp).unwrap(),
@@ -294,18 +348,24 @@ impl TransferService {
             std::fs::remove_file(&path_tmp).ok();
         }
 
+        let cache = self.cache.clone();
+        let work_dir = self.work_dir.clone();
         let handles = self.abort_handles.clone();
         let fut = async move {
-            if path.exists() {
-                log::info!("Deploying cached image: {:?}", path);
+            if let Some(cached) = find_cached(&cache, &src_name, &path) {
+                log::info!("Deploying cached image: {:?}", cached);
+                let lock = cache.lock(&cached)?;
                 ctx.reporter()
                     .report_message("Deployed image from cache".to_string());
-                return Ok(Some(path));
+                return Ok((cached, lock));
             }
 
+            let (src, src_url) = &sources[0];
+            preflight(src.as_ref(), src_url, &cache, &work_dir).await?;
+
             let (abort, reg) = Abort::new_pair();
             {
-                let retry = transfer_with(src, &src_url, dst, &dst_url, &ctx);
+                let retry = transfer_from_mirrors(&sources, dst, &dst_url, &ctx);
 
                 let _guard = AbortHandleGuard::register(handles, abort);
                 Ok::<_, Error>(
@@ -322,11 +382,24 @@ impl TransferService {
             }?;
 
             move_file(&path_tmp, &path).await?;
-            log::info!("Deployment from {:?} finished", src_url.url);
+            log::info!("Deployment from {:?} finished", sources[0].1.url);
+            let lock = cache.lock(&path)?;
+
+            if let Some(max_size) = cache.max_size() {
+                match cache.evict(max_size, Some(&path)) {
+                    Ok(0) => (),
+                    Ok(freed) => log::info!("Evicted {} B from image cache", freed),
+                    Err(e) => log::warn!("Image cache eviction failed: {}", e),
+                }
+            }
 
-            Ok(Some(path))
+            Ok::<_, Error>((path, lock))
         };
-        ActorResponse::r#async(fut.into_actor(self))
+        ActorResponse::r#async(fut.into_actor(self).map(|result, act, _| {
+            let (path, lock) = result?;
+            act.image_lock = Some(lock);
+            Ok(Some(path))
+        }))
     }
 }
 
@@ -352,22 +425,60 @@ impl Handler<DeployImage> for TransferService {
             None => return ActorResponse::reply(Ok(None)),
         };
 
-        let src_url = actor_try!(TransferUrl::parse_with_hash(&image, "file"));
-        let src_name = actor_try!(Cache::name(&src_url));
+        let src_urls = actor_try!(TransferUrl::parse_mirrors_with_hash(&image, "file"));
+        let src_name = actor_try!(Cache::name(&src_urls[0]));
         let path = self.cache.to_final_path(&src_name).to_path_buf();
 
-        log::info!("Deploying from {:?} to {:?}", src_url.url, path);
+        log::info!("Deploying from {:?} to {:?}", src_urls[0].url, path);
+        if src_urls.len() > 1 {
+            log::info!("{} mirrors available", src_urls.len() - 1);
+        }
 
         let mut ctx = TransferContext::default();
         ctx.state.retry_with(self.deploy_retry.clone());
+        ctx.state.set_min_throughput(self.min_throughput);
         ctx.progress
             .register_reporter(deploy.progress_config, 1, Some("Bytes".to_string()));
 
         #[cfg(not(feature = "sgx"))]
-        return self.deploy_no_sgx(src_url, src_name, path, ctx);
+        return self.deploy_no_sgx(src_urls, src_name, path, ctx);
 
         #[cfg(feature = "sgx")]
-        return self.deploy_sgx(src_url, src_name, path, ctx);
+        return self.deploy_sgx(src_urls, src_name, path, ctx);
+    }
+}
+
+impl Handler<PreflightDeploy> for TransferService {
+    type Result = ActorResponse<Self, Result<Option<PreflightReport>>>;
+
+    fn handle(&mut self, msg: PreflightDeploy, _: &mut Self::Context) -> Self::Result {
+        let image = match msg.task_package.or(self.task_package.clone()) {
+            Some(image) => image,
+            None => return ActorResponse::reply(Ok(None)),
+        };
+
+        // Deploy may fall back to any of the mirrors, so all of them have to be usable
+        let src_urls = actor_try!(TransferUrl::parse_mirrors_with_hash(&image, "file"));
+        let (src, src_url) = actor_try!(self.providers_for(src_urls)).remove(0);
+        let src_name = actor_try!(Cache::name(&src_url));
+        let path = self.cache.to_final_path(&src_name).to_path_buf();
+        let cache = self.cache.clone();
+        let work_dir = self.work_dir.clone();
+
+        let fut = async move {
+            if find_cached(&cache, &src_name, &path).is_some() {
+                return Ok(Some(PreflightReport {
+                    cached: true,
+                    source_size: None,
+                    available_space: fs2::available_space(cache.dir())?,
+                    work_dir_space: fs2::available_space(&work_dir)?,
+                }));
+            }
+            Ok(Some(
+                preflight(src.as_ref(), &src_url, &cache, &work_dir).await?,
+            ))
+        };
+        ActorResponse::r#async(fut.into_actor(self))
     }
 }
 
@@ -375,13 +486,14 @@ impl Handler<TransferResource> for TransferService {
     type Result = ActorResponse<Self, Result<()>>;
 
     fn handle(&mut self, msg: TransferResource, _: &mut Self::Context) -> Self::Result {
-        let src_url = actor_try!(TransferUrl::parse(&msg.from, "container"));
+        let src_urls = actor_try!(TransferUrl::parse_source(&msg.from, "container"));
         let dst_url = actor_try!(TransferUrl::parse(&msg.to, "container"));
-        let src = actor_try!(self.provider(&src_url));
+        let sources = actor_try!(self.providers_for(src_urls));
         let dst = actor_try!(self.provider(&dst_url));
 
         let mut ctx = TransferContext::default();
         ctx.state.retry_with(self.transfer_retry.clone());
+        ctx.state.set_min_throughput(self.min_throughput);
         ctx.progress
             .register_reporter(msg.progress_config, 1, Some("Bytes".to_string()));
 
@@ -389,9 +501,10 @@ impl Handler<TransferResource> for TransferService {
 
         let handles = self.abort_handles.clone();
         let fut = async move {
+            let src_url = &sources[0].1;
             log::info!("Transferring {:?} to {:?}", src_url.url, dst_url.url);
             {
-                let retry = transfer_with(src, &src_url, dst, &dst_url, &ctx);
+                let retry = transfer_from_mirrors(&sources, dst, &dst_url, &ctx);
 
                 let _guard = AbortHandleGuard::register(handles, abort);
                 Abortable::new(retry, reg)
@@ -446,6 +559,63 @@ impl Handler<Shutdown> for TransferService {
     }
 }
 
+/// The same image might have been cached under a different name.
+fn find_cached(cache: &Cache, src_name: &CachePath, path: &Path) -> Option<PathBuf> {
+    Some(path.to_path_buf())
+        .filter(|path| path.exists())
+        .or_else(|| cache.find(src_name.hash()))
+}
+
+/// Fails fast when the image won't fit on disk or into the cache,
+/// instead of discovering it after downloading gigabytes.
+/// Runtimes may unpack the image into the work dir, so it needs the space as well.
+async fn preflight(
+    src: &dyn TransferProvider<TransferData, TransferError>,
+    src_url: &TransferUrl,
+    cache: &Cache,
+    work_dir: &Path,
+) -> Result<PreflightReport> {
+    let source_size = match src.source_size(&src_url.url).await {
+        Ok(size) => size,
+        Err(e) => {
+            log::warn!("Unable to resolve size of {}: {}", src_url.url, e);
+            None
+        }
+    };
+    let available_space = fs2::available_space(cache.dir())?;
+    let work_dir_space = fs2::available_space(work_dir)?;
+
+    if let Some(size) = source_size {
+        log::info!(
+            "Image size: {} B, available disk space: {} B in cache dir, {} B in work dir",
+            size,
+            available_space,
+            work_dir_space
+        );
+        for (path, available) in [(cache.dir(), available_space), (work_dir, work_dir_space)] {
+            if size > available {
+                return Err(Error::InsufficientSpace {
+                    path: path.to_path_buf(),
+                    required: size,
+                    available,
+                });
+            }
+        }
+        if let Some(capacity) = cache.max_size() {
+            if size > capacity {
+                return Err(Error::CacheCapacityExceeded { size, capacity });
+            }
+        }
+    }
+
+    Ok(PreflightReport {
+        cached: false,
+        source_size,
+        available_space,
+        work_dir_space,
+    })
+}
+
 struct AbortHandleGuard {
     inner: Rc<RefCell<HashSet<Abort>>>,
     abort: Abort,
@@ -14,7 +14,10 @@ use ya_framework_basic::file::generate_random_file_with_hash;
 use ya_framework_basic::log::enable_logs;
 use ya_framework_basic::server_external::start_http;
 use ya_framework_basic::temp_dir;
-use ya_transfer::transfer::{AbortTransfers, DeployImage, TransferService, TransferServiceContext};
+use ya_transfer::error::Error;
+use ya_transfer::transfer::{
+    AbortTransfers, DeployImage, PreflightDeploy, TransferService, TransferServiceContext,
+};
 
 /// When re-deploying image, `TransferService` should uses partially downloaded image.
 /// Hash computations should be correct in both cases.
@@ -150,3 +153,82 @@ async fn test_deploy_progress(ctx: &mut DroppableTestContext) -> anyhow::Result<
 
     Ok(())
 }
+
+/// Image larger than cache capacity should be rejected before downloading it.
+#[cfg_attr(not(feature = "system-test"), ignore)]
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_deploy_preflight(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("deploy-preflight")?;
+    let temp_dir = dir.path();
+
+    let work_dir = temp_dir.join("work_dir");
+    let cache_dir = temp_dir.join("cache_dir");
+    for dir in [work_dir.clone(), cache_dir.clone()] {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let chunk_size = 4096_usize;
+    let chunk_count = 16;
+    let file_size = (chunk_size * chunk_count) as u64;
+    let hash = generate_random_file_with_hash(temp_dir, "rnd", chunk_size, chunk_count);
+
+    start_http(ctx, temp_dir.to_path_buf())
+        .await
+        .expect("unable to start http servers");
+
+    let task_package = format!(
+        "hash://sha3:{}:http://127.0.0.1:8001/rnd",
+        hex::encode(hash)
+    );
+
+    let exe_ctx = TransferServiceContext {
+        work_dir: work_dir.clone(),
+        cache_dir: cache_dir.clone(),
+        ..TransferServiceContext::default()
+    };
+    let addr = TransferService::new(exe_ctx).start();
+    let report = addr
+        .send(PreflightDeploy {
+            task_package: Some(task_package.clone()),
+        })
+        .await??
+        .expect("preflight report");
+    assert!(!report.cached);
+    assert_eq!(report.source_size, Some(file_size));
+    assert!(report.available_space >= file_size);
+    assert!(report.work_dir_space >= file_size);
+
+    // All mirrors have to be supported, not only the first one
+    let result = addr
+        .send(PreflightDeploy {
+            task_package: Some(format!("{task_package}|unknown://host/image")),
+        })
+        .await?;
+    assert!(result.is_err());
+
+    let exe_ctx = TransferServiceContext {
+        work_dir,
+        cache_dir: cache_dir.clone(),
+        cache_max_size: Some(file_size - 1),
+        ..TransferServiceContext::default()
+    };
+    let addr = TransferService::new(exe_ctx).start();
+
+    let preflight = PreflightDeploy {
+        task_package: Some(task_package.clone()),
+    };
+    let result = addr.send(preflight).await?;
+    assert!(matches!(
+        result,
+        Err(Error::CacheCapacityExceeded { size, .. }) if size == file_size
+    ));
+
+    let result = addr.send(DeployImage::with_package(&task_package)).await?;
+    assert!(matches!(result, Err(Error::CacheCapacityExceeded { .. })));
+    assert_eq!(std::fs::read_dir(cache_dir.join("tmp"))?.count(), 0);
+
+    Ok(())
+}
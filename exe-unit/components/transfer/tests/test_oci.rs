@@ -0,0 +1,321 @@
+use actix_web::web::{Data, Path};
+use actix_web::{web, App, HttpResponse, HttpServer};
+use bytes::Bytes;
+use futures::{stream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use test_context::test_context;
+use url::Url;
+
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::log::enable_logs;
+use ya_transfer::error::Error;
+use ya_transfer::{OciTransferProvider, TransferContext, TransferProvider};
+
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const GVMI_MEDIA_TYPE: &str = "application/vnd.golem.gvmi";
+
+/// Serves manifests and blobs of a single repository
+#[derive(Default)]
+struct MockRegistry {
+    /// Manifests by tag and by digest
+    manifests: Mutex<HashMap<String, Bytes>>,
+    blobs: Mutex<HashMap<String, Bytes>>,
+    manifest_requests: AtomicUsize,
+    /// Blobs are redirected to this storage when set
+    storage: Mutex<Option<String>>,
+}
+
+impl MockRegistry {
+    fn push_blob(&self, data: impl Into<Bytes>) -> String {
+        let data = data.into();
+        let digest = digest(&data);
+        self.blobs.lock().unwrap().insert(digest.clone(), data);
+        digest
+    }
+
+    /// Tags a manifest of a single layer, returns the manifest digest
+    fn tag(&self, tag: &str, media_type: &str, layer_digest: &str, layer_size: usize) -> String {
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": MANIFEST_MEDIA_TYPE,
+            "layers": [{ "mediaType": media_type, "digest": layer_digest, "size": layer_size }]
+        });
+        let manifest = Bytes::from(serde_json::to_vec(&manifest).unwrap());
+        let digest = digest(&manifest);
+
+        let mut manifests = self.manifests.lock().unwrap();
+        manifests.insert(tag.to_string(), manifest.clone());
+        manifests.insert(digest.clone(), manifest);
+        digest
+    }
+
+    fn push(&self, tag: &str, media_type: &str, data: impl Into<Bytes>) -> String {
+        let data = data.into();
+        let size = data.len();
+        let layer_digest = self.push_blob(data);
+        self.tag(tag, media_type, &layer_digest, size)
+    }
+
+    fn manifest_requests(&self) -> usize {
+        self.manifest_requests.load(Ordering::SeqCst)
+    }
+}
+
+fn digest(data: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(data)))
+}
+
+async fn manifest(registry: Data<MockRegistry>, path: Path<(String, String)>) -> HttpResponse {
+    registry.manifest_requests.fetch_add(1, Ordering::SeqCst);
+    match registry.manifests.lock().unwrap().get(&path.1) {
+        Some(manifest) => HttpResponse::Ok()
+            .content_type(MANIFEST_MEDIA_TYPE)
+            .body(manifest.clone()),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+async fn blob(registry: Data<MockRegistry>, path: Path<(String, String)>) -> HttpResponse {
+    if let Some(storage) = registry.storage.lock().unwrap().as_ref() {
+        return HttpResponse::TemporaryRedirect()
+            .insert_header(("Location", format!("{storage}/storage/{}", path.1)))
+            .finish();
+    }
+    stored_blob(registry, &path.1)
+}
+
+async fn storage(registry: Data<MockRegistry>, path: Path<String>) -> HttpResponse {
+    stored_blob(registry, &path)
+}
+
+fn stored_blob(registry: Data<MockRegistry>, digest: &str) -> HttpResponse {
+    match registry.blobs.lock().unwrap().get(digest) {
+        Some(blob) => HttpResponse::Ok().body(blob.clone()),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+/// Starts the registry on a random port, returns the base `oci://` URL of the repository
+async fn start_registry(
+    ctx: &mut DroppableTestContext,
+    registry: Arc<MockRegistry>,
+) -> anyhow::Result<String> {
+    let data = Data::from(registry);
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route(
+                "/v2/{repository}/manifests/{reference}",
+                web::get().to(manifest),
+            )
+            .route("/v2/{repository}/blobs/{digest}", web::get().to(blob))
+            .route("/storage/{digest}", web::get().to(storage))
+    })
+    .bind("127.0.0.1:0")?;
+    let port = server.addrs()[0].port();
+    let srv = server.run();
+
+    ctx.register(srv.handle());
+    tokio::task::spawn_local(async move { anyhow::Ok(srv.await?) });
+
+    Ok(format!("oci://127.0.0.1:{port}/image"))
+}
+
+/// Runs the same steps as a deployment: size check, preparation and download
+async fn download(provider: &OciTransferProvider, url: &str) -> Result<Vec<u8>, Error> {
+    let url = Url::parse(url)?;
+    let ctx = TransferContext::new(0);
+
+    provider.source_size(&url).await?;
+    provider.prepare_source(&url, &ctx).await?;
+
+    let mut stream = provider.source(&url, &ctx);
+    let mut data = vec![];
+    while let Some(chunk) = stream.next().await {
+        data.extend_from_slice(chunk?.as_ref());
+    }
+    Ok(data)
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_resolves_tag_once(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+    let v1 = registry.push("1.0", GVMI_MEDIA_TYPE, b"image v1".to_vec());
+    let url = Url::parse(&format!("{repository}:1.0"))?;
+    let provider = OciTransferProvider::default().insecure();
+    let transfer_ctx = TransferContext::new(0);
+
+    provider.source_size(&url).await?;
+    provider.prepare_source(&url, &transfer_ctx).await?;
+    // The tag is moved after the transfer has been prepared
+    registry.push("1.0", GVMI_MEDIA_TYPE, b"image v2".to_vec());
+
+    let data: Vec<Bytes> = provider
+        .source(&url, &transfer_ctx)
+        .map_ok(|chunk| Bytes::copy_from_slice(chunk.as_ref()))
+        .try_collect()
+        .await?;
+    assert_eq!(data.concat(), b"image v1");
+    assert_eq!(registry.manifest_requests(), 1);
+
+    // Finished transfers resolve the tag again
+    assert_eq!(download(&provider, url.as_str()).await?, b"image v2");
+    assert_eq!(registry.manifest_requests(), 2);
+
+    // Digest references are verified against the manifest
+    assert_eq!(
+        download(&provider, &format!("{repository}@{v1}")).await?,
+        b"image v1"
+    );
+    Ok(())
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_verifies_layer_digest(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+
+    // Registry serves other content than the manifest refers to
+    let expected = digest(b"original image");
+    registry
+        .blobs
+        .lock()
+        .unwrap()
+        .insert(expected.clone(), Bytes::from_static(b"tampered image"));
+    registry.tag("latest", GVMI_MEDIA_TYPE, &expected, 14);
+
+    let provider = OciTransferProvider::default().insecure();
+    match download(&provider, &repository).await {
+        Err(Error::InvalidHashError { .. }) => {}
+        result => panic!("Expected digest verification error, got: {result:?}"),
+    }
+    Ok(())
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_decompresses_gzip_layer(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+
+    let image = b"compressed image".repeat(1024);
+    let input = stream::iter([std::io::Result::Ok(Bytes::from(image.clone()))]);
+    let compressed: Vec<Bytes> = async_compression::stream::GzipEncoder::new(input)
+        .try_collect()
+        .await?;
+    registry.push(
+        "latest",
+        "application/vnd.golem.gvmi+gzip",
+        compressed.concat(),
+    );
+
+    let provider = OciTransferProvider::default().insecure();
+    assert_eq!(download(&provider, &repository).await?, image);
+    Ok(())
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_rejects_unsupported_layers(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+    registry.push(
+        "container",
+        "application/vnd.oci.image.layer.v1.tar+gzip",
+        b"container layer".to_vec(),
+    );
+
+    let provider = OciTransferProvider::default().insecure();
+    let result = download(&provider, &format!("{repository}:container")).await;
+    assert!(
+        result
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("Unsupported layer media type"),
+        "Unexpected result: {result:?}"
+    );
+    Ok(())
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_rejects_gzip_bomb(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+
+    let input = stream::iter([std::io::Result::Ok(Bytes::from(vec![
+        0u8;
+        16 * 1024 * 1024
+    ]))]);
+    let compressed: Vec<Bytes> = async_compression::stream::GzipEncoder::new(input)
+        .try_collect()
+        .await?;
+    registry.push(
+        "latest",
+        "application/vnd.golem.gvmi+gzip",
+        compressed.concat(),
+    );
+
+    let provider = OciTransferProvider::default().insecure();
+    let result = download(&provider, &repository).await;
+    assert!(
+        result
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("decompresses to more than"),
+        "Unexpected result: {result:?}"
+    );
+    Ok(())
+}
+
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_oci_checks_blob_location(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let registry = Arc::new(MockRegistry::default());
+    let repository = start_registry(ctx, registry.clone()).await?;
+    registry.push("latest", GVMI_MEDIA_TYPE, b"image".to_vec());
+
+    // Storage is the same server reached by another host name
+    let port = Url::parse(&repository)?.port().unwrap();
+    *registry.storage.lock().unwrap() = Some(format!("http://localhost:{port}"));
+
+    let provider = OciTransferProvider::default()
+        .insecure()
+        .with_outbound(Rc::new(|url: &Url| url.host_str() != Some("localhost")));
+    let result = download(&provider, &repository).await;
+    assert!(
+        result
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("not allowed by the outbound rules"),
+        "Unexpected result: {result:?}"
+    );
+
+    let provider = OciTransferProvider::default()
+        .insecure()
+        .with_outbound(Rc::new(|_: &Url| true));
+    assert_eq!(download(&provider, &repository).await?, b"image");
+    Ok(())
+}
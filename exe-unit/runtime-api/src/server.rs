@@ -11,14 +11,28 @@ pub mod proto {
             e
         }
     }
+
+    impl Request {
+        /// Request with process environment values hidden, safe to log
+        pub(crate) fn redacted(&self) -> Self {
+            let mut request = self.clone();
+            if let Some(request::Command::Run(run)) = request.command.as_mut() {
+                run.env
+                    .values_mut()
+                    .for_each(|v| *v = "<hidden>".to_string());
+            }
+            request
+        }
+    }
 }
 mod codec;
 
 #[cfg(feature = "codec")]
 pub use codec::Codec;
-pub use proto::request::{CreateNetwork, KillProcess, RunProcess};
+pub use proto::request::{CreateNetwork, KillProcess, RunProcess, WriteStdin};
 pub use proto::response::create_network::Endpoint as NetworkEndpoint;
 pub use proto::response::runtime_status::Counter as RuntimeCounter;
+pub use proto::response::runtime_status::CounterDefinition as RuntimeCounterDefinition;
 pub use proto::response::runtime_status::Kind as RuntimeStatusKind;
 pub use proto::response::runtime_status::State as RuntimeState;
 pub use proto::response::CreateNetwork as CreateNetworkResp;
@@ -43,6 +57,12 @@ pub trait RuntimeService {
     fn run_process(&self, run: RunProcess) -> AsyncResponse<'_, RunProcessResp>;
     /// Kill a spawned process
     fn kill_process(&self, kill: KillProcess) -> AsyncResponse<'_, ()>;
+    /// Write to stdin of a process spawned with `stdin` enabled
+    fn write_stdin(&self, _write: WriteStdin) -> AsyncResponse<'_, ()> {
+        let mut error = ErrorResponse::msg("writing to process stdin is not supported");
+        error.set_code(ErrorCode::BadRequest);
+        future::err(error).boxed_local()
+    }
     /// Setup a virtual private network
     fn create_network(&self, network: CreateNetwork) -> AsyncResponse<'_, CreateNetworkResp>;
     /// Perform service shutdown
@@ -54,7 +54,7 @@ where
             let id = inner.ids;
             param.id = id;
             let _ = inner.response_callbacks.insert(id, tx);
-            log::debug!("sending request: {:?}", param);
+            log::debug!("sending request: {:?}", param.redacted());
             if let Err(e) = SinkExt::send(&mut inner.output, param).await {
                 log::error!("Runtime client write error: {:?}", e);
             }
@@ -152,6 +152,23 @@ where
         .boxed_local()
     }
 
+    fn write_stdin(&self, write: WriteStdin) -> AsyncResponse<()> {
+        let id = REQUEST_ID.fetch_add(1, Relaxed);
+        let request = proto::Request {
+            id,
+            command: Some(proto::request::Command::Stdin(write)),
+        };
+        let fut = self.call(request);
+        async move {
+            match fut.await.command {
+                Some(proto::response::Command::Stdin(_stdin)) => Ok(()),
+                Some(proto::response::Command::Error(error)) => Err(error),
+                _ => panic!("invalid response"),
+            }
+        }
+        .boxed_local()
+    }
+
     fn create_network(&self, network: CreateNetwork) -> AsyncResponse<CreateNetworkResp> {
         let id = REQUEST_ID.fetch_add(1, Relaxed);
         let request = proto::Request {
@@ -24,6 +24,10 @@ async fn handle_command(
             service.kill_process(kill).await?;
             proto::response::Command::Kill(Default::default())
         }
+        proto::request::Command::Stdin(write) => {
+            service.write_stdin(write).await?;
+            proto::response::Command::Stdin(Default::default())
+        }
         proto::request::Command::Network(network) => {
             proto::response::Command::Network(service.create_network(network).await?)
         }
@@ -118,7 +122,7 @@ where
                     Ok(request) => {
                         let service = service.clone();
                         let output = output.clone();
-                        log::trace!("received request: {:?}", request);
+                        log::trace!("received request: {:?}", request.redacted());
                         let resp = handle(service.as_ref(), request).await;
                         log::trace!("response to send: {:?}", resp);
                         let mut output = output.lock().await;
@@ -1,14 +1,32 @@
+use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::rc::Rc;
 
-pub type Acl = AccessControl<String>;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+use ya_agreement_utils::agreement::{AgreementView, Error as AgreementError};
+use ya_client_model::activity::ExeScriptCommand;
+use ya_client_model::NodeId;
+use ya_core_model::activity::local::ReportViolation;
+
+/// Signed Demand property granting additional roles to other nodes,
+/// e.g. `{"0x...": ["observe"]}`
+pub const ACL_PROPERTY: &str = "/demand/properties/golem/srv/comp/acl";
+/// Signed Demand property listing GSB address prefixes the activity may call,
+/// e.g. `["/net/0x..."]`. Outbound calls are not restricted when not set
+pub const ACL_OUTBOUND_PROPERTY: &str = "/demand/properties/golem/srv/comp/acl-outbound";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AccessRole {
+    /// Execute commands and manage the activity
     Control,
+    /// Exchange VPN traffic with the activity
     Host,
+    /// Query state, usage and command results
     Observe,
 }
 
@@ -16,6 +34,16 @@ pub enum AccessRole {
 pub enum Error {
     #[error("Forbidden call from {0}: role '{1:?}' not granted")]
     Forbidden(String, AccessRole),
+    #[error("Forbidden call to {0}: address not allowed")]
+    OutboundForbidden(String),
+    #[error("Invalid access control list in agreement: {0}")]
+    Agreement(String),
+}
+
+impl From<AgreementError> for Error {
+    fn from(e: AgreementError) -> Self {
+        Error::Agreement(e.to_string())
+    }
 }
 
 #[derive(Clone, Default)]
@@ -23,29 +51,35 @@ pub struct AccessControl<K: Hash + Eq> {
     inner: Rc<RefCell<HashMap<K, HashSet<AccessRole>>>>,
 }
 
-impl<K: Hash + Eq + ToOwned<Owned = K>> AccessControl<K> {
-    pub fn grant<T: AsRef<K>>(&self, id: T, role: AccessRole) {
+impl<K: Hash + Eq> AccessControl<K> {
+    pub fn grant(&self, id: impl Into<K>, role: AccessRole) {
         self.inner
             .borrow_mut()
-            .entry(id.as_ref().to_owned())
+            .entry(id.into())
             .or_default()
             .insert(role);
     }
-}
 
-impl<K: Hash + Eq> AccessControl<K> {
-    pub fn has_access<T: AsRef<K>>(&self, id: T, role: AccessRole) -> bool {
+    pub fn has_access<Q>(&self, id: &Q, role: AccessRole) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         self.inner
             .borrow()
-            .get(id.as_ref())
+            .get(id)
             .map(|e| e.contains(&role))
             .unwrap_or(false)
     }
 
-    pub fn revoke<T: AsRef<K>>(&self, id: T, role: AccessRole) -> bool {
+    pub fn revoke<Q>(&self, id: &Q, role: AccessRole) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         self.inner
             .borrow_mut()
-            .get_mut(id.as_ref())
+            .get_mut(id)
             .map(|e| e.remove(&role))
             .unwrap_or(false)
     }
@@ -57,3 +91,271 @@ impl<K: Hash + Eq + std::fmt::Debug> std::fmt::Debug for AccessControl<K> {
         write!(f, "{:?}", *inner)
     }
 }
+
+/// Roles granted to remote nodes calling the activity's GSB endpoints,
+/// and GSB addresses the ExeUnit may call on behalf of the activity.
+///
+/// Calls made over the local bus (i.e. by the Provider's own services) are not
+/// subject to access control, since only remote calls carry a caller's node id.
+#[derive(Clone, Debug, Default)]
+pub struct Acl {
+    grants: AccessControl<NodeId>,
+    outbound: Option<Rc<Vec<String>>>,
+    reporter: Option<Rc<Reporter>>,
+}
+
+#[derive(Debug)]
+struct Reporter {
+    report_url: String,
+    activity_id: String,
+}
+
+impl Acl {
+    /// Grants the Requestor and the Provider full access to the activity.
+    /// Additional grants are read from the signed [`ACL_PROPERTY`] Demand property,
+    /// outbound calls are restricted by the [`ACL_OUTBOUND_PROPERTY`].
+    pub fn from_agreement(agreement: &AgreementView) -> Result<Self, Error> {
+        let mut acl = Acl::default();
+        for id in [agreement.requestor_id()?, agreement.provider_id()?] {
+            acl.grant(id, AccessRole::Control);
+            acl.grant(id, AccessRole::Observe);
+        }
+
+        let grants = match agreement.pointer_typed::<HashMap<String, Vec<AccessRole>>>(ACL_PROPERTY)
+        {
+            Ok(grants) => grants,
+            Err(AgreementError::NoKey(_)) => Default::default(),
+            Err(e) => return Err(e.into()),
+        };
+        for (id, roles) in grants {
+            let id = id
+                .parse::<NodeId>()
+                .map_err(|e| Error::Agreement(format!("invalid node id '{id}': {e}")))?;
+            roles.into_iter().for_each(|role| acl.grant(id, role));
+        }
+
+        acl.outbound = match agreement.pointer_typed::<Vec<String>>(ACL_OUTBOUND_PROPERTY) {
+            Ok(prefixes) => Some(Rc::new(prefixes)),
+            Err(AgreementError::NoKey(_)) => None,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(acl)
+    }
+
+    /// Reports violations to the activity service on `report_url`
+    pub fn with_reporter(
+        mut self,
+        report_url: Option<String>,
+        activity_id: Option<String>,
+    ) -> Self {
+        self.reporter = match (report_url, activity_id) {
+            (Some(report_url), Some(activity_id)) => Some(Rc::new(Reporter {
+                report_url,
+                activity_id,
+            })),
+            _ => None,
+        };
+        self
+    }
+
+    pub fn grant(&self, id: NodeId, role: AccessRole) {
+        self.grants.grant(id, role);
+    }
+
+    pub fn revoke(&self, id: &NodeId, role: AccessRole) -> bool {
+        self.grants.revoke(id, role)
+    }
+
+    pub fn has_access(&self, caller: &str, role: AccessRole) -> bool {
+        match caller.parse::<NodeId>() {
+            Ok(id) => self.grants.has_access(&id, role),
+            Err(_) => true,
+        }
+    }
+
+    /// Checks whether the activity may call GSB `address`. Addresses match allowed
+    /// prefixes on segment boundaries, i.e. `/net/0x1` doesn't allow `/net/0x12`.
+    pub fn may_call(&self, address: &str) -> bool {
+        let prefixes = match self.outbound.as_ref() {
+            Some(prefixes) => prefixes,
+            None => return true,
+        };
+        prefixes.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            match address.strip_prefix(prefix) {
+                Some(rest) => rest.is_empty() || rest.starts_with('/'),
+                None => false,
+            }
+        })
+    }
+
+    /// Verifies that `caller` was granted `role` before calling `endpoint`.
+    /// Violations are logged and reported to the activity service.
+    pub fn check(&self, caller: &str, role: AccessRole, endpoint: &str) -> Result<(), Error> {
+        if self.has_access(caller, role) {
+            return Ok(());
+        }
+
+        let error = Error::Forbidden(caller.to_string(), role);
+        log::warn!("ACL violation calling {endpoint}: {error}");
+        self.report(caller, endpoint, &error, false);
+        Err(error)
+    }
+
+    /// Verifies that the activity may call GSB `address`.
+    /// Violations are logged and reported to the activity service.
+    pub fn check_outbound(&self, address: &str) -> Result<(), Error> {
+        if self.may_call(address) {
+            return Ok(());
+        }
+
+        let error = Error::OutboundForbidden(address.to_string());
+        log::warn!("ACL violation: {error}");
+        self.report("", address, &error, true);
+        Err(error)
+    }
+
+    /// Verifies GSB addresses of remote nodes used by `transfer` commands.
+    pub fn check_transfers<'a>(
+        &self,
+        mut commands: impl Iterator<Item = &'a ExeScriptCommand>,
+    ) -> Result<(), Error> {
+        commands.try_for_each(|command| match command {
+            ExeScriptCommand::Transfer { from, to, .. } => [from, to]
+                .into_iter()
+                .filter_map(|url| transfer_address(url))
+                .try_for_each(|address| self.check_outbound(&address)),
+            _ => Ok(()),
+        })
+    }
+
+    fn report(&self, caller: &str, endpoint: &str, error: &Error, outbound: bool) {
+        if let Some(reporter) = self.reporter.clone() {
+            let msg = ReportViolation {
+                activity_id: reporter.activity_id.clone(),
+                caller: caller.to_string(),
+                endpoint: endpoint.to_string(),
+                reason: error.to_string(),
+                outbound,
+                timestamp: Utc::now(),
+            };
+            tokio::task::spawn_local(async move {
+                crate::report(&reporter.report_url, msg).await;
+            });
+        }
+    }
+}
+
+/// GSB address of the remote node serving a GFTP transfer url
+fn transfer_address(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    match url.scheme() {
+        "gftp" => url.host_str().map(|node| format!("/net/{node}/gftp")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::convert::TryFrom;
+
+    const REQUESTOR: &str = "0x1111111111111111111111111111111111111111";
+    const PROVIDER: &str = "0x2222222222222222222222222222222222222222";
+    const OBSERVER: &str = "0x3333333333333333333333333333333333333333";
+    const STRANGER: &str = "0x4444444444444444444444444444444444444444";
+
+    fn agreement(acl: Option<serde_json::Value>) -> AgreementView {
+        let mut comp = json!({});
+        if let Some(acl) = acl {
+            comp["acl"] = acl;
+        }
+        agreement_with(comp)
+    }
+
+    fn agreement_with(comp: serde_json::Value) -> AgreementView {
+        let demand = json!({ "golem": { "srv": { "comp": comp } } });
+        AgreementView::try_from(json!({
+            "agreementId": "agreement",
+            "demand": { "requestorId": REQUESTOR, "properties": demand, "constraints": "" },
+            "offer": { "providerId": PROVIDER, "properties": {}, "constraints": "" },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn agreement_parties_have_access() {
+        let acl = Acl::from_agreement(&agreement(None)).unwrap();
+
+        for id in [REQUESTOR, PROVIDER] {
+            assert!(acl.check(id, AccessRole::Control, "Exec").is_ok());
+            assert!(acl.check(id, AccessRole::Observe, "GetState").is_ok());
+            assert!(acl.check(id, AccessRole::Host, "VpnPacket").is_err());
+        }
+        assert!(matches!(
+            acl.check(STRANGER, AccessRole::Observe, "GetState"),
+            Err(Error::Forbidden(_, AccessRole::Observe))
+        ));
+    }
+
+    #[test]
+    fn demand_grants() {
+        let acl = Acl::from_agreement(&agreement(Some(json!({ OBSERVER: ["observe"] })))).unwrap();
+
+        assert!(acl.has_access(OBSERVER, AccessRole::Observe));
+        assert!(!acl.has_access(OBSERVER, AccessRole::Control));
+
+        let id = OBSERVER.parse().unwrap();
+        assert!(acl.revoke(&id, AccessRole::Observe));
+        assert!(!acl.has_access(OBSERVER, AccessRole::Observe));
+
+        assert!(Acl::from_agreement(&agreement(Some(json!({ "node": ["observe"] })))).is_err());
+        assert!(Acl::from_agreement(&agreement(Some(json!({ OBSERVER: ["admin"] })))).is_err());
+    }
+
+    #[test]
+    fn outbound_calls() {
+        let acl = Acl::from_agreement(&agreement(None)).unwrap();
+        assert!(acl
+            .check_outbound(&format!("/net/{STRANGER}/vpn/net"))
+            .is_ok());
+
+        let comp = json!({ "acl-outbound": [format!("/net/{OBSERVER}/")] });
+        let acl = Acl::from_agreement(&agreement_with(comp)).unwrap();
+
+        assert!(acl.check_outbound(&format!("/net/{OBSERVER}")).is_ok());
+        assert!(acl
+            .check_outbound(&format!("/net/{OBSERVER}/vpn/net"))
+            .is_ok());
+        assert!(matches!(
+            acl.check_outbound(&format!("/net/{OBSERVER}1/vpn/net")),
+            Err(Error::OutboundForbidden(_))
+        ));
+        assert!(acl
+            .check_outbound(&format!("/net/{STRANGER}/vpn/net"))
+            .is_err());
+
+        let transfer = |to: String| ExeScriptCommand::Transfer {
+            from: "container:/output/file".to_string(),
+            to,
+            args: Default::default(),
+            progress: None,
+        };
+        let allowed = transfer(format!("gftp://{OBSERVER}/hash"));
+        let denied = transfer(format!("gftp://{STRANGER}/hash"));
+        let http = transfer("http://example.com/file".to_string());
+        assert!(acl.check_transfers([&allowed, &http].into_iter()).is_ok());
+        assert!(acl
+            .check_transfers([&allowed, &denied].into_iter())
+            .is_err());
+    }
+
+    #[test]
+    fn local_calls_are_trusted() {
+        let acl = Acl::default();
+        assert!(acl.check("local", AccessRole::Control, "Exec").is_ok());
+        assert!(acl.check("", AccessRole::Observe, "GetUsage").is_ok());
+    }
+}
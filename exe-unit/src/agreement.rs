@@ -20,6 +20,17 @@ impl Agreement {
     pub fn pointer(&self, pointer: &str) -> Option<&Value> {
         self.inner.pointer(pointer)
     }
+
+    /// Counters from negotiated usage vector, which are not known to be reported.
+    /// They may still be registered by the runtime after it starts;
+    /// otherwise zeroed counters will be sent to the Requestor.
+    pub fn unsupported_counters(&self, supported: &[String]) -> Vec<String> {
+        self.usage_vector
+            .iter()
+            .filter(|counter| !supported.contains(counter))
+            .cloned()
+            .collect()
+    }
 }
 
 impl TryFrom<Value> for Agreement {
@@ -28,11 +39,13 @@ impl TryFrom<Value> for Agreement {
     fn try_from(value: Value) -> Result<Self, Self::Error> {
         let agreement = AgreementView::try_from(value)?;
         let task_package = agreement
-            .pointer_typed::<String>("/demand/properties/golem/srv/comp/task_package")
-            .ok();
-        let usage_vector =
-            agreement.pointer_typed::<Vec<String>>("/offer/properties/golem/com/usage/vector")?;
-        let infra = agreement.properties::<f64>("/offer/properties/golem/inf")?;
+            .demand_properties()
+            .and_then(|demand| demand.task_package())
+            .ok()
+            .flatten();
+        let offer = agreement.offer_properties()?;
+        let usage_vector = offer.usage_vector()?;
+        let infra = offer.infrastructure()?;
 
         let limits = vec![
             (MemCounter::ID, MemCounter::INF),
@@ -71,4 +84,19 @@ mod tests {
         path.push("examples/agreement.json");
         Agreement::try_from(&path).unwrap();
     }
+
+    #[test]
+    fn usage_vector_validation() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("examples/agreement.json");
+        let agreement = Agreement::try_from(&path).unwrap();
+
+        assert!(agreement
+            .unsupported_counters(&agreement.usage_vector)
+            .is_empty());
+        assert_eq!(
+            agreement.unsupported_counters(&agreement.usage_vector[1..]),
+            agreement.usage_vector[..1]
+        );
+    }
 }
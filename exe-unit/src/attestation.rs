@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
+use std::env;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use ya_core_model::activity::local::Credentials;
+
+use crate::crypto::Crypto;
+use crate::error::Error;
+
+/// Comma-separated list of hex-encoded MRENCLAVE measurements
+pub const ALLOWED_ENCLAVES_ENV_VAR: &str = "SGX_ALLOWED_ENCLAVES";
+/// Comma-separated list of accepted IAS quote statuses (TCB levels)
+pub const ALLOWED_QUOTE_STATUSES_ENV_VAR: &str = "SGX_ALLOWED_QUOTE_STATUSES";
+/// Maximum age of the IAS report, e.g. `12h`
+pub const MAX_REPORT_AGE_ENV_VAR: &str = "SGX_MAX_REPORT_AGE";
+
+const DEFAULT_QUOTE_STATUS: &str = "OK";
+const IAS_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+/// Requirements an attestation report needs to meet
+#[derive(Clone, Debug)]
+pub struct AttestationPolicy {
+    /// Accepted enclave measurements. Any enclave is accepted when empty
+    pub allowed_enclaves: HashSet<[u8; 32]>,
+    /// Accepted TCB levels, as reported in `isvEnclaveQuoteStatus`
+    pub allowed_quote_statuses: HashSet<String>,
+    /// Older reports are refreshed before being handed out
+    pub max_report_age: Option<Duration>,
+}
+
+impl Default for AttestationPolicy {
+    fn default() -> Self {
+        AttestationPolicy {
+            allowed_enclaves: Default::default(),
+            allowed_quote_statuses: [DEFAULT_QUOTE_STATUS.to_string()].into(),
+            max_report_age: None,
+        }
+    }
+}
+
+impl AttestationPolicy {
+    pub fn from_env() -> Result<Self, Error> {
+        let mut policy = Self::default();
+        if let Ok(value) = env::var(ALLOWED_ENCLAVES_ENV_VAR) {
+            policy.allowed_enclaves = list(&value)
+                .map(parse_measurement)
+                .collect::<Result<_, _>>()?;
+        }
+        if let Ok(value) = env::var(ALLOWED_QUOTE_STATUSES_ENV_VAR) {
+            policy.allowed_quote_statuses = list(&value).map(str::to_uppercase).collect();
+        }
+        if let Ok(value) = env::var(MAX_REPORT_AGE_ENV_VAR) {
+            let age = humantime::parse_duration(&value).map_err(|e| {
+                Error::Attestation(format!("Invalid {MAX_REPORT_AGE_ENV_VAR} value: {e}"))
+            })?;
+            policy.max_report_age = Some(age);
+        }
+        Ok(policy)
+    }
+
+    pub fn verify(&self, evidence: &Evidence, now: DateTime<Utc>) -> Result<(), Error> {
+        if !self.allowed_enclaves.is_empty()
+            && !self.allowed_enclaves.contains(&evidence.enclave_hash)
+        {
+            return Err(Error::Attestation(format!(
+                "Enclave measurement {} is not allowed",
+                hex::encode(evidence.enclave_hash)
+            )));
+        }
+        if !self
+            .allowed_quote_statuses
+            .contains(&evidence.quote_status.to_uppercase())
+        {
+            return Err(Error::Attestation(format!(
+                "Enclave quote status {} is not allowed",
+                evidence.quote_status
+            )));
+        }
+        if self.expired(evidence, now) {
+            return Err(Error::Attestation(format!(
+                "Attestation report issued at {} is too old",
+                evidence.issued_at
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn expired(&self, evidence: &Evidence, now: DateTime<Utc>) -> bool {
+        match self
+            .max_report_age
+            .and_then(|age| chrono::Duration::from_std(age).ok())
+        {
+            Some(age) => evidence.issued_at + age < now,
+            None => false,
+        }
+    }
+}
+
+/// Attestation evidence of the enclave, verified by IAS
+#[derive(Clone, Debug)]
+pub struct Evidence {
+    pub credentials: Credentials,
+    pub enclave_hash: [u8; 32],
+    pub quote_status: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+impl TryFrom<Credentials> for Evidence {
+    type Error = Error;
+
+    fn try_from(credentials: Credentials) -> Result<Self, Self::Error> {
+        let (enclave_hash, report) = match &credentials {
+            Credentials::Sgx {
+                enclave_hash,
+                ias_report,
+                ..
+            } => (
+                *enclave_hash,
+                serde_json::from_str::<IasReport>(ias_report)?,
+            ),
+        };
+        let issued_at = NaiveDateTime::parse_from_str(&report.timestamp, IAS_TIMESTAMP_FORMAT)
+            .map_err(|e| Error::Attestation(format!("Invalid IAS report timestamp: {e}")))?
+            .and_utc();
+
+        Ok(Evidence {
+            credentials,
+            enclave_hash,
+            quote_status: report.isv_enclave_quote_status,
+            issued_at,
+        })
+    }
+}
+
+/// Fields of the IAS attestation verification report
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IasReport {
+    /// UTC, without a time zone designator
+    timestamp: String,
+    isv_enclave_quote_status: String,
+}
+
+/// Last verified evidence, handed out until it expires under the policy
+#[derive(Clone, Debug, Default)]
+pub struct Attestation {
+    pub policy: AttestationPolicy,
+    pub evidence: Option<Evidence>,
+}
+
+impl Attestation {
+    pub fn new(policy: AttestationPolicy) -> Self {
+        Attestation {
+            policy,
+            evidence: None,
+        }
+    }
+
+    pub fn cached(&self, now: DateTime<Utc>) -> Option<&Evidence> {
+        self.evidence
+            .as_ref()
+            .filter(|evidence| !self.policy.expired(evidence, now))
+    }
+}
+
+/// Quotes the enclave, has the quote verified by IAS and checks the result against the policy
+pub async fn attest(
+    crypto: Crypto,
+    nonce: Option<String>,
+    task_package: Option<String>,
+    policy: AttestationPolicy,
+) -> Result<Evidence, Error> {
+    use graphene_sgx::sgx::SgxQuote;
+    use sha3::{Digest, Sha3_256};
+    use ya_client_model::node_id::{NodeId, ParseError};
+    use ya_core_model::net::RemoteEndpoint;
+    use ya_core_model::sgx::VerifyAttestationEvidence;
+
+    let task_package = task_package.ok_or_else(|| {
+        Error::Other(
+            "Agreement has no `task_package` defined which is mandatory in case of SGX ExeUnit"
+                .into(),
+        )
+    })?;
+
+    let att_dev = std::path::Path::new("/dev/attestation");
+    if !att_dev.exists() {
+        let msg = format!("'{}' does not exist", att_dev.display());
+        return Err(Error::Attestation(msg));
+    }
+
+    let quote = SgxQuote::hasher()
+        .data(&crypto.requestor_pub_key.serialize())
+        .data(&crypto.pub_key.serialize())
+        .data(task_package.as_bytes())
+        .build()?;
+
+    let mr_enclave = quote.body.report_body.mr_enclave;
+    log::debug!("Enclave quote: {:?}", &quote);
+
+    let remote: NodeId = env::var("IAS_SERVICE_ADDRESS")
+        .map_err(|_| Error::Attestation("IAS_SERVICE_ADDRESS variable not found".into()))?
+        .parse()
+        .map_err(|e: ParseError| Error::Attestation(e.to_string()))?;
+
+    let response = remote
+        .service("/public/sgx")
+        .call(VerifyAttestationEvidence {
+            enclave_quote: quote.into(),
+            ias_nonce: nonce,
+            production: false,
+        })
+        .await?
+        .map_err(|e| Error::Attestation(e.to_string()))?;
+
+    log::debug!("IAS report: {}", &response.report);
+    let mut hasher = Sha3_256::new();
+    hasher.input(task_package.as_bytes());
+
+    let mut payload_hash = [0u8; 32];
+    payload_hash.copy_from_slice(hasher.result().as_ref());
+
+    let evidence = Evidence::try_from(Credentials::Sgx {
+        requestor: crypto.requestor_pub_key.serialize().to_vec(),
+        enclave: crypto.pub_key.serialize().to_vec(),
+        payload_sha3: payload_hash,
+        enclave_hash: mr_enclave,
+        ias_report: response.report,
+        ias_sig: response.signature,
+    })?;
+    policy.verify(&evidence, Utc::now())?;
+    Ok(evidence)
+}
+
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn parse_measurement(value: &str) -> Result<[u8; 32], Error> {
+    let bytes = hex::decode(value.trim_start_matches("0x"))?;
+    bytes
+        .try_into()
+        .map_err(|_| Error::Attestation(format!("Invalid enclave measurement: {value}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn evidence(quote_status: &str) -> Evidence {
+        let report = serde_json::json!({
+            "id": "1",
+            "timestamp": "2021-03-08T10:43:22.527658",
+            "isvEnclaveQuoteStatus": quote_status,
+        });
+        Evidence::try_from(Credentials::Sgx {
+            requestor: vec![],
+            enclave: vec![],
+            payload_sha3: [0u8; 32],
+            enclave_hash: [1u8; 32],
+            ias_report: report.to_string(),
+            ias_sig: vec![],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_report() {
+        let evidence = evidence("OK");
+        assert_eq!(evidence.quote_status, "OK");
+        assert_eq!(
+            evidence.issued_at.timestamp(),
+            Utc.with_ymd_and_hms(2021, 3, 8, 10, 43, 22)
+                .unwrap()
+                .timestamp()
+        );
+    }
+
+    #[test]
+    fn verify_policy() {
+        let now = evidence("OK").issued_at + chrono::Duration::hours(1);
+        let mut policy = AttestationPolicy::default();
+
+        assert!(policy.verify(&evidence("OK"), now).is_ok());
+        assert!(policy.verify(&evidence("GROUP_OUT_OF_DATE"), now).is_err());
+
+        policy
+            .allowed_quote_statuses
+            .insert("GROUP_OUT_OF_DATE".into());
+        assert!(policy.verify(&evidence("GROUP_OUT_OF_DATE"), now).is_ok());
+
+        policy.allowed_enclaves.insert([2u8; 32]);
+        assert!(policy.verify(&evidence("OK"), now).is_err());
+        policy
+            .allowed_enclaves
+            .insert(parse_measurement(&hex::encode([1u8; 32])).unwrap());
+        assert!(policy.verify(&evidence("OK"), now).is_ok());
+
+        policy.max_report_age = Some(Duration::from_secs(600));
+        assert!(policy.verify(&evidence("OK"), now).is_err());
+    }
+
+    #[test]
+    fn cached_evidence_expires() {
+        let evidence = evidence("OK");
+        let mut attestation = Attestation::new(AttestationPolicy {
+            max_report_age: Some(Duration::from_secs(600)),
+            ..Default::default()
+        });
+        attestation.evidence = Some(evidence.clone());
+
+        assert!(attestation.cached(evidence.issued_at).is_some());
+        let later = evidence.issued_at + chrono::Duration::minutes(11);
+        assert!(attestation.cached(later).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::Error;
+use crate::runtime::environment::SecretsKey;
 use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
 use sha3::Digest;
 use ya_client_model::activity::encrypted::EncryptionCtx;
@@ -59,6 +60,11 @@ impl Crypto {
         EncryptionCtx::new(&self.requestor_pub_key, &self.sec_key)
     }
 
+    /// Key shared with the requestor, used to decrypt secrets passed in the Deploy command
+    pub fn secrets_key(&self) -> SecretsKey {
+        SecretsKey::new(&self.sec_key, &self.requestor_pub_key)
+    }
+
     pub fn sign<T: AsRef<[u8]>>(&self, data: T) -> Result<Vec<u8>, Error> {
         let ec = Secp256k1::new();
         let hash = sha3::Sha3_256::digest(data.as_ref());
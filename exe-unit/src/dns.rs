@@ -1,35 +1,89 @@
 use std::collections::HashSet;
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 #[cfg(test)]
 use std::time::Duration;
 
+use chrono::Utc;
 use trust_dns_resolver::config;
 use trust_dns_resolver::TokioAsyncResolver;
 
+use ya_core_model::activity::local::DnsResolution;
+
+/// Comma separated list of domains allowed to be resolved. Supports `*.` prefix wildcards.
+pub const DNS_ALLOW_ENV_VAR: &str = "YA_DNS_ALLOW_DOMAINS";
+/// Comma separated list of domains denied to be resolved. Supports `*.` prefix wildcards.
+pub const DNS_DENY_ENV_VAR: &str = "YA_DNS_DENY_DOMAINS";
+
+const MAX_RESOLUTIONS: usize = 1024;
+
 #[derive(Clone)]
 pub struct StableResolver {
     stable_dns: IpAddr,
     resolver: TokioAsyncResolver,
+    filter: DomainFilter,
+    resolutions: Arc<Mutex<Vec<DnsResolution>>>,
 }
 
 impl StableResolver {
+    fn new(stable_dns: IpAddr, resolver: TokioAsyncResolver) -> Self {
+        StableResolver {
+            stable_dns,
+            resolver,
+            filter: DomainFilter::from_env(),
+            resolutions: Default::default(),
+        }
+    }
+
+    /// Resolves IP addresses of `host_name`.
+    ///
+    /// Host names rejected by the provider's domain filter resolve to an empty set.
     pub async fn ips(&self, host_name: &str) -> anyhow::Result<HashSet<IpAddr>> {
         if let Ok(ip_addr) = IpAddr::from_str(host_name) {
             return Ok(HashSet::from([ip_addr]));
         }
+
+        if !self.filter.is_allowed(host_name) {
+            log::warn!("[dns] Domain '{}' is not allowed by provider", host_name);
+            self.record(host_name, Default::default(), false);
+            return Ok(Default::default());
+        }
+
         log::debug!("Resolving IP addresses of '{}'", host_name);
 
         let response = self.resolver.lookup_ip(host_name).await?;
+        let ips: HashSet<IpAddr> = response.into_iter().collect();
+
+        log::info!("[dns] Resolved '{}' to {:?}", host_name, ips);
+        self.record(host_name, ips.iter().cloned().collect(), true);
 
-        Ok(response.into_iter().collect())
+        Ok(ips)
     }
 
     pub fn stable_dns(&self) -> IpAddr {
         self.stable_dns
     }
 
+    /// Host names resolved so far, oldest first.
+    pub fn resolutions(&self) -> Vec<DnsResolution> {
+        self.resolutions.lock().unwrap().clone()
+    }
+
+    fn record(&self, host: &str, addresses: Vec<IpAddr>, allowed: bool) {
+        let mut resolutions = self.resolutions.lock().unwrap();
+        if resolutions.len() >= MAX_RESOLUTIONS {
+            resolutions.remove(0);
+        }
+        resolutions.push(DnsResolution {
+            host: host.to_string(),
+            addresses,
+            allowed,
+            timestamp: Utc::now(),
+        });
+    }
+
     #[cfg(test)]
     fn clear_cache(&self) {
         self.resolver.clear_cache();
@@ -45,10 +99,7 @@ async fn google_resolver() -> anyhow::Result<StableResolver> {
     let resolver = TokioAsyncResolver::tokio(config, options)?;
     let stable_dns = config::GOOGLE_IPS[0];
 
-    Ok(StableResolver {
-        stable_dns,
-        resolver,
-    })
+    Ok(StableResolver::new(stable_dns, resolver))
 }
 
 pub async fn resolver() -> anyhow::Result<StableResolver> {
@@ -63,10 +114,7 @@ pub async fn resolver() -> anyhow::Result<StableResolver> {
         trust_dns_resolver::config::Protocol::Udp,
     ));
     let resolver = TokioAsyncResolver::tokio(config, Default::default())?;
-    Ok(StableResolver {
-        stable_dns,
-        resolver,
-    })
+    Ok(StableResolver::new(stable_dns, resolver))
 }
 
 pub const DNS_PORT: u16 = 53;
@@ -81,6 +129,80 @@ pub fn dns_servers() -> impl Iterator<Item = IpAddr> {
         .chain(QUAD9_IPS.iter().cloned())
 }
 
+/// Provider-configured domain allow and deny lists.
+///
+/// Denied domains take precedence. When the allow list is empty, all domains not
+/// explicitly denied are allowed.
+#[derive(Clone, Debug, Default)]
+pub struct DomainFilter {
+    allow: Vec<DomainPattern>,
+    deny: Vec<DomainPattern>,
+}
+
+impl DomainFilter {
+    pub fn new(allow: &str, deny: &str) -> Self {
+        DomainFilter {
+            allow: DomainPattern::parse_list(allow),
+            deny: DomainPattern::parse_list(deny),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let allow = std::env::var(DNS_ALLOW_ENV_VAR).unwrap_or_default();
+        let deny = std::env::var(DNS_DENY_ENV_VAR).unwrap_or_default();
+        Self::new(&allow, &deny)
+    }
+
+    pub fn is_allowed(&self, host: &str) -> bool {
+        let host = normalize(host);
+        if self.deny.iter().any(|p| p.matches(&host)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|p| p.matches(&host))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DomainPattern {
+    Any,
+    Exact(String),
+    /// Matches all subdomains of the contained domain (but not the domain itself)
+    Subdomains(String),
+}
+
+impl DomainPattern {
+    fn parse_list(list: &str) -> Vec<Self> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    fn parse(pattern: &str) -> Self {
+        let pattern = normalize(pattern);
+        match pattern.as_str() {
+            "*" => Self::Any,
+            p => match p.strip_prefix("*.") {
+                Some(domain) => Self::Subdomains(format!(".{}", domain)),
+                None => Self::Exact(pattern),
+            },
+        }
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(domain) => host == domain,
+            Self::Subdomains(suffix) => host.ends_with(suffix.as_str()),
+        }
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().trim_end_matches('.').to_lowercase()
+}
+
 // Do not use it in CI
 #[cfg(test)]
 #[ignore]
@@ -112,3 +234,33 @@ async fn test_fail_resolver() {
         assert_eq!(ac, ac2);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domain_filter_defaults_to_allow() {
+        let filter = DomainFilter::new("", "");
+        assert!(filter.is_allowed("golem.network"));
+    }
+
+    #[test]
+    fn domain_filter_wildcards() {
+        let filter = DomainFilter::new("*.golem.network, example.com", "bad.golem.network");
+
+        assert!(filter.is_allowed("api.golem.network"));
+        assert!(filter.is_allowed("API.Golem.Network."));
+        assert!(filter.is_allowed("example.com"));
+        assert!(!filter.is_allowed("golem.network"));
+        assert!(!filter.is_allowed("bad.golem.network"));
+        assert!(!filter.is_allowed("sub.example.com"));
+        assert!(!filter.is_allowed("notgolem.network"));
+    }
+
+    #[test]
+    fn domain_filter_deny_all() {
+        let filter = DomainFilter::new("example.com", "*");
+        assert!(!filter.is_allowed("example.com"));
+    }
+}
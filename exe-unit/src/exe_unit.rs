@@ -7,8 +7,10 @@ use chrono::Utc;
 use futures::channel::{mpsc, oneshot};
 use futures::{FutureExt, SinkExt};
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::time::Duration;
 use tokio::sync::broadcast;
+use url::Url;
 use ya_counters::error::CounterError;
 use ya_counters::message::GetCounters;
 use ya_counters::service::CountersService;
@@ -22,17 +24,24 @@ use ya_runtime_api::deploy;
 use ya_runtime_api::deploy::ContainerVolume;
 use ya_service_bus::{actix_rpc, RpcEndpoint, RpcMessage};
 use ya_transfer::transfer::{
-    AddVolumes, DeployImage, ForwardProgressToSink, TransferResource, TransferService,
-    TransferServiceContext,
+    AddVolumes, DeployImage, ForwardProgressToSink, PreflightDeploy, TransferResource,
+    TransferService, TransferServiceContext,
 };
+use ya_transfer::{MinThroughput, OutboundPolicy};
 
-use crate::acl::Acl;
+use crate::acl::{AccessRole, Acl};
 use crate::agreement::Agreement;
 use crate::error::Error;
+use crate::journal::Journal;
+use crate::manifest::{ManifestContext, ManifestValidatorExt, UrlValidator};
 use crate::message::{
-    ExecuteCommand, GetStdOut, Initialize, RuntimeEvent, SetState, Shutdown, ShutdownReason,
-    SignExeScript, Stop, UpdateDeployment,
+    ExecuteCommand, GetRunningProcesses, GetStdOut, Initialize, ResolveEnv, RuntimeEvent, SetState,
+    Shutdown, ShutdownReason, SignExeScript, Stop, UpdateDeployment,
 };
+use crate::network::NetworkTraffic;
+use crate::output::OutputConfig;
+use crate::runtime::environment::{stdin_enabled, EnvPolicy, SecretsKey};
+use crate::runtime::sidecar::Sidecar;
 use crate::runtime::{Runtime, RuntimeMode};
 use crate::service::{self, ServiceAddr, ServiceControl};
 use crate::state::{ExeUnitState, StateError, Supervision};
@@ -40,6 +49,7 @@ use crate::Result;
 
 lazy_static::lazy_static! {
     static ref DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(1u64);
+    static ref IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60u64);
 }
 
 #[derive(Clone, Debug, Default, Message)]
@@ -55,6 +65,7 @@ pub struct ExeUnit<R: Runtime> {
     pub(crate) transfers: Addr<TransferService>,
     pub(crate) services: Vec<Box<dyn ServiceControl>>,
     pub(crate) shutdown_tx: broadcast::Sender<()>,
+    pub(crate) journal: Journal,
 }
 
 impl<R: Runtime> ExeUnit<R> {
@@ -65,9 +76,11 @@ impl<R: Runtime> ExeUnit<R> {
         runtime: Addr<R>,
     ) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
+        let journal = Journal::new(&ctx.work_dir, ctx.activity_id.clone());
+        let state = ExeUnitState::new(ctx.output.clone());
         ExeUnit {
             ctx,
-            state: ExeUnitState::default(),
+            state,
             events: Channel::default(),
             runtime: runtime.clone(),
             counters: counters.clone(),
@@ -78,6 +91,7 @@ impl<R: Runtime> ExeUnit<R> {
                 Box::new(ServiceAddr::new(runtime)),
             ],
             shutdown_tx,
+            journal,
         }
     }
 
@@ -122,6 +136,20 @@ impl<R: Runtime> ExeUnit<R> {
             log::warn!("Unable to stop the runtime: {:?}", e);
         }
     }
+
+    /// Checks every mirror of a requestor-provided image against the manifest's outbound
+    /// rules. The payload listed in the manifest itself is allowed by definition.
+    pub(crate) fn validate_image(&self) -> crate::Result<()> {
+        let manifest = &self.ctx.supervise.manifest;
+        let image = match &self.ctx.agreement.task_package {
+            Some(image) if manifest.payload().as_ref() != Some(image) => image,
+            _ => return Ok(()),
+        };
+        manifest
+            .validator::<UrlValidator>()
+            .with(|c| c.validate_image(image))?;
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
@@ -319,8 +347,17 @@ impl<R: Runtime> RuntimeRef<R> {
                 hosts,
                 progress,
                 volumes,
+                env,
                 ..
             } => {
+                // rejected variables fail the command before the image is downloaded
+                let runtime_env = self.send(ResolveEnv { env: env.clone() }).await??;
+
+                // so does an image, which doesn't fit on disk
+                if let Some(report) = transfer_service.send(PreflightDeploy::default()).await?? {
+                    log::debug!("Deploy preflight: {:?}", report);
+                }
+
                 let volumes = if let Some(v) = &volumes {
                     v.clone()
                         .as_volumes()
@@ -355,6 +392,9 @@ impl<R: Runtime> RuntimeRef<R> {
                         task_package,
                         networks: Some(net.clone()),
                         hosts: Some(hosts.clone()),
+                        sidecars: Some(Sidecar::from_env(env)?),
+                        env: Some(runtime_env),
+                        stdin: Some(stdin_enabled(env)?),
                         ..Default::default()
                     })
                     .await??;
@@ -370,7 +410,7 @@ impl<R: Runtime> RuntimeRef<R> {
         runtime: &Addr<R>,
         transfer_service: &Addr<TransferService>,
     ) -> crate::Result<()> {
-        if let ExeScriptCommand::Deploy { .. } = &runtime_cmd.command {
+        if let ExeScriptCommand::Deploy { env, .. } = &runtime_cmd.command {
             let mut runtime_mode = RuntimeMode::ProcessPerCommand;
             let stdout = self
                 .send(GetStdOut {
@@ -389,6 +429,23 @@ impl<R: Runtime> RuntimeRef<R> {
                     .await??;
                 runtime_mode = deployment.start_mode.into();
             }
+
+            // variables are delivered with `RunProcess` to processes inside the guest,
+            // they are never set on the runtime binary running on the host
+            if matches!(runtime_mode, RuntimeMode::ProcessPerCommand) {
+                let runtime_env = self.send(ResolveEnv { env: env.clone() }).await??;
+                if !runtime_env.is_empty() {
+                    return Err(Error::CommandError(
+                        "environment variables are supported only by runtimes running in service mode"
+                            .into(),
+                    ));
+                }
+                if stdin_enabled(env)? {
+                    return Err(Error::CommandError(
+                        "stdin is supported only by runtimes running in service mode".into(),
+                    ));
+                }
+            }
             runtime
                 .send(UpdateDeployment {
                     runtime_mode: Some(runtime_mode),
@@ -412,6 +469,10 @@ impl<R: Runtime> Actor for ExeUnit<R> {
             let srv_id = activity::exeunit::bus_id(activity_id);
             actix_rpc::bind::<activity::GetState>(&srv_id, addr.clone().recipient());
             actix_rpc::bind::<activity::GetUsage>(&srv_id, addr.clone().recipient());
+            actix_rpc::bind::<activity::local::GetDnsResolutions>(
+                &activity::local::exeunit_bus_id(activity_id),
+                addr.clone().recipient(),
+            );
 
             #[cfg(feature = "sgx")]
             {
@@ -419,12 +480,19 @@ impl<R: Runtime> Actor for ExeUnit<R> {
                     &srv_id,
                     addr.clone().recipient(),
                 );
+                actix_rpc::bind::<activity::sgx::GetAttestationEvidence>(
+                    &srv_id,
+                    addr.clone().recipient(),
+                );
             }
             #[cfg(not(feature = "sgx"))]
             {
                 actix_rpc::bind::<activity::Exec>(&srv_id, addr.clone().recipient());
                 actix_rpc::bind::<activity::GetExecBatchResults>(&srv_id, addr.clone().recipient());
+                actix_rpc::bind::<activity::GetExecBatches>(&srv_id, addr.clone().recipient());
                 actix_rpc::bind::<activity::GetRunningCommand>(&srv_id, addr.clone().recipient());
+                actix_rpc::bind::<activity::GetCommandLog>(&srv_id, addr.clone().recipient());
+                actix_rpc::bind::<activity::WriteStdin>(&srv_id, addr.clone().recipient());
                 actix_rpc::binds::<activity::StreamExecBatchResults>(
                     &srv_id,
                     addr.clone().recipient(),
@@ -436,6 +504,31 @@ impl<R: Runtime> Actor for ExeUnit<R> {
             .finish()
             .spawn(ctx);
 
+        if let Some(idle_timeout) = self.ctx.idle_timeout {
+            let interval =
+                (idle_timeout / 10).clamp(*DEFAULT_REPORT_INTERVAL, *IDLE_CHECK_INTERVAL);
+            ctx.run_interval(interval, move |this, ctx| {
+                let fut = this.runtime.send(GetRunningProcesses).into_actor(this).map(
+                    move |result, this, ctx| {
+                        let processes = match result {
+                            Ok(processes) => processes,
+                            Err(e) => {
+                                log::warn!("Unable to query running processes: {}", e);
+                                return;
+                            }
+                        };
+                        if matches!(this.state.idle(processes), Some(idle) if idle >= idle_timeout)
+                        {
+                            log::info!("Activity is idle for {:?}, terminating", idle_timeout);
+                            ctx.address()
+                                .do_send(Shutdown(ShutdownReason::Idle(idle_timeout)));
+                        }
+                    },
+                );
+                ctx.spawn(fut);
+            });
+        }
+
         log::info!("Initializing manifests");
         self.ctx
             .supervise
@@ -496,15 +589,35 @@ pub struct ExeUnitContext {
     pub agreement: Agreement,
     pub work_dir: PathBuf,
     pub cache_dir: PathBuf,
+    pub cache_max_size: Option<u64>,
+    pub idle_timeout: Option<Duration>,
+    pub transfer_min_throughput: Option<MinThroughput>,
+    pub output: OutputConfig,
     pub runtime_args: Vec<String>,
     pub acl: Acl,
     pub credentials: Option<Credentials>,
+    pub traffic: NetworkTraffic,
+    pub env_policy: EnvPolicy,
+    #[derivative(Debug = "ignore")]
+    pub secrets_key: Option<SecretsKey>,
     #[cfg(feature = "sgx")]
     #[derivative(Debug = "ignore")]
     pub crypto: crate::crypto::Crypto,
+    #[cfg(feature = "sgx")]
+    pub attestation: crate::attestation::Attestation,
 }
 
 impl ExeUnitContext {
+    /// Verifies that the caller was granted `role` by the agreement
+    pub fn verify_caller(
+        &self,
+        caller: &str,
+        role: AccessRole,
+        endpoint: &str,
+    ) -> crate::Result<()> {
+        Ok(self.acl.check(caller, role, endpoint)?)
+    }
+
     pub fn verify_activity_id(&self, activity_id: &str) -> crate::Result<()> {
         match &self.activity_id {
             Some(act_id) => match act_id == activity_id {
@@ -525,12 +638,27 @@ impl From<&ExeUnitContext> for TransferServiceContext {
             task_package: val.agreement.task_package.clone(),
             deploy_retry: None,
             cache_dir: val.cache_dir.clone(),
+            cache_max_size: val.cache_max_size,
             work_dir: val.work_dir.clone(),
             transfer_retry: None,
+            min_throughput: val.transfer_min_throughput,
+            outbound: Some(outbound_policy(&val.supervise.manifest)),
         }
     }
 }
 
+/// Validators are built after the transfer service is started, so the policy looks them up
+/// on each check. Without the outbound rules of the manifest every URL is allowed.
+fn outbound_policy(manifest: &ManifestContext) -> OutboundPolicy {
+    let manifest = manifest.clone();
+    Rc::new(move |url: &Url| {
+        manifest
+            .validator::<UrlValidator>()
+            .map(|validator| validator.allows(url))
+            .unwrap_or(true)
+    })
+}
+
 pub struct Channel<T> {
     pub(crate) tx: mpsc::Sender<T>,
     rx: Option<mpsc::Receiver<T>>,
@@ -575,6 +703,7 @@ async fn report_usage<R: Runtime>(
     match metrics.send(GetCounters).await {
         Ok(resp) => match resp {
             Ok(data) => {
+                exe_unit.do_send(RecordUsage(data.clone()));
                 let msg = activity::local::SetUsage {
                     activity_id,
                     usage: ActivityUsage {
@@ -601,6 +730,18 @@ async fn report_usage<R: Runtime>(
     }
 }
 
+#[derive(Message)]
+#[rtype(result = "()")]
+struct RecordUsage(Vec<f64>);
+
+impl<R: Runtime> Handler<RecordUsage> for ExeUnit<R> {
+    type Result = ();
+
+    fn handle(&mut self, msg: RecordUsage, _: &mut Self::Context) -> Self::Result {
+        self.journal.set_usage(msg.0);
+    }
+}
+
 impl<R: Runtime> Handler<FinishNotifier> for ExeUnit<R> {
     type Result = Result<broadcast::Receiver<()>>;
 
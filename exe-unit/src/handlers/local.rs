@@ -3,6 +3,7 @@ use futures::FutureExt;
 
 use crate::error::Error;
 use crate::message::*;
+use crate::runtime::environment::RuntimeEnv;
 use crate::runtime::Runtime;
 use crate::service::ServiceAddr;
 use crate::state::State;
@@ -10,7 +11,7 @@ use crate::{report, ExeUnit};
 
 use ya_client_model::activity;
 use ya_core_model::activity::local::SetState as SetActivityState;
-use ya_counters::message::SetCounter;
+use ya_counters::message::{RegisterCounter, SetCounter};
 
 impl<R: Runtime> StreamHandler<RuntimeEvent> for ExeUnit<R> {
     fn handle(&mut self, event: RuntimeEvent, ctx: &mut Context<Self>) {
@@ -20,6 +21,16 @@ impl<R: Runtime> StreamHandler<RuntimeEvent> for ExeUnit<R> {
                     let batch_id = event.batch_id.clone();
                     self.state.last_batch = Some(batch_id.clone());
 
+                    match &event.kind {
+                        activity::RuntimeEventKind::Started { .. } => self
+                            .journal
+                            .set_command(Some(batch_id.clone()), Some(event.index)),
+                        activity::RuntimeEventKind::Finished { .. } => {
+                            self.journal.set_command(None, None)
+                        }
+                        _ => (),
+                    }
+
                     if let Err(err) = batch.handle_event(event) {
                         log::error!("Batch {} event error: {}", batch_id, err);
                     }
@@ -33,6 +44,24 @@ impl<R: Runtime> StreamHandler<RuntimeEvent> for ExeUnit<R> {
                 };
                 ctx.spawn(fut.into_actor(self));
             }
+            RuntimeEvent::CounterDefinition {
+                name,
+                unit,
+                monotonic,
+            } => {
+                let addr = self.counters.clone();
+                let fut = async move {
+                    let msg = RegisterCounter {
+                        name,
+                        unit,
+                        monotonic,
+                    };
+                    if let Ok(Err(e)) = addr.send(msg).await {
+                        log::warn!("Unable to register runtime counter: {e}");
+                    }
+                };
+                ctx.spawn(fut.into_actor(self));
+            }
             other => {
                 log::warn!("Unsupported runtime event: {:?}", other);
             }
@@ -60,6 +89,7 @@ impl<R: Runtime> Handler<SetState> for ExeUnit<R> {
         log::debug!("Entering state: {:?}", update.state);
         log::debug!("Report: {}", self.state.report());
         self.state.inner = update.state;
+        self.journal.set_state(update.state);
 
         if self.ctx.activity_id.is_none() || self.ctx.report_url.is_none() {
             return ActorResponse::reply(());
@@ -108,78 +138,17 @@ impl<R: Runtime> Handler<Initialize> for ExeUnit<R> {
 
     #[cfg(feature = "sgx")]
     fn handle(&mut self, _: Initialize, _: &mut Context<Self>) -> Self::Result {
-        let crypto = self.ctx.crypto.clone();
-        let nonce = self.ctx.activity_id.to_owned();
-        let task_package = self.ctx.agreement.task_package.to_owned();
-
-        let fut = async move {
-            Ok::<_, Error>({
-                {
-                    use graphene_sgx::sgx::SgxQuote;
-                    use sha3::{Digest, Sha3_256};
-                    use std::env;
-                    use ya_client_model::node_id::{NodeId, ParseError};
-                    use ya_core_model::activity::local::Credentials;
-                    use ya_core_model::net::RemoteEndpoint;
-                    use ya_core_model::sgx::VerifyAttestationEvidence;
-
-                    let task_package = task_package.ok_or_else(||
-                        Error::Other("Agreement has no `task_package` defined which is mandatory in case of SGX ExeUnit".into())
-                    )?;
-
-                    let att_dev = std::path::Path::new("/dev/attestation");
-                    if !att_dev.exists() {
-                        let msg = format!("'{}' does not exist", att_dev.display());
-                        return Err(Error::Attestation(msg));
-                    }
-
-                    let quote = SgxQuote::hasher()
-                        .data(&crypto.requestor_pub_key.serialize())
-                        .data(&crypto.pub_key.serialize())
-                        .data(task_package.as_bytes())
-                        .build()?;
-
-                    let mr_enclave = quote.body.report_body.mr_enclave;
-                    log::debug!("Enclave quote: {:?}", &quote);
-
-                    let remote: NodeId = env::var("IAS_SERVICE_ADDRESS")
-                        .map_err(|_| {
-                            Error::Attestation("IAS_SERVICE_ADDRESS variable not found".into())
-                        })?
-                        .parse()
-                        .map_err(|e: ParseError| Error::Attestation(e.to_string()))?;
-
-                    let evidence = remote
-                        .service("/public/sgx")
-                        .call(VerifyAttestationEvidence {
-                            enclave_quote: quote.into(),
-                            ias_nonce: nonce,
-                            production: false,
-                        })
-                        .await?
-                        .map_err(|e| Error::Attestation(e.to_string()))?;
-
-                    log::debug!("IAS report: {}", &evidence.report);
-                    let mut hasher = Sha3_256::new();
-                    hasher.input(task_package.as_bytes());
-
-                    let mut payload_hash = [0u8; 32];
-                    payload_hash.copy_from_slice(hasher.result().as_ref());
-
-                    Some(Credentials::Sgx {
-                        requestor: crypto.requestor_pub_key.serialize().to_vec(),
-                        enclave: crypto.pub_key.serialize().to_vec(),
-                        payload_sha3: payload_hash,
-                        enclave_hash: mr_enclave,
-                        ias_report: evidence.report,
-                        ias_sig: evidence.signature,
-                    })
-                }
-            })
-        }
+        let fut = crate::attestation::attest(
+            self.ctx.crypto.clone(),
+            self.ctx.activity_id.to_owned(),
+            self.ctx.agreement.task_package.to_owned(),
+            self.ctx.attestation.policy.clone(),
+        )
         .into_actor(self)
         .map(move |result, actor, _| {
-            actor.ctx.credentials = result?;
+            let evidence = result?;
+            actor.ctx.credentials = Some(evidence.credentials.clone());
+            actor.ctx.attestation.evidence = Some(evidence);
             Ok(())
         });
 
@@ -250,6 +219,18 @@ impl<R: Runtime> Handler<SignExeScript> for ExeUnit<R> {
     }
 }
 
+impl<R: Runtime> Handler<ResolveEnv> for ExeUnit<R> {
+    type Result = <ResolveEnv as Message>::Result;
+
+    fn handle(&mut self, msg: ResolveEnv, _: &mut Context<Self>) -> Self::Result {
+        RuntimeEnv::from_env(
+            &msg.env,
+            &self.ctx.env_policy,
+            self.ctx.secrets_key.as_ref(),
+        )
+    }
+}
+
 impl<R: Runtime> Handler<Stop> for ExeUnit<R> {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
@@ -279,6 +260,7 @@ impl<R: Runtime> Handler<Shutdown> for ExeUnit<R> {
 
         let address = ctx.address();
         let services = std::mem::take(&mut self.services);
+        let output = self.ctx.output.clone();
         let state = self.state.inner.to_pending(State::Terminated);
         let reason = format!("{}: {}", msg.0, self.state.report());
 
@@ -290,6 +272,8 @@ impl<R: Runtime> Handler<Shutdown> for ExeUnit<R> {
             for mut service in services {
                 service.stop().await;
             }
+            // Logs can only be fetched from a running ExeUnit
+            output.remove_logs();
 
             let set_state = SetState::new(State::Terminated.into(), reason);
             let _ = address.send(set_state).await;
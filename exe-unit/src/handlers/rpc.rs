@@ -8,14 +8,20 @@ use tokio::time::timeout;
 
 #[cfg(feature = "sgx")]
 use ya_client_model::activity::encrypted::RpcMessageError as SgxMessageError;
-use ya_client_model::activity::{ActivityState, ActivityUsage, ExeScriptCommandResult};
+use ya_client_model::activity::{
+    ActivityState, ActivityUsage, ExeScriptCommand, ExeScriptCommandResult,
+};
 use ya_core_model::activity::*;
 use ya_counters::message::GetCounters;
-use ya_service_bus::{Error as RpcError, RpcEnvelope, RpcStreamCall};
+use ya_service_bus::{Error as RpcError, RpcEnvelope, RpcMessage, RpcStreamCall};
+use ya_transfer::transfer::TransferResource;
 
+use crate::acl::AccessRole;
 use crate::error::Error;
-use crate::manifest::{ManifestValidatorExt, ScriptValidator};
-use crate::message::GetBatchResults;
+use crate::manifest::{ManifestValidatorExt, ScriptValidator, UrlValidator};
+use crate::message::{self, GetBatchResults};
+use crate::runtime::environment::RuntimeEnv;
+use crate::runtime::sidecar::Sidecar;
 use crate::runtime::Runtime;
 use crate::{ExeUnit, RuntimeRef};
 
@@ -25,6 +31,8 @@ impl<R: Runtime> Handler<RpcEnvelope<Exec>> for ExeUnit<R> {
     fn handle(&mut self, msg: RpcEnvelope<Exec>, ctx: &mut Self::Context) -> Self::Result {
         log::debug!("Received Exec message: {:?}", msg.as_ref());
         self.ctx.verify_activity_id(&msg.activity_id)?;
+        self.ctx
+            .verify_caller(msg.caller(), AccessRole::Control, Exec::ID)?;
 
         let batch_id = msg.batch_id.clone();
         let msg = msg.into_inner();
@@ -34,11 +42,44 @@ impl<R: Runtime> Handler<RpcEnvelope<Exec>> for ExeUnit<R> {
             return Err(RpcMessageError::BadRequest(m));
         }
 
+        let sidecars = msg
+            .exe_script
+            .iter()
+            .map(Sidecar::from_command)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| RpcMessageError::BadRequest(e.to_string()))?
+            .into_iter()
+            .flatten()
+            .map(|sidecar| sidecar.as_command())
+            .collect::<Vec<_>>();
+
+        let script = || msg.exe_script.iter().chain(sidecars.iter());
         let validator = self.ctx.supervise.manifest.validator::<ScriptValidator>();
-        if let Err(e) = validator.with(|c| c.validate(msg.exe_script.iter())) {
+        if let Err(e) = validator.with(|c| c.validate(script())) {
             let m = format!("Manifest violation in ExeScript: {}", e);
             return Err(RpcMessageError::BadRequest(m));
         }
+        let validator = self.ctx.supervise.manifest.validator::<UrlValidator>();
+        if let Err(e) = validator.with(|c| c.validate_transfers(script())) {
+            let m = format!("Manifest violation in ExeScript: {}", e);
+            return Err(RpcMessageError::BadRequest(m));
+        }
+        if let Err(e) = self.ctx.acl.check_transfers(script()) {
+            return Err(RpcMessageError::Forbidden(e.to_string()));
+        }
+        // rejected variables and secrets fail the batch before any command is run
+        for command in msg.exe_script.iter() {
+            if let ExeScriptCommand::Deploy { env, .. } = command {
+                RuntimeEnv::from_env(env, &self.ctx.env_policy, self.ctx.secrets_key.as_ref())
+                    .map_err(|e| RpcMessageError::BadRequest(e.to_string()))?;
+            }
+        }
+        if script().any(|c| matches!(c, ExeScriptCommand::Deploy { .. })) {
+            if let Err(e) = self.validate_image() {
+                let m = format!("Manifest violation in deployed image: {}", e);
+                return Err(RpcMessageError::BadRequest(m));
+            }
+        }
 
         let (tx, rx) = oneshot::channel();
         self.state.start_batch(msg.clone(), tx);
@@ -63,6 +104,8 @@ impl<R: Runtime> Handler<RpcEnvelope<GetState>> for ExeUnit<R> {
 
     fn handle(&mut self, msg: RpcEnvelope<GetState>, _: &mut Self::Context) -> Self::Result {
         self.ctx.verify_activity_id(&msg.activity_id)?;
+        self.ctx
+            .verify_caller(msg.caller(), AccessRole::Observe, GetState::ID)?;
 
         Ok(ActivityState {
             state: self.state.inner,
@@ -72,11 +115,39 @@ impl<R: Runtime> Handler<RpcEnvelope<GetState>> for ExeUnit<R> {
     }
 }
 
+impl<R: Runtime> Handler<RpcEnvelope<local::GetDnsResolutions>> for ExeUnit<R> {
+    type Result = <RpcEnvelope<local::GetDnsResolutions> as Message>::Result;
+
+    fn handle(
+        &mut self,
+        msg: RpcEnvelope<local::GetDnsResolutions>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        self.ctx.verify_activity_id(&msg.activity_id)?;
+        self.ctx.verify_caller(
+            msg.caller(),
+            AccessRole::Observe,
+            local::GetDnsResolutions::ID,
+        )?;
+
+        Ok(self
+            .ctx
+            .supervise
+            .manifest
+            .validator::<UrlValidator>()
+            .map(|validator| validator.resolutions())
+            .unwrap_or_default())
+    }
+}
+
 impl<R: Runtime> Handler<RpcEnvelope<GetUsage>> for ExeUnit<R> {
     type Result = ActorResponse<Self, Result<ActivityUsage, RpcMessageError>>;
 
     fn handle(&mut self, msg: RpcEnvelope<GetUsage>, _: &mut Self::Context) -> Self::Result {
-        if let Err(e) = self.ctx.verify_activity_id(&msg.activity_id) {
+        if let Err(e) = self.ctx.verify_activity_id(&msg.activity_id).and_then(|_| {
+            self.ctx
+                .verify_caller(msg.caller(), AccessRole::Observe, GetUsage::ID)
+        }) {
             return ActorResponse::reply(Err(e.into()));
         }
 
@@ -112,6 +183,8 @@ impl<R: Runtime> Handler<RpcEnvelope<GetRunningCommand>> for ExeUnit<R> {
         _: &mut Self::Context,
     ) -> Self::Result {
         self.ctx.verify_activity_id(&msg.activity_id)?;
+        self.ctx
+            .verify_caller(msg.caller(), AccessRole::Observe, GetRunningCommand::ID)?;
         let commands = self
             .state
             .batches
@@ -138,7 +211,10 @@ impl<R: Runtime> Handler<RpcEnvelope<GetExecBatchResults>> for ExeUnit<R> {
         msg: RpcEnvelope<GetExecBatchResults>,
         ctx: &mut Self::Context,
     ) -> Self::Result {
-        if let Err(err) = self.ctx.verify_activity_id(&msg.activity_id) {
+        if let Err(err) = self.ctx.verify_activity_id(&msg.activity_id).and_then(|_| {
+            self.ctx
+                .verify_caller(msg.caller(), AccessRole::Observe, GetExecBatchResults::ID)
+        }) {
             return ActorResponse::reply(Err(err.into()));
         }
 
@@ -179,6 +255,166 @@ impl<R: Runtime> Handler<RpcEnvelope<GetExecBatchResults>> for ExeUnit<R> {
     }
 }
 
+impl<R: Runtime> Handler<RpcEnvelope<GetCommandLog>> for ExeUnit<R> {
+    type Result = ActorResponse<Self, Result<u64, RpcMessageError>>;
+
+    fn handle(&mut self, msg: RpcEnvelope<GetCommandLog>, _: &mut Self::Context) -> Self::Result {
+        if let Err(err) = self.ctx.verify_activity_id(&msg.activity_id).and_then(|_| {
+            self.ctx
+                .verify_caller(msg.caller(), AccessRole::Control, GetCommandLog::ID)
+        }) {
+            return ActorResponse::reply(Err(err.into()));
+        }
+
+        let path = self
+            .state
+            .batches
+            .get(&msg.batch_id)
+            .and_then(|batch| batch.results.get(msg.idx))
+            .and_then(|state| state.output(msg.stream).log_path())
+            .map(|path| path.to_path_buf());
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let err = RpcMessageError::NotFound(format!(
+                    "{:?} log of command {} in batch {}",
+                    msg.stream, msg.idx, msg.batch_id
+                ));
+                return ActorResponse::reply(Err(err));
+            }
+        };
+        let from = match url::Url::from_file_path(&path) {
+            Ok(url) => url.to_string(),
+            Err(_) => {
+                let err = RpcMessageError::Service(format!("Invalid log path: {}", path.display()));
+                return ActorResponse::reply(Err(err));
+            }
+        };
+
+        // Uploading the log is a transfer to a requestor-supplied url,
+        // so it's subject to the same manifest rules as ExeScript transfers
+        let msg = msg.into_inner();
+        let duration = msg
+            .timeout
+            .and_then(|secs| Duration::try_from_secs_f32(secs).ok());
+        let to = msg.to;
+        let command = ExeScriptCommand::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            args: Default::default(),
+            progress: None,
+        };
+        let manifest = &self.ctx.supervise.manifest;
+        if let Err(e) = manifest
+            .validator::<ScriptValidator>()
+            .with(|c| c.validate(std::iter::once(&command)))
+            .and_then(|_| {
+                manifest
+                    .validator::<UrlValidator>()
+                    .with(|c| c.validate_transfers(std::iter::once(&command)))
+            })
+        {
+            let err = RpcMessageError::BadRequest(format!("Manifest violation in transfer: {}", e));
+            return ActorResponse::reply(Err(err));
+        }
+        if let Err(e) = self.ctx.acl.check_transfers(std::iter::once(&command)) {
+            return ActorResponse::reply(Err(RpcMessageError::Forbidden(e.to_string())));
+        }
+
+        let transfer = TransferResource {
+            from,
+            to,
+            args: Default::default(),
+            progress_config: None,
+        };
+        let transfers = self.transfers.clone();
+        let fut = async move {
+            let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            let send = transfers.send(transfer);
+            let result = match duration {
+                Some(duration) => timeout(duration, send)
+                    .await
+                    .map_err(|_| RpcMessageError::Timeout)?,
+                None => send.await,
+            };
+            match result {
+                Ok(Ok(())) => Ok(size),
+                Ok(Err(e)) => Err(Error::from(e).into()),
+                Err(e) => Err(Error::from(e).into()),
+            }
+        };
+
+        ActorResponse::r#async(fut.into_actor(self))
+    }
+}
+
+impl<R: Runtime> Handler<RpcEnvelope<WriteStdin>> for ExeUnit<R> {
+    type Result = ActorResponse<Self, Result<(), RpcMessageError>>;
+
+    fn handle(&mut self, msg: RpcEnvelope<WriteStdin>, _: &mut Self::Context) -> Self::Result {
+        if let Err(err) = self.ctx.verify_activity_id(&msg.activity_id).and_then(|_| {
+            self.ctx
+                .verify_caller(msg.caller(), AccessRole::Control, WriteStdin::ID)
+        }) {
+            return ActorResponse::reply(Err(err.into()));
+        }
+
+        let running = self
+            .state
+            .batches
+            .get(&msg.batch_id)
+            .and_then(|batch| batch.results.get(msg.idx))
+            .map(|state| state.result.is_none())
+            .unwrap_or(false);
+        if !running {
+            let err = RpcMessageError::NotFound(format!(
+                "running command {} in batch {}",
+                msg.idx, msg.batch_id
+            ));
+            return ActorResponse::reply(Err(err));
+        }
+
+        let msg = msg.into_inner();
+        let write = message::WriteStdin {
+            batch_id: msg.batch_id,
+            idx: msg.idx,
+            data: msg.data,
+            eof: msg.eof,
+        };
+        let duration = Duration::from_secs_f32(msg.timeout.unwrap_or(10.));
+        let runtime = self.runtime.clone();
+        let fut = async move {
+            match timeout(duration, runtime.send(write)).await {
+                Ok(Ok(Ok(()))) => Ok(()),
+                Ok(Ok(Err(e))) => Err(RpcMessageError::BadRequest(e.to_string())),
+                Ok(Err(e)) => Err(Error::from(e).into()),
+                Err(_) => Err(RpcMessageError::Timeout),
+            }
+        };
+
+        ActorResponse::r#async(fut.into_actor(self))
+    }
+}
+
+impl<R: Runtime> Handler<RpcEnvelope<GetExecBatches>> for ExeUnit<R> {
+    type Result = <RpcEnvelope<GetExecBatches> as Message>::Result;
+
+    fn handle(&mut self, msg: RpcEnvelope<GetExecBatches>, _: &mut Self::Context) -> Self::Result {
+        self.ctx.verify_activity_id(&msg.activity_id)?;
+        self.ctx
+            .verify_caller(msg.caller(), AccessRole::Observe, GetExecBatches::ID)?;
+
+        let mut batches = self
+            .state
+            .batches
+            .values()
+            .map(|b| b.batch_state())
+            .collect::<Vec<_>>();
+        batches.sort_by(|a, b| a.batch_id.cmp(&b.batch_id));
+        Ok(batches)
+    }
+}
+
 impl<R: Runtime> Handler<RpcStreamCall<StreamExecBatchResults>> for ExeUnit<R> {
     type Result = ActorResponse<Self, Result<(), RpcError>>;
 
@@ -190,6 +426,12 @@ impl<R: Runtime> Handler<RpcStreamCall<StreamExecBatchResults>> for ExeUnit<R> {
         if let Err(e) = self.ctx.verify_activity_id(&msg.body.activity_id) {
             return ActorResponse::reply(Err(RpcError::GsbBadRequest(e.to_string())));
         }
+        if let Err(e) =
+            self.ctx
+                .verify_caller(&msg.caller, AccessRole::Observe, StreamExecBatchResults::ID)
+        {
+            return ActorResponse::reply(Err(RpcError::GsbFailure(e.to_string())));
+        }
         let batch = match self.state.batches.get_mut(&msg.body.batch_id) {
             Some(batch) => batch,
             _ => {
@@ -222,6 +464,14 @@ impl<R: Runtime> Handler<RpcEnvelope<sgx::CallEncryptedService>> for ExeUnit<R>
         use futures::prelude::*;
         use ya_client_model::activity::encrypted::{Request, RequestCommand, Response};
 
+        if let Err(e) = self.ctx.verify_caller(
+            msg.caller(),
+            AccessRole::Control,
+            sgx::CallEncryptedService::ID,
+        ) {
+            return future::err(e.into()).boxed_local();
+        }
+
         let me = ctx.address();
         let dec = self.ctx.crypto.ctx();
         let enc = self.ctx.crypto.ctx();
@@ -300,6 +550,59 @@ impl<R: Runtime> Handler<RpcEnvelope<sgx::CallEncryptedService>> for ExeUnit<R>
     }
 }
 
+#[cfg(feature = "sgx")]
+impl<R: Runtime> Handler<RpcEnvelope<sgx::GetAttestationEvidence>> for ExeUnit<R> {
+    type Result = ResponseActFuture<Self, Result<sgx::AttestationEvidence, RpcMessageError>>;
+
+    fn handle(
+        &mut self,
+        msg: RpcEnvelope<sgx::GetAttestationEvidence>,
+        _: &mut Context<Self>,
+    ) -> Self::Result {
+        use crate::attestation::{attest, Evidence};
+
+        fn response(evidence: &Evidence) -> sgx::AttestationEvidence {
+            sgx::AttestationEvidence {
+                credentials: evidence.credentials.clone(),
+                quote_status: evidence.quote_status.clone(),
+                issued_at: evidence.issued_at,
+            }
+        }
+
+        if let Err(e) = self.ctx.verify_activity_id(&msg.activity_id).and_then(|_| {
+            self.ctx.verify_caller(
+                msg.caller(),
+                AccessRole::Observe,
+                sgx::GetAttestationEvidence::ID,
+            )
+        }) {
+            return Box::pin(actix::fut::ready(Err(e.into())));
+        }
+
+        if let Some(evidence) = self.ctx.attestation.cached(Utc::now()) {
+            return Box::pin(actix::fut::ready(Ok(response(evidence))));
+        }
+
+        log::info!("Refreshing enclave attestation evidence");
+        let fut = attest(
+            self.ctx.crypto.clone(),
+            self.ctx.activity_id.to_owned(),
+            self.ctx.agreement.task_package.to_owned(),
+            self.ctx.attestation.policy.clone(),
+        )
+        .into_actor(self)
+        .map(|result, actor, _| {
+            let evidence = result?;
+            let response = response(&evidence);
+            actor.ctx.credentials = Some(evidence.credentials.clone());
+            actor.ctx.attestation.evidence = Some(evidence);
+            Ok(response)
+        });
+
+        Box::pin(fut)
+    }
+}
+
 #[cfg(feature = "sgx")]
 fn rpc_to_sgx_error(error: RpcMessageError) -> SgxMessageError {
     match error {
@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::activity::{ActivityState, ActivityUsage};
+use ya_core_model::activity::local::{SetState as SetActivityState, SetUsage};
+
+use crate::error::Error;
+use crate::report;
+use crate::state::{State, StatePair};
+
+pub const JOURNAL_FILE: &str = ".exe-unit-journal.json";
+
+/// Last known ExeUnit state, persisted in the work dir on every change
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub activity_id: Option<String>,
+    pub state: StatePair,
+    pub batch_id: Option<String>,
+    pub command_idx: Option<usize>,
+    pub usage: Option<Vec<f64>>,
+    pub timestamp: i64,
+}
+
+impl JournalEntry {
+    pub fn terminated(&self) -> bool {
+        self.state == StatePair::from(State::Terminated)
+    }
+
+    fn crash_reason(&self) -> String {
+        match (&self.batch_id, self.command_idx) {
+            (Some(batch_id), Some(idx)) => format!(
+                "ExeUnit crashed in state {:?} while executing command {} of batch {}",
+                self.state, idx, batch_id
+            ),
+            _ => format!("ExeUnit crashed in state {:?}", self.state),
+        }
+    }
+}
+
+/// Crash-recovery journal. Allows reporting a consistent terminal state and
+/// the final usage after the ExeUnit process was killed.
+pub struct Journal {
+    path: PathBuf,
+    entry: JournalEntry,
+}
+
+impl Journal {
+    pub fn new(work_dir: &Path, activity_id: Option<String>) -> Self {
+        Journal {
+            path: work_dir.join(JOURNAL_FILE),
+            entry: JournalEntry {
+                activity_id,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn read(work_dir: &Path) -> Result<JournalEntry, Error> {
+        let path = work_dir.join(JOURNAL_FILE);
+        let contents = std::fs::read(&path)
+            .map_err(|e| Error::Other(format!("Cannot read journal {}: {e}", path.display())))?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+
+    pub fn set_state(&mut self, state: StatePair) {
+        self.entry.state = state;
+        self.persist();
+    }
+
+    pub fn set_command(&mut self, batch_id: Option<String>, idx: Option<usize>) {
+        self.entry.batch_id = batch_id;
+        self.entry.command_idx = idx;
+        self.persist();
+    }
+
+    pub fn set_usage(&mut self, usage: Vec<f64>) {
+        if self.entry.usage.as_ref() == Some(&usage) {
+            return;
+        }
+        self.entry.usage = Some(usage);
+        self.persist();
+    }
+
+    fn persist(&mut self) {
+        self.entry.timestamp = Utc::now().timestamp();
+        if let Err(e) = write(&self.path, &self.entry) {
+            log::warn!("Unable to write journal {}: {e}", self.path.display());
+        }
+    }
+}
+
+fn write(path: &Path, entry: &JournalEntry) -> Result<(), Error> {
+    // write & rename, so that a crash never leaves a partially written journal
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(entry)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Reports the final usage and `Terminated` state of an activity whose ExeUnit
+/// exited without reaching a terminal state
+pub async fn recover(work_dir: &Path, activity_id: &str, report_url: &str) -> Result<(), Error> {
+    let mut entry = Journal::read(work_dir)?;
+    if entry.activity_id.as_deref() != Some(activity_id) {
+        return Err(Error::Other(format!(
+            "Journal does not belong to activity {activity_id}"
+        )));
+    }
+    if entry.terminated() {
+        log::info!("Activity {activity_id} has already been terminated");
+        return Ok(());
+    }
+
+    if let Some(usage) = entry.usage.clone() {
+        let msg = SetUsage {
+            activity_id: activity_id.to_string(),
+            usage: ActivityUsage {
+                current_usage: Some(usage),
+                timestamp: entry.timestamp,
+            },
+            timeout: None,
+        };
+        if !report(report_url, msg).await {
+            return Err(Error::Other("Unable to report final usage".into()));
+        }
+    }
+
+    let reason = entry.crash_reason();
+    log::warn!("Recovering activity {activity_id}: {reason}");
+
+    let msg = SetActivityState::new(
+        activity_id.to_string(),
+        ActivityState {
+            state: State::Terminated.into(),
+            reason: Some("execution aborted".into()),
+            error_message: Some(reason),
+        },
+        None,
+    );
+    if !report(report_url, msg).await {
+        return Err(Error::Other("Unable to report terminal state".into()));
+    }
+
+    entry.state = State::Terminated.into();
+    write(&work_dir.join(JOURNAL_FILE), &entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn journal_roundtrip() {
+        let dir = tempdir::TempDir::new("journal").unwrap();
+        let mut journal = Journal::new(dir.path(), Some("act".into()));
+
+        journal.set_state(StatePair(State::Ready, Some(State::Ready)));
+        journal.set_command(Some("batch".into()), Some(2));
+        journal.set_usage(vec![1.0, 2.5]);
+
+        let entry = Journal::read(dir.path()).unwrap();
+        assert_eq!(entry.activity_id.as_deref(), Some("act"));
+        assert_eq!(entry.state, StatePair(State::Ready, Some(State::Ready)));
+        assert_eq!(entry.batch_id.as_deref(), Some("batch"));
+        assert_eq!(entry.command_idx, Some(2));
+        assert_eq!(entry.usage, Some(vec![1.0, 2.5]));
+        assert!(!entry.terminated());
+
+        journal.set_state(State::Terminated.into());
+        assert!(Journal::read(dir.path()).unwrap().terminated());
+    }
+}
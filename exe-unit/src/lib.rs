@@ -11,12 +11,16 @@ use ya_client_model::activity::ExeScriptCommand;
 use ya_core_model::activity;
 use ya_service_bus::RpcEnvelope;
 use ya_transfer::transfer::TransferService;
+use ya_transfer::MinThroughput;
 use ya_utils_path::normalize_path;
 
+use crate::acl::Acl;
 use crate::agreement::Agreement;
 use crate::error::Error;
 use crate::manifest::ManifestContext;
 use crate::message::{GetState, GetStateResponse, Register};
+use crate::output::{OutputConfig, OUTPUT_LOG_DIR};
+use crate::runtime::environment::EnvPolicy;
 use crate::runtime::process::RuntimeProcess;
 use crate::service::signal::SignalMonitor;
 use crate::state::Supervision;
@@ -24,9 +28,12 @@ use crate::state::Supervision;
 mod acl;
 pub mod agreement;
 #[cfg(feature = "sgx")]
+pub mod attestation;
+#[cfg(feature = "sgx")]
 pub mod crypto;
 pub mod error;
 mod handlers;
+pub mod journal;
 pub mod logger;
 pub mod manifest;
 pub mod message;
@@ -61,7 +68,7 @@ pub struct Cli {
         number_of_values = 1,
     )]
     pub runtime_arg: Vec<String>,
-    /// Enclave secret key used in secure communication
+    /// Enclave secret key used in secure communication and to decrypt requestor secrets
     #[structopt(
         long,
         env = "EXE_UNIT_SEC_KEY",
@@ -127,6 +134,16 @@ pub enum Command {
         #[structopt(flatten)]
         args: RunArgs,
     },
+    /// Report the terminal state of an activity after its ExeUnit crashed
+    Recover {
+        /// ExeUnit service ID
+        service_id: String,
+        /// ExeUnit daemon GSB URL
+        report_url: String,
+        /// Working directory of the crashed ExeUnit
+        #[structopt(long, short)]
+        work_dir: PathBuf,
+    },
     /// Print an offer template in JSON format
     OfferTemplate,
     /// Run runtime's test command
@@ -144,6 +161,39 @@ pub struct RunArgs {
     /// Common cache directory
     #[structopt(long, short)]
     pub cache_dir: PathBuf,
+    /// Maximum size of the common cache directory, e.g. `20GiB`.
+    /// Least recently used images are removed when exceeded
+    #[structopt(long, env = "EXE_UNIT_CACHE_MAX_SIZE")]
+    pub cache_max_size: Option<bytesize::ByteSize>,
+    /// Terminates the activity when no commands were executed and no processes were running
+    /// in the runtime for given time, e.g. `30min`.
+    /// Prevents abandoned activities from occupying resources until Agreement expiration
+    #[structopt(long, env = "EXE_UNIT_IDLE_TIMEOUT", parse(try_from_str = humantime::parse_duration))]
+    pub idle_timeout: Option<std::time::Duration>,
+    /// Minimum download throughput per second, e.g. `100KiB`. Slower sources are abandoned
+    /// in favour of the next mirror, or retried when there are no more mirrors
+    #[structopt(long, env = "EXE_UNIT_TRANSFER_MIN_THROUGHPUT")]
+    pub transfer_min_throughput: Option<bytesize::ByteSize>,
+    /// Time window over which the minimum transfer throughput is measured
+    #[structopt(
+        long,
+        env = "EXE_UNIT_TRANSFER_STALL_WINDOW",
+        parse(try_from_str = humantime::parse_duration),
+        default_value = "30s"
+    )]
+    pub transfer_stall_window: std::time::Duration,
+    /// Maximum size of a single command output stream kept in memory.
+    /// Only the most recent output is kept when exceeded
+    #[structopt(long, env = "EXE_UNIT_OUTPUT_BUFFER_SIZE", default_value = "8MiB")]
+    pub output_buffer_size: bytesize::ByteSize,
+    /// Enables writing complete command output to log files in the work directory,
+    /// up to the given size per output stream, e.g. `100MiB`
+    #[structopt(long, env = "EXE_UNIT_OUTPUT_LOG_SIZE")]
+    pub output_log_size: Option<bytesize::ByteSize>,
+    /// Maximum size of all command output log files together.
+    /// Logs are removed when the ExeUnit shuts down
+    #[structopt(long, env = "EXE_UNIT_OUTPUT_LOG_TOTAL_SIZE", default_value = "1GiB")]
+    pub output_log_total_size: bytesize::ByteSize,
 }
 
 fn create_path(path: &PathBuf) -> anyhow::Result<PathBuf> {
@@ -252,6 +302,14 @@ pub async fn run(mut cli: Cli) -> anyhow::Result<()> {
             ctx_report_url = Some(report_url.clone());
             args
         }
+        Command::Recover {
+            service_id,
+            report_url,
+            work_dir,
+        } => {
+            journal::recover(&work_dir, &service_id, &report_url).await?;
+            return Ok(());
+        }
         Command::OfferTemplate => {
             let args = cli.runtime_arg.clone();
             let offer_template = ExeUnit::<RuntimeProcess>::offer_template(cli.binary, args)?;
@@ -345,6 +403,32 @@ pub async fn exe_unit(mut config: ExeUnitConfig) -> anyhow::Result<Addr<ExeUnit<
     log::info!("Manifest-enabled features: {:?}", manifest_ctx.features());
     log::info!("User-provided payload: {:?}", agreement.task_package);
 
+    let acl = Acl::from_agreement(&agreement.inner)
+        .context("Invalid access control list")?
+        .with_reporter(config.report_url.clone(), config.service_id.clone());
+
+    let output = OutputConfig {
+        buffer_size: usize::try_from(args.output_buffer_size.as_u64()).unwrap_or(usize::MAX),
+        log_dir: args.output_log_size.map(|_| work_dir.join(OUTPUT_LOG_DIR)),
+        log_size: args
+            .output_log_size
+            .map(|size| size.as_u64())
+            .unwrap_or(u64::MAX),
+        log_total_size: args.output_log_total_size.as_u64(),
+        ..Default::default()
+    };
+
+    #[cfg(feature = "sgx")]
+    let crypto = init_crypto(
+        config.sec_key.replace("<hidden>".into()),
+        config.requestor_pub_key.clone(),
+    )?;
+    #[cfg(feature = "sgx")]
+    let secrets_key = Some(crypto.secrets_key());
+    // Secrets need a key the requestor can trust, which only an enclave provides
+    #[cfg(not(feature = "sgx"))]
+    let secrets_key = None;
+
     let ctx = ExeUnitContext {
         supervise: Supervision {
             hardware: config.supervise.hardware,
@@ -356,18 +440,42 @@ pub async fn exe_unit(mut config: ExeUnitConfig) -> anyhow::Result<Addr<ExeUnit<
         agreement,
         work_dir,
         cache_dir,
+        cache_max_size: args.cache_max_size.map(|size| size.as_u64()),
+        idle_timeout: args.idle_timeout,
+        transfer_min_throughput: args
+            .transfer_min_throughput
+            .map(|min| MinThroughput::new(min.as_u64(), args.transfer_stall_window)),
+        output,
         runtime_args: config.runtime_args,
-        acl: Default::default(),
+        acl,
         credentials: None,
+        traffic: Default::default(),
+        env_policy: EnvPolicy::from_env()?,
+        secrets_key,
+        #[cfg(feature = "sgx")]
+        crypto,
         #[cfg(feature = "sgx")]
-        crypto: init_crypto(
-            config.sec_key.replace("<hidden>".into()),
-            config.requestor_pub_key.clone(),
-        )?,
+        attestation: crate::attestation::Attestation::new(
+            crate::attestation::AttestationPolicy::from_env()?,
+        ),
     };
 
     log::debug!("ExeUnitContext args: {:?}", ctx);
 
+    let (binary, runtime_args) = (config.binary.clone(), ctx.runtime_args.clone());
+    let runtime_template =
+        tokio::task::spawn_blocking(move || RuntimeProcess::offer_template(binary, runtime_args))
+            .await??;
+    let unsupported = ctx
+        .agreement
+        .unsupported_counters(&service::counters::supported_counters(&runtime_template));
+    if !unsupported.is_empty() {
+        log::warn!(
+            "Usage vector contains counters not declared by the runtime: {}",
+            unsupported.join(", ")
+        );
+    }
+
     let counters = service::counters::build(&ctx, Some(1000), ctx.supervise.hardware).start();
     let transfers = TransferService::new((&ctx).into()).start();
     let runtime = RuntimeProcess::new(&ctx, config.binary).start();
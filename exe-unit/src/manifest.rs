@@ -14,8 +14,12 @@ use url::Url;
 use crate::dns::{StableResolver, DNS_PORT};
 use ya_agreement_utils::AgreementView;
 use ya_client_model::activity::ExeScriptCommand;
-use ya_manifest_utils::{read_manifest, AppManifest, ArgMatch, Command, Feature, Script};
+use ya_core_model::activity::local::DnsResolution;
+use ya_manifest_utils::{
+    read_manifest, AppManifest, ArgMatch, Command, Feature, OutboundAccess, Script,
+};
 use ya_manifest_utils::{Policy, PolicyConfig};
+use ya_transfer::TransferUrl;
 use ya_utils_networking::vpn::Protocol;
 
 type ValidatorMap = HashMap<Validator, Box<dyn Any>>;
@@ -388,6 +392,7 @@ impl FromStr for ScriptValidator {
 #[derive(Clone)]
 pub struct UrlValidator {
     inner: Arc<AllowedAccess>,
+    access: Arc<OutboundAccess>,
     resolver: Option<Arc<StableResolver>>,
 }
 
@@ -415,8 +420,8 @@ impl ManifestValidator for UrlValidator {
 
         async move {
             if let Some(access) = access {
-                match access {
-                    ya_manifest_utils::OutboundAccess::Urls(urls) => {
+                match &access {
+                    OutboundAccess::Urls(urls) => {
                         let resolver = crate::dns::resolver().await?;
 
                         // by default we whitelist well known dns servers.
@@ -430,11 +435,13 @@ impl ManifestValidator for UrlValidator {
 
                         Ok(Some(Self {
                             inner: Arc::new(AllowedAccess::Urls(set)),
+                            access: Arc::new(access),
                             resolver: Some(Arc::new(resolver)),
                         }))
                     }
-                    ya_manifest_utils::OutboundAccess::Unrestricted => Ok(Some(Self {
+                    OutboundAccess::Unrestricted => Ok(Some(Self {
                         inner: Arc::new(AllowedAccess::Unrestricted),
+                        access: Arc::new(access),
                         resolver: None,
                     })),
                 }
@@ -462,9 +469,57 @@ impl UrlValidator {
         }
     }
 
+    /// Validates URLs of `transfer` commands against the outbound URL patterns.
+    /// Unlike connections from inside the VM, which are filtered by address and port only,
+    /// transfers are performed by the ExeUnit, so also the scheme and path are enforced.
+    /// Every mirror of a source is validated, and only local paths are exempt.
+    pub fn validate_transfers<'a>(
+        &self,
+        iter: impl IntoIterator<Item = &'a ExeScriptCommand>,
+    ) -> Result<(), ValidationError> {
+        iter.into_iter().try_for_each(|cmd| match cmd {
+            ExeScriptCommand::Transfer { from, to, .. } => [from, to]
+                .into_iter()
+                .try_for_each(|url| self.validate_url(url)),
+            _ => Ok(()),
+        })
+    }
+
+    /// Checks a URL reached by the ExeUnit on behalf of a transfer, e.g. a registry redirect.
+    pub fn allows(&self, url: &Url) -> bool {
+        self.access.allows(url)
+    }
+
+    /// Validates all mirrors of a deployed image.
+    pub fn validate_image(&self, image: &str) -> Result<(), ValidationError> {
+        self.validate_url(image)
+    }
+
+    fn validate_url(&self, url: &str) -> Result<(), ValidationError> {
+        let urls = TransferUrl::parse_source(url, "container")
+            .map_err(|e| ValidationError::Url(format!("invalid URL {url}: {e}")))?;
+        urls.into_iter()
+            .map(|transfer_url| transfer_url.url)
+            .filter(|url| TransferUrl::is_local(url).not())
+            .try_for_each(|url| {
+                self.access
+                    .allows(&url)
+                    .then_some(())
+                    .ok_or_else(|| ValidationError::Url(format!("URL not allowed: {url}")))
+            })
+    }
+
     pub fn stable_dns(&self) -> Option<IpAddr> {
         self.resolver.as_ref().map(|r| r.stable_dns())
     }
+
+    /// Host names resolved on behalf of the activity.
+    pub fn resolutions(&self) -> Vec<DnsResolution> {
+        self.resolver
+            .as_ref()
+            .map(|r| r.resolutions())
+            .unwrap_or_default()
+    }
 }
 
 async fn resolve_ips<'a>(
@@ -474,6 +529,8 @@ async fn resolve_ips<'a>(
     futures::stream::iter(urls)
         .map(Ok)
         .try_fold(HashSet::default(), |mut set, url| async move {
+            // Connections are filtered on the transport layer, so only the transport
+            // protocol and port implied by the scheme are enforced here.
             let protocol = match url.scheme() {
                 "udp" => Protocol::Udp,
                 _ => Protocol::Tcp,
@@ -710,4 +767,47 @@ mod tests {
         .unwrap();
         validator.validate(&commands).unwrap();
     }
+
+    #[test]
+    fn url_validator_transfers() {
+        let validator = UrlValidator {
+            inner: Arc::new(AllowedAccess::Unrestricted),
+            access: Arc::new(OutboundAccess::Urls(vec![Url::parse(
+                "https://example.com/data/*",
+            )
+            .unwrap()])),
+            resolver: None,
+        };
+        let transfer = |from: &str, to: &str| ExeScriptCommand::Transfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            args: Default::default(),
+            progress: None,
+        };
+        let validate = |from: &str, to: &str| validator.validate_transfers(&[transfer(from, to)]);
+
+        assert!(validate("https://example.com/data/a", "container:/a").is_ok());
+        assert!(validate("container:/out", "https://example.com/data/out").is_ok());
+        assert!(validate("https://example.com/other", "container:/a").is_err());
+        assert!(validate("http://example.com/data/a", "container:/a").is_err());
+        assert!(validate("container:/out", "https://example.com:8443/data/out").is_err());
+
+        // Hashed and mirrored sources are validated as a whole
+        let hash = "hash:sha3:0a0b:";
+        assert!(validate(&format!("{hash}https://example.com/data/a"), "container:/a").is_ok());
+        assert!(validate(&format!("{hash}https://evil.com/a"), "container:/a").is_err());
+        assert!(validate(
+            "https://example.com/data/a|https://evil.com/a",
+            "container:/a"
+        )
+        .is_err());
+
+        let image = "hash:sha3:0a0b:https://example.com/data/a|https://evil.com/a";
+        assert!(validator.validate_image(image).is_err());
+
+        // Other schemes are denied unless allowed explicitly
+        assert!(validate("gftp://0x0add/a", "container:/a").is_err());
+        assert!(validate("oci://registry.com/image:latest", "container:/a").is_err());
+        assert!(validate("ftp://example.com/data/a", "container:/a").is_err());
+    }
 }
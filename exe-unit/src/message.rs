@@ -1,4 +1,6 @@
 use crate::error::Error;
+use crate::runtime::environment::RuntimeEnv;
+use crate::runtime::sidecar::Sidecar;
 use crate::runtime::RuntimeMode;
 use crate::state::CommandStateRepr;
 use crate::Result;
@@ -126,6 +128,11 @@ pub enum RuntimeEvent {
         name: String,
         value: f64,
     },
+    CounterDefinition {
+        name: String,
+        unit: String,
+        monotonic: bool,
+    },
 }
 
 impl RuntimeEvent {
@@ -174,12 +181,30 @@ pub struct UpdateDeployment {
     pub runtime_mode: Option<RuntimeMode>,
     pub networks: Option<Vec<Network>>,
     pub hosts: Option<HashMap<String, String>>,
+    pub sidecars: Option<Vec<Sidecar>>,
+    pub env: Option<RuntimeEnv>,
+    pub stdin: Option<bool>,
 }
 
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "Result<()>")]
 pub struct Initialize;
 
+/// Writes data to stdin of a process started by `Run` command in the runtime service.
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct WriteStdin {
+    pub batch_id: String,
+    pub idx: usize,
+    pub data: Vec<u8>,
+    pub eof: bool,
+}
+
+/// Returns the number of processes currently running in the runtime.
+#[derive(Clone, Debug, Default, Message)]
+#[rtype(result = "usize")]
+pub struct GetRunningProcesses;
+
 #[derive(Clone, Debug, PartialEq, Eq, Message)]
 #[rtype(result = "()")]
 pub struct Register<Svc>(pub Addr<Svc>)
@@ -192,6 +217,13 @@ pub struct SignExeScript {
     pub batch_id: String,
 }
 
+/// Resolves variables and secrets of a Deploy command with the provider's policy
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<RuntimeEnv>")]
+pub struct ResolveEnv {
+    pub env: HashMap<String, String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignExeScriptResponse {
     pub output: String,
@@ -224,6 +256,8 @@ pub enum ShutdownReason {
     Interrupted(i32),
     #[error("Usage limit exceeded: {0}")]
     UsageLimitExceeded(String),
+    #[error("No commands executed for {}", humantime::format_duration(*.0))]
+    Idle(std::time::Duration),
     #[error("{0}")]
     Error(#[from] Error),
 }
@@ -3,6 +3,7 @@ use std::mem::size_of;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::Path;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -37,6 +38,35 @@ type SocketChannel = (
 const SOCKET_BUFFER_SIZE: usize = 2097152;
 const BUFFER_SIZE: usize = DEFAULT_MAX_FRAME_SIZE * 4;
 
+/// Byte counters of traffic passing through the network services of an activity.
+///
+/// Inbound traffic is directed to the runtime, outbound traffic originates from the runtime.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkTraffic {
+    inbound: Arc<AtomicU64>,
+    outbound: Arc<AtomicU64>,
+}
+
+impl NetworkTraffic {
+    #[inline]
+    pub fn add_inbound(&self, bytes: usize) {
+        self.inbound.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn add_outbound(&self, bytes: usize) {
+        self.outbound.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn inbound(&self) -> Arc<AtomicU64> {
+        self.inbound.clone()
+    }
+
+    pub fn outbound(&self) -> Arc<AtomicU64> {
+        self.outbound.clone()
+    }
+}
+
 pub(crate) enum Endpoint {
     New {
         local: LocalEndpoint,
@@ -529,9 +559,14 @@ where
     }
 }
 
+/// GSB address of the VPN network `net_id` on a remote node
+fn gsb_address(node_id: &str, net_id: &str) -> String {
+    format!("/net/{}/vpn/{}", node_id, net_id)
+}
+
 fn gsb_endpoint(node_id: &str, net_id: &str) -> DuoEndpoint<GsbEndpoint> {
     DuoEndpoint {
-        tcp: typed::service(format!("/net/{}/vpn/{}", node_id, net_id)),
+        tcp: typed::service(gsb_address(node_id, net_id)),
         udp: typed::service(format!("/udp/net/{}/vpn/{}/raw", node_id, net_id)),
     }
 }
@@ -46,7 +46,7 @@ use ya_utils_networking::vpn::{
 use crate::dns::DNS_PORT;
 use crate::manifest::UrlValidator;
 use crate::message::Shutdown;
-use crate::network::Endpoint;
+use crate::network::{Endpoint, NetworkTraffic};
 use crate::{dns, Error, Result};
 
 // 10.0.0.0/8 is a reserved private address space
@@ -74,6 +74,7 @@ pub(crate) async fn start_inet<R: RuntimeService>(
     mut endpoint: Endpoint,
     service: &R,
     filter: Option<UrlValidator>,
+    traffic: NetworkTraffic,
 ) -> Result<Addr<Inet>> {
     use ya_runtime_api::server::Network;
 
@@ -125,23 +126,25 @@ pub(crate) async fn start_inet<R: RuntimeService>(
         }
     };
 
-    Ok(Inet::new(endpoint, filter).start())
+    Ok(Inet::new(endpoint, filter, traffic).start())
 }
 
 pub(crate) struct Inet {
     network: net::Network,
     endpoint: Endpoint,
     proxy: Proxy,
+    traffic: NetworkTraffic,
 }
 
 impl Inet {
-    pub fn new(endpoint: Endpoint, filter: Option<UrlValidator>) -> Self {
+    pub fn new(endpoint: Endpoint, filter: Option<UrlValidator>, traffic: NetworkTraffic) -> Self {
         let network = Self::create_network();
         let proxy = Proxy::new(network.clone(), filter);
         Self {
             network,
             endpoint,
             proxy,
+            traffic,
         }
     }
 
@@ -206,7 +209,7 @@ impl Actor for Inet {
             .egress_receiver()
             .expect("Egress receiver already taken");
 
-        inet_endpoint_egress_handler(rx, router)
+        inet_endpoint_egress_handler(rx, router, self.traffic.clone())
             .into_actor(self)
             .spawn(ctx);
 
@@ -214,7 +217,7 @@ impl Actor for Inet {
             .into_actor(self)
             .spawn(ctx);
 
-        inet_egress_handler(egress_rx, tx)
+        inet_egress_handler(egress_rx, tx, self.traffic.clone())
             .into_actor(self)
             .spawn(ctx);
     }
@@ -239,12 +242,17 @@ impl Handler<Shutdown> for Inet {
 }
 
 /// Receives packets from ExeUnit Runtime and forwards them to proxy network stack for dispatching.
-async fn inet_endpoint_egress_handler(mut rx: BoxStream<'static, Result<Vec<u8>>>, router: Router) {
+async fn inet_endpoint_egress_handler(
+    mut rx: BoxStream<'static, Result<Vec<u8>>>,
+    router: Router,
+    traffic: NetworkTraffic,
+) {
     while let Some(result) = rx.next().await {
         let packet = match result {
             Ok(vec) => vec,
             Err(err) => return log::debug!("[inet] runtime -> inet error: {err}"),
         };
+        traffic.add_outbound(packet.len());
 
         // If we failed during handling packet, we should save the error for later.
         // First connection must be established in network stack, so we can close it.
@@ -332,10 +340,12 @@ async fn inet_ingress_handler(rx: IngressReceiver, proxy: Proxy) {
 async fn inet_egress_handler<E: std::fmt::Display>(
     rx: EgressReceiver,
     fwd: tokio::sync::mpsc::UnboundedSender<std::result::Result<Vec<u8>, E>>,
+    traffic: NetworkTraffic,
 ) {
     let mut rx = UnboundedReceiverStream::new(rx);
     while let Some(event) = rx.next().await {
         let frame = event.payload.into_vec();
+        traffic.add_inbound(frame.len());
 
         let desc = dispatch_desc(&frame)
             .map(|desc| format!("{desc:?}"))
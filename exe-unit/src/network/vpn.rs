@@ -9,15 +9,15 @@ use ya_core_model::identity;
 use ya_runtime_api::deploy::ContainerEndpoint;
 use ya_runtime_api::server::{CreateNetwork, NetworkInterface, RuntimeService};
 use ya_service_bus::typed::Endpoint as GsbEndpoint;
-use ya_service_bus::{actix_rpc, typed, RpcEndpoint, RpcEnvelope, RpcRawCall};
+use ya_service_bus::{actix_rpc, typed, RpcEndpoint, RpcEnvelope, RpcMessage, RpcRawCall};
 use ya_utils_networking::vpn::network::DuoEndpoint;
 use ya_utils_networking::vpn::{common::ntoh, Error as NetError, PeekPacket};
 use ya_utils_networking::vpn::{ArpField, ArpPacket, EtherFrame, EtherType, IpPacket, Networks};
 
-use crate::acl::Acl;
+use crate::acl::{AccessRole, Acl};
 use crate::error::Error;
 use crate::message::Shutdown;
-use crate::network::{self, Endpoint};
+use crate::network::{self, Endpoint, NetworkTraffic};
 use crate::state::Deployment;
 
 pub(crate) async fn start_vpn<R: RuntimeService>(
@@ -25,6 +25,7 @@ pub(crate) async fn start_vpn<R: RuntimeService>(
     acl: Acl,
     service: &R,
     deployment: &Deployment,
+    traffic: NetworkTraffic,
 ) -> crate::Result<Option<Addr<Vpn>>> {
     if !deployment.networking() {
         return Ok(None);
@@ -67,17 +68,16 @@ pub(crate) async fn start_vpn<R: RuntimeService>(
         }
     };
 
-    let vpn = Vpn::try_new(node_id, acl, endpoint, deployment.clone())?;
+    let vpn = Vpn::try_new(node_id, acl, endpoint, deployment.clone(), traffic)?;
     Ok(Some(vpn.start()))
 }
 
 pub(crate) struct Vpn {
     default_id: String,
-    // TODO: Populate & utilize ACL
-    #[allow(unused)]
     acl: Acl,
     networks: Networks<DuoEndpoint<GsbEndpoint>>,
     endpoint: Endpoint,
+    traffic: NetworkTraffic,
 }
 
 impl Vpn {
@@ -86,6 +86,7 @@ impl Vpn {
         acl: Acl,
         endpoint: Endpoint,
         deployment: Deployment,
+        traffic: NetworkTraffic,
     ) -> crate::Result<Self> {
         let mut networks = Networks::default();
 
@@ -94,19 +95,28 @@ impl Vpn {
             .iter()
             .try_for_each(|(id, net)| networks.add(id.clone(), net.network))?;
 
-        deployment.networks.into_iter().try_for_each(|(id, net)| {
-            let network = networks.get_mut(&id).unwrap();
-            net.nodes
-                .into_iter()
-                .try_for_each(|(ip, id)| network.add_node(ip, &id, network::gsb_endpoint))?;
-            Ok::<_, NetError>(())
-        })?;
+        deployment
+            .networks
+            .into_iter()
+            .try_for_each(|(net_id, net)| {
+                let network = networks.get_mut(&net_id).unwrap();
+                net.nodes.into_iter().try_for_each(|(ip, id)| {
+                    grant_host(&acl, &id);
+                    // Violation is reported, the node stays unreachable
+                    match acl.check_outbound(&network::gsb_address(&id, &net_id)) {
+                        Ok(_) => network.add_node(ip, &id, network::gsb_endpoint),
+                        Err(_) => Ok(()),
+                    }
+                })?;
+                Ok::<_, NetError>(())
+            })?;
 
         Ok(Self {
             default_id: node_id.to_string(),
             acl,
             networks,
             endpoint,
+            traffic,
         })
     }
 
@@ -119,6 +129,11 @@ impl Vpn {
         let node_id = packet.caller;
         let data = packet.data;
 
+        if !self.acl.has_access(&node_id, AccessRole::Host) {
+            log::debug!("[vpn] dropping packet from unknown node {node_id}");
+            return Ok(());
+        }
+
         // fixme: should requestor be queried for unknown IP addresses instead?
         // read and add unknown node id -> ip if it doesn't exist
         if let Ok(ether_type) = EtherFrame::peek_type(&data) {
@@ -136,8 +151,11 @@ impl Vpn {
             };
 
             if let Some(ip) = ip {
+                let acl = &self.acl;
                 let _ = self.networks.get_mut(&network_id).map(|network| {
-                    if !network.nodes().contains_key(&node_id) {
+                    if !network.nodes().contains_key(&node_id)
+                        && acl.may_call(&network::gsb_address(&node_id, &network_id))
+                    {
                         log::debug!("[vpn] adding new node: {} {}", ip, node_id);
                         let _ = network.add_node(ip, &node_id, network::gsb_endpoint);
                     }
@@ -145,6 +163,7 @@ impl Vpn {
             }
         }
 
+        self.traffic.add_inbound(data.len());
         if let Err(e) = self.endpoint.send(Ok(data)) {
             log::debug!("[vpn] ingress error: {}", e);
         }
@@ -275,6 +294,7 @@ impl StreamHandler<crate::Result<Vec<u8>>> for Vpn {
             Ok(vec) => vec,
             Err(err) => return log::debug!("[vpn] error (egress): {err}"),
         };
+        self.traffic.add_outbound(packet.len());
 
         ya_packet_trace::packet_trace_maybe!("exe-unit::Vpn::Handler<Egress>", {
             ya_packet_trace::try_extract_from_ip_frame(&packet)
@@ -343,14 +363,18 @@ impl Handler<RpcEnvelope<VpnControl>> for Vpn {
     type Result = <RpcEnvelope<VpnControl> as Message>::Result;
 
     fn handle(&mut self, msg: RpcEnvelope<VpnControl>, _: &mut Context<Self>) -> Self::Result {
-        // if !self.acl.has_access(msg.caller(), AccessRole::Control) {
-        //     return Err(AclError::Forbidden(msg.caller().to_string(), AccessRole::Control).into());
-        // }
+        self.acl
+            .check(msg.caller(), AccessRole::Control, VpnControl::ID)
+            .map_err(Error::from)?;
 
         match msg.into_inner() {
             VpnControl::AddNodes { network_id, nodes } => {
                 let network = self.networks.get_mut(&network_id).map_err(Error::from)?;
                 for (ip, id) in Deployment::map_nodes(nodes).map_err(Error::from)? {
+                    grant_host(&self.acl, &id);
+                    self.acl
+                        .check_outbound(&network::gsb_address(&id, &network_id))
+                        .map_err(Error::from)?;
                     network
                         .add_node(ip, &id, network::gsb_endpoint)
                         .map_err(Error::from)?;
@@ -361,7 +385,12 @@ impl Handler<RpcEnvelope<VpnControl>> for Vpn {
                 node_ids,
             } => {
                 let network = self.networks.get_mut(&network_id).map_err(Error::from)?;
-                node_ids.into_iter().for_each(|id| network.remove_node(&id));
+                node_ids.into_iter().for_each(|id| {
+                    if let Ok(node_id) = id.parse::<NodeId>() {
+                        self.acl.revoke(&node_id, AccessRole::Host);
+                    }
+                    network.remove_node(&id)
+                });
             }
         }
         Ok(())
@@ -378,6 +407,14 @@ impl Handler<Shutdown> for Vpn {
     }
 }
 
+/// Nodes of the Requestor's network may exchange traffic with the activity
+fn grant_host(acl: &Acl, id: &str) {
+    match id.parse::<NodeId>() {
+        Ok(node_id) => acl.grant(node_id, AccessRole::Host),
+        Err(e) => log::warn!("[vpn] invalid network node id '{id}': {e}"),
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "<RpcEnvelope<VpnPacket> as Message>::Result")]
 pub(crate) struct Packet {
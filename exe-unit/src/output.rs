@@ -1,10 +1,88 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
 use futures::channel::mpsc;
 use futures::StreamExt;
 use tokio_util::codec::{BytesCodec, FramedRead};
 use ya_client_model::activity::{CaptureFormat, CaptureMode, CapturePart, CommandOutput};
+use ya_core_model::activity::OutputStream;
 
 use crate::message::RuntimeEvent;
 
+pub const DEFAULT_OUTPUT_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+/// Output log directory, relative to the work dir
+pub const OUTPUT_LOG_DIR: &str = "output-logs";
+
+/// Limits of command output kept by the ExeUnit
+#[derive(Clone, Debug)]
+pub struct OutputConfig {
+    /// Maximum number of bytes of a single output stream kept in memory
+    pub buffer_size: usize,
+    /// Directory where complete output is written to. Disabled when `None`
+    pub log_dir: Option<PathBuf>,
+    /// Maximum size of a single output log file
+    pub log_size: u64,
+    /// Maximum size of all output log files together
+    pub log_total_size: u64,
+    /// Size of all output log files written so far
+    log_usage: Arc<AtomicU64>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            buffer_size: DEFAULT_OUTPUT_BUFFER_SIZE,
+            log_dir: None,
+            log_size: u64::MAX,
+            log_total_size: u64::MAX,
+            log_usage: Default::default(),
+        }
+    }
+}
+
+impl OutputConfig {
+    pub fn log_path(&self, batch_id: &str, idx: usize, stream: OutputStream) -> Option<PathBuf> {
+        let batch_id = batch_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect::<String>();
+        let ext = match stream {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        };
+        self.log_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{batch_id}-{idx}.{ext}")))
+    }
+
+    fn log(&self, batch_id: &str, idx: usize, stream: OutputStream) -> Option<OutputLog> {
+        self.log_path(batch_id, idx, stream).map(|path| {
+            OutputLog::new(
+                path,
+                self.log_size,
+                self.log_total_size,
+                self.log_usage.clone(),
+            )
+        })
+    }
+
+    /// Removes all output log files
+    pub fn remove_logs(&self) {
+        if let Some(dir) = self.log_dir.as_ref() {
+            match std::fs::remove_dir_all(dir) {
+                Ok(_) => self.log_usage.store(0, Ordering::Relaxed),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+                Err(e) => log::warn!("Unable to remove output logs {}: {e}", dir.display()),
+            }
+        }
+    }
+}
+
 pub(crate) async fn forward_output<F, R>(read: R, tx: &mpsc::Sender<RuntimeEvent>, f: F)
 where
     F: Fn(Vec<u8>) -> RuntimeEvent + 'static,
@@ -28,6 +106,7 @@ pub(crate) struct CapturedOutput {
     pub format: CaptureFormat,
     head: CaptureBuffer,
     tail: CaptureBuffer,
+    log: Option<OutputLog>,
 }
 
 impl CapturedOutput {
@@ -37,6 +116,7 @@ impl CapturedOutput {
             format: CaptureFormat::default(),
             head: CaptureBuffer::all(),
             tail: CaptureBuffer::discard(),
+            log: None,
         }
     }
 
@@ -46,14 +126,43 @@ impl CapturedOutput {
             format: CaptureFormat::default(),
             head: CaptureBuffer::discard(),
             tail: CaptureBuffer::discard(),
+            log: None,
         }
     }
 
+    /// Applies output limits. Unbounded buffers are replaced with ring buffers,
+    /// keeping the most recent output. Discarded output is not logged.
+    pub fn configure(
+        mut self,
+        config: &OutputConfig,
+        batch_id: &str,
+        idx: usize,
+        stream: OutputStream,
+    ) -> Self {
+        if self.is_discarded() {
+            return self;
+        }
+        self.head = self.head.bounded(config.buffer_size);
+        self.tail = self.tail.bounded(config.buffer_size);
+        self.log = config.log(batch_id, idx, stream);
+        self
+    }
+
+    fn is_discarded(&self) -> bool {
+        matches!(
+            (&self.head, &self.tail),
+            (CaptureBuffer::Discard, CaptureBuffer::Discard)
+        )
+    }
+
+    /// Path of the complete output log, if any output was written to it
+    pub fn log_path(&self) -> Option<&Path> {
+        self.log.as_ref().and_then(OutputLog::path)
+    }
+
     pub fn output(&self) -> Option<CommandOutput> {
-        let head = self.head.as_slice().unwrap_or(&[]);
-        let tail = self.tail.as_slice().unwrap_or(&[]);
-        let mut output = head.to_vec();
-        output.extend_from_slice(tail);
+        let mut output = self.head.to_vec().unwrap_or_default();
+        output.extend(self.tail.to_vec().unwrap_or_default());
 
         if output.is_empty() {
             None
@@ -73,6 +182,13 @@ impl CapturedOutput {
     }
 
     pub fn write<B: AsRef<[u8]> + ?Sized>(&mut self, bytes: &B) -> Option<CommandOutput> {
+        if let Some(log) = self.log.as_mut() {
+            if let Err(e) = log.write(bytes.as_ref()) {
+                log::warn!("Unable to write output log {}: {e}", log.path.display());
+                self.log = None;
+            }
+        }
+
         let bytes_head = self.head.write(bytes);
         let bytes_tail = self.tail.write(bytes);
         let bytes = bytes_head.or(bytes_tail);
@@ -116,6 +232,7 @@ impl From<Option<CaptureMode>> for CapturedOutput {
                     format: format.unwrap_or_default(),
                     head,
                     tail,
+                    log: None,
                 }
             }
             CaptureMode::Stream { limit, format } => CapturedOutput {
@@ -126,15 +243,71 @@ impl From<Option<CaptureMode>> for CapturedOutput {
                     None => CaptureBuffer::all(),
                 },
                 tail: CaptureBuffer::discard(),
+                log: None,
             },
         }
     }
 }
 
+/// Complete command output spilled to disk, up to a size limit
+/// and within the total size of all logs. The file is created on first write.
+pub(crate) struct OutputLog {
+    path: PathBuf,
+    file: Option<File>,
+    size: u64,
+    limit: u64,
+    total_limit: u64,
+    total_size: Arc<AtomicU64>,
+}
+
+impl OutputLog {
+    pub fn new(path: PathBuf, limit: u64, total_limit: u64, total_size: Arc<AtomicU64>) -> Self {
+        OutputLog {
+            path,
+            file: None,
+            size: 0,
+            limit,
+            total_limit,
+            total_size,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|_| self.path.as_path())
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let total_available = self
+            .total_limit
+            .saturating_sub(self.total_size.load(Ordering::Relaxed));
+        let available = (self.limit - self.size).min(total_available);
+        let available = usize::try_from(available).unwrap_or(usize::MAX);
+        let bytes = &bytes[..bytes.len().min(available)];
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        let file = match &mut self.file {
+            Some(file) => file,
+            file @ None => {
+                if let Some(dir) = self.path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                file.insert(File::create(&self.path)?)
+            }
+        };
+        file.write_all(bytes)?;
+        self.size += bytes.len() as u64;
+        self.total_size
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
 pub(crate) enum CaptureBuffer {
     All(Vec<u8>),
     Capped(Vec<u8>, usize),
-    Ring(Vec<u8>, usize),
+    Ring(VecDeque<u8>, usize),
     Discard,
 }
 
@@ -147,27 +320,37 @@ impl CaptureBuffer {
         if limit == 0 {
             return CaptureBuffer::Discard;
         }
-        CaptureBuffer::Capped(Vec::with_capacity(limit), limit)
+        CaptureBuffer::Capped(Vec::new(), limit)
     }
 
     pub fn ring(limit: usize) -> Self {
         if limit == 0 {
             return CaptureBuffer::Discard;
         }
-        CaptureBuffer::Ring(Vec::with_capacity(limit), limit)
+        CaptureBuffer::Ring(VecDeque::new(), limit)
     }
 
     pub fn discard() -> Self {
         CaptureBuffer::Discard
     }
+
+    /// Caps the buffer at `limit` bytes. Must be called before any output is written.
+    pub fn bounded(self, limit: usize) -> Self {
+        match self {
+            CaptureBuffer::All(_) => CaptureBuffer::ring(limit),
+            CaptureBuffer::Capped(_, size) if size > limit => CaptureBuffer::capped(limit),
+            CaptureBuffer::Ring(_, size) if size > limit => CaptureBuffer::ring(limit),
+            buffer => buffer,
+        }
+    }
 }
 
 impl CaptureBuffer {
-    pub fn as_slice(&self) -> Option<&[u8]> {
+    pub fn to_vec(&self) -> Option<Vec<u8>> {
         match self {
-            CaptureBuffer::All(vec) => Some(vec.as_slice()),
-            CaptureBuffer::Capped(vec, _) => Some(vec.as_slice()),
-            CaptureBuffer::Ring(vec, _) => Some(vec.as_slice()),
+            CaptureBuffer::All(vec) => Some(vec.clone()),
+            CaptureBuffer::Capped(vec, _) => Some(vec.clone()),
+            CaptureBuffer::Ring(deque, _) => Some(deque.iter().copied().collect()),
             CaptureBuffer::Discard => None,
         }
     }
@@ -195,28 +378,11 @@ impl CaptureBuffer {
                     Some(slice)
                 }
             }
-            CaptureBuffer::Ring(vec, limit) => {
-                let len = vec.len();
-                let slice = if sz > *limit {
-                    &bytes[sz - *limit..]
-                } else {
-                    bytes
-                };
-
-                let mut end_idx = len + slice.len();
-                let shift = if end_idx >= *limit {
-                    end_idx - *limit
-                } else {
-                    0
-                };
-                let start_idx = len - shift;
-                end_idx -= shift;
-
-                vec.rotate_left(shift);
-                if len < end_idx {
-                    vec.extend(std::iter::repeat(0).take(end_idx - len));
-                }
-                vec.as_mut_slice()[start_idx..end_idx].copy_from_slice(slice);
+            CaptureBuffer::Ring(deque, limit) => {
+                let slice = &bytes[sz.saturating_sub(*limit)..];
+                let overflow = (deque.len() + slice.len()).saturating_sub(*limit);
+                deque.drain(..overflow);
+                deque.extend(slice);
                 Some(slice)
             }
             CaptureBuffer::Discard => None,
@@ -246,38 +412,111 @@ mod tests {
     #[test]
     fn fixed_buffer() {
         let mut buf = CaptureBuffer::capped(5);
-        assert_eq!(buf.as_slice(), Some(&[][..]));
+        assert_eq!(buf.to_vec(), Some(vec![]));
         buf.write(&[]);
-        assert_eq!(buf.as_slice(), Some(&[][..]));
+        assert_eq!(buf.to_vec(), Some(vec![]));
 
         buf.write(&[0]);
-        assert_eq!(buf.as_slice(), Some(&[0][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0]));
         buf.write(&[1]);
-        assert_eq!(buf.as_slice(), Some(&[0, 1][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0, 1]));
         buf.write(&[2, 3, 4, 5, 6, 7, 8, 9, 10]);
-        assert_eq!(buf.as_slice(), Some(&[0, 1, 2, 3, 4][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0, 1, 2, 3, 4]));
         buf.write(&[11, 12, 13, 14, 15, 16]);
-        assert_eq!(buf.as_slice(), Some(&[0, 1, 2, 3, 4][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0, 1, 2, 3, 4]));
     }
 
     #[test]
     fn ring_buffer() {
         let mut buf = CaptureBuffer::ring(5);
-        assert_eq!(buf.as_slice(), Some(&[][..]));
+        assert_eq!(buf.to_vec(), Some(vec![]));
         buf.write(&[]);
-        assert_eq!(buf.as_slice(), Some(&[][..]));
+        assert_eq!(buf.to_vec(), Some(vec![]));
 
         buf.write(&[0]);
-        assert_eq!(buf.as_slice(), Some(&[0][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0]));
         buf.write(&[1, 2, 3, 4]);
-        assert_eq!(buf.as_slice(), Some(&[0, 1, 2, 3, 4][..]));
+        assert_eq!(buf.to_vec(), Some(vec![0, 1, 2, 3, 4]));
         buf.write(&[5]);
-        assert_eq!(buf.as_slice(), Some(&[1, 2, 3, 4, 5][..]));
+        assert_eq!(buf.to_vec(), Some(vec![1, 2, 3, 4, 5]));
         buf.write(&[0, 0, 0]);
-        assert_eq!(buf.as_slice(), Some(&[4, 5, 0, 0, 0][..]));
+        assert_eq!(buf.to_vec(), Some(vec![4, 5, 0, 0, 0]));
         buf.write(&[6, 7, 8, 9, 10][..]);
-        assert_eq!(buf.as_slice(), Some(&[6, 7, 8, 9, 10][..]));
+        assert_eq!(buf.to_vec(), Some(vec![6, 7, 8, 9, 10]));
         buf.write(&[6, 7, 8, 9, 10, 11, 12, 13, 14][..]);
-        assert_eq!(buf.as_slice(), Some(&[10, 11, 12, 13, 14][..]));
+        assert_eq!(buf.to_vec(), Some(vec![10, 11, 12, 13, 14]));
+    }
+
+    #[test]
+    fn bounded_output() {
+        let config = OutputConfig {
+            buffer_size: 4,
+            ..Default::default()
+        };
+        let mut output = CapturedOutput::all().configure(&config, "batch", 0, OutputStream::Stdout);
+        output.write(b"012345");
+        output.write(b"67");
+        assert_eq!(output.output_string().as_deref(), Some("4567"));
+
+        let mode = CaptureMode::AtEnd {
+            part: Some(CapturePart::Head(1024)),
+            format: None,
+        };
+        let mut output =
+            CapturedOutput::from(Some(mode)).configure(&config, "batch", 0, OutputStream::Stdout);
+        output.write(b"012345");
+        assert_eq!(output.output_string().as_deref(), Some("0123"));
+    }
+
+    #[test]
+    fn output_log() {
+        let dir = tempdir::TempDir::new("output-log").unwrap();
+        let config = OutputConfig {
+            buffer_size: 4,
+            log_dir: Some(dir.path().to_path_buf()),
+            log_size: 8,
+            ..Default::default()
+        };
+
+        let discarded = CapturedOutput::discard().configure(&config, "b", 0, OutputStream::Stdout);
+        assert!(discarded.log.is_none());
+
+        let mut output = CapturedOutput::all().configure(&config, "../b", 1, OutputStream::Stderr);
+        assert_eq!(output.log_path(), None);
+        output.write(b"012345");
+        output.write(b"6789");
+
+        let path = output.log_path().unwrap().to_path_buf();
+        assert_eq!(path, dir.path().join("b-1.stderr"));
+        assert_eq!(std::fs::read(path).unwrap(), b"01234567");
+        assert_eq!(output.output_string().as_deref(), Some("6789"));
+
+        config.remove_logs();
+        assert!(!dir.path().exists());
+    }
+
+    #[test]
+    fn output_log_total_size() {
+        let dir = tempdir::TempDir::new("output-log").unwrap();
+        let config = OutputConfig {
+            log_dir: Some(dir.path().to_path_buf()),
+            log_size: 8,
+            log_total_size: 12,
+            ..Default::default()
+        };
+
+        let mut first = CapturedOutput::all().configure(&config, "b", 0, OutputStream::Stdout);
+        first.write(b"0123456789");
+        let mut second = CapturedOutput::all().configure(&config, "b", 1, OutputStream::Stdout);
+        second.write(b"0123456789");
+        let mut third = CapturedOutput::all().configure(&config, "b", 2, OutputStream::Stdout);
+        third.write(b"0123456789");
+
+        assert_eq!(
+            std::fs::read(first.log_path().unwrap()).unwrap(),
+            b"01234567"
+        );
+        assert_eq!(std::fs::read(second.log_path().unwrap()).unwrap(), b"0123");
+        assert_eq!(third.log_path(), None);
     }
 }
@@ -2,14 +2,18 @@ use crate::message::*;
 use actix::prelude::*;
 use ya_runtime_api::deploy::StartMode;
 
+pub mod environment;
 mod event;
 pub mod process;
+pub mod sidecar;
 
 pub trait Runtime:
     Actor<Context = Context<Self>>
     + Handler<Shutdown>
     + Handler<ExecuteCommand>
     + Handler<UpdateDeployment>
+    + Handler<WriteStdin>
+    + Handler<GetRunningProcesses>
 {
 }
 
@@ -0,0 +1,367 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use openssl::symm::{decrypt_aead, Cipher};
+use secp256k1::ecdh::SharedSecret;
+use secp256k1::{PublicKey, SecretKey};
+
+use crate::error::Error;
+use crate::runtime::sidecar::SIDECARS_ENV_VAR;
+
+/// Deploy command environment variable carrying a JSON object of encrypted secrets,
+/// e.g. `{"API_TOKEN": "<hex>"}`
+pub const SECRETS_ENV_VAR: &str = "GOLEM_SECRETS";
+/// Deploy command environment variable, which set to `true` keeps stdin of processes
+/// started by `Run` commands open for `WriteStdin` requests
+pub const STDIN_ENV_VAR: &str = "GOLEM_STDIN";
+/// Comma-separated list of variable name patterns requestors are allowed to set
+pub const ENV_ALLOW_ENV_VAR: &str = "EXE_UNIT_ENV_ALLOW";
+/// Comma-separated list of variable name patterns requestors are not allowed to set
+pub const ENV_DENY_ENV_VAR: &str = "EXE_UNIT_ENV_DENY";
+
+pub const MAX_ENV_VARS: usize = 64;
+pub const MAX_ENV_VALUE_LEN: usize = 32 * 1024;
+
+/// Names which are never passed to the runtime, regardless of the provider's filters
+const RESERVED: &[&str] = &["GOLEM_*", "YAGNA_*", "EXE_UNIT_*", "LD_*", "DYLD_*", "PATH"];
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Provider-side filter of environment variable names set by requestors.
+/// Patterns match names exactly or by prefix, when ending with `*`.
+/// Nothing is allowed by default.
+#[derive(Clone, Debug, Default)]
+pub struct EnvPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl EnvPolicy {
+    pub fn from_env() -> Result<Self, Error> {
+        let mut policy = Self::default();
+        if let Ok(value) = std::env::var(ENV_ALLOW_ENV_VAR) {
+            policy.allow = list(&value);
+        }
+        if let Ok(value) = std::env::var(ENV_DENY_ENV_VAR) {
+            policy.deny = list(&value);
+        }
+        Ok(policy)
+    }
+
+    pub fn permits(&self, name: &str) -> bool {
+        valid_name(name)
+            && !RESERVED.iter().any(|p| matches(p, name))
+            && !self.deny.iter().any(|p| matches(p, name))
+            && self.allow.iter().any(|p| matches(p, name))
+    }
+}
+
+/// Key used to decrypt secrets, shared by the requestor and the ExeUnit via ECDH
+#[derive(Clone)]
+pub struct SecretsKey([u8; 32]);
+
+impl SecretsKey {
+    #[cfg_attr(not(feature = "sgx"), allow(dead_code))]
+    pub fn new(sec_key: &SecretKey, requestor_pub_key: &PublicKey) -> Self {
+        SecretsKey(SharedSecret::new(requestor_pub_key, sec_key).secret_bytes())
+    }
+
+    /// Decrypts a hex-encoded `nonce || ciphertext || tag` AES-256-GCM message,
+    /// authenticated with the variable name
+    pub fn decrypt(&self, name: &str, value: &str) -> Result<String, Error> {
+        let err = || Error::CommandError(format!("unable to decrypt secret {name}"));
+        let bytes = hex::decode(value).map_err(|_| err())?;
+        if bytes.len() < NONCE_LEN + TAG_LEN {
+            return Err(err());
+        }
+        let (nonce, rest) = bytes.split_at(NONCE_LEN);
+        let (data, tag) = rest.split_at(rest.len() - TAG_LEN);
+        let plain = decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &self.0,
+            Some(nonce),
+            name.as_bytes(),
+            data,
+            tag,
+        )
+        .map_err(|_| err())?;
+        String::from_utf8(plain).map_err(|_| err())
+    }
+}
+
+impl fmt::Debug for SecretsKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretsKey(<hidden>)")
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct Secret(String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<hidden>")
+    }
+}
+
+/// Environment variables and decrypted secrets passed to runtime processes
+#[derive(Clone, Debug, Default)]
+pub struct RuntimeEnv {
+    vars: BTreeMap<String, String>,
+    secrets: BTreeMap<String, Secret>,
+}
+
+impl RuntimeEnv {
+    /// Reads variables and secrets from the `env` map of a Deploy command.
+    /// Fails when any of the names is rejected by `policy`.
+    pub fn from_env(
+        env: &HashMap<String, String>,
+        policy: &EnvPolicy,
+        key: Option<&SecretsKey>,
+    ) -> Result<Self, Error> {
+        let mut runtime_env = RuntimeEnv::default();
+
+        for (name, value) in env {
+            if name == SIDECARS_ENV_VAR || name == SECRETS_ENV_VAR || name == STDIN_ENV_VAR {
+                continue;
+            }
+            check(name, value, policy)?;
+            runtime_env.vars.insert(name.clone(), value.clone());
+        }
+
+        if let Some(json) = env.get(SECRETS_ENV_VAR) {
+            let secrets = serde_json::from_str::<HashMap<String, String>>(json)
+                .map_err(|e| Error::CommandError(format!("invalid secret list: {e}")))?;
+            let key = match (secrets.is_empty(), key) {
+                (true, _) => None,
+                (false, Some(key)) => Some(key),
+                (false, None) => {
+                    return Err(Error::CommandError(format!(
+                        "{SECRETS_ENV_VAR} is supported only by ExeUnits running in an SGX enclave"
+                    )))
+                }
+            };
+            for (name, value) in secrets {
+                if runtime_env.vars.contains_key(&name) {
+                    return Err(Error::CommandError(format!(
+                        "{name} is set both as a variable and a secret"
+                    )));
+                }
+                let value = key.expect("key is set").decrypt(&name, &value)?;
+                check(&name, &value, policy)?;
+                runtime_env.secrets.insert(name, Secret(value));
+            }
+        }
+
+        let count = runtime_env.vars.len() + runtime_env.secrets.len();
+        if count > MAX_ENV_VARS {
+            return Err(Error::CommandError(format!(
+                "too many environment variables: {count} (max {MAX_ENV_VARS})"
+            )));
+        }
+
+        Ok(runtime_env)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty() && self.secrets.is_empty()
+    }
+
+    /// Variables followed by secrets. Values must not be logged.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .chain(self.secrets.iter().map(|(k, v)| (k.as_str(), v.0.as_str())))
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+}
+
+/// Reads the `GOLEM_STDIN` flag from the `env` map of a Deploy command
+pub fn stdin_enabled(env: &HashMap<String, String>) -> Result<bool, Error> {
+    match env.get(STDIN_ENV_VAR) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| Error::CommandError(format!("invalid {STDIN_ENV_VAR} value: {value}"))),
+        None => Ok(false),
+    }
+}
+
+fn check(name: &str, value: &str, policy: &EnvPolicy) -> Result<(), Error> {
+    if !policy.permits(name) {
+        return Err(Error::CommandError(format!(
+            "environment variable {name} is not allowed by the provider"
+        )));
+    }
+    if value.len() > MAX_ENV_VALUE_LEN {
+        return Err(Error::CommandError(format!(
+            "value of {name} is too long (max {MAX_ENV_VALUE_LEN} bytes)"
+        )));
+    }
+    Ok(())
+}
+
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+fn list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::symm::encrypt_aead;
+    use secp256k1::Secp256k1;
+
+    fn keys() -> (SecretsKey, SecretsKey) {
+        let ec = Secp256k1::new();
+        let exe_unit = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let requestor = SecretKey::from_slice(&[2u8; 32]).unwrap();
+        (
+            SecretsKey::new(&exe_unit, &PublicKey::from_secret_key(&ec, &requestor)),
+            SecretsKey::new(&requestor, &PublicKey::from_secret_key(&ec, &exe_unit)),
+        )
+    }
+
+    fn encrypt(key: &SecretsKey, name: &str, value: &str) -> String {
+        let nonce = [7u8; NONCE_LEN];
+        let mut tag = [0u8; TAG_LEN];
+        let data = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key.0,
+            Some(&nonce),
+            name.as_bytes(),
+            value.as_bytes(),
+            &mut tag,
+        )
+        .unwrap();
+        hex::encode([&nonce[..], &data, &tag].concat())
+    }
+
+    fn allow_all() -> EnvPolicy {
+        EnvPolicy {
+            allow: vec!["*".into()],
+            deny: vec![],
+        }
+    }
+
+    fn env(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn policy() {
+        let policy = EnvPolicy {
+            allow: vec!["APP_*".into(), "TOKEN".into()],
+            deny: vec!["APP_DEBUG".into()],
+        };
+        assert!(policy.permits("APP_PORT"));
+        assert!(policy.permits("TOKEN"));
+        assert!(!policy.permits("TOKEN_2"));
+        assert!(!policy.permits("APP_DEBUG"));
+        assert!(!policy.permits("APP-PORT"));
+
+        assert!(!EnvPolicy::default().permits("HOME"));
+
+        let policy = allow_all();
+        assert!(policy.permits("HOME"));
+        assert!(!policy.permits("LD_PRELOAD"));
+        assert!(!policy.permits("PATH"));
+        assert!(!policy.permits("1ABC"));
+    }
+
+    #[test]
+    fn variables_and_secrets() {
+        let (exe_unit, requestor) = keys();
+        let secrets = format!(
+            r#"{{"API_TOKEN": "{}"}}"#,
+            encrypt(&requestor, "API_TOKEN", "s3cr3t")
+        );
+        let env = env(&[
+            ("MODE", "fast"),
+            (SIDECARS_ENV_VAR, "[]"),
+            (SECRETS_ENV_VAR, &secrets),
+            (STDIN_ENV_VAR, "true"),
+        ]);
+
+        let runtime_env = RuntimeEnv::from_env(&env, &allow_all(), Some(&exe_unit)).unwrap();
+        let map = runtime_env.to_map();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["MODE"], "fast");
+        assert_eq!(map["API_TOKEN"], "s3cr3t");
+        assert!(!format!("{runtime_env:?}").contains("s3cr3t"));
+
+        assert!(RuntimeEnv::from_env(&env, &allow_all(), None).is_err());
+    }
+
+    #[test]
+    fn reject_secrets() {
+        let (exe_unit, requestor) = keys();
+        // ciphertext bound to a different name
+        let secrets = format!(
+            r#"{{"API_TOKEN": "{}"}}"#,
+            encrypt(&requestor, "OTHER", "s3cr3t")
+        );
+        let env = env(&[(SECRETS_ENV_VAR, &secrets)]);
+        assert!(RuntimeEnv::from_env(&env, &allow_all(), Some(&exe_unit)).is_err());
+
+        let policy = EnvPolicy {
+            allow: vec!["APP_*".into()],
+            deny: vec![],
+        };
+        let secrets = format!(
+            r#"{{"API_TOKEN": "{}"}}"#,
+            encrypt(&requestor, "API_TOKEN", "s3cr3t")
+        );
+        let env = env(&[(SECRETS_ENV_VAR, &secrets)]);
+        assert!(RuntimeEnv::from_env(&env, &policy, Some(&exe_unit)).is_err());
+    }
+
+    #[test]
+    fn reject_variables() {
+        assert!(RuntimeEnv::from_env(&env(&[("A", "x")]), &Default::default(), None).is_err());
+        let policy = allow_all();
+        assert!(RuntimeEnv::from_env(&env(&[("LD_PRELOAD", "x")]), &policy, None).is_err());
+        let value = "x".repeat(MAX_ENV_VALUE_LEN + 1);
+        assert!(RuntimeEnv::from_env(&env(&[("A", &value)]), &policy, None).is_err());
+        assert!(RuntimeEnv::from_env(&Default::default(), &policy, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn stdin_flag() {
+        assert!(!stdin_enabled(&Default::default()).unwrap());
+        assert!(!stdin_enabled(&env(&[(STDIN_ENV_VAR, "false")])).unwrap());
+        assert!(stdin_enabled(&env(&[(STDIN_ENV_VAR, "true")])).unwrap());
+        assert!(stdin_enabled(&env(&[(STDIN_ENV_VAR, "yes")])).is_err());
+        // the flag is not passed to the runtime and not subject to the policy
+        let runtime_env =
+            RuntimeEnv::from_env(&env(&[(STDIN_ENV_VAR, "true")]), &Default::default(), None);
+        assert!(runtime_env.unwrap().is_empty());
+    }
+}
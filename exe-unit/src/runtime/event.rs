@@ -44,7 +44,11 @@ impl EventMonitor {
         handle
     }
 
-    #[allow(unused)]
+    /// Number of processes started in the runtime service which haven't exited yet
+    pub fn running(&self) -> usize {
+        self.inner.lock().unwrap().processes.len()
+    }
+
     pub fn process<'a>(&mut self, ctx: CommandContext, pid: u64) -> Handle<'a> {
         let mut inner = self.inner.lock().unwrap();
         let channel = Channel::new(ctx, pid);
@@ -137,6 +141,11 @@ impl ya_runtime_api::server::RuntimeHandler for EventMonitor {
                     name: counter.name,
                     value: counter.value,
                 },
+                Some(Kind::CounterDefinition(def)) => RuntimeEvent::CounterDefinition {
+                    name: def.name,
+                    unit: def.unit,
+                    monotonic: def.monotonic,
+                },
                 evt => {
                     log::warn!("Unsupported runtime event: {:?}", evt);
                     return Ok::<_, SendError>(());
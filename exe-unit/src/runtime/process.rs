@@ -4,7 +4,7 @@ use std::hash::{Hash, Hasher};
 use std::ops::Not;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use actix::prelude::*;
 use futures::future::{self, LocalBoxFuture};
@@ -14,21 +14,24 @@ use tokio::process::Command;
 use ya_agreement_utils::agreement::OfferTemplate;
 use ya_client_model::activity::{CommandOutput, ExeScriptCommand};
 use ya_manifest_utils::Feature;
-use ya_runtime_api::server::{spawn, RunProcess, RuntimeControl, RuntimeService};
+use ya_runtime_api::server::{self, spawn, RunProcess, RuntimeControl, RuntimeService};
 use ya_utils_process::{kill, ProcessTree, SystemError};
 
 use crate::acl::Acl;
 use crate::error::Error;
 use crate::manifest::{ManifestContext, UrlValidator};
 use crate::message::{
-    CommandContext, ExecuteCommand, RuntimeEvent, Shutdown, ShutdownReason, UpdateDeployment,
+    CommandContext, ExecuteCommand, GetRunningProcesses, RuntimeEvent, Shutdown, ShutdownReason,
+    UpdateDeployment, WriteStdin,
 };
 use crate::network::inet::start_inet;
 use crate::network::inet::Inet;
 use crate::network::vpn::{start_vpn, Vpn};
-use crate::network::Endpoint;
+use crate::network::{Endpoint, NetworkTraffic};
 use crate::output::forward_output;
+use crate::runtime::environment::RuntimeEnv;
 use crate::runtime::event::EventMonitor;
+use crate::runtime::sidecar::{Sidecar, MAX_SIDECAR_RESTARTS, SIDECAR_RESTART_DELAY};
 use crate::runtime::{Runtime, RuntimeMode};
 use crate::state::Deployment;
 use crate::ExeUnitContext;
@@ -55,6 +58,9 @@ pub struct RuntimeProcess {
     acl: Acl,
     vpn: Option<Addr<Vpn>>,
     inet: Option<Addr<Inet>>,
+    sidecars: Vec<SpawnHandle>,
+    /// Pids of running processes with open stdin, by batch id and command index
+    stdin: Arc<Mutex<HashMap<(String, usize), u64>>>,
 }
 
 impl RuntimeProcess {
@@ -69,6 +75,8 @@ impl RuntimeProcess {
             acl: ctx.acl.clone(),
             vpn: None,
             inet: None,
+            sidecars: Default::default(),
+            stdin: Default::default(),
         }
     }
 
@@ -243,40 +251,27 @@ impl RuntimeProcess {
             std::env::current_dir()
         );
 
-        async move {
-            let mut child = Command::new(binary)
-                .current_dir(&work_dir)
-                .args(rt_args)
-                .kill_on_drop(true)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()?;
-
-            let idx = ctx.idx;
-            let id = ctx.batch_id.clone();
-            let stdout = forward_output(child.stdout.take().unwrap(), &ctx.tx, move |out| {
-                RuntimeEvent::stdout(id.clone(), idx, CommandOutput::Bin(out))
-            });
-            let id = ctx.batch_id.clone();
-            let stderr = forward_output(child.stderr.take().unwrap(), &ctx.tx, move |out| {
-                RuntimeEvent::stderr(id.clone(), idx, CommandOutput::Bin(out))
-            });
-
-            let pid = child
-                .id()
-                .ok_or_else(|| Error::runtime("Missing process id"))?;
-            let proc = if cfg!(feature = "sgx") {
-                ChildProcess::from(pid)
-            } else {
-                let tree = ProcessTree::try_new(pid).map_err(Error::runtime)?;
-                ChildProcess::from(tree)
-            };
-            let _guard = ChildProcessGuard::new(proc, address.clone());
+        run_process(binary, work_dir, rt_args, ctx, address).boxed_local()
+    }
 
-            let result = future::join3(child.wait(), stdout, stderr).await;
-            Ok(result.0?.code().unwrap_or(-1))
-        }
-        .boxed_local()
+    fn sidecar_launcher(&mut self, address: Addr<Self>) -> Result<SidecarLauncher, Error> {
+        let service = match self.deployment.runtime_mode {
+            RuntimeMode::ProcessPerCommand => None,
+            RuntimeMode::Service => match self.service.as_ref() {
+                Some(svc) => Some(svc.clone()),
+                None => return Err(Error::runtime("START command not run")),
+            },
+        };
+
+        Ok(SidecarLauncher {
+            binary: self.binary.clone(),
+            work_dir: self.ctx.work_dir.clone(),
+            rt_args: self.args()?,
+            env: self.deployment.env.clone(),
+            service,
+            monitor: self.monitor.get_or_insert_with(Default::default).clone(),
+            address,
+        })
     }
 
     fn handle_service_command<'f>(
@@ -371,13 +366,22 @@ impl RuntimeProcess {
                         endpoint,
                         &service_,
                         rt_ctx.manifest.validator::<UrlValidator>(),
+                        rt_ctx.traffic.clone(),
                     )
                     .await?;
                     address.send(SetInetService(inet)).await?;
                 }
 
                 if let Some(endpoint) = vpn_endpoint {
-                    if let Some(vpn) = start_vpn(endpoint, acl, &service_, &deployment).await? {
+                    if let Some(vpn) = start_vpn(
+                        endpoint,
+                        acl,
+                        &service_,
+                        &deployment,
+                        rt_ctx.traffic.clone(),
+                    )
+                    .await?
+                    {
                         address.send(SetVpnService(vpn)).await?;
                     }
                 }
@@ -417,6 +421,10 @@ impl RuntimeProcess {
             args
         );
 
+        let env = self.deployment.env.to_map();
+        let stdin = self.deployment.stdin;
+        let pids = self.stdin.clone();
+        let key = (ctx.batch_id.clone(), ctx.idx);
         let mut monitor = self.monitor.get_or_insert_with(Default::default).clone();
         let exec = async move {
             let name = Path::new(&entry_point)
@@ -427,15 +435,24 @@ impl RuntimeProcess {
             let run_process = RunProcess {
                 bin: entry_point,
                 args,
+                env,
+                stdin,
                 ..Default::default()
             };
 
             let handle = monitor.next_process(ctx);
-            if let Err(error) = service.run_process(run_process).await {
-                return Err(Error::RuntimeError(format!("{:?}", error)));
+            let pid = match service.run_process(run_process).await {
+                Ok(resp) => resp.pid,
+                Err(error) => return Err(Error::RuntimeError(format!("{:?}", error))),
             };
 
-            Ok(handle.await)
+            if !stdin {
+                return Ok(handle.await);
+            }
+            pids.lock().unwrap().insert(key.clone(), pid);
+            let result = handle.await;
+            pids.lock().unwrap().remove(&key);
+            Ok(result)
         };
 
         async move {
@@ -447,6 +464,143 @@ impl RuntimeProcess {
     }
 }
 
+/// Spawns a runtime binary process and forwards its output to the command context
+async fn run_process(
+    binary: PathBuf,
+    work_dir: PathBuf,
+    rt_args: CommandArgs,
+    ctx: CommandContext,
+    address: Addr<RuntimeProcess>,
+) -> Result<i32, Error> {
+    let mut child = Command::new(binary)
+        .current_dir(&work_dir)
+        .args(rt_args)
+        .kill_on_drop(true)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let idx = ctx.idx;
+    let id = ctx.batch_id.clone();
+    let stdout = forward_output(child.stdout.take().unwrap(), &ctx.tx, move |out| {
+        RuntimeEvent::stdout(id.clone(), idx, CommandOutput::Bin(out))
+    });
+    let id = ctx.batch_id.clone();
+    let stderr = forward_output(child.stderr.take().unwrap(), &ctx.tx, move |out| {
+        RuntimeEvent::stderr(id.clone(), idx, CommandOutput::Bin(out))
+    });
+
+    let pid = child
+        .id()
+        .ok_or_else(|| Error::runtime("Missing process id"))?;
+    let proc = if cfg!(feature = "sgx") {
+        ChildProcess::from(pid)
+    } else {
+        let tree = ProcessTree::try_new(pid).map_err(Error::runtime)?;
+        ChildProcess::from(tree)
+    };
+    let _guard = ChildProcessGuard::new(proc, address.clone());
+
+    let result = future::join3(child.wait(), stdout, stderr).await;
+    Ok(result.0?.code().unwrap_or(-1))
+}
+
+/// Runs sidecar processes next to the main runtime process, sharing its work dir
+/// and (in service mode) the runtime instance with its network
+#[derive(Clone)]
+struct SidecarLauncher {
+    binary: PathBuf,
+    work_dir: PathBuf,
+    rt_args: CommandArgs,
+    env: RuntimeEnv,
+    service: Option<ProcessService>,
+    monitor: EventMonitor,
+    address: Addr<RuntimeProcess>,
+}
+
+impl SidecarLauncher {
+    async fn supervise(self, sidecar: Sidecar) {
+        let mut restarts = 0;
+        loop {
+            log::info!("Starting sidecar '{}'", sidecar.name);
+
+            let code = match self.run(&sidecar).await {
+                Ok(code) => code,
+                Err(e) => {
+                    log::warn!("Sidecar '{}' failed: {e}", sidecar.name);
+                    -1
+                }
+            };
+            log::info!("Sidecar '{}' exited with code {code}", sidecar.name);
+
+            if !sidecar.restart.should_restart(code) {
+                break;
+            }
+            if restarts >= MAX_SIDECAR_RESTARTS {
+                log::warn!(
+                    "Sidecar '{}' restarted {restarts} times, giving up",
+                    sidecar.name
+                );
+                break;
+            }
+            restarts += 1;
+            tokio::time::sleep(SIDECAR_RESTART_DELAY).await;
+        }
+    }
+
+    async fn run(&self, sidecar: &Sidecar) -> Result<i32, Error> {
+        let ctx = sidecar.command_context();
+        let svc = match self.service.as_ref() {
+            Some(svc) => svc,
+            None => {
+                let mut rt_args = self.rt_args.clone();
+                rt_args
+                    .args(["run", "--entrypoint"])
+                    .arg(&sidecar.entry_point)
+                    .arg("--")
+                    .args(&sidecar.args);
+                return run_process(
+                    self.binary.clone(),
+                    self.work_dir.clone(),
+                    rt_args,
+                    ctx,
+                    self.address.clone(),
+                )
+                .await;
+            }
+        };
+
+        let name = Path::new(&sidecar.entry_point)
+            .file_name()
+            .ok_or_else(|| Error::runtime("Invalid binary name"))?;
+        let mut args = sidecar.args.clone();
+        args.insert(0, name.to_string_lossy().to_string());
+
+        let run_process = RunProcess {
+            bin: sidecar.entry_point.clone(),
+            args,
+            env: self.env.to_map(),
+            ..Default::default()
+        };
+
+        let exec = async {
+            let pid = svc
+                .service
+                .run_process(run_process)
+                .await
+                .map_err(|error| Error::RuntimeError(format!("{:?}", error)))?
+                .pid;
+            // sidecars run concurrently with ExeScript commands, so the process
+            // is tracked by its pid instead of the `next_process` slot
+            Ok(self.monitor.clone().process(ctx, pid).await)
+        };
+
+        futures::pin_mut!(exec);
+        let exited = svc.control.stopped().map(Ok);
+        future::select(exited, exec).await.factor_first().0
+    }
+}
+
 impl Runtime for RuntimeProcess {}
 
 impl Actor for RuntimeProcess {
@@ -467,6 +621,8 @@ impl Handler<ExecuteCommand> for RuntimeProcess {
     fn handle(&mut self, cmd: ExecuteCommand, ctx: &mut Self::Context) -> Self::Result {
         let address = ctx.address();
         let cmd_ = cmd.clone();
+        let start = matches!(&cmd.command, ExeScriptCommand::Start { .. });
+        let sidecars = start.then(|| address.clone());
         match &cmd.command {
             ExeScriptCommand::Deploy { .. } => self.handle_process_command(cmd, address),
             _ => match &self.deployment.runtime_mode {
@@ -474,11 +630,14 @@ impl Handler<ExecuteCommand> for RuntimeProcess {
                 RuntimeMode::Service => self.handle_service_command(cmd, address),
             },
         }
-        .map(move |result| {
+        .then(move |result| async move {
             match &result {
                 Ok(value) => log::debug!("{cmd_} returned code: {value}"),
                 Err(e) => log::debug!("{cmd_} failed (ExeUnit error): {e}"),
             };
+            if let (Ok(0), Some(address)) = (&result, sidecars) {
+                address.send(StartSidecars).await?;
+            }
             result
         })
         .boxed_local()
@@ -501,10 +660,98 @@ impl Handler<UpdateDeployment> for RuntimeProcess {
         if let Some(hosts) = msg.hosts {
             self.deployment.hosts.extend(hosts);
         }
+        if let Some(sidecars) = msg.sidecars {
+            self.deployment.sidecars = sidecars;
+        }
+        if let Some(env) = msg.env {
+            self.deployment.env = env;
+        }
+        if let Some(stdin) = msg.stdin {
+            self.deployment.stdin = stdin;
+        }
         Ok(())
     }
 }
 
+impl Handler<GetRunningProcesses> for RuntimeProcess {
+    type Result = <GetRunningProcesses as Message>::Result;
+
+    fn handle(&mut self, _: GetRunningProcesses, _: &mut Self::Context) -> Self::Result {
+        match self.service {
+            // the runtime service itself is one of the children
+            Some(_) => self
+                .monitor
+                .as_ref()
+                .map(EventMonitor::running)
+                .unwrap_or(0),
+            None => self.children.len(),
+        }
+    }
+}
+
+impl Handler<WriteStdin> for RuntimeProcess {
+    type Result = ResponseFuture<<WriteStdin as Message>::Result>;
+
+    fn handle(&mut self, msg: WriteStdin, _: &mut Self::Context) -> Self::Result {
+        let service = match self.service.as_ref() {
+            Some(svc) => svc.service.clone(),
+            None => return Box::pin(future::err(Error::runtime("START command not run"))),
+        };
+
+        let pid = match self
+            .stdin
+            .lock()
+            .unwrap()
+            .get(&(msg.batch_id.clone(), msg.idx))
+        {
+            Some(pid) => *pid,
+            None => {
+                let err = Error::CommandError(format!(
+                    "command {} in batch {} is not running with open stdin",
+                    msg.idx, msg.batch_id
+                ));
+                return Box::pin(future::err(err));
+            }
+        };
+
+        async move {
+            let write = server::WriteStdin {
+                pid,
+                data: msg.data,
+                eof: msg.eof,
+            };
+            service
+                .write_stdin(write)
+                .await
+                .map_err(|error| Error::RuntimeError(format!("{:?}", error)))
+        }
+        .boxed_local()
+    }
+}
+
+impl Handler<StartSidecars> for RuntimeProcess {
+    type Result = <StartSidecars as Message>::Result;
+
+    fn handle(&mut self, _: StartSidecars, ctx: &mut Self::Context) -> Self::Result {
+        if self.deployment.sidecars.is_empty() {
+            return;
+        }
+
+        let launcher = match self.sidecar_launcher(ctx.address()) {
+            Ok(launcher) => launcher,
+            Err(e) => {
+                log::error!("Unable to start sidecars: {e}");
+                return;
+            }
+        };
+
+        for sidecar in self.deployment.sidecars.clone() {
+            let fut = launcher.clone().supervise(sidecar);
+            self.sidecars.push(ctx.spawn(fut.into_actor(self)));
+        }
+    }
+}
+
 impl Handler<SetProcessService> for RuntimeProcess {
     type Result = <SetProcessService as Message>::Result;
 
@@ -554,8 +801,11 @@ impl Handler<RemoveChildProcess> for RuntimeProcess {
 impl Handler<Shutdown> for RuntimeProcess {
     type Result = ResponseFuture<Result<(), Error>>;
 
-    fn handle(&mut self, msg: Shutdown, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: Shutdown, ctx: &mut Self::Context) -> Self::Result {
         let timeout = process_kill_timeout_seconds();
+        for handle in std::mem::take(&mut self.sidecars) {
+            ctx.cancel_future(handle);
+        }
         let proc = self.service.take();
         let vpn = self.vpn.take();
         let inet = self.inet.take();
@@ -588,6 +838,7 @@ struct RuntimeProcessContext {
     supervise_hardware: bool,
     infrastructure: HashMap<String, f64>,
     manifest: ManifestContext,
+    traffic: NetworkTraffic,
 }
 
 impl<'a> From<&'a ExeUnitContext> for RuntimeProcessContext {
@@ -599,6 +850,7 @@ impl<'a> From<&'a ExeUnitContext> for RuntimeProcessContext {
             supervise_hardware: ctx.supervise.hardware,
             infrastructure: ctx.agreement.infrastructure.clone(),
             manifest: ctx.supervise.manifest.clone(),
+            traffic: ctx.traffic.clone(),
         }
     }
 }
@@ -729,6 +981,10 @@ impl PartialEq for ProcessService {
 
 impl Eq for ProcessService {}
 
+#[derive(Message)]
+#[rtype("()")]
+struct StartSidecars;
+
 #[derive(Message)]
 #[rtype("()")]
 struct SetProcessService(ProcessService);
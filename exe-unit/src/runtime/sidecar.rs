@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::activity::{CommandOutput, ExeScriptCommand, RuntimeEventKind};
+
+use crate::error::Error;
+use crate::message::{CommandContext, RuntimeEvent};
+
+/// Deploy command environment variable carrying a JSON list of sidecar processes
+pub const SIDECARS_ENV_VAR: &str = "GOLEM_SIDECARS";
+pub const MAX_SIDECARS: usize = 8;
+pub const MAX_SIDECAR_RESTARTS: usize = 5;
+pub const SIDECAR_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Auxiliary process started alongside the main runtime process (e.g. a monitoring
+/// agent or an HTTP proxy). Sidecars share the activity work dir and network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sidecar {
+    pub name: String,
+    pub entry_point: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub restart: RestartPolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn should_restart(&self, exit_code: i32) -> bool {
+        match self {
+            RestartPolicy::Never => false,
+            RestartPolicy::OnFailure => exit_code != 0,
+            RestartPolicy::Always => true,
+        }
+    }
+}
+
+impl Sidecar {
+    /// Reads sidecar declarations from the `env` map of a Deploy command
+    pub fn from_env(env: &HashMap<String, String>) -> Result<Vec<Sidecar>, Error> {
+        let sidecars = match env.get(SIDECARS_ENV_VAR) {
+            Some(json) => serde_json::from_str::<Vec<Sidecar>>(json)
+                .map_err(|e| Error::CommandError(format!("invalid sidecar list: {e}")))?,
+            None => return Ok(Default::default()),
+        };
+
+        if sidecars.len() > MAX_SIDECARS {
+            return Err(Error::CommandError(format!(
+                "too many sidecars: {} (max {MAX_SIDECARS})",
+                sidecars.len()
+            )));
+        }
+
+        let mut names = HashSet::new();
+        for sidecar in sidecars.iter() {
+            if sidecar.name.is_empty() || sidecar.entry_point.is_empty() {
+                return Err(Error::CommandError(
+                    "sidecar name and entry point must not be empty".into(),
+                ));
+            }
+            if !names.insert(sidecar.name.as_str()) {
+                return Err(Error::CommandError(format!(
+                    "duplicate sidecar name: {}",
+                    sidecar.name
+                )));
+            }
+        }
+
+        Ok(sidecars)
+    }
+
+    /// Sidecar declarations found in an ExeScript command, if any
+    pub fn from_command(command: &ExeScriptCommand) -> Result<Vec<Sidecar>, Error> {
+        match command {
+            ExeScriptCommand::Deploy { env, .. } => Self::from_env(env),
+            _ => Ok(Default::default()),
+        }
+    }
+
+    /// Equivalent RUN command, used for manifest validation and execution
+    pub fn as_command(&self) -> ExeScriptCommand {
+        ExeScriptCommand::Run {
+            entry_point: self.entry_point.clone(),
+            args: self.args.clone(),
+            capture: None,
+        }
+    }
+
+    /// Command context which forwards sidecar output to the ExeUnit log
+    /// instead of batch results
+    pub(crate) fn command_context(&self) -> CommandContext {
+        let (tx, rx) = mpsc::channel(16);
+        let name = self.name.clone();
+
+        tokio::task::spawn_local(rx.for_each(move |event| {
+            if let RuntimeEvent::Process(event) = event {
+                match event.kind {
+                    RuntimeEventKind::StdOut(out) => {
+                        log::info!("[sidecar {}] {}", name, output_str(&out).trim_end())
+                    }
+                    RuntimeEventKind::StdErr(out) => {
+                        log::warn!("[sidecar {}] {}", name, output_str(&out).trim_end())
+                    }
+                    _ => (),
+                }
+            }
+            futures::future::ready(())
+        }));
+
+        CommandContext {
+            batch_id: format!("sidecar-{}", self.name),
+            idx: 0,
+            tx,
+        }
+    }
+}
+
+fn output_str(output: &CommandOutput) -> std::borrow::Cow<'_, str> {
+    match output {
+        CommandOutput::Bin(vec) => String::from_utf8_lossy(vec),
+        CommandOutput::Str(string) => string.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(json: &str) -> HashMap<String, String> {
+        [(SIDECARS_ENV_VAR.to_string(), json.to_string())]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn parse_sidecars() {
+        let sidecars = Sidecar::from_env(&env(r#"[
+                {"name": "proxy", "entryPoint": "/usr/bin/proxy", "args": ["-p", "8080"]},
+                {"name": "agent", "entryPoint": "/bin/agent", "restart": "on-failure"}
+            ]"#))
+        .unwrap();
+
+        assert_eq!(sidecars.len(), 2);
+        assert_eq!(sidecars[0].args, vec!["-p", "8080"]);
+        assert_eq!(sidecars[0].restart, RestartPolicy::Never);
+        assert_eq!(sidecars[1].restart, RestartPolicy::OnFailure);
+    }
+
+    #[test]
+    fn parse_no_sidecars() {
+        assert!(Sidecar::from_env(&Default::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_sidecars() {
+        assert!(Sidecar::from_env(&env("{}")).is_err());
+        assert!(Sidecar::from_env(&env(r#"[{"name": "", "entryPoint": "/bin/a"}]"#)).is_err());
+        assert!(Sidecar::from_env(&env(
+            r#"[{"name": "a", "entryPoint": "/bin/a"}, {"name": "a", "entryPoint": "/bin/b"}]"#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn restart_policy() {
+        assert!(!RestartPolicy::Never.should_restart(1));
+        assert!(!RestartPolicy::OnFailure.should_restart(0));
+        assert!(RestartPolicy::OnFailure.should_restart(1));
+        assert!(RestartPolicy::Always.should_restart(0));
+    }
+}
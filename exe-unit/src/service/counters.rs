@@ -1,10 +1,11 @@
 #[allow(unused_imports)]
 use crate::ExeUnitContext;
 
+use ya_agreement_utils::{Error, OfferTemplate};
 use ya_counters::service::{CountersService, CountersServiceBuilder};
 use ya_counters::{Counter, TimeCounter};
 #[cfg(not(feature = "sgx"))]
-use ya_counters::{CpuCounter, MemCounter, StorageCounter};
+use ya_counters::{CpuCounter, MemCounter, NetworkCounter, StorageCounter};
 
 use std::collections::HashMap;
 
@@ -35,6 +36,11 @@ pub fn usage_vector() -> Vec<String> {
     vec![TimeCounter::ID.to_string()]
 }
 
+#[cfg(feature = "sgx")]
+pub fn supervisor_counters() -> Vec<String> {
+    usage_vector()
+}
+
 #[cfg(feature = "sgx")]
 fn counters(_ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
     vec![(
@@ -57,6 +63,26 @@ pub fn usage_vector() -> Vec<String> {
     .collect()
 }
 
+#[cfg(not(feature = "sgx"))]
+pub fn supervisor_counters() -> Vec<String> {
+    let mut counters = usage_vector();
+    counters.push(NetworkCounter::IN_ID.to_string());
+    counters.push(NetworkCounter::OUT_ID.to_string());
+    counters
+}
+
+/// Counters which can be reported during the Activity: counters provided by the supervisor
+/// and custom counters declared by the runtime in its offer template.
+pub fn supported_counters(runtime_template: &OfferTemplate) -> Vec<String> {
+    let mut counters = supervisor_counters();
+    match runtime_template.golem_properties().usage_vector() {
+        Ok(runtime_counters) => counters.extend(runtime_counters),
+        Err(Error::NoKey(_)) => (),
+        Err(e) => log::warn!("Invalid usage vector in runtime offer template: {e}"),
+    }
+    counters
+}
+
 #[cfg(not(feature = "sgx"))]
 fn counters(ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
     vec![
@@ -79,6 +105,14 @@ fn counters(ctx: &ExeUnitContext) -> HashMap<String, Box<dyn Counter>> {
             TimeCounter::ID.to_string(),
             Box::<TimeCounter>::default() as Box<dyn Counter>,
         ),
+        (
+            NetworkCounter::IN_ID.to_string(),
+            Box::new(NetworkCounter::new(ctx.traffic.inbound())) as Box<dyn Counter>,
+        ),
+        (
+            NetworkCounter::OUT_ID.to_string(),
+            Box::new(NetworkCounter::new(ctx.traffic.outbound())) as Box<dyn Counter>,
+        ),
     ]
     .into_iter()
     .collect()
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::net::IpAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
 use futures::channel::{mpsc, oneshot};
@@ -14,14 +15,16 @@ use tokio::sync::broadcast;
 pub use ya_client_model::activity::activity_state::{State, StatePair};
 use ya_client_model::activity::exe_script_command::Network;
 use ya_client_model::activity::*;
-use ya_core_model::activity::Exec;
+use ya_core_model::activity::{Exec, ExecBatchState, OutputStream};
 use ya_utils_networking::vpn::common::{to_ip, to_net};
 use ya_utils_networking::vpn::Error as NetError;
 
 use crate::error::Error;
 use crate::manifest::ManifestContext;
 use crate::notify::Notify;
-use crate::output::CapturedOutput;
+use crate::output::{CapturedOutput, OutputConfig};
+use crate::runtime::environment::RuntimeEnv;
+use crate::runtime::sidecar::Sidecar;
 use crate::runtime::RuntimeMode;
 
 fn invalid_state_err_msg(state_pair: &StatePair) -> String {
@@ -67,12 +70,37 @@ pub(crate) struct ExeUnitState {
     pub inner: StatePair,
     pub last_batch: Option<String>,
     pub batches: HashMap<String, Batch>,
+    /// Since when the activity is `Ready` without running any commands.
+    pub idle_since: Option<Instant>,
+    pub output: OutputConfig,
 }
 
 impl ExeUnitState {
+    pub fn new(output: OutputConfig) -> Self {
+        ExeUnitState {
+            output,
+            ..Default::default()
+        }
+    }
+
     pub fn start_batch(&mut self, script: Exec, control: oneshot::Sender<()>) {
         let batch_id = script.batch_id.clone();
-        self.batches.insert(batch_id, Batch::new(script, control));
+        let batch = Batch::new(script, control, self.output.clone());
+        self.batches.insert(batch_id, batch);
+        self.idle_since = None;
+    }
+
+    /// Returns for how long the activity has been idle, i.e. `Ready` with no pending batches
+    /// and no processes running in the runtime.
+    pub fn idle(&mut self, running_processes: usize) -> Option<Duration> {
+        let busy = self.inner != StatePair(State::Ready, None)
+            || self.report().batches_pending > 0
+            || running_processes > 0;
+        if busy {
+            self.idle_since = None;
+            return None;
+        }
+        Some(self.idle_since.get_or_insert_with(Instant::now).elapsed())
     }
 
     pub fn report(&self) -> ExeUnitReport {
@@ -97,16 +125,18 @@ pub(crate) struct Batch {
     pub control: Option<oneshot::Sender<()>>,
     pub notifier: Notify<usize>,
     pub stream: Broadcast<RuntimeEvent>,
+    pub output: OutputConfig,
 }
 
 impl Batch {
-    pub fn new(exec: Exec, control: oneshot::Sender<()>) -> Self {
+    pub fn new(exec: Exec, control: oneshot::Sender<()>, output: OutputConfig) -> Self {
         Batch {
             exec,
             results: Default::default(),
             control: Some(control),
             notifier: Default::default(),
             stream: Default::default(),
+            output,
         }
     }
 
@@ -220,6 +250,32 @@ impl Batch {
             .collect::<Vec<_>>()
     }
 
+    pub fn batch_state(&self) -> ExecBatchState {
+        let results = match self.total() {
+            0 => Vec::new(),
+            _ => self
+                .results(None)
+                .into_iter()
+                .map(|r| ExeScriptCommandResult {
+                    stdout: None,
+                    stderr: None,
+                    ..r
+                })
+                .collect(),
+        };
+        let is_batch_finished =
+            results.iter().any(|r| r.is_batch_finished) || self.done() == self.total();
+
+        ExecBatchState {
+            batch_id: self.exec.batch_id.clone(),
+            commands_total: self.total(),
+            commands_done: self.done(),
+            is_batch_finished,
+            running_command: self.running_command(),
+            results,
+        }
+    }
+
     #[inline]
     fn state(&mut self, idx: usize) -> Result<&mut CommandState, Error> {
         let exe_script = &self.exec.exe_script;
@@ -228,13 +284,19 @@ impl Batch {
         if idx >= exe_script.len() {
             return Err(Error::runtime(format!("unknown command index: {}", idx)));
         } else if idx >= available {
+            let batch_id = &self.exec.batch_id;
+            let output = &self.output;
             let iter = exe_script
                 .iter()
+                .enumerate()
                 .skip(available)
                 .take(idx - available + 1)
-                .map(|cmd| match cmd {
-                    ExeScriptCommand::Run { capture, .. } => CommandState::from(capture),
-                    _ => CommandState::all(),
+                .map(|(i, cmd)| {
+                    let state = match cmd {
+                        ExeScriptCommand::Run { capture, .. } => CommandState::from(capture),
+                        _ => CommandState::all(),
+                    };
+                    state.configure(output, batch_id, i)
                 });
             self.results.extend(iter);
         }
@@ -320,6 +382,23 @@ impl CommandState {
         Self::new(CapturedOutput::discard(), CapturedOutput::discard())
     }
 
+    fn configure(mut self, config: &OutputConfig, batch_id: &str, idx: usize) -> Self {
+        self.stdout = self
+            .stdout
+            .configure(config, batch_id, idx, OutputStream::Stdout);
+        self.stderr = self
+            .stderr
+            .configure(config, batch_id, idx, OutputStream::Stderr);
+        self
+    }
+
+    pub fn output(&self, stream: OutputStream) -> &CapturedOutput {
+        match stream {
+            OutputStream::Stdout => &self.stdout,
+            OutputStream::Stderr => &self.stderr,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn repr(&self) -> CommandStateRepr {
         CommandStateRepr {
@@ -387,6 +466,10 @@ pub(crate) struct Deployment {
     pub task_package: Option<PathBuf>,
     pub networks: HashMap<String, DeploymentNetwork>,
     pub hosts: HashMap<String, String>,
+    pub sidecars: Vec<Sidecar>,
+    pub env: RuntimeEnv,
+    /// Keep stdin of `Run` processes open
+    pub stdin: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -430,3 +513,66 @@ impl Deployment {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec(batch_id: &str) -> Exec {
+        Exec {
+            activity_id: "act".into(),
+            batch_id: batch_id.into(),
+            exe_script: vec![ExeScriptCommand::Start { args: vec![] }],
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn idle_when_ready() {
+        let mut state = ExeUnitState::default();
+        assert_eq!(state.idle(0), None);
+
+        state.inner = StatePair(State::Ready, None);
+        assert!(state.idle(0).is_some());
+        let since = state.idle_since;
+        assert!(state.idle(0).is_some());
+        assert_eq!(state.idle_since, since);
+
+        state.inner = StatePair(State::Ready, Some(State::Terminated));
+        assert_eq!(state.idle(0), None);
+        assert_eq!(state.idle_since, None);
+    }
+
+    #[test]
+    fn busy_with_pending_batch() {
+        let mut state = ExeUnitState::default();
+        state.inner = StatePair(State::Ready, None);
+        assert!(state.idle(0).is_some());
+
+        let (tx, _rx) = oneshot::channel();
+        state.start_batch(exec("batch"), tx);
+        assert_eq!(state.idle_since, None);
+        assert_eq!(state.idle(0), None);
+
+        let kind = RuntimeEventKind::Finished {
+            return_code: 0,
+            message: None,
+        };
+        let batch = state.batches.get_mut("batch").unwrap();
+        batch
+            .handle_event(RuntimeEvent::new("batch".into(), 0, kind))
+            .unwrap();
+        assert!(state.idle(0).is_some());
+    }
+
+    #[test]
+    fn busy_with_running_processes() {
+        let mut state = ExeUnitState::default();
+        state.inner = StatePair(State::Ready, None);
+        assert!(state.idle(0).is_some());
+
+        assert_eq!(state.idle(1), None);
+        assert_eq!(state.idle_since, None);
+        assert!(state.idle(0).is_some());
+    }
+}
@@ -0,0 +1,126 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+use test_context::test_context;
+
+use ya_client_model::activity::{CommandResult, ExeScriptCommandResult};
+use ya_exe_unit::message::GetBatchResults;
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::file::generate_image;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::server_external::start_http;
+use ya_framework_basic::test_dirs::cargo_binary;
+use ya_framework_basic::{resource, temp_dir};
+use ya_mock_runtime::scenario::Scenario;
+use ya_mock_runtime::testing::{create_exe_unit, exe_unit_config, ExeUnitExt, ExeUnitHandle};
+
+async fn start_exe_unit(
+    ctx: &mut DroppableTestContext,
+    temp_dir: &Path,
+    scenario: Scenario,
+) -> anyhow::Result<ExeUnitHandle> {
+    let image_repo = temp_dir.join("images");
+    generate_image(&image_repo, "image-1", 4096_usize, 10);
+    start_http(ctx, image_repo)
+        .await
+        .expect("unable to start http servers");
+
+    scenario.install(temp_dir)?;
+    let config = exe_unit_config(
+        temp_dir,
+        &resource!("agreement.json"),
+        cargo_binary("ya-mock-runtime")?,
+    );
+
+    let exe = create_exe_unit(config, ctx).await?;
+    exe.await_init().await?;
+    exe.wait_for_batch(&exe.deploy(None).await?).await?;
+    Ok(exe)
+}
+
+async fn batch_results(
+    exe: &ExeUnitHandle,
+    batch_id: &str,
+) -> anyhow::Result<Vec<ExeScriptCommandResult>> {
+    Ok(exe
+        .addr
+        .send(GetBatchResults {
+            batch_id: batch_id.to_string(),
+            idx: None,
+        })
+        .await?
+        .0)
+}
+
+/// Runtime crashing in the middle of `start` fails the command.
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_runtime_crash(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("exe-unit-runtime-crash")?;
+    let scenario = Scenario::new().crash_after(Duration::from_millis(500), 3);
+    let exe = start_exe_unit(ctx, dir.path(), scenario).await?;
+
+    let batch_id = exe.start(vec![]).await?;
+    exe.wait_for_batch(&batch_id).await?;
+
+    let results = batch_results(&exe, &batch_id).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, CommandResult::Error);
+
+    Scenario::uninstall();
+    exe.shutdown().await?;
+    Ok(())
+}
+
+/// Output written by the runtime in a burst is captured in full.
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_runtime_stdout_burst(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("exe-unit-stdout-burst")?;
+    let scenario = Scenario::new().stdout_burst(3, 4);
+    let exe = start_exe_unit(ctx, dir.path(), scenario).await?;
+
+    let batch_id = exe.start(vec![]).await?;
+    exe.wait_for_batch(&batch_id).await?;
+
+    let results = batch_results(&exe, &batch_id).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, CommandResult::Ok);
+    let stdout = results[0].stdout.clone().unwrap_or_default();
+    assert!(
+        stdout.contains("aaaa\nbbbb\ncccc\n"),
+        "unexpected output: {stdout}"
+    );
+
+    Scenario::uninstall();
+    exe.shutdown().await?;
+    Ok(())
+}
+
+/// `start` of a runtime service completes only after the service is up.
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_runtime_delayed_start(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("exe-unit-delayed-start")?;
+    let delay = Duration::from_millis(1500);
+    let scenario = Scenario::new().service_mode().start_delay(delay);
+    let exe = start_exe_unit(ctx, dir.path(), scenario).await?;
+
+    let started = Instant::now();
+    let batch_id = exe.start(vec![]).await?;
+    exe.wait_for_batch(&batch_id).await?;
+    assert!(started.elapsed() >= delay);
+
+    let results = batch_results(&exe, &batch_id).await?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, CommandResult::Ok);
+
+    Scenario::uninstall();
+    exe.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::path::{Path, PathBuf};
+use test_context::test_context;
+
+use ya_client_model::activity::exe_script_command::Volumes;
+use ya_client_model::activity::{CommandResult, ExeScriptCommand};
+use ya_core_model::activity::{self, OutputStream};
+use ya_exe_unit::message::GetBatchResults;
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::file::generate_image;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::server_external::start_http;
+use ya_framework_basic::test_dirs::cargo_binary;
+use ya_framework_basic::{resource, temp_dir};
+use ya_mock_runtime::scenario::Scenario;
+use ya_mock_runtime::testing::{create_exe_unit, exe_unit_config, ExeUnitExt};
+use ya_service_bus::RpcEnvelope;
+
+fn find_file(dir: &Path, name: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir).ok()?.flatten().find_map(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            find_file(&path, name)
+        } else if entry.file_name() == name {
+            Some(path)
+        } else {
+            None
+        }
+    })
+}
+
+/// Complete output of a command is spilled to a log file, which can be uploaded
+/// with `GetCommandLog` and is removed when the ExeUnit shuts down.
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_exe_unit_command_log(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("exe-unit-command-log")?;
+    let temp_dir = dir.path();
+    let image_repo = temp_dir.join("images");
+
+    generate_image(&image_repo, "image-1", 4096_usize, 10);
+    start_http(ctx, image_repo)
+        .await
+        .expect("unable to start http servers");
+
+    Scenario::new().stdout_burst(3, 4).install(temp_dir)?;
+    let mut config = exe_unit_config(
+        temp_dir,
+        &resource!("agreement.json"),
+        cargo_binary("ya-mock-runtime")?,
+    );
+    config.args.output_log_size = Some(bytesize::ByteSize::kib(64));
+    let work_dir = config.args.work_dir.clone();
+
+    let exe = create_exe_unit(config.clone(), ctx).await?;
+    exe.await_init().await?;
+
+    let deploy = ExeScriptCommand::Deploy {
+        net: vec![],
+        progress: None,
+        env: Default::default(),
+        hosts: Default::default(),
+        hostname: None,
+        volumes: Some(Volumes::Simple(vec!["/input".to_owned()])),
+    };
+    exe.wait_for_batch(&exe.exec(None, vec![deploy]).await?)
+        .await?;
+    let batch_id = exe.start(vec![]).await?;
+    exe.wait_for_batch(&batch_id).await?;
+
+    let results = exe
+        .addr
+        .send(GetBatchResults {
+            batch_id: batch_id.clone(),
+            idx: None,
+        })
+        .await?
+        .0;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, CommandResult::Ok);
+
+    let get_log = |idx: usize| activity::GetCommandLog {
+        activity_id: config.service_id.clone().unwrap_or_default(),
+        batch_id: batch_id.clone(),
+        idx,
+        stream: OutputStream::Stdout,
+        to: "container:/input/stdout.log".to_string(),
+        timeout: Some(10.),
+    };
+    let size = exe
+        .addr
+        .send(RpcEnvelope::with_caller(String::new(), get_log(0)))
+        .await?
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+    let uploaded = find_file(&work_dir, "stdout.log").expect("log not uploaded");
+    let content = std::fs::read_to_string(uploaded)?;
+    assert_eq!(size, content.len() as u64);
+    assert!(
+        content.contains("aaaa\nbbbb\ncccc\n"),
+        "unexpected log: {content}"
+    );
+
+    // There is no log of a command outside of the batch
+    let missing = exe
+        .addr
+        .send(RpcEnvelope::with_caller(String::new(), get_log(1)))
+        .await?;
+    assert!(matches!(
+        missing,
+        Err(activity::RpcMessageError::NotFound(_))
+    ));
+
+    Scenario::uninstall();
+    exe.shutdown().await?;
+    assert!(find_file(&work_dir, "stdout.log").is_some());
+    assert!(!work_dir.join("output-logs").exists());
+    Ok(())
+}
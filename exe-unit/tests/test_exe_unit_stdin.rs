@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use test_context::test_context;
+
+use ya_client_model::activity::ExeScriptCommand;
+use ya_core_model::activity;
+use ya_exe_unit::message::GetBatchResults;
+use ya_exe_unit::runtime::environment::STDIN_ENV_VAR;
+use ya_framework_basic::async_drop::DroppableTestContext;
+use ya_framework_basic::file::generate_image;
+use ya_framework_basic::log::enable_logs;
+use ya_framework_basic::server_external::start_http;
+use ya_framework_basic::test_dirs::cargo_binary;
+use ya_framework_basic::{resource, temp_dir};
+use ya_mock_runtime::scenario::Scenario;
+use ya_mock_runtime::testing::{create_exe_unit, exe_unit_config, ExeUnitExt, ExeUnitHandle};
+use ya_service_bus::RpcEnvelope;
+
+async fn write_stdin(
+    exe: &ExeUnitHandle,
+    batch_id: &str,
+    data: &[u8],
+    eof: bool,
+) -> anyhow::Result<()> {
+    let msg = activity::WriteStdin {
+        activity_id: exe.config.service_id.clone().unwrap_or_default(),
+        batch_id: batch_id.to_string(),
+        idx: 0,
+        data: data.to_vec(),
+        eof,
+        timeout: None,
+    };
+    exe.addr
+        .send(RpcEnvelope::with_caller(String::new(), msg))
+        .await?
+        .map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+/// Data written with `WriteStdin` reaches the process started by `Run` command
+/// and is echoed back to its output by the mock runtime.
+#[test_context(DroppableTestContext)]
+#[serial_test::serial]
+async fn test_exe_unit_write_stdin(ctx: &mut DroppableTestContext) -> anyhow::Result<()> {
+    enable_logs(false);
+
+    let dir = temp_dir!("exe-unit-write-stdin")?;
+    let temp_dir = dir.path();
+    let image_repo = temp_dir.join("images");
+
+    generate_image(&image_repo, "image-1", 4096_usize, 10);
+    start_http(ctx, image_repo)
+        .await
+        .expect("unable to start http servers");
+
+    Scenario::new().service_mode().install(temp_dir)?;
+    let config = exe_unit_config(
+        temp_dir,
+        &resource!("agreement.json"),
+        cargo_binary("ya-mock-runtime")?,
+    );
+
+    let exe = create_exe_unit(config.clone(), ctx).await.unwrap();
+    exe.await_init().await.unwrap();
+
+    let deploy = ExeScriptCommand::Deploy {
+        net: vec![],
+        progress: None,
+        env: HashMap::from([(STDIN_ENV_VAR.to_string(), "true".to_string())]),
+        hosts: Default::default(),
+        hostname: None,
+        volumes: None,
+    };
+    exe.wait_for_batch(&exe.exec(None, vec![deploy]).await?)
+        .await?;
+    exe.wait_for_batch(&exe.start(vec![]).await?).await?;
+
+    let run = ExeScriptCommand::Run {
+        entry_point: "/bin/cat".to_string(),
+        args: vec![],
+        capture: None,
+    };
+    let batch_id = exe.exec(None, vec![run]).await?;
+
+    // stdin is available once the runtime has spawned the process
+    let mut attempts = 0;
+    while let Err(e) = write_stdin(&exe, &batch_id, b"hello\n", false).await {
+        attempts += 1;
+        anyhow::ensure!(attempts < 50, "Unable to write to stdin: {e}");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    write_stdin(&exe, &batch_id, b"world\n", true).await?;
+    exe.wait_for_batch(&batch_id).await?;
+
+    let results = exe
+        .addr
+        .send(GetBatchResults {
+            batch_id: batch_id.clone(),
+            idx: None,
+        })
+        .await?
+        .0;
+    assert_eq!(results.len(), 1);
+    let stdout = results[0].stdout.clone().unwrap_or_default();
+    assert!(
+        stdout.contains("hello\nworld"),
+        "unexpected output: {stdout}"
+    );
+
+    // stdin of a finished command is closed
+    assert!(write_stdin(&exe, &batch_id, b"again\n", false)
+        .await
+        .is_err());
+
+    Scenario::uninstall();
+    exe.shutdown().await?;
+    Ok(())
+}
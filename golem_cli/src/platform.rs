@@ -54,3 +54,43 @@ pub fn kvm_status() -> Status {
 pub fn kvm_status() -> Status {
     Status::NotImplemented
 }
+
+/// Checks whether a GPU is available for passthrough to the VM runtime.
+#[cfg(target_os = "linux")]
+pub fn gpu_status() -> Status {
+    use nix::unistd::AccessFlags;
+    use std::{fs, path};
+    let dev_vfio = path::Path::new("/dev/vfio");
+    if !dev_vfio.join("vfio").exists() {
+        return Status::InvalidEnv(Cow::Borrowed(
+            "vfio kernel module is not loaded. Enable IOMMU in BIOS and kernel parameters, then load the vfio-pci module",
+        ));
+    }
+    // Devices bound to vfio-pci are exposed as their IOMMU group numbers
+    let groups: Vec<_> = fs::read_dir(dev_vfio)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_name() != "vfio")
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    if groups.is_empty() {
+        return Status::InvalidEnv(Cow::Borrowed("no GPU is bound to the vfio-pci driver"));
+    }
+    match groups
+        .iter()
+        .all(|group| nix::unistd::access(group, AccessFlags::W_OK | AccessFlags::R_OK).is_ok())
+    {
+        true => Status::Valid,
+        false => Status::Permission(Cow::Borrowed(
+            "the user has no access to devices in /dev/vfio",
+        )),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn gpu_status() -> Status {
+    Status::NotImplemented
+}
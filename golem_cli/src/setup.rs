@@ -1,6 +1,8 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt::Write;
 use std::path::PathBuf;
 use structopt::clap;
 use structopt::StructOpt;
@@ -8,11 +10,16 @@ use strum::VariantNames;
 use ya_utils_consent::{consent_check_before_startup, set_consent_path_in_yagna_dir};
 
 use ya_core_model::NodeId;
+use ya_utils_path::data_dir::DataDir;
 
-use crate::command::NetworkGroup;
 use crate::command::UsageDef;
+use crate::command::{NetworkGroup, ProviderConfig, NETWORK_GROUP_MAP};
 use crate::terminal::clear_stdin;
 
+mod checks;
+
+const CONFIG_FILE: &str = "golemsp.env";
+
 #[derive(StructOpt, Clone, Debug, Deserialize, Serialize)]
 pub struct ConfigAccount {
     /// Account for payments.
@@ -48,6 +55,11 @@ pub struct RunConfig {
     #[structopt(long)]
     pub no_interactive: bool,
 
+    /// Don't validate the configuration (wallet address, runtimes, relay and
+    /// payment RPC endpoints) before saving it.
+    #[structopt(long)]
+    pub skip_checks: bool,
+
     /// By default, provider will clean up agreement and activity logs, set this flag to leave logs.
     /// It can result in a large amount of disk space being used after some time.
     #[structopt(long, env)]
@@ -85,7 +97,11 @@ pub async fn setup(run_config: &RunConfig, force: bool) -> Result<i32> {
         config.subnet = run_config.subnet.clone();
     }
 
-    if config.node_name.is_none() || force {
+    let configure = config.node_name.is_none() || force;
+    // Raw address is kept to validate its checksum, which is lost after parsing
+    let mut account_input = env::var("YA_ACCOUNT").ok();
+
+    if configure {
         let node_name = if interactive {
             promptly::prompt_default(
                 "Node name ",
@@ -111,10 +127,11 @@ pub async fn setup(run_config: &RunConfig, force: bool) -> Result<i32> {
 
         if interactive {
             while let Some(account) = promptly::prompt_opt::<String, _>(&message)? {
-                match account.parse::<NodeId>() {
-                    Err(e) => eprintln!("Invalid ethereum address, is should be 20-byte hex (example 0xB1974E1F44EAD2d22bB995167A709b89Fc466B6c): {}", e),
-                    Ok(account) => {
-                        config.account = Some(account);
+                match checks::validate_address(account.trim()) {
+                    Err(e) => eprintln!("{}", e),
+                    Ok(node_id) => {
+                        config.account = Some(node_id);
+                        account_input = Some(account.trim().to_string());
                         break;
                     }
                 }
@@ -124,9 +141,24 @@ pub async fn setup(run_config: &RunConfig, force: bool) -> Result<i32> {
         }
 
         config.node_name = Some(node_name);
+
+        // Configuration isn't saved until problems are fixed, so the checks run again
+        if !run_config.skip_checks {
+            let runtimes = cmd.ya_provider()?.list_runtimes().await?;
+            checks::run(
+                account_input.as_deref(),
+                &run_config.account.network,
+                &runtimes,
+            )
+            .await?;
+        }
+
         cmd.ya_provider()?
             .set_config(&config, &run_config.account.network)
             .await?;
+        if let Err(e) = write_config_file(&config, run_config) {
+            log::warn!("Failed to write {}: {:#}", CONFIG_FILE, e);
+        }
     }
 
     let is_configured = {
@@ -209,3 +241,69 @@ pub async fn setup(run_config: &RunConfig, force: bool) -> Result<i32> {
 
     Ok(0)
 }
+
+/// Writes the configuration as commented environment variables, for reference
+/// and to ease diagnosing problems.
+fn write_config_file(config: &ProviderConfig, run_config: &RunConfig) -> Result<()> {
+    let path = DataDir::new("ya-provider")
+        .get_or_create()?
+        .join(CONFIG_FILE);
+    let network = &run_config.account.network;
+    let account = config.account.map(|a| a.to_string()).unwrap_or_default();
+
+    let mut content = String::new();
+    writeln!(
+        content,
+        "# Configuration of golemsp, written by `golemsp setup` on {}.",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+    )?;
+    writeln!(
+        content,
+        "# Settings are stored by ya-provider, this file is for reference only."
+    )?;
+    writeln!(
+        content,
+        "# To override one, uncomment it in a `.env` file in the directory golemsp is run from.\n"
+    )?;
+
+    let mut entry = |comment: &str, name: &str, value: &str| {
+        writeln!(content, "# {comment}\n#{name}={value}\n")
+    };
+    entry(
+        "Name of the node, visible to requestors",
+        "NODE_NAME",
+        config.node_name.as_deref().unwrap_or_default(),
+    )?;
+    entry(
+        "Account receiving payments. Empty means the internal Golem wallet",
+        "YA_ACCOUNT",
+        &account,
+    )?;
+    entry(
+        "Payment network group: mainnet or testnet",
+        "YA_PAYMENT_NETWORK_GROUP",
+        &network.to_string(),
+    )?;
+    entry(
+        "Subnet, only requestors in the same subnet see this node",
+        "SUBNET",
+        config.subnet.as_deref().unwrap_or_default(),
+    )?;
+    entry(
+        "Comma-separated relay servers (host:port). Resolved from DNS when empty",
+        checks::RELAY_HOST_ENV,
+        &env::var(checks::RELAY_HOST_ENV).unwrap_or_default(),
+    )?;
+    for network in NETWORK_GROUP_MAP[network].iter() {
+        let name = checks::rpc_env_var(network);
+        entry(
+            &format!("Comma-separated {network} RPC endpoints. Built-in ones are used when empty"),
+            &name,
+            &env::var(&name).unwrap_or_default(),
+        )?;
+    }
+
+    std::fs::write(&path, content).with_context(|| format!("writing {}", path.display()))?;
+    eprintln!("Configuration written to {}", path.display());
+    Ok(())
+}
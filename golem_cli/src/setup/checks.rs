@@ -0,0 +1,512 @@
+//! Validation of the provider configuration, run by `golemsp setup` before it's saved.
+//!
+//! Each check reports a problem together with a hint how to fix it, so that
+//! misconfiguration is caught before the node starts taking agreements.
+//! Network problems are often transient, so they are reported as warnings.
+use ansi_term::{Colour, Style};
+use anyhow::{anyhow, Context, Result};
+use futures::future::join_all;
+use serde::Deserialize;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::lookup_host;
+use url::Url;
+
+use ya_core_model::payment::local::NetworkName;
+use ya_core_model::NodeId;
+use ya_relay_client::{ClientBuilder, FailFast};
+use ya_utils_networking::resolver::{resolve_href_record, resolve_yagna_srv_record};
+
+use crate::command::{NetworkGroup, RuntimeInfo, NETWORK_GROUP_MAP};
+use crate::platform;
+
+pub(super) const RELAY_HOST_ENV: &str = "YA_NET_RELAY_HOST";
+const RELAY_SRV_PREFIX: &str = "_net_relay._udp";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Ok,
+    Warning,
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct Check {
+    pub name: String,
+    pub level: Level,
+    pub message: String,
+    /// What the user should do to fix the problem
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            level: Level::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn failed(
+        name: impl Into<String>,
+        message: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Check {
+            name: name.into(),
+            level: Level::Failed,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn warning(
+        name: impl Into<String>,
+        message: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Check {
+            level: Level::Warning,
+            ..Self::failed(name, message, hint)
+        }
+    }
+
+    fn print(&self) {
+        let status = match self.level {
+            Level::Ok => Style::new().fg(Colour::Green).paint("ok"),
+            Level::Warning => Style::new().fg(Colour::Yellow).paint("warning"),
+            Level::Failed => Style::new().fg(Colour::Red).paint("failed"),
+        };
+        eprintln!("{:20} {:8} {}", self.name, status, self.message);
+        if let Some(hint) = &self.hint {
+            eprintln!("{:29} {}", "", Style::new().dimmed().paint(hint));
+        }
+    }
+}
+
+/// Runs all checks and prints the report. Fails if any of the checks failed.
+pub async fn run(
+    account: Option<&str>,
+    network: &NetworkGroup,
+    runtimes: &[RuntimeInfo],
+) -> Result<()> {
+    eprintln!("Validating configuration");
+
+    let mut checks = vec![];
+    if let Some(account) = account {
+        checks.push(match validate_address(account) {
+            Ok(_) => Check::ok("Wallet address", account),
+            Err(e) => Check::failed(
+                "Wallet address",
+                e.to_string(),
+                "Copy the address from your wallet again, or run `golemsp settings set --account <address>`",
+            ),
+        });
+    }
+    checks.extend(check_runtimes(runtimes));
+    checks.push(check_relay().await);
+    checks.extend(check_payment_rpc(network).await);
+
+    checks.iter().for_each(Check::print);
+
+    let failed = checks.iter().filter(|c| c.level == Level::Failed).count();
+    match failed {
+        0 => Ok(()),
+        n => Err(anyhow!(
+            "{n} configuration problem(s) found. Fix them and run `golemsp setup` again"
+        )),
+    }
+}
+
+/// Parses an Ethereum address. Mixed-case addresses must have a valid EIP-55 checksum.
+pub fn validate_address(address: &str) -> Result<NodeId> {
+    let node_id: NodeId = address.parse().map_err(|e| {
+        anyhow!("Invalid ethereum address, it should be 20-byte hex (example 0xB1974E1F44EAD2d22bB995167A709b89Fc466B6c): {e}")
+    })?;
+
+    let hex = address.trim_start_matches("0x");
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case {
+        let expected = checksum_address(&node_id);
+        if address != expected {
+            return Err(anyhow!(
+                "Invalid checksum of ethereum address {address}, it may contain a typo"
+            ));
+        }
+    }
+    Ok(node_id)
+}
+
+/// Formats the address with EIP-55 mixed-case checksum.
+pub fn checksum_address(node_id: &NodeId) -> String {
+    let lower = node_id.to_string();
+    let lower = lower.trim_start_matches("0x");
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{checksummed}")
+}
+
+fn check_runtimes(runtimes: &[RuntimeInfo]) -> Vec<Check> {
+    if runtimes.is_empty() {
+        return vec![Check::failed(
+            "Runtimes",
+            "no runtimes found",
+            "Reinstall golemsp, or point EXE_UNIT_PATH to runtime descriptors (ya-*.json)",
+        )];
+    }
+
+    let names: Vec<_> = runtimes.iter().map(|r| r.name.as_str()).collect();
+    let mut checks = vec![Check::ok("Runtimes", names.join(", "))];
+
+    // A platform problem fails the setup only when no other runtime is usable
+    let is_vm = |name: &str| name.starts_with("vm");
+    let is_gpu = |name: &str| name.contains("gpu") || name.contains("nvidia");
+    if names.iter().any(|name| is_vm(name)) {
+        checks.push(platform_check(
+            "VM runtime",
+            platform::kvm_status(),
+            "Enable virtualization in BIOS and add your user to the kvm group (`sudo usermod -aG kvm $USER`), then log in again",
+            names.iter().all(|name| is_vm(name)),
+        ));
+    }
+    if names.iter().any(|name| is_gpu(name)) {
+        checks.push(platform_check(
+            "GPU runtime",
+            platform::gpu_status(),
+            "Bind the GPU to vfio-pci and make /dev/vfio accessible to your user, or remove the GPU runtime",
+            names.iter().all(|name| is_gpu(name)),
+        ));
+    }
+    checks
+}
+
+fn platform_check(name: &str, status: platform::Status, hint: &str, required: bool) -> Check {
+    match status.problem() {
+        Some(problem) if required => Check::failed(name, problem, hint),
+        Some(problem) => Check::warning(name, problem, hint),
+        None if status.is_valid() => Check::ok(name, "available"),
+        None => Check::ok(name, "not checked on this platform"),
+    }
+}
+
+async fn check_relay() -> Check {
+    const NAME: &str = "Relay";
+    let hint = format!(
+        "Check your internet connection and that outgoing UDP traffic is allowed, or set {RELAY_HOST_ENV}"
+    );
+
+    let hosts = match relay_hosts().await {
+        Ok(hosts) => hosts,
+        Err(e) => return Check::warning(NAME, format!("{e:#}"), hint),
+    };
+
+    let mut errors = vec![];
+    for host in &hosts {
+        match ping_relay(host).await {
+            Ok((addr, rtt)) => {
+                return Check::ok(
+                    NAME,
+                    format!("{host} ({addr}) responded in {}ms", rtt.as_millis()),
+                )
+            }
+            Err(e) => errors.push(format!("{host}: {e:#}")),
+        }
+    }
+    Check::warning(
+        NAME,
+        format!("no relay is reachable ({})", errors.join(", ")),
+        hint,
+    )
+}
+
+/// Relay servers from `YA_NET_RELAY_HOST`, or the default one resolved from DNS.
+async fn relay_hosts() -> Result<Vec<String>> {
+    if let Ok(hosts) = env::var(RELAY_HOST_ENV) {
+        return Ok(split_list(&hosts).map(ToString::to_string).collect());
+    }
+    match tokio::time::timeout(PROBE_TIMEOUT, resolve_yagna_srv_record(RELAY_SRV_PREFIX)).await {
+        Ok(Ok(host)) => Ok(vec![host]),
+        Ok(Err(e)) => Err(anyhow!("can't resolve relay address: {e}")),
+        Err(_) => Err(anyhow!("resolving relay address timed out")),
+    }
+}
+
+/// Establishes a session with the relay server over UDP, the same way yagna does.
+/// Returns the address of the server and the time it took.
+async fn ping_relay(host: &str) -> Result<(SocketAddr, Duration)> {
+    let addr = tokio::time::timeout(PROBE_TIMEOUT, lookup_host(host))
+        .await
+        .map_err(|_| anyhow!("resolving timed out"))??
+        .next()
+        .ok_or_else(|| anyhow!("no addresses"))?;
+
+    let started = Instant::now();
+    let mut client = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        ClientBuilder::from_url(Url::parse(&format!("udp://{addr}"))?)
+            .listen(Url::parse("udp://0.0.0.0:0")?)
+            .connect(FailFast::Yes)
+            .build(),
+    )
+    .await
+    .map_err(|_| anyhow!("no response from {addr}"))??;
+    let rtt = started.elapsed();
+
+    if let Err(e) = client.shutdown().await {
+        log::debug!("Closing session with relay {addr} failed: {e}");
+    }
+    Ok((addr, rtt))
+}
+
+/// Probes RPC endpoints configured with `<NETWORK>_GETH_ADDR` variables, or
+/// endpoints built into the payment driver for networks without them.
+async fn check_payment_rpc(network: &NetworkGroup) -> Vec<Check> {
+    let mut checks = vec![];
+    for network in NETWORK_GROUP_MAP[network].iter() {
+        let expected = match chain_id(network) {
+            Some(chain_id) => chain_id,
+            None => continue,
+        };
+        let name = format!("{network} RPC");
+        let var = rpc_env_var(network);
+
+        let urls = match env::var(&var) {
+            Ok(urls) => urls,
+            Err(_) => {
+                checks.push(check_builtin_rpc(&name, network, expected).await);
+                continue;
+            }
+        };
+        for url in split_list(&urls) {
+            checks.push(match probe_rpc(url).await {
+                Ok(chain_id) if chain_id == expected => Check::ok(&name, url),
+                Ok(chain_id) => Check::failed(
+                    &name,
+                    format!("{url} serves chain {chain_id}, expected {expected}"),
+                    format!("Set {var} to an endpoint of the {network} network"),
+                ),
+                Err(e) => Check::warning(
+                    &name,
+                    format!("{url} is not reachable: {e:#}"),
+                    format!("Fix or remove it from {var}, payments fall back to other endpoints"),
+                ),
+            });
+        }
+    }
+    checks
+}
+
+/// Payments work as long as any of the built-in endpoints is reachable.
+async fn check_builtin_rpc(name: &str, network: &NetworkName, expected: u64) -> Check {
+    let hint = format!(
+        "Check your internet connection, or set {} to an endpoint of the {network} network",
+        rpc_env_var(network)
+    );
+    let urls = match builtin_rpc_endpoints(network).await {
+        Ok(urls) if urls.is_empty() => {
+            return Check::warning(name, "no built-in endpoints found", hint)
+        }
+        Ok(urls) => urls,
+        Err(e) => return Check::warning(name, format!("{e:#}"), hint),
+    };
+
+    let probes = join_all(urls.iter().map(|url| probe_rpc(url))).await;
+    let reachable = probes
+        .iter()
+        .filter(|chain_id| matches!(chain_id, Ok(chain_id) if *chain_id == expected))
+        .count();
+    match reachable {
+        0 => Check::warning(
+            name,
+            format!("none of {} built-in endpoints is reachable", urls.len()),
+            hint,
+        ),
+        n => Check::ok(
+            name,
+            format!("{n} of {} built-in endpoints reachable", urls.len()),
+        ),
+    }
+}
+
+#[derive(Deserialize)]
+struct DriverConfig {
+    chain: HashMap<String, DriverChain>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DriverChain {
+    #[serde(default)]
+    rpc_endpoints: Vec<DriverRpcEndpoints>,
+}
+
+/// Endpoints are listed explicitly, or published in DNS TXT records.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DriverRpcEndpoints {
+    endpoints: Option<String>,
+    dns_source: Option<String>,
+}
+
+/// Built-in endpoints listed explicitly and DNS records publishing the rest.
+fn builtin_rpc_sources(network: &NetworkName) -> Result<(Vec<String>, Vec<String>)> {
+    let config: DriverConfig = toml::from_str(ya_erc20_driver::DEFAULT_CONFIG)
+        .context("invalid payment driver configuration")?;
+    let sources = config
+        .chain
+        .get(&network.to_string())
+        .map(|chain| chain.rpc_endpoints.as_slice())
+        .unwrap_or_default();
+
+    let urls = sources
+        .iter()
+        .filter_map(|source| source.endpoints.as_deref())
+        .flat_map(split_list)
+        .map(ToString::to_string)
+        .collect();
+    let records = sources
+        .iter()
+        .filter_map(|source| source.dns_source.clone())
+        .collect();
+    Ok((urls, records))
+}
+
+async fn builtin_rpc_endpoints(network: &NetworkName) -> Result<Vec<String>> {
+    let (mut urls, records) = builtin_rpc_sources(network)?;
+    for record in records {
+        match tokio::time::timeout(PROBE_TIMEOUT, resolve_href_record(&record)).await {
+            Ok(Ok(endpoints)) => urls.extend(endpoints),
+            Ok(Err(e)) => log::debug!("Can't resolve RPC endpoints from {record}: {e}"),
+            Err(_) => log::debug!("Resolving RPC endpoints from {record} timed out"),
+        }
+    }
+    Ok(urls)
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+pub(super) fn rpc_env_var(network: &NetworkName) -> String {
+    format!("{}_GETH_ADDR", network.to_string().to_uppercase())
+}
+
+fn chain_id(network: &NetworkName) -> Option<u64> {
+    match network {
+        NetworkName::Mainnet => Some(1),
+        NetworkName::Polygon => Some(137),
+        NetworkName::Holesky => Some(17000),
+        NetworkName::Amoy => Some(80002),
+        NetworkName::Sepolia => Some(11155111),
+        _ => None,
+    }
+}
+
+async fn probe_rpc(url: &str) -> Result<u64> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "eth_chainId",
+        "params": [],
+        "id": 1,
+    });
+    let response: serde_json::Value = awc::Client::default()
+        .post(url)
+        .timeout(PROBE_TIMEOUT)
+        .send_json(&request)
+        .await
+        .map_err(|e| anyhow!("{e}"))?
+        .json()
+        .await
+        .context("invalid JSON-RPC response")?;
+
+    let chain_id = response["result"]
+        .as_str()
+        .ok_or_else(|| anyhow!("invalid JSON-RPC response: {response}"))?;
+    u64::from_str_radix(chain_id.trim_start_matches("0x"), 16)
+        .with_context(|| format!("invalid chain id: {chain_id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test vectors from EIP-55
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn test_checksum_address() {
+        for address in CHECKSUMMED {
+            let node_id: NodeId = address.to_lowercase().parse().unwrap();
+            assert_eq!(checksum_address(&node_id), address);
+        }
+    }
+
+    #[test]
+    fn test_validate_address() {
+        for address in CHECKSUMMED {
+            assert!(validate_address(address).is_ok());
+            assert!(validate_address(&address.to_lowercase()).is_ok());
+
+            // Single character with a wrong case
+            let typo = address.replacen('a', "A", 1);
+            assert!(validate_address(&typo).is_err(), "{typo}");
+        }
+        assert!(validate_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+        assert!(validate_address("not an address").is_err());
+    }
+
+    #[test]
+    fn test_builtin_rpc_sources() {
+        let (urls, records) = builtin_rpc_sources(&NetworkName::Polygon).unwrap();
+        assert!(!urls.is_empty() || !records.is_empty());
+        assert!(urls.iter().all(|url| url.starts_with("http")), "{urls:?}");
+
+        let (urls, _) = builtin_rpc_sources(&NetworkName::Sepolia).unwrap();
+        assert!(urls.contains(&"https://endpoints.omniatech.io/v1/eth/sepolia/public".to_string()));
+
+        let (urls, records) = builtin_rpc_sources(&NetworkName::Devnet).unwrap();
+        assert!(urls.is_empty() && records.is_empty());
+    }
+
+    #[test]
+    fn test_platform_problem_level() {
+        let status = || platform::Status::Permission("no access to /dev/kvm".into());
+        assert_eq!(
+            platform_check("VM", status(), "", true).level,
+            Level::Failed
+        );
+        assert_eq!(
+            platform_check("VM", status(), "", false).level,
+            Level::Warning
+        );
+        assert_eq!(
+            platform_check("VM", platform::Status::Valid, "", true).level,
+            Level::Ok
+        );
+    }
+}
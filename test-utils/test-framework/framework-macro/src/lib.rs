@@ -1,17 +1,28 @@
 use proc_macro::{self, TokenStream};
 use quote::{format_ident, quote};
-use syn::{parse, ItemFn};
+use syn::{parse, parse_macro_input, AttributeArgs, ItemFn, Lit, Meta, NestedMeta};
 
+/// Optional parameters:
+/// - `requestors = N`, `providers = N` - nodes started before the test,
+/// - `timeout = SECONDS` - test fails when it doesn't finish in time,
+/// - `env(NAME = "value", ...)` - environment variables overridden for every node.
 #[proc_macro_attribute]
 pub fn framework_test(attr: TokenStream, input: TokenStream) -> TokenStream {
     let function = parse::<ItemFn>(input).unwrap();
-
-    if attr.into_iter().count() > 0 {
-        panic!("`framework_test` macro doesn't take any parameters");
-    }
+    let args = parse_macro_input!(attr as AttributeArgs);
 
     validate_function(&function);
 
+    let params = TestParams::parse(args);
+    let requestors = params.requestors;
+    let providers = params.providers;
+    let timeout = match params.timeout {
+        Some(secs) => quote! { Some(std::time::Duration::from_secs(#secs)) },
+        None => quote! { None },
+    };
+    let env_keys = params.env.iter().map(|(key, _)| key);
+    let env_values = params.env.iter().map(|(_, value)| value);
+
     let name = function.sig.ident;
     let code = function.block;
     let attributes = function.attrs;
@@ -28,13 +39,76 @@ pub fn framework_test(attr: TokenStream, input: TokenStream) -> TokenStream {
                 #code
             }
 
-            ya_test_framework::framework::framework_setup( #internal_name, &prepare_test_dir!(), #test_name );
+            let config = ya_test_framework::framework::FrameworkConfig {
+                requestors: #requestors,
+                providers: #providers,
+                env: vec![ #( (#env_keys.to_string(), #env_values.to_string()) ),* ],
+                timeout: #timeout,
+            };
+            ya_test_framework::framework::framework_setup_with( #internal_name, &prepare_test_dir!(), #test_name, config );
         }
     };
 
     tokens.into()
 }
 
+#[derive(Default)]
+struct TestParams {
+    requestors: usize,
+    providers: usize,
+    timeout: Option<u64>,
+    env: Vec<(String, String)>,
+}
+
+impl TestParams {
+    fn parse(args: AttributeArgs) -> TestParams {
+        let mut params = TestParams::default();
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::NameValue(nv)) => {
+                    let name = path_name(&nv.path);
+                    let value = match &nv.lit {
+                        Lit::Int(value) => value.base10_parse::<u64>().unwrap_or_else(|e| {
+                            panic!("Invalid `{name}` value: {e}");
+                        }),
+                        _ => panic!("`{name}` should be an integer"),
+                    };
+                    match name.as_str() {
+                        "requestors" => params.requestors = value as usize,
+                        "providers" => params.providers = value as usize,
+                        "timeout" => params.timeout = Some(value),
+                        _ => panic!("Unknown `framework_test` parameter: `{name}`"),
+                    }
+                }
+                NestedMeta::Meta(Meta::List(list)) if path_name(&list.path) == "env" => {
+                    for var in list.nested {
+                        match var {
+                            NestedMeta::Meta(Meta::NameValue(nv)) => {
+                                let value = match &nv.lit {
+                                    Lit::Str(value) => value.value(),
+                                    Lit::Int(value) => value.to_string(),
+                                    Lit::Bool(value) => value.value.to_string(),
+                                    _ => panic!("Unsupported `env` value type"),
+                                };
+                                params.env.push((path_name(&nv.path), value));
+                            }
+                            _ => panic!("`env` expects `NAME = \"value\"` pairs"),
+                        }
+                    }
+                }
+                _ => panic!("Unsupported `framework_test` parameter"),
+            }
+        }
+        params
+    }
+}
+
+fn path_name(path: &syn::Path) -> String {
+    path.get_ident()
+        .map(|ident| ident.to_string())
+        .unwrap_or_else(|| panic!("Expected parameter name"))
+}
+
 fn validate_function(function: &ItemFn) {
     if function.sig.asyncness.is_none() {
         panic!(
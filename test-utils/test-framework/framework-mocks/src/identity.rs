@@ -164,6 +164,7 @@ impl RealIdentity {
             role: "manager".to_string(),
             id: Some(id.to_string()),
             allow_origins: vec![],
+            expires_in: None,
         };
         let _key = command.run_command(&ctx).await?;
 
@@ -211,8 +212,7 @@ impl RealIdentity {
             .service(model::identity::BUS_SERVICE_NAME)
     }
 
-    #[allow(unused)]
-    fn gsb_appkey(&self) -> GsbBindPoints {
+    pub fn gsb_appkey(&self) -> GsbBindPoints {
         self.gsb
             .clone()
             .unwrap_or_default()
@@ -195,6 +195,11 @@ impl MockNode {
         Ok(provider)
     }
 
+    /// Base URL of the REST API, for endpoints not covered by `ya-client`.
+    pub fn rest_url(&self) -> Url {
+        self.rest_url.clone()
+    }
+
     /// Start actix server with all requested modules and some additional middlewares, that are
     /// normally used by yagna.
     /// You can make REST API requests using client created with `rest_payments` function.
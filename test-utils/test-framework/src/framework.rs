@@ -1,8 +1,9 @@
 use actix_rt;
-use anyhow;
+use anyhow::{self, anyhow};
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use crate::YagnaMock;
 
@@ -17,6 +18,7 @@ pub struct YagnaFramework {
     test_dir: PathBuf,
     #[allow(dead_code)]
     test_name: String,
+    config: FrameworkConfig,
 }
 
 /// Entities that require tear down after test is finished.
@@ -24,6 +26,22 @@ pub struct YagnaFramework {
 #[derive(Clone)]
 pub struct YagnaNetworkImpl {
     nodes: Vec<YagnaMock>,
+    requestors: Vec<YagnaMock>,
+    providers: Vec<YagnaMock>,
+}
+
+/// Test setup selected by `framework_test` macro parameters, e.g.
+/// `#[framework_test(requestors = 1, providers = 2, timeout = 300, env(YA_NET_TYPE = "central"))]`.
+#[derive(Clone, Debug, Default)]
+pub struct FrameworkConfig {
+    /// Number of requestor nodes started before the test.
+    pub requestors: usize,
+    /// Number of provider nodes started before the test.
+    pub providers: usize,
+    /// Environment variables overridden for every node.
+    pub env: Vec<(String, String)>,
+    /// Test fails if it doesn't finish in this time.
+    pub timeout: Option<Duration>,
 }
 
 pub mod macros {
@@ -53,11 +71,32 @@ where
     T: FnOnce(YagnaFramework) -> F + std::panic::UnwindSafe,
     F: Future<Output = anyhow::Result<()>>,
 {
-    let framework = YagnaFramework::new(test_dir, test_name);
+    framework_setup_with(test_fn, test_dir, test_name, FrameworkConfig::default())
+}
+
+pub fn framework_setup_with<T, F>(
+    test_fn: T,
+    test_dir: &Path,
+    test_name: &str,
+    config: FrameworkConfig,
+) where
+    T: FnOnce(YagnaFramework) -> F + std::panic::UnwindSafe,
+    F: Future<Output = anyhow::Result<()>>,
+{
+    let timeout = config.timeout;
+    let framework = YagnaFramework::with_config(test_dir, test_name, config);
     let framework_ = framework.clone();
 
     let result = std::panic::catch_unwind(|| {
-        actix_rt::System::new().block_on(async move { test_fn(framework).await })
+        actix_rt::System::new().block_on(async move {
+            framework.start_topology().await?;
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, test_fn(framework))
+                    .await
+                    .map_err(|_| anyhow!("Test didn't finish in {timeout:?}"))?,
+                None => test_fn(framework).await,
+            }
+        })
     });
 
     if let Err(e) = actix_rt::System::new().block_on(async { framework_.tear_down().await }) {
@@ -69,6 +108,14 @@ where
 
 impl YagnaFramework {
     pub fn new(tests_dir: impl Into<PathBuf>, test_name: impl Into<String>) -> YagnaFramework {
+        Self::with_config(tests_dir, test_name, FrameworkConfig::default())
+    }
+
+    pub fn with_config(
+        tests_dir: impl Into<PathBuf>,
+        test_name: impl Into<String>,
+        config: FrameworkConfig,
+    ) -> YagnaFramework {
         let test_name = test_name.into();
         let test_dir = tests_dir.into().join(&test_name);
 
@@ -76,21 +123,79 @@ impl YagnaFramework {
         let _ = std::fs::create_dir_all(&test_dir);
 
         YagnaFramework {
-            inner: Arc::new(Mutex::new(YagnaNetworkImpl { nodes: vec![] })),
+            inner: Arc::new(Mutex::new(YagnaNetworkImpl {
+                nodes: vec![],
+                requestors: vec![],
+                providers: vec![],
+            })),
             test_dir,
             test_name,
+            config,
         }
     }
 
     pub fn new_node(&self, name: impl ToString) -> YagnaMock {
         let yagna_dir = self.test_dir.join(name.to_string());
-        let yagna = YagnaMock::new(&yagna_dir);
+        let yagna = self
+            .config
+            .env
+            .iter()
+            .fold(YagnaMock::new(&yagna_dir), |yagna, (key, value)| {
+                yagna.env(key, value)
+            });
         {
             self.inner.lock().unwrap().nodes.push(yagna.clone());
         }
         yagna
     }
 
+    /// Requestor node started by the framework (see [`FrameworkConfig::requestors`]).
+    pub fn requestor(&self, idx: usize) -> anyhow::Result<YagnaMock> {
+        self.inner
+            .lock()
+            .unwrap()
+            .requestors
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| anyhow!("Requestor {idx} not configured for test"))
+    }
+
+    /// Provider node started by the framework (see [`FrameworkConfig::providers`]).
+    pub fn provider(&self, idx: usize) -> anyhow::Result<YagnaMock> {
+        self.inner
+            .lock()
+            .unwrap()
+            .providers
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| anyhow!("Provider {idx} not configured for test"))
+    }
+
+    /// Starts nodes configured in [`FrameworkConfig`]. Each node gets a free port for REST API,
+    /// so they don't collide with each other and nodes created with [`Self::new_node`].
+    async fn start_topology(&self) -> anyhow::Result<()> {
+        let names = (0..self.config.requestors)
+            .map(|idx| format!("requestor-{idx}"))
+            .chain((0..self.config.providers).map(|idx| format!("provider-{idx}")));
+
+        for name in names {
+            let port = portpicker::pick_unused_port()
+                .ok_or_else(|| anyhow!("No free port for REST API of node {name}"))?;
+            let yagna = self
+                .new_node(&name)
+                .env("YAGNA_API_URL", format!("http://127.0.0.1:{port}"))
+                .service_run()
+                .await?;
+
+            let mut inner = self.inner.lock().unwrap();
+            match name.starts_with("requestor") {
+                true => inner.requestors.push(yagna),
+                false => inner.providers.push(yagna),
+            }
+        }
+        Ok(())
+    }
+
     pub(crate) async fn tear_down(&self) -> anyhow::Result<()> {
         let timeout = std::time::Duration::from_secs(5);
         let nodes = {
@@ -80,6 +80,12 @@ impl YagnaMock {
         self
     }
 
+    /// Overrides environment variable for commands and service run by this node.
+    pub fn env(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.command = self.command.env(key, value);
+        self
+    }
+
     pub fn command(&self) -> Command {
         self.command.build("yagna").unwrap()
     }
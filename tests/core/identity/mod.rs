@@ -1 +1,2 @@
 pub mod test_appkey;
+pub mod test_topology;
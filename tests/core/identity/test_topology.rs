@@ -0,0 +1,40 @@
+use ya_test_framework::framework::macros::{prepare_test_dir, serial_test};
+use ya_test_framework::framework::{framework_test, YagnaFramework};
+
+/// Nodes started by the framework share environment overrides,
+/// but each of them runs its own REST API.
+#[cfg_attr(not(feature = "framework-test"), ignore)]
+#[framework_test(
+    requestors = 1,
+    providers = 2,
+    timeout = 300,
+    env(YAGNA_AUTOCONF_APPKEY = "topology-test-appkey")
+)]
+async fn test_topology_env_override(framework: YagnaFramework) -> anyhow::Result<()> {
+    let nodes = vec![
+        framework.requestor(0)?,
+        framework.provider(0)?,
+        framework.provider(1)?,
+    ];
+    assert!(framework.provider(2).is_err());
+
+    for yagna in nodes {
+        // Preconfigured app-key blocks creating another one with the same name
+        yagna
+            .command()
+            .arg("app-key")
+            .arg("create")
+            .arg("autoconfigured")
+            .assert()
+            .failure();
+
+        yagna
+            .command()
+            .arg("app-key")
+            .arg("create")
+            .arg("topology-test")
+            .assert()
+            .success();
+    }
+    Ok(())
+}
@@ -97,6 +97,29 @@ impl AgreementView {
     pub fn creation_timestamp(&self) -> Result<DateTime<Utc>, Error> {
         self.pointer_typed("/timestamp")
     }
+
+    /// Properties and constraints of the Offer part of the Agreement
+    pub fn offer_content(&self) -> Result<OfferTemplate, Error> {
+        self.content("/offer")
+    }
+
+    /// Properties and constraints of the Demand part of the Agreement
+    pub fn demand_content(&self) -> Result<OfferTemplate, Error> {
+        self.content("/demand")
+    }
+
+    fn content(&self, pointer: &str) -> Result<OfferTemplate, Error> {
+        Ok(OfferTemplate {
+            properties: self
+                .pointer(&format!("{pointer}/properties"))
+                .cloned()
+                .ok_or_else(|| Error::NoKey(format!("{pointer}/properties")))?,
+            constraints: self
+                .pointer(&format!("{pointer}/constraints"))
+                .as_typed(Value::as_str)?
+                .to_owned(),
+        })
+    }
 }
 
 impl TryFrom<Value> for AgreementView {
@@ -0,0 +1,258 @@
+//! Structured difference between two proposals, or a proposal and an agreement.
+//!
+//! Properties are compared by their flat names (e.g. `golem.inf.mem.gib`). Constraints
+//! are compared as sets of clauses (e.g. `(golem.inf.mem.gib>=0.5)`), ignoring whitespace.
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::agreement::flatten;
+use crate::OfferTemplate;
+
+/// Prefix of properties describing the price of a proposal
+pub const PRICING_PREFIX: &str = "golem.com.pricing";
+
+/// Innermost parenthesized expression, which is not an operator
+const CLAUSE_PATTERN: &str = r"\(\s*([^()&|!\s][^()]*)\)";
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "change")]
+pub enum PropertyChange {
+    Added { value: Value },
+    Removed { value: Value },
+    Modified { old: Value, new: Value },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstraintsDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProposalDiff {
+    pub properties: BTreeMap<String, PropertyChange>,
+    /// Present when constraints differ, even if only in structure (e.g. a changed operator)
+    pub constraints: Option<ConstraintsDiff>,
+}
+
+impl ProposalDiff {
+    pub fn new(old: &OfferTemplate, new: &OfferTemplate) -> Self {
+        ProposalDiff {
+            properties: diff_properties(&old.properties, &new.properties),
+            constraints: diff_constraints(&old.constraints, &new.constraints),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.constraints.is_none()
+    }
+
+    /// Checks whether constraints are unchanged and all changed properties
+    /// belong to one of `prefixes`
+    pub fn only_changed(&self, prefixes: &[&str]) -> bool {
+        self.constraints.is_none()
+            && self
+                .properties
+                .keys()
+                .all(|name| prefixes.iter().any(|prefix| has_prefix(name, prefix)))
+    }
+
+    /// Checks whether nothing but the price has changed
+    pub fn only_price_changed(&self) -> bool {
+        self.only_changed(&[PRICING_PREFIX])
+    }
+}
+
+impl fmt::Display for ProposalDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+        let mut lines = Vec::new();
+        for (name, change) in self.properties.iter() {
+            lines.push(match change {
+                PropertyChange::Added { value } => format!("+ {name}: {value}"),
+                PropertyChange::Removed { value } => format!("- {name}: {value}"),
+                PropertyChange::Modified { old, new } => format!("~ {name}: {old} -> {new}"),
+            });
+        }
+        if let Some(constraints) = &self.constraints {
+            lines.extend(constraints.added.iter().map(|c| format!("+ {c}")));
+            lines.extend(constraints.removed.iter().map(|c| format!("- {c}")));
+            if constraints.added.is_empty() && constraints.removed.is_empty() {
+                lines.push("~ constraints structure".to_string());
+            }
+        }
+        write!(f, "{}", lines.join(", "))
+    }
+}
+
+fn diff_properties(old: &Value, new: &Value) -> BTreeMap<String, PropertyChange> {
+    let old = flatten(old.clone());
+    let mut new = flatten(new.clone());
+    let mut changes = BTreeMap::new();
+
+    for (name, old) in old.into_iter() {
+        match new.remove(&name) {
+            Some(new) if new == old => (),
+            Some(new) => {
+                changes.insert(name, PropertyChange::Modified { old, new });
+            }
+            None => {
+                changes.insert(name, PropertyChange::Removed { value: old });
+            }
+        }
+    }
+    for (name, value) in new.into_iter() {
+        changes.insert(name, PropertyChange::Added { value });
+    }
+    changes
+}
+
+fn diff_constraints(old: &str, new: &str) -> Option<ConstraintsDiff> {
+    if normalize(old) == normalize(new) {
+        return None;
+    }
+    let old = clauses(old);
+    let new = clauses(new);
+    Some(ConstraintsDiff {
+        added: new.difference(&old).cloned().collect(),
+        removed: old.difference(&new).cloned().collect(),
+    })
+}
+
+fn clauses(constraints: &str) -> BTreeSet<String> {
+    static CLAUSE: OnceLock<Regex> = OnceLock::new();
+    CLAUSE
+        .get_or_init(|| Regex::new(CLAUSE_PATTERN).unwrap())
+        .captures_iter(constraints)
+        .map(|c| format!("({})", normalize(&c[1])))
+        .collect()
+}
+
+fn normalize(s: &str) -> String {
+    s.split_whitespace().collect()
+}
+
+fn has_prefix(name: &str, prefix: &str) -> bool {
+    name == prefix
+        || name
+            .strip_prefix(prefix)
+            .map(|rest| rest.starts_with('.'))
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn template(properties: Value, constraints: &str) -> OfferTemplate {
+        OfferTemplate {
+            properties,
+            constraints: constraints.to_string(),
+        }
+    }
+
+    #[test]
+    fn properties_diff() {
+        let old = template(
+            json!({
+                "golem": {
+                    "inf.mem.gib": 0.5,
+                    "com.pricing.model": { "@tag": "linear", "linear.coeffs": [0.1, 0.2] },
+                    "runtime.name": "vm"
+                }
+            }),
+            "",
+        );
+        let new = template(
+            json!({
+                "golem.inf.mem.gib": 0.5,
+                "golem.com.pricing.model": "linear",
+                "golem.com.pricing.model.linear.coeffs": [0.1, 0.3],
+                "golem.srv.caps.multi-activity": true
+            }),
+            "",
+        );
+
+        let diff = ProposalDiff::new(&old, &new);
+        assert_eq!(diff.properties.len(), 3);
+        assert_eq!(
+            diff.properties["golem.com.pricing.model.linear.coeffs"],
+            PropertyChange::Modified {
+                old: json!([0.1, 0.2]),
+                new: json!([0.1, 0.3])
+            }
+        );
+        assert_eq!(
+            diff.properties["golem.runtime.name"],
+            PropertyChange::Removed { value: json!("vm") }
+        );
+        assert_eq!(
+            diff.properties["golem.srv.caps.multi-activity"],
+            PropertyChange::Added { value: json!(true) }
+        );
+        assert!(!diff.only_price_changed());
+        assert!(diff.only_changed(&["golem.com", "golem.runtime", "golem.srv"]));
+    }
+
+    #[test]
+    fn only_price_changed() {
+        let old = template(
+            json!({"golem.com.pricing.model.linear.coeffs": [0.1], "golem.inf.cpu.threads": 4}),
+            "(&(golem.srv.comp.expiration>1)\n\t(golem.inf.mem.gib>=0.5))",
+        );
+        let new = template(
+            json!({"golem.com.pricing.model.linear.coeffs": [0.2], "golem.inf.cpu.threads": 4}),
+            "(& (golem.srv.comp.expiration>1) (golem.inf.mem.gib>=0.5))",
+        );
+
+        let diff = ProposalDiff::new(&old, &new);
+        assert!(diff.constraints.is_none());
+        assert!(diff.only_price_changed());
+        assert!(!has_prefix("golem.com.pricingx", PRICING_PREFIX));
+        assert!(ProposalDiff::new(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn constraints_diff() {
+        let old = template(
+            json!({}),
+            "(&(golem.inf.mem.gib>=0.5)(golem.inf.cpu.threads>=1))",
+        );
+        let new = template(
+            json!({}),
+            "(&(golem.inf.mem.gib>=1)\n  (golem.inf.cpu.threads >= 1))",
+        );
+
+        let diff = ProposalDiff::new(&old, &new);
+        assert_eq!(
+            diff.constraints,
+            Some(ConstraintsDiff {
+                added: vec!["(golem.inf.mem.gib>=1)".to_string()],
+                removed: vec!["(golem.inf.mem.gib>=0.5)".to_string()],
+            })
+        );
+        assert!(!diff.only_price_changed());
+        assert_eq!(
+            diff.to_string(),
+            "+ (golem.inf.mem.gib>=1), - (golem.inf.mem.gib>=0.5)"
+        );
+
+        let new = template(
+            json!({}),
+            "(|(golem.inf.mem.gib>=0.5)(golem.inf.cpu.threads>=1))",
+        );
+        assert_eq!(
+            ProposalDiff::new(&old, &new).constraints,
+            Some(ConstraintsDiff::default())
+        );
+    }
+}
@@ -1,10 +1,14 @@
 pub mod agreement;
 mod constraints;
+pub mod diff;
+pub mod properties;
 pub mod proposal;
 pub mod template;
 mod typed_props;
 
 pub use agreement::{AgreementView, Error, OfferTemplate};
 pub use constraints::*;
+pub use diff::{PropertyChange, ProposalDiff};
+pub use properties::{GolemProperties, GolemPropertiesBuilder, PaymentPlatform, PricingModel};
 pub use proposal::ProposalView;
 pub use typed_props::*;
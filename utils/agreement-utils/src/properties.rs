@@ -0,0 +1,403 @@
+//! Typed access to canonical `golem.*` properties of Offers, Demands and Agreements.
+//!
+//! Views operate on expanded property trees (see [`expand`](crate::agreement::expand)),
+//! which is the form kept by [`AgreementView`], [`ProposalView`] and [`OfferTemplate`].
+//! [`GolemPropertiesBuilder`] writes the same properties in the flat form.
+//!
+//! Layout of properties is versioned by `golem.properties.version`. Nodes which don't
+//! publish it predate the versioning.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::agreement::{expand, flatten, Error};
+use crate::template::property_to_pointer_paths;
+use crate::{AgreementView, OfferTemplate, ProposalView};
+
+pub const NODE_NAME_PROPERTY: &str = "golem.node.id.name";
+pub const NODE_SUBNET_PROPERTY: &str = "golem.node.debug.subnet";
+pub const NODE_IS_PUBLIC_PROPERTY: &str = "golem.node.net.is-public";
+pub const INF_PROPERTY: &str = "golem.inf";
+pub const TASK_PACKAGE_PROPERTY: &str = "golem.srv.comp.task_package";
+pub const USAGE_VECTOR_PROPERTY: &str = "golem.com.usage.vector";
+pub const PRICING_MODEL_PROPERTY: &str = "golem.com.pricing.model";
+pub const LINEAR_COEFFS_PROPERTY: &str = "golem.com.pricing.model.linear.coeffs";
+pub const PAYMENT_PLATFORM_PROPERTY: &str = "golem.com.payment.platform";
+pub const PROPERTIES_VERSION_PROPERTY: &str = "golem.properties.version";
+
+/// Version of properties layout published by this version of the library.
+pub const PROPERTIES_VERSION: u32 = 1;
+
+/// Pricing models known to this version of the library.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PricingModel {
+    /// Price is a linear combination of usage vector with `coeffs`.
+    /// The last coefficient is a constant price for starting Activity.
+    Linear { coeffs: Vec<f64> },
+    /// Pricing model which can't be interpreted by this library.
+    Unknown(String),
+}
+
+/// Payment platform declared in `golem.com.payment.platform.<name>` property.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentPlatform {
+    pub name: String,
+    pub address: Option<String>,
+}
+
+/// Typed view over properties of a single Offer or Demand.
+#[derive(Clone, Debug)]
+pub struct GolemProperties<'a> {
+    properties: Cow<'a, Value>,
+}
+
+impl<'a> GolemProperties<'a> {
+    /// Expects expanded properties.
+    pub fn new(properties: &'a Value) -> Self {
+        GolemProperties {
+            properties: Cow::Borrowed(properties),
+        }
+    }
+
+    /// Accepts both flat and expanded properties.
+    pub fn from_flat(properties: &Value) -> GolemProperties<'static> {
+        GolemProperties {
+            properties: Cow::Owned(expand(properties.clone())),
+        }
+    }
+
+    /// Reads property by its flat name, e.g. `golem.inf.mem.gib`.
+    /// Properties, which are also prefixes of other properties, are stored under `@tag`.
+    pub fn get<T: DeserializeOwned>(&self, property: &str) -> Result<T, Error> {
+        let pointers = property_to_pointer_paths(property);
+        let value = self
+            .properties
+            .pointer(&pointers.path_w_tag)
+            .or_else(|| self.properties.pointer(&pointers.path))
+            .ok_or_else(|| Error::NoKey(property.to_string()))?;
+        T::deserialize(value.clone())
+            .map_err(|error| Error::UnexpectedType(property.to_string(), error))
+    }
+
+    /// Like `get`, but missing property results in `None`.
+    pub fn get_opt<T: DeserializeOwned>(&self, property: &str) -> Result<Option<T>, Error> {
+        match self.get(property) {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NoKey(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// `None` for nodes which predate versioning of properties.
+    pub fn properties_version(&self) -> Result<Option<u32>, Error> {
+        self.get_opt(PROPERTIES_VERSION_PROPERTY)
+    }
+
+    pub fn node_name(&self) -> Result<Option<String>, Error> {
+        self.get_opt(NODE_NAME_PROPERTY)
+    }
+
+    pub fn subnet(&self) -> Result<Option<String>, Error> {
+        self.get_opt(NODE_SUBNET_PROPERTY)
+    }
+
+    pub fn is_public(&self) -> Result<bool, Error> {
+        Ok(self.get_opt(NODE_IS_PUBLIC_PROPERTY)?.unwrap_or(false))
+    }
+
+    pub fn task_package(&self) -> Result<Option<String>, Error> {
+        self.get_opt(TASK_PACKAGE_PROPERTY)
+    }
+
+    /// Numeric infrastructure properties with names relative to `golem.inf`,
+    /// e.g. `mem.gib`, `storage.gib`, `cpu.threads`.
+    pub fn infrastructure(&self) -> Result<HashMap<String, f64>, Error> {
+        let inf: Value = self.get(INF_PROPERTY)?;
+        Ok(flatten(inf)
+            .into_iter()
+            .filter_map(|(name, value)| value.as_f64().map(|value| (name, value)))
+            .collect())
+    }
+
+    pub fn usage_vector(&self) -> Result<Vec<String>, Error> {
+        self.get(USAGE_VECTOR_PROPERTY)
+    }
+
+    pub fn pricing_model(&self) -> Result<PricingModel, Error> {
+        let model: String = self.get(PRICING_MODEL_PROPERTY)?;
+        match model.as_str() {
+            "linear" => Ok(PricingModel::Linear {
+                coeffs: self.get(LINEAR_COEFFS_PROPERTY)?,
+            }),
+            _ => Ok(PricingModel::Unknown(model)),
+        }
+    }
+
+    pub fn payment_platforms(&self) -> Result<Vec<PaymentPlatform>, Error> {
+        let platforms = match self.get_opt::<Value>(PAYMENT_PLATFORM_PROPERTY)? {
+            Some(Value::Object(platforms)) => platforms,
+            Some(_) => {
+                return Err(Error::InvalidValue(format!(
+                    "{} is not an object",
+                    PAYMENT_PLATFORM_PROPERTY
+                )))
+            }
+            None => return Ok(vec![]),
+        };
+
+        Ok(platforms
+            .into_iter()
+            .map(|(name, params)| PaymentPlatform {
+                address: params
+                    .get("address")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                name,
+            })
+            .collect())
+    }
+}
+
+/// Builds canonical `golem.*` properties in the flat form, e.g. `golem.inf.mem.gib`.
+#[derive(Clone, Debug)]
+pub struct GolemPropertiesBuilder {
+    properties: Map<String, Value>,
+}
+
+impl Default for GolemPropertiesBuilder {
+    fn default() -> Self {
+        GolemPropertiesBuilder::new()
+    }
+}
+
+impl GolemPropertiesBuilder {
+    /// Properties are marked with the current [`PROPERTIES_VERSION`].
+    pub fn new() -> Self {
+        GolemPropertiesBuilder {
+            properties: Map::new(),
+        }
+        .set(PROPERTIES_VERSION_PROPERTY, PROPERTIES_VERSION)
+    }
+
+    /// Sets property by its flat name. Overrides previous value.
+    pub fn set(mut self, property: &str, value: impl Into<Value>) -> Self {
+        self.properties.insert(property.to_string(), value.into());
+        self
+    }
+
+    pub fn node_name(self, name: impl Into<String>) -> Self {
+        self.set(NODE_NAME_PROPERTY, name.into())
+    }
+
+    pub fn subnet(self, subnet: impl Into<String>) -> Self {
+        self.set(NODE_SUBNET_PROPERTY, subnet.into())
+    }
+
+    pub fn is_public(self, is_public: bool) -> Self {
+        self.set(NODE_IS_PUBLIC_PROPERTY, is_public)
+    }
+
+    pub fn task_package(self, task_package: impl Into<String>) -> Self {
+        self.set(TASK_PACKAGE_PROPERTY, task_package.into())
+    }
+
+    /// Infrastructure property with name relative to `golem.inf`, e.g. `mem.gib`.
+    pub fn infrastructure(self, name: &str, value: f64) -> Self {
+        self.set(&format!("{}.{}", INF_PROPERTY, name), value)
+    }
+
+    pub fn usage_vector<S: Into<String>>(self, usage_vector: impl IntoIterator<Item = S>) -> Self {
+        let usage_vector: Vec<String> = usage_vector.into_iter().map(Into::into).collect();
+        self.set(USAGE_VECTOR_PROPERTY, usage_vector)
+    }
+
+    /// Fails for [`PricingModel::Unknown`], which parameters can't be serialized.
+    pub fn pricing_model(self, model: &PricingModel) -> Result<Self, Error> {
+        match model {
+            PricingModel::Linear { coeffs } => Ok(self
+                .set(PRICING_MODEL_PROPERTY, "linear")
+                .set(LINEAR_COEFFS_PROPERTY, coeffs.clone())),
+            PricingModel::Unknown(name) => Err(Error::InvalidValue(format!(
+                "Unknown pricing model: {}",
+                name
+            ))),
+        }
+    }
+
+    pub fn payment_platform(self, platform: &PaymentPlatform) -> Self {
+        let property = format!("{}.{}", PAYMENT_PLATFORM_PROPERTY, platform.name);
+        match &platform.address {
+            Some(address) => self.set(&format!("{}.address", property), address.clone()),
+            None => self.set(&property, Value::Object(Map::new())),
+        }
+    }
+
+    /// Flat properties, as sent to the market.
+    pub fn build(self) -> Value {
+        Value::Object(self.properties)
+    }
+}
+
+impl AgreementView {
+    pub fn offer_properties(&self) -> Result<GolemProperties, Error> {
+        self.pointer("/offer/properties")
+            .map(GolemProperties::new)
+            .ok_or_else(|| Error::NoKey("/offer/properties".to_string()))
+    }
+
+    pub fn demand_properties(&self) -> Result<GolemProperties, Error> {
+        self.pointer("/demand/properties")
+            .map(GolemProperties::new)
+            .ok_or_else(|| Error::NoKey("/demand/properties".to_string()))
+    }
+}
+
+impl ProposalView {
+    pub fn golem_properties(&self) -> GolemProperties {
+        GolemProperties::new(&self.content.properties)
+    }
+}
+
+impl OfferTemplate {
+    /// Templates keep flat properties, so they are expanded first.
+    pub fn golem_properties(&self) -> GolemProperties<'static> {
+        GolemProperties::from_flat(&self.properties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn properties() -> Value {
+        expand(json!({
+            "golem.node.id.name": "node",
+            "golem.inf.mem.gib": 0.5,
+            "golem.inf.storage.gib": 50.0,
+            "golem.inf.cpu.architecture": "x86_64",
+            "golem.com.usage.vector": ["golem.usage.duration_sec", "golem.usage.cpu_sec"],
+            "golem.com.pricing.model": "linear",
+            "golem.com.pricing.model.linear.coeffs": [0.1, 0.2, 1.0],
+            "golem.com.payment.platform.erc20-holesky-tglm.address": "0x1234",
+        }))
+    }
+
+    #[test]
+    fn test_typed_properties() {
+        let properties = properties();
+        let view = GolemProperties::new(&properties);
+
+        assert_eq!(view.node_name().unwrap(), Some("node".to_string()));
+        assert_eq!(view.subnet().unwrap(), None);
+        assert!(!view.is_public().unwrap());
+
+        let inf = view.infrastructure().unwrap();
+        assert_eq!(inf.get("mem.gib"), Some(&0.5));
+        assert_eq!(inf.get("storage.gib"), Some(&50.0));
+        assert!(!inf.contains_key("cpu.architecture"));
+
+        assert_eq!(view.usage_vector().unwrap().len(), 2);
+        assert_eq!(
+            view.pricing_model().unwrap(),
+            PricingModel::Linear {
+                coeffs: vec![0.1, 0.2, 1.0]
+            }
+        );
+        assert_eq!(
+            view.payment_platforms().unwrap(),
+            vec![PaymentPlatform {
+                name: "erc20-holesky-tglm".to_string(),
+                address: Some("0x1234".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_and_invalid_properties() {
+        let properties = expand(json!({ "golem.com.usage.vector": "not-a-list" }));
+        let view = GolemProperties::new(&properties);
+
+        assert!(matches!(view.infrastructure(), Err(Error::NoKey(_))));
+        assert!(matches!(
+            view.usage_vector(),
+            Err(Error::UnexpectedType(..))
+        ));
+        assert!(view.payment_platforms().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_builder_round_trip() {
+        let platform = PaymentPlatform {
+            name: "erc20-holesky-tglm".to_string(),
+            address: Some("0x1234".to_string()),
+        };
+        let pricing = PricingModel::Linear {
+            coeffs: vec![0.1, 0.2, 1.0],
+        };
+        let properties = GolemPropertiesBuilder::new()
+            .node_name("node")
+            .is_public(true)
+            .task_package("hash:sha3:abc:http://127.0.0.1/image")
+            .infrastructure("mem.gib", 0.5)
+            .infrastructure("cpu.threads", 4.0)
+            .usage_vector(["golem.usage.duration_sec", "golem.usage.cpu_sec"])
+            .pricing_model(&pricing)
+            .unwrap()
+            .payment_platform(&platform)
+            .build();
+
+        assert_eq!(properties["golem.inf.mem.gib"], json!(0.5));
+        assert_eq!(properties["golem.com.pricing.model"], json!("linear"));
+
+        let view = GolemProperties::from_flat(&properties);
+        assert_eq!(view.properties_version().unwrap(), Some(PROPERTIES_VERSION));
+        assert_eq!(view.node_name().unwrap(), Some("node".to_string()));
+        assert!(view.is_public().unwrap());
+        assert_eq!(
+            view.task_package().unwrap().as_deref(),
+            Some("hash:sha3:abc:http://127.0.0.1/image")
+        );
+        assert_eq!(
+            view.infrastructure().unwrap().get("cpu.threads"),
+            Some(&4.0)
+        );
+        assert_eq!(view.usage_vector().unwrap().len(), 2);
+        assert_eq!(view.pricing_model().unwrap(), pricing);
+        assert_eq!(view.payment_platforms().unwrap(), vec![platform]);
+
+        assert!(GolemPropertiesBuilder::new()
+            .pricing_model(&PricingModel::Unknown("fixed".to_string()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_properties_version() {
+        let properties = properties();
+        let view = GolemProperties::new(&properties);
+        assert_eq!(view.properties_version().unwrap(), None);
+
+        let properties = GolemPropertiesBuilder::new()
+            .set(PROPERTIES_VERSION_PROPERTY, 2)
+            .build();
+        let view = GolemProperties::from_flat(&properties);
+        assert_eq!(view.properties_version().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_flat_template_properties() {
+        let template = OfferTemplate::new(json!({
+            "golem.com.usage.vector": ["golem.usage.duration_sec"],
+            "golem.inf.mem.gib": 0.5,
+        }));
+        let view = template.golem_properties();
+
+        assert_eq!(
+            view.usage_vector().unwrap(),
+            vec!["golem.usage.duration_sec".to_string()]
+        );
+        assert_eq!(view.infrastructure().unwrap().get("mem.gib"), Some(&0.5));
+    }
+}
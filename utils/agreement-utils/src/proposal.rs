@@ -4,7 +4,7 @@ use ya_client_model::NodeId;
 
 use crate::agreement::{expand, flatten, try_from_path, TypedPointer};
 use crate::template::property_to_pointer_paths;
-use crate::{Error, OfferTemplate};
+use crate::{Error, OfferTemplate, ProposalDiff};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -50,6 +50,11 @@ impl ProposalView {
         }
     }
 
+    /// Changes made in `newer` proposal relative to this one
+    pub fn diff(&self, newer: &ProposalView) -> ProposalDiff {
+        ProposalDiff::new(&self.content, &newer.content)
+    }
+
     pub fn remove_property(&mut self, pointer: &str) -> Result<(), Error> {
         let path: Vec<&str> = pointer.split('/').collect();
 
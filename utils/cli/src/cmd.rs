@@ -35,30 +35,26 @@ impl CommandOutput {
     }
 
     fn print_json(&self) -> anyhow::Result<()> {
+        println!("{}", serde_json::to_string_pretty(&self.to_json())?);
+        Ok(())
+    }
+
+    /// JSON representation printed with `--json` flag. Every command prints a single
+    /// JSON document: `null`, object, array of table rows or array of tables.
+    pub fn to_json(&self) -> serde_json::Value {
         match self {
-            CommandOutput::NoOutput => println!("null"),
+            CommandOutput::NoOutput => serde_json::Value::Null,
             CommandOutput::Table {
                 columns,
                 values,
                 summary: _,
                 header: _,
-            } => crate::table::print_json_table(columns, values)?,
+            } => crate::table::json_table(columns, values),
             CommandOutput::MultiTable { tables } => {
-                for table in tables {
-                    if let CommandOutput::Table {
-                        columns,
-                        values,
-                        summary: _,
-                        header: _,
-                    } = table
-                    {
-                        crate::table::print_json_table(columns, values)?
-                    }
-                }
+                serde_json::Value::Array(tables.iter().map(CommandOutput::to_json).collect())
             }
-            CommandOutput::Object(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+            CommandOutput::Object(value) => value.clone(),
         }
-        Ok(())
     }
 
     fn print_plain(&self) -> anyhow::Result<()> {
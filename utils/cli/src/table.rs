@@ -1,6 +1,5 @@
 use crate::cmd::CommandOutput;
 use prettytable::{color, format, format::TableFormat, Attr, Cell, Row, Table};
-use std::collections::HashMap;
 
 pub fn print_table(
     columns: &[String],
@@ -71,39 +70,31 @@ pub fn print_table(
     table.printstd();
 }
 
-pub fn print_json_table(
-    columns: &Vec<String>,
-    values: &Vec<serde_json::Value>,
-) -> Result<(), anyhow::Error> {
-    let columns_size_eq_values_size = values.iter().all(|row| match row {
-        serde_json::Value::Array(values) => values.len() == columns.len(),
-        _ => false,
-    });
-    if columns_size_eq_values_size {
-        let kvs: Vec<HashMap<&String, &serde_json::Value>> = values
-            .iter()
-            .map(|row| match row {
-                serde_json::Value::Array(row_values) if columns.len() == row_values.len() => {
-                    columns
-                        .iter()
-                        .enumerate()
-                        .map(|(idx, key)| (key, &row_values[idx]))
-                        .collect()
-                }
-                _ => unreachable!(),
-            })
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&serde_json::json!(kvs))?)
-    } else {
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&serde_json::json!({
-                "headers": columns,
-                "values": values
-            }))?
-        )
+/// Rows are converted to objects with column names as keys. When some rows
+/// don't match the columns, table is returned as `{"headers": [..], "values": [..]}`.
+pub fn json_table(columns: &[String], values: &[serde_json::Value]) -> serde_json::Value {
+    let rows: Option<Vec<serde_json::Value>> = values
+        .iter()
+        .map(|row| match row {
+            serde_json::Value::Array(row_values) if columns.len() == row_values.len() => Some(
+                columns
+                    .iter()
+                    .cloned()
+                    .zip(row_values.iter().cloned())
+                    .collect::<serde_json::Map<_, _>>()
+                    .into(),
+            ),
+            _ => None,
+        })
+        .collect();
+
+    match rows {
+        Some(rows) => serde_json::Value::Array(rows),
+        None => serde_json::json!({
+            "headers": columns,
+            "values": values
+        }),
     }
-    Ok(())
 }
 
 pub struct ResponseTable {
@@ -181,3 +172,22 @@ lazy_static::lazy_static! {
         .padding(2, 2)
         .build();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_table() {
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let table = json_table(&columns, &[json!([1, "a"]), json!([2, "b"])]);
+        assert_eq!(
+            table,
+            json!([{"id": 1, "name": "a"}, {"id": 2, "name": "b"}])
+        );
+
+        let table = json_table(&columns, &[json!([1])]);
+        assert_eq!(table, json!({"headers": ["id", "name"], "values": [[1]]}));
+    }
+}
@@ -8,9 +8,15 @@ use flexi_logger::{
 };
 use std::env;
 use std::path::Path;
+use std::sync::{Mutex, OnceLock};
 
 pub use flexi_logger::LoggerHandle;
 
+/// Log specification set at logger start, restored by `reset_log_spec`.
+static START_LOG_SPEC: OnceLock<LogSpecification> = OnceLock::new();
+/// Log specification currently used by the logger.
+static CURRENT_LOG_SPEC: Mutex<Option<LogSpecification>> = Mutex::new(None);
+
 #[allow(clippy::useless_conversion)]
 fn log_format_date(now: &mut DeferredNow) -> DelayedFormat<StrftimeItems> {
     //use DateTime::<Local> instead of DateTime::<UTC> to obtain local date
@@ -46,6 +52,42 @@ fn log_format(
     write!(w, "[packet-trace]{}", record.args(),)
 }
 
+/// One JSON object per line, for log collectors like Loki or Elasticsearch.
+fn log_format_json(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let mut line = serde_json::json!({
+        "timestamp": log_format_date(now).to_string(),
+        "level": record.level().as_str(),
+        "module": record.module_path().unwrap_or("<unnamed>"),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+
+    let mut fields = KeyValues(serde_json::Map::new());
+    let _ = record.key_values().visit(&mut fields);
+    if !fields.0.is_empty() {
+        line["fields"] = fields.0.into();
+    }
+
+    serde_json::to_writer(w, &line).map_err(Into::into)
+}
+
+struct KeyValues(serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for KeyValues {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), value.to_string().into());
+        Ok(())
+    }
+}
+
 fn log_format_color(
     w: &mut dyn std::io::Write,
     now: &mut DeferredNow,
@@ -166,9 +208,31 @@ pub fn start_logger(
     }
 
     let log_spec = log_spec_builder.finalize();
-    let mut logger = Logger::with(log_spec).format(log_format);
+    let _ = START_LOG_SPEC.set(log_spec.clone());
+    *CURRENT_LOG_SPEC.lock().unwrap() = Some(log_spec.clone());
 
     let mut messages = Vec::new();
+    let json_format = match env::var("LOG_FORMAT") {
+        Ok(format) => match format.to_ascii_lowercase().as_str() {
+            "json" => true,
+            "text" => false,
+            _ => {
+                messages.push((
+                    log::Level::Error,
+                    format!("LOG_FORMAT ({format}) is neither TEXT nor JSON nor unset"),
+                ));
+                false
+            }
+        },
+        Err(_) => false,
+    };
+
+    let mut logger = Logger::with(log_spec);
+    logger = match json_format {
+        true => logger.format(log_format_json),
+        false => logger.format(log_format),
+    };
+
     if let Some(log_dir) = log_dir {
         let config = FileLogConfig::with_env(log_dir, &mut messages);
         messages.push((
@@ -178,9 +242,12 @@ pub fn start_logger(
 
         logger = set_logging_to_files(logger, config);
     }
-    logger = logger
-        .adaptive_format_for_stderr(AdaptiveFormat::Custom(log_format, log_format_color))
-        .set_palette("9;11;2;7;8".to_string());
+    logger = match json_format {
+        true => logger.format_for_stderr(log_format_json),
+        false => logger
+            .adaptive_format_for_stderr(AdaptiveFormat::Custom(log_format, log_format_color))
+            .set_palette("9;11;2;7;8".to_string()),
+    };
 
     let handle = logger.start()?;
 
@@ -190,3 +257,69 @@ pub fn start_logger(
 
     Ok(handle)
 }
+
+/// Changes log levels of the running logger. `update` uses `RUST_LOG` syntax,
+/// e.g. `ya_net=trace`. Only listed modules (and default level if given) are changed.
+///
+/// Returns log specification in effect.
+pub fn update_log_spec(handle: &mut LoggerHandle, update: &str) -> Result<String> {
+    let update = LogSpecification::parse(update)?;
+
+    let mut current = CURRENT_LOG_SPEC.lock().unwrap();
+    let mut builder = match current.as_ref() {
+        Some(spec) => LogSpecBuilder::from_module_filters(spec.module_filters()),
+        None => LogSpecBuilder::new(),
+    };
+    for filter in update.module_filters() {
+        match &filter.module_name {
+            Some(module) => builder.module(module, filter.level_filter),
+            None => builder.default(filter.level_filter),
+        };
+    }
+
+    let spec = builder.finalize();
+    handle.set_new_spec(spec.clone());
+    let result = log_spec_to_string(&spec);
+    *current = Some(spec);
+
+    log::info!("Log specification changed to: {result}");
+    Ok(result)
+}
+
+/// Restores log levels set at logger start.
+pub fn reset_log_spec(handle: &mut LoggerHandle) -> Result<String> {
+    let spec = START_LOG_SPEC
+        .get()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Logger not started"))?;
+
+    handle.set_new_spec(spec.clone());
+    let result = log_spec_to_string(&spec);
+    *CURRENT_LOG_SPEC.lock().unwrap() = Some(spec);
+
+    log::info!("Log specification reset to: {result}");
+    Ok(result)
+}
+
+/// Log specification in effect, in `RUST_LOG` syntax.
+pub fn current_log_spec() -> Option<String> {
+    CURRENT_LOG_SPEC
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(log_spec_to_string)
+}
+
+fn log_spec_to_string(spec: &LogSpecification) -> String {
+    spec.module_filters()
+        .iter()
+        .map(|filter| {
+            let level = filter.level_filter.to_string().to_lowercase();
+            match &filter.module_name {
+                Some(module) => format!("{module}={level}"),
+                None => level,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
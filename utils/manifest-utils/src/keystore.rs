@@ -1,8 +1,10 @@
 pub mod golem_keystore;
+pub mod revocation;
 pub mod x509_keystore;
 
 use self::{
     golem_keystore::{GolemKeystore, GolemKeystoreBuilder},
+    revocation::RevocationConfig,
     x509_keystore::{X509CertData, X509Keystore, X509KeystoreBuilder, X509KeystoreManager},
 };
 use chrono::{DateTime, Utc};
@@ -169,9 +171,18 @@ pub struct CompositeKeystore {
 }
 
 impl CompositeKeystore {
+    /// Loads keystore from `cert_dir`.
+    /// X.509 revocation checks are configured with `YA_CERT_REVOCATION_*` environment variables.
     pub fn load(cert_dir: &PathBuf) -> anyhow::Result<Self> {
+        Self::load_with_revocation(cert_dir, RevocationConfig::from_env()?)
+    }
+
+    pub fn load_with_revocation(
+        cert_dir: &PathBuf,
+        revocation: RevocationConfig,
+    ) -> anyhow::Result<Self> {
         std::fs::create_dir_all(cert_dir)?;
-        let mut x509_builder = X509KeystoreBuilder::new(cert_dir);
+        let mut x509_builder = X509KeystoreBuilder::new(cert_dir).with_revocation(revocation);
         let mut golem_builder = GolemKeystoreBuilder::new(cert_dir);
 
         let cert_dir = std::fs::read_dir(cert_dir)?;
@@ -0,0 +1,686 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+    str::FromStr,
+    sync::{mpsc, Arc, Condvar, Mutex, OnceLock},
+};
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use openssl::{
+    hash::MessageDigest,
+    ocsp::{OcspCertId, OcspCertStatus, OcspFlag, OcspRequest, OcspResponse, OcspResponseStatus},
+    stack::Stack,
+    x509::{
+        store::X509StoreBuilder, verify::X509VerifyFlags, CrlStatus, X509Crl, X509CrlRef, X509Ref,
+        X509,
+    },
+};
+use reqwest::blocking::{Client, Response};
+use reqwest::header::CONTENT_TYPE;
+use strum::{Display, EnumString, EnumVariantNames};
+
+use super::x509_keystore::{asn1_time_to_date_time, cert_to_id};
+
+/// Revocation check mode. One of `off`, `warn`, `hard-fail`.
+pub const REVOCATION_MODE_ENV_VAR: &str = "YA_CERT_REVOCATION_MODE";
+/// Enables OCSP queries (`true`/`false`). CRLs are used when OCSP is disabled or unavailable.
+pub const REVOCATION_OCSP_ENV_VAR: &str = "YA_CERT_REVOCATION_OCSP";
+/// CRL and OCSP request timeout in seconds.
+pub const REVOCATION_TIMEOUT_ENV_VAR: &str = "YA_CERT_REVOCATION_TIMEOUT";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+/// Maximum age of a cached CRL which does not specify its `nextUpdate` time.
+const DEFAULT_CRL_MAX_AGE_SECS: i64 = 3600;
+const OCSP_MAX_AGE_SECS: i64 = 300;
+/// Failed fetches are not retried for this long.
+const FAILURE_BACKOFF_SECS: i64 = 60;
+const MAX_RESPONSE_SIZE: u64 = 10 * 1024 * 1024;
+/// Maximum number of cached CRLs and, separately, OCSP responses.
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+/// Decides what happens when certificate revocation status cannot be determined,
+/// e.g. when CRL distribution point or OCSP responder is unreachable.
+///
+/// Revoked certificates are rejected in every mode except `Off`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString, EnumVariantNames, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RevocationMode {
+    /// Revocation is not checked.
+    #[default]
+    Off,
+    /// Failed checks are logged and the certificate is accepted.
+    Warn,
+    /// Failed checks reject the certificate.
+    HardFail,
+}
+
+#[derive(Clone, Debug)]
+pub struct RevocationConfig {
+    pub mode: RevocationMode,
+    pub ocsp: bool,
+    pub timeout: std::time::Duration,
+}
+
+impl Default for RevocationConfig {
+    fn default() -> Self {
+        RevocationConfig {
+            mode: RevocationMode::default(),
+            ocsp: false,
+            timeout: std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl RevocationConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut config = Self::default();
+        if let Ok(mode) = std::env::var(REVOCATION_MODE_ENV_VAR) {
+            config.mode = RevocationMode::from_str(mode.trim())
+                .map_err(|_| anyhow!("Invalid {REVOCATION_MODE_ENV_VAR} value: {mode}"))?;
+        }
+        if let Ok(ocsp) = std::env::var(REVOCATION_OCSP_ENV_VAR) {
+            config.ocsp = ocsp
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Invalid {REVOCATION_OCSP_ENV_VAR} value: {ocsp}"))?;
+        }
+        if let Ok(timeout) = std::env::var(REVOCATION_TIMEOUT_ENV_VAR) {
+            let secs: u64 = timeout
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("Invalid {REVOCATION_TIMEOUT_ENV_VAR} value: {timeout}"))?;
+            config.timeout = std::time::Duration::from_secs(secs);
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RevocationStatus {
+    Good,
+    Revoked,
+    /// Certificate does not specify any CRL distribution point nor OCSP responder.
+    Unchecked,
+}
+
+enum Entry<T> {
+    Fetched {
+        value: T,
+        valid_until: DateTime<Utc>,
+    },
+    Failed {
+        error: String,
+        retry_after: DateTime<Utc>,
+    },
+}
+
+impl<T> Entry<T> {
+    fn expires(&self) -> DateTime<Utc> {
+        match self {
+            Entry::Fetched { valid_until, .. } => *valid_until,
+            Entry::Failed { retry_after, .. } => *retry_after,
+        }
+    }
+}
+
+/// Entries are kept until they expire. When the cache is full, expired entries are
+/// evicted first, then the ones which would expire soonest.
+struct TtlCache<T> {
+    entries: HashMap<String, Entry<T>>,
+    capacity: usize,
+}
+
+impl<T> Default for TtlCache<T> {
+    fn default() -> Self {
+        TtlCache {
+            entries: Default::default(),
+            capacity: MAX_CACHE_ENTRIES,
+        }
+    }
+}
+
+impl<T: Clone> TtlCache<T> {
+    /// Returns `None` when `key` is missing or has expired.
+    fn get(&self, key: &str, now: DateTime<Utc>) -> Option<anyhow::Result<T>> {
+        match self.entries.get(key)? {
+            entry if entry.expires() <= now => None,
+            Entry::Fetched { value, .. } => Some(Ok(value.clone())),
+            Entry::Failed { error, .. } => Some(Err(anyhow!("{error}"))),
+        }
+    }
+
+    fn insert(&mut self, key: String, entry: Entry<T>, now: DateTime<Utc>) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.entries.retain(|_, entry| entry.expires() > now);
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let soonest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires())
+                .map(|(key, _)| key.clone());
+            if let Some(soonest) = soonest {
+                self.entries.remove(&soonest);
+            }
+        }
+        self.entries.insert(key, entry);
+    }
+}
+
+#[derive(Default)]
+struct Cache {
+    crls: TtlCache<Arc<X509Crl>>,
+    ocsp: TtlCache<RevocationStatus>,
+    /// Keys of fetches in progress
+    pending: HashSet<String>,
+}
+
+type Select<T> = fn(&mut Cache) -> &mut TtlCache<T>;
+
+type Fetch = Box<dyn FnOnce(&Client) + Send>;
+
+/// Revocation information for every keystore is fetched by a single thread,
+/// so verification never does network I/O while holding the keystore lock.
+fn fetch_in_background(fetch: Fetch) -> anyhow::Result<()> {
+    static FETCHER: OnceLock<mpsc::Sender<Fetch>> = OnceLock::new();
+    FETCHER
+        .get_or_init(|| {
+            let (tx, rx) = mpsc::channel::<Fetch>();
+            std::thread::spawn(move || {
+                let client = Client::new();
+                for fetch in rx {
+                    fetch(&client);
+                }
+            });
+            tx
+        })
+        .send(fetch)
+        .map_err(|_| anyhow!("revocation information fetcher is not running"))
+}
+
+/// Checks revocation status of X.509 certificate chains using CRLs and OCSP.
+///
+/// Revocation information is cached: CRLs until their `nextUpdate` time, OCSP responses
+/// for a few minutes and failures for a minute. On a cache miss the check waits for
+/// the fetch up to the configured timeout, in every mode, so a revoked certificate
+/// is accepted only when its status can't be determined in `Warn` mode.
+#[derive(Clone, Default)]
+pub struct RevocationChecker {
+    config: RevocationConfig,
+    cache: Arc<(Mutex<Cache>, Condvar)>,
+}
+
+impl RevocationChecker {
+    pub fn new(config: RevocationConfig) -> Self {
+        RevocationChecker {
+            config,
+            cache: Default::default(),
+        }
+    }
+
+    pub fn config(&self) -> &RevocationConfig {
+        &self.config
+    }
+
+    /// Checks every certificate of verified `chain` (sorted from leaf to root)
+    /// against the revocation information published by its issuer.
+    pub fn check_chain(&self, chain: &[X509]) -> anyhow::Result<()> {
+        if self.config.mode == RevocationMode::Off {
+            return Ok(());
+        }
+
+        for pair in chain.windows(2) {
+            let (cert, issuer) = (&pair[0], &pair[1]);
+            let id = cert_to_id(cert)?;
+            match self.status(cert, issuer) {
+                Ok(RevocationStatus::Good) => {
+                    log::trace!("X.509 certificate {id} is not revoked");
+                }
+                Ok(RevocationStatus::Unchecked) => {
+                    log::trace!("X.509 certificate {id} has no revocation information");
+                }
+                Ok(RevocationStatus::Revoked) => {
+                    bail!("X.509 certificate {id} has been revoked")
+                }
+                Err(err) => match self.config.mode {
+                    RevocationMode::HardFail => {
+                        bail!("Unable to check revocation status of X.509 certificate {id}: {err}")
+                    }
+                    _ => log::warn!(
+                        "Unable to check revocation status of X.509 certificate {id}: {err}"
+                    ),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn status(&self, cert: &X509Ref, issuer: &X509Ref) -> anyhow::Result<RevocationStatus> {
+        let mut last_err = None;
+        let timeout = self.config.timeout;
+
+        if self.config.ocsp {
+            for responder in cert.ocsp_responders()?.iter() {
+                let url = responder.to_string();
+                let key = format!("{url} {}", cert_to_id(cert)?);
+                let (cert, issuer) = (cert.to_owned(), issuer.to_owned());
+                let fetch =
+                    move |client: &Client| ocsp_status(client, &url, &cert, &issuer, timeout);
+                match self.cached(key, |cache| &mut cache.ocsp, fetch) {
+                    Ok(status) => return Ok(status),
+                    Err(err) => {
+                        log::debug!("OCSP query to '{responder}' failed: {err}");
+                        last_err = Some(err);
+                    }
+                }
+            }
+        }
+
+        for url in crl_urls(cert) {
+            let (key, issuer) = (url.clone(), issuer.to_owned());
+            let fetch = move |client: &Client| fetch_crl(client, &key, &issuer, timeout);
+            match self.cached(url.clone(), |cache| &mut cache.crls, fetch) {
+                Ok(crl) => return Ok(crl_status(&crl, cert)),
+                Err(err) => {
+                    log::debug!("Failed to fetch CRL from '{url}': {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        match last_err {
+            Some(err) => Err(err),
+            None => Ok(RevocationStatus::Unchecked),
+        }
+    }
+
+    /// Returns cached value of `key`, fetching it when missing or outdated.
+    /// Concurrent checks share a single fetch.
+    fn cached<T, F>(&self, key: String, select: Select<T>, fetch: F) -> anyhow::Result<T>
+    where
+        T: Clone + Send + 'static,
+        F: FnOnce(&Client) -> anyhow::Result<(T, DateTime<Utc>)> + Send + 'static,
+    {
+        let (lock, fetched) = &*self.cache;
+        let mut cache = lock.lock().unwrap();
+        if let Some(result) = select(&mut cache).get(&key, Utc::now()) {
+            return result;
+        }
+
+        if cache.pending.insert(key.clone()) {
+            let shared = self.cache.clone();
+            let pending = key.clone();
+            let job = Box::new(move |client: &Client| {
+                let entry = match fetch(client) {
+                    Ok((value, valid_until)) => Entry::Fetched { value, valid_until },
+                    Err(err) => Entry::Failed {
+                        error: err.to_string(),
+                        retry_after: Utc::now() + Duration::seconds(FAILURE_BACKOFF_SECS),
+                    },
+                };
+                let (lock, fetched) = &*shared;
+                let mut cache = lock.lock().unwrap();
+                cache.pending.remove(&pending);
+                select(&mut cache).insert(pending, entry, Utc::now());
+                fetched.notify_all();
+            });
+            if let Err(err) = fetch_in_background(job) {
+                cache.pending.remove(&key);
+                return Err(err);
+            }
+        }
+
+        let (mut cache, _) = fetched
+            .wait_timeout_while(cache, self.config.timeout, |cache| {
+                cache.pending.contains(&key)
+            })
+            .unwrap();
+        if cache.pending.contains(&key) {
+            bail!("fetching revocation information timed out");
+        }
+        match select(&mut cache).get(&key, Utc::now()) {
+            Some(result) => result,
+            None => bail!("revocation information is not available"),
+        }
+    }
+}
+
+fn ocsp_status(
+    client: &Client,
+    url: &str,
+    cert: &X509Ref,
+    issuer: &X509Ref,
+    timeout: std::time::Duration,
+) -> anyhow::Result<(RevocationStatus, DateTime<Utc>)> {
+    let mut request = OcspRequest::new()?;
+    request.add_id(OcspCertId::from_cert(MessageDigest::sha1(), cert, issuer)?)?;
+
+    let response = client
+        .post(url)
+        .timeout(timeout)
+        .header(CONTENT_TYPE, "application/ocsp-request")
+        .body(request.to_der()?)
+        .send()?
+        .error_for_status()?;
+    let status = ocsp_response_status(&read_body(response)?, cert, issuer)?;
+    Ok((status, Utc::now() + Duration::seconds(OCSP_MAX_AGE_SECS)))
+}
+
+/// Verifies OCSP response and returns the status of `cert` it contains.
+fn ocsp_response_status(
+    der: &[u8],
+    cert: &X509Ref,
+    issuer: &X509Ref,
+) -> anyhow::Result<RevocationStatus> {
+    let response = OcspResponse::from_der(der)?;
+    if response.status() != OcspResponseStatus::SUCCESSFUL {
+        bail!("OCSP responder returned status {:?}", response.status());
+    }
+
+    // Responder is either the issuer or delegated by it.
+    // Issuer itself has been verified against the keystore.
+    let mut store = X509StoreBuilder::new()?;
+    store.add_cert(issuer.to_owned())?;
+    store.set_flags(X509VerifyFlags::PARTIAL_CHAIN)?;
+    let store = store.build();
+
+    let basic = response.basic()?;
+    let mut certs = Stack::new()?;
+    certs.push(issuer.to_owned())?;
+    basic.verify(&certs, &store, OcspFlag::empty())?;
+
+    let id = OcspCertId::from_cert(MessageDigest::sha1(), cert, issuer)?;
+    let status = basic
+        .find_status(&id)
+        .ok_or_else(|| anyhow!("OCSP response does not contain certificate status"))?;
+    status.check_validity(300, None)?;
+
+    match status.status {
+        OcspCertStatus::GOOD => Ok(RevocationStatus::Good),
+        OcspCertStatus::REVOKED => Ok(RevocationStatus::Revoked),
+        _ => bail!("OCSP responder does not know the certificate"),
+    }
+}
+
+fn fetch_crl(
+    client: &Client,
+    url: &str,
+    issuer: &X509Ref,
+    timeout: std::time::Duration,
+) -> anyhow::Result<(Arc<X509Crl>, DateTime<Utc>)> {
+    log::debug!("Fetching CRL from '{url}'");
+    let response = client
+        .get(url)
+        .timeout(timeout)
+        .send()?
+        .error_for_status()?;
+    parse_crl(&read_body(response)?, issuer)
+}
+
+/// Parses CRL in DER or PEM format and verifies it was signed by `issuer`.
+fn parse_crl(body: &[u8], issuer: &X509Ref) -> anyhow::Result<(Arc<X509Crl>, DateTime<Utc>)> {
+    let crl = X509Crl::from_der(body).or_else(|_| X509Crl::from_pem(body))?;
+
+    if !crl.verify(issuer.public_key()?.as_ref())? {
+        bail!("Invalid CRL signature");
+    }
+    let now = Utc::now();
+    let valid_until = match crl.next_update() {
+        Some(next_update) => asn1_time_to_date_time(next_update)?,
+        None => now + Duration::seconds(DEFAULT_CRL_MAX_AGE_SECS),
+    };
+    if valid_until <= now {
+        bail!("CRL is outdated (next update: {valid_until})");
+    }
+    Ok((Arc::new(crl), valid_until))
+}
+
+fn crl_status(crl: &X509CrlRef, cert: &X509Ref) -> RevocationStatus {
+    match crl.get_by_cert(&cert.to_owned()) {
+        CrlStatus::Revoked(_) => RevocationStatus::Revoked,
+        _ => RevocationStatus::Good,
+    }
+}
+
+/// Lists URIs of CRL distribution points of given certificate.
+fn crl_urls(cert: &X509Ref) -> Vec<String> {
+    cert.crl_distribution_points()
+        .into_iter()
+        .flat_map(|points| {
+            points
+                .iter()
+                .filter_map(|point| point.distpoint().and_then(|name| name.fullname()))
+                .flat_map(|names| names.iter().filter_map(|name| name.uri()))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn read_body(response: Response) -> anyhow::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    response.take(MAX_RESPONSE_SIZE).read_to_end(&mut body)?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ya_manifest_test_utils::TestResources;
+
+    #[test]
+    fn revocation_mode_from_str() {
+        assert_eq!(
+            RevocationMode::from_str("off").unwrap(),
+            RevocationMode::Off
+        );
+        assert_eq!(
+            RevocationMode::from_str("warn").unwrap(),
+            RevocationMode::Warn
+        );
+        assert_eq!(
+            RevocationMode::from_str("hard-fail").unwrap(),
+            RevocationMode::HardFail
+        );
+        assert!(RevocationMode::from_str("fail").is_err());
+    }
+
+    fn checker(mode: RevocationMode) -> RevocationChecker {
+        RevocationChecker::new(RevocationConfig {
+            mode,
+            ocsp: true,
+            timeout: std::time::Duration::from_secs(5),
+        })
+    }
+
+    fn good(_: &Client) -> anyhow::Result<(RevocationStatus, DateTime<Utc>)> {
+        Ok((RevocationStatus::Good, Utc::now() + Duration::seconds(60)))
+    }
+
+    fn revoked(_: &Client) -> anyhow::Result<(RevocationStatus, DateTime<Utc>)> {
+        Ok((
+            RevocationStatus::Revoked,
+            Utc::now() + Duration::seconds(60),
+        ))
+    }
+
+    fn resource(name: &str) -> Vec<u8> {
+        let path = TestResources::test_resources_dir_path().join("revocation");
+        std::fs::read(path.join(name)).unwrap()
+    }
+
+    fn cert(name: &str) -> X509 {
+        X509::from_pem(&resource(name)).unwrap()
+    }
+
+    /// Responds to a single HTTP request with `body`. Returns the URL to query.
+    fn serve(body: Vec<u8>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim().to_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut request = vec![0; content_length];
+            reader.read_exact(&mut request).unwrap();
+
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn hard_fail_waits_for_fetch() {
+        let checker = checker(RevocationMode::HardFail);
+        let status = checker.cached("key".into(), |cache| &mut cache.ocsp, good);
+        assert_eq!(status.unwrap(), RevocationStatus::Good);
+
+        // cached value is used
+        let status = checker.cached("key".into(), |cache| &mut cache.ocsp, |_| panic!());
+        assert_eq!(status.unwrap(), RevocationStatus::Good);
+    }
+
+    #[test]
+    fn warn_waits_for_fetch() {
+        let checker = checker(RevocationMode::Warn);
+        let status = checker.cached("key".into(), |cache| &mut cache.ocsp, revoked);
+        assert_eq!(status.unwrap(), RevocationStatus::Revoked);
+    }
+
+    #[test]
+    fn pending_fetch_is_shared() {
+        let checker = checker(RevocationMode::HardFail);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let fetch = move |client: &Client| {
+            rx.recv().ok();
+            good(client)
+        };
+
+        let waiting = checker.clone();
+        let first = std::thread::spawn(move || {
+            waiting.cached("key".into(), |cache| &mut cache.ocsp, fetch)
+        });
+        while !checker.cache.0.lock().unwrap().pending.contains("key") {
+            std::thread::yield_now();
+        }
+        let second = {
+            let waiting = checker.clone();
+            std::thread::spawn(move || {
+                waiting.cached("key".into(), |cache| &mut cache.ocsp, |_| panic!())
+            })
+        };
+
+        tx.send(()).unwrap();
+        assert_eq!(first.join().unwrap().unwrap(), RevocationStatus::Good);
+        assert_eq!(second.join().unwrap().unwrap(), RevocationStatus::Good);
+    }
+
+    #[test]
+    fn failed_fetch_is_not_retried_immediately() {
+        let checker = checker(RevocationMode::HardFail);
+        let failed = |_: &Client| -> anyhow::Result<(RevocationStatus, DateTime<Utc>)> {
+            bail!("unreachable")
+        };
+        let err = checker
+            .cached("key".into(), |cache| &mut cache.ocsp, failed)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unreachable");
+
+        let err = checker
+            .cached("key".into(), |cache| &mut cache.ocsp, |_| panic!())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unreachable");
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let now = Utc::now();
+        let entry = |secs| Entry::Fetched {
+            value: (),
+            valid_until: now + Duration::seconds(secs),
+        };
+        let mut cache = TtlCache {
+            entries: Default::default(),
+            capacity: 2,
+        };
+        cache.insert("expired".into(), entry(-1), now);
+        cache.insert("late".into(), entry(20), now);
+        cache.insert("early".into(), entry(10), now);
+        assert!(!cache.entries.contains_key("expired"));
+        assert!(cache.get("early", now).is_some());
+
+        cache.insert("latest".into(), entry(30), now);
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get("early", now).is_none());
+        assert!(cache.get("late", now).is_some());
+        assert!(cache.get("late", now + Duration::seconds(20)).is_none());
+    }
+
+    #[test]
+    fn crl_revokes_certificate() {
+        let (crl, valid_until) = parse_crl(&resource("crl.der"), &cert("ca.pem")).unwrap();
+        assert!(valid_until > Utc::now());
+        assert_eq!(crl_status(&crl, &cert("good.pem")), RevocationStatus::Good);
+        assert_eq!(
+            crl_status(&crl, &cert("revoked.pem")),
+            RevocationStatus::Revoked
+        );
+    }
+
+    #[test]
+    fn crl_signed_by_other_issuer_is_rejected() {
+        assert!(parse_crl(&resource("crl.der"), &cert("good.pem")).is_err());
+    }
+
+    #[test]
+    fn ocsp_response_revokes_certificate() {
+        let ca = cert("ca.pem");
+        for (name, status) in [
+            ("good", RevocationStatus::Good),
+            ("revoked", RevocationStatus::Revoked),
+        ] {
+            let cert = cert(&format!("{name}.pem"));
+            let response = resource(&format!("{name}.ocsp.der"));
+            assert_eq!(ocsp_response_status(&response, &cert, &ca).unwrap(), status);
+        }
+        // Response doesn't describe another certificate
+        let response = resource("good.ocsp.der");
+        assert!(ocsp_response_status(&response, &cert("revoked.pem"), &ca).is_err());
+    }
+
+    #[test]
+    fn fetch_revocation_information() {
+        let client = Client::new();
+        let timeout = std::time::Duration::from_secs(5);
+        let (ca, revoked) = (cert("ca.pem"), cert("revoked.pem"));
+
+        let url = serve(resource("crl.der"));
+        let (crl, _) = fetch_crl(&client, &url, &ca, timeout).unwrap();
+        assert_eq!(crl_status(&crl, &revoked), RevocationStatus::Revoked);
+
+        let url = serve(resource("revoked.ocsp.der"));
+        let (status, _) = ocsp_status(&client, &url, &revoked, &ca, timeout).unwrap();
+        assert_eq!(status, RevocationStatus::Revoked);
+    }
+}
@@ -1,4 +1,5 @@
 use super::{
+    revocation::{RevocationChecker, RevocationConfig},
     AddParams, AddResponse, Cert, CommonAddParams, Keystore, KeystoreBuilder, RemoveParams,
     RemoveResponse, SignatureVerifier,
 };
@@ -55,7 +56,7 @@ impl X509CertData {
     }
 }
 
-fn asn1_time_to_date_time(time: &Asn1TimeRef) -> anyhow::Result<DateTime<Utc>> {
+pub(super) fn asn1_time_to_date_time(time: &Asn1TimeRef) -> anyhow::Result<DateTime<Utc>> {
     // Openssl lib allows to access time only through ASN1_TIME_print.
     // Diff starting from epoch is a workaround to get `not_after` value.
     let time_diff = Asn1Time::from_unix(0)?.diff(time)?;
@@ -75,13 +76,23 @@ pub(super) struct AddX509Response {
 pub struct X509KeystoreBuilder {
     builder: X509StoreBuilder,
     cert_dir: PathBuf,
+    revocation: RevocationConfig,
 }
 
 impl X509KeystoreBuilder {
     pub fn new(cert_dir: impl AsRef<Path>) -> Self {
         let builder = X509StoreBuilder::new().expect("OpenSSL works");
         let cert_dir = PathBuf::from(cert_dir.as_ref());
-        Self { builder, cert_dir }
+        Self {
+            builder,
+            cert_dir,
+            revocation: Default::default(),
+        }
+    }
+
+    pub fn with_revocation(mut self, revocation: RevocationConfig) -> Self {
+        self.revocation = revocation;
+        self
     }
 }
 
@@ -96,7 +107,10 @@ impl KeystoreBuilder<X509KeystoreManager> for X509KeystoreBuilder {
     fn build(self) -> anyhow::Result<X509KeystoreManager> {
         let keystore = self.builder.build();
         let inner = Arc::new(RwLock::new(CertStore::new(keystore)));
-        let keystore = X509Keystore { store: inner };
+        let keystore = X509Keystore {
+            store: inner,
+            revocation: RevocationChecker::new(self.revocation),
+        };
         let ids = keystore.certs_ids()?;
         Ok(X509KeystoreManager {
             keystore,
@@ -324,10 +338,12 @@ impl Keystore for X509KeystoreManager {
     fn verifier(&self, cert: &str) -> anyhow::Result<Box<dyn super::SignatureVerifier>> {
         let cert_chain = X509Keystore::decode_cert_chain(cert)?;
         let cert_store = self.keystore.store.clone();
+        let revocation = self.keystore.revocation.clone();
         Ok(Box::new(X509SignatureVerifier {
             signature_alg: "sha256".into(),
             cert_chain,
             cert_store,
+            revocation,
         }))
     }
 }
@@ -336,6 +352,7 @@ struct X509SignatureVerifier {
     signature_alg: String,
     cert_chain: Vec<X509>,
     cert_store: Arc<RwLock<CertStore>>,
+    revocation: RevocationChecker,
 }
 
 impl SignatureVerifier for X509SignatureVerifier {
@@ -346,11 +363,15 @@ impl SignatureVerifier for X509SignatureVerifier {
 
     fn verify(&self, data: &str, signature: &str) -> anyhow::Result<Vec<String>> {
         let sig = crate::decode_data(signature)?;
-        let cert_store = self
-            .cert_store
-            .read()
-            .map_err(|err| anyhow::anyhow!("Err: {}", err.to_string()))?;
-        let pub_key = verify_cert_chain(&cert_store, &self.cert_chain)?;
+        let (pub_key, verified_chain) = {
+            let cert_store = self
+                .cert_store
+                .read()
+                .map_err(|err| anyhow::anyhow!("Err: {}", err.to_string()))?;
+            verify_cert_chain(&cert_store, &self.cert_chain)?
+        };
+        // Revocation information might need to be fetched, so keystore is not locked.
+        self.revocation.check_chain(&verified_chain)?;
 
         let msg_digest =
             MessageDigest::from_name(self.signature_alg.as_ref()).ok_or_else(|| {
@@ -361,6 +382,10 @@ impl SignatureVerifier for X509SignatureVerifier {
             return Err(anyhow::anyhow!("Invalid signature."));
         }
 
+        let cert_store = self
+            .cert_store
+            .read()
+            .map_err(|err| anyhow::anyhow!("Err: {}", err.to_string()))?;
         self.whole_cert_chain_ids(&cert_store)
     }
 }
@@ -395,6 +420,7 @@ struct CertStore {
 #[derive(Clone)]
 pub struct X509Keystore {
     store: Arc<RwLock<CertStore>>,
+    revocation: RevocationChecker,
 }
 
 impl Default for X509Keystore {
@@ -403,6 +429,7 @@ impl Default for X509Keystore {
         let store = CertStore::new(store);
         Self {
             store: Arc::new(RwLock::new(store)),
+            revocation: Default::default(),
         }
     }
 }
@@ -429,7 +456,16 @@ impl X509Keystore {
         }
         let store = CertStore::new(store.build());
         let store = Arc::new(RwLock::new(store));
-        Ok(Self { store })
+        Ok(Self {
+            store,
+            revocation: Default::default(),
+        })
+    }
+
+    /// Enables certificate revocation checks on signature verification.
+    pub fn with_revocation(mut self, revocation: RevocationConfig) -> Self {
+        self.revocation = RevocationChecker::new(revocation);
+        self
     }
 
     pub fn reload(&self, cert_dir: impl AsRef<Path>) -> anyhow::Result<()> {
@@ -510,11 +546,15 @@ impl X509Keystore {
 
     fn verify_cert<S: AsRef<str>>(&self, cert: S) -> anyhow::Result<PKey<Public>> {
         let cert_chain = Self::decode_cert_chain(cert)?;
-        let cert_store = self
-            .store
-            .read()
-            .map_err(|err| anyhow::anyhow!("Err: {}", err.to_string()))?;
-        verify_cert_chain(&cert_store, &cert_chain)
+        let (pub_key, verified_chain) = {
+            let cert_store = self
+                .store
+                .read()
+                .map_err(|err| anyhow::anyhow!("Err: {}", err.to_string()))?;
+            verify_cert_chain(&cert_store, &cert_chain)?
+        };
+        self.revocation.check_chain(&verified_chain)?;
+        Ok(pub_key)
     }
 
     /// Decodes certificate chain.
@@ -529,10 +569,12 @@ impl X509Keystore {
     }
 }
 
+/// Verifies `cert_chain` against the keystore. Returns public key of the leaf certificate
+/// and the verified chain, sorted from leaf to root.
 fn verify_cert_chain(
     cert_store: &CertStore,
     cert_chain: &Vec<X509>,
-) -> anyhow::Result<PKey<Public>> {
+) -> anyhow::Result<(PKey<Public>, Vec<X509>)> {
     let cert = match cert_chain.last().cloned() {
         Some(cert) => cert,
         None => bail!("Unable to verify X509 certificate. No X509 certificate in payload."),
@@ -545,12 +587,18 @@ fn verify_cert_chain(
         cert_stack.push(cert.clone()).unwrap();
     }
     let mut ctx = X509StoreContext::new()?;
-    if !(ctx.init(&cert_store.store, &cert, &cert_stack, |ctx| {
-        ctx.verify_cert()
-    })?) {
-        bail!("Unable to verify X509 certificate.");
+    let verified_chain = ctx.init(&cert_store.store, &cert, &cert_stack, |ctx| {
+        if !ctx.verify_cert()? {
+            return Ok(None);
+        }
+        Ok(ctx
+            .chain()
+            .map(|chain| chain.iter().map(ToOwned::to_owned).collect::<Vec<X509>>()))
+    })?;
+    match verified_chain {
+        Some(chain) => Ok((cert.public_key()?, chain)),
+        None => bail!("Unable to verify X509 certificate."),
     }
-    Ok(cert.public_key()?)
 }
 
 fn issuer(cert_store: &CertStore, cert: &X509Ref) -> Option<X509> {
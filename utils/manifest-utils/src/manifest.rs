@@ -17,6 +17,7 @@ use ya_agreement_utils::AgreementView;
 use ya_agreement_utils::Error as AgreementError;
 
 use crate::decode_data;
+use crate::matching::url_pattern::UrlPattern;
 
 pub const CAPABILITIES_PROPERTY: &str = "golem.runtime.capabilities";
 pub const DEMAND_MANIFEST_PROPERTY: &str = "golem.srv.comp.payload";
@@ -344,6 +345,17 @@ impl OutboundAccess {
             OutboundAccess::Unrestricted => true,
         }
     }
+
+    /// Checks whether outbound access to `url` is permitted.
+    /// Each of the requested URLs is interpreted as a `UrlPattern`.
+    pub fn allows(&self, url: &Url) -> bool {
+        match self {
+            OutboundAccess::Urls(urls) => urls
+                .iter()
+                .any(|pattern| UrlPattern::from(pattern).matches(url)),
+            OutboundAccess::Unrestricted => true,
+        }
+    }
 }
 
 mod outbound_access {
@@ -355,7 +367,8 @@ mod outbound_access {
     pub enum Representation {
         /// List of allowed external URLs that outbound requests can be sent to.
         /// Empty list means no outbound access is requested.
-        /// E.g. ["http://golemfactory.s3.amazonaws.com/file1", "http://golemfactory.s3.amazonaws.com/file2"]
+        /// URLs are restricted to their scheme and port. Path ending with `*` is treated as a prefix.
+        /// E.g. ["http://golemfactory.s3.amazonaws.com/file1", "https://api.example.com:8443/v1/*"]
         Urls { urls: Vec<Url> },
         /// Every URL is allowed for outbound connection.
         Unrestricted { unrestricted: Unrestricted },
@@ -477,6 +490,21 @@ mod tests {
         println!("{}", general_purpose::STANDARD.encode(serialized));
     }
 
+    #[test]
+    fn outbound_access_allows_url_patterns() {
+        let access = OutboundAccess::Urls(vec![
+            Url::parse("https://api.example.com:8443/v1/*").unwrap(),
+            Url::parse("http://example.net").unwrap(),
+        ]);
+
+        let allowed = |url: &str| access.allows(&Url::parse(url).unwrap());
+        assert!(allowed("https://api.example.com:8443/v1/users"));
+        assert!(allowed("http://example.net/any"));
+        assert!(!allowed("https://api.example.com:8443/v2/users"));
+        assert!(!allowed("https://api.example.com/v1/users"));
+        assert!(!allowed("https://example.net/"));
+    }
+
     mod outbound_access_serde {
         use super::*;
 
@@ -1,4 +1,5 @@
 pub mod domain;
+pub mod url_pattern;
 
 use std::{collections::HashSet, fmt::Debug};
 
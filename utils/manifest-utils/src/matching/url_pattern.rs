@@ -0,0 +1,104 @@
+use url::Url;
+
+/// Outbound URL pattern, e.g. `https://api.example.com:8443/v1/*`.
+///
+/// A pattern matches URLs with the same scheme, host and port (explicit or the scheme's
+/// default one). Path is matched as follows:
+///  - empty path or `/` matches any path,
+///  - path ending with `*` matches paths starting with the preceding prefix,
+///  - any other path has to match exactly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrlPattern {
+    scheme: String,
+    host: String,
+    port: Option<u16>,
+    path: PathPattern,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PathPattern {
+    Any,
+    Prefix(String),
+    Exact(String),
+}
+
+impl UrlPattern {
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Checks whether `url` is permitted by the pattern.
+    ///
+    /// `url` can be a pattern itself, in which case it is matched when all URLs
+    /// permitted by it are permitted by `self` as well.
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.to_lowercase(),
+            None => return false,
+        };
+        if self.scheme != url.scheme() || self.host != host {
+            return false;
+        }
+        if self.port != url.port_or_known_default() {
+            return false;
+        }
+        match &self.path {
+            PathPattern::Any => true,
+            PathPattern::Prefix(prefix) => url.path().starts_with(prefix.as_str()),
+            PathPattern::Exact(path) => url.path() == path,
+        }
+    }
+}
+
+impl From<&Url> for UrlPattern {
+    fn from(url: &Url) -> Self {
+        let path = match url.path() {
+            "" | "/" => PathPattern::Any,
+            path => match path.strip_suffix('*') {
+                Some(prefix) => PathPattern::Prefix(prefix.to_string()),
+                None => PathPattern::Exact(path.to_string()),
+            },
+        };
+        UrlPattern {
+            scheme: url.scheme().to_string(),
+            host: url.host_str().unwrap_or_default().to_lowercase(),
+            port: url.port_or_known_default(),
+            path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn pattern(url: &str) -> UrlPattern {
+        UrlPattern::from(&Url::parse(url).unwrap())
+    }
+
+    #[test_case("https://example.com", "https://example.com/any/path", true ; "Host matches any path")]
+    #[test_case("https://example.com", "https://example.com:443/", true ; "Default port")]
+    #[test_case("https://example.com", "http://example.com/", false ; "Different scheme")]
+    #[test_case("https://example.com", "https://example.com:8443/", false ; "Different port")]
+    #[test_case("https://example.com", "https://sub.example.com/", false ; "Different host")]
+    #[test_case("https://api.example.com:8443/v1/*", "https://api.example.com:8443/v1/users", true ; "Path prefix")]
+    #[test_case("https://api.example.com:8443/v1/*", "https://api.example.com:8443/v1/*", true ; "Same pattern")]
+    #[test_case("https://api.example.com:8443/v1/*", "https://api.example.com:8443/v2/users", false ; "Path outside prefix")]
+    #[test_case("https://api.example.com:8443/v1/*", "https://api.example.com:8443/", false ; "Wider pattern")]
+    #[test_case("https://api.example.com:8443/v1/*", "https://api.example.com/v1/users", false ; "Prefix on other port")]
+    #[test_case("http://example.com/file", "http://example.com/file", true ; "Exact path")]
+    #[test_case("http://example.com/file", "http://example.com/file2", false ; "Different path")]
+    fn url_pattern_matches(pattern_url: &str, url: &str, expected: bool) {
+        let url = Url::parse(url).unwrap();
+        assert_eq!(pattern(pattern_url).matches(&url), expected);
+    }
+}
@@ -41,7 +41,7 @@ pub async fn resolve_srv_record(record: &str) -> std::io::Result<String> {
 }
 
 pub async fn resolve_href_record(record: &str) -> std::io::Result<Vec<String>> {
-    eprintln!("record={record}");
+    log::debug!("Resolving TXT record: {record}");
     let opts = ResolverOpts::default();
 
     let resolver: TokioAsyncResolver =
@@ -1,8 +1,8 @@
 use anyhow::{anyhow, Result};
 use derive_more::Display;
 use futures::channel::oneshot::channel;
-use futures::future::{AbortHandle, Abortable};
 use shared_child::SharedChild;
+use std::collections::HashSet;
 use std::process::Command;
 use std::sync::Arc;
 use std::thread;
@@ -30,6 +30,9 @@ use winapi::um::{
     wincon::{GenerateConsoleCtrlEvent, CTRL_BREAK_EVENT},
 };
 
+/// Interval of checking whether terminated processes are still running.
+const TERMINATE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum ProcessError {
     #[error("Unsupported: {0}")]
@@ -97,6 +100,55 @@ pub enum ExeUnitExitStatus {
     Error(std::io::Error),
 }
 
+/// Stage of process tree termination.
+///
+/// On Windows both `Interrupt` and `Terminate` send CTRL-BREAK event,
+/// while `Kill` terminates the job object of the process.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum TerminateStage {
+    /// SIGINT
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+    /// SIGKILL
+    Kill,
+}
+
+impl TerminateStage {
+    /// SIGINT, SIGTERM and SIGKILL, each followed by `timeout`.
+    pub fn cascade(timeout: Duration) -> Vec<(TerminateStage, Duration)> {
+        vec![
+            (TerminateStage::Interrupt, timeout),
+            (TerminateStage::Terminate, timeout),
+            (TerminateStage::Kill, timeout),
+        ]
+    }
+}
+
+/// Resource usage of a process and all its descendants.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessTreeUsage {
+    /// User and system CPU time, including children which have already finished.
+    pub cpu_time: Duration,
+    /// Resident memory size in bytes. Peak memory usage on Windows.
+    pub rss_bytes: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+impl std::ops::Add for ProcessTreeUsage {
+    type Output = ProcessTreeUsage;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        ProcessTreeUsage {
+            cpu_time: self.cpu_time + rhs.cpu_time,
+            rss_bytes: self.rss_bytes + rhs.rss_bytes,
+            read_bytes: self.read_bytes + rhs.read_bytes,
+            write_bytes: self.write_bytes + rhs.write_bytes,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ProcessHandle {
     process: Arc<SharedChild>,
@@ -104,6 +156,9 @@ pub struct ProcessHandle {
     #[allow(unused)]
     #[cfg(windows)]
     job_object: JobObject,
+    // Nested job object of the spawned process, used for resource accounting
+    #[cfg(windows)]
+    tree_job: Option<JobObject>,
 }
 
 impl ProcessHandle {
@@ -112,10 +167,16 @@ impl ProcessHandle {
         #[cfg(windows)]
         let job_object = JobObject::try_new_current()?;
         let process = Arc::new(SharedChild::spawn(command)?);
+        #[cfg(windows)]
+        let tree_job = JobObject::try_new(Some(process.id()))
+            .map_err(|e| log::warn!("Unable to track usage of process {}: {e}", process.id()))
+            .ok();
         Ok(ProcessHandle {
             process,
             #[cfg(windows)]
             job_object,
+            #[cfg(windows)]
+            tree_job,
         })
     }
 
@@ -127,45 +188,135 @@ impl ProcessHandle {
         self.process.id()
     }
 
+    /// Samples resource usage of the process and all its descendants.
     #[cfg(unix)]
+    pub fn usage(&self) -> Result<ProcessTreeUsage, SystemError> {
+        tree_usage(self.process.id() as i32)
+    }
+
+    /// Samples resource usage of the process and all its descendants.
+    #[cfg(windows)]
+    pub fn usage(&self) -> Result<ProcessTreeUsage, SystemError> {
+        match &self.tree_job {
+            Some(job) => job.usage(),
+            None => Err(SystemError::Unsupported(format!(
+                "usage of process {}",
+                self.process.id()
+            ))),
+        }
+    }
+
+    /// Sends SIGTERM (CTRL-BREAK on Windows) to the process and all its descendants
+    /// and waits up to `timeout` for them to finish.
     pub async fn terminate(&self, timeout: Duration) -> Result<()> {
-        let process = self.process.clone();
-        if process.send_signal(libc::SIGTERM).is_err() {
-            // Error means, that probably process was already terminated, because:
-            // - We have permissions to send signal, since we created this process.
-            // - We specified correct signal SIGTERM.
-            // But better let's check.
-            return self.check_if_running();
+        self.terminate_cascade(&[(TerminateStage::Terminate, timeout)])
+            .await
+            .map(|_| ())
+    }
+
+    /// Terminates the process and all its descendants, escalating through `stages`.
+    /// Each stage is followed by waiting up to its timeout for the processes to finish.
+    ///
+    /// Returns the stage which succeeded or `None` if the process had already finished.
+    /// Fails if processes are still running after the last stage.
+    pub async fn terminate_cascade(
+        &self,
+        stages: &[(TerminateStage, Duration)],
+    ) -> Result<Option<TerminateStage>> {
+        // Snapshot of descendants, which are re-parented once their parent finishes
+        #[cfg(unix)]
+        let mut tree = tree_pids(self.pid() as i32);
+        #[cfg(windows)]
+        let tree = HashSet::new();
+
+        if !self.is_tree_running(&tree) {
+            return Ok(None);
         }
 
-        let process = self.clone();
-        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        for (stage, timeout) in stages {
+            #[cfg(unix)]
+            {
+                // Descendants spawned in the meantime
+                if self.check_if_running().is_err() {
+                    tree.extend(tree_pids(self.pid() as i32));
+                }
+                self.signal_tree(&tree, *stage);
+            }
+            #[cfg(windows)]
+            self.signal_tree(*stage)?;
+
+            log::debug!(
+                "Sent {stage} to process [pid={}] and its descendants",
+                self.pid()
+            );
+
+            let deadline = std::time::Instant::now() + *timeout;
+            while std::time::Instant::now() < deadline {
+                if !self.is_tree_running(&tree) {
+                    return Ok(Some(*stage));
+                }
+                tokio::time::sleep(TERMINATE_POLL_INTERVAL).await;
+            }
+            if !self.is_tree_running(&tree) {
+                return Ok(Some(*stage));
+            }
+        }
 
-        tokio::task::spawn_local(async move {
-            tokio::time::sleep(timeout).await;
-            abort_handle.abort();
-        });
+        Err(anyhow!(
+            "Process [pid={}] or its descendants are still running.",
+            self.pid()
+        ))
+    }
 
-        let _ = Abortable::new(process.wait_until_finished(), abort_registration).await;
-        self.check_if_running()
+    #[cfg(unix)]
+    fn signal_tree(&self, tree: &HashSet<i32>, stage: TerminateStage) {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+
+        let signal = match stage {
+            TerminateStage::Interrupt => Signal::SIGINT,
+            TerminateStage::Terminate => Signal::SIGTERM,
+            TerminateStage::Kill => Signal::SIGKILL,
+        };
+        for pid in tree {
+            // Error means that the process has already finished
+            let _ = kill(Pid::from_raw(*pid), signal);
+        }
     }
 
     #[cfg(windows)]
-    pub async fn terminate(&self, timeout: Duration) -> Result<()> {
-        unsafe { ctrl_break_process(self.process.clone()) }?;
-        let process = self.process.clone();
-
-        let (abort_handle, abort_registration) = AbortHandle::new_pair();
-        let _process_pid = process.id();
+    fn signal_tree(&self, stage: TerminateStage) -> Result<()> {
+        match (stage, &self.tree_job) {
+            (TerminateStage::Kill, Some(job)) => Ok(job.terminate()?),
+            (TerminateStage::Kill, None) => Ok(self.process.kill()?),
+            _ => unsafe { ctrl_break_process(self.process.clone()) },
+        }
+    }
 
-        tokio::task::spawn_local(async move {
-            tokio::time::sleep(timeout).await;
-            abort_handle.abort();
-        });
+    #[cfg(unix)]
+    fn is_tree_running(&self, tree: &HashSet<i32>) -> bool {
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        let pid = self.pid() as i32;
+        // Waiting reaps the process, otherwise it would be signalled as a zombie
+        self.check_if_running().is_err()
+            || tree
+                .iter()
+                .filter(|p| **p != pid)
+                .any(|p| kill(Pid::from_raw(*p), None).is_ok())
+    }
 
-        let process = self.clone();
-        let _ = Abortable::new(process.wait_until_finished(), abort_registration).await;
-        self.check_if_running()
+    #[cfg(windows)]
+    fn is_tree_running(&self, _tree: &HashSet<i32>) -> bool {
+        let descendants_running = match &self.tree_job {
+            Some(job) => job
+                .accounting()
+                .map(|info| info.ActiveProcesses > 0)
+                .unwrap_or(false),
+            None => false,
+        };
+        self.check_if_running().is_err() || descendants_running
     }
 
     pub fn check_if_running(&self) -> Result<()> {
@@ -225,3 +376,55 @@ unsafe fn ctrl_break_process(process: Arc<SharedChild>) -> Result<()> {
     };
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+
+    fn spawn_tree(script: &str) -> (ProcessHandle, HashSet<i32>) {
+        let handle = ProcessHandle::new(Command::new("sh").args(["-c", script])).unwrap();
+        let pid = handle.pid() as i32;
+        for _ in 0..50 {
+            let tree = tree_pids(pid);
+            if tree.len() >= 3 {
+                return (handle, tree);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        panic!("children of process {pid} were not spawned");
+    }
+
+    fn any_running(tree: &HashSet<i32>) -> bool {
+        tree.iter()
+            .any(|pid| kill(Pid::from_raw(*pid), None).is_ok())
+    }
+
+    #[tokio::test]
+    async fn terminate_kills_descendants() {
+        let (handle, tree) = spawn_tree("sleep 60 & sleep 60 & wait");
+
+        handle.terminate(Duration::from_secs(5)).await.unwrap();
+        assert!(!any_running(&tree));
+    }
+
+    #[tokio::test]
+    async fn terminate_cascade_escalates() {
+        // Ignored signals are inherited by children
+        let (handle, tree) = spawn_tree("trap '' INT TERM; sleep 60 & sleep 60 & wait");
+
+        let stage = handle
+            .terminate_cascade(&TerminateStage::cascade(Duration::from_millis(300)))
+            .await
+            .unwrap();
+        assert_eq!(stage, Some(TerminateStage::Kill));
+        assert!(!any_running(&tree));
+
+        let stage = handle
+            .terminate_cascade(&TerminateStage::cascade(Duration::from_millis(300)))
+            .await
+            .unwrap();
+        assert_eq!(stage, None);
+    }
+}
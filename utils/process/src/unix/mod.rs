@@ -12,6 +12,8 @@ use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::unistd::Pid;
 use thiserror::Error;
 
+use crate::ProcessTreeUsage;
+
 #[cfg(target_os = "macos")]
 use libproc::libproc::bsd_info::BSDInfo;
 #[cfg(target_os = "macos")]
@@ -20,7 +22,7 @@ use libproc::libproc::proc_pid::{listpids, pidinfo, ProcType};
 #[cfg(target_os = "linux")]
 use nix::unistd::sysconf;
 #[cfg(target_os = "linux")]
-use nix::unistd::SysconfVar::CLK_TCK;
+use nix::unistd::SysconfVar::{CLK_TCK, PAGE_SIZE};
 
 #[derive(Clone, Debug, Error)]
 pub enum SystemError {
@@ -53,7 +55,7 @@ pub struct Process {
 
 #[cfg(target_os = "linux")]
 impl Process {
-    pub fn group(group: i32) -> impl Iterator<Item = Process> {
+    pub fn all() -> impl Iterator<Item = Process> {
         use std::str::FromStr;
 
         std::fs::read_dir("/proc/")
@@ -61,7 +63,10 @@ impl Process {
             .filter_map(|res| res.ok())
             .filter_map(|entry| i32::from_str(&entry.file_name().to_string_lossy()).ok())
             .filter_map(|pid| Process::info(pid).ok())
-            .filter(move |proc| proc.pgid == group)
+    }
+
+    pub fn group(group: i32) -> impl Iterator<Item = Process> {
+        Self::all().filter(move |proc| proc.pgid == group)
     }
 
     pub fn info(pid: i32) -> Result<Process, SystemError> {
@@ -83,6 +88,27 @@ impl Process {
         Ok(Usage { cpu_sec, rss_gib })
     }
 
+    /// Usage of a single process. CPU time includes children which have been waited for.
+    pub fn tree_member_usage(pid: i32) -> Result<ProcessTreeUsage, SystemError> {
+        let stat = StatStub::read(pid)?;
+        let tps = Self::ticks_per_second()? as f64;
+        let page_size = match sysconf(PAGE_SIZE)? {
+            Some(size) => size as u64,
+            None => return Err(nix::errno::Errno::ENOTSUP.into()),
+        };
+
+        let ticks = stat.utime + stat.stime + stat.cutime + stat.cstime;
+        // IO statistics may be unavailable, e.g. due to ptrace access restrictions
+        let io = IoStub::read(pid).unwrap_or_default();
+
+        Ok(ProcessTreeUsage {
+            cpu_time: Duration::from_secs_f64(ticks as f64 / tps),
+            rss_bytes: stat.rss.max(0) as u64 * page_size,
+            read_bytes: io.read_bytes,
+            write_bytes: io.write_bytes,
+        })
+    }
+
     fn ticks_per_second() -> Result<i64, SystemError> {
         match sysconf(CLK_TCK) {
             Ok(Some(tps)) => Ok(tps),
@@ -94,12 +120,15 @@ impl Process {
 
 #[cfg(target_os = "macos")]
 impl Process {
-    pub fn group(pgid: i32) -> impl Iterator<Item = Process> {
+    pub fn all() -> impl Iterator<Item = Process> {
         listpids(ProcType::ProcAllPIDS)
             .unwrap_or_else(|_| Vec::new())
             .into_iter()
             .filter_map(|p| Process::info(p as i32).ok())
-            .filter(move |p| p.pgid == pgid)
+    }
+
+    pub fn group(pgid: i32) -> impl Iterator<Item = Process> {
+        Self::all().filter(move |p| p.pgid == pgid)
     }
 
     pub fn info(pid: i32) -> Result<Process, SystemError> {
@@ -122,6 +151,59 @@ impl Process {
 
         Ok(Usage { cpu_sec, rss_gib })
     }
+
+    /// Usage of a single process. CPU time includes children which have been waited for.
+    pub fn tree_member_usage(pid: i32) -> Result<ProcessTreeUsage, SystemError> {
+        use libproc::libproc::pid_rusage::{pidrusage, RUsageInfoV2};
+
+        let usage = pidrusage::<RUsageInfoV2>(pid).map_err(SystemError::Error)?;
+        let cpu_nanos = usage.ri_user_time
+            + usage.ri_system_time
+            + usage.ri_child_user_time
+            + usage.ri_child_system_time;
+
+        Ok(ProcessTreeUsage {
+            cpu_time: Duration::from_nanos(cpu_nanos),
+            rss_bytes: usage.ri_resident_size,
+            read_bytes: usage.ri_diskio_bytesread,
+            write_bytes: usage.ri_diskio_byteswritten,
+        })
+    }
+}
+
+/// Lists `pid` and all its descendants. Members of the process group led by `pid`
+/// are included as well, since daemonized workers are re-parented to init.
+pub fn tree_pids(pid: i32) -> HashSet<i32> {
+    let processes = Process::all().collect::<Vec<_>>();
+    let mut tree = processes
+        .iter()
+        .filter(|p| p.pgid == pid)
+        .map(|p| p.pid)
+        .collect::<HashSet<_>>();
+    tree.insert(pid);
+
+    loop {
+        let size = tree.len();
+        for p in processes.iter() {
+            if tree.contains(&p.ppid) {
+                tree.insert(p.pid);
+            }
+        }
+        if tree.len() == size {
+            return tree;
+        }
+    }
+}
+
+/// Aggregates resource usage of `pid` and all its descendants.
+pub fn tree_usage(pid: i32) -> Result<ProcessTreeUsage, SystemError> {
+    let root = Process::tree_member_usage(pid)?;
+    Ok(tree_pids(pid)
+        .into_iter()
+        .filter(|p| *p != pid)
+        // processes may exit while being sampled
+        .filter_map(|p| Process::tree_member_usage(p).ok())
+        .fold(root, |total, usage| total + usage))
 }
 
 #[derive(Clone, Debug)]
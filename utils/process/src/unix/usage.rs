@@ -50,6 +50,8 @@ pub(super) struct StatStub {
     pub sid: i32,
     pub utime: u64,
     pub stime: u64,
+    pub cutime: u64,
+    pub cstime: u64,
     pub vsize: u64,
     pub rss: i64,
 }
@@ -92,9 +94,11 @@ impl StatStub {
         let mut it = it.skip(7);
         stub.utime = next(&mut it)?;
         stub.stime = next(&mut it)?;
+        stub.cutime = next(&mut it)?;
+        stub.cstime = next(&mut it)?;
 
-        // cutime, cstime, priority, nice, num_threads, itrealvalue, starttime
-        let mut it = it.skip(7);
+        // priority, nice, num_threads, itrealvalue, starttime
+        let mut it = it.skip(5);
         stub.vsize = next(&mut it)?;
         stub.rss = next(&mut it)?;
 
@@ -102,6 +106,34 @@ impl StatStub {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub(super) struct IoStub {
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+#[cfg(target_os = "linux")]
+impl IoStub {
+    pub fn read(pid: i32) -> Result<IoStub, SystemError> {
+        let io = std::fs::read_to_string(format!("/proc/{}/io", pid))?;
+        Ok(Self::parse_io(&io))
+    }
+
+    fn parse_io(io: &str) -> IoStub {
+        let mut stub = IoStub::default();
+        for (key, value) in io.lines().filter_map(|line| line.split_once(':')) {
+            let value = value.trim().parse().unwrap_or_default();
+            match key {
+                "read_bytes" => stub.read_bytes = value,
+                "write_bytes" => stub.write_bytes = value,
+                _ => (),
+            }
+        }
+        stub
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -123,10 +155,27 @@ mod test {
             sid: 7832,
             utime: 44,
             stime: 2,
+            cutime: 0,
+            cstime: 0,
             vsize: 816193536,
             rss: 1793,
         };
 
         assert_eq!(parsed, expected);
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_io() {
+        let io = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\n\
+        read_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0";
+
+        let parsed = super::IoStub::parse_io(io);
+        let expected = super::IoStub {
+            read_bytes: 4096,
+            write_bytes: 323932160,
+        };
+
+        assert_eq!(parsed, expected);
+    }
 }
@@ -6,7 +6,7 @@ use winapi::shared::ntdef::{HANDLE, NULL};
 use winapi::um;
 use winapi::um::handleapi::INVALID_HANDLE_VALUE;
 
-use crate::SystemError;
+use crate::{ProcessTreeUsage, SystemError};
 
 #[derive(Clone, Debug)]
 pub struct JobObject {
@@ -61,6 +61,45 @@ impl JobObject {
         Ok(info)
     }
 
+    pub fn io_accounting(
+        &self,
+    ) -> Result<um::winnt::JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION, SystemError> {
+        let mut info: um::winnt::JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION =
+            unsafe { mem::zeroed() };
+
+        if unsafe {
+            um::jobapi2::QueryInformationJobObject(
+                self.handle,
+                um::winnt::JobObjectBasicAndIoAccountingInformation,
+                &mut info as *mut _ as LPVOID,
+                mem::size_of::<um::winnt::JOBOBJECT_BASIC_AND_IO_ACCOUNTING_INFORMATION>() as DWORD,
+                NULL as *mut _ as LPDWORD,
+            )
+        } == 0
+        {
+            return Err(SystemError::last());
+        }
+
+        Ok(info)
+    }
+
+    /// Aggregated usage of all processes assigned to the job.
+    pub fn usage(&self) -> Result<ProcessTreeUsage, SystemError> {
+        let info = self.io_accounting()?;
+        let limits = self.limits()?;
+        // Times are expressed in 100-nanosecond ticks
+        let ticks = unsafe {
+            *info.BasicInfo.TotalUserTime.QuadPart() + *info.BasicInfo.TotalKernelTime.QuadPart()
+        };
+
+        Ok(ProcessTreeUsage {
+            cpu_time: std::time::Duration::from_nanos(ticks as u64 * 100),
+            rss_bytes: limits.PeakJobMemoryUsed as u64,
+            read_bytes: info.IoInfo.ReadTransferCount,
+            write_bytes: info.IoInfo.WriteTransferCount,
+        })
+    }
+
     pub fn limits(&self) -> Result<um::winnt::JOBOBJECT_EXTENDED_LIMIT_INFORMATION, SystemError> {
         let mut info: um::winnt::JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { mem::zeroed() };
 
@@ -25,6 +25,8 @@ pub enum SystemError {
     PoisonError,
     #[error("API error: {0}")]
     ApiError(u32),
+    #[error("Unsupported: {0}")]
+    Unsupported(String),
 }
 
 impl<T> From<std::sync::PoisonError<T>> for SystemError {